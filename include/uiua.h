@@ -0,0 +1,49 @@
+/* C declarations for the Uiua interpreter
+ *
+ * See the `capi` module of the `uiua` crate for ownership rules.
+ */
+
+#ifndef UIUA_H
+#define UIUA_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define UIUA_BYTE 0    /* uint8_t elements */
+#define UIUA_NUM 1     /* double elements */
+#define UIUA_CHAR 2    /* uint32_t Unicode scalar values */
+#define UIUA_COMPLEX 3 /* pairs of doubles */
+#define UIUA_BOX 4     /* no flat data, see uiua_value_unbox */
+#define UIUA_TIME 5    /* no flat data */
+
+typedef struct UiuaValue UiuaValue;
+typedef struct Uiua Uiua;
+
+typedef struct UiuaArray {
+    uint32_t ty;
+    size_t rank;
+    const size_t *shape;
+    const void *data;
+} UiuaArray;
+
+UiuaValue *uiua_value_from_array(UiuaArray array);
+UiuaArray uiua_value_array(const UiuaValue *value);
+const UiuaValue *uiua_value_unbox(const UiuaValue *value, size_t index);
+void uiua_value_free(UiuaValue *value);
+
+Uiua *uiua_new(void);
+void uiua_free(Uiua *env);
+void uiua_push(Uiua *env, UiuaValue *value);
+char *uiua_pop(Uiua *env, UiuaValue **value);
+char *uiua_run(Uiua *env, const char *code);
+void uiua_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -10,7 +10,9 @@ use std::{
 use crate::{editor::get_ast_time, weewuh};
 use leptos::*;
 use leptos_query::{create_query, QueryOptions};
-use uiua::{Handle, Report, SysBackend, EXAMPLE_TXT, EXAMPLE_UA};
+use uiua::{
+    FileKind, FileMetadata, Handle, Report, SysBackend, Timestamp, EXAMPLE_TXT, EXAMPLE_UA,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, RequestMode, Response};
@@ -209,6 +211,15 @@ impl SysBackend for WebBackend {
     fn is_file(&self, path: &str) -> Result<bool, String> {
         Ok(self.file(path.as_ref(), |_| {}).is_ok())
     }
+    fn file_metadata(&self, path: &str) -> Result<FileMetadata, String> {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        let size = self.file(path.as_ref(), |contents| contents.len())?;
+        Ok(FileMetadata {
+            size: size as u64,
+            modified: Timestamp::EPOCH,
+            kind: FileKind::File,
+        })
+    }
     fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
         self.file_mut(path, true, |file| *file = contents.to_vec())
     }
@@ -296,6 +307,19 @@ impl SysBackend for WebBackend {
         stream.pos = (end + delim.len()).min(stream.contents.len());
         Ok(data)
     }
+    fn read_line(&self, handle: Handle) -> Result<Option<String>, String> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.get_mut(&handle).ok_or("Invalid stream handle")?;
+        let rest = &stream.contents[stream.pos..];
+        if rest.is_empty() {
+            return Ok(None);
+        }
+        let len = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        let line = rest[..len].strip_suffix(b"\r").unwrap_or(&rest[..len]);
+        let line = String::from_utf8(line.to_vec()).map_err(|e| e.to_string())?;
+        stream.pos += (len + 1).min(rest.len());
+        Ok(Some(line))
+    }
     fn delete(&self, path: &str) -> Result<(), String> {
         self.files.lock().unwrap().remove(Path::new(path));
         Ok(())
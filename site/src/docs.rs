@@ -291,7 +291,7 @@ fn aliases() -> HashMap<&'static str, &'static [Primitive]> {
         ("filter", &[Primitive::Keep] as &[_]),
         ("search", &[Primitive::Find]),
         ("intersect", &[Primitive::Member]),
-        ("split", &[Primitive::Partition]),
+        ("split", &[Primitive::Split, Primitive::Partition]),
         ("while", &[Primitive::Do]),
         ("for", &[Primitive::Repeat]),
         ("invert", &[Primitive::Un]),
@@ -406,6 +406,7 @@ impl Allowed {
                 ("gifs", &[PrimClass::Sys(SysOpClass::Gifs)]),
                 ("audio", &[PrimClass::Sys(SysOpClass::Audio)]),
                 ("tcp", &[PrimClass::Sys(SysOpClass::Tcp)]),
+                ("channel", &[PrimClass::Sys(SysOpClass::Channel)]),
                 ("env", &[PrimClass::Sys(SysOpClass::Env)]),
                 ("command", &[PrimClass::Sys(SysOpClass::Command)]),
                 ("filesystem", &[PrimClass::Sys(SysOpClass::Filesystem)]),
@@ -545,6 +546,7 @@ impl Allowed {
                         SysOpClass::Images => ("System - Images".into_view(), "Work with static images"),
                         SysOpClass::Gifs => ("System - GIFs".into_view(), "Work with animated GIFs"),
                         SysOpClass::Tcp => ("System - TCP".into_view(), "Work with TCP sockets"),
+                        SysOpClass::Channel => ("System - Channels".into_view(), "Pass values between threads"),
                         SysOpClass::Ffi => ("System - FFI".into_view(), "Foreign function interface"),
                         SysOpClass::Misc => ("System - Misc".into_view(), ""),
                     }
@@ -173,7 +173,7 @@ pub fn Site() -> impl IntoView {
                     <Route path="tutorial/:page?" view=Tutorial/>
                     <Route path="docs/:page?" view=Docs/>
                     <Route path="isms/:search?" view=Uiuisms/>
-                    <Route path="pad" view=Pad/>
+                    <Route path="pad" view=crate::Pad/>
                     <Route path="install" view=Install/>
                     <Route path="tour" view=Tour/>
                     <Route path="isms" view=Uiuisms/>
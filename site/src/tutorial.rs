@@ -1717,6 +1717,12 @@ fn TutorialModules() -> impl IntoView {
         <p>"Try formatting the following example to see how this works."</p>
         <Editor example="A = +1\nB ← +2\nC =~ +3\nD ←~ +4"/>
 
+        <Hd id="embedded-modules">"Embedded Modules"</Hd>
+        <p>"Some modules are built into the interpreter. They are imported with a name prefixed with "<code>"std:"</code>" instead of a path."</p>
+        <Editor example="~ \"std:stats\" ~ Mean StdDev\nMean [1 2 3 4]\nStdDev [2 4 4 4 5 5 7 9]"/>
+        <p>"The built-in modules are "<code>"stats"</code>", for statistics, and "<code>"string"</code>", for working with strings."</p>
+        <p>"Programs that embed Uiua can add their own modules with "<code>"Compiler::embed_module"</code>" in the Rust API."</p>
+
         <Hd id="git-modules">"Git Modules"</Hd>
         <p>"Modules can be imported from Git repositories. Instead of a path, use a URL prefixed with "<code>"git:"</code>"."</p>
         <p>"The Uiua GitHub organization hosts an example module at "<a href="https://github.com/uiua-lang/example-module">"https://github.com/uiua-lang/example-module"</a>". The protocol specification can be omitted."</p>
@@ -1724,6 +1730,8 @@ fn TutorialModules() -> impl IntoView {
         <p>"On the site, code is pulled from a "<code>"lib.ua"</code>" file at the root of the repository. Loading other files is not supported."</p>
         <p>"To use Git modules in the "<A href="/docs/install">"native interpreter"</A>", you must have Git installed. The repository is added as a Git submodule and the "<code>"lib.ua"</code>" file is loaded as the module's contents."</p>
         <p>"The native interpreter also supports adding an additional "<code>"branch: <branch-name>"</code>" specifier after the URL."</p>
+        <p>"The native interpreter can also import a single file from a URL starting with "<code>"https://"</code>". Downloaded files are kept in the "<code>"uiua-modules"</code>" folder. Plain "<code>"http://"</code>" URLs are only allowed when running with "<code>"uiua run --allow-http-imports"</code>"."</p>
+        <p>"The first time a Git or URL module is imported, a hash of its contents, and of every file it imports, is saved in a "<code>"uiua.lock"</code>" file in the current directory. If the module's contents ever change, importing it fails. To accept the new contents, remove the module from "<code>"uiua.lock"</code>"."</p>
         <p>"You can find a curated list of Uiua modules "<a href="https://github.com/uiua-lang/uiua-modules">"here"</a>"."</p>
     }
 }
@@ -0,0 +1,72 @@
+//! Pro-rata allocation of integer amounts
+
+use ecow::EcoVec;
+
+use crate::{Array, Shape, Uiua, UiuaResult, Value};
+
+use super::{decimal::Decimal, num_array};
+
+/// The largest integer amount that can be allocated
+///
+/// Beyond this, not every integer is exactly representable.
+const MAX_AMOUNT: f64 = 9007199254740992.0;
+
+impl Value {
+    /// Split integer amounts into parts proportional to some weights
+    pub(crate) fn allocate(&self, totals: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let weights = self.as_nums(env, "Weights must be a list of numbers")?;
+        if let Some(w) = (weights.iter()).find(|w| !w.is_finite() || **w < 0.0) {
+            return Err(env.error(format!(
+                "Weights must be non-negative and finite, but one is {w}"
+            )));
+        }
+        let totals = num_array(totals, "Amounts", env)?;
+        if let Some(t) = (totals.data.iter()).find(|t| t.fract() != 0.0 || t.abs() > MAX_AMOUNT) {
+            return Err(env.error(format!(
+                "Amounts must be integers no larger than 2^53 in magnitude, but one is {t}"
+            )));
+        }
+        // Weights are split exactly as the decimals they are written as
+        let too_precise = || env.error("Weights have too many decimal places to allocate exactly");
+        let decimals: Vec<Decimal> = (weights.iter())
+            .map(|&w| Decimal::from_f64(w))
+            .collect::<Option<_>>()
+            .ok_or_else(too_precise)?;
+        let (weights, _) = Decimal::common(&decimals).ok_or_else(too_precise)?;
+        let weight_sum = (weights.iter().copied())
+            .try_fold(0i128, i128::checked_add)
+            .ok_or_else(too_precise)?;
+        if weight_sum == 0 {
+            return Err(env.error("Cannot allocate with weights that sum to 0"));
+        }
+        let n = weights.len();
+        let mut data = EcoVec::with_capacity(totals.data.len() * n);
+        let mut parts = Vec::with_capacity(n);
+        let mut remainders = Vec::with_capacity(n);
+        let mut order: Vec<usize> = Vec::with_capacity(n);
+        for &total in &totals.data {
+            let total = total as i128;
+            parts.clear();
+            remainders.clear();
+            for &w in &weights {
+                let scaled = total.checked_mul(w).ok_or_else(too_precise)?;
+                parts.push(scaled.div_euclid(weight_sum));
+                remainders.push(scaled.rem_euclid(weight_sum));
+            }
+            // Distribute what is left over to the parts with the largest
+            // remainders, breaking ties in favor of earlier parts
+            let left = (total - parts.iter().sum::<i128>()) as usize;
+            debug_assert!(left < n);
+            order.clear();
+            order.extend(0..n);
+            order.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]).then(a.cmp(&b)));
+            for &i in &order[..left] {
+                parts[i] += 1;
+            }
+            data.extend(parts.iter().map(|&p| p as f64));
+        }
+        let mut shape: Shape = totals.shape.clone();
+        shape.push(n);
+        Ok(Array::new(shape, data).into())
+    }
+}
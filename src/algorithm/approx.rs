@@ -0,0 +1,148 @@
+//! Approximate comparison of numeric arrays
+
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+
+use crate::{Complex, Uiua, UiuaResult, Value};
+
+/// Above this many elements, deviations are computed in parallel
+const PAR_THRESHOLD: usize = 10_000;
+
+/// The absolute deviation between two numbers and the larger of their magnitudes
+fn real_deviation(a: f64, b: f64) -> (f64, f64) {
+    let dev = if a == b || a.is_nan() && b.is_nan() {
+        0.0
+    } else {
+        let dev = (a - b).abs();
+        if dev.is_nan() {
+            f64::INFINITY
+        } else {
+            dev
+        }
+    };
+    (dev, a.abs().max(b.abs()))
+}
+
+fn complex_deviation(a: Complex, b: Complex) -> (f64, f64) {
+    let (re, _) = real_deviation(a.re, b.re);
+    let (im, _) = real_deviation(a.im, b.im);
+    (re.hypot(im), a.abs().max(b.abs()))
+}
+
+fn reals(val: &Value) -> Option<Cow<'_, [f64]>> {
+    match val {
+        Value::Num(arr) => Some(Cow::Borrowed(&arr.data)),
+        Value::Byte(arr) => Some(Cow::Owned(arr.data.iter().map(|&b| b as f64).collect())),
+        _ => None,
+    }
+}
+
+fn complexes(val: &Value) -> Option<Cow<'_, [Complex]>> {
+    match val {
+        Value::Complex(arr) => Some(Cow::Borrowed(&arr.data)),
+        val => reals(val).map(|nums| Cow::Owned(nums.iter().map(|&n| n.into()).collect())),
+    }
+}
+
+fn zip_map<T: Copy + Sync>(a: &[T], b: &[T], f: fn(T, T) -> (f64, f64)) -> Vec<(f64, f64)> {
+    if a.len() > PAR_THRESHOLD {
+        (a.par_iter().zip(b)).map(|(&a, &b)| f(a, b)).collect()
+    } else {
+        (a.iter().zip(b)).map(|(&a, &b)| f(a, b)).collect()
+    }
+}
+
+/// Get the deviation and magnitude of each pair of elements
+///
+/// Returns `None` if the shapes do not match.
+fn deviations(a: &Value, b: &Value, prim: &str, env: &Uiua) -> UiuaResult<Option<Vec<(f64, f64)>>> {
+    let numeric = |val: &Value| matches!(val, Value::Num(_) | Value::Byte(_) | Value::Complex(_));
+    if !numeric(a) || !numeric(b) {
+        return Err(env.error(format!(
+            "Cannot {prim} {} and {}",
+            a.type_name_plural(),
+            b.type_name_plural()
+        )));
+    }
+    if a.shape() != b.shape() {
+        return Ok(None);
+    }
+    Ok(Some(match (reals(a), reals(b)) {
+        (Some(a), Some(b)) => zip_map(&a, &b, real_deviation),
+        _ => zip_map(
+            &complexes(a).unwrap(),
+            &complexes(b).unwrap(),
+            complex_deviation,
+        ),
+    }))
+}
+
+impl Value {
+    /// Check if two numeric arrays are approximately equal
+    ///
+    /// Corresponding elements `a` and `b` are close if
+    /// `|a - b| <= abs + rel * max(|a|, |b|)`.
+    /// Arrays with different shapes are never approximately equal.
+    pub fn approx_eq(&self, other: &Self, abs: f64, rel: f64, env: &Uiua) -> UiuaResult<bool> {
+        let Some(devs) = deviations(self, other, "approximately compare", env)? else {
+            return Ok(false);
+        };
+        let close = |&(dev, mag): &(f64, f64)| dev == 0.0 || dev <= abs + rel * mag;
+        Ok(if devs.len() > PAR_THRESHOLD {
+            devs.par_iter().all(close)
+        } else {
+            devs.iter().all(close)
+        })
+    }
+    /// Get the largest absolute deviation between two numeric arrays and its coordinate
+    ///
+    /// The coordinate is empty if the arrays are empty or scalars.
+    pub fn max_deviation(&self, other: &Self, env: &Uiua) -> UiuaResult<(f64, Vec<usize>)> {
+        let Some(devs) = deviations(self, other, "get the deviation of", env)? else {
+            return Err(env.error(format!(
+                "Cannot get the deviation of arrays with shapes {} and {}",
+                self.shape(),
+                other.shape()
+            )));
+        };
+        let max = |a: (usize, f64), b: (usize, f64)| if b.1 > a.1 { b } else { a };
+        let (index, dev) = if devs.len() > PAR_THRESHOLD {
+            (devs.par_iter().map(|&(dev, _)| dev).enumerate()).reduce(|| (0, 0.0), max)
+        } else {
+            (devs.iter().map(|&(dev, _)| dev).enumerate()).fold((0, 0.0), max)
+        };
+        let mut coord = Vec::new();
+        if !devs.is_empty() && self.rank() > 0 {
+            self.shape().flat_to_dims(index, &mut coord);
+        }
+        Ok((dev, coord))
+    }
+    /// `approx` two arrays with a tolerance
+    pub(crate) fn approx(&self, a: &Self, b: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let tolerance = self.as_nums(
+            env,
+            "Tolerance must be a number or a list of \
+            an absolute and a relative tolerance",
+        )?;
+        let (abs, rel) = match *tolerance.as_slice() {
+            [abs] => (abs, 0.0),
+            [abs, rel] => (abs, rel),
+            _ => {
+                return Err(env.error(format!(
+                    "Tolerance must have 1 or 2 numbers, but it has {}",
+                    tolerance.len()
+                )))
+            }
+        };
+        if abs < 0.0 || rel < 0.0 || abs.is_nan() || rel.is_nan() {
+            return Err(env.error("Tolerances cannot be negative"));
+        }
+        Ok(a.approx_eq(b, abs, rel, env)?.into())
+    }
+    /// Get the `deviation` of two arrays
+    pub(crate) fn deviation(&self, other: &Self, env: &Uiua) -> UiuaResult<(Self, Self)> {
+        let (dev, coord) = self.max_deviation(other, env)?;
+        Ok((dev.into(), coord.into_iter().map(|i| i as f64).collect()))
+    }
+}
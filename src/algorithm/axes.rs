@@ -0,0 +1,438 @@
+//! Operations on specific axes, and named axes, which are names for the dimensions of an array
+//! stored in its metadata
+
+use std::iter::repeat;
+
+use ecow::{EcoString, EcoVec};
+
+use crate::{algorithm::FillContext, Array, ArrayValue, Boxed, Shape, Uiua, UiuaResult, Value};
+
+fn axis_names(names: &Value, env: &Uiua) -> UiuaResult<EcoVec<EcoString>> {
+    match names {
+        Value::Char(arr) if arr.rank() <= 1 => Ok([arr.data.iter().copied().collect()].into()),
+        Value::Box(arr) if arr.rank() == 1 => (arr.data.iter())
+            .map(|Boxed(name)| {
+                (name.as_string(env, "Axis names must be strings")).map(EcoString::from)
+            })
+            .collect(),
+        val => Err(env.error(format!(
+            "Axis names must be a string or list of strings, \
+            but they are a {} array of shape {}",
+            val.type_name(),
+            val.shape()
+        ))),
+    }
+}
+
+impl Value {
+    /// Name the axes of a value
+    pub fn name_axes(&self, mut target: Value, env: &Uiua) -> UiuaResult<Value> {
+        let names = axis_names(self, env)?;
+        if names.len() != target.rank() {
+            return Err(env.error(format!(
+                "Cannot name the axes of a rank {} array with {} name{}",
+                target.rank(),
+                names.len(),
+                if names.len() == 1 { "" } else { "s" }
+            )));
+        }
+        for (i, name) in names.iter().enumerate() {
+            if !name.is_empty() && names[..i].contains(name) {
+                return Err(env.error(format!("Axis name {name:?} is used more than once")));
+            }
+        }
+        target.meta_mut().axes = Some(names);
+        Ok(target)
+    }
+    /// Take the axis names from a value
+    ///
+    /// Unnamed axes get empty names.
+    pub fn unname_axes(mut self) -> (Value, Value) {
+        let rank = self.rank();
+        let names = (self.take_axes()).unwrap_or_else(|| vec![EcoString::new(); rank].into());
+        let names: Value = (names.into_iter())
+            .map(|name| Boxed(name.as_str().into()))
+            .collect::<EcoVec<_>>()
+            .into();
+        (names, self)
+    }
+    /// Pick from a value along the axis with the given name
+    pub fn pick_axis(&self, index: &Value, from: &Value, env: &Uiua) -> UiuaResult<Value> {
+        let name = self.as_string(env, "Axis name must be a string")?;
+        let axis = (from.axes())
+            .and_then(|axes| axes.iter().position(|axis| *axis == name))
+            .ok_or_else(|| {
+                env.error(format!(
+                    "Array of shape {} has no axis named {name:?}",
+                    from.shape_string()
+                ))
+            })?;
+        let indices = if index.rank() == 0 {
+            Ok(index.as_int(env, "Axis index must be an integer")?)
+        } else {
+            Err(index.as_ints(env, "Axis indices must be a list of integers")?)
+        };
+        Ok(match from {
+            Value::Num(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+            Value::Byte(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+            Value::Complex(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+            Value::Char(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+            Value::Box(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+            Value::Time(arr) => arr.pick_axis(axis, &indices, env)?.into(),
+        })
+    }
+    /// Reverse a value along several axes
+    ///
+    /// Axes can be given by name or by index.
+    pub fn reverse_axes(&self, mut from: Value, env: &Uiua) -> UiuaResult<Value> {
+        let rank = from.rank();
+        let axes: Vec<usize> = match self {
+            Value::Char(_) | Value::Box(_) => {
+                let names = axis_names(self, env)?;
+                let from_axes = from.axes();
+                (names.iter())
+                    .map(|name| {
+                        (from_axes.and_then(|axes| axes.iter().position(|axis| axis == name)))
+                            .ok_or_else(|| {
+                                env.error(format!(
+                                    "Array of shape {} has no axis named {name:?}",
+                                    from.shape_string()
+                                ))
+                            })
+                    })
+                    .collect::<UiuaResult<_>>()?
+            }
+            _ => (self
+                .as_ints(env, "Axes must be integers or axis names")?
+                .into_iter())
+            .map(|i| {
+                let j = if i >= 0 { i } else { i + rank as isize };
+                if j < 0 || j >= rank as isize {
+                    Err(env.error(format!("Axis {i} is out of bounds of a rank {rank} array")))
+                } else {
+                    Ok(j as usize)
+                }
+            })
+            .collect::<UiuaResult<_>>()?,
+        };
+        for (i, axis) in axes.iter().enumerate() {
+            if axes[..i].contains(axis) {
+                return Err(env.error(format!("Axis {axis} is reversed more than once")));
+            }
+        }
+        match &mut from {
+            Value::Num(arr) => arr.reverse_axes(&axes),
+            Value::Byte(arr) => arr.reverse_axes(&axes),
+            Value::Complex(arr) => arr.reverse_axes(&axes),
+            Value::Char(arr) => arr.reverse_axes(&axes),
+            Value::Box(arr) => arr.reverse_axes(&axes),
+            Value::Time(arr) => arr.reverse_axes(&axes),
+        }
+        Ok(from)
+    }
+    /// Move the axes of a value to the given positions
+    ///
+    /// Axes that are moved to the same position are combined into their diagonal.
+    pub fn transpose_axes(&self, from: Value, env: &Uiua) -> UiuaResult<Value> {
+        let perm = self.as_nats(env, "Axis positions must be natural numbers")?;
+        if perm.len() != from.rank() {
+            return Err(env.error(format!(
+                "Cannot transpose a rank {} array with {} axis position{}",
+                from.rank(),
+                perm.len(),
+                if perm.len() == 1 { "" } else { "s" }
+            )));
+        }
+        if perm.iter().any(|&p| p >= from.rank()) {
+            return Err(env.error(format!(
+                "Axis positions must be less than the array's rank of {}",
+                from.rank()
+            )));
+        }
+        let new_rank = perm.iter().max().map_or(0, |&max| max + 1);
+        if let Some(missing) = (0..new_rank).find(|j| !perm.contains(j)) {
+            return Err(env.error(format!(
+                "Axis positions must include every position up to {}, \
+                but {missing} is missing",
+                new_rank - 1
+            )));
+        }
+        Ok(match from {
+            Value::Num(arr) => arr.transpose_axes(&perm, new_rank).into(),
+            Value::Byte(arr) => arr.transpose_axes(&perm, new_rank).into(),
+            Value::Complex(arr) => arr.transpose_axes(&perm, new_rank).into(),
+            Value::Char(arr) => arr.transpose_axes(&perm, new_rank).into(),
+            Value::Box(arr) => arr.transpose_axes(&perm, new_rank).into(),
+            Value::Time(arr) => arr.transpose_axes(&perm, new_rank).into(),
+        })
+    }
+    /// Take a diagonal of a value
+    ///
+    /// The diagonal is taken along the first two axes. A positive offset moves it
+    /// above the leading diagonal, and a negative offset moves it below.
+    pub fn diagonal(&self, from: &Value, env: &Uiua) -> UiuaResult<Value> {
+        let k = self.as_int(env, "Diagonal offset must be an integer")?;
+        from.diagonal_at(k, env)
+    }
+    pub(crate) fn diagonal_at(&self, k: isize, env: &Uiua) -> UiuaResult<Value> {
+        if self.rank() < 2 {
+            return Err(env.error(format!(
+                "Cannot take the diagonal of a rank {} array",
+                self.rank()
+            )));
+        }
+        Ok(match self {
+            Value::Num(arr) => arr.diagonal(k).into(),
+            Value::Byte(arr) => arr.diagonal(k).into(),
+            Value::Complex(arr) => arr.diagonal(k).into(),
+            Value::Char(arr) => arr.diagonal(k).into(),
+            Value::Box(arr) => arr.diagonal(k).into(),
+            Value::Time(arr) => arr.diagonal(k).into(),
+        })
+    }
+    pub(crate) fn undo_diagonal(self, k: &Value, into: Self, env: &Uiua) -> UiuaResult<Self> {
+        let k = k.as_int(env, "Diagonal offset must be an integer")?;
+        self.generic_bin_into(
+            into,
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| a.undo_diagonal(k, b, env).map(Into::into),
+            |a, b| {
+                env.error(format!(
+                    "Cannot undo diagonal of {} into {}",
+                    a.type_name(),
+                    b.type_name()
+                ))
+            },
+        )
+    }
+    /// Make a diagonal matrix from the rows of a value
+    ///
+    /// Elements off the diagonal are the fill value, or the type's default if no fill is set.
+    pub fn diag(&self, env: &Uiua) -> UiuaResult<Value> {
+        if self.rank() == 0 {
+            return Err(env.error("Cannot make a diagonal matrix from a scalar"));
+        }
+        Ok(match self {
+            Value::Num(arr) => arr.diag(env).into(),
+            Value::Byte(arr) => arr.diag(env).into(),
+            Value::Complex(arr) => arr.diag(env).into(),
+            Value::Char(arr) => arr.diag(env).into(),
+            Value::Box(arr) => arr.diag(env).into(),
+            Value::Time(arr) => arr.diag(env).into(),
+        })
+    }
+}
+
+/// Get the first row and column of a diagonal and its length
+fn diagonal_bounds(rows: usize, cols: usize, k: isize) -> (usize, usize, usize) {
+    let (row_start, col_start) = if k >= 0 {
+        (0, k as usize)
+    } else {
+        (k.unsigned_abs(), 0)
+    };
+    let len = (rows.saturating_sub(row_start)).min(cols.saturating_sub(col_start));
+    (row_start, col_start, len)
+}
+
+impl<T: ArrayValue> Array<T> {
+    /// Move the axes of the array in one pass over the data
+    ///
+    /// Axis `k` is moved to position `perm[k]`.
+    pub(crate) fn transpose_axes(mut self, perm: &[usize], new_rank: usize) -> Self {
+        if perm.iter().enumerate().all(|(k, &j)| k == j) {
+            return self;
+        }
+        self.take_map_keys();
+        let rank = self.rank();
+        let mut src_strides = vec![1; rank];
+        for k in (0..rank.saturating_sub(1)).rev() {
+            src_strides[k] = src_strides[k + 1] * self.shape[k + 1];
+        }
+        let mut shape: Shape = repeat(usize::MAX).take(new_rank).collect();
+        let mut strides = vec![0; new_rank];
+        for (k, &j) in perm.iter().enumerate() {
+            shape[j] = shape[j].min(self.shape[k]);
+            strides[j] += src_strides[k];
+        }
+        // Trailing axes that stay in place are copied as contiguous blocks
+        let mut kept = 0;
+        while kept < rank.min(new_rank)
+            && perm[rank - 1 - kept] == new_rank - 1 - kept
+            && perm.iter().filter(|&&j| j == new_rank - 1 - kept).count() == 1
+        {
+            kept += 1;
+        }
+        let block: usize = self.shape[rank - kept..].iter().product();
+        let outer_rank = new_rank - kept;
+        let outer_count: usize = if block == 0 {
+            0
+        } else {
+            shape[..outer_rank].iter().product()
+        };
+        let mut data = EcoVec::with_capacity(outer_count * block);
+        let mut index = vec![0; outer_rank];
+        let mut offset = 0;
+        for _ in 0..outer_count {
+            data.extend_from_slice(&self.data[offset..offset + block]);
+            for j in (0..outer_rank).rev() {
+                index[j] += 1;
+                offset += strides[j];
+                if index[j] < shape[j] {
+                    break;
+                }
+                offset -= strides[j] * index[j];
+                index[j] = 0;
+            }
+        }
+        if new_rank == rank {
+            self.map_axes(|axes| {
+                let old = axes.clone();
+                let axes = axes.make_mut();
+                for (k, &j) in perm.iter().enumerate() {
+                    axes[j] = old[k].clone();
+                }
+            });
+        } else {
+            self.take_axes();
+        }
+        self.shape = shape;
+        self.data = data.into();
+        self.validate_shape();
+        self
+    }
+    /// Take the `k`th diagonal of the first two axes
+    fn diagonal(&self, k: isize) -> Self {
+        let cols = self.shape[1];
+        let (row_start, col_start, len) = diagonal_bounds(self.shape[0], cols, k);
+        let inner: usize = self.shape[2..].iter().product();
+        let mut data = EcoVec::with_capacity(len * inner);
+        for i in 0..len {
+            let start = ((row_start + i) * cols + col_start + i) * inner;
+            data.extend_from_slice(&self.data[start..start + inner]);
+        }
+        let mut shape = Shape::from(&self.shape[1..]);
+        shape[0] = len;
+        Array::new(shape, data)
+    }
+    /// Put a diagonal back into the array it was taken from
+    fn undo_diagonal(self, k: isize, mut into: Self, env: &Uiua) -> UiuaResult<Self> {
+        let cols = into.shape[1];
+        let (row_start, col_start, len) = diagonal_bounds(into.shape[0], cols, k);
+        let mut expected_shape = Shape::from(&into.shape[1..]);
+        expected_shape[0] = len;
+        if self.shape != expected_shape {
+            return Err(env.error(format!(
+                "Attempted to undo diagonal, but the shape of the diagonal \
+                changed from {} to {}",
+                expected_shape, self.shape
+            )));
+        }
+        let inner = self.row_len();
+        let data = into.data.as_mut_slice();
+        for (i, row) in self.data.chunks_exact(inner.max(1)).enumerate() {
+            let start = ((row_start + i) * cols + col_start + i) * inner;
+            data[start..start + inner].clone_from_slice(row);
+        }
+        into.reset_meta_flags();
+        Ok(into)
+    }
+    /// Make a diagonal matrix with the array's rows on the diagonal
+    fn diag(&self, env: &Uiua) -> Self {
+        let n = self.row_count();
+        let inner = self.row_len();
+        let fill = env.scalar_fill().unwrap_or_else(|_| T::proxy());
+        let mut data: EcoVec<T> = repeat(fill).take(n * n * inner).collect();
+        let slice = data.make_mut();
+        for (i, row) in self.data.chunks_exact(inner.max(1)).enumerate() {
+            let start = (i * n + i) * inner;
+            slice[start..start + inner].clone_from_slice(row);
+        }
+        let mut shape = self.shape.clone();
+        shape.insert(0, n);
+        Array::new(shape, data)
+    }
+    /// Reverse the array along several axes in one pass over the data
+    fn reverse_axes(&mut self, axes: &[usize]) {
+        let &[first, ..] = axes else {
+            return;
+        };
+        if axes.len() == 1 {
+            self.reverse_depth(first);
+            return;
+        }
+        let last = *axes.iter().max().unwrap();
+        let outer_shape = Shape::from(&self.shape[..=last]);
+        let inner: usize = self.shape[last + 1..].iter().product();
+        if self.data.is_empty() {
+            return;
+        }
+        let mut data = EcoVec::with_capacity(self.data.len());
+        let mut index = Vec::with_capacity(outer_shape.len());
+        for o in 0..outer_shape.elements() {
+            outer_shape.flat_to_dims(o, &mut index);
+            for &axis in axes {
+                index[axis] = outer_shape[axis] - 1 - index[axis];
+            }
+            let start = outer_shape.dims_to_flat(&index).unwrap() * inner;
+            data.extend_from_slice(&self.data[start..start + inner]);
+        }
+        self.data = data.into();
+        if axes.contains(&0) {
+            if let Some(keys) = self.get_meta_mut().and_then(|meta| meta.map_keys.as_mut()) {
+                keys.reverse();
+            }
+        }
+    }
+    /// Pick from the array along an axis
+    ///
+    /// A single index removes the axis. A list of indices keeps it.
+    fn pick_axis(
+        &self,
+        axis: usize,
+        indices: &Result<isize, Vec<isize>>,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        let (indices, single) = match indices {
+            Ok(i) => (std::slice::from_ref(i), true),
+            Err(is) => (is.as_slice(), false),
+        };
+        let mut axes = self.meta().axes.clone().unwrap_or_default();
+        let dim = self.shape[axis];
+        let indices = (indices.iter())
+            .map(|&i| {
+                let j = if i >= 0 { i } else { i + dim as isize };
+                if j < 0 || j >= dim as isize {
+                    Err(env.error(format!(
+                        "Index {i} is out of bounds of axis {:?} of length {dim}",
+                        axes[axis]
+                    )))
+                } else {
+                    Ok(j as usize)
+                }
+            })
+            .collect::<UiuaResult<Vec<_>>>()?;
+        let outer: usize = self.shape[..axis].iter().product();
+        let inner: usize = self.shape[axis + 1..].iter().product();
+        let mut data = EcoVec::with_capacity(outer * indices.len() * inner);
+        for o in 0..outer {
+            for &i in &indices {
+                let start = (o * dim + i) * inner;
+                data.extend_from_slice(&self.data[start..start + inner]);
+            }
+        }
+        let mut shape = self.shape.clone();
+        if single {
+            shape.remove(axis);
+            axes.remove(axis);
+        } else {
+            shape[axis] = indices.len();
+        }
+        let mut arr = Array::new(shape, data);
+        arr.meta_mut().axes = Some(axes);
+        Ok(arr)
+    }
+}
@@ -0,0 +1,167 @@
+//! Business-day calendar arithmetic
+//!
+//! Dates are Unix timestamps in seconds, the same as those returned by `now`.
+//! Saturdays and Sundays are never business days.
+
+use crate::{
+    algorithm::{
+        num_array,
+        pervade::{bin_pervade, FalliblePerasiveFn},
+    },
+    grid_fmt::GridFmt,
+    Uiua, UiuaResult, Value,
+};
+
+const SECS_PER_DAY: f64 = 86400.0;
+/// The largest number of days a date or count may be from zero
+///
+/// This is about 2.7 million years, which keeps day arithmetic from overflowing.
+const MAX_DAYS: f64 = 1e9;
+
+/// A sorted, deduplicated list of holidays that fall on weekdays
+struct Holidays(Vec<i64>);
+
+impl Holidays {
+    fn from_value(val: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let dates = num_array(val, "Holidays", env)?;
+        let mut days = Vec::with_capacity(dates.element_count());
+        for &date in &dates.data {
+            let day = day_of(date, env)?;
+            if !is_weekend(day) {
+                days.push(day);
+            }
+        }
+        days.sort_unstable();
+        days.dedup();
+        Ok(Holidays(days))
+    }
+    /// The number of holidays in the day range `[start, end)`
+    fn count(&self, start: i64, end: i64) -> i64 {
+        let start = self.0.partition_point(|&d| d < start);
+        let end = self.0.partition_point(|&d| d < end);
+        end.saturating_sub(start) as i64
+    }
+}
+
+fn day_of(date: f64, env: &Uiua) -> UiuaResult<i64> {
+    if !date.is_finite() {
+        return Err(env.error(format!("Cannot use {date} as a date")));
+    }
+    let day = (date / SECS_PER_DAY).floor();
+    if day.abs() > MAX_DAYS {
+        return Err(env.error(format!(
+            "Date {} is too far from 1970",
+            date.grid_string(true)
+        )));
+    }
+    Ok(day as i64)
+}
+
+fn is_weekend(day: i64) -> bool {
+    // Day 0 was a Thursday
+    matches!((day + 3).rem_euclid(7), 5 | 6)
+}
+
+/// The number of weekdays before a day, counting from a Monday
+fn weekdays_before(day: i64) -> i64 {
+    // Day -3 was a Monday
+    let k = day + 3;
+    k.div_euclid(7) * 5 + k.rem_euclid(7).min(5)
+}
+
+/// The day of the weekday with the given index
+///
+/// This is the inverse of [`weekdays_before`] for weekdays.
+fn weekday_at(index: i64) -> i64 {
+    index.div_euclid(5) * 7 + index.rem_euclid(5) - 3
+}
+
+/// The number of business days in the day range `[start, end)`
+fn business_days(start: i64, end: i64, holidays: &Holidays) -> i64 {
+    if end < start {
+        return -business_days(end, start, holidays);
+    }
+    weekdays_before(end) - weekdays_before(start) - holidays.count(start, end)
+}
+
+/// Offset a day by `n` business days, not counting the day itself
+fn offset_business_days(day: i64, n: i64, holidays: &Holidays) -> i64 {
+    if n == 0 {
+        return day;
+    }
+    // Each iteration skips the holidays found in the previous one.
+    // The holiday count only grows as the range does, so this terminates.
+    let mut skipped = 0;
+    if n > 0 {
+        let first = weekdays_before(day + 1);
+        loop {
+            let end = weekday_at(first + n - 1 + skipped);
+            let count = holidays.count(day + 1, end + 1);
+            if count == skipped {
+                break end;
+            }
+            skipped = count;
+        }
+    } else {
+        let last = weekdays_before(day);
+        loop {
+            let start = weekday_at(last + n - skipped);
+            let count = holidays.count(start, day);
+            if count == skipped {
+                break start;
+            }
+            skipped = count;
+        }
+    }
+}
+
+impl Value {
+    /// Offset dates by numbers of business days
+    pub(crate) fn workday(&self, dates: &Self, holidays: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let holidays = Holidays::from_value(holidays, env)?;
+        let counts = num_array(self, "Business day counts", env)?;
+        let dates = num_array(dates, "Dates", env)?;
+        let res = bin_pervade(
+            counts,
+            dates,
+            0,
+            0,
+            env,
+            FalliblePerasiveFn::new(|n: f64, date: f64, env: &Uiua| {
+                if n.fract() != 0.0 || !n.is_finite() {
+                    return Err(env.error(format!(
+                        "Business day counts must be integers, but one is {n}"
+                    )));
+                }
+                if n.abs() > MAX_DAYS {
+                    return Err(env.error(format!(
+                        "Business day count {} is too large",
+                        n.grid_string(true)
+                    )));
+                }
+                let day = day_of(date, env)?;
+                let new_day = offset_business_days(day, n as i64, &holidays);
+                Ok(date + (new_day - day) as f64 * SECS_PER_DAY)
+            }),
+        )?;
+        Ok(res.into())
+    }
+    /// Count the business days between dates
+    pub(crate) fn workdays(&self, ends: &Self, holidays: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let holidays = Holidays::from_value(holidays, env)?;
+        let starts = num_array(self, "Dates", env)?;
+        let ends = num_array(ends, "Dates", env)?;
+        let res = bin_pervade(
+            starts,
+            ends,
+            0,
+            0,
+            env,
+            FalliblePerasiveFn::new(|start: f64, end: f64, env: &Uiua| {
+                let (start, end) = (day_of(start, env)?, day_of(end, env)?);
+                Ok(business_days(start, end, &holidays) as f64)
+            }),
+        )?;
+        Ok(res.into())
+    }
+}
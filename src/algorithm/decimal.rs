@@ -0,0 +1,87 @@
+//! Exact arithmetic on numbers as the decimals they are written as
+
+/// A number as an exact decimal, `mantissa × 10^exponent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Decimal {
+    pub mantissa: i128,
+    pub exponent: i32,
+}
+
+impl Decimal {
+    /// Get the shortest decimal that rounds to a number
+    ///
+    /// This is the decimal the number is formatted as, so `0.1` is exactly one tenth.
+    /// Returns `None` for non-finite numbers.
+    pub fn from_f64(x: f64) -> Option<Self> {
+        if !x.is_finite() {
+            return None;
+        }
+        let s = format!("{x:e}");
+        let (digits, exponent) = s.split_once('e')?;
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let mantissa: i128 = format!("{int}{frac}").parse().ok()?;
+        let exponent = exponent.parse::<i32>().ok()? - frac.len() as i32;
+        Some(Decimal { mantissa, exponent })
+    }
+    /// Round the decimal to the nearest number
+    pub fn to_f64(self) -> f64 {
+        format!("{}e{}", self.mantissa, self.exponent)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+    /// Get the mantissa of the decimal at a smaller exponent
+    ///
+    /// Returns `None` if it does not fit.
+    pub fn mantissa_at(self, exponent: i32) -> Option<i128> {
+        if self.mantissa == 0 {
+            return Some(0);
+        }
+        let shift = u32::try_from(self.exponent.checked_sub(exponent)?).ok()?;
+        (self.mantissa).checked_mul(10i128.checked_pow(shift)?)
+    }
+    /// Sum decimals exactly
+    ///
+    /// Returns `None` if the sum does not fit.
+    pub fn sum(decimals: &[Self]) -> Option<Self> {
+        let (mantissas, exponent) = Self::common(decimals)?;
+        let mantissa = (mantissas.into_iter()).try_fold(0i128, i128::checked_add)?;
+        Some(Decimal { mantissa, exponent })
+    }
+    /// Put decimals over a common exponent
+    ///
+    /// Returns `None` if they span too many decimal places.
+    pub fn common(decimals: &[Self]) -> Option<(Vec<i128>, i32)> {
+        let exponent = (decimals.iter())
+            .filter(|d| d.mantissa != 0)
+            .map(|d| d.exponent)
+            .min()
+            .unwrap_or(0);
+        let mantissas = (decimals.iter())
+            .map(|d| d.mantissa_at(exponent))
+            .collect::<Option<_>>()?;
+        Some((mantissas, exponent))
+    }
+    /// Divide the decimal by a count, rounding once to the nearest number
+    pub fn div_to_f64(self, n: usize) -> f64 {
+        let n = n as i128;
+        // Get enough digits that rounding the truncated quotient is exact
+        let (mut mantissa, mut exponent) = (self.mantissa, self.exponent);
+        while mantissa != 0 && mantissa.unsigned_abs() < 10u128.pow(36) {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+        let mut quotient = mantissa / n;
+        if mantissa % n != 0 {
+            // A final nonzero digit keeps inexact quotients from rounding like ties
+            if let Some(q) = quotient.checked_mul(10) {
+                quotient = q + mantissa.signum();
+                exponent -= 1;
+            }
+        }
+        Decimal {
+            mantissa: quotient,
+            exponent,
+        }
+        .to_f64()
+    }
+}
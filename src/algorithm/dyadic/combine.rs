@@ -3,9 +3,9 @@
 use std::{cmp::Ordering, mem::take};
 
 use crate::{
-    algorithm::{max_shape, op2_bytes_retry_fill, FillContext},
+    algorithm::{max_shape, op2_bytes_retry_fill, validate_size, ErrorContext, FillContext},
     cowslice::cowslice,
-    Array, ArrayValue, FormatShape, Primitive, Uiua, UiuaResult, Value,
+    Array, ArrayBuilder, ArrayValue, FormatShape, Primitive, Uiua, UiuaResult, Value,
 };
 
 fn data_index_to_shape_index(mut index: usize, shape: &[usize], out: &mut [usize]) -> bool {
@@ -81,6 +81,7 @@ impl<T: ArrayValue> Array<T> {
         }
         self.data = new_data;
         self.shape = shape.into();
+        self.reset_meta_flags();
     }
 }
 
@@ -108,6 +109,7 @@ impl Value {
             )?,
             (Value::Complex(a), Value::Complex(b)) => a.join_impl(b, ctx)?.into(),
             (Value::Char(a), Value::Char(b)) => a.join_impl(b, ctx)?.into(),
+            (Value::Time(a), Value::Time(b)) => a.join_impl(b, ctx)?.into(),
             (Value::Byte(a), Value::Num(b)) => a.convert().join_impl(b, ctx)?.into(),
             (Value::Num(a), Value::Byte(b)) => a.join_impl(b.convert(), ctx)?.into(),
             (Value::Complex(a), Value::Num(b)) => a.join_impl(b.convert(), ctx)?.into(),
@@ -142,6 +144,7 @@ impl Value {
             }
             (Value::Complex(a), Value::Complex(b)) => a.append(b, ctx)?,
             (Value::Char(a), Value::Char(b)) => a.append(b, ctx)?,
+            (Value::Time(a), Value::Time(b)) => a.append(b, ctx)?,
             (Value::Byte(a), Value::Num(b)) => {
                 let mut a = a.convert_ref();
                 a.append(b, ctx)?;
@@ -193,6 +196,9 @@ impl Value {
             Value::Box(a) => a
                 .undo_join(&a_shape, &b_shape, env)
                 .map(|(a, b)| (a.into(), b.into())),
+            Value::Time(a) => a
+                .undo_join(&a_shape, &b_shape, env)
+                .map(|(a, b)| (a.into(), b.into())),
         }
     }
     pub(crate) fn unjoin(self, env: &Uiua) -> UiuaResult<(Self, Self)> {
@@ -202,6 +208,7 @@ impl Value {
             |arr| arr.unjoin(env).map(|(a, b)| (a.into(), b.into())),
             |arr| arr.unjoin(env).map(|(a, b)| (a.into(), b.into())),
             |arr| arr.unjoin(env).map(|(a, b)| (a.into(), b.into())),
+            |arr| arr.unjoin(env).map(|(a, b)| (a.into(), b.into())),
         )
     }
     pub(crate) fn unjoin_shape(self, shape: &[usize], env: &Uiua) -> UiuaResult<(Self, Self)> {
@@ -226,6 +233,10 @@ impl Value {
                 arr.unjoin_shape(shape, env)
                     .map(|(a, b)| (a.into(), b.into()))
             },
+            |arr| {
+                arr.unjoin_shape(shape, env)
+                    .map(|(a, b)| (a.into(), b.into()))
+            },
         )
     }
 }
@@ -528,6 +539,8 @@ impl<T: ArrayValue> Array<T> {
 impl Value {
     /// `couple` the value with another
     pub fn couple(mut self, other: Self, env: &Uiua) -> UiuaResult<Self> {
+        let max_elements = self.element_count().max(other.element_count());
+        validate_size::<f64>([2, max_elements], env)?;
         self.couple_impl(other, env)?;
         Ok(self)
     }
@@ -563,6 +576,7 @@ impl Value {
             }
             (Value::Complex(a), Value::Complex(b)) => a.couple_impl(b, ctx)?,
             (Value::Char(a), Value::Char(b)) => a.couple_impl(b, ctx)?,
+            (Value::Time(a), Value::Time(b)) => a.couple_impl(b, ctx)?,
             (Value::Box(a), Value::Box(b)) => a.couple_impl(b, ctx)?,
             (Value::Num(a), Value::Byte(b)) => a.couple_impl(b.convert(), ctx)?,
             (Value::Byte(a), Value::Num(b)) => {
@@ -599,6 +613,7 @@ impl Value {
             Value::Complex(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Char(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Box(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
+            Value::Time(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
         }
     }
 }
@@ -707,6 +722,26 @@ impl<T: ArrayValue> Array<T> {
     {
         Self::from_row_arrays_impl(values, env)
     }
+    /// Create an array from row arrays that all have the same shape
+    ///
+    /// Unlike [`Array::from_row_arrays`], this never uses fill values.
+    pub fn from_rows<C: ErrorContext>(rows: Vec<Self>, ctx: &C) -> Result<Self, C::Error> {
+        let Some(first) = rows.first() else {
+            return Ok(Self::default());
+        };
+        if let Some(row) = rows.iter().find(|row| row.shape() != first.shape()) {
+            return Err(ctx.error(format!(
+                "Cannot create array from rows with shapes {} and {}",
+                first.shape(),
+                row.shape()
+            )));
+        }
+        let mut builder = ArrayBuilder::with_capacity(rows.len());
+        for row in rows {
+            builder.push_row(row, ctx)?;
+        }
+        Ok(builder.finish())
+    }
     #[track_caller]
     /// Create an array from row arrays
     ///
@@ -731,16 +766,34 @@ impl<T: ArrayValue> Array<T> {
         let Some(mut arr) = row_values.next() else {
             return Ok(Self::default());
         };
-        if let Some(row) = row_values.next() {
-            let total_elements = total_rows * arr.shape().iter().product::<usize>();
-            arr.data.reserve_min(total_elements);
+        let Some(row) = row_values.next() else {
+            arr.shape.insert(0, 1);
+            return Ok(arr);
+        };
+        if arr.shape != row.shape {
             arr.couple_impl(row, ctx)?;
             for row in row_values {
                 arr.append(row, ctx)?;
             }
-        } else {
-            arr.shape.insert(0, 1);
+            return Ok(arr);
+        }
+        // Rows with the same shape are collected without joining
+        let mut builder = ArrayBuilder::with_capacity(total_rows);
+        builder.push_row(arr, ctx)?;
+        builder.push_row(row, ctx)?;
+        for row in row_values.by_ref() {
+            if builder.fits(&row) {
+                builder.push_row(row, ctx)?;
+                continue;
+            }
+            // Rows with different shapes may need to be filled
+            let mut arr = builder.finish();
+            arr.append(row, ctx)?;
+            for row in row_values {
+                arr.append(row, ctx)?;
+            }
+            return Ok(arr);
         }
-        Ok(arr)
+        Ok(builder.finish())
     }
 }
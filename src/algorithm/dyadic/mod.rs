@@ -10,21 +10,22 @@ use std::{
     hash::{Hash, Hasher},
     iter::{once, repeat},
     mem::take,
+    ops::{Add, Mul},
 };
 
-use ecow::{eco_vec, EcoVec};
+use ecow::{eco_vec, EcoString, EcoVec};
 use rayon::prelude::*;
 
 use crate::{
-    algorithm::pervade::{self, bin_pervade_recursive, InfalliblePervasiveFn},
+    algorithm::pervade::{bin_pervade_recursive, InfalliblePervasiveFn},
     array::*,
     boxed::Boxed,
     cowslice::{cowslice, CowSlice},
     value::Value,
-    Shape, Uiua, UiuaResult,
+    Complex, Shape, Uiua, UiuaResult,
 };
 
-use super::{shape_prefixes_match, validate_size, ArrayCmpSlice, FillContext};
+use super::{shape_prefixes_match, validate_size, ArrayCmpSlice, FillContext, SizeError};
 
 impl Value {
     pub(crate) fn bin_coerce_to_boxes<T, C: FillContext, E: ToString>(
@@ -155,12 +156,16 @@ impl Value {
         )?;
         if shape.rank() == 0 {
             let n = target_shape[0];
+            if let Ok(n) = n {
+                validate_size::<f64>([n.unsigned_abs(), self.element_count()], env)?;
+            }
             match self {
                 Value::Num(a) => a.reshape_scalar(n),
                 Value::Byte(a) => a.reshape_scalar(n),
                 Value::Complex(a) => a.reshape_scalar(n),
                 Value::Char(a) => a.reshape_scalar(n),
                 Value::Box(a) => a.reshape_scalar(n),
+                Value::Time(a) => a.reshape_scalar(n),
             }
         } else {
             match self {
@@ -178,13 +183,32 @@ impl Value {
                 Value::Complex(a) => a.reshape(&target_shape, env),
                 Value::Char(a) => a.reshape(&target_shape, env),
                 Value::Box(a) => a.reshape(&target_shape, env),
+                Value::Time(a) => a.reshape(&target_shape, env),
             }?
         }
         Ok(())
     }
-    pub(crate) fn undo_reshape(&mut self, old_shape: &Self, env: &Uiua) -> UiuaResult {
-        if old_shape.as_nat(env, "").is_ok() {
-            return Err(env.error("Cannot undo scalar reshae"));
+    pub(crate) fn undo_reshape(
+        &mut self,
+        shape: &Self,
+        old_shape: &Self,
+        env: &Uiua,
+    ) -> UiuaResult {
+        if shape.rank() == 0 {
+            let target_shape = shape.as_ints_or_infs(
+                env,
+                "Shape should be a single integer \
+                or a list of integers or infinity",
+            )?;
+            let n = target_shape[0];
+            return match self {
+                Value::Num(a) => a.undo_reshape_scalar(n, env),
+                Value::Byte(a) => a.undo_reshape_scalar(n, env),
+                Value::Complex(a) => a.undo_reshape_scalar(n, env),
+                Value::Char(a) => a.undo_reshape_scalar(n, env),
+                Value::Box(a) => a.undo_reshape_scalar(n, env),
+                Value::Time(a) => a.undo_reshape_scalar(n, env),
+            };
         }
         let orig_shape = old_shape.as_nats(env, "Shape should be a list of integers")?;
         if orig_shape.iter().product::<usize>() == self.shape().iter().product::<usize>() {
@@ -221,6 +245,7 @@ impl<T: Clone> Array<T> {
                 if count < 0 {
                     self.reverse();
                 }
+                self.map_axes(|axes| axes.insert(0, EcoString::new()));
                 self.shape.insert(0, count.unsigned_abs());
             }
             Err(rev) => {
@@ -233,6 +258,47 @@ impl<T: Clone> Array<T> {
 }
 
 impl<T: ArrayValue> Array<T> {
+    /// Undo a scalar `reshape`
+    ///
+    /// The rows must all be the same, since they were copies of the original array.
+    fn undo_reshape_scalar(&mut self, count: Result<isize, bool>, env: &Uiua) -> UiuaResult {
+        let count = match count {
+            Ok(count) => count,
+            Err(rev) => {
+                if rev {
+                    self.reverse();
+                }
+                return Ok(());
+            }
+        };
+        if self.rank() == 0 {
+            return Err(env.error("Cannot undo scalar reshape of a scalar"));
+        }
+        if self.row_count() == 0 {
+            return Err(env.error(
+                "Cannot undo scalar reshape because there are no rows \
+                to get the original array from",
+            ));
+        }
+        let row_len = self.row_len();
+        if row_len > 0 {
+            let first = ArrayCmpSlice(&self.data[..row_len]);
+            if (self.data.chunks_exact(row_len)).any(|row| ArrayCmpSlice(row) != first) {
+                return Err(env.error(
+                    "Cannot undo scalar reshape because \
+                    the rows are not all the same",
+                ));
+            }
+        }
+        self.take_map_keys();
+        self.data.truncate(row_len);
+        if count < 0 {
+            self.data.as_mut_slice().reverse();
+        }
+        self.shape.remove(0);
+        self.validate_shape();
+        Ok(())
+    }
     /// `reshape` the array
     pub fn reshape(&mut self, dims: &[Result<isize, bool>], env: &Uiua) -> UiuaResult {
         let fill = env.scalar_fill::<T>();
@@ -244,12 +310,13 @@ impl<T: ArrayValue> Array<T> {
             .filter_map(|(i, &s)| if s < 0 { Some(i) } else { None })
             .collect();
         let shape: Shape = axes.iter().map(|&s| s.unsigned_abs()).collect();
-        let target_len: usize = shape.iter().product();
+        let target_len = validate_size::<T>(shape.iter().copied(), env)?;
         if self.data.len() < target_len {
             match env.scalar_fill::<T>() {
                 Ok(fill) => {
                     let start = self.data.len();
                     self.data.extend(repeat(fill).take(target_len - start));
+                    self.reset_meta_flags();
                 }
                 Err(e) => {
                     if self.data.is_empty() {
@@ -277,6 +344,9 @@ impl<T: ArrayValue> Array<T> {
         } else {
             self.data.truncate(target_len);
         }
+        if shape.len() != self.rank() {
+            self.take_axes();
+        }
         self.shape = shape;
         self.validate_shape();
         for s in reversed_axes {
@@ -309,7 +379,7 @@ fn derive_shape(
                 if dims[1..].iter().any(|&dim| dim.is_err()) {
                     return Err(env.error("Cannot reshape array with multiple infinite dimensions"));
                 }
-                let shape_non_leading_len = dims[1..].iter().flatten().product::<isize>() as usize;
+                let shape_non_leading_len = dims_product(dims[1..].iter().flatten(), env)?;
                 if shape_non_leading_len == 0 {
                     return Err(env.error("Cannot reshape array with any 0 non-leading dimensions"));
                 }
@@ -324,7 +394,7 @@ fn derive_shape(
                     return Err(env.error("Cannot reshape array with multiple infinite dimensions"));
                 }
                 let mut axes: Vec<isize> = dims.iter().copied().flatten().collect();
-                let shape_non_trailing_len = axes.iter().copied().product::<isize>().unsigned_abs();
+                let shape_non_trailing_len = dims_product(&axes, env)?;
                 if shape_non_trailing_len == 0 {
                     return Err(
                         env.error("Cannot reshape array with any 0 non-trailing dimensions")
@@ -340,12 +410,14 @@ fn derive_shape(
                 let rev = back[0].unwrap_err();
                 let rev_mul = if rev { -1 } else { 1 };
                 let back = &back[1..];
-                let front_len = front.iter().flatten().product::<isize>().unsigned_abs();
-                let back_len = back.iter().flatten().product::<isize>().unsigned_abs();
+                let front_len = dims_product(front.iter().flatten(), env)?;
+                let back_len = dims_product(back.iter().flatten(), env)?;
                 if front_len == 0 || back_len == 0 {
                     return Err(env.error("Cannot reshape array with any 0 outer dimensions"));
                 }
-                let middle_len = rev_mul * derive_len(shape.iter().product(), front_len * back_len);
+                let outer_len = (front_len.checked_mul(back_len))
+                    .ok_or_else(|| env.error(SizeError::Overflow))?;
+                let middle_len = rev_mul * derive_len(shape.iter().product(), outer_len);
                 let mut axes: Vec<isize> = front.iter().copied().flatten().collect();
                 axes.push(middle_len);
                 axes.extend(back.iter().flatten());
@@ -356,6 +428,12 @@ fn derive_shape(
     })
 }
 
+/// Get the number of elements in some reshape dimensions
+fn dims_product<'a>(dims: impl IntoIterator<Item = &'a isize>, env: &Uiua) -> UiuaResult<usize> {
+    let shape: Shape = dims.into_iter().map(|d| d.unsigned_abs()).collect();
+    shape.checked_elements().map_err(|e| env.error(e))
+}
+
 impl Value {
     /// `rerank` this value with another
     pub fn rerank(&mut self, rank: &Self, env: &Uiua) -> UiuaResult {
@@ -441,12 +519,14 @@ impl Value {
             or list of natural numbers",
         )?;
         Ok(if self.rank() == 0 {
+            validate_size::<f64>([counts[0], kept.element_count()], env)?;
             match kept {
                 Value::Num(a) => a.scalar_keep(counts[0]).into(),
                 Value::Byte(a) => a.scalar_keep(counts[0]).into(),
                 Value::Complex(a) => a.scalar_keep(counts[0]).into(),
                 Value::Char(a) => a.scalar_keep(counts[0]).into(),
                 Value::Box(a) => a.scalar_keep(counts[0]).into(),
+                Value::Time(a) => a.scalar_keep(counts[0]).into(),
             }
         } else {
             match kept {
@@ -455,6 +535,7 @@ impl Value {
                 Value::Complex(a) => a.list_keep(&counts, env)?.into(),
                 Value::Char(a) => a.list_keep(&counts, env)?.into(),
                 Value::Box(a) => a.list_keep(&counts, env)?.into(),
+                Value::Time(a) => a.list_keep(&counts, env)?.into(),
             }
         })
     }
@@ -465,6 +546,7 @@ impl Value {
             |a| a.unkeep(env).map(|(a, b)| (a, b.into())),
             |a| a.unkeep(env).map(|(a, b)| (a, b.into())),
             |a| a.unkeep(env).map(|(a, b)| (a, b.into())),
+            |a| a.unkeep(env).map(|(a, b)| (a, b.into())),
         )
     }
     pub(crate) fn undo_keep(self, kept: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -474,7 +556,17 @@ impl Value {
             or list of natural numbers",
         )?;
         if self.rank() == 0 {
-            return Err(env.error("Cannot invert scalar keep"));
+            let count = counts[0];
+            return kept.generic_bin_into(
+                into,
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| a.undo_scalar_keep(count, b, env).map(Into::into),
+                |a, b| env.error(format!("Cannot unkeep {a} array with {b} array")),
+            );
         }
         kept.generic_bin_into(
             into,
@@ -483,6 +575,7 @@ impl Value {
             |a, b| a.undo_keep(&counts, b, env).map(Into::into),
             |a, b| a.undo_keep(&counts, b, env).map(Into::into),
             |a, b| a.undo_keep(&counts, b, env).map(Into::into),
+            |a, b| a.undo_keep(&counts, b, env).map(Into::into),
             |a, b| env.error(format!("Cannot unkeep {a} array with {b} array")),
         )
     }
@@ -529,6 +622,8 @@ impl<T: ArrayValue> Array<T> {
     pub fn list_keep(mut self, counts: &[usize], env: &Uiua) -> UiuaResult<Self> {
         self.take_map_keys();
         let counts = pad_keep_counts(counts, self.row_count(), env)?;
+        let total = (counts.iter()).fold(0usize, |acc, &n| acc.saturating_add(n));
+        validate_size::<T>([total, self.row_len()], env)?;
         if self.rank() == 0 {
             if counts.len() != 1 {
                 return Err(env.error("Scalar array can only be kept with a single number"));
@@ -616,6 +711,54 @@ impl<T: ArrayValue> Array<T> {
         self.validate_shape();
         Ok((counts.into(), self))
     }
+    /// Undo a scalar `keep` by taking one of each group of repeated rows
+    fn undo_scalar_keep(self, count: usize, into: Self, env: &Uiua) -> UiuaResult<Self> {
+        if count == 0 {
+            if self.row_count() != 0 {
+                return Err(env.error(
+                    "Kept array has rows, but none were kept, \
+                    so the keep cannot be inverted",
+                ));
+            }
+            return Ok(into);
+        }
+        let orig_rows = if into.rank() == 0 {
+            1
+        } else {
+            into.row_count()
+        };
+        if self.rank() == 0 || self.row_count() != count * orig_rows {
+            return Err(env.error(format!(
+                "Kept array should have {} rows, but it has shape {}, \
+                so the keep cannot be inverted",
+                count * orig_rows,
+                self.shape()
+            )));
+        }
+        let row_len = self.row_len();
+        let mut data = EcoVec::with_capacity(orig_rows * row_len);
+        if row_len > 0 {
+            for group in self.data.chunks_exact(count * row_len) {
+                let first = &group[..row_len];
+                if (group.chunks_exact(row_len))
+                    .any(|row| ArrayCmpSlice(row) != ArrayCmpSlice(first))
+                {
+                    return Err(env.error(
+                        "Repeated rows were changed differently, \
+                        so the keep cannot be inverted",
+                    ));
+                }
+                data.extend_from_slice(first);
+            }
+        }
+        let mut shape = self.shape.clone();
+        if into.rank() == 0 {
+            shape.remove(0);
+        } else {
+            shape[0] = orig_rows;
+        }
+        Ok(Array::new(shape, data))
+    }
     fn undo_keep(self, counts: &[usize], into: Self, env: &Uiua) -> UiuaResult<Self> {
         let counts = pad_keep_counts(counts, into.row_count(), env)?;
         if counts.iter().any(|&n| n > 1) {
@@ -737,7 +880,13 @@ impl Value {
             return Ok(rotated);
         }
         let by_ints = || self.as_integer_array(env, "Rotation amount must be an array of integers");
-        if env.num_scalar_fill().is_ok() {
+        if let Some(Value::Complex(_)) = env.value_fill() {
+            match &rotated {
+                Value::Num(nums) => rotated = nums.convert_ref::<Complex>().into(),
+                Value::Byte(bytes) => rotated = bytes.convert_ref::<Complex>().into(),
+                _ => {}
+            }
+        } else if env.num_scalar_fill().is_ok() {
             if let Value::Byte(bytes) = &rotated {
                 rotated = bytes.convert_ref::<f64>().into();
             }
@@ -753,9 +902,47 @@ impl Value {
                 }
             }
             Value::Box(a) => a.rotate_depth(by_ints()?, b_depth, a_depth, env)?,
+            Value::Time(a) => a.rotate_depth(by_ints()?, b_depth, a_depth, env)?,
         }
         Ok(rotated)
     }
+    /// Undo a `rotate` by this amount
+    ///
+    /// If a fill is set, the elements that were shifted out are restored from the original array.
+    pub(crate) fn undo_rotate(
+        &self,
+        rotated: Self,
+        original: Self,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        let unrotated = self.clone().neg(env)?.rotate(rotated, env)?;
+        if self.row_count() == 0 {
+            return Ok(unrotated);
+        }
+        let by: Vec<isize> = (self.as_ints(
+            env,
+            "Rotation amount must be an integer or list of integers",
+        )?)
+        .into_iter()
+        .map(|i| -i)
+        .collect();
+        unrotated.generic_bin_into(
+            original,
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| a.restore_shifted(&by, b, env).map(Into::into),
+            |a, b| {
+                env.error(format!(
+                    "Cannot restore rotated {} array from {} array",
+                    a.type_name(),
+                    b.type_name()
+                ))
+            },
+        )
+    }
 }
 
 impl<T: ArrayValue> Array<T> {
@@ -803,6 +990,34 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+impl<T: ArrayValue> Array<T> {
+    /// Restore the elements that a filled `rotate` shifted out
+    ///
+    /// `by` is the inverse rotation amount.
+    fn restore_shifted(mut self, by: &[isize], original: Self, env: &Uiua) -> UiuaResult<Self> {
+        if env.scalar_fill::<T>().is_err() {
+            return Ok(self);
+        }
+        if self.shape != original.shape {
+            return Err(env.error(format!(
+                "Rotated array's shape was changed from {} to {}, \
+                so the filled rotation cannot be inverted",
+                original.shape(),
+                self.shape()
+            )));
+        }
+        let mut shifted_out = vec![false; self.element_count()];
+        fill_shift(by, &self.shape, &mut shifted_out, true);
+        let data = self.data.as_mut_slice();
+        for ((elem, orig), shifted_out) in data.iter_mut().zip(&original.data).zip(shifted_out) {
+            if shifted_out {
+                *elem = orig.clone();
+            }
+        }
+        Ok(self)
+    }
+}
+
 fn rotate<T>(by: &[isize], shape: &[usize], data: &mut [T]) {
     if by.is_empty() || shape.is_empty() {
         return;
@@ -871,8 +1086,34 @@ impl Value {
             Value::Complex(a) => a.windows(&size_spec, env)?.into(),
             Value::Char(a) => a.windows(&size_spec, env)?.into(),
             Value::Box(a) => a.windows(&size_spec, env)?.into(),
+            Value::Time(a) => a.windows(&size_spec, env)?.into(),
+        })
+    }
+    /// Reconstruct an array from its `windows`, using this value as the window size
+    pub(crate) fn unwindows(&self, windows: Self, env: &Uiua) -> UiuaResult<Self> {
+        let size_spec = self.as_ints(env, "Window size must be an integer or list of integers")?;
+        Ok(match windows {
+            Value::Num(a) => a.unwindows(size_spec.len(), env)?.into(),
+            Value::Byte(a) => a.unwindows(size_spec.len(), env)?.into(),
+            Value::Complex(a) => a.unwindows(size_spec.len(), env)?.into(),
+            Value::Char(a) => a.unwindows(size_spec.len(), env)?.into(),
+            Value::Box(a) => a.unwindows(size_spec.len(), env)?.into(),
+            Value::Time(a) => a.unwindows(size_spec.len(), env)?.into(),
         })
     }
+    pub(crate) fn undo_windows(self, windows: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
+        let size_spec = self.as_ints(env, "Window size must be an integer or list of integers")?;
+        windows.generic_bin_into(
+            into,
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| a.undo_windows(&size_spec, b, env).map(Into::into),
+            |a, b| env.error(format!("Cannot unwindows {a} array with {b} array")),
+        )
+    }
 }
 
 impl<T: ArrayValue> Array<T> {
@@ -887,18 +1128,37 @@ impl<T: ArrayValue> Array<T> {
                 self.shape()
             )));
         }
+        // The number of windows along each axis is the axis length + 1 - the window size
+        let mut lens_plus_one = Vec::with_capacity(isize_spec.len());
+        for d in &self.shape[..isize_spec.len()] {
+            let len = (isize::try_from(*d).ok())
+                .and_then(|d| d.checked_add(1))
+                .ok_or_else(|| env.error(SizeError::Overflow))?;
+            lens_plus_one.push(len);
+        }
         let mut size_spec = Vec::with_capacity(isize_spec.len());
-        for (d, s) in self.shape.iter().zip(isize_spec) {
-            size_spec.push(if *s >= 0 { *s } else { *d as isize + 1 + *s });
+        for ((d, len), s) in self.shape.iter().zip(&lens_plus_one).zip(isize_spec) {
+            size_spec.push(if *s >= 0 {
+                *s
+            } else {
+                match len.checked_add(*s) {
+                    Some(size) if size >= 0 => size,
+                    _ => {
+                        return Err(env.error(format!(
+                            "Negative window size {s} is too large for an axis of length {d}"
+                        )))
+                    }
+                }
+            });
         }
         // Determine the shape of the windows array
         let mut new_shape = Shape::with_capacity(self.shape.len() + size_spec.len());
-        new_shape.extend(
-            self.shape
-                .iter()
-                .zip(&size_spec)
-                .map(|(a, b)| ((*a as isize + 1) - *b).max(0) as usize),
-        );
+        for (len, size) in lens_plus_one.iter().zip(&size_spec) {
+            let count = len
+                .checked_sub(*size)
+                .ok_or_else(|| env.error(SizeError::Overflow))?;
+            new_shape.push(count.max(0) as usize);
+        }
         new_shape.extend(size_spec.iter().map(|&s| s.max(0) as usize));
         new_shape.extend_from_slice(&self.shape[size_spec.len()..]);
         // Check if the window size is too large
@@ -907,6 +1167,10 @@ impl<T: ArrayValue> Array<T> {
                 return Ok(Self::new(new_shape, CowSlice::new()));
             }
         }
+        // Unwindowed axes may still be empty
+        if self.data.is_empty() {
+            return Ok(Self::new(new_shape, CowSlice::new()));
+        }
         // Make a new window shape with the same rank as the windowed array
         let mut true_size: Vec<usize> = Vec::with_capacity(self.shape.len());
         true_size.extend(size_spec.iter().map(|&s| s as usize));
@@ -914,12 +1178,14 @@ impl<T: ArrayValue> Array<T> {
             true_size.extend(&self.shape[true_size.len()..]);
         }
 
-        let mut dst = EcoVec::from_elem(self.data[0].clone(), new_shape.iter().product());
+        let dst_len = validate_size::<T>(new_shape.iter().copied(), env)?;
+        let mut dst = EcoVec::from_elem(self.data[0].clone(), dst_len);
         let dst_slice = dst.make_mut();
         let mut corner = vec![0; self.shape.len()];
         let mut curr = vec![0; self.shape.len()];
         let mut k = 0;
         'windows: loop {
+            env.respect_cancellation()?;
             // Reset curr
             for i in curr.iter_mut() {
                 *i = 0;
@@ -958,13 +1224,109 @@ impl<T: ArrayValue> Array<T> {
             break Ok(Array::new(new_shape, dst));
         }
     }
+    /// Reconstruct an array from its `windows` along the given number of axes
+    ///
+    /// Windows overlap, so each element of the reconstructed array must be
+    /// the same in every window that contains it.
+    fn unwindows(self, axes: usize, env: &Uiua) -> UiuaResult<Self> {
+        if self.rank() < axes * 2 {
+            return Err(env.error(format!(
+                "Cannot invert {axes}-axis windows of an array with shape {}",
+                self.shape()
+            )));
+        }
+        let counts = Shape::from(&self.shape[..axes]);
+        let sizes = Shape::from(&self.shape[axes..axes * 2]);
+        if counts.contains(&0) || sizes.contains(&0) {
+            return Err(env.error(format!(
+                "Cannot invert windows with shape {} because there are no windows",
+                self.shape()
+            )));
+        }
+        let mut shape: Shape = (counts.iter().zip(&sizes))
+            .map(|(c, s)| c + s - 1)
+            .collect();
+        shape.extend_from_slice(&self.shape[axes * 2..]);
+        let cell_len: usize = self.shape[axes * 2..].iter().product();
+        if cell_len == 0 {
+            return Ok(Array::new(shape, CowSlice::new()));
+        }
+        let len = validate_size::<T>(shape.iter().copied(), env)?;
+        let mut data = EcoVec::from_elem(self.data[0].clone(), len);
+        let data_slice = data.make_mut();
+        let mut written = vec![false; len / cell_len];
+        let window_len = sizes.iter().product::<usize>() * cell_len;
+        let mut corner = Vec::with_capacity(axes);
+        let mut item = Vec::with_capacity(axes);
+        for (w, window) in self.data.chunks_exact(window_len).enumerate() {
+            env.respect_cancellation()?;
+            counts.flat_to_dims(w, &mut corner);
+            for (i, cell) in window.chunks_exact(cell_len).enumerate() {
+                sizes.flat_to_dims(i, &mut item);
+                let mut dst = 0;
+                for ((c, i), d) in corner.iter().zip(&item).zip(&shape) {
+                    dst = dst * d + c + i;
+                }
+                let dst_cell = &mut data_slice[dst * cell_len..(dst + 1) * cell_len];
+                if !written[dst] {
+                    dst_cell.clone_from_slice(cell);
+                    written[dst] = true;
+                } else if ArrayCmpSlice(dst_cell) != ArrayCmpSlice(cell) {
+                    return Err(env.error(
+                        "Overlapping windows have different values, \
+                        so the windows cannot be inverted",
+                    ));
+                }
+            }
+        }
+        Ok(Array::new(shape, data))
+    }
+    fn undo_windows(self, size_spec: &[isize], into: Self, env: &Uiua) -> UiuaResult<Self> {
+        if self.data.is_empty() {
+            let orig_shape = into.windows(size_spec, env)?.shape;
+            if self.shape != orig_shape {
+                return Err(env.error(format!(
+                    "Windows' shape was changed from {orig_shape} to {}, \
+                    so the windows cannot be inverted",
+                    self.shape()
+                )));
+            }
+            return Ok(into);
+        }
+        let new = self.unwindows(size_spec.len(), env)?;
+        if new.shape != into.shape {
+            return Err(env.error(format!(
+                "Windows were changed such that they form an array with shape {} \
+                instead of {}, so the windows cannot be inverted",
+                new.shape(),
+                into.shape()
+            )));
+        }
+        Ok(new)
+    }
+}
+
+/// Convert a real array to a complex one if the fill value is complex,
+/// so that the fill can pad it
+fn complex_for_fill<'a>(val: &'a Value, env: &Uiua) -> Cow<'a, Value> {
+    if !matches!(env.value_fill(), Some(Value::Complex(_))) {
+        return Cow::Borrowed(val);
+    }
+    match val {
+        Value::Num(nums) => Cow::Owned(nums.convert_ref::<Complex>().into()),
+        Value::Byte(bytes) => Cow::Owned(bytes.convert_ref::<Complex>().into()),
+        val => Cow::Borrowed(val),
+    }
 }
 
 impl Value {
     /// Try to `find` this value in another
     pub fn find(&self, searched: &Self, env: &Uiua) -> UiuaResult<Self> {
-        self.generic_bin_ref(
-            searched,
+        let searched_for = complex_for_fill(self, env);
+        let searched = complex_for_fill(searched, env);
+        searched_for.generic_bin_ref(
+            &searched,
+            |a, b| a.find(b, env).map(Into::into),
             |a, b| a.find(b, env).map(Into::into),
             |a, b| a.find(b, env).map(Into::into),
             |a, b| a.find(b, env).map(Into::into),
@@ -979,6 +1341,61 @@ impl Value {
             },
         )
     }
+    /// Get the non-overlapping occurrences of this value in another as the rows of an array
+    pub(crate) fn find_matches(&self, haystack: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.generic_bin_ref(
+            haystack,
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| Ok(a.find_matches(b).into()),
+            |a, b| {
+                env.error(format!(
+                    "Cannot find {} in {} array",
+                    a.type_name(),
+                    b.type_name()
+                ))
+            },
+        )
+    }
+    /// Write modified occurrences of this value back into the array they were found in
+    pub(crate) fn undo_find(&self, matches: Self, haystack: Self, env: &Uiua) -> UiuaResult<Self> {
+        let indices = self.generic_bin_ref(
+            &haystack,
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| Ok(a.match_indices(b)),
+            |a, b| {
+                env.error(format!(
+                    "Cannot find {} in {} array",
+                    a.type_name(),
+                    b.type_name()
+                ))
+            },
+        )?;
+        let shape = self.shape();
+        matches.generic_bin_into(
+            haystack,
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| b.undo_find(shape, &indices, a, env).map(Into::into),
+            |a, b| {
+                env.error(format!(
+                    "Cannot write {} back into {} array",
+                    a.type_name_plural(),
+                    b.type_name()
+                ))
+            },
+        )
+    }
     /// Try to `mask` this value in another
     pub fn mask(&self, searched: &Self, env: &Uiua) -> UiuaResult<Self> {
         self.generic_bin_ref(
@@ -988,6 +1405,7 @@ impl Value {
             |a, b| a.mask(b, env).map(Into::into),
             |a, b| a.mask(b, env).map(Into::into),
             |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env),
             |a, b| {
                 env.error(format!(
                     "Cannot mask {} in {} array",
@@ -1011,14 +1429,19 @@ impl<T: ArrayValue> Array<T> {
         if self.rank() > searched.rank() || any_dim_greater {
             // Fill
             match env.scalar_fill() {
-                Ok(fill) => {
+                Ok(fill) if self.rank() <= searched.rank() => {
+                    // Grow each axis that is too small for the searched-for array
                     let mut target_shape = searched.shape.clone();
-                    target_shape[0] = searched_for.row_count();
+                    for (t, &f) in
+                        (target_shape.iter_mut().rev()).zip(searched_for.shape.iter().rev())
+                    {
+                        *t = (*t).max(f);
+                    }
                     local_searched = searched.clone();
                     local_searched.fill_to_shape(&target_shape, fill);
                     searched = &local_searched;
                 }
-                Err(_) => {
+                _ => {
                     let data = cowslice![0; searched.element_count()];
                     let mut arr = Array::new(searched.shape.clone(), data);
                     arr.meta_mut().flags.set(ArrayFlags::BOOLEAN, true);
@@ -1049,6 +1472,7 @@ impl<T: ArrayValue> Array<T> {
 
         if searched.shape.iter().all(|&d| d > 0) {
             'windows: loop {
+                env.respect_cancellation()?;
                 // Reset curr
                 for i in curr.iter_mut() {
                     *i = 0;
@@ -1133,48 +1557,105 @@ impl<T: ArrayValue> Array<T> {
         }
         let mut result_data = eco_vec![0.0; haystack.element_count()];
         let res = result_data.make_mut();
+        let needle_elems = needle.element_count();
+        if needle_elems > 0 {
+            let indices = needle.match_indices(haystack);
+            for (i, region) in indices.chunks_exact(needle_elems).enumerate() {
+                for &k in region {
+                    res[k] = (i + 1) as f64;
+                }
+            }
+        }
+        let mut val: Value = Array::new(haystack.shape.clone(), result_data).into();
+        val.compress();
+        Ok(val)
+    }
+    /// Get the flat indices of the non-overlapping occurrences of this array in another
+    ///
+    /// The indices of each occurrence are contiguous and in row-major order.
+    fn match_indices(&self, haystack: &Self) -> Vec<usize> {
+        let needle = self;
+        let mut indices = Vec::new();
+        if needle.rank() > haystack.rank()
+            || (needle.shape.iter().rev())
+                .zip(haystack.shape.iter().rev())
+                .any(|(n, h)| n > h)
+        {
+            return indices;
+        }
         let needle_data = needle.data.as_slice();
         let mut needle_shape = needle.shape.clone();
         while needle_shape.len() < haystack.shape.len() {
             needle_shape.insert(0, 1);
         }
-        let needle_elems = needle.element_count();
+        let mut taken = vec![false; haystack.element_count()];
         let mut curr = Vec::new();
         let mut offset = Vec::new();
         let mut sum = vec![0; needle_shape.len()];
-        let mut match_num = 0u64;
-        for i in 0..res.len() {
+        for i in 0..taken.len() {
             // Check if the needle matches the haystack at the current index
             haystack.shape.flat_to_dims(i, &mut curr);
-            let mut matches = true;
-            for j in 0..needle_elems {
+            let start = indices.len();
+            for (j, elem) in needle_data.iter().enumerate() {
                 needle_shape.flat_to_dims(j, &mut offset);
                 for ((c, o), s) in curr.iter().zip(&offset).zip(&mut sum) {
                     *s = *c + *o;
                 }
-                if (haystack.shape.dims_to_flat(&sum)).map_or(true, |k| {
-                    res[k] > 0.0 || !needle_data[j].array_eq(&haystack.data[k])
-                }) {
-                    matches = false;
-                    break;
-                }
-            }
-            // Fill matches
-            if matches {
-                match_num += 1;
-                for j in 0..needle_elems {
-                    needle_shape.flat_to_dims(j, &mut offset);
-                    for ((c, o), s) in curr.iter().zip(&offset).zip(&mut sum) {
-                        *s = *c + *o;
+                match haystack.shape.dims_to_flat(&sum) {
+                    Some(k) if !taken[k] && elem.array_eq(&haystack.data[k]) => indices.push(k),
+                    _ => {
+                        indices.truncate(start);
+                        break;
                     }
-                    let k = haystack.shape.dims_to_flat(&sum).unwrap();
-                    res[k] = match_num as f64;
                 }
             }
+            for &k in &indices[start..] {
+                taken[k] = true;
+            }
         }
-        let mut val: Value = Array::new(haystack.shape.clone(), result_data).into();
-        val.compress();
-        Ok(val)
+        indices
+    }
+    fn find_matches(&self, haystack: &Self) -> Self {
+        let indices = self.match_indices(haystack);
+        let mut shape = self.shape.clone();
+        shape.insert(
+            0,
+            indices.len().checked_div(self.element_count()).unwrap_or(0),
+        );
+        let data: EcoVec<T> = indices.iter().map(|&k| haystack.data[k].clone()).collect();
+        Array::new(shape, data)
+    }
+    fn undo_find(
+        mut self,
+        needle_shape: &Shape,
+        indices: &[usize],
+        matches: Self,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        let count = indices
+            .len()
+            .checked_div(needle_shape.elements())
+            .unwrap_or(0);
+        if matches.rank() == 0 || matches.row_count() != count {
+            return Err(env.error(format!(
+                "Number of found occurrences was changed from {count} to {}, \
+                so the find cannot be inverted",
+                matches.row_count()
+            )));
+        }
+        if matches.shape[1..] != needle_shape[..] {
+            return Err(env.error(format!(
+                "Found occurrences' shape was changed from {needle_shape} to {}, \
+                so the find cannot be inverted",
+                Shape::from(&matches.shape[1..])
+            )));
+        }
+        let data = self.data.as_mut_slice();
+        for (&k, elem) in indices.iter().zip(&matches.data) {
+            data[k] = elem.clone();
+        }
+        self.reset_meta_flags();
+        Ok(self)
     }
 }
 
@@ -1188,6 +1669,7 @@ impl Value {
             |a, b| a.member(b, env).map(Into::into),
             |a, b| a.member(b, env).map(Into::into),
             |a, b| a.member(b, env).map(Into::into),
+            |a, b| a.member(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for members of {} array in {} array",
@@ -1256,6 +1738,7 @@ impl Value {
             |a, b| a.index_of(b, env).map(Into::into),
             |a, b| a.index_of(b, env).map(Into::into),
             |a, b| a.index_of(b, env).map(Into::into),
+            |a, b| a.index_of(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for indices of {} array in {} array",
@@ -1274,6 +1757,7 @@ impl Value {
             |a, b| a.coordinate(b, env).map(Into::into),
             |a, b| a.coordinate(b, env).map(Into::into),
             |a, b| a.coordinate(b, env).map(Into::into),
+            |a, b| a.coordinate(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for coordinates of {} array in {} array",
@@ -1292,6 +1776,7 @@ impl Value {
             |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| a.progressive_index_of(b, env).map(Into::into),
+            |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for indices of {} array in {} array",
@@ -1323,7 +1808,7 @@ impl<T: ArrayValue> Array<T> {
                     );
                 }
                 let shape: Shape = self.shape.iter().cloned().take(1).collect();
-                Array::new(shape, result_data)
+                Array::new(shape, result_data).integers()
             }
             Ordering::Greater => {
                 let mut rows = Vec::with_capacity(needle.row_count());
@@ -1492,7 +1977,10 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
-impl Array<f64> {
+impl<T> Array<T>
+where
+    T: ArrayValue + Copy + Add<Output = T> + Mul<Output = T>,
+{
     pub(crate) fn matrix_mul(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
         let (a, b) = (self, other);
         let a_row_shape = a.shape().row();
@@ -1511,12 +1999,16 @@ impl Array<f64> {
         };
         let prod_row_shape = prod_shape.row();
         let prod_elems = prod_row_shape.elements();
-        let mut result_data = eco_vec![0.0; self.row_count() * other.row_count() * prod_elems];
+        let mut result_data =
+            eco_vec![T::default(); self.row_count() * other.row_count() * prod_elems];
         let result_slice = result_data.make_mut();
         let mut result_shape = Shape::from([a.row_count(), b.row_count()]);
         result_shape.extend(prod_row_shape.iter().copied());
-        let inner = |a_row: &[f64], res_row: &mut [f64]| {
-            let mut prod_row = vec![0.0; prod_shape.elements()];
+        let inner = |a_row: &[T], res_row: &mut [T]| {
+            if env.rt.cancel.is_cancelled() {
+                return;
+            }
+            let mut prod_row = vec![T::default(); prod_shape.elements()];
             let mut i = 0;
             for b_row in b.row_slices() {
                 _ = bin_pervade_recursive(
@@ -1524,12 +2016,12 @@ impl Array<f64> {
                     &(&*b_row_shape, b_row),
                     &mut prod_row,
                     env,
-                    InfalliblePervasiveFn::new(pervade::mul::num_num),
+                    InfalliblePervasiveFn::new(|a: T, b: T| b * a),
                 );
                 let (sum, rest) = prod_row.split_at_mut(prod_elems);
                 for chunk in rest.chunks_exact(prod_elems) {
                     for (a, b) in sum.iter_mut().zip(chunk.iter()) {
-                        *a += *b;
+                        *a = *a + *b;
                     }
                 }
                 res_row[i..i + prod_elems].copy_from_slice(sum);
@@ -1542,6 +2034,7 @@ impl Array<f64> {
         } else {
             iter.for_each(|(a_row, res_row)| inner(a_row, res_row));
         }
+        env.respect_cancellation()?;
         Ok(Array::new(result_shape, result_data))
     }
 }
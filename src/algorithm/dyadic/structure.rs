@@ -8,9 +8,9 @@ use std::{
 
 use ecow::EcoVec;
 
-use crate::algorithm::{op_bytes_ref_retry_fill, op_bytes_retry_fill};
+use crate::algorithm::{op_bytes_ref_retry_fill, op_bytes_retry_fill, op_nums_retry_complex_fill};
 use crate::{
-    algorithm::FillContext,
+    algorithm::{padding::resolve_index, FillContext},
     cowslice::{cowslice, CowSlice},
     Array, ArrayValue, FormatShape, Primitive, Shape, Uiua, UiuaResult, Value,
 };
@@ -27,6 +27,26 @@ impl<T: Clone> Array<T> {
         self.data.truncate(new_len);
         self.shape[0] -= 1;
     }
+    /// Remove the rows at the given sorted, deduplicated indices
+    pub(crate) fn remove_rows(&mut self, indices: &[usize]) {
+        let row_len = self.row_len();
+        let data = self.data.as_mut_slice();
+        let mut removed = indices.iter().peekable();
+        let mut kept = 0;
+        for i in 0..self.shape[0] {
+            if removed.next_if_eq(&&i).is_some() {
+                continue;
+            }
+            if kept != i {
+                for j in 0..row_len {
+                    data[kept * row_len + j] = data[i * row_len + j].clone();
+                }
+            }
+            kept += 1;
+        }
+        self.data.truncate(kept * row_len);
+        self.shape[0] = kept;
+    }
     pub(crate) fn set_row(&mut self, index: usize, row: Self) {
         let row_len = row.row_len();
         let start = index * row_len;
@@ -50,6 +70,13 @@ impl<T: Clone> Array<T> {
 impl Value {
     pub(crate) fn as_shaped_indices(&self, env: &Uiua) -> UiuaResult<(&[usize], Vec<isize>)> {
         Ok(match self {
+            Value::Num(arr) if arr.meta().flags.is_integer() => {
+                debug_assert!(
+                    arr.data.iter().all(|n| n.fract() == 0.0),
+                    "Array flagged as integers has non-integer elements"
+                );
+                (&arr.shape, arr.data.iter().map(|&n| n as isize).collect())
+            }
             Value::Num(arr) => {
                 let mut index_data = Vec::with_capacity(arr.element_count());
                 for &n in &arr.data {
@@ -90,6 +117,7 @@ impl Value {
             Value::Complex(a) => Value::Complex(a.pick(index_shape, &index_data, env)?),
             Value::Char(a) => Value::Char(a.pick(index_shape, &index_data, env)?),
             Value::Box(a) => Value::Box(a.pick(index_shape, &index_data, env)?),
+            Value::Time(a) => Value::Time(a.pick(index_shape, &index_data, env)?),
         })
     }
     pub(crate) fn undo_pick(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -139,6 +167,7 @@ impl Value {
             |a, b| a.undo_pick(idx_shape, &index_data, b, env).map(Into::into),
             |a, b| a.undo_pick(idx_shape, &index_data, b, env).map(Into::into),
             |a, b| a.undo_pick(idx_shape, &index_data, b, env).map(Into::into),
+            |a, b| a.undo_pick(idx_shape, &index_data, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot unpick {} array from {} array",
@@ -200,10 +229,10 @@ impl<T: ArrayValue> Array<T> {
             )));
         }
         let mut picked = self.data.clone();
+        let modes = env.pad_modes();
         for (d, (&s, &i)) in self.shape.iter().zip(index).enumerate() {
             let row_len: usize = self.shape[d + 1..].iter().product();
-            let s = s as isize;
-            if i >= s || i < -s {
+            let Some(i) = resolve_index(modes.as_deref(), d, i, s) else {
                 match env.scalar_fill::<T>() {
                     Ok(fill) => {
                         picked = cowslice![fill; row_len];
@@ -218,8 +247,7 @@ impl<T: ArrayValue> Array<T> {
                             .fill());
                     }
                 }
-            }
-            let i = if i >= 0 { i as usize } else { (s + i) as usize };
+            };
             let start = i * row_len;
             let end = start + row_len;
             picked = picked.slice(start..end);
@@ -315,16 +343,23 @@ impl Value {
             return Err(env.error("Cannot take from scalar"));
         }
         let index = self.as_ints_or_infs(env, "Index must be a list of integers or infinity")?;
-        Ok(match from {
-            Value::Num(a) => Value::Num(a.take(&index, env)?),
-            Value::Byte(a) => op_bytes_retry_fill(
+        let take_nums = |a: Array<f64>| {
+            op_nums_retry_complex_fill(
                 a,
+                env,
                 |a| a.take(&index, env).map(Into::into),
                 |a| a.take(&index, env).map(Into::into),
-            )?,
+            )
+        };
+        Ok(match from {
+            Value::Num(a) => take_nums(a)?,
+            Value::Byte(a) => {
+                op_bytes_retry_fill(a, |a| a.take(&index, env).map(Into::into), take_nums)?
+            }
             Value::Complex(a) => Value::Complex(a.take(&index, env)?),
             Value::Char(a) => Value::Char(a.take(&index, env)?),
             Value::Box(a) => Value::Box(a.take(&index, env)?),
+            Value::Time(a) => Value::Time(a.take(&index, env)?),
         })
     }
     /// Use this value to `drop` from another
@@ -336,6 +371,7 @@ impl Value {
             Value::Complex(a) => Value::Complex(a.drop(&index, env)?),
             Value::Char(a) => Value::Char(a.drop(&index, env)?),
             Value::Box(a) => Value::Box(a.drop(&index, env)?),
+            Value::Time(a) => Value::Time(a.drop(&index, env)?),
         })
     }
     pub(crate) fn undo_take(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -358,6 +394,7 @@ impl Value {
             |a, b| a.undo_take(&index, b, env).map(Into::into),
             |a, b| a.undo_take(&index, b, env).map(Into::into),
             |a, b| a.undo_take(&index, b, env).map(Into::into),
+            |a, b| a.undo_take(&index, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot untake {} into {}",
@@ -387,6 +424,7 @@ impl Value {
             |a, b| a.undo_drop(&index, b, env).map(Into::into),
             |a, b| a.undo_drop(&index, b, env).map(Into::into),
             |a, b| a.undo_drop(&index, b, env).map(Into::into),
+            |a, b| a.undo_drop(&index, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot undo drop {} into {}",
@@ -471,6 +509,9 @@ impl<T: ArrayValue> Array<T> {
                 } else if filled {
                     self.shape.push(abs_taking);
                 }
+                if filled {
+                    self.reset_meta_flags();
+                }
                 self.validate_shape();
                 self
             }
@@ -561,6 +602,7 @@ impl<T: ArrayValue> Array<T> {
                     }
                     arr
                 };
+                arr.reset_meta_flags();
                 arr.shape = self.shape;
                 for (i, s) in index.iter().zip(&mut *arr.shape) {
                     *s = i.map_or(*s, isize::unsigned_abs);
@@ -769,6 +811,7 @@ impl Value {
             Value::Complex(a) => a.select(indices_shape, &indices_data, env)?.into(),
             Value::Char(a) => a.select(indices_shape, &indices_data, env)?.into(),
             Value::Box(a) => a.select(indices_shape, &indices_data, env)?.into(),
+            Value::Time(a) => a.select(indices_shape, &indices_data, env)?.into(),
         })
     }
     pub(crate) fn undo_select(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -803,6 +846,7 @@ impl Value {
             |a, b| a.undo_select_impl(idx_shape, &ind, b, env).map(Into::into),
             |a, b| a.undo_select_impl(idx_shape, &ind, b, env).map(Into::into),
             |a, b| a.undo_select_impl(idx_shape, &ind, b, env).map(Into::into),
+            |a, b| a.undo_select_impl(idx_shape, &ind, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot untake {} into {}",
@@ -862,45 +906,23 @@ impl<T: ArrayValue> Array<T> {
         let mut selected = CowSlice::with_capacity(self.row_len() * indices.len());
         let row_len = self.row_len();
         let row_count = self.row_count();
+        let modes = env.pad_modes();
         for &i in indices {
-            let i = if i >= 0 {
-                let ui = i as usize;
-                if ui >= row_count {
-                    match env.scalar_fill::<T>() {
-                        Ok(fill) => {
-                            selected.extend(repeat(fill).take(row_len));
-                            continue;
-                        }
-                        Err(e) => {
-                            return Err(env
-                                .error(format!(
-                                    "Index {} is out of bounds of length {}{e}",
-                                    i, row_count
-                                ))
-                                .fill());
-                        }
+            let Some(i) = resolve_index(modes.as_deref(), 0, i, row_count) else {
+                match env.scalar_fill::<T>() {
+                    Ok(fill) => {
+                        selected.extend(repeat(fill).take(row_len));
+                        continue;
                     }
-                }
-                ui
-            } else {
-                let pos_i = (row_count as isize + i) as usize;
-                if pos_i >= row_count {
-                    match env.scalar_fill::<T>() {
-                        Ok(fill) => {
-                            selected.extend(repeat(fill).take(row_len));
-                            continue;
-                        }
-                        Err(e) => {
-                            return Err(env
-                                .error(format!(
-                                    "Index {} is out of bounds of length {}{e}",
-                                    i, row_count
-                                ))
-                                .fill());
-                        }
+                    Err(e) => {
+                        return Err(env
+                            .error(format!(
+                                "Index {} is out of bounds of length {}{e}",
+                                i, row_count
+                            ))
+                            .fill());
                     }
                 }
-                pos_i
             };
             let start = i * row_len;
             let end = start + row_len;
@@ -1039,3 +1061,118 @@ fn under_select_different_size<T: ArrayValue>(
     }
     Array::from_row_arrays(new_rows, env)
 }
+
+impl Value {
+    /// Use this value to `permute` the rows of another
+    pub(crate) fn permute(&self, mut val: Self, env: &Uiua) -> UiuaResult<Self> {
+        let perm = self.as_permutation(val.row_count(), env)?;
+        val.permute_rows(&perm);
+        Ok(val)
+    }
+    /// Use this value to undo `permute` on the rows of another
+    pub(crate) fn unpermute(&self, mut val: Self, env: &Uiua) -> UiuaResult<Self> {
+        let perm = self.as_permutation(val.row_count(), env)?;
+        let mut inverse = vec![0; perm.len()];
+        for (i, &p) in perm.iter().enumerate() {
+            inverse[p] = i;
+        }
+        val.permute_rows(&inverse);
+        Ok(val)
+    }
+    fn as_permutation(&self, row_count: usize, env: &Uiua) -> UiuaResult<Vec<usize>> {
+        if self.rank() != 1 {
+            return Err(env.error(format!(
+                "Permutation must be a list, but it has shape {}",
+                self.shape()
+            )));
+        }
+        let perm = self.as_nats(env, "Permutation must be a list of natural numbers")?;
+        if perm.len() != row_count {
+            return Err(env.error(format!(
+                "Cannot permute {row_count} rows with a permutation of length {}",
+                perm.len()
+            )));
+        }
+        let mut seen = vec![false; perm.len()];
+        for &i in &perm {
+            if i >= perm.len() {
+                return Err(env.error(format!(
+                    "Index {i} is out of bounds of a permutation of length {}",
+                    perm.len()
+                )));
+            }
+            if replace(&mut seen[i], true) {
+                return Err(env.error(format!("Index {i} appears twice in the permutation")));
+            }
+        }
+        Ok(perm)
+    }
+    /// Permute the rows in place so that row `i` is the old row `perm[i]`
+    ///
+    /// `perm` must be a permutation of the row indices.
+    pub(crate) fn permute_rows(&mut self, perm: &[usize]) {
+        self.take_map_keys();
+        self.generic_mut_shallow(
+            |a| a.permute_rows(perm),
+            |a| a.permute_rows(perm),
+            |a| a.permute_rows(perm),
+            |a| a.permute_rows(perm),
+            |a| a.permute_rows(perm),
+            |a| a.permute_rows(perm),
+        )
+    }
+    /// Swap pairs of rows in place, in order
+    pub(crate) fn swap_rows(&mut self, swaps: &[(usize, usize)]) {
+        self.take_map_keys();
+        self.generic_mut_shallow(
+            |a| a.swap_rows(swaps),
+            |a| a.swap_rows(swaps),
+            |a| a.swap_rows(swaps),
+            |a| a.swap_rows(swaps),
+            |a| a.swap_rows(swaps),
+            |a| a.swap_rows(swaps),
+        )
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    fn swap_row_pair(data: &mut [T], row_len: usize, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        let (lo, hi) = (i.min(j), i.max(j));
+        let (left, right) = data.split_at_mut(hi * row_len);
+        left[lo * row_len..][..row_len].swap_with_slice(&mut right[..row_len]);
+    }
+    /// Permute the rows in place by following each cycle of the permutation
+    pub(crate) fn permute_rows(&mut self, perm: &[usize]) {
+        let row_len = self.row_len();
+        if row_len == 0 {
+            return;
+        }
+        let data = self.data.as_mut_slice();
+        let mut visited = vec![false; perm.len()];
+        for start in 0..perm.len() {
+            let mut i = start;
+            while !replace(&mut visited[i], true) {
+                let j = perm[i];
+                if j == start {
+                    break;
+                }
+                Self::swap_row_pair(data, row_len, i, j);
+                i = j;
+            }
+        }
+    }
+    /// Swap pairs of rows in place, in order
+    pub(crate) fn swap_rows(&mut self, swaps: &[(usize, usize)]) {
+        let row_len = self.row_len();
+        if row_len == 0 {
+            return;
+        }
+        let data = self.data.as_mut_slice();
+        for &(i, j) in swaps {
+            Self::swap_row_pair(data, row_len, i, j);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Einstein summation over lettered axes of numeric arrays
+
+use std::{
+    collections::HashMap,
+    iter::repeat,
+    ops::{Add, Mul},
+};
+
+use ecow::EcoVec;
+
+use crate::{
+    algorithm::validate_size, cowslice::CowSlice, Array, ArrayValue, Boxed, Complex, Shape, Uiua,
+    UiuaResult, Value,
+};
+
+impl Value {
+    /// Evaluate an `einsum` expression
+    ///
+    /// The subscripts look like `"ij,jk->ik"`, with one comma-separated term for each operand.
+    /// If there is no `->`, the output axes are the letters that appear only once, in
+    /// alphabetical order.
+    pub(crate) fn einsum(&self, operands: &Value, env: &Uiua) -> UiuaResult<Value> {
+        let subscripts = self.as_string(env, "Einsum subscripts must be a string")?;
+        let operands: Vec<Value> = match operands {
+            Value::Box(arr) if arr.rank() <= 1 => {
+                arr.data.iter().map(|Boxed(val)| val.clone()).collect()
+            }
+            val => {
+                return Err(env.error(format!(
+                    "Einsum operands must be a list of boxed arrays, \
+                    but they are a {} array of shape {}",
+                    val.type_name(),
+                    val.shape()
+                )))
+            }
+        };
+        let (inputs, output) = parse_subscripts(&subscripts, operands.len(), env)?;
+        if let Some(val) = (operands.iter())
+            .find(|val| matches!(val, Value::Char(_) | Value::Box(_) | Value::Time(_)))
+        {
+            return Err(env.error(format!("Cannot einsum {}", val.type_name_plural())));
+        }
+        Ok(
+            if operands.iter().any(|val| matches!(val, Value::Complex(_))) {
+                let arrays: Vec<Array<Complex>> = (operands.into_iter())
+                    .map(|val| match val {
+                        Value::Num(arr) => arr.convert(),
+                        Value::Byte(arr) => arr.convert(),
+                        Value::Complex(arr) => arr,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                einsum(inputs, arrays, &output, env)?.into()
+            } else {
+                let arrays: Vec<Array<f64>> = (operands.into_iter())
+                    .map(|val| match val {
+                        Value::Num(arr) => arr,
+                        Value::Byte(arr) => arr.convert(),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                einsum(inputs, arrays, &output, env)?.into()
+            },
+        )
+    }
+}
+
+/// Parse einsum subscripts into the letters of each input and of the output
+fn parse_subscripts(
+    subscripts: &str,
+    operand_count: usize,
+    env: &Uiua,
+) -> UiuaResult<(Vec<Vec<char>>, Vec<char>)> {
+    let subscripts: String = subscripts.chars().filter(|c| !c.is_whitespace()).collect();
+    let (inputs, output) = match subscripts.split_once("->") {
+        Some((inputs, output)) => (inputs, Some(output)),
+        None => (subscripts.as_str(), None),
+    };
+    if let Some(c) = (inputs
+        .chars()
+        .chain(output.into_iter().flat_map(str::chars)))
+    .find(|c| *c != ',' && !c.is_ascii_alphabetic())
+    {
+        return Err(env.error(format!(
+            "Einsum subscripts must be letters, but they contain {c:?}"
+        )));
+    }
+    let inputs: Vec<Vec<char>> = inputs
+        .split(',')
+        .map(|term| term.chars().collect())
+        .collect();
+    if inputs.len() != operand_count {
+        return Err(env.error(format!(
+            "Einsum subscripts describe {} operand{}, but there {} {operand_count}",
+            inputs.len(),
+            if inputs.len() == 1 { "" } else { "s" },
+            if operand_count == 1 { "is" } else { "are" }
+        )));
+    }
+    let count = |letter: char| inputs.iter().flatten().filter(|&&l| l == letter).count();
+    let output: Vec<char> = if let Some(output) = output {
+        if output.contains(',') {
+            return Err(env.error("Einsum output must be a single term"));
+        }
+        let output: Vec<char> = output.chars().collect();
+        for (i, &letter) in output.iter().enumerate() {
+            if output[..i].contains(&letter) {
+                return Err(env.error(format!(
+                    "Einsum output axis {letter:?} appears more than once"
+                )));
+            }
+            if count(letter) == 0 {
+                return Err(env.error(format!(
+                    "Einsum output axis {letter:?} does not appear in the inputs"
+                )));
+            }
+        }
+        output
+    } else {
+        let mut output: Vec<char> = (inputs.iter().flatten().copied())
+            .filter(|&l| count(l) == 1)
+            .collect();
+        output.sort_unstable();
+        output
+    };
+    Ok((inputs, output))
+}
+
+/// An array whose axes are labeled with the letters of einsum subscripts
+struct Operand<T> {
+    letters: Vec<char>,
+    arr: Array<T>,
+}
+
+/// Contract operands pairwise, choosing the pair with the smallest result each time
+fn einsum<T>(
+    inputs: Vec<Vec<char>>,
+    arrays: Vec<Array<T>>,
+    output: &[char],
+    env: &Uiua,
+) -> UiuaResult<Array<T>>
+where
+    T: ArrayValue + Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    let mut dims: HashMap<char, usize> = HashMap::new();
+    let mut operands = Vec::with_capacity(arrays.len());
+    for (i, (letters, arr)) in inputs.into_iter().zip(arrays).enumerate() {
+        if letters.len() != arr.rank() {
+            return Err(env.error(format!(
+                "Einsum operand {i} has rank {}, but its subscripts have {} letter{}",
+                arr.rank(),
+                letters.len(),
+                if letters.len() == 1 { "" } else { "s" }
+            )));
+        }
+        for (&letter, &dim) in letters.iter().zip(&*arr.shape) {
+            let expected = *dims.entry(letter).or_insert(dim);
+            if dim != expected {
+                return Err(env.error(format!(
+                    "Einsum axis {letter:?} has length {expected} \
+                    in one place but {dim} in another"
+                )));
+            }
+        }
+        operands.push(Operand::new(letters, arr));
+    }
+    while operands.len() > 1 {
+        let keep = |i: usize, j: usize, letter: char| {
+            output.contains(&letter)
+                || (operands.iter().enumerate())
+                    .any(|(k, op)| k != i && k != j && op.letters.contains(&letter))
+        };
+        let mut best = (usize::MAX, 0, 1);
+        for i in 0..operands.len() {
+            for j in i + 1..operands.len() {
+                let (a, b) = (&operands[i], &operands[j]);
+                let size = (a.letters.iter())
+                    .chain(b.letters.iter().filter(|l| !a.letters.contains(l)))
+                    .filter(|&&l| keep(i, j, l))
+                    .fold(1usize, |size, l| size.saturating_mul(dims[l]));
+                if size < best.0 {
+                    best = (size, i, j);
+                }
+            }
+        }
+        let (_, i, j) = best;
+        let keep: Vec<char> = (operands[i].letters.iter())
+            .chain(&operands[j].letters)
+            .copied()
+            .filter(|&l| keep(i, j, l))
+            .collect();
+        let b = operands.remove(j);
+        let a = operands.remove(i);
+        operands.push(a.contract(b, &keep, &dims, env)?);
+    }
+    operands.pop().unwrap().arrange(output, &dims, env)
+}
+
+impl<T> Operand<T>
+where
+    T: ArrayValue + Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    /// Create an operand, taking the diagonals of axes with repeated letters
+    fn new(letters: Vec<char>, arr: Array<T>) -> Self {
+        let mut unique = Vec::with_capacity(letters.len());
+        for &letter in &letters {
+            if !unique.contains(&letter) {
+                unique.push(letter);
+            }
+        }
+        if unique.len() == letters.len() {
+            return Operand { letters, arr };
+        }
+        let perm: Vec<usize> = (letters.iter())
+            .map(|l| unique.iter().position(|u| u == l).unwrap())
+            .collect();
+        let arr = arr.transpose_axes(&perm, unique.len());
+        Operand {
+            letters: unique,
+            arr,
+        }
+    }
+    /// Move the axes with the given letters to the front in order, summing the others
+    fn arrange(
+        self,
+        order: &[char],
+        dims: &HashMap<char, usize>,
+        env: &Uiua,
+    ) -> UiuaResult<Array<T>> {
+        let mut summed = order.len();
+        let perm: Vec<usize> = (self.letters.iter())
+            .map(|l| {
+                order.iter().position(|o| o == l).unwrap_or_else(|| {
+                    summed += 1;
+                    summed - 1
+                })
+            })
+            .collect();
+        let arr = self.arr.transpose_axes(&perm, summed);
+        let shape: Shape = order.iter().map(|l| dims[l]).collect();
+        let elements = validate_size::<T>(shape.iter().copied(), env)?;
+        let block: usize = arr.shape[order.len()..].iter().product();
+        let data: CowSlice<T> = match block {
+            0 => repeat(T::default()).take(elements).collect(),
+            1 => arr.data,
+            _ => (arr.data.chunks_exact(block))
+                .map(|chunk| chunk.iter().fold(T::default(), |sum, &x| sum + x))
+                .collect(),
+        };
+        Ok(Array::new(shape, data))
+    }
+    /// Contract two operands with batched matrix products
+    ///
+    /// Letters in `keep` are kept in the result. Other letters are summed.
+    fn contract(
+        self,
+        other: Self,
+        keep: &[char],
+        dims: &HashMap<char, usize>,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        let (a, b) = (self, other);
+        let filter = |letters: &[char], f: &dyn Fn(char) -> bool| -> Vec<char> {
+            letters.iter().copied().filter(|&l| f(l)).collect()
+        };
+        let batch = filter(&a.letters, &|l| b.letters.contains(&l) && keep.contains(&l));
+        let summed = filter(&a.letters, &|l| {
+            b.letters.contains(&l) && !keep.contains(&l)
+        });
+        let a_only = filter(&a.letters, &|l| {
+            !b.letters.contains(&l) && keep.contains(&l)
+        });
+        let b_only = filter(&b.letters, &|l| {
+            !a.letters.contains(&l) && keep.contains(&l)
+        });
+        let len = |letters: &[char]| letters.iter().map(|l| dims[l]).product::<usize>();
+        let (batch_len, m, n, k) = (len(&batch), len(&a_only), len(&b_only), len(&summed));
+        let letters = [&batch[..], &a_only, &b_only].concat();
+        let shape: Shape = letters.iter().map(|l| dims[l]).collect();
+        validate_size::<T>(shape.iter().copied(), env)?;
+        let a_arr = a.arrange(&[&batch[..], &a_only, &summed].concat(), dims, env)?;
+        let b_arr = b.arrange(&[&batch[..], &b_only, &summed].concat(), dims, env)?;
+        let mut data = EcoVec::with_capacity(batch_len * m * n);
+        for t in 0..batch_len {
+            if k == 0 {
+                data.extend(repeat(T::default()).take(m * n));
+                continue;
+            }
+            let a_mat = Array::new([m, k], &a_arr.data[t * m * k..(t + 1) * m * k]);
+            let b_mat = Array::new([n, k], &b_arr.data[t * n * k..(t + 1) * n * k]);
+            data.extend_from_slice(&a_mat.matrix_mul(&b_mat, env)?.data);
+        }
+        Ok(Operand {
+            letters,
+            arr: Array::new(shape, data),
+        })
+    }
+}
@@ -0,0 +1,373 @@
+//! Encodings and compression of bytes
+
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+use std::io;
+
+use ecow::EcoVec;
+
+use crate::{Array, Boxed, Uiua, UiuaResult, Value};
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encode bytes as padded base64 characters
+pub(crate) fn base64_encode(bytes: &[u8]) -> EcoVec<char> {
+    let mut s = EcoVec::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+        for i in 0..4 {
+            s.push(if i <= chunk.len() {
+                BASE64_CHARS[(n >> (18 - 6 * i) & 0x3f) as usize] as char
+            } else {
+                '='
+            });
+        }
+    }
+    s
+}
+
+/// Call a function on the bytes of a byte list or the UTF-8 bytes of a string
+fn with_bytes<R>(val: &Value, name: &str, env: &Uiua, f: impl FnOnce(&[u8]) -> R) -> UiuaResult<R> {
+    if val.rank() > 1 {
+        return Err(env.error(format!("Cannot {name} an array of shape {}", val.shape())));
+    }
+    Ok(match val {
+        Value::Byte(arr) => f(&arr.data),
+        Value::Char(arr) => f(arr.data.iter().collect::<String>().as_bytes()),
+        val => f(&val.as_bytes(env, "Only bytes and strings can be encoded")?),
+    })
+}
+
+/// Call a function on the characters of a string
+fn with_chars<R>(
+    val: &Value,
+    name: &str,
+    env: &Uiua,
+    f: impl FnOnce(&[char]) -> UiuaResult<R>,
+) -> UiuaResult<R> {
+    match val {
+        Value::Char(arr) if arr.rank() <= 1 => f(&arr.data),
+        val => Err(env.error(format!(
+            "Cannot {name} decode {} of shape {}",
+            val.type_name_plural(),
+            val.shape()
+        ))),
+    }
+}
+
+impl Value {
+    /// Apply a function to the value, or to each boxed value
+    pub(crate) fn each_boxed(
+        &self,
+        env: &Uiua,
+        f: impl Fn(&Self, &Uiua) -> UiuaResult<Self>,
+    ) -> UiuaResult<Self> {
+        match self {
+            Value::Box(arr) => {
+                let mut arr = arr.clone();
+                for Boxed(val) in arr.data.as_mut_slice() {
+                    *val = f(val, env)?;
+                }
+                Ok(arr.into())
+            }
+            val => f(val, env),
+        }
+    }
+    /// Encode bytes as base64
+    pub(crate) fn base64(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_bytes(val, "base64 encode", env, |bytes| {
+                Array::<char>::from(base64_encode(bytes)).into()
+            })
+        })
+    }
+    /// Decode base64 into bytes
+    pub(crate) fn unbase64(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_chars(val, "base64", env, |chars| {
+                let chars = match chars.iter().position(|&c| c == '=') {
+                    Some(i) if chars[i..].iter().all(|&c| c == '=') && chars.len() % 4 == 0 => {
+                        &chars[..i]
+                    }
+                    _ => chars,
+                };
+                if chars.len() % 4 == 1 {
+                    return Err(env.error("Invalid base64 length"));
+                }
+                let mut bytes = EcoVec::with_capacity(chars.len() * 3 / 4);
+                for chunk in chars.chunks(4) {
+                    let mut n = 0u32;
+                    for (i, &c) in chunk.iter().enumerate() {
+                        let digit = match c {
+                            'A'..='Z' => c as u32 - 'A' as u32,
+                            'a'..='z' => c as u32 - 'a' as u32 + 26,
+                            '0'..='9' => c as u32 - '0' as u32 + 52,
+                            '+' => 62,
+                            '/' => 63,
+                            c => return Err(env.error(format!("Invalid base64 character {c:?}"))),
+                        };
+                        n |= digit << (18 - 6 * i);
+                    }
+                    for i in 0..chunk.len() - 1 {
+                        bytes.push((n >> (16 - 8 * i)) as u8);
+                    }
+                }
+                Ok(Array::<u8>::from(bytes).into())
+            })
+        })
+    }
+    /// Encode bytes as hexadecimal
+    pub(crate) fn hex(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_bytes(val, "hex encode", env, |bytes| {
+                let mut s = EcoVec::with_capacity(bytes.len() * 2);
+                for &b in bytes {
+                    s.push(HEX_CHARS[(b >> 4) as usize] as char);
+                    s.push(HEX_CHARS[(b & 0xf) as usize] as char);
+                }
+                Array::<char>::from(s).into()
+            })
+        })
+    }
+    /// Decode hexadecimal into bytes
+    pub(crate) fn unhex(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_chars(val, "hex", env, |chars| {
+                if chars.len() % 2 != 0 {
+                    return Err(env.error("Hex strings must have an even number of digits"));
+                }
+                let digit = |c: char| {
+                    (c.to_digit(16).map(|d| d as u8))
+                        .ok_or_else(|| env.error(format!("Invalid hex digit {c:?}")))
+                };
+                let mut bytes = EcoVec::with_capacity(chars.len() / 2);
+                for pair in chars.chunks_exact(2) {
+                    bytes.push(digit(pair[0])? << 4 | digit(pair[1])?);
+                }
+                Ok(Array::<u8>::from(bytes).into())
+            })
+        })
+    }
+    /// Percent-encode a string for use in a URL
+    pub(crate) fn url(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_bytes(val, "url encode", env, |bytes| {
+                let mut s = EcoVec::with_capacity(bytes.len());
+                for &b in bytes {
+                    if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+                        s.push(b as char);
+                    } else {
+                        s.push('%');
+                        s.push(HEX_CHARS[(b >> 4) as usize].to_ascii_uppercase() as char);
+                        s.push(HEX_CHARS[(b & 0xf) as usize].to_ascii_uppercase() as char);
+                    }
+                }
+                Array::<char>::from(s).into()
+            })
+        })
+    }
+    /// Decode a percent-encoded string
+    pub(crate) fn unurl(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.each_boxed(env, |val, env| {
+            with_chars(val, "url", env, |chars| {
+                let mut bytes = Vec::with_capacity(chars.len());
+                let mut buf = [0; 4];
+                let mut i = 0;
+                while i < chars.len() {
+                    if chars[i] == '%' {
+                        let byte = (chars.get(i + 1..i + 3))
+                            .and_then(|d| {
+                                Some((d[0].to_digit(16)? << 4 | d[1].to_digit(16)?) as u8)
+                            })
+                            .ok_or_else(|| env.error("Invalid percent-encoding"))?;
+                        bytes.push(byte);
+                        i += 3;
+                    } else {
+                        bytes.extend_from_slice(chars[i].encode_utf8(&mut buf).as_bytes());
+                        i += 1;
+                    }
+                }
+                let s = String::from_utf8(bytes)
+                    .map_err(|_| env.error("Percent-encoded bytes are not valid UTF-8"))?;
+                Ok(s.into())
+            })
+        })
+    }
+}
+
+/// A writer that appends to an [`EcoVec`]
+///
+/// This lets compression write straight into the result array's buffer.
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+struct EcoWriter(EcoVec<u8>);
+
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+impl io::Write for EcoWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A compression format
+#[derive(Clone, Copy)]
+enum Compression {
+    Gzip,
+    Zlib,
+    Deflate,
+    Zstd,
+}
+
+impl Compression {
+    fn from_value(val: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let name = val.as_string(env, "Compression format must be a string")?;
+        Ok(match name.as_str() {
+            "gzip" => Compression::Gzip,
+            "zlib" => Compression::Zlib,
+            "deflate" => Compression::Deflate,
+            "zstd" => Compression::Zstd,
+            name => {
+                return Err(env.error(format!(
+                    "Unknown compression format {name:?}. \
+                    The formats are \"gzip\", \"zlib\", \"deflate\", and \"zstd\"."
+                )))
+            }
+        })
+    }
+}
+
+impl Value {
+    /// Compress bytes
+    pub(crate) fn compress_bytes(&self, data: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let format = Compression::from_value(self, env)?;
+        data.each_boxed(env, |val, env| {
+            let bytes = with_bytes(val, "compress", env, |bytes| compress(format, bytes))?;
+            Ok(Array::<u8>::from(bytes.map_err(|e| env.error(e))?).into())
+        })
+    }
+    /// Decompress bytes
+    pub(crate) fn decompress_bytes(&self, data: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let format = Compression::from_value(self, env)?;
+        data.each_boxed(env, |val, env| {
+            let bytes = match val {
+                Value::Byte(arr) if arr.rank() <= 1 => decompress(format, &arr.data),
+                val => decompress(
+                    format,
+                    &val.as_bytes(env, "Only bytes can be decompressed")?,
+                ),
+            };
+            Ok(Array::<u8>::from(bytes.map_err(|e| env.error(e))?).into())
+        })
+    }
+}
+
+fn compress(format: Compression, bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    match format {
+        Compression::Zstd => zstd_compress(bytes),
+        format => flate_compress(format, bytes),
+    }
+}
+
+fn decompress(format: Compression, bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    match format {
+        Compression::Zstd => zstd_decompress(bytes),
+        format => flate_decompress(format, bytes),
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn flate_compress(format: Compression, bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    use flate2::{write::*, Compression as Level};
+    use io::Write;
+    // Compressed data is usually much smaller than the input
+    let writer = EcoWriter(EcoVec::with_capacity(bytes.len() / 4));
+    let res = match format {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(writer, Level::default());
+            encoder.write_all(bytes).and_then(|_| encoder.finish())
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(writer, Level::default());
+            encoder.write_all(bytes).and_then(|_| encoder.finish())
+        }
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(writer, Level::default());
+            encoder.write_all(bytes).and_then(|_| encoder.finish())
+        }
+        Compression::Zstd => unreachable!("zstd is not a flate format"),
+    };
+    res.map(|writer| writer.0).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "flate2")]
+fn flate_decompress(format: Compression, bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    use flate2::read::*;
+    let mut writer = EcoWriter(EcoVec::with_capacity(bytes.len() * 2));
+    let res = match format {
+        Compression::Gzip => io::copy(&mut MultiGzDecoder::new(bytes), &mut writer),
+        Compression::Zlib => io::copy(&mut ZlibDecoder::new(bytes), &mut writer),
+        Compression::Deflate => io::copy(&mut DeflateDecoder::new(bytes), &mut writer),
+        Compression::Zstd => unreachable!("zstd is not a flate format"),
+    };
+    res.map(|_| writer.0).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "flate2"))]
+fn flate_compress(_: Compression, _: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("Compression is not enabled in this environment".into())
+}
+
+#[cfg(not(feature = "flate2"))]
+fn flate_decompress(_: Compression, _: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("Decompression is not enabled in this environment".into())
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    let mut writer = EcoWriter(EcoVec::with_capacity(bytes.len() / 4));
+    zstd::stream::copy_encode(bytes, &mut writer, zstd::DEFAULT_COMPRESSION_LEVEL)
+        .map(|_| writer.0)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    let mut writer = EcoWriter(EcoVec::with_capacity(bytes.len() * 2));
+    zstd::stream::copy_decode(bytes, &mut writer)
+        .map(|_| writer.0)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("Zstandard compression is not enabled in this environment".into())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("Zstandard decompression is not enabled in this environment".into())
+}
+
+#[cfg(all(test, feature = "zstd"))]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn zstd_compression() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(
+            "# Experimental!\n\
+            ⍤⟜≍: \"abc\" °utf decompress \"zstd\" compress \"zstd\" \"abc\"\n\
+            ⍤⟜≍: 1 <100 ⧻compress \"zstd\" ▽10000 \"abc\"",
+        )
+        .unwrap();
+        assert!(env
+            .run_str("# Experimental!\ndecompress \"zstd\" [1 2 3]")
+            .is_err());
+    }
+}
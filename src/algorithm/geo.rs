@@ -0,0 +1,100 @@
+//! Geospatial algorithms on coordinate arrays
+
+use ecow::EcoVec;
+use rayon::prelude::*;
+
+use crate::{Array, ArrayFlags, Shape, Uiua, UiuaResult, Value};
+
+use super::num_array;
+
+/// Above this many pairs of points, work is done in parallel
+const PAR_THRESHOLD: usize = 10_000;
+
+/// The mean radius of the Earth in kilometers
+const EARTH_RADIUS: f64 = 6371.0088;
+
+/// Get a numeric array of 2D points and the shape of the array of points
+fn points(val: &Value, what: &str, env: &Uiua) -> UiuaResult<(Array<f64>, Shape)> {
+    let arr = num_array(val, what, env)?;
+    if arr.shape.last() != Some(&2) {
+        return Err(env.error(format!(
+            "{what} must have a last axis of length 2, but their shape is {}",
+            arr.shape
+        )));
+    }
+    let mut shape = arr.shape.clone();
+    shape.pop();
+    Ok((arr, shape))
+}
+
+/// The great-circle distance between two `[latitude longitude]` points in degrees
+fn haversine(a: &[f64], b: &[f64]) -> f64 {
+    let (lat1, lon1) = (a[0].to_radians(), a[1].to_radians());
+    let (lat2, lon2) = (b[0].to_radians(), b[1].to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+}
+
+/// Whether a point is inside a polygon, using the even-odd rule
+fn in_polygon(vertices: &[f64], point: &[f64]) -> bool {
+    let (x, y) = (point[0], point[1]);
+    let n = vertices.len() / 2;
+    let mut inside = false;
+    let mut j = n.saturating_sub(1);
+    for i in 0..n {
+        let (xi, yi) = (vertices[2 * i], vertices[2 * i + 1]);
+        let (xj, yj) = (vertices[2 * j], vertices[2 * j + 1]);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl Value {
+    /// Get the `haversine` distances between two arrays of points
+    pub(crate) fn haversine(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let (a, a_shape) = points(self, "Points", env)?;
+        let (b, b_shape) = points(other, "Points", env)?;
+        let b_count = b_shape.elements();
+        let row =
+            |a: &[f64]| -> Vec<f64> { (b.data.chunks_exact(2)).map(|b| haversine(a, b)).collect() };
+        let data: EcoVec<f64> = if a_shape.elements() * b_count > PAR_THRESHOLD {
+            (a.data.par_chunks_exact(2))
+                .flat_map_iter(row)
+                .collect::<Vec<_>>()
+                .into()
+        } else {
+            a.data.chunks_exact(2).flat_map(row).collect()
+        };
+        let mut shape = a_shape;
+        shape.extend_from_slice(&b_shape);
+        Ok(Array::new(shape, data).into())
+    }
+    /// Check which points are `inpolygon`
+    pub(crate) fn in_polygon(&self, points_val: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let (polygon, shape) = points(self, "Polygon vertices", env)?;
+        if shape.len() != 1 {
+            return Err(env.error(format!(
+                "Polygon must be a list of points, but its shape is {}",
+                polygon.shape
+            )));
+        }
+        let (points, shape) = points(points_val, "Points", env)?;
+        let vertices = polygon.data.as_slice();
+        let test = |point: &[f64]| in_polygon(vertices, point) as u8;
+        let data: EcoVec<u8> = if shape.elements() * (vertices.len() / 2) > PAR_THRESHOLD {
+            (points.data.par_chunks_exact(2))
+                .map(test)
+                .collect::<Vec<_>>()
+                .into()
+        } else {
+            points.data.chunks_exact(2).map(test).collect()
+        };
+        let mut arr = Array::new(shape, data);
+        arr.meta_mut().flags.set(ArrayFlags::BOOLEAN, true);
+        Ok(arr.into())
+    }
+}
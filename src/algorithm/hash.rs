@@ -0,0 +1,131 @@
+//! Hash functions and checksums
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use ecow::EcoVec;
+
+use crate::{Array, ArrayValue, Uiua, UiuaResult, Value};
+
+use super::ArrayCmpSlice;
+
+/// A hash function
+#[derive(Clone, Copy)]
+enum HashFunction {
+    Sha256,
+    Md5,
+    Crc32,
+}
+
+impl HashFunction {
+    fn from_value(val: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let name = val.as_string(env, "Hash function must be a string")?;
+        Ok(match name.as_str() {
+            "sha256" => HashFunction::Sha256,
+            "md5" => HashFunction::Md5,
+            "crc32" => HashFunction::Crc32,
+            name => {
+                return Err(env.error(format!(
+                    "Unknown hash function {name:?}. \
+                    The functions are \"sha256\", \"md5\", and \"crc32\"."
+                )))
+            }
+        })
+    }
+    fn digest(self, bytes: &[u8], env: &Uiua) -> UiuaResult<EcoVec<u8>> {
+        match self {
+            HashFunction::Sha256 => sha256(bytes),
+            HashFunction::Md5 => md5(bytes),
+            HashFunction::Crc32 => crc32(bytes),
+        }
+        .map_err(|e| env.error(e))
+    }
+}
+
+impl Value {
+    /// Hash bytes or a string
+    pub(crate) fn hash_bytes(&self, data: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let function = HashFunction::from_value(self, env)?;
+        data.each_boxed(env, |val, env| {
+            let bytes = match val {
+                Value::Byte(arr) if arr.rank() <= 1 => function.digest(&arr.data, env)?,
+                Value::Char(arr) if arr.rank() <= 1 => {
+                    function.digest(arr.data.iter().collect::<String>().as_bytes(), env)?
+                }
+                val => function.digest(
+                    &val.as_bytes(env, "Only bytes and strings can be hashed")?,
+                    env,
+                )?,
+            };
+            Ok(Array::<u8>::from(bytes).into())
+        })
+    }
+    /// Get a non-cryptographic hash of each row
+    ///
+    /// Rows that [`match`](crate::Primitive::Match) have the same hash.
+    pub(crate) fn row_hashes(&self) -> Self {
+        match self {
+            Value::Num(arr) => arr.row_hashes(),
+            Value::Byte(arr) => arr.row_hashes(),
+            Value::Complex(arr) => arr.row_hashes(),
+            Value::Char(arr) => arr.row_hashes(),
+            Value::Box(arr) => arr.row_hashes(),
+            Value::Time(arr) => arr.row_hashes(),
+        }
+        .into()
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    fn row_hashes(&self) -> Array<f64> {
+        let hash = |row: &[T]| {
+            let mut hasher = DefaultHasher::new();
+            ArrayCmpSlice(row).hash(&mut hasher);
+            // Keep the hash exactly representable as a number
+            (hasher.finish() >> 11) as f64
+        };
+        if self.rank() == 0 {
+            return hash(&self.data).into();
+        }
+        let data: EcoVec<f64> = self.row_slices().map(hash).collect();
+        Array::from(data)
+    }
+}
+
+/// The SHA-256 digest of some bytes
+#[cfg(feature = "sha2")]
+fn sha256(bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    use sha2::{Digest, Sha256};
+    Ok(Sha256::digest(bytes).into_iter().collect())
+}
+
+#[cfg(not(feature = "sha2"))]
+fn sha256(_bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("SHA-256 hashing is not enabled in this environment".into())
+}
+
+/// The MD5 digest of some bytes
+///
+/// MD5 is broken for security purposes, but it is still common as a checksum.
+#[cfg(feature = "md5")]
+fn md5(bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    Ok(md5::compute(bytes).0.into_iter().collect())
+}
+
+#[cfg(not(feature = "md5"))]
+fn md5(_bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("MD5 hashing is not enabled in this environment".into())
+}
+
+/// The CRC-32 checksum of some bytes, as used by gzip and PNG
+#[cfg(feature = "crc32fast")]
+fn crc32(bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    Ok(crc32fast::hash(bytes).to_be_bytes().into_iter().collect())
+}
+
+#[cfg(not(feature = "crc32fast"))]
+fn crc32(_bytes: &[u8]) -> Result<EcoVec<u8>, String> {
+    Err("CRC-32 checksums are not enabled in this environment".into())
+}
@@ -0,0 +1,166 @@
+//! Linear and cubic interpolation and resampling
+
+use ecow::EcoVec;
+
+use crate::{Array, Shape, Uiua, UiuaResult, Value};
+
+use super::num_array;
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    if t == 0.0 {
+        a
+    } else {
+        a + (b - a) * t
+    }
+}
+
+/// Interpolate between two points on a cubic curve with the given slopes
+///
+/// `h` is the distance between the points, and `t` is how far between them to go.
+fn hermite(a: f64, b: f64, ma: f64, mb: f64, h: f64, t: f64) -> f64 {
+    if t == 0.0 {
+        return a;
+    }
+    let (t2, t3) = (t * t, t * t * t);
+    (2.0 * t3 - 3.0 * t2 + 1.0) * a
+        + (t3 - 2.0 * t2 + t) * h * ma
+        + (3.0 * t2 - 2.0 * t3) * b
+        + (t3 - t2) * h * mb
+}
+
+/// The slope of a cubic curve at each sample
+///
+/// Each slope is the average of the slopes of the lines to the neighboring samples.
+fn slopes(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let secant = |i: usize| {
+        let h = xs[i + 1] - xs[i];
+        if h == 0.0 {
+            0.0
+        } else {
+            (ys[i + 1] - ys[i]) / h
+        }
+    };
+    (0..n)
+        .map(|i| match (i.checked_sub(1), i + 1 < n) {
+            (None, false) => 0.0,
+            (None, true) => secant(i),
+            (Some(prev), false) => secant(prev),
+            (Some(prev), true) => (secant(prev) + secant(i)) / 2.0,
+        })
+        .collect()
+}
+
+impl Value {
+    /// Interpolate between sample points
+    pub(crate) fn interp(&self, queries: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let samples = num_array(self, "Samples", env)?;
+        if samples.rank() != 2 || samples.shape[0] != 2 {
+            return Err(env.error(format!(
+                "Samples must be a rank 2 array with 2 rows, \
+                but their shape is {}",
+                samples.shape
+            )));
+        }
+        let n = samples.shape[1];
+        if n == 0 {
+            return Err(env.error("Cannot interpolate with no samples"));
+        }
+        let (xs, ys) = samples.data.split_at(n);
+        if let Some(x) = xs.iter().find(|x| x.is_nan()) {
+            return Err(env.error(format!("Sample x values cannot be {x}")));
+        }
+        for (i, win) in xs.windows(2).enumerate() {
+            if win[0] > win[1] {
+                return Err(env.error(format!(
+                    "Sample x values must be sorted ascending, \
+                    but {} at index {} is followed by {}",
+                    win[0], i, win[1]
+                )));
+            }
+        }
+        let slopes = env.cubic().then(|| slopes(xs, ys));
+        let fill = env.num_scalar_fill().ok();
+        let mut queries = num_array(queries, "Queries", env)?;
+        for q in queries.data.as_mut_slice() {
+            *q = if q.is_nan() {
+                f64::NAN
+            } else if *q < xs[0] {
+                fill.unwrap_or(ys[0])
+            } else if *q > xs[n - 1] {
+                fill.unwrap_or(ys[n - 1])
+            } else {
+                // Index of the first sample strictly after the query
+                let i = xs.partition_point(|&x| x <= *q);
+                if i == n {
+                    ys[n - 1]
+                } else {
+                    let (x0, x1) = (xs[i - 1], xs[i]);
+                    let t = (*q - x0) / (x1 - x0);
+                    match &slopes {
+                        Some(m) => hermite(ys[i - 1], ys[i], m[i - 1], m[i], x1 - x0, t),
+                        None => lerp(ys[i - 1], ys[i], t),
+                    }
+                }
+            };
+        }
+        queries.reset_meta_flags();
+        Ok(queries.into())
+    }
+    /// Sample the rows of an array at fractional indices
+    pub(crate) fn resample(&self, from: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let indices = num_array(self, "Indices", env)?;
+        let from = num_array(from, "Resampled array", env)?;
+        if from.rank() == 0 {
+            return Err(env.error("Cannot resample a scalar"));
+        }
+        let row_count = from.row_count();
+        let row_len = from.row_len();
+        let cubic = env.cubic();
+        // The slope of a cubic curve through the rows at an element of a row
+        let slope = |k: usize, j: usize| {
+            let y = |k: usize| from.data[k * row_len + j];
+            if row_count == 1 {
+                0.0
+            } else if k == 0 {
+                y(1) - y(0)
+            } else if k == row_count - 1 {
+                y(k) - y(k - 1)
+            } else {
+                (y(k + 1) - y(k - 1)) / 2.0
+            }
+        };
+        let mut data = EcoVec::with_capacity(indices.element_count() * row_len);
+        for &i in &indices.data {
+            if i.is_nan() || i < 0.0 || i > row_count as f64 - 1.0 {
+                match env.num_scalar_fill() {
+                    Ok(fill) => {
+                        data.extend((0..row_len).map(|_| fill));
+                        continue;
+                    }
+                    Err(e) => {
+                        return Err(env.error(format!(
+                            "Index {i} is out of bounds of length {row_count}{e}"
+                        )))
+                    }
+                }
+            }
+            let lo = i.floor() as usize;
+            let hi = (lo + 1).min(row_count - 1);
+            let t = i - lo as f64;
+            let lo_row = &from.data[lo * row_len..][..row_len];
+            let hi_row = &from.data[hi * row_len..][..row_len];
+            if cubic {
+                data.extend(
+                    (0..row_len)
+                        .map(|j| hermite(lo_row[j], hi_row[j], slope(lo, j), slope(hi, j), 1.0, t)),
+                );
+            } else {
+                data.extend(lo_row.iter().zip(hi_row).map(|(&a, &b)| lerp(a, b, t)));
+            }
+        }
+        let mut shape: Shape = indices.shape.clone();
+        shape.extend_from_slice(&from.shape[1..]);
+        Ok(Array::new(shape, data).into())
+    }
+}
@@ -93,9 +93,16 @@ fn prim_inverse(prim: Primitive, span: usize) -> Option<Instr> {
         Box => Instr::ImplPrim(UnBox, span),
         Where => Instr::ImplPrim(UnWhere, span),
         Utf => Instr::ImplPrim(UnUtf, span),
+        Base64 => Instr::ImplPrim(UnBase64, span),
+        Hex => Instr::ImplPrim(UnHex, span),
+        Url => Instr::ImplPrim(UnUrl, span),
         Parse => Instr::ImplPrim(UnParse, span),
         Fix => Instr::ImplPrim(UnFix, span),
         Map => Instr::ImplPrim(UnMap, span),
+        Axes => Instr::ImplPrim(UnAxes, span),
+        Diag => Instr::ImplPrim(UnDiag, span),
+        Unit => Instr::ImplPrim(UnUnit, span),
+        Timestamp => Instr::ImplPrim(UnTimestamp, span),
         Trace => Instr::ImplPrim(UnTrace, span),
         Stack => Instr::ImplPrim(UnStack, span),
         Join => Instr::ImplPrim(UnJoin, span),
@@ -125,12 +132,19 @@ fn impl_prim_inverse(prim: ImplPrimitive, span: usize) -> Option<Instr> {
         UnBits => Instr::Prim(Bits, span),
         UnWhere => Instr::Prim(Where, span),
         UnUtf => Instr::Prim(Utf, span),
+        UnBase64 => Instr::Prim(Base64, span),
+        UnHex => Instr::Prim(Hex, span),
+        UnUrl => Instr::Prim(Url, span),
         UnAtan => Instr::Prim(Atan, span),
         UnComplex => Instr::Prim(Complex, span),
         UnCouple => Instr::Prim(Couple, span),
         UnParse => Instr::Prim(Parse, span),
         UnFix => Instr::Prim(Fix, span),
         UnMap => Instr::Prim(Map, span),
+        UnAxes => Instr::Prim(Axes, span),
+        UnDiag => Instr::Prim(Diag, span),
+        UnUnit => Instr::Prim(Unit, span),
+        UnTimestamp => Instr::Prim(Timestamp, span),
         UnTrace => Instr::Prim(Trace, span),
         UnStack => Instr::Prim(Stack, span),
         UnJoin => Instr::Prim(Join, span),
@@ -194,6 +208,9 @@ static INVERT_PATTERNS: &[&dyn InvertPattern] = {
         &InvertPatternFn(invert_select_pattern, "select"),
         &(Val, InvertPatternFn(invert_repeat_pattern, "repeat")),
         &(Val, ([Rotate], [Neg, Rotate])),
+        &(Val, ([Permute], [UnPermute])),
+        &(Val, ([UnPermute], [Permute])),
+        &(Val, pat!(Windows, (UnWindows))),
         &pat!(Sqrt, (Dup, Mul)),
         &(Val, IgnoreMany(Flip), ([Add], [Sub])),
         &(Val, ([Sub], [Add])),
@@ -224,6 +241,8 @@ static PSEUDO_INVERT_PATTERNS: &[&dyn InvertPattern] = {
         &([Div], [Mul]),
         &([Rotate], [Neg, Rotate]),
         &([Neg, Rotate], [Rotate]),
+        &([Permute], [UnPermute]),
+        &([UnPermute], [Permute]),
         &([Min], [Min]),
         &([Max], [Max]),
         &pat!(
@@ -460,7 +479,31 @@ pub(crate) fn under_instrs(
             (CopyToUnder(2), Keep),
             (PopUnder(1), Flip, PopUnder(1), UndoKeep),
         )),
-        &stash1!(Rotate, (Neg, Rotate)),
+        &maybe_val!(pat!(
+            Rotate,
+            (CopyToUnder(2), Rotate),
+            (PopUnder(1), Flip, PopUnder(1), UndoRotate),
+        )),
+        &maybe_val!(pat!(
+            Permute,
+            (CopyToUnder(1), Permute),
+            (PopUnder(1), UnPermute),
+        )),
+        &pat!(
+            Shuffle,
+            (Dup, Len, Permutation, CopyToUnder(1), Permute),
+            (PopUnder(1), UnPermute),
+        ),
+        &maybe_val!(pat!(
+            Windows,
+            (CopyToUnder(2), Windows),
+            (PopUnder(1), Flip, PopUnder(1), UndoWindows),
+        )),
+        &maybe_val!(pat!(
+            Find,
+            (CopyToUnder(2), FindMatches),
+            (PopUnder(1), Flip, PopUnder(1), UndoFind),
+        )),
         &maybe_val!(pat!(
             Join,
             (Over, Shape, Over, Shape, PushToUnder(2), Join),
@@ -488,6 +531,7 @@ pub(crate) fn under_instrs(
         &stash1!(Last, UndoLast),
         &maybe_val!(stash2!(Pick, UndoPick)),
         &maybe_val!(stash2!(Select, UndoSelect)),
+        &maybe_val!(stash2!(Diagonal, UndoDiagonal)),
         // Map control
         &maybe_val!(pat!(
             Get,
@@ -500,6 +544,12 @@ pub(crate) fn under_instrs(
             (CopyToUnder(3), Insert),
             (PopUnder(3), UndoInsert)
         )),
+        // Record fields
+        &maybe_val!(pat!(
+            Field,
+            (CopyToUnder(2), Field),
+            (PopUnder(1), Flip, PopUnder(1), SetField),
+        )),
         // Shaping
         &pat!(Fix, (Fix), (UndoFix)),
         &pat!(UndoFix, (UndoFix), (Fix)),
@@ -516,8 +566,8 @@ pub(crate) fn under_instrs(
         )),
         &maybe_val!(pat!(
             Reshape,
-            (Over, Shape, PushToUnder(1), Reshape),
-            (PopUnder(1), UndoReshape),
+            (Over, Shape, Over, PushToUnder(2), Reshape),
+            (PopUnder(2), UndoReshape),
         )),
         // Classify and deduplicate
         &pat!(
@@ -527,11 +577,16 @@ pub(crate) fn under_instrs(
         ),
         &pat!(
             Deduplicate,
-            (Dup, Classify, PushToUnder(1), Deduplicate),
+            (DeduplicateInverse, PushToUnder(1)),
             (PopUnder(1), Select),
         ),
         // System stuff
         &pat!(Now, (Now, PushToUnder(1)), (PopUnder(1), Now, Flip, Sub)),
+        &pat!(
+            Clock,
+            (Clock, PushToUnder(1)),
+            (PopUnder(1), Clock, Flip, Sub)
+        ),
         &maybe_val!(store1copy!(Sys(SysOp::FOpen), Sys(SysOp::Close))),
         &maybe_val!(store1copy!(Sys(SysOp::FCreate), Sys(SysOp::Close))),
         &maybe_val!(store1copy!(Sys(SysOp::RunStream), Sys(SysOp::Close))),
@@ -540,6 +595,7 @@ pub(crate) fn under_instrs(
         &maybe_val!(store1copy!(Sys(SysOp::TcpAccept), Sys(SysOp::Close))),
         &maybe_val!(store1copy!(Sys(SysOp::TcpListen), Sys(SysOp::Close))),
         &maybe_val!(store1copy!(Sys(SysOp::TlsListen), Sys(SysOp::Close))),
+        &maybe_val!(store1copy!(Sys(SysOp::UdpBind), Sys(SysOp::Close))),
         &maybe_val!(stash1!(Sys(SysOp::FReadAllStr), Sys(SysOp::FWriteAll))),
         &maybe_val!(stash1!(Sys(SysOp::FReadAllBytes), Sys(SysOp::FWriteAll))),
         &pat!(BothTrace, (BothTrace), (UnTrace)),
@@ -0,0 +1,242 @@
+//! Fills whose values are computed by a function
+
+use ecow::EcoVec;
+
+use crate::{
+    algorithm::num_array,
+    array::{Array, ArrayValue},
+    value::Value,
+    Function, FunctionId, Primitive, Shape, Uiua, UiuaResult,
+};
+
+/// Call a function with a fill function set
+pub fn fill_by(env: &mut Uiua) -> UiuaResult {
+    let fill = env.pop_function()?;
+    let f = env.pop_function()?;
+    let sig = fill.signature();
+    if sig.args > 2 || sig.outputs != 1 {
+        return Err(env.error(format!(
+            "{}'s fill function must take at most 2 arguments and return 1 value, \
+            but its signature is {sig}",
+            Primitive::FillBy.format()
+        )));
+    }
+    env.with_fill_by(fill, |env| {
+        if matches!(f.id, FunctionId::Named(_)) {
+            env.use_fill();
+        }
+        env.call(f)
+    })
+}
+
+pub fn take(fill: Function, env: &mut Uiua) -> UiuaResult {
+    let index = env.pop(1)?;
+    let from = env.pop(2)?;
+    let taken = lazy_filled(from, fill, env, |from, env| index.take(from, env))?;
+    env.push(taken);
+    Ok(())
+}
+
+pub fn rotate(fill: Function, env: &mut Uiua) -> UiuaResult {
+    let by = env.pop(1)?;
+    let rotated = env.pop(2)?;
+    let rotated = lazy_filled(rotated, fill, env, |rotated, env| by.rotate(rotated, env))?;
+    env.push(rotated);
+    Ok(())
+}
+
+pub fn reshape(fill: Function, env: &mut Uiua) -> UiuaResult {
+    let shape = env.pop(1)?;
+    let array = env.pop(2)?;
+    let reshaped = lazy_filled(array, fill, env, |mut array, env| {
+        array.reshape(&shape, env)?;
+        Ok(array)
+    })?;
+    env.push(reshaped);
+    Ok(())
+}
+
+pub fn find(fill: Function, env: &mut Uiua) -> UiuaResult {
+    let needle = env.pop(1)?;
+    let mut haystack = env.pop(2)?;
+    let any_dim_greater = (needle.shape().iter().rev())
+        .zip(haystack.shape().iter().rev())
+        .any(|(n, h)| n > h);
+    if needle.rank() <= haystack.rank() && any_dim_greater {
+        // Grow each axis that is too small for the needle
+        let mut target_shape = haystack.shape().clone();
+        for (t, &n) in (target_shape.iter_mut().rev()).zip(needle.shape().iter().rev()) {
+            *t = (*t).max(n);
+        }
+        haystack = lazy_filled(haystack, fill, env, |indices, env| {
+            let mut indices = num_array(&indices, "Indices", env)?;
+            indices.fill_to_shape(&target_shape, f64::NAN);
+            Ok(indices.into())
+        })?;
+    }
+    let found = env.without_fill(|env| needle.find(&haystack, env))?;
+    env.push(found);
+    Ok(())
+}
+
+/// Apply a structural operation to a value, calling the fill function for each filled element
+///
+/// The operation is applied to an array of the value's flat indices with a `NaN` fill,
+/// so filled elements can be told apart from moved ones.
+fn lazy_filled(
+    val: Value,
+    fill: Function,
+    env: &mut Uiua,
+    op: impl FnOnce(Value, &Uiua) -> UiuaResult<Value>,
+) -> UiuaResult<Value> {
+    let indices: EcoVec<f64> = (0..val.element_count()).map(|i| i as f64).collect();
+    let indices = Array::new(val.shape().clone(), indices);
+    let indices = env.with_fill(f64::NAN.into(), |env| op(indices.into(), env))?;
+    let indices = num_array(&indices, "Indices", env)?;
+    let filled: Vec<usize> = (indices.data.iter().enumerate())
+        .filter(|(_, i)| i.is_nan())
+        .map(|(i, _)| i)
+        .collect();
+    let gathered = match &val {
+        Value::Num(arr) => gather(arr, &indices).into(),
+        Value::Byte(arr) => gather(arr, &indices).into(),
+        Value::Complex(arr) => gather(arr, &indices).into(),
+        Value::Char(arr) => gather(arr, &indices).into(),
+        Value::Box(arr) => gather(arr, &indices).into(),
+        Value::Time(arr) => gather(arr, &indices).into(),
+    };
+    if filled.is_empty() {
+        return Ok(gathered);
+    }
+    // Compute the fill values
+    let args = fill.signature().args;
+    let edges = if args >= 2 {
+        Some(Edges::new(&indices, &gathered))
+    } else {
+        None
+    };
+    let mut fill_values = Vec::with_capacity(filled.len());
+    let mut index = Vec::new();
+    env.without_fill(|env| -> UiuaResult {
+        for &i in &filled {
+            if let Some(edges) = &edges {
+                let edge = edges.nearest(i, &indices).ok_or_else(|| {
+                    env.error("Cannot fill with an edge value because there are no elements")
+                })?;
+                env.push(edge);
+            }
+            if args >= 1 {
+                indices.shape.flat_to_dims(i, &mut index);
+                env.push(index.iter().map(|&i| i as f64).collect::<Value>());
+            }
+            env.call(fill.clone())?;
+            let value = env.pop("fill value")?;
+            if value.rank() != 0 {
+                return Err(env.error(format!(
+                    "{}'s fill function must return a scalar, \
+                    but it returned an array of shape {}",
+                    Primitive::FillBy.format(),
+                    value.shape()
+                )));
+            }
+            fill_values.push(value);
+        }
+        Ok(())
+    })?;
+    let fill_values = Value::from_row_values(fill_values, env)?;
+    if filled.len() == indices.data.len() {
+        let mut filled = fill_values;
+        *filled.shape_mut() = indices.shape;
+        filled.validate_shape();
+        return Ok(filled);
+    }
+    gathered.generic_bin_into(
+        fill_values,
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| Ok(scatter(a, &filled, b).into()),
+        |a, b| {
+            env.error(format!(
+                "Cannot fill {} array with {}",
+                a.type_name(),
+                b.type_name_plural()
+            ))
+        },
+    )
+}
+
+/// Get the elements at the given flat indices, with placeholders for `NaN` indices
+fn gather<T: ArrayValue>(arr: &Array<T>, indices: &Array<f64>) -> Array<T> {
+    let placeholder = arr.data.first().cloned().unwrap_or_else(T::proxy);
+    let data: EcoVec<T> = (indices.data.iter())
+        .map(|&i| {
+            if i.is_nan() {
+                placeholder.clone()
+            } else {
+                arr.data[i as usize].clone()
+            }
+        })
+        .collect();
+    Array::new(indices.shape.clone(), data)
+}
+
+fn scatter<T: ArrayValue>(mut arr: Array<T>, positions: &[usize], values: Array<T>) -> Array<T> {
+    let data = arr.data.as_mut_slice();
+    for (&i, value) in positions.iter().zip(values.data) {
+        data[i] = value;
+    }
+    arr.reset_meta_flags();
+    arr
+}
+
+/// Finds the nearest unfilled element to a filled one
+struct Edges {
+    /// The unfilled elements, flattened
+    elements: Value,
+    /// The bounds of the unfilled elements along each axis
+    bounds: Vec<(usize, usize)>,
+}
+
+impl Edges {
+    fn new(indices: &Array<f64>, elements: &Value) -> Self {
+        let mut bounds = vec![(usize::MAX, 0); indices.rank()];
+        let mut index = Vec::new();
+        for (i, n) in indices.data.iter().enumerate() {
+            if n.is_nan() {
+                continue;
+            }
+            indices.shape.flat_to_dims(i, &mut index);
+            for (&d, (min, max)) in index.iter().zip(&mut bounds) {
+                *min = (*min).min(d);
+                *max = (*max).max(d);
+            }
+        }
+        let mut elements = elements.clone();
+        elements.deshape();
+        Edges { elements, bounds }
+    }
+    /// Get the unfilled element nearest to the filled element at the given flat index
+    ///
+    /// The element's index is clamped to the bounds of the unfilled elements.
+    /// If that element is also filled, the nearest unfilled element in flat order is used.
+    fn nearest(&self, i: usize, indices: &Array<f64>) -> Option<Value> {
+        if self.bounds.iter().any(|&(min, max)| min > max) {
+            return None;
+        }
+        let mut index = Vec::new();
+        indices.shape.flat_to_dims(i, &mut index);
+        for (d, &(min, max)) in index.iter_mut().zip(&self.bounds) {
+            *d = (*d).clamp(min, max);
+        }
+        let clamped = Shape::from(indices.shape.dims()).dims_to_flat(&index)?;
+        let is_unfilled = |&j: &usize| !indices.data[j].is_nan();
+        let nearest = Some(clamped)
+            .filter(is_unfilled)
+            .or_else(|| (0..i).rev().find(is_unfilled))
+            .or_else(|| (i..indices.data.len()).find(is_unfilled))?;
+        Some(self.elements.row(nearest))
+    }
+}
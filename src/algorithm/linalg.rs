@@ -0,0 +1,325 @@
+//! Matrix decompositions and triangular and banded matrices
+
+use ecow::EcoVec;
+use rayon::prelude::*;
+
+use crate::{
+    algorithm::{num_array, FillContext},
+    Array, ArrayValue, Complex, Shape, Uiua, UiuaResult, Value,
+};
+
+/// Above this many matrix elements, row updates are done in parallel
+const PAR_THRESHOLD: usize = 10_000;
+
+/// The most sweeps of Jacobi rotations to do before giving up
+const MAX_SWEEPS: usize = 100;
+
+/// A row-major matrix
+struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    fn from_value(val: &Value, prim: &str, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(val, "Matrices", env)?;
+        if arr.rank() != 2 {
+            return Err(env.error(format!(
+                "Cannot {prim} a rank {} array. It must be a matrix.",
+                arr.rank()
+            )));
+        }
+        Ok(Matrix {
+            rows: arr.shape[0],
+            cols: arr.shape[1],
+            data: arr.data.into_iter().collect(),
+        })
+    }
+    fn require_square(&self, prim: &str, env: &Uiua) -> UiuaResult {
+        if self.rows != self.cols {
+            return Err(env.error(format!(
+                "Cannot {prim} a non-square matrix of shape {}",
+                Shape::from([self.rows, self.cols])
+            )));
+        }
+        Ok(())
+    }
+    fn require_symmetric(&self, prim: &str, env: &Uiua) -> UiuaResult {
+        self.require_square(prim, env)?;
+        let n = self.rows;
+        let scale = (self.data.iter()).fold(1.0f64, |max, x| max.max(x.abs()));
+        for i in 0..n {
+            for j in 0..i {
+                let (a, b) = (self[(i, j)], self[(j, i)]);
+                if (a - b).abs() > 1e-9 * scale || (a - b).is_nan() {
+                    return Err(env.error(format!(
+                        "Cannot {prim} a non-symmetric matrix. \
+                        Element {i}_{j} is {a}, but element {j}_{i} is {b}."
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn identity(rows: usize, cols: usize) -> Self {
+        let mut data = vec![0.0; rows * cols];
+        for i in 0..rows.min(cols) {
+            data[i * cols + i] = 1.0;
+        }
+        Matrix { rows, cols, data }
+    }
+    /// Apply the Householder reflection `I - 2vvᵀ` to the rows at or after `start`
+    ///
+    /// Only columns at or after `col` are changed.
+    fn reflect(&mut self, v: &[f64], start: usize, col: usize) {
+        let cols = self.cols;
+        let rows = &mut self.data[start * cols..];
+        let mut w = vec![0.0; cols - col];
+        for (row, &vi) in rows.chunks_exact(cols).zip(v) {
+            for (w, &x) in w.iter_mut().zip(&row[col..]) {
+                *w += vi * x;
+            }
+        }
+        let update = |(row, &vi): (&mut [f64], &f64)| {
+            for (x, &w) in row[col..].iter_mut().zip(&w) {
+                *x -= 2.0 * vi * w;
+            }
+        };
+        if rows.len() > PAR_THRESHOLD {
+            rows.par_chunks_exact_mut(cols).zip(v).for_each(update);
+        } else {
+            rows.chunks_exact_mut(cols).zip(v).for_each(update);
+        }
+    }
+    fn into_value(self) -> Value {
+        let shape = Shape::from([self.rows, self.cols]);
+        Array::new(shape, self.data.into_iter().collect::<EcoVec<_>>()).into()
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = f64;
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl Value {
+    /// Get the `qr` decomposition of a matrix
+    ///
+    /// Returns `(Q, R)`, where `Q` has orthonormal columns and `R` is upper triangular
+    /// with a nonnegative diagonal.
+    pub(crate) fn qr(&self, env: &Uiua) -> UiuaResult<(Self, Self)> {
+        let mut r = Matrix::from_value(self, "get the QR decomposition of", env)?;
+        let (m, n) = (r.rows, r.cols);
+        let k = m.min(n);
+        let mut reflectors = Vec::with_capacity(k);
+        for j in 0..k {
+            let mut v: Vec<f64> = (j..m).map(|i| r[(i, j)]).collect();
+            let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                reflectors.push(None);
+                continue;
+            }
+            v[0] += if v[0] < 0.0 { -norm } else { norm };
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            v.iter_mut().for_each(|x| *x /= v_norm);
+            r.reflect(&v, j, j);
+            reflectors.push(Some(v));
+        }
+        // Accumulate Q by applying the reflectors to the identity in reverse
+        let mut q = Matrix::identity(m, k);
+        for (j, v) in reflectors.iter().enumerate().rev() {
+            if let Some(v) = v {
+                q.reflect(v, j, 0);
+            }
+        }
+        // Make the diagonal of R nonnegative
+        for j in 0..k {
+            if r[(j, j)] < 0.0 {
+                (j..n).for_each(|c| r[(j, c)] = -r[(j, c)]);
+                (0..m).for_each(|i| q[(i, j)] = -q[(i, j)]);
+            }
+            for i in j + 1..m {
+                r[(i, j)] = 0.0;
+            }
+        }
+        r.data.truncate(k * n);
+        r.rows = k;
+        Ok((q.into_value(), r.into_value()))
+    }
+    /// Get the `cholesky` decomposition of a symmetric positive-definite matrix
+    pub(crate) fn cholesky(&self, env: &Uiua) -> UiuaResult<Self> {
+        let prim = "get the Cholesky decomposition of";
+        let a = Matrix::from_value(self, prim, env)?;
+        a.require_symmetric(prim, env)?;
+        let n = a.rows;
+        let mut l = Matrix::identity(n, n);
+        l.data.fill(0.0);
+        for j in 0..n {
+            let row_j = &l.data[j * n..j * n + j];
+            let d = a[(j, j)] - row_j.iter().map(|x| x * x).sum::<f64>();
+            if d <= 0.0 || d.is_nan() {
+                return Err(env.error(format!(
+                    "Cannot {prim} a matrix that is not positive-definite"
+                )));
+            }
+            let d = d.sqrt();
+            l[(j, j)] = d;
+            let (top, bottom) = l.data.split_at_mut((j + 1) * n);
+            let row_j = &top[j * n..j * n + j];
+            let update = |(i, row): (usize, &mut [f64])| {
+                let dot: f64 = row[..j].iter().zip(row_j).map(|(a, b)| a * b).sum();
+                row[j] = (a[(j + 1 + i, j)] - dot) / d;
+            };
+            if bottom.len() > PAR_THRESHOLD {
+                bottom.par_chunks_exact_mut(n).enumerate().for_each(update);
+            } else {
+                bottom.chunks_exact_mut(n).enumerate().for_each(update);
+            }
+        }
+        Ok(l.into_value())
+    }
+    /// Get the `eigenvalues` of a symmetric matrix in ascending order
+    pub(crate) fn eigenvalues(&self, env: &Uiua) -> UiuaResult<Self> {
+        let prim = "get the eigenvalues of";
+        let mut a = Matrix::from_value(self, prim, env)?;
+        a.require_symmetric(prim, env)?;
+        let n = a.rows;
+        let norm = a.data.iter().map(|x| x * x).sum::<f64>();
+        // Cyclic Jacobi rotations
+        for _ in 0..MAX_SWEEPS {
+            let off: f64 = (0..n)
+                .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+                .map(|ij| a[ij] * a[ij])
+                .sum();
+            if off <= f64::EPSILON * f64::EPSILON * norm {
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    let apq = a[(p, q)];
+                    if apq == 0.0 {
+                        continue;
+                    }
+                    let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * apq);
+                    let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                    let c = 1.0 / t.hypot(1.0);
+                    let s = t * c;
+                    for k in (0..n).filter(|&k| k != p && k != q) {
+                        let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                        a[(k, p)] = c * akp - s * akq;
+                        a[(k, q)] = s * akp + c * akq;
+                        a[(p, k)] = a[(k, p)];
+                        a[(q, k)] = a[(k, q)];
+                    }
+                    a[(p, p)] -= t * apq;
+                    a[(q, q)] += t * apq;
+                    a[(p, q)] = 0.0;
+                    a[(q, p)] = 0.0;
+                }
+            }
+        }
+        let mut values: Vec<f64> = (0..n).map(|i| a[(i, i)]).collect();
+        values.sort_by(f64::total_cmp);
+        Ok(values.into_iter().collect::<EcoVec<_>>().into())
+    }
+    /// Keep the elements of a matrix on and below the `k`th diagonal
+    pub(crate) fn tril(&self, from: Self, env: &Uiua) -> UiuaResult<Self> {
+        let k = self.as_int(env, "Diagonal offset must be an integer")?;
+        from.keep_band(None, Some(k), "take the lower triangle of", env)
+    }
+    /// Keep the elements of a matrix on and above the `k`th diagonal
+    pub(crate) fn triu(&self, from: Self, env: &Uiua) -> UiuaResult<Self> {
+        let k = self.as_int(env, "Diagonal offset must be an integer")?;
+        from.keep_band(Some(k), None, "take the upper triangle of", env)
+    }
+    /// Keep the elements of a matrix within some number of diagonals below and above
+    /// the leading diagonal
+    pub(crate) fn band(&self, from: Self, env: &Uiua) -> UiuaResult<Self> {
+        let widths = self.as_nats(env, "Band widths must be natural numbers")?;
+        let (below, above) = match *widths.as_slice() {
+            [width] => (width, width),
+            [below, above] => (below, above),
+            _ => {
+                return Err(env.error(format!(
+                    "Band widths must be 1 or 2 natural numbers, but there are {}",
+                    widths.len()
+                )))
+            }
+        };
+        let offset = |width: usize| isize::try_from(width).unwrap_or(isize::MAX);
+        from.keep_band(
+            Some(-offset(below)),
+            Some(offset(above)),
+            "take a band of",
+            env,
+        )
+    }
+    /// Replace the elements of a matrix outside of a range of diagonals
+    ///
+    /// Diagonal `d` contains the elements whose column minus row is `d`.
+    /// Replaced elements become the fill value, or `0` if no fill is set.
+    fn keep_band(
+        self,
+        min: Option<isize>,
+        max: Option<isize>,
+        prim: &str,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        if let Value::Char(_) | Value::Box(_) | Value::Time(_) = self {
+            return Err(env.error(format!("Cannot {prim} {}", self.type_name_plural())));
+        }
+        if self.rank() != 2 {
+            return Err(env.error(format!(
+                "Cannot {prim} a rank {} array. It must be a matrix.",
+                self.rank()
+            )));
+        }
+        Ok(match self {
+            Value::Num(arr) => {
+                let fill = env.scalar_fill().unwrap_or(0.0);
+                arr.keep_band(min, max, fill).into()
+            }
+            Value::Byte(arr) => match (env.scalar_fill::<u8>(), env.scalar_fill::<f64>()) {
+                (Ok(fill), _) => arr.keep_band(min, max, fill).into(),
+                (Err(_), Ok(fill)) => arr.convert::<f64>().keep_band(min, max, fill).into(),
+                (Err(_), Err(_)) => arr.keep_band(min, max, 0).into(),
+            },
+            Value::Complex(arr) => {
+                let fill = env.scalar_fill().unwrap_or(Complex::ZERO);
+                arr.keep_band(min, max, fill).into()
+            }
+            Value::Char(_) | Value::Box(_) | Value::Time(_) => unreachable!(),
+        })
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    /// Replace the elements of a matrix outside of a range of diagonals in one pass
+    ///
+    /// Only the elements before and after the kept range of each row are touched.
+    fn keep_band(mut self, min: Option<isize>, max: Option<isize>, fill: T) -> Self {
+        let cols = self.shape[1];
+        if cols == 0 {
+            return self;
+        }
+        let clamp = |col: isize| col.clamp(0, cols as isize) as usize;
+        for (i, row) in self.data.as_mut_slice().chunks_exact_mut(cols).enumerate() {
+            let i = i as isize;
+            let start = min.map_or(0, |min| clamp(i.saturating_add(min)));
+            let end = max.map_or(cols, |max| clamp(i.saturating_add(max).saturating_add(1)));
+            row[..start].fill(fill.clone());
+            row[end.max(start)..].fill(fill.clone());
+        }
+        self.reset_meta_flags();
+        self
+    }
+}
@@ -224,6 +224,7 @@ impl Value {
             Value::Complex(arr) => arr.partition_groups(markers, env)?,
             Value::Char(arr) => arr.partition_groups(markers, env)?,
             Value::Box(arr) => arr.partition_groups(markers, env)?,
+            Value::Time(arr) => arr.partition_groups(markers, env)?,
         })
     }
     fn partition_firsts(self, markers: &[isize], env: &Uiua) -> UiuaResult<Self> {
@@ -233,6 +234,7 @@ impl Value {
             Value::Complex(arr) => arr.partition_firsts(markers, env)?.into(),
             Value::Char(arr) => arr.partition_firsts(markers, env)?.into(),
             Value::Box(arr) => arr.partition_firsts(markers, env)?.into(),
+            Value::Time(arr) => arr.partition_firsts(markers, env)?.into(),
         })
     }
     fn partition_lasts(self, markers: &[isize], env: &Uiua) -> UiuaResult<Self> {
@@ -242,6 +244,7 @@ impl Value {
             Value::Complex(arr) => arr.partition_lasts(markers, env)?.into(),
             Value::Char(arr) => arr.partition_lasts(markers, env)?.into(),
             Value::Box(arr) => arr.partition_lasts(markers, env)?.into(),
+            Value::Time(arr) => arr.partition_lasts(markers, env)?.into(),
         })
     }
 }
@@ -615,6 +618,7 @@ pub fn undo_partition_part2(env: &mut Uiua) -> UiuaResult {
                     |a, b| Ok(update_array_at(a, start, b.data.as_slice())),
                     |a, b| Ok(update_array_at(a, start, b.data.as_slice())),
                     |a, b| Ok(update_array_at(a, start, b.data.as_slice())),
+                    |a, b| Ok(update_array_at(a, start, b.data.as_slice())),
                     |a, b| {
                         env.error(format!(
                             "Cannot unpartition {} array into {} array",
@@ -633,6 +637,7 @@ pub fn undo_partition_part2(env: &mut Uiua) -> UiuaResult {
 fn update_array_at<T: Clone>(arr: &mut Array<T>, start: usize, new: &[T]) {
     let end = start + new.len();
     arr.data.as_mut_slice()[start..end].clone_from_slice(new);
+    arr.reset_meta_flags();
 }
 
 pub fn group(env: &mut Uiua) -> UiuaResult {
@@ -670,6 +675,7 @@ impl Value {
             Value::Complex(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Char(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Box(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
+            Value::Time(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
         })
     }
     fn group_firsts(self, indices: &[isize], env: &Uiua) -> UiuaResult<Self> {
@@ -679,6 +685,7 @@ impl Value {
             Value::Complex(arr) => arr.group_firsts(indices, env)?.into(),
             Value::Char(arr) => arr.group_firsts(indices, env)?.into(),
             Value::Box(arr) => arr.group_firsts(indices, env)?.into(),
+            Value::Time(arr) => arr.group_firsts(indices, env)?.into(),
         })
     }
     fn group_lasts(self, indices: &[isize], env: &Uiua) -> UiuaResult<Self> {
@@ -688,6 +695,7 @@ impl Value {
             Value::Complex(arr) => arr.group_lasts(indices, env)?.into(),
             Value::Char(arr) => arr.group_lasts(indices, env)?.into(),
             Value::Box(arr) => arr.group_lasts(indices, env)?.into(),
+            Value::Time(arr) => arr.group_lasts(indices, env)?.into(),
         })
     }
 }
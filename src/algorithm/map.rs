@@ -9,8 +9,8 @@ use ecow::EcoVec;
 use serde::*;
 
 use crate::{
-    algorithm::ArrayCmpSlice, Array, ArrayValue, Boxed, Complex, FormatShape, Uiua, UiuaResult,
-    Value,
+    algorithm::ArrayCmpSlice, Array, ArrayValue, Boxed, Complex, FormatShape, Timestamp, Uiua,
+    UiuaResult, Value,
 };
 
 use super::FillContext;
@@ -42,10 +42,10 @@ impl<T: ArrayValue> Array<T> {
         kv
     }
     /// Create a map array
-    pub fn map(&mut self, mut keys: Value, env: &Uiua) -> UiuaResult {
+    pub fn map<C: FillContext>(&mut self, mut keys: Value, ctx: &C) -> Result<(), C::Error> {
         let values = self;
         if keys.row_count() != values.row_count() {
-            return Err(env.error(format!(
+            return Err(ctx.error(format!(
                 "Map array's keys and values must have the same length, but they have lengths {} and {}",
                 keys.row_count(),
                 values.row_count()
@@ -64,7 +64,7 @@ impl<T: ArrayValue> Array<T> {
             fix_stack: Vec::new(),
         };
         for (i, key) in keys.into_rows().enumerate() {
-            map_keys.insert(key, i, env)?;
+            map_keys.insert(key, i, ctx)?;
         }
         values.meta_mut().map_keys = Some(map_keys);
         Ok(())
@@ -84,17 +84,41 @@ impl Value {
             |arr| arr.map_kv().map(|(k, v)| (k, v.into())).collect(),
             |arr| arr.map_kv().map(|(k, v)| (k, v.into())).collect(),
             |arr| arr.map_kv().map(|(k, v)| (k, v.into())).collect(),
+            |arr| arr.map_kv().map(|(k, v)| (k, v.into())).collect(),
         )
     }
     /// Create a map array
-    pub fn map(&mut self, keys: Self, env: &Uiua) -> UiuaResult {
+    pub fn map<C: FillContext>(&mut self, keys: Self, ctx: &C) -> Result<(), C::Error> {
         match self {
-            Value::Num(arr) => arr.map(keys, env),
-            Value::Byte(arr) => arr.map(keys, env),
-            Value::Complex(arr) => arr.map(keys, env),
-            Value::Char(arr) => arr.map(keys, env),
-            Value::Box(arr) => arr.map(keys, env),
+            Value::Num(arr) => arr.map(keys, ctx),
+            Value::Byte(arr) => arr.map(keys, ctx),
+            Value::Complex(arr) => arr.map(keys, ctx),
+            Value::Char(arr) => arr.map(keys, ctx),
+            Value::Box(arr) => arr.map(keys, ctx),
+            Value::Time(arr) => arr.map(keys, ctx),
+        }
+    }
+    /// Create a map array from key-value pairs
+    ///
+    /// All keys must be able to be coupled together, as must all values.
+    /// Keys must be unique.
+    pub fn map_from_pairs<C: FillContext>(
+        pairs: impl IntoIterator<Item = (Value, Value)>,
+        ctx: &C,
+    ) -> Result<Self, C::Error> {
+        let (keys, values): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        let keys = Value::from_row_values(keys, ctx)?;
+        let mut values = Value::from_row_values(values, ctx)?;
+        values.map(keys, ctx)?;
+        let key_count = values.map_keys().map_or(0, |keys| keys.len);
+        if key_count != values.row_count() {
+            return Err(ctx.error(format!(
+                "Map keys must be unique, but there are {} unique keys for {} values",
+                key_count,
+                values.row_count()
+            )));
         }
+        Ok(values)
     }
     /// Turn a map array into its keys and values
     pub fn unmap(mut self, env: &Uiua) -> UiuaResult<(Value, Value)> {
@@ -166,6 +190,7 @@ impl Value {
                 |arr, value| Ok(arr.set_row(index, value)),
                 |arr, value| Ok(arr.set_row(index, value)),
                 |arr, value| Ok(arr.set_row(index, value)),
+                |arr, value| Ok(arr.set_row(index, value)),
                 |a, b| {
                     env.error(format!(
                         "Cannot insert {} value into map with {} values",
@@ -210,6 +235,7 @@ impl Value {
                 |arr, value| Ok(arr.set_row(index, value)),
                 |arr, value| Ok(arr.set_row(index, value)),
                 |arr, value| Ok(arr.set_row(index, value)),
+                |arr, value| Ok(arr.set_row(index, value)),
                 |a, b| {
                     env.error(format!(
                         "Cannot insert {} value into map with {} values",
@@ -226,6 +252,7 @@ impl Value {
                 |arr, value| Ok(arr.insert_row(index, value)),
                 |arr, value| Ok(arr.insert_row(index, value)),
                 |arr, value| Ok(arr.insert_row(index, value)),
+                |arr, value| Ok(arr.insert_row(index, value)),
                 |a, b| {
                     env.error(format!(
                         "Cannot insert {} value into map with {} values",
@@ -238,6 +265,27 @@ impl Value {
         self.meta_mut().map_keys = Some(keys);
         Ok(())
     }
+    /// Insert many key-value pairs into a map array
+    ///
+    /// Keys that are already present have their values updated.
+    /// The table is grown once for all the pairs rather than as each key is inserted.
+    pub fn insert_all(
+        &mut self,
+        pairs: impl ExactSizeIterator<Item = (Value, Value)>,
+        env: &Uiua,
+    ) -> UiuaResult {
+        let count = pairs.len();
+        for (i, (key, value)) in pairs.enumerate() {
+            self.insert(key, value, env)?;
+            // The first insertion settles the key type, so the rest can be presized
+            if i == 0 {
+                if let Some(keys) = self.get_meta_mut().and_then(|m| m.map_keys.as_mut()) {
+                    keys.reserve(count - 1);
+                }
+            }
+        }
+        Ok(())
+    }
     /// Return a key's value to what it used to be, including if it didn't exist before
     pub fn undo_insert(&mut self, key: Value, original: &Self, env: &Uiua) -> UiuaResult {
         let orig_keys =
@@ -280,11 +328,57 @@ impl Value {
                 Value::Complex(arr) => arr.remove_row(index),
                 Value::Char(arr) => arr.remove_row(index),
                 Value::Box(arr) => arr.remove_row(index),
+                Value::Time(arr) => arr.remove_row(index),
                 Value::Byte(arr) => arr.remove_row(index),
             }
         }
         Ok(())
     }
+    /// Remove many keys and their values from a map array
+    ///
+    /// The remaining indices are fixed up and the rows are removed in a single pass.
+    pub fn remove_all(&mut self, keys: impl IntoIterator<Item = Value>, env: &Uiua) -> UiuaResult {
+        if self.row_count() == 0 {
+            return Ok(());
+        }
+        let row_count = self.row_count();
+        let map_keys = (self.get_meta_mut().and_then(|m| m.map_keys.as_mut()))
+            .ok_or_else(|| env.error("Value is not a map"))?;
+        if map_keys.len != row_count {
+            return Err(env.error(format!(
+                "Cannot remove from map with {} keys and {} value(s)",
+                map_keys.len, row_count
+            )));
+        }
+        let mut removed = Vec::new();
+        for key in keys {
+            if let Some(index) = map_keys.remove(key, env)? {
+                if index >= row_count {
+                    return Err(env.error("Map was corrupted"));
+                }
+                removed.push(index);
+            }
+        }
+        if removed.is_empty() {
+            return Ok(());
+        }
+        removed.sort_unstable();
+
+        // Shift indices down by the number of removed indices before them
+        for i in &mut map_keys.indices {
+            *i -= removed.partition_point(|&r| r < *i);
+        }
+
+        match self {
+            Value::Num(arr) => arr.remove_rows(&removed),
+            Value::Complex(arr) => arr.remove_rows(&removed),
+            Value::Char(arr) => arr.remove_rows(&removed),
+            Value::Box(arr) => arr.remove_rows(&removed),
+            Value::Time(arr) => arr.remove_rows(&removed),
+            Value::Byte(arr) => arr.remove_rows(&removed),
+        }
+        Ok(())
+    }
     /// Re-insert a key-value pair to a modified map array if it got removed
     pub fn undo_remove(&mut self, key: Value, original: &Self, env: &Uiua) -> UiuaResult {
         let keys =
@@ -294,6 +388,57 @@ impl Value {
         }
         Ok(())
     }
+    /// Sort the entries of a map array by their keys
+    pub fn sort_map_by_keys(&self, env: &Uiua) -> UiuaResult<Self> {
+        let (keys, values) = self.clone().unmap(env)?;
+        let order = keys.rise(env)?.into();
+        reorder_map(keys, values, &order, env)
+    }
+    /// Sort the entries of a map array by their values
+    pub fn sort_map_by_values(&self, env: &Uiua) -> UiuaResult<Self> {
+        let (keys, values) = self.clone().unmap(env)?;
+        let order = values.rise(env)?.into();
+        reorder_map(keys, values, &order, env)
+    }
+    /// Get the entries of a map array whose keys are at least `low` and less than `high`
+    ///
+    /// Keys must be numbers or strings. The resulting map is sorted by its keys.
+    pub fn map_slice(&self, low: &Value, high: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let (keys, values) = self.clone().unmap(env)?;
+        let (order, range) = match &keys {
+            Value::Num(_) | Value::Byte(_) if keys.rank() == 1 => {
+                let keys = keys.as_nums(env, "")?;
+                let low = low.as_num(env, "Map slice start must be a number")?;
+                let high = high.as_num(env, "Map slice end must be a number")?;
+                let mut order: Vec<usize> = (0..keys.len()).collect();
+                order.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+                let start = order.partition_point(|&i| keys[i] < low);
+                let end = order.partition_point(|&i| keys[i] < high);
+                (order, start..end.max(start))
+            }
+            _ => {
+                let keys =
+                    keys.as_string_list(env, "Map keys must be numbers or strings to be sliced")?;
+                let low = low.as_string(env, "Map slice start must be a string")?;
+                let high = high.as_string(env, "Map slice end must be a string")?;
+                let mut order: Vec<usize> = (0..keys.len()).collect();
+                order.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+                let start = order.partition_point(|&i| keys[i] < low);
+                let end = order.partition_point(|&i| keys[i] < high);
+                (order, start..end.max(start))
+            }
+        };
+        let order = Value::from_iter(order[range].iter().map(|&i| i as f64));
+        reorder_map(keys, values, &order, env)
+    }
+}
+
+/// Rebuild a map from its keys and values with its entries in the given order
+fn reorder_map(keys: Value, values: Value, order: &Value, env: &Uiua) -> UiuaResult<Value> {
+    let keys = order.select(&keys, env)?;
+    let mut values = order.select(&values, env)?;
+    values.map(keys, env)?;
+    Ok(values)
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -313,6 +458,13 @@ impl MapKeys {
             self.grow_to((self.capacity() * 2).max(1));
         }
     }
+    /// Make room for some number of additional keys without exceeding the load factor
+    fn reserve(&mut self, additional: usize) {
+        let needed = ((self.len + additional) as f64 / LOAD_FACTOR).ceil() as usize + 1;
+        if needed > self.capacity() {
+            self.grow_to(needed);
+        }
+    }
     fn grow_to(&mut self, new_capacity: usize) {
         {
             if let Value::Byte(keys) = &self.keys {
@@ -324,6 +476,7 @@ impl MapKeys {
             Value::Complex(a) => Self::grow_impl(a, &mut self.indices, new_capacity),
             Value::Char(a) => Self::grow_impl(a, &mut self.indices, new_capacity),
             Value::Box(a) => Self::grow_impl(a, &mut self.indices, new_capacity),
+            Value::Time(a) => Self::grow_impl(a, &mut self.indices, new_capacity),
             Value::Byte(_) => unreachable!(),
         }
     }
@@ -436,6 +589,7 @@ impl MapKeys {
             Value::Complex(a) => hash_start(a, self.capacity()),
             Value::Char(a) => hash_start(a, self.capacity()),
             Value::Box(a) => hash_start(a, self.capacity()),
+            Value::Time(a) => hash_start(a, self.capacity()),
             Value::Byte(a) => hash_start(a, self.capacity()),
         };
         let mut key_index = start;
@@ -540,6 +694,55 @@ impl MapKeys {
     pub fn into_value(self) -> Value {
         remove_empty_rows(self.keys.into_rows())
     }
+    /// Check that deserialized map keys are consistent with a map array's values
+    ///
+    /// Keys that are not where their hashes put them are rehashed.
+    pub(crate) fn validate(&mut self, row_count: usize) -> Result<(), String> {
+        if self.keys.rank() == 0 || self.keys.row_count() != self.capacity() {
+            return Err(format!(
+                "Map has {} key slots but {} indices",
+                self.keys.row_count(),
+                self.capacity()
+            ));
+        }
+        if (self.fix_stack.iter()).any(|(len, indices)| *len > indices.len()) {
+            return Err("Map has an invalid fix stack".into());
+        }
+        let mut present: Vec<(Value, usize)> = (self.keys.rows().zip(&self.indices))
+            .filter(|(k, _)| !k.is_any_empty_cell() && !k.is_any_tombstone())
+            .map(|(k, &i)| (k, i))
+            .collect();
+        if present.len() != self.len {
+            return Err(format!(
+                "Map has {} keys but a length of {}",
+                present.len(),
+                self.len
+            ));
+        }
+        if let Some((_, i)) = present.iter().find(|(_, i)| *i >= row_count) {
+            return Err(format!(
+                "Map key index {i} is out of bounds of {row_count} values"
+            ));
+        }
+        if present.iter().all(|(k, i)| self.get(k) == Some(*i)) {
+            return Ok(());
+        }
+        if !self.fix_stack.is_empty() {
+            return Err("Map keys are not in their hashed positions".into());
+        }
+        present.sort_unstable_by_key(|(_, i)| *i);
+        let mut rehashed = MapKeys {
+            keys: self.keys.clone(),
+            indices: Vec::new(),
+            len: 0,
+            fix_stack: Vec::new(),
+        };
+        for (key, i) in present {
+            rehashed.insert(key, i, &()).unwrap_or_else(|e| match e {});
+        }
+        *self = rehashed;
+        Ok(())
+    }
     fn present_indices(&self) -> Vec<usize> {
         let mut present_indices: Vec<_> = (self.keys.rows().enumerate())
             .filter(|(_, k)| !k.is_any_empty_cell() && !k.is_any_tombstone())
@@ -580,6 +783,7 @@ impl MapKeys {
             Value::Complex(keys) => set_tombstones(keys, dropped),
             Value::Char(keys) => set_tombstones(keys, dropped),
             Value::Box(keys) => set_tombstones(keys, dropped),
+            Value::Time(keys) => set_tombstones(keys, dropped),
             Value::Byte(keys) => {
                 let mut nums = keys.convert_ref();
                 set_tombstones(&mut nums, dropped);
@@ -600,6 +804,7 @@ impl MapKeys {
             Value::Complex(keys) => set_tombstones(keys, not_taken),
             Value::Char(keys) => set_tombstones(keys, not_taken),
             Value::Box(keys) => set_tombstones(keys, not_taken),
+            Value::Time(keys) => set_tombstones(keys, not_taken),
             Value::Byte(keys) => {
                 let mut nums = keys.convert_ref();
                 set_tombstones(&mut nums, not_taken);
@@ -834,6 +1039,21 @@ impl MapItem for char {
     }
 }
 
+impl MapItem for Timestamp {
+    fn empty_cell() -> Self {
+        Timestamp(i64::MIN)
+    }
+    fn tombstone_cell() -> Self {
+        Timestamp(i64::MIN + 1)
+    }
+    fn is_any_empty_cell(&self) -> bool {
+        self.0 == i64::MIN
+    }
+    fn is_any_tombstone(&self) -> bool {
+        self.0 == i64::MIN + 1
+    }
+}
+
 impl MapItem for Boxed {
     fn empty_cell() -> Self {
         Boxed(Value::empty_cell())
@@ -863,6 +1083,7 @@ impl MapItem for Value {
             Value::Complex(num) => num.data.iter().any(|v| v.is_any_empty_cell()),
             Value::Char(num) => num.data.iter().any(|v| v.is_any_empty_cell()),
             Value::Box(num) => num.data.iter().any(|v| v.is_any_empty_cell()),
+            Value::Time(num) => num.data.iter().any(|v| v.is_any_empty_cell()),
         }
     }
     fn is_any_tombstone(&self) -> bool {
@@ -872,6 +1093,7 @@ impl MapItem for Value {
             Value::Complex(num) => num.data.iter().any(|v| v.is_any_tombstone()),
             Value::Char(num) => num.data.iter().any(|v| v.is_any_tombstone()),
             Value::Box(num) => num.data.iter().any(|v| v.is_any_tombstone()),
+            Value::Time(num) => num.data.iter().any(|v| v.is_any_tombstone()),
         }
     }
     fn is_all_empty_cell(&self) -> bool {
@@ -881,6 +1103,7 @@ impl MapItem for Value {
             Value::Complex(num) => num.data.iter().all(|v| v.is_any_empty_cell()),
             Value::Char(num) => num.data.iter().all(|v| v.is_any_empty_cell()),
             Value::Box(num) => num.data.iter().all(|v| v.is_any_empty_cell()),
+            Value::Time(num) => num.data.iter().all(|v| v.is_any_empty_cell()),
         }
     }
     fn is_all_tombstone(&self) -> bool {
@@ -890,6 +1113,101 @@ impl MapItem for Value {
             Value::Complex(num) => num.data.iter().all(|v| v.is_any_tombstone()),
             Value::Char(num) => num.data.iter().all(|v| v.is_any_tombstone()),
             Value::Box(num) => num.data.iter().all(|v| v.is_any_tombstone()),
+            Value::Time(num) => num.data.iter().all(|v| v.is_any_tombstone()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn map_builder() {
+        let env = Uiua::with_safe_sys();
+        let map = Value::map_from_pairs(
+            [
+                (Boxed("a".into()).into(), 1.into()),
+                (Boxed("bc".into()).into(), 2.into()),
+            ],
+            &env,
+        )
+        .unwrap();
+        assert_eq!(map.representation(), r#"map {"a" "bc"} [1 2]"#);
+        let duplicate = Value::map_from_pairs([(1.into(), 1.into()), (1.into(), 2.into())], &env);
+        assert!(duplicate.is_err());
+        let mismatched =
+            Value::map_from_pairs([(1.into(), 1.into()), ('a'.into(), 2.into())], &env);
+        assert!(mismatched.is_err());
+
+        let labeled = Value::from(5).with_label("x");
+        assert_eq!(labeled.representation(), "$x 5");
+
+        let rows = vec![Array::from_iter([1.0, 2.0]), Array::from_iter([3.0, 4.0])];
+        let matrix = Array::from_rows(rows, &env).unwrap();
+        assert_eq!(matrix.shape(), &[2, 2]);
+        let rows = vec![Array::from_iter([1.0, 2.0]), Array::from_iter([3.0])];
+        assert!(Array::from_rows(rows, &env).is_err());
+    }
+
+    #[test]
+    fn ordered_maps() {
+        let env = Uiua::with_safe_sys();
+        let map = Value::map_from_pairs(
+            [(3, 30), (1, 20), (4, 10), (2, 40)].map(|(k, v)| (k.into(), v.into())),
+            &env,
+        )
+        .unwrap();
+        let nums = |val: Value| val.as_nums(&env, "").unwrap();
+        let sorted = map.sort_map_by_keys(&env).unwrap();
+        let (keys, values) = sorted.clone().unmap(&env).unwrap();
+        assert_eq!(nums(keys), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(nums(values), [20.0, 40.0, 30.0, 10.0]);
+        assert_eq!(nums(sorted.get(&4.into(), &env).unwrap()), [10.0]);
+
+        let (keys, _) = map.sort_map_by_values(&env).unwrap().unmap(&env).unwrap();
+        assert_eq!(nums(keys), [4.0, 1.0, 3.0, 2.0]);
+
+        let slice = map.map_slice(&2.into(), &4.into(), &env).unwrap();
+        let (keys, values) = slice.clone().unmap(&env).unwrap();
+        assert_eq!(nums(keys), [2.0, 3.0]);
+        assert_eq!(nums(values), [40.0, 30.0]);
+        assert!(slice.has_key(&3.into(), &env).unwrap());
+        assert!(!slice.has_key(&4.into(), &env).unwrap());
+        let empty = map.map_slice(&4.into(), &2.into(), &env).unwrap();
+        assert_eq!(empty.row_count(), 0);
+
+        let words = Value::map_from_pairs(
+            ["pear", "fig", "apple", "kiwi"]
+                .map(|w| (Boxed(w.into()).into(), (w.len() as f64).into())),
+            &env,
+        )
+        .unwrap();
+        let slice = words.map_slice(&"b".into(), &"kiwi".into(), &env).unwrap();
+        let (keys, _) = slice.unmap(&env).unwrap();
+        assert_eq!(keys.as_string_list(&env, "").unwrap(), ["fig"]);
+        assert!(words.map_slice(&1.into(), &2.into(), &env).is_err());
+    }
+
+    #[test]
+    fn deserialize_validation() {
+        let env = Uiua::with_safe_sys();
+        let err = serde_json::from_str::<Value>("[[2,2],[1,2,3]]").unwrap_err();
+        assert!(err.to_string().contains("should have 4 elements"), "{err}");
+
+        let map = |keys: &str, indices: &str| {
+            serde_json::from_str::<Value>(&format!(
+                r#"[[3],[5,6,7],{{"map_keys":{{"keys":{keys},"indices":{indices},"len":3,"fix_stack":[]}}}}]"#
+            ))
+        };
+        let valid = map(r#"[1.0,3.0,"empty",2.0]"#, "[0,2,0,1]").unwrap();
+        assert_eq!(valid.get(&1.into(), &env).unwrap(), Value::from(5));
+        // Keys in the wrong slots are rehashed
+        let moved = map(r#"[3.0,"empty",2.0,1.0]"#, "[2,0,1,0]").unwrap();
+        assert_eq!(moved, valid);
+        assert_eq!(moved.get(&1.into(), &env).unwrap(), Value::from(5));
+        let err = map(r#"[1.0,3.0,"empty",2.0]"#, "[0,9,0,1]").unwrap_err();
+        assert!(err.to_string().contains("out of bounds"), "{err}");
+        assert!(map(r#"[1.0,3.0,2.0]"#, "[0,2,0,1]").is_err());
+    }
+}
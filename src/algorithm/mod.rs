@@ -13,18 +13,37 @@ use std::{
 use tinyvec::TinyVec;
 
 use crate::{
-    Array, ArrayValue, CodeSpan, ExactDoubleIterator, Function, Inputs, PersistentMeta, Shape,
-    Signature, Span, TempStack, Uiua, UiuaError, UiuaResult, Value,
+    Array, ArrayValue, CodeSpan, Complex, ExactDoubleIterator, Function, Inputs, PersistentMeta,
+    Shape, Signature, Span, TempStack, Uiua, UiuaError, UiuaResult, Value,
 };
 
+mod allocate;
+mod approx;
+mod axes;
+mod calendar;
+mod decimal;
 mod dyadic;
+mod einsum;
+pub(crate) mod encode;
+mod geo;
+mod hash;
+mod interp;
 pub(crate) mod invert;
+pub(crate) mod lazy_fill;
+mod linalg;
 pub mod loops;
 pub(crate) mod map;
 mod monadic;
+pub(crate) mod padding;
 pub mod pervade;
+mod ragged;
+pub(crate) mod random;
+mod record;
 pub mod reduce;
+mod stats;
+mod string;
 pub mod table;
+pub(crate) mod units;
 pub mod zip;
 
 type MultiOutput<T> = TinyVec<[T; 1]>;
@@ -58,16 +77,19 @@ fn max_shape(a: &[usize], b: &[usize]) -> Shape {
     new_shape
 }
 
-#[derive(Debug)]
+/// An error for arrays that would be too large
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SizeError {
+    /// The number of elements does not fit in a `usize`
     Overflow,
+    /// The array has this many elements, which would take too much memory
     TooLarge(usize),
 }
 
 impl fmt::Display for SizeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            SizeError::Overflow => write!(f, "Array size calculation overflowed"),
+            SizeError::Overflow => write!(f, "Array would be too large because its size overflows"),
             SizeError::TooLarge(size) => write!(f, "Array of {size} elements would be too large"),
         }
     }
@@ -79,7 +101,9 @@ pub fn validate_size<T>(
     sizes: impl IntoIterator<Item = usize> + Clone,
     env: &Uiua,
 ) -> UiuaResult<usize> {
-    validate_size_impl(size_of::<T>(), sizes).map_err(|e| env.error(e))
+    let elements = validate_size_impl(size_of::<T>(), sizes).map_err(|e| env.error(e))?;
+    env.respect_size_limits(elements)?;
+    Ok(elements)
 }
 
 pub(crate) fn validate_size_impl(
@@ -255,6 +279,7 @@ where
         Value::Complex(arr) => fill_array_shape(arr, target, expand_fixed, ctx),
         Value::Char(arr) => fill_array_shape(arr, target, expand_fixed, ctx),
         Value::Box(arr) => fill_array_shape(arr, target, expand_fixed, ctx),
+        Value::Time(arr) => fill_array_shape(arr, target, expand_fixed, ctx),
     }
 }
 
@@ -557,6 +582,9 @@ pub fn try_(env: &mut Uiua) -> UiuaResult {
     }
     let backup = env.clone_stack_top(f_sig.args.min(handler_sig.args))?;
     if let Err(e) = env.call_clean_stack(f) {
+        if e.is_interrupt() {
+            return Err(e);
+        }
         if handler_sig.args > f_sig.args {
             (env.rt.backend).save_error_color(e.message(), e.report().to_string());
             env.push(e.value());
@@ -569,6 +597,20 @@ pub fn try_(env: &mut Uiua) -> UiuaResult {
     Ok(())
 }
 
+/// Get an argument as a number array, converting bytes
+///
+/// `what` names the argument in the error message.
+pub(crate) fn num_array(val: &Value, what: &str, env: &Uiua) -> UiuaResult<Array<f64>> {
+    match val {
+        Value::Num(arr) => Ok(arr.clone()),
+        Value::Byte(arr) => Ok(arr.convert_ref()),
+        val => Err(env.error(format!(
+            "{what} must be numbers, but they are {}",
+            val.type_name_plural()
+        ))),
+    }
+}
+
 /// If a function fails on a byte array because no fill byte is defined,
 /// convert the byte array to a number array and try again.
 fn op_bytes_retry_fill<T, E: FillError>(
@@ -583,6 +625,24 @@ fn op_bytes_retry_fill<T, E: FillError>(
     }
 }
 
+/// If a function fails on a number array because the fill value is complex,
+/// convert the number array to a complex array and try again.
+fn op_nums_retry_complex_fill<T>(
+    nums: Array<f64>,
+    env: &Uiua,
+    on_nums: impl FnOnce(Array<f64>) -> UiuaResult<T>,
+    on_complex: impl FnOnce(Array<Complex>) -> UiuaResult<T>,
+) -> UiuaResult<T> {
+    if !matches!(env.value_fill(), Some(Value::Complex(_))) {
+        return on_nums(nums);
+    }
+    match on_nums(nums.clone()) {
+        Ok(res) => Ok(res),
+        Err(err) if err.is_fill() => on_complex(nums.convert()),
+        Err(err) => Err(err),
+    }
+}
+
 /// If a function fails on a byte array because no fill byte is defined,
 /// convert the byte array to a number array and try again.
 fn op_bytes_ref_retry_fill<T>(
@@ -10,15 +10,16 @@ use std::{
     ptr, slice,
 };
 
-use ecow::{eco_vec, EcoVec};
+use ecow::{eco_vec, EcoString, EcoVec};
 use rayon::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
     array::*,
     cowslice::{cowslice, CowSlice},
-    grid_fmt::GridFmt,
+    grid_fmt::{format_char_inner, GridFmt},
     value::Value,
-    Boxed, Complex, Primitive, Shape, Uiua, UiuaResult,
+    Boxed, Complex, Primitive, Shape, Timestamp, Uiua, UiuaResult, WILDCARD_CHAR,
 };
 
 use super::{op_bytes_retry_fill, validate_size, ArrayCmpSlice, FillContext};
@@ -45,6 +46,7 @@ impl Value {
                     b.deshape_depth(depth);
                 }
             }
+            Value::Time(t) => t.deshape_depth(depth),
         }
     }
     /// Attempt to parse the value into a number
@@ -94,6 +96,58 @@ impl Value {
             (val, _) => return Err(env.error(format!("Cannot parse {} array", val.type_name()))),
         })
     }
+    /// Convert seconds since the Unix epoch or ISO 8601 strings to datetimes
+    pub(crate) fn to_timestamp(&self, env: &Uiua) -> UiuaResult<Self> {
+        Ok(match (self, self.shape().dims()) {
+            (Value::Time(_), _) => self.clone(),
+            (Value::Num(_) | Value::Byte(_), _) => {
+                let secs = match self {
+                    Value::Num(arr) => arr.clone(),
+                    Value::Byte(arr) => arr.convert_ref(),
+                    _ => unreachable!(),
+                };
+                let mut data = EcoVec::with_capacity(secs.data.len());
+                for &s in &secs.data {
+                    data.push(
+                        Timestamp::from_secs(s).ok_or_else(|| {
+                            env.error(format!("Cannot convert {s} to a datetime"))
+                        })?,
+                    );
+                }
+                Array::new(secs.shape, data).into()
+            }
+            (Value::Char(arr), [] | [_]) => {
+                let s: String = arr.data.iter().collect();
+                Timestamp::parse(&s)
+                    .ok_or_else(|| env.error(format!("Cannot parse {s:?} as a datetime")))?
+                    .into()
+            }
+            (Value::Box(arr), []) => arr.data[0].0.to_timestamp(env)?,
+            (Value::Char(_) | Value::Box(_), _) => {
+                let mut rows = Vec::with_capacity(self.row_count());
+                for row in self.rows() {
+                    rows.push(row.to_timestamp(env)?);
+                }
+                Value::from_row_values(rows, env)?
+            }
+            (val, _) => {
+                return Err(env.error(format!(
+                    "Cannot convert {} array to datetimes",
+                    val.type_name()
+                )))
+            }
+        })
+    }
+    /// Convert datetimes to seconds since the Unix epoch
+    pub(crate) fn untimestamp(&self, env: &Uiua) -> UiuaResult<Self> {
+        match self {
+            Value::Time(arr) => Ok(arr.convert_ref_with(Timestamp::secs).into()),
+            val => Err(env.error(format!(
+                "Cannot convert {} array from datetimes",
+                val.type_name()
+            ))),
+        }
+    }
     pub(crate) fn unparse(&self, env: &Uiua) -> UiuaResult<Self> {
         if self.rank() == 0 {
             return match self {
@@ -123,6 +177,13 @@ impl Value {
                     .collect();
                 Array::new(complexes.shape.clone(), new_data).into()
             }
+            Value::Time(times) => {
+                let new_data: CowSlice<Boxed> = (times.data.iter().map(|v| v.to_string()))
+                    .map(Value::from)
+                    .map(Boxed)
+                    .collect();
+                Array::new(times.shape.clone(), new_data).into()
+            }
             val => return Err(env.error(format!("Cannot unparse {} array", val.type_name()))),
         })
     }
@@ -137,6 +198,7 @@ impl<T: ArrayValue> Array<T> {
         if self.is_map() {
             self.take_map_keys();
         }
+        self.take_axes();
         self.shape = self.element_count().into();
     }
     pub(crate) fn deshape_depth(&mut self, mut depth: usize) {
@@ -144,6 +206,9 @@ impl<T: ArrayValue> Array<T> {
             self.take_map_keys();
         }
         depth = depth.min(self.rank());
+        if depth + 1 != self.rank() {
+            self.take_axes();
+        }
         let deshaped = self.shape.split_off(depth).into_iter().product();
         self.shape.push(deshaped);
     }
@@ -153,6 +218,7 @@ impl<T: ArrayValue> Array<T> {
     }
     pub(crate) fn fix_depth(&mut self, depth: usize) {
         let depth = depth.min(self.rank());
+        self.map_axes(|axes| axes.insert(depth, EcoString::new()));
         self.shape.insert(depth, 1);
         if depth == 0 {
             if let Some(keys) = self.map_keys_mut() {
@@ -165,6 +231,11 @@ impl<T: ArrayValue> Array<T> {
         if let Some(keys) = self.map_keys_mut() {
             keys.unfix();
         }
+        if self.shape.first() == Some(&1) {
+            self.map_axes(|axes| {
+                axes.remove(0);
+            });
+        }
         match self.shape.unfix() {
             Some(1) => Ok(()),
             Some(d) => Err(env.error(format!("Cannot unfix array with length {d}"))),
@@ -195,11 +266,15 @@ impl Value {
                     (0..max).map(|i| i as u8).collect()
                 } else {
                     validate_size::<f64>([max.unsigned_abs()], env)?;
-                    (0..max).map(|i| i as f64).collect()
+                    Array::from_iter((0..max).map(|i| i as f64))
+                        .integers()
+                        .into()
                 }
             } else {
                 validate_size::<f64>([max.unsigned_abs()], env)?;
-                (max..0).map(|i| i as f64).rev().collect()
+                Array::from_iter((max..0).map(|i| i as f64).rev())
+                    .integers()
+                    .into()
             });
         }
         if ishape.is_empty() {
@@ -209,7 +284,7 @@ impl Value {
         shape.push(shape.len());
         let data = range(&ishape, env)?;
         Ok(match data {
-            Ok(data) => Array::new(shape, data).into(),
+            Ok(data) => Array::new(shape, data).integers().into(),
             Err(data) => Array::new(shape, data).into(),
         })
     }
@@ -316,6 +391,7 @@ impl Value {
             |a| a.first(env).map(Into::into),
             |a| a.first(env).map(Into::into),
             |a| a.first(env).map(Into::into),
+            |a| a.first(env).map(Into::into),
         )
     }
     /// Get the last row of the value
@@ -332,6 +408,7 @@ impl Value {
             |a| a.last(env).map(Into::into),
             |a| a.last(env).map(Into::into),
             |a| a.last(env).map(Into::into),
+            |a| a.last(env).map(Into::into),
         )
     }
     pub(crate) fn undo_first(self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -343,6 +420,7 @@ impl Value {
                 |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| a.unfirst(b, env).map(Into::into),
+                |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| {
                     env.error(format!(
                         "Cannot unfirst {} into {}",
@@ -362,6 +440,7 @@ impl Value {
                 |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| a.unlast(b, env).map(Into::into),
+                |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| {
                     env.error(format!(
                         "Cannot unlast {} into {}",
@@ -383,6 +462,7 @@ impl<T: ArrayValue> Array<T> {
                 Ok(fill) => {
                     self.data.extend(repeat(fill).take(self.row_len()));
                     self.shape = rest.into();
+                    self.reset_meta_flags();
                     Ok(self)
                 }
                 Err(e) => Err(env
@@ -407,6 +487,7 @@ impl<T: ArrayValue> Array<T> {
                 Ok(fill) => {
                     self.data.extend(repeat(fill).take(self.row_len()));
                     self.shape = rest.into();
+                    self.reset_meta_flags();
                     Ok(self)
                 }
                 Err(e) => Err(env
@@ -444,6 +525,7 @@ impl Value {
             |a| a.reverse_depth(depth),
             |a| a.reverse_depth(depth),
             |a| a.reverse_depth(depth),
+            |a| a.reverse_depth(depth),
         )
     }
 }
@@ -498,6 +580,7 @@ impl Value {
             Array::transpose,
             Array::transpose,
             Array::transpose,
+            Array::transpose,
         )
     }
     pub(crate) fn transpose_depth(&mut self, depth: usize, amnt: i32) {
@@ -515,6 +598,7 @@ impl Value {
                     b.transpose_depth(depth, amnt);
                 }
             }
+            Value::Time(t) => t.transpose_depth(depth, amnt),
         }
     }
 }
@@ -542,11 +626,7 @@ impl<T: ArrayValue> Array<T> {
         let forward = amnt.is_positive();
         // Early return if any dimension is 0, because there are no elements
         if self.shape[depth..].iter().any(|&d| d == 0) || depth > 0 && self.shape[depth - 1] == 0 {
-            if forward {
-                self.shape[depth..].rotate_left(trans_count);
-            } else {
-                self.shape[depth..].rotate_right(trans_count);
-            }
+            self.rotate_shape(depth, trans_count, forward);
             return;
         }
         let square_matrix = trans_rank == 2 && self.shape[depth] == self.shape[depth + 1];
@@ -598,53 +678,140 @@ impl<T: ArrayValue> Array<T> {
             }
             data.clone_from_slice(&temp);
         }
+        self.rotate_shape(depth, trans_count, forward);
+    }
+    /// Rotate the shape and axis names after a transpose
+    fn rotate_shape(&mut self, depth: usize, count: usize, forward: bool) {
+        self.map_axes(|axes| {
+            let axes = &mut axes.make_mut()[depth..];
+            if forward {
+                axes.rotate_left(count);
+            } else {
+                axes.rotate_right(count);
+            }
+        });
         if forward {
-            self.shape[depth..].rotate_left(trans_count);
+            self.shape[depth..].rotate_left(count);
         } else {
-            self.shape[depth..].rotate_right(trans_count);
+            self.shape[depth..].rotate_right(count);
         }
     }
 }
 
 impl Value {
     /// Get the `rise` of the value
-    pub fn rise(&self) -> Array<f64> {
+    pub fn rise(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
         self.generic_ref(
-            Array::rise,
-            Array::rise,
-            Array::rise,
-            Array::rise,
-            Array::rise,
+            |a| a.rise(env),
+            |a| a.rise(env),
+            |a| a.rise(env),
+            |a| a.rise(env),
+            |a| a.rise(env),
+            |a| a.rise(env),
         )
     }
     /// Get the `fall` of the value
-    pub fn fall(&self) -> Array<f64> {
+    pub fn fall(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
+        self.generic_ref(
+            |a| a.fall(env),
+            |a| a.fall(env),
+            |a| a.fall(env),
+            |a| a.fall(env),
+            |a| a.fall(env),
+            |a| a.fall(env),
+        )
+    }
+    pub(crate) fn rise_depth(&self, depth: usize, env: &Uiua) -> UiuaResult<Array<f64>> {
+        self.generic_ref(
+            |a| a.grade_depth(depth, false, env),
+            |a| a.grade_depth(depth, false, env),
+            |a| a.grade_depth(depth, false, env),
+            |a| a.grade_depth(depth, false, env),
+            |a| a.grade_depth(depth, false, env),
+            |a| a.grade_depth(depth, false, env),
+        )
+    }
+    pub(crate) fn fall_depth(&self, depth: usize, env: &Uiua) -> UiuaResult<Array<f64>> {
         self.generic_ref(
-            Array::fall,
-            Array::fall,
-            Array::fall,
-            Array::fall,
-            Array::fall,
+            |a| a.grade_depth(depth, true, env),
+            |a| a.grade_depth(depth, true, env),
+            |a| a.grade_depth(depth, true, env),
+            |a| a.grade_depth(depth, true, env),
+            |a| a.grade_depth(depth, true, env),
+            |a| a.grade_depth(depth, true, env),
+        )
+    }
+    /// Sort the rows of the value by the rows of some keys
+    pub fn sort_by_key(&self, sorted: &Self, env: &Uiua) -> UiuaResult<Self> {
+        fn by<T>(keys: &Value, sorted: &Array<T>, env: &Uiua) -> UiuaResult<Value>
+        where
+            T: ArrayValue,
+            Value: From<Array<T>>,
+        {
+            keys.generic_ref(
+                |k| sorted.sort_by_key(k, env),
+                |k| sorted.sort_by_key(k, env),
+                |k| sorted.sort_by_key(k, env),
+                |k| sorted.sort_by_key(k, env),
+                |k| sorted.sort_by_key(k, env),
+                |k| sorted.sort_by_key(k, env),
+            )
+            .map(Into::into)
+        }
+        sorted.generic_ref(
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+        )
+    }
+    /// Group the rows of the value by the rows of some keys into a map array
+    pub fn group_by_key(&self, grouped: &Self, env: &Uiua) -> UiuaResult<Self> {
+        fn by<T>(keys: &Value, grouped: &Array<T>, env: &Uiua) -> UiuaResult<Value>
+        where
+            T: ArrayValue,
+            Value: From<Array<T>>,
+        {
+            keys.generic_ref(
+                |k| grouped.group_by_key::<f64>(k, env),
+                |k| grouped.group_by_key::<u8>(k, env),
+                |k| grouped.group_by_key::<Complex>(k, env),
+                |k| grouped.group_by_key::<char>(k, env),
+                |k| grouped.group_by_key::<Boxed>(k, env),
+                |k| grouped.group_by_key::<Timestamp>(k, env),
+            )
+        }
+        grouped.generic_ref(
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
+            |a| by(self, a, env),
         )
     }
     /// Sort the value ascending
-    pub fn sort_up(&mut self) {
+    pub fn sort_up(&mut self, env: &Uiua) -> UiuaResult {
         self.generic_mut_shallow(
-            Array::sort_up,
-            Array::sort_up,
-            Array::sort_up,
-            Array::sort_up,
-            Array::sort_up,
+            |a| a.sort_up(env),
+            |a| a.sort_up(env),
+            |a| a.sort_up(env),
+            |a| a.sort_up(env),
+            |a| a.sort_up(env),
+            |a| a.sort_up(env),
         )
     }
     /// Sort the value descending
-    pub fn sort_down(&mut self) {
+    pub fn sort_down(&mut self, env: &Uiua) -> UiuaResult {
         self.generic_mut_shallow(
-            Array::sort_down,
-            Array::sort_down,
-            Array::sort_down,
-            Array::sort_down,
-            Array::sort_down,
+            |a| a.sort_down(env),
+            |a| a.sort_down(env),
+            |a| a.sort_down(env),
+            |a| a.sort_down(env),
+            |a| a.sort_down(env),
+            |a| a.sort_down(env),
         )
     }
     /// `classify` the rows of the value
@@ -660,6 +827,7 @@ impl Value {
                 Array::classify,
                 Array::classify,
                 Array::classify,
+                Array::classify,
             )
             .into_iter()
             .collect();
@@ -676,6 +844,7 @@ impl Value {
             |a| a.classify_depth(depth),
             |a| a.classify_depth(depth),
             |a| a.classify_depth(depth),
+            |a| a.classify_depth(depth),
         );
         if let Some(map_keys) = map_keys {
             val.meta_mut().map_keys = Some(map_keys);
@@ -690,8 +859,27 @@ impl Value {
             |a| a.deduplicate(env),
             |a| a.deduplicate(env),
             |a| a.deduplicate(env),
+            |a| a.deduplicate(env),
         )
     }
+    /// `deduplicate` the rows of the value and get the index of each original row's unique row
+    ///
+    /// Selecting from the deduplicated value with the indices reconstructs the original.
+    pub fn deduplicate_inverse(&mut self, env: &Uiua) -> UiuaResult<Self> {
+        let map_keys = self.map_keys().cloned();
+        let mut inverse = match self {
+            Value::Num(a) => a.deduplicate_inverse(env)?,
+            Value::Byte(a) => a.deduplicate_inverse(env)?,
+            Value::Complex(a) => a.deduplicate_inverse(env)?,
+            Value::Char(a) => a.deduplicate_inverse(env)?,
+            Value::Box(a) => a.deduplicate_inverse(env)?,
+            Value::Time(a) => a.deduplicate_inverse(env)?,
+        };
+        if let Some(map_keys) = map_keys {
+            inverse.meta_mut().map_keys = Some(map_keys);
+        }
+        Ok(inverse.into())
+    }
     /// Mask the `unique` rows of the value
     pub fn unique(&self) -> Self {
         self.generic_ref(
@@ -700,6 +888,7 @@ impl Value {
             Array::unique,
             Array::unique,
             Array::unique,
+            Array::unique,
         )
         .into()
     }
@@ -711,84 +900,305 @@ impl Value {
             Array::count_unique,
             Array::count_unique,
             Array::count_unique,
+            Array::count_unique,
         )
     }
 }
 
+/// Above this many items, sorting is done in parallel
+const PAR_SORT_THRESHOLD: usize = 10_000;
+
+/// Above this many rows, grouping is done in parallel
+const PAR_GROUP_THRESHOLD: usize = 10_000;
+
+/// Group row indices by equal key rows, in order of each key's first appearance
+///
+/// Large key arrays are split into chunks that are grouped in parallel.
+/// The chunks' groups are then merged in order.
+fn group_indices<T: ArrayValue>(keys: &Array<T>) -> Vec<Vec<usize>> {
+    let group_range = |start: usize, end: usize| {
+        let mut classes = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for i in start..end {
+            let new_class = groups.len();
+            let class = *classes
+                .entry(ArrayCmpSlice(keys.row_slice(i)))
+                .or_insert(new_class);
+            if class == new_class {
+                groups.push(vec![i]);
+            } else {
+                groups[class].push(i);
+            }
+        }
+        groups
+    };
+    let row_count = keys.row_count();
+    if row_count <= PAR_GROUP_THRESHOLD {
+        return group_range(0, row_count);
+    }
+    let chunk_size = row_count.div_ceil(rayon::current_num_threads()).max(1);
+    let chunk_groups: Vec<Vec<Vec<usize>>> = (0..row_count.div_ceil(chunk_size))
+        .into_par_iter()
+        .map(|c| group_range(c * chunk_size, ((c + 1) * chunk_size).min(row_count)))
+        .collect();
+    let mut classes = HashMap::new();
+    let mut merged: Vec<Vec<usize>> = Vec::new();
+    for group in chunk_groups.into_iter().flatten() {
+        let new_class = merged.len();
+        let class = *classes
+            .entry(ArrayCmpSlice(keys.row_slice(group[0])))
+            .or_insert(new_class);
+        if class == new_class {
+            merged.push(group);
+        } else {
+            merged[class].extend(group);
+        }
+    }
+    merged
+}
+
+/// How many items are sorted or merged between checks for cancellation
+const SORT_CHUNK_LEN: usize = 1 << 14;
+
+/// Stably sort a slice, in parallel if it is large
+///
+/// The slice is sorted in chunks which are then merged pairwise,
+/// so that a cancelled sort stops between chunks.
+fn sort_by<T: Clone + Send>(
+    slice: &mut [T],
+    compare: impl Fn(&T, &T) -> Ordering + Sync,
+    env: &Uiua,
+) -> UiuaResult {
+    env.respect_cancellation()?;
+    let parallel = slice.len() > PAR_SORT_THRESHOLD;
+    let cancel = &env.rt.cancel;
+    let mut run_len = SORT_CHUNK_LEN;
+    if parallel {
+        let _ = slice.par_chunks_mut(run_len).try_for_each(|chunk| {
+            if cancel.is_cancelled() {
+                return Err(());
+            }
+            chunk.sort_by(&compare);
+            Ok(())
+        });
+    } else {
+        for chunk in slice.chunks_mut(run_len) {
+            env.respect_cancellation()?;
+            chunk.sort_by(&compare);
+        }
+    }
+    while run_len < slice.len() {
+        env.respect_cancellation()?;
+        if parallel {
+            let _ = slice.par_chunks_mut(2 * run_len).try_for_each(|pair| {
+                if cancel.is_cancelled() {
+                    return Err(());
+                }
+                merge_runs(pair, run_len, &compare);
+                Ok(())
+            });
+        } else {
+            for pair in slice.chunks_mut(2 * run_len) {
+                env.respect_cancellation()?;
+                merge_runs(pair, run_len, &compare);
+            }
+        }
+        run_len *= 2;
+    }
+    env.respect_cancellation()
+}
+
+/// Stably merge the sorted runs on either side of `mid`
+fn merge_runs<T: Clone>(slice: &mut [T], mid: usize, compare: impl Fn(&T, &T) -> Ordering) {
+    if mid == 0 || mid >= slice.len() || compare(&slice[mid - 1], &slice[mid]).is_le() {
+        return;
+    }
+    let (left, right) = slice.split_at(mid);
+    let mut merged = Vec::with_capacity(slice.len());
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if compare(&right[j], &left[i]).is_lt() {
+            merged.push(right[j].clone());
+            j += 1;
+        } else {
+            merged.push(left[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+    slice.clone_from_slice(&merged);
+}
+
+/// Get the indices that would sort some rows
+///
+/// Only the indices are moved while sorting, not the rows themselves.
+fn grade_rows<T: ArrayValue>(
+    data: &[T],
+    row_count: usize,
+    row_len: usize,
+    descending: bool,
+    env: &Uiua,
+) -> UiuaResult<Vec<usize>> {
+    let mut indices: Vec<usize> = (0..row_count).collect();
+    if row_len == 0 {
+        return Ok(indices);
+    }
+    let rows: Vec<&[T]> = data.chunks_exact(row_len).collect();
+    if descending {
+        sort_by(&mut indices, |&a, &b| row_cmp(rows[b], rows[a]), env)?;
+    } else {
+        sort_by(&mut indices, |&a, &b| row_cmp(rows[a], rows[b]), env)?;
+    }
+    Ok(indices)
+}
+
+fn row_cmp<T: ArrayValue>(a: &[T], b: &[T]) -> Ordering {
+    (a.iter().zip(b))
+        .map(|(a, b)| a.array_cmp(b))
+        .find(|x| x != &Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
 impl<T: ArrayValue> Array<T> {
     /// Get the `rise` of the array
-    pub fn rise(&self) -> Array<f64> {
-        if self.rank() == 0 {
-            return Array::scalar(0.0);
-        }
-        if self.element_count() == 0 {
-            return Array::default();
-        }
-        let mut indices = (0..self.row_count())
-            .map(|i| i as f64)
-            .collect::<EcoVec<_>>();
-        indices.make_mut().par_sort_by(|&a, &b| {
-            self.row_slice(a as usize)
-                .iter()
-                .zip(self.row_slice(b as usize))
-                .map(|(a, b)| a.array_cmp(b))
-                .find(|x| x != &Ordering::Equal)
-                .unwrap_or(Ordering::Equal)
-        });
-        indices.into()
+    pub fn rise(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
+        self.grade_depth(0, false, env)
     }
     /// Get the `fall` of the array
-    pub fn fall(&self) -> Array<f64> {
+    pub fn fall(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
+        self.grade_depth(0, true, env)
+    }
+    /// Get the indices that would sort the rows of the array
+    pub(crate) fn grade(&self, descending: bool, env: &Uiua) -> UiuaResult<Vec<usize>> {
         if self.rank() == 0 {
-            return Array::scalar(0.0);
-        }
-        if self.element_count() == 0 {
-            return Array::default();
-        }
-        let mut indices = (0..self.row_count())
-            .map(|i| i as f64)
-            .collect::<EcoVec<_>>();
-        indices.make_mut().par_sort_by(|&a, &b| {
-            self.row_slice(a as usize)
-                .iter()
-                .zip(self.row_slice(b as usize))
-                .map(|(a, b)| b.array_cmp(a))
-                .find(|x| x != &Ordering::Equal)
-                .unwrap_or(Ordering::Equal)
-        });
-        indices.into()
+            return Ok(vec![0]);
+        }
+        grade_rows(
+            &self.data,
+            self.row_count(),
+            self.row_len(),
+            descending,
+            env,
+        )
+    }
+    /// Grade the rows of each subarray at some depth
+    pub(crate) fn grade_depth(
+        &self,
+        depth: usize,
+        descending: bool,
+        env: &Uiua,
+    ) -> UiuaResult<Array<f64>> {
+        if depth >= self.rank() {
+            let shape = Shape::from(&self.shape[..]);
+            return Ok(Array::new(shape.clone(), eco_vec![0.0; shape.elements()]));
+        }
+        let mut shape = Shape::from(&self.shape[..=depth]);
+        let sub_shape = Shape::from(&self.shape[depth..]);
+        if sub_shape.elements() == 0 {
+            // Grading an empty array gives an empty list
+            shape[depth] = 0;
+            return Ok(Array::new(shape, EcoVec::new()));
+        }
+        let (row_count, row_len) = (sub_shape.row_count(), sub_shape.row_len());
+        let mut data = EcoVec::with_capacity(shape.elements());
+        for sub in self.data.chunks_exact(sub_shape.elements()) {
+            let indices = grade_rows(sub, row_count, row_len, descending, env)?;
+            data.extend(indices.into_iter().map(|i| i as f64));
+        }
+        Ok(Array::new(shape, data).integers())
     }
     /// Sort an array ascending
-    pub fn sort_up(&mut self) {
+    pub fn sort_up(&mut self, env: &Uiua) -> UiuaResult {
+        self.sort(false, env)
+    }
+    /// Sort an array descending
+    pub fn sort_down(&mut self, env: &Uiua) -> UiuaResult {
+        self.sort(true, env)
+    }
+    fn sort(&mut self, descending: bool, env: &Uiua) -> UiuaResult {
         if self.rank() == 0 || self.element_count() == 0 {
-            return;
+            return Ok(());
         }
         if self.rank() == 1 {
-            self.data.as_mut_slice().par_sort_by(|a, b| a.array_cmp(b));
-        } else {
-            let rise = self.rise();
-            let mut new_data = EcoVec::with_capacity(self.data.len());
-            for &i in &rise.data {
-                new_data.extend_from_slice(self.row_slice(i as usize));
+            let slice = self.data.as_mut_slice();
+            if descending {
+                sort_by(slice, |a, b| b.array_cmp(a), env)
+            } else {
+                sort_by(slice, |a, b| a.array_cmp(b), env)
             }
-            self.data = new_data.into();
+        } else {
+            self.data = self.select_rows(&self.grade(descending, env)?).into();
+            Ok(())
         }
     }
-    /// Sort an array descending
-    pub fn sort_down(&mut self) {
-        if self.rank() == 0 || self.element_count() == 0 {
-            return;
+    /// Get the rows at some indices, which must be in bounds
+    fn select_rows(&self, indices: &[usize]) -> EcoVec<T> {
+        let mut new_data = EcoVec::with_capacity(indices.len() * self.row_len());
+        for &i in indices {
+            new_data.extend_from_slice(self.row_slice(i));
         }
-        if self.rank() == 1 {
-            self.data.as_mut_slice().par_sort_by(|a, b| b.array_cmp(a));
-        } else {
-            let fall = self.fall();
-            let mut new_data = EcoVec::with_capacity(self.data.len());
-            for &i in &fall.data {
-                new_data.extend_from_slice(self.row_slice(i as usize));
-            }
-            self.data = new_data.into();
+        new_data
+    }
+    /// Sort the rows of this array by the rows of another
+    pub(crate) fn sort_by_key<U: ArrayValue>(
+        &self,
+        keys: &Array<U>,
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        if self.rank() == 0 {
+            return Err(env.error("Cannot sort a scalar"));
+        }
+        if keys.row_count() != self.row_count() {
+            return Err(env.error(format!(
+                "Cannot sort array with {} rows by {} keys",
+                self.row_count(),
+                keys.row_count()
+            )));
         }
+        let mut sorted = self.clone();
+        sorted.data = self.select_rows(&keys.grade(false, env)?).into();
+        if let Some(meta) = sorted.get_meta_mut() {
+            meta.map_keys = None;
+        }
+        Ok(sorted)
+    }
+    /// Group the rows of this array by the rows of another into a map array
+    ///
+    /// The map's keys are the unique keys in order of first appearance.
+    /// Its values are the boxed groups of rows.
+    pub(crate) fn group_by_key<U>(&self, keys: &Array<U>, env: &Uiua) -> UiuaResult<Value>
+    where
+        U: ArrayValue,
+        Value: From<Array<T>> + From<Array<U>>,
+    {
+        if self.rank() == 0 {
+            return Err(env.error("Cannot group a scalar"));
+        }
+        if keys.rank() == 0 {
+            return Err(env.error("Cannot group by a scalar key"));
+        }
+        if keys.row_count() != self.row_count() {
+            return Err(env.error(format!(
+                "Cannot group array with {} rows by {} keys",
+                self.row_count(),
+                keys.row_count()
+            )));
+        }
+        let groups = group_indices(keys);
+        let first_rows: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+        let mut key_shape = keys.shape.clone();
+        key_shape[0] = groups.len();
+        let unique_keys = Array::new(key_shape, keys.select_rows(&first_rows));
+        let mut boxes: Array<Boxed> = (groups.iter())
+            .map(|group| {
+                let mut shape = self.shape.clone();
+                shape[0] = group.len();
+                Boxed(Array::new(shape, self.select_rows(group)).into())
+            })
+            .collect();
+        boxes.map(unique_keys.into(), env)?;
+        Ok(boxes.into())
     }
     /// `classify` the rows of the array
     pub fn classify(&self) -> Vec<usize> {
@@ -849,28 +1259,43 @@ impl<T: ArrayValue> Array<T> {
     }
     /// `deduplicate` the rows of the array
     pub fn deduplicate(&mut self, env: &Uiua) -> UiuaResult {
+        self.deduplicate_inverse(env).map(drop)
+    }
+    /// `deduplicate` the rows of the array and get the index of each original row's unique row
+    ///
+    /// Rows are only hashed once. The map keys of the unique rows are kept.
+    pub fn deduplicate_inverse(&mut self, env: &Uiua) -> UiuaResult<Array<f64>> {
         if self.rank() == 0 {
-            return Ok(());
+            return Ok(Array::scalar(0.0).integers());
         }
-        let map_keys_unique = self
-            .take_map_keys()
-            .map(|keys| (keys.into_value(), self.unique()));
+        let map_keys = self.take_map_keys();
         let mut deduped = CowSlice::new();
-        let mut seen = HashSet::new();
-        let mut new_len = 0;
+        let mut classes = HashMap::new();
+        let mut inverse = EcoVec::with_capacity(self.row_count());
+        let mut unique = Vec::with_capacity(if map_keys.is_some() {
+            self.row_count()
+        } else {
+            0
+        });
         for row in self.row_slices() {
-            if seen.insert(ArrayCmpSlice(row)) {
+            let new_class = classes.len();
+            let class = *classes.entry(ArrayCmpSlice(row)).or_insert_with(|| {
                 deduped.extend_from_slice(row);
-                new_len += 1;
+                new_class
+            });
+            inverse.push(class as f64);
+            if map_keys.is_some() {
+                unique.push(class == new_class);
             }
         }
+        self.shape[0] = classes.len();
         self.data = deduped;
-        self.shape[0] = new_len;
-        if let Some((keys, unique)) = map_keys_unique {
-            let keys = Value::from(unique).keep(keys, env)?;
+        if let Some(keys) = map_keys {
+            let unique: Value = unique.into_iter().map(u8::from).collect();
+            let keys = unique.keep(keys.normalized(), env)?;
             self.map(keys, env)?;
         }
-        Ok(())
+        Ok(Array::from(inverse).integers())
     }
     /// Mask the `unique` rows of the array
     pub fn unique(&self) -> Array<u8> {
@@ -1049,7 +1474,7 @@ impl Value {
                         data.push(i);
                     }
                 }
-                Array::from(data).into()
+                Array::from(data).integers().into()
             }
             _ => {
                 validate_size::<f64>([total, counts.rank()], env)?;
@@ -1065,7 +1490,7 @@ impl Value {
                     }
                 }
                 let shape = Shape::from([total, counts.rank()].as_ref());
-                Array::new(shape, data).into()
+                Array::new(shape, data).integers().into()
             }
         })
     }
@@ -1271,6 +1696,7 @@ impl Value {
             Array::first_min_index,
             Array::first_min_index,
             Array::first_min_index,
+            Array::first_min_index,
             env,
         )
         .map(Into::into)
@@ -1282,6 +1708,7 @@ impl Value {
             Array::first_max_index,
             Array::first_max_index,
             Array::first_max_index,
+            Array::first_max_index,
             env,
         )
         .map(Into::into)
@@ -1293,6 +1720,7 @@ impl Value {
             Array::last_min_index,
             Array::last_min_index,
             Array::last_min_index,
+            Array::last_min_index,
             env,
         )
         .map(Into::into)
@@ -1304,6 +1732,7 @@ impl Value {
             Array::last_max_index,
             Array::last_max_index,
             Array::last_max_index,
+            Array::last_max_index,
             env,
         )
         .map(Into::into)
@@ -1499,11 +1928,37 @@ impl Value {
         })
     }
     pub(crate) fn from_json_string(json: &str, env: &Uiua) -> UiuaResult<Self> {
-        #[cfg(not(feature = "json5"))]
-        let json_value: serde_json::Value = serde_json::from_str(json).map_err(|e| env.error(e))?;
-        #[cfg(feature = "json5")]
-        let json_value: serde_json::Value = json5::from_str(json).map_err(|e| env.error(e))?;
-        Self::from_json_value(json_value, env)
+        Self::from_json_value(parse_json(json, env)?, env)
+    }
+    /// Decode JSON lines into a map of keys to columns
+    ///
+    /// Each line must be an object. Keys missing from a line are `null`.
+    pub(crate) fn from_json_lines(lines: &[String], env: &Uiua) -> UiuaResult<Self> {
+        let mut columns: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let serde_json::Value::Object(object) = parse_json(line, env)? else {
+                return Err(env.error(format!("JSON line {} is not an object", i + 1)));
+            };
+            for (key, value) in object {
+                let j = match columns.iter().position(|(k, _)| *k == key) {
+                    Some(j) => j,
+                    None => {
+                        columns.push((key, Vec::new()));
+                        columns.len() - 1
+                    }
+                };
+                let column = &mut columns[j].1;
+                column.resize(i, serde_json::Value::Null);
+                column.push(value);
+            }
+        }
+        let object = (columns.into_iter())
+            .map(|(key, mut column)| {
+                column.resize(lines.len(), serde_json::Value::Null);
+                (key, serde_json::Value::Array(column))
+            })
+            .collect();
+        Self::from_json_value(serde_json::Value::Object(object), env)
     }
     pub(crate) fn from_json_value(json_value: serde_json::Value, _env: &Uiua) -> UiuaResult<Self> {
         Ok(match json_value {
@@ -1563,6 +2018,13 @@ impl Value {
     }
 }
 
+fn parse_json(json: &str, env: &Uiua) -> UiuaResult<serde_json::Value> {
+    #[cfg(not(feature = "json5"))]
+    return serde_json::from_str(json).map_err(|e| env.error(e));
+    #[cfg(feature = "json5")]
+    return json5::from_str(json).map_err(|e| env.error(e));
+}
+
 impl Value {
     pub(crate) fn to_csv(&self, env: &Uiua) -> UiuaResult<String> {
         #[cfg(not(feature = "csv"))]
@@ -1645,6 +2107,7 @@ impl Value {
                                     Value::Byte(b) => sheet_row.add_cell(b.data[0] as f64),
                                     Value::Char(c) => sheet_row.add_cell(c.data[0].to_string()),
                                     Value::Complex(c) => sheet_row.add_cell(c.data[0].to_string()),
+                                    Value::Time(t) => sheet_row.add_cell(t.data[0].to_string()),
                                     Value::Box(b) => {
                                         let Boxed(b) = &b.data[0];
                                         if b.row_count() == 0 {
@@ -1691,6 +2154,49 @@ impl Value {
             })
         }
     }
+    /// Decode CSV records into a map of headers to columns of boxed strings
+    ///
+    /// Records with fewer fields than the header are filled with empty strings.
+    pub(crate) fn from_csv_columns(
+        _header: &str,
+        _records: &[String],
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
+        #[cfg(not(feature = "csv"))]
+        return Err(env.error("CSV support is not enabled in this environment"));
+        #[cfg(feature = "csv")]
+        {
+            let parse = |csv: &str| -> UiuaResult<Vec<csv::StringRecord>> {
+                (csv::ReaderBuilder::new().has_headers(false).flexible(true))
+                    .from_reader(csv.as_bytes())
+                    .records()
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| env.error(e))
+            };
+            let header = parse(_header)?.into_iter().next().unwrap_or_default();
+            let mut columns = vec![EcoVec::with_capacity(_records.len()); header.len()];
+            for record in parse(&_records.join("\n"))? {
+                if record.len() > header.len() {
+                    return Err(env.error(format!(
+                        "CSV record has {} fields, but the header has {}",
+                        record.len(),
+                        header.len()
+                    )));
+                }
+                for (i, column) in columns.iter_mut().enumerate() {
+                    column.push(Boxed(record.get(i).unwrap_or("").into()));
+                }
+            }
+            let keys: EcoVec<Boxed> = header.iter().map(|key| Boxed(key.into())).collect();
+            let mut values = Value::from_row_values_infallible(
+                (columns.into_iter())
+                    .map(|column| Value::from(Array::from(column)))
+                    .collect::<Vec<_>>(),
+            );
+            values.map(keys.into(), env)?;
+            Ok(values)
+        }
+    }
     pub(crate) fn from_xlsx(_xlsx: &[u8], env: &mut Uiua) -> UiuaResult<Self> {
         #[cfg(not(feature = "calamine"))]
         return Err(env.error("XLSX decoding is not enabled in this environment"));
@@ -1746,6 +2252,8 @@ fn f64_repr(n: f64) -> String {
         "π".into()
     } else if abs == TAU {
         "τ".into()
+    } else if abs == f64::INFINITY {
+        "∞".into()
     } else {
         abs.to_string()
     };
@@ -1756,11 +2264,60 @@ fn f64_repr(n: f64) -> String {
     }
 }
 
+/// Escape characters for a string or character literal
+///
+/// Uiua code is lexed by grapheme, so characters that would combine
+/// with the surrounding code into a single grapheme are escaped too.
+fn escape_chars(
+    chars: &[char],
+    escape: impl Fn(char) -> String,
+    before: &str,
+    after: &str,
+) -> String {
+    let mut pieces: Vec<String> = chars.iter().map(|&c| escape(c)).collect();
+    let merges = |a: &str, b: &str| {
+        let (Some(a), Some(b)) = (a.chars().last(), b.chars().next()) else {
+            return false;
+        };
+        [a, b].iter().collect::<String>().graphemes(true).count() == 1
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..pieces.len() {
+            if pieces[i].chars().count() > 1 {
+                continue;
+            }
+            let prev = if i == 0 { before } else { &pieces[i - 1] };
+            let next = pieces.get(i + 1).map_or(after, String::as_str);
+            if merges(prev, &pieces[i]) || merges(&pieces[i], next) {
+                pieces[i] = format!("\\u{{{:x}}}", chars[i] as u32);
+                changed = true;
+            }
+        }
+    }
+    pieces.concat()
+}
+
 impl Value {
     /// Get the `repr` of a value
     pub fn representation(&self) -> String {
         const MAX_SINGLE_LINE_LEN: usize = 40;
         let mut s = match self.rank() {
+            // Empty arrays are written as a reshaped scalar so they keep their shape and type
+            _ if self.element_count() == 0
+                && (self.rank() > 1 || matches!(self, Value::Complex(_) | Value::Time(_))) =>
+            {
+                let proto = match self {
+                    Value::Num(_) | Value::Byte(_) => "0",
+                    Value::Complex(_) => "i",
+                    Value::Char(_) => "@a",
+                    Value::Box(_) => "□0",
+                    Value::Time(_) => "timestamp 0",
+                };
+                let shape = Value::from_iter(self.shape().iter().map(|&d| d as f64));
+                format!("↯{} {proto}", shape.representation())
+            }
             0 => match self {
                 Value::Num(arr) => {
                     let n = arr.data[0];
@@ -1794,17 +2351,28 @@ impl Value {
                         format!("ℂ{} {}", f64_repr(c.im), f64_repr(c.re))
                     }
                 }
-                Value::Char(arr) => {
-                    let c = arr.data[0];
-                    match c {
-                        ' ' => "@\\s".into(),
-                        c => c.grid_string(false),
+                Value::Char(arr) => match arr.data[0] {
+                    ' ' => "@\\s".into(),
+                    c => {
+                        let escape = |c| match c {
+                            char::MAX => "\\_".into(),
+                            WILDCARD_CHAR => "\\W".into(),
+                            c => format_char_inner(c),
+                        };
+                        format!("@{}", escape_chars(&[c], escape, "@", " "))
                     }
-                }
+                },
                 Value::Box(arr) => format!("□{}", arr.data[0].0.representation()),
+                Value::Time(arr) => format!("timestamp {:?}", arr.data[0].to_string()),
             },
             1 => match self {
-                Value::Char(arr) => format!("{:?}", arr.data.iter().collect::<String>()),
+                Value::Char(arr) => {
+                    let escape = |c: char| match c {
+                        '\'' => "'".into(),
+                        c => c.escape_debug().to_string(),
+                    };
+                    format!("\"{}\"", escape_chars(&arr.data, escape, "\"", "\""))
+                }
                 Value::Box(arr) => {
                     let mut s = '{'.to_string();
                     for (i, v) in arr.data.iter().enumerate() {
@@ -1847,7 +2415,8 @@ impl Value {
             }
         };
         if let Some(map_keys) = self.map_keys() {
-            s = format!("map {} {}", map_keys.clone().normalized(), s);
+            let keys = map_keys.clone().normalized();
+            s = format!("map {} {}", keys.representation(), s);
         }
         if let Some(label) = &self.meta().label {
             s = format!("${label} {s}");
@@ -1855,3 +2424,114 @@ impl Value {
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_sort() {
+        let env = Uiua::with_safe_sys();
+        // Enough items for several parallel chunks, plus a ragged one
+        let len = 3 * SORT_CHUNK_LEN + 123;
+        let nums: Vec<f64> = (0..len).map(|i| ((i * 7919) % 1000) as f64).collect();
+        let mut expected = nums.clone();
+        expected.sort_by(|a, b| a.array_cmp(b));
+        let mut arr = Array::<f64>::from_iter(nums.iter().copied());
+        arr.sort_up(&env).unwrap();
+        assert_eq!(arr.data.as_slice(), expected.as_slice());
+        // Grading is stable
+        let grade = Array::<f64>::from_iter(nums.iter().copied())
+            .grade(false, &env)
+            .unwrap();
+        let mut expected: Vec<usize> = (0..len).collect();
+        expected.sort_by(|&a, &b| nums[a].array_cmp(&nums[b]));
+        assert_eq!(grade, expected);
+    }
+
+    #[test]
+    fn cancelled_sort() {
+        let env = Uiua::with_safe_sys();
+        env.cancel_token().cancel();
+        for len in [10, PAR_SORT_THRESHOLD + 1] {
+            let mut arr = Array::<f64>::from_iter((0..len).rev().map(|i| i as f64));
+            let err = arr.sort_up(&env).unwrap_err();
+            assert!(err.is_interrupt(), "{err}");
+            let err = arr.grade(true, &env).unwrap_err();
+            assert!(err.is_interrupt(), "{err}");
+        }
+    }
+
+    mod repr {
+        use proptest::{collection::vec, prelude::*};
+
+        use crate::*;
+
+        fn array<T: ArrayValue>(
+            elem: impl Strategy<Value = T> + Clone,
+        ) -> impl Strategy<Value = Array<T>> {
+            vec(0usize..4, 0..4).prop_flat_map(move |shape| {
+                let len: usize = shape.iter().product();
+                vec(elem.clone(), len).prop_map(move |data| {
+                    Array::new(
+                        shape.as_slice(),
+                        data.into_iter().collect::<ecow::EcoVec<_>>(),
+                    )
+                })
+            })
+        }
+
+        fn num() -> impl Strategy<Value = f64> + Clone {
+            prop_oneof![
+                (-1000i32..1000).prop_map(f64::from),
+                -1e6..1e6,
+                any::<f64>(),
+                Just(f64::INFINITY),
+                Just(f64::NEG_INFINITY),
+                Just(f64::NAN),
+                Just(std::f64::consts::PI),
+            ]
+        }
+
+        fn value() -> impl Strategy<Value = Value> {
+            let leaf = prop_oneof![
+                array(num()).prop_map(Value::from),
+                array(any::<u8>()).prop_map(Value::from),
+                array((num(), num()).prop_map(|(re, im)| Complex::new(re, im)))
+                    .prop_map(Value::from),
+                array(any::<char>()).prop_map(Value::from),
+            ];
+            let val = leaf.prop_recursive(3, 32, 4, |inner| {
+                array(inner.prop_map(Boxed)).prop_map(Value::from)
+            });
+            (val, proptest::option::of("[a-z]{1,4}"), any::<bool>()).prop_map(
+                |(mut val, label, map)| {
+                    if map && val.rank() > 0 && val.row_count() > 0 {
+                        let keys = Value::from_iter((0..val.row_count()).map(|i| i as f64));
+                        val.map(keys, &Uiua::with_safe_sys()).unwrap();
+                    }
+                    val.meta_mut().label = label.map(Into::into);
+                    val
+                },
+            )
+        }
+
+        proptest! {
+            #[test]
+            fn round_trip(val in value()) {
+                let repr = val.representation();
+                let mut env = Uiua::with_safe_sys();
+                env.run_str(&format!("# Experimental!\n{repr}"))
+                    .map_err(|e| TestCaseError::fail(format!("{repr}\n{}", e.report())))?;
+                let parsed = env.pop(()).unwrap();
+                prop_assert_eq!(&parsed, &val, "{}", repr);
+                prop_assert_eq!(parsed.meta().label.as_ref(), val.meta().label.as_ref(), "{}", repr);
+                prop_assert_eq!(
+                    parsed.map_keys().map(|k| k.clone().normalized()),
+                    val.map_keys().map(|k| k.clone().normalized()),
+                    "{}", repr
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Per-axis padding modes for fills
+
+use std::{fmt, sync::Arc};
+
+use ecow::EcoVec;
+
+use crate::{
+    algorithm::{op_bytes_retry_fill, validate_size},
+    array::{Array, ArrayValue},
+    value::Value,
+    FunctionId, Primitive, Shape, Uiua, UiuaResult,
+};
+
+/// How to pad an axis when indexing past its bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadMode {
+    /// Use the fill value
+    Fill,
+    /// Repeat the nearest edge element
+    Clamp,
+    /// Wrap around to the other side
+    Wrap,
+    /// Mirror the elements, including the edge element
+    Reflect,
+}
+
+impl PadMode {
+    const ALL: [Self; 4] = [Self::Fill, Self::Clamp, Self::Wrap, Self::Reflect];
+    fn name(&self) -> &'static str {
+        match self {
+            PadMode::Fill => "fill",
+            PadMode::Clamp => "clamp",
+            PadMode::Wrap => "wrap",
+            PadMode::Reflect => "reflect",
+        }
+    }
+    /// Get the index of the element used at some index along an axis of the given length
+    ///
+    /// Returns `None` if the fill value should be used.
+    pub fn source(&self, i: isize, len: usize) -> Option<usize> {
+        if (0..len as isize).contains(&i) {
+            return Some(i as usize);
+        }
+        if len == 0 {
+            return None;
+        }
+        let len = len as isize;
+        Some(match self {
+            PadMode::Fill => return None,
+            PadMode::Clamp => i.clamp(0, len - 1),
+            PadMode::Wrap => i.rem_euclid(len),
+            PadMode::Reflect => {
+                let i = i.rem_euclid(2 * len);
+                if i < len {
+                    i
+                } else {
+                    2 * len - 1 - i
+                }
+            }
+        } as usize)
+    }
+}
+
+impl fmt::Display for PadMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Get the padding mode for an axis
+///
+/// The last mode applies to all remaining axes.
+fn axis_mode(modes: &[PadMode], axis: usize) -> PadMode {
+    (modes.get(axis).or(modes.last()).copied()).unwrap_or(PadMode::Fill)
+}
+
+/// Resolve an index along an axis of the given length
+///
+/// Without padding modes, negative indices count from the end.
+/// With them, indices outside the axis, including negative ones, are resolved by the axis's mode.
+/// Returns `None` if the fill value should be used.
+pub(crate) fn resolve_index(
+    modes: Option<&[PadMode]>,
+    axis: usize,
+    i: isize,
+    len: usize,
+) -> Option<usize> {
+    if let Some(modes) = modes {
+        return axis_mode(modes, axis).source(i, len);
+    }
+    let i = if i < 0 { i + len as isize } else { i };
+    (0..len as isize).contains(&i).then_some(i as usize)
+}
+
+fn parse_modes(spec: &Value, env: &Uiua) -> UiuaResult<Arc<[PadMode]>> {
+    const REQUIREMENT: &str = "Padding modes must be a string or a list of strings";
+    let names = if let Value::Char(arr) = spec {
+        if arr.rank() <= 1 {
+            vec![spec.as_string(env, REQUIREMENT)?]
+        } else {
+            spec.as_string_list(env, REQUIREMENT)?
+        }
+    } else {
+        spec.as_string_list(env, REQUIREMENT)?
+    };
+    if names.is_empty() {
+        return Err(env.error("Padding modes cannot be empty"));
+    }
+    (names.iter())
+        .map(|name| {
+            PadMode::ALL
+                .into_iter()
+                .find(|mode| mode.name() == name)
+                .ok_or_else(|| {
+                    env.error(format!(
+                        "Unknown padding mode {name:?}. Valid modes are {}",
+                        PadMode::ALL
+                            .map(|mode| format!("{:?}", mode.name()))
+                            .join(", ")
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Call a function with padding modes set
+pub fn pad_mode(env: &mut Uiua) -> UiuaResult {
+    let modes = env.pop_function()?;
+    let f = env.pop_function()?;
+    let sig = modes.signature();
+    if sig.outputs != 1 {
+        return Err(env.error(format!(
+            "{}'s modes function must return 1 value, but its signature is {sig}",
+            Primitive::PadMode.format()
+        )));
+    }
+    env.call(modes)?;
+    let spec = env.pop("padding modes")?;
+    let modes = parse_modes(&spec, env)?;
+    env.with_pad_modes(modes, |env| {
+        if matches!(f.id, FunctionId::Named(_)) {
+            env.use_fill();
+        }
+        env.call(f)
+    })
+}
+
+/// `take` with padding modes
+pub fn take(modes: Arc<[PadMode]>, env: &mut Uiua) -> UiuaResult {
+    let index = env.pop(1)?;
+    let from = env.pop(2)?;
+    if from.rank() == 0 {
+        return Err(env.error("Cannot take from scalar"));
+    }
+    let index = index.as_ints_or_infs(env, "Index must be a list of integers or infinity")?;
+    if index.len() > from.rank() {
+        return Err(env.error(format!(
+            "Cannot take from rank {} array with index of length {}",
+            from.rank(),
+            index.len()
+        )));
+    }
+    let maps: Vec<Vec<Option<usize>>> = (from.shape().iter().enumerate())
+        .map(|(axis, &len)| {
+            let mode = axis_mode(&modes, axis);
+            match index.get(axis) {
+                Some(&Ok(n)) if n >= 0 => (0..n).map(|i| mode.source(i, len)).collect(),
+                Some(&Ok(n)) => {
+                    let start = len as isize + n;
+                    (start..len as isize).map(|i| mode.source(i, len)).collect()
+                }
+                _ => (0..len).map(Some).collect(),
+            }
+        })
+        .collect();
+    let taken = gather_axes(from, &maps, env)?;
+    env.push(taken);
+    Ok(())
+}
+
+/// `windows` with padding modes
+pub fn windows(modes: Arc<[PadMode]>, env: &mut Uiua) -> UiuaResult {
+    let size = env.pop(1)?;
+    let from = env.pop(2)?;
+    let windows = padded_windows(&modes, &size, from, env)?;
+    env.push(windows);
+    Ok(())
+}
+
+/// Get the `windows` of a value with padding modes
+///
+/// Each windowed axis is padded so that there is one window for each of its elements.
+pub fn padded_windows(
+    modes: &[PadMode],
+    size: &Value,
+    from: Value,
+    env: &Uiua,
+) -> UiuaResult<Value> {
+    let size_spec = size.as_ints(env, "Window size must be an integer or list of integers")?;
+    if size_spec.len() > from.rank() {
+        return Err(env.error(format!(
+            "Window size {size_spec:?} has too many axes for shape {}",
+            from.shape()
+        )));
+    }
+    let mut padded_spec = Vec::with_capacity(size_spec.len());
+    let maps: Vec<Vec<Option<usize>>> = (from.shape().iter().enumerate())
+        .map(|(axis, &len)| {
+            let mode = axis_mode(modes, axis);
+            let Some(&s) = size_spec.get(axis) else {
+                return Ok((0..len).map(Some).collect());
+            };
+            if s == 0 {
+                return Err(env.error("Window size cannot be zero"));
+            }
+            let s = if s >= 0 { s } else { len as isize + 1 + s };
+            if s <= 0 {
+                return Err(env.error(format!(
+                    "Window size {} is too large for axis of length {len}",
+                    size_spec[axis]
+                )));
+            }
+            padded_spec.push(s);
+            let before = (s - 1) / 2;
+            let after = s - 1 - before;
+            Ok((-before..len as isize + after)
+                .map(|i| mode.source(i, len))
+                .collect())
+        })
+        .collect::<UiuaResult<_>>()?;
+    let padded = gather_axes(from, &maps, env)?;
+    let padded_spec: Value = padded_spec.into_iter().map(|s| s as f64).collect();
+    padded_spec.windows(&padded, env)
+}
+
+/// Build an array from the elements at the given indices along each axis
+///
+/// `None` indices use the fill value.
+fn gather_axes(val: Value, maps: &[Vec<Option<usize>>], env: &Uiua) -> UiuaResult<Value> {
+    Ok(match val {
+        Value::Num(arr) => gather(&arr, maps, env)?.into(),
+        Value::Byte(arr) => op_bytes_retry_fill(
+            arr,
+            |arr| gather(&arr, maps, env).map(Into::into),
+            |arr| gather(&arr, maps, env).map(Into::into),
+        )?,
+        Value::Complex(arr) => gather(&arr, maps, env)?.into(),
+        Value::Char(arr) => gather(&arr, maps, env)?.into(),
+        Value::Box(arr) => gather(&arr, maps, env)?.into(),
+        Value::Time(arr) => gather(&arr, maps, env)?.into(),
+    })
+}
+
+fn gather<T: ArrayValue>(
+    arr: &Array<T>,
+    maps: &[Vec<Option<usize>>],
+    env: &Uiua,
+) -> UiuaResult<Array<T>> {
+    let shape: Shape = maps.iter().map(Vec::len).collect();
+    let elem_count = validate_size::<T>(shape.iter().copied(), env)?;
+    let fill = if maps.iter().flatten().any(Option::is_none) {
+        Some(T::get_scalar_fill(env).map_err(|e| {
+            env.error(format!(
+                "Cannot pad with the {} mode outside a fill context{e}",
+                PadMode::Fill
+            ))
+            .fill()
+        })?)
+    } else {
+        None
+    };
+    let mut data = EcoVec::with_capacity(elem_count);
+    let mut index = vec![0; maps.len()];
+    for i in 0..elem_count {
+        shape.flat_to_dims(i, &mut index);
+        let mut src = 0;
+        let mut in_bounds = true;
+        for ((map, &d), &len) in maps.iter().zip(&index).zip(arr.shape.dims()) {
+            match map[d] {
+                Some(j) => src = src * len + j,
+                None => {
+                    in_bounds = false;
+                    break;
+                }
+            }
+        }
+        data.push(if in_bounds {
+            arr.data[src].clone()
+        } else {
+            fill.clone().unwrap()
+        });
+    }
+    Ok(Array::new(shape, data))
+}
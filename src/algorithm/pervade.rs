@@ -8,10 +8,10 @@ use std::{
     slice::{self, ChunksExact},
 };
 
-use ecow::eco_vec;
+use ecow::{eco_vec, EcoVec};
 
 use crate::{array::*, Uiua, UiuaError, UiuaResult};
-use crate::{Complex, Shape};
+use crate::{Complex, Shape, Timestamp};
 
 use super::fill_array_shapes;
 
@@ -168,6 +168,34 @@ where
     Ok(Array::new(shape, data))
 }
 
+/// Apply a checked kernel to byte arrays without promoting them to numbers
+///
+/// Only arrays with the same shape, or where one is a scalar, are handled.
+/// Returns `None` for other shapes or if any result does not fit in a byte.
+pub fn bin_pervade_bytes(
+    a: &Array<u8>,
+    b: &Array<u8>,
+    f: fn(u8, u8) -> Option<u8>,
+) -> Option<Array<u8>> {
+    let (shape, data) = if a.shape == b.shape {
+        let data: Option<EcoVec<u8>> = (a.data.iter().zip(&b.data))
+            .map(|(&a, &b)| f(a, b))
+            .collect();
+        (a.shape.clone(), data?)
+    } else if a.rank() == 0 {
+        let a = a.data[0];
+        let data: Option<EcoVec<u8>> = b.data.iter().map(|&b| f(a, b)).collect();
+        (b.shape.clone(), data?)
+    } else if b.rank() == 0 {
+        let b = b.data[0];
+        let data: Option<EcoVec<u8>> = a.data.iter().map(|&a| f(a, b)).collect();
+        (a.shape.clone(), data?)
+    } else {
+        return None;
+    };
+    Some(Array::new(shape, data))
+}
+
 pub fn bin_pervade_recursive<A, B, C, F>(
     a: &A,
     b: &B,
@@ -745,6 +773,9 @@ pub mod add {
     pub fn bool_bool(a: u8, b: u8) -> u8 {
         b + a
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        b.checked_add(a)
+    }
     pub fn byte_num(a: u8, b: f64) -> f64 {
         b + f64::from(a)
     }
@@ -769,6 +800,18 @@ pub mod add {
     pub fn char_byte(a: char, b: u8) -> char {
         char::from_u32((b as i64 + a as i64) as u32).unwrap_or('\0')
     }
+    pub fn num_time(a: f64, b: Timestamp) -> Timestamp {
+        b.add_secs(a)
+    }
+    pub fn time_num(a: Timestamp, b: f64) -> Timestamp {
+        a.add_secs(b)
+    }
+    pub fn byte_time(a: u8, b: Timestamp) -> Timestamp {
+        b.add_secs(a.into())
+    }
+    pub fn time_byte(a: Timestamp, b: u8) -> Timestamp {
+        a.add_secs(b.into())
+    }
     pub fn error<T: Display>(a: T, b: T, env: &Uiua) -> UiuaError {
         env.error(format!("Cannot add {a} and {b}"))
     }
@@ -782,6 +825,9 @@ pub mod sub {
     pub fn byte_byte(a: u8, b: u8) -> f64 {
         f64::from(b) - f64::from(a)
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        b.checked_sub(a)
+    }
     pub fn byte_num(a: u8, b: f64) -> f64 {
         b - f64::from(a)
     }
@@ -803,6 +849,15 @@ pub mod sub {
     pub fn byte_char(a: u8, b: char) -> char {
         char::from_u32(((b as i64) - (a as i64)) as u32).unwrap_or('\0')
     }
+    pub fn num_time(a: f64, b: Timestamp) -> Timestamp {
+        b.add_secs(-a)
+    }
+    pub fn byte_time(a: u8, b: Timestamp) -> Timestamp {
+        b.add_secs(-f64::from(a))
+    }
+    pub fn time_time(a: Timestamp, b: Timestamp) -> f64 {
+        b.secs_since(a)
+    }
     pub fn error<T: Display>(a: T, b: T, env: &Uiua) -> UiuaError {
         env.error(format!("Cannot subtract {a} from {b}"))
     }
@@ -856,6 +911,9 @@ pub mod mul {
     pub fn bool_bool(a: u8, b: u8) -> u8 {
         b & a
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        b.checked_mul(a)
+    }
     pub fn byte_num(a: u8, b: f64) -> f64 {
         b * f64::from(a)
     }
@@ -936,6 +994,9 @@ pub mod modulus {
     pub fn byte_byte(a: u8, b: u8) -> f64 {
         num_num(a.into(), b.into())
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        b.checked_rem(a)
+    }
     pub fn byte_num(a: u8, b: f64) -> f64 {
         num_num(a.into(), b)
     }
@@ -1034,6 +1095,9 @@ pub mod max {
     pub fn bool_bool(a: u8, b: u8) -> u8 {
         a | b
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        Some(a.max(b))
+    }
     pub fn char_char(a: char, b: char) -> char {
         a.max(b)
     }
@@ -1065,6 +1129,9 @@ pub mod min {
     pub fn bool_bool(a: u8, b: u8) -> u8 {
         a & b
     }
+    pub fn byte_checked(a: u8, b: u8) -> Option<u8> {
+        Some(a.min(b))
+    }
     pub fn char_char(a: char, b: char) -> char {
         a.min(b)
     }
@@ -1085,6 +1152,105 @@ pub mod min {
     }
 }
 
+/// Get the integer a number represents for bitwise operations
+///
+/// Numbers that are not integers in the range of an `i64` give `None`.
+fn bit_int(n: f64) -> Option<i64> {
+    (n.fract() == 0.0 && n.abs() < 9223372036854775808.0).then_some(n as i64)
+}
+
+pub mod bit_not {
+    use super::*;
+    pub fn num(a: f64) -> f64 {
+        bit_int(a).map_or(f64::NAN, |a| !a as f64)
+    }
+    pub fn byte(a: u8) -> f64 {
+        num(a.into())
+    }
+    pub fn error<T: Display>(a: T, env: &Uiua) -> UiuaError {
+        env.error(format!("Cannot bitwise not {a}"))
+    }
+}
+
+macro_rules! bitwise_impl {
+    ($name:ident, $verb:literal, $op:tt) => {
+        pub mod $name {
+            use super::*;
+            pub fn num_num(a: f64, b: f64) -> f64 {
+                match (bit_int(a), bit_int(b)) {
+                    (Some(a), Some(b)) => (a $op b) as f64,
+                    _ => f64::NAN,
+                }
+            }
+            pub fn byte_byte(a: u8, b: u8) -> u8 {
+                a $op b
+            }
+            pub fn num_byte(a: f64, b: u8) -> f64 {
+                num_num(a, b.into())
+            }
+            pub fn byte_num(a: u8, b: f64) -> f64 {
+                num_num(a.into(), b)
+            }
+            pub fn error<T: Display>(a: T, b: T, env: &Uiua) -> UiuaError {
+                env.error(format!(concat!("Cannot bitwise ", $verb, " {} and {}"), a, b))
+            }
+        }
+    };
+}
+
+bitwise_impl!(bit_and, "and", &);
+bitwise_impl!(bit_or, "or", |);
+bitwise_impl!(bit_xor, "xor", ^);
+
+/// Shift an integer left by some number of bits, or right if the shift is negative
+///
+/// Right shifts are arithmetic, so they round toward negative infinity.
+fn shift_int(n: f64, by: f64) -> f64 {
+    match (bit_int(n), bit_int(by)) {
+        (Some(_), Some(by)) if by >= 0 => n * 2f64.powi(by.min(2048) as i32),
+        (Some(n), Some(by)) => (n >> by.unsigned_abs().min(63)) as f64,
+        _ => f64::NAN,
+    }
+}
+
+pub mod shift_left {
+    use super::*;
+    pub fn num_num(a: f64, b: f64) -> f64 {
+        shift_int(b, a)
+    }
+    pub fn byte_byte(a: u8, b: u8) -> f64 {
+        num_num(a.into(), b.into())
+    }
+    pub fn num_byte(a: f64, b: u8) -> f64 {
+        num_num(a, b.into())
+    }
+    pub fn byte_num(a: u8, b: f64) -> f64 {
+        num_num(a.into(), b)
+    }
+    pub fn error<T: Display>(a: T, b: T, env: &Uiua) -> UiuaError {
+        env.error(format!("Cannot shift {b} left by {a}"))
+    }
+}
+
+pub mod shift_right {
+    use super::*;
+    pub fn num_num(a: f64, b: f64) -> f64 {
+        shift_int(b, -a)
+    }
+    pub fn byte_byte(a: u8, b: u8) -> u8 {
+        b.checked_shr(a.into()).unwrap_or(0)
+    }
+    pub fn num_byte(a: f64, b: u8) -> f64 {
+        num_num(a, b.into())
+    }
+    pub fn byte_num(a: u8, b: f64) -> f64 {
+        num_num(a.into(), b)
+    }
+    pub fn error<T: Display>(a: T, b: T, env: &Uiua) -> UiuaError {
+        env.error(format!("Cannot shift {b} right by {a}"))
+    }
+}
+
 pub trait PervasiveInput: IntoIterator + Sized {
     type OwnedItem: Clone;
     fn len(&self) -> usize;
@@ -0,0 +1,160 @@
+//! Algorithms for ragged arrays of boxes
+
+use ecow::{eco_vec, EcoVec};
+
+use crate::{Array, ArrayValue, Boxed, Shape, Uiua, UiuaResult, Value};
+
+impl Value {
+    /// Unbox the contents of a box array into a single array, padding them with the fill value
+    pub fn rectangularize(self, env: &Uiua) -> UiuaResult<Self> {
+        let arr = match self {
+            Value::Box(arr) => arr,
+            val => return Ok(val),
+        };
+        if arr.rank() == 0 {
+            return Ok(arr.data.into_iter().next().unwrap().0);
+        }
+        let Some(first) = arr.data.first() else {
+            return Ok(Array::<f64>::new(arr.shape, EcoVec::new()).into());
+        };
+        let padded = match &first.0 {
+            Value::Num(_) => contents(&arr, |val| match val {
+                Value::Num(arr) => Some(arr),
+                _ => None,
+            })
+            .and_then(|cells| pad_cells(&cells, &arr.shape, env))
+            .map(Value::from),
+            Value::Byte(_) => contents(&arr, |val| match val {
+                Value::Byte(arr) => Some(arr),
+                _ => None,
+            })
+            .and_then(|cells| pad_cells(&cells, &arr.shape, env))
+            .map(Value::from),
+            Value::Complex(_) => contents(&arr, |val| match val {
+                Value::Complex(arr) => Some(arr),
+                _ => None,
+            })
+            .and_then(|cells| pad_cells(&cells, &arr.shape, env))
+            .map(Value::from),
+            Value::Char(_) => contents(&arr, |val| match val {
+                Value::Char(arr) => Some(arr),
+                _ => None,
+            })
+            .and_then(|cells| pad_cells(&cells, &arr.shape, env))
+            .map(Value::from),
+            Value::Box(_) => None,
+            Value::Time(_) => None,
+        };
+        if let Some(padded) = padded {
+            return Ok(padded);
+        }
+        // Mixed types and ranks are combined row by row
+        let mut val = Value::from_row_values(arr.data.iter().map(|b| b.0.clone()), env)?;
+        let shape = val.shape_mut();
+        shape.remove(0);
+        for (i, &dim) in arr.shape.iter().enumerate() {
+            shape.insert(i, dim);
+        }
+        val.validate_shape();
+        Ok(val)
+    }
+    /// Transpose a list of boxed arrays of differing lengths
+    ///
+    /// The nth box of the result contains the nth rows of every array that has one.
+    pub fn ragged_transpose(self, env: &Uiua) -> UiuaResult<Self> {
+        let arr = match self {
+            Value::Box(arr) if arr.rank() == 1 => arr,
+            val => {
+                return Err(env.error(format!(
+                    "Cannot ragged transpose {} array of rank {}, \
+                    it must be a list of boxes",
+                    val.type_name(),
+                    val.rank()
+                )))
+            }
+        };
+        let row_count = (arr.data.iter())
+            .map(|b| b.0.row_count())
+            .max()
+            .unwrap_or(0);
+        let mut rows: Vec<_> = (arr.data.into_iter())
+            .map(|b| match b.0 {
+                val if val.rank() == 0 => Value::from_row_values([val], env),
+                val => Ok(val),
+            })
+            .map(|val| val.map(Value::into_rows))
+            .collect::<UiuaResult<_>>()?;
+        let mut new_data = EcoVec::with_capacity(row_count);
+        for _ in 0..row_count {
+            let row = Value::from_row_values(rows.iter_mut().filter_map(Iterator::next), env)?;
+            new_data.push(Boxed(row));
+        }
+        Ok(Array::from(new_data).into())
+    }
+}
+
+/// Get the contents of every box if they are all the same type
+fn contents<'a, T: ArrayValue>(
+    arr: &'a Array<Boxed>,
+    f: impl Fn(&'a Value) -> Option<&'a Array<T>>,
+) -> Option<Vec<&'a Array<T>>> {
+    arr.data.iter().map(|b| f(&b.0)).collect()
+}
+
+/// Copy arrays of the same rank into a single buffer, padding them with the fill value
+///
+/// Returns `None` if the ranks differ or if padding is needed but there is no fill.
+fn pad_cells<T: ArrayValue>(
+    cells: &[&Array<T>],
+    outer_shape: &Shape,
+    env: &Uiua,
+) -> Option<Array<T>> {
+    let rank = cells[0].rank();
+    if cells.iter().any(|cell| cell.rank() != rank) {
+        return None;
+    }
+    let mut cell_shape = cells[0].shape.clone();
+    for cell in &cells[1..] {
+        for (max, &dim) in cell_shape.iter_mut().zip(&cell.shape) {
+            *max = (*max).max(dim);
+        }
+    }
+    let fill = if cells.iter().all(|cell| cell.shape == cell_shape) {
+        T::default()
+    } else {
+        T::get_scalar_fill(env).ok()?
+    };
+    let cell_len = cell_shape.elements();
+    let mut data = eco_vec![fill; cells.len() * cell_len];
+    if cell_len > 0 {
+        let slice = data.make_mut();
+        for (cell, dest) in cells.iter().zip(slice.chunks_exact_mut(cell_len)) {
+            copy_padded(&cell.data, &cell.shape, dest, &cell_shape);
+        }
+    }
+    let mut shape = outer_shape.clone();
+    shape.extend_from_slice(&cell_shape);
+    Some(Array::new(shape, data))
+}
+
+/// Copy the data of an array into the corner of a larger one
+fn copy_padded<T: Clone>(src: &[T], src_shape: &[usize], dest: &mut [T], dest_shape: &[usize]) {
+    let Some((&line_len, src_outer)) = src_shape.split_last() else {
+        dest[0] = src[0].clone();
+        return;
+    };
+    if line_len == 0 {
+        return;
+    }
+    for (l, line) in src.chunks_exact(line_len).enumerate() {
+        let mut rem = l;
+        let mut offset = 0;
+        let mut stride = dest_shape[dest_shape.len() - 1];
+        for (&src_dim, &dest_dim) in src_outer.iter().zip(dest_shape).rev() {
+            offset += rem % src_dim * stride;
+            rem /= src_dim;
+            stride *= dest_dim;
+        }
+        dest[offset..][..line_len].clone_from_slice(line);
+    }
+}
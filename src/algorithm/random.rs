@@ -0,0 +1,293 @@
+//! The interpreter's random number generator
+//!
+//! The algorithms here are part of Uiua's observable behavior.
+//! A given seed must produce the same values in every version, so they should not be changed.
+//!
+//! - The generator is xoshiro256**, with its state initialized from the seed by SplitMix64.
+//!   The seed is the bits of the seed number as an IEEE 754 double.
+//! - Uniform numbers use the top 53 bits of a `u64` to make a number in `[0, 1)`.
+//! - Normal numbers are generated in pairs with the Box-Muller transform.
+//!   `√(¯2 ₙ(1-u₁))` is multiplied by `○(τu₂)` for the first and `∿(τu₂)` for the second.
+//!   If an odd number is needed, the second of the last pair is discarded.
+//! - Integers below `n` take the remainder of a `u64` divided by `n`,
+//!   rejecting values from the biased top of the range.
+//! - Permutations are generated with a Fisher-Yates shuffle,
+//!   swapping index `i` with a random index below `i + 1` from the end down.
+//!   [`shuffle`](crate::Primitive::Shuffle) swaps rows the same way,
+//!   so it gives the same result as [`select`](crate::Primitive::Select)ing with a permutation.
+//! - [`choice`](crate::Primitive::Choice) with equal weights takes integers below the row count.
+//!   With weights, it uses Vose's alias method. A row is chosen as an integer below the row count,
+//!   then kept if a uniform number is below its probability, or replaced by its alias otherwise.
+//! - [`sample`](crate::Primitive::Sample) with equal weights is a partial Fisher-Yates shuffle of `⇡n`
+//!   from the front, swapping index `i` with `i` plus a random index below `n - i`.
+//!   With weights, each row with a positive weight `w` gets the key `ₙ(1-u)÷w` in row order,
+//!   and the rows with the largest keys are taken, largest first.
+
+use std::{collections::HashMap, f64::consts::TAU};
+
+use ecow::EcoVec;
+
+use crate::{algorithm::validate_size, Array, Shape, Uiua, UiuaResult, Value};
+
+/// A seedable random number generator with a stable algorithm
+#[derive(Debug, Clone)]
+pub(crate) struct Rng([u64; 4]);
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl Rng {
+    /// Create a generator from a seed
+    pub fn from_seed(seed: u64) -> Self {
+        let mut sm = seed;
+        let mut next = || {
+            sm = sm.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = sm;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        Rng([next(), next(), next(), next()])
+    }
+    /// Create a generator from a number seed, using its bits
+    pub fn from_number(seed: f64) -> Self {
+        Self::from_seed(seed.to_bits())
+    }
+    /// Create a generator with a seed from the operating system
+    pub fn from_entropy() -> Self {
+        Self::from_seed(rand::random())
+    }
+    /// Create an independent generator seeded from this one
+    pub fn fork(&mut self) -> Self {
+        Self::from_seed(self.next_u64())
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.0;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+    /// Generate a number in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+    /// Generate an integer in `[0, n)`
+    pub fn below(&mut self, n: u64) -> u64 {
+        debug_assert!(n > 0);
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+    /// Generate a pair of numbers from the standard normal distribution
+    pub fn normal_pair(&mut self) -> (f64, f64) {
+        let u1 = self.next_f64();
+        let u2 = self.next_f64();
+        let r = (-2.0 * (1.0 - u1).ln()).sqrt();
+        let theta = TAU * u2;
+        (r * theta.cos(), r * theta.sin())
+    }
+}
+
+/// A table for sampling from a discrete distribution in constant time
+struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Build a table from nonnegative weights with a positive sum
+    fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+        let mut prob = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            prob[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Anything left over is only off from 1 by rounding error
+        AliasTable { prob, alias }
+    }
+    fn draw(&self, rng: &mut Rng) -> usize {
+        let i = rng.below(self.prob.len() as u64) as usize;
+        if rng.next_f64() < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+/// Get `k` distinct random indices below `n`
+fn distinct_indices(rng: &mut Rng, k: usize, n: usize) -> Vec<usize> {
+    // Only the swapped positions are stored, so this is O(k) even for huge n
+    let mut swapped: HashMap<usize, usize> = HashMap::with_capacity(k);
+    let mut dealt = Vec::with_capacity(k);
+    for i in 0..k {
+        let j = i + rng.below((n - i) as u64) as usize;
+        let at_j = swapped.get(&j).copied().unwrap_or(j);
+        let at_i = swapped.get(&i).copied().unwrap_or(i);
+        swapped.insert(j, at_i);
+        dealt.push(at_j);
+    }
+    dealt
+}
+
+/// Get the weights of the rows of an array, or `None` if they are all equal
+fn row_weights(weights: &Value, rows: usize, env: &Uiua) -> UiuaResult<Option<Vec<f64>>> {
+    if weights.rank() == 0 {
+        let w = weights.as_num(env, "Weights must be numbers")?;
+        if !(w > 0.0 && w.is_finite()) {
+            return Err(env.error(format!("Weights must be positive, but the weight is {w}")));
+        }
+        return Ok(None);
+    }
+    let weights = weights.as_nums(env, "Weights must be a list of numbers")?;
+    if weights.len() != rows {
+        return Err(env.error(format!(
+            "There are {} weights but {rows} rows",
+            weights.len()
+        )));
+    }
+    if let Some(w) = weights.iter().find(|w| !(**w >= 0.0 && w.is_finite())) {
+        return Err(env.error(format!("Weights cannot be {w}")));
+    }
+    if rows > 0 && weights.iter().sum::<f64>() <= 0.0 {
+        return Err(env.error("Weights must not all be zero"));
+    }
+    Ok(Some(weights))
+}
+
+fn shape_of(val: &Value, env: &Uiua) -> UiuaResult<Shape> {
+    Ok(val
+        .as_nats(env, "Shape must be a list of natural numbers")?
+        .into_iter()
+        .collect())
+}
+
+impl Uiua {
+    /// Seed the random number generator
+    pub(crate) fn seed_rng(&mut self, seed: f64) {
+        self.rt.rng = Rng::from_number(seed);
+    }
+    /// Generate a random number in `[0, 1)`
+    pub(crate) fn random(&mut self) -> f64 {
+        self.rt.rng.next_f64()
+    }
+    /// Generate an array of random numbers in `[0, 1)`
+    pub(crate) fn uniform(&mut self, shape: &Value) -> UiuaResult<Value> {
+        let shape = shape_of(shape, self)?;
+        let len = validate_size::<f64>(shape.iter().copied(), self)?;
+        let rng = &mut self.rt.rng;
+        let data: EcoVec<f64> = (0..len).map(|_| rng.next_f64()).collect();
+        Ok(Array::new(shape, data).into())
+    }
+    /// Generate an array of random numbers from the standard normal distribution
+    pub(crate) fn normal(&mut self, shape: &Value) -> UiuaResult<Value> {
+        let shape = shape_of(shape, self)?;
+        let len = validate_size::<f64>(shape.iter().copied(), self)?;
+        let rng = &mut self.rt.rng;
+        let mut data = EcoVec::with_capacity(len);
+        while data.len() < len {
+            let (a, b) = rng.normal_pair();
+            data.push(a);
+            if data.len() < len {
+                data.push(b);
+            }
+        }
+        Ok(Array::new(shape, data).into())
+    }
+    /// Generate a random permutation of `⇡n`
+    pub(crate) fn permutation(&mut self, n: &Value) -> UiuaResult<Value> {
+        let n = n.as_nat(self, "Permutation length must be a natural number")?;
+        validate_size::<f64>([n], self)?;
+        let mut indices: Vec<usize> = (0..n).collect();
+        let rng = &mut self.rt.rng;
+        for i in (1..n).rev() {
+            let j = rng.below(i as u64 + 1) as usize;
+            indices.swap(i, j);
+        }
+        Ok(Array::from(indices).into())
+    }
+    /// Randomly `shuffle` the rows of an array
+    pub(crate) fn shuffle(&mut self, mut val: Value) -> Value {
+        let rng = &mut self.rt.rng;
+        let swaps: Vec<(usize, usize)> = (1..val.row_count())
+            .rev()
+            .map(|i| (i, rng.below(i as u64 + 1) as usize))
+            .collect();
+        val.swap_rows(&swaps);
+        val
+    }
+    /// Randomly choose `k` rows of an array with replacement
+    pub(crate) fn choice(&mut self, k: &Value, weights: &Value, from: &Value) -> UiuaResult<Value> {
+        let k = k.as_nat(self, "Choice count must be a natural number")?;
+        validate_size::<usize>([k], self)?;
+        let n = from.row_count();
+        let weights = row_weights(weights, n, self)?;
+        if n == 0 && k > 0 {
+            return Err(self.error("Cannot choose rows from an empty array"));
+        }
+        let rng = &mut self.rt.rng;
+        let indices: Vec<usize> = if let Some(weights) = weights {
+            let table = AliasTable::new(&weights);
+            (0..k).map(|_| table.draw(rng)).collect()
+        } else {
+            (0..k).map(|_| rng.below(n as u64) as usize).collect()
+        };
+        Value::from(Array::from(indices)).select(from, self)
+    }
+    /// Randomly choose `k` distinct rows of an array
+    pub(crate) fn sample(&mut self, k: &Value, weights: &Value, from: &Value) -> UiuaResult<Value> {
+        let k = k.as_nat(self, "Sample count must be a natural number")?;
+        let n = from.row_count();
+        let weights = row_weights(weights, n, self)?;
+        let rng = &mut self.rt.rng;
+        let indices = if let Some(weights) = weights {
+            let mut keys: Vec<(f64, usize)> = (weights.iter().enumerate())
+                .filter(|(_, w)| **w > 0.0)
+                .map(|(i, w)| ((1.0 - rng.next_f64()).ln() / w, i))
+                .collect();
+            if k > keys.len() {
+                return Err(self.error(format!(
+                    "Cannot sample {k} rows when only {} have positive weights",
+                    keys.len()
+                )));
+            }
+            let by_key = |a: &(f64, usize), b: &(f64, usize)| b.0.total_cmp(&a.0);
+            if k < keys.len() {
+                keys.select_nth_unstable_by(k, by_key);
+                keys.truncate(k);
+            }
+            keys.sort_by(by_key);
+            keys.into_iter().map(|(_, i)| i).collect()
+        } else {
+            if k > n {
+                return Err(self.error(format!("Cannot sample {k} rows from {n} rows")));
+            }
+            distinct_indices(rng, k, n)
+        };
+        Value::from(Array::from(indices)).select(from, self)
+    }
+}
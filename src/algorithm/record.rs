@@ -0,0 +1,62 @@
+//! Records, which are box lists whose items are labeled with field names
+
+use ecow::EcoVec;
+
+use crate::{Array, Boxed, Uiua, UiuaError, UiuaResult, Value};
+
+fn field_name(name: &Value, env: &Uiua) -> UiuaResult<String> {
+    name.as_string(env, "Field name must be a string")
+}
+
+fn not_record(val: &Value, action: &str, env: &Uiua) -> UiuaError {
+    env.error(format!(
+        "Cannot {action} field of {} array of shape {}",
+        val.type_name(),
+        val.shape()
+    ))
+}
+
+fn field_index(fields: &[Boxed], name: &str) -> Option<usize> {
+    (fields.iter()).position(|Boxed(val)| val.meta().label.as_deref() == Some(name))
+}
+
+impl Value {
+    /// Get the value of a field of a record
+    pub fn get_field(&self, name: &Value, env: &Uiua) -> UiuaResult<Value> {
+        let name = field_name(name, env)?;
+        let fields = match self {
+            Value::Box(arr) if arr.rank() == 1 && arr.meta().map_keys.is_none() => &arr.data,
+            val => return Err(not_record(val, "get", env)),
+        };
+        if let Some(index) = field_index(fields, &name) {
+            let mut val = fields[index].0.clone();
+            val.take_label();
+            Ok(val)
+        } else {
+            (env.value_fill().cloned())
+                .ok_or_else(|| env.error(format!("Record has no field {name:?}")))
+        }
+    }
+    /// Set the value of a field of a record
+    ///
+    /// If the field does not exist, it is added to the end.
+    pub fn set_field(&mut self, name: &Value, value: Value, env: &Uiua) -> UiuaResult {
+        let name = field_name(name, env)?;
+        if !matches!(self, Value::Box(_)) && self.rank() == 1 && self.row_count() == 0 {
+            *self = Array::<Boxed>::from(EcoVec::new()).into();
+        }
+        let arr = match self {
+            Value::Box(arr) if arr.rank() == 1 && arr.meta().map_keys.is_none() => arr,
+            val => return Err(not_record(val, "set", env)),
+        };
+        let mut value = value.unboxed();
+        value.meta_mut().label = Some(name.as_str().into());
+        if let Some(index) = field_index(&arr.data, &name) {
+            arr.data.as_mut_slice()[index] = Boxed(value);
+        } else {
+            arr.data.extend_from_array([Boxed(value)]);
+            arr.shape[0] += 1;
+        }
+        Ok(())
+    }
+}
@@ -3,9 +3,10 @@
 use std::{collections::VecDeque, convert::identity, iter::repeat};
 
 use ecow::{eco_vec, EcoVec};
+use rayon::prelude::*;
 
 use crate::{
-    algorithm::{loops::flip, pervade::*},
+    algorithm::{loops::flip, padding, pervade::*},
     check::instrs_signature,
     cowslice::cowslice,
     Array, ArrayValue, Complex, Function, ImplPrimitive, Instr, Primitive, Shape, Signature, Uiua,
@@ -35,7 +36,19 @@ pub fn reduce(depth: usize, env: &mut Uiua) -> UiuaResult {
             *xs.shape_mut() = new_shape;
             env.push(xs);
         }
+        (Some((prim, flipped)), Value::Num(nums)) if env.skip_nan() => {
+            if let Err(nums) = reduce_nums_skip_nan(prim, flipped, nums, depth, env) {
+                return generic_reduce(f, Value::Num(nums), depth, env);
+            }
+        }
         (Some((prim, flipped)), Value::Num(nums)) => {
+            #[cfg(feature = "gpu")]
+            if depth == 0 && env.num_scalar_fill().is_err() {
+                if let Some(reduced) = crate::gpu::reduce(prim, &nums, env) {
+                    env.push(reduced);
+                    return Ok(());
+                }
+            }
             if let Err(nums) = reduce_nums(prim, flipped, nums, depth, env) {
                 return generic_reduce(f, Value::Num(nums), depth, env);
             }
@@ -313,6 +326,80 @@ macro_rules! reduce_math {
 reduce_math!(reduce_nums, f64, num_num, num_scalar_fill);
 reduce_math!(reduce_coms, Complex, com_x, complex_scalar_fill);
 
+/// Reduce numbers with a primitive, skipping `NaN`s
+///
+/// Where every number being reduced is `NaN`, the result is the function's identity.
+#[allow(clippy::result_large_err)]
+fn reduce_nums_skip_nan(
+    prim: Primitive,
+    flipped: bool,
+    xs: Array<f64>,
+    depth: usize,
+    env: &mut Uiua,
+) -> Result<(), Array<f64>> {
+    fn skip(f: impl Fn(f64, f64) -> f64) -> impl Fn(f64, f64) -> f64 {
+        move |a, b| {
+            if b.is_nan() {
+                a
+            } else if a.is_nan() {
+                b
+            } else {
+                f(a, b)
+            }
+        }
+    }
+    let fill = env.num_scalar_fill().ok();
+    let (identity, mut reduced) = match prim {
+        Primitive::Add => (0.0, fast_reduce(xs, 0.0, fill, depth, skip(add::num_num))),
+        Primitive::Sub if flipped => {
+            let f = skip(flip(sub::num_num));
+            (0.0, fast_reduce(xs, 0.0, fill, depth, f))
+        }
+        Primitive::Sub => (0.0, fast_reduce(xs, 0.0, fill, depth, skip(sub::num_num))),
+        Primitive::Mul => (1.0, fast_reduce(xs, 1.0, fill, depth, skip(mul::num_num))),
+        Primitive::Div if flipped => {
+            let f = skip(flip(div::num_num));
+            (1.0, fast_reduce(xs, 1.0, fill, depth, f))
+        }
+        Primitive::Div => (1.0, fast_reduce(xs, 1.0, fill, depth, skip(div::num_num))),
+        Primitive::Mod if flipped => {
+            let f = skip(flip(modulus::num_num));
+            (1.0, fast_reduce(xs, 1.0, fill, depth, f))
+        }
+        Primitive::Mod => {
+            let f = skip(modulus::num_num);
+            (1.0, fast_reduce(xs, 1.0, fill, depth, f))
+        }
+        Primitive::Atan if flipped => {
+            let f = skip(flip(atan2::num_num));
+            (0.0, fast_reduce(xs, 0.0, fill, depth, f))
+        }
+        Primitive::Atan => (0.0, fast_reduce(xs, 0.0, fill, depth, skip(atan2::num_num))),
+        Primitive::Max => {
+            let f = skip(max::num_num);
+            (
+                f64::NEG_INFINITY,
+                fast_reduce(xs, f64::NEG_INFINITY, fill, depth, f),
+            )
+        }
+        Primitive::Min => {
+            let f = skip(min::num_num);
+            (
+                f64::INFINITY,
+                fast_reduce(xs, f64::INFINITY, fill, depth, f),
+            )
+        }
+        _ => return Err(xs),
+    };
+    for x in reduced.data.as_mut_slice() {
+        if x.is_nan() {
+            *x = identity;
+        }
+    }
+    env.push(reduced);
+    Ok(())
+}
+
 fn fast_reduce_different<T, U>(
     arr: Array<T>,
     identity: U,
@@ -349,6 +436,7 @@ fn fast_reduce<T>(
 where
     T: ArrayValue + Copy,
 {
+    arr.reset_meta_flags();
     depth = depth.min(arr.rank());
     if depth == 0 && arr.rank() == 1 {
         return if let Some(default) = default {
@@ -599,98 +687,210 @@ pub fn scan(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
     let f = env.pop_function()?;
     let xs = env.pop(1)?;
-    if xs.rank() == 0 {
+    let scanned = scan_depth(&f, xs, 0, env)?;
+    env.push(scanned);
+    Ok(())
+}
+
+/// Whether scanning with a primitive has a native implementation
+pub(crate) fn has_fast_scan(prim: Primitive) -> bool {
+    use Primitive::*;
+    matches!(
+        prim,
+        Eq | Ne | Add | Sub | Mul | Div | Mod | Atan | Max | Min
+    )
+}
+
+/// Scan along the axis at some depth
+pub(crate) fn scan_depth(
+    f: &Function,
+    xs: Value,
+    depth: usize,
+    env: &mut Uiua,
+) -> UiuaResult<Value> {
+    if xs.rank() <= depth {
         return Err(env.error(format!("Cannot {} rank 0 array", Primitive::Scan.format())));
     }
-    match (f.as_flipped_primitive(&env.asm), xs) {
-        (Some((prim, flipped)), Value::Num(nums)) => {
-            let arr = match prim {
-                Primitive::Eq => fast_scan(nums, |a, b| is_eq::num_num(a, b) as f64),
-                Primitive::Ne => fast_scan(nums, |a, b| is_ne::num_num(a, b) as f64),
-                Primitive::Add => fast_scan(nums, add::num_num),
-                Primitive::Sub if flipped => fast_scan(nums, flip(sub::num_num)),
-                Primitive::Sub => fast_scan(nums, sub::num_num),
-                Primitive::Mul => fast_scan(nums, mul::num_num),
-                Primitive::Div if flipped => fast_scan(nums, flip(div::num_num)),
-                Primitive::Div => fast_scan(nums, div::num_num),
-                Primitive::Mod if flipped => fast_scan(nums, flip(modulus::num_num)),
-                Primitive::Mod => fast_scan(nums, modulus::num_num),
-                Primitive::Atan if flipped => fast_scan(nums, flip(atan2::num_num)),
-                Primitive::Atan => fast_scan(nums, atan2::num_num),
-                Primitive::Max => fast_scan(nums, max::num_num),
-                Primitive::Min => fast_scan(nums, min::num_num),
-                _ => return generic_scan(f, Value::Num(nums), env),
-            };
-            env.push(arr);
-            Ok(())
-        }
-        (Some((prim, flipped)), Value::Byte(bytes)) => {
-            match prim {
-                Primitive::Eq => env.push(fast_scan(bytes, is_eq::generic)),
-                Primitive::Ne => env.push(fast_scan(bytes, is_ne::generic)),
-                Primitive::Add => env.push(fast_scan::<f64>(bytes.convert(), add::num_num)),
-                Primitive::Sub if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(sub::num_num)))
-                }
-                Primitive::Sub => env.push(fast_scan::<f64>(bytes.convert(), sub::num_num)),
-                Primitive::Mul => env.push(fast_scan::<f64>(bytes.convert(), mul::num_num)),
-                Primitive::Div if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(div::num_num)))
-                }
-                Primitive::Div => env.push(fast_scan::<f64>(bytes.convert(), div::num_num)),
-                Primitive::Mod if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(modulus::num_num)))
-                }
-                Primitive::Mod => env.push(fast_scan::<f64>(bytes.convert(), modulus::num_num)),
-                Primitive::Atan if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(atan2::num_num)))
-                }
-                Primitive::Atan => env.push(fast_scan::<f64>(bytes.convert(), atan2::num_num)),
-                Primitive::Max => env.push(fast_scan(bytes, u8::max)),
-                Primitive::Min => env.push(fast_scan(bytes, u8::min)),
-                _ => return generic_scan(f, Value::Byte(bytes), env),
+    let xs = match f.as_flipped_primitive(&env.asm) {
+        Some((prim, flipped)) if has_fast_scan(prim) => {
+            match fast_scan_value(prim, flipped, xs, depth) {
+                Ok(scanned) => return Ok(scanned),
+                Err(xs) => xs,
             }
-            Ok(())
         }
-        (_, xs) => generic_scan(f, xs, env),
+        _ => xs,
+    };
+    if depth == 0 {
+        generic_scan(f.clone(), xs, env)?;
+        return env.pop("scanned array");
     }
+    // Scan each row
+    if xs.row_count() == 0 {
+        return Ok(xs);
+    }
+    let rows = (xs.into_rows())
+        .map(|row| scan_depth(f, row, depth - 1, env))
+        .collect::<UiuaResult<Vec<_>>>()?;
+    Value::from_row_values(rows, env)
 }
 
-fn fast_scan<T>(mut arr: Array<T>, f: impl Fn(T, T) -> T) -> Array<T>
-where
-    T: ArrayValue + Copy,
-{
-    match arr.shape.len() {
-        0 => unreachable!("fast_scan called on unit array, should have been guarded against"),
-        1 => {
-            if arr.row_count() == 0 {
-                return arr;
+/// Scan numbers with a primitive
+///
+/// Returns the value back if it is not numeric
+fn fast_scan_value(
+    prim: Primitive,
+    flipped: bool,
+    xs: Value,
+    depth: usize,
+) -> Result<Value, Value> {
+    Ok(match xs {
+        Value::Num(nums) => match prim {
+            Primitive::Eq => fast_scan(nums, depth, |a, b| is_eq::num_num(a, b) as f64).into(),
+            Primitive::Ne => fast_scan(nums, depth, |a, b| is_ne::num_num(a, b) as f64).into(),
+            Primitive::Add if exact_sums(&nums) => assoc_scan(nums, depth, add::num_num).into(),
+            Primitive::Add => fast_scan(nums, depth, add::num_num).into(),
+            Primitive::Sub if flipped => fast_scan(nums, depth, flip(sub::num_num)).into(),
+            Primitive::Sub => fast_scan(nums, depth, sub::num_num).into(),
+            Primitive::Mul => fast_scan(nums, depth, mul::num_num).into(),
+            Primitive::Div if flipped => fast_scan(nums, depth, flip(div::num_num)).into(),
+            Primitive::Div => fast_scan(nums, depth, div::num_num).into(),
+            Primitive::Mod if flipped => fast_scan(nums, depth, flip(modulus::num_num)).into(),
+            Primitive::Mod => fast_scan(nums, depth, modulus::num_num).into(),
+            Primitive::Atan if flipped => fast_scan(nums, depth, flip(atan2::num_num)).into(),
+            Primitive::Atan => fast_scan(nums, depth, atan2::num_num).into(),
+            Primitive::Max => assoc_scan(nums, depth, max::num_num).into(),
+            Primitive::Min => assoc_scan(nums, depth, min::num_num).into(),
+            _ => return Err(Value::Num(nums)),
+        },
+        Value::Byte(bytes) => match prim {
+            Primitive::Eq => fast_scan(bytes, depth, is_eq::generic).into(),
+            Primitive::Ne => fast_scan(bytes, depth, is_ne::generic).into(),
+            Primitive::Add => assoc_scan::<f64>(bytes.convert(), depth, add::num_num).into(),
+            Primitive::Sub if flipped => {
+                fast_scan::<f64>(bytes.convert(), depth, flip(sub::num_num)).into()
             }
-            let mut acc = arr.data[0];
-            for val in arr.data.as_mut_slice().iter_mut().skip(1) {
-                acc = f(acc, *val);
-                *val = acc;
+            Primitive::Sub => fast_scan::<f64>(bytes.convert(), depth, sub::num_num).into(),
+            Primitive::Mul => fast_scan::<f64>(bytes.convert(), depth, mul::num_num).into(),
+            Primitive::Div if flipped => {
+                fast_scan::<f64>(bytes.convert(), depth, flip(div::num_num)).into()
             }
-            arr
-        }
-        _ => {
-            let row_len: usize = arr.row_len();
-            if arr.row_count() == 0 {
-                return arr;
+            Primitive::Div => fast_scan::<f64>(bytes.convert(), depth, div::num_num).into(),
+            Primitive::Mod if flipped => {
+                fast_scan::<f64>(bytes.convert(), depth, flip(modulus::num_num)).into()
             }
-            let shape = arr.shape.clone();
-            let mut new_data = EcoVec::with_capacity(arr.data.len());
-            let mut rows = arr.into_rows();
-            new_data.extend(rows.next().unwrap().data);
-            for row in rows {
-                let start = new_data.len() - row_len;
-                for (i, r) in row.data.into_iter().enumerate() {
-                    new_data.push(f(new_data[start + i], r));
-                }
+            Primitive::Mod => fast_scan::<f64>(bytes.convert(), depth, modulus::num_num).into(),
+            Primitive::Atan if flipped => {
+                fast_scan::<f64>(bytes.convert(), depth, flip(atan2::num_num)).into()
             }
-            Array::new(shape, new_data)
+            Primitive::Atan => fast_scan::<f64>(bytes.convert(), depth, atan2::num_num).into(),
+            Primitive::Max => assoc_scan(bytes, depth, u8::max).into(),
+            Primitive::Min => assoc_scan(bytes, depth, u8::min).into(),
+            _ => return Err(Value::Byte(bytes)),
+        },
+        xs => return Err(xs),
+    })
+}
+
+/// Above this many elements, scans are done in parallel
+const PAR_SCAN_THRESHOLD: usize = 100_000;
+
+/// Whether every partial sum of some numbers is exact,
+/// so that they can be added in any order
+fn exact_sums(nums: &Array<f64>) -> bool {
+    const MAX_EXACT: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+    (nums.data.iter()).all(|n| n.fract() == 0.0)
+        && nums.data.iter().map(|n| n.abs()).sum::<f64>() <= MAX_EXACT
+}
+
+/// Scan the rows of each chunk of a slice in place
+fn scan_rows<T: Copy>(chunk: &mut [T], row_len: usize, f: &impl Fn(T, T) -> T) {
+    for i in row_len..chunk.len() {
+        chunk[i] = f(chunk[i - row_len], chunk[i]);
+    }
+}
+
+fn fast_scan<T>(arr: Array<T>, depth: usize, f: impl Fn(T, T) -> T + Sync) -> Array<T>
+where
+    T: ArrayValue + Copy,
+{
+    scan_impl(arr, depth, false, f)
+}
+
+/// Scan with an associative function
+///
+/// A single long axis is scanned in parallel blocks
+fn assoc_scan<T>(arr: Array<T>, depth: usize, f: impl Fn(T, T) -> T + Sync) -> Array<T>
+where
+    T: ArrayValue + Copy,
+{
+    scan_impl(arr, depth, true, f)
+}
+
+fn scan_impl<T>(
+    mut arr: Array<T>,
+    depth: usize,
+    assoc: bool,
+    f: impl Fn(T, T) -> T + Sync,
+) -> Array<T>
+where
+    T: ArrayValue + Copy,
+{
+    arr.reset_meta_flags();
+    debug_assert!(
+        depth < arr.rank(),
+        "scan depth should have been guarded against"
+    );
+    let chunk_len: usize = arr.shape[depth..].iter().product();
+    let row_len: usize = arr.shape[depth + 1..].iter().product();
+    if chunk_len == 0 || row_len == 0 {
+        return arr;
+    }
+    let data = arr.data.as_mut_slice();
+    if data.len() <= PAR_SCAN_THRESHOLD {
+        for chunk in data.chunks_exact_mut(chunk_len) {
+            scan_rows(chunk, row_len, &f);
         }
+    } else if chunk_len < data.len() {
+        (data.par_chunks_exact_mut(chunk_len)).for_each(|chunk| scan_rows(chunk, row_len, &f));
+    } else if assoc {
+        par_scan(data, row_len, &f);
+    } else {
+        scan_rows(data, row_len, &f);
     }
+    arr
+}
+
+/// A work-efficient parallel scan of the rows of a slice
+///
+/// Blocks of rows are scanned independently, then each block is
+/// combined with the last row of the blocks before it.
+fn par_scan<T>(data: &mut [T], row_len: usize, f: &(impl Fn(T, T) -> T + Sync))
+where
+    T: Copy + Send + Sync,
+{
+    let row_count = data.len() / row_len;
+    let block_rows = row_count.div_ceil(rayon::current_num_threads()).max(1);
+    let block_len = block_rows * row_len;
+    (data.par_chunks_mut(block_len)).for_each(|block| scan_rows(block, row_len, f));
+    // The running total before each block
+    let mut carries: Vec<Vec<T>> = Vec::new();
+    let mut carry = data[block_len - row_len..block_len].to_vec();
+    for block in data.chunks(block_len).skip(1) {
+        carries.push(carry.clone());
+        let last = &block[block.len() - row_len..];
+        for (c, &l) in carry.iter_mut().zip(last) {
+            *c = f(*c, l);
+        }
+    }
+    (data.par_chunks_mut(block_len).skip(1))
+        .zip(&carries)
+        .for_each(|(block, carry)| {
+            for row in block.chunks_exact_mut(row_len) {
+                for (x, &c) in row.iter_mut().zip(carry) {
+                    *x = f(c, *x);
+                }
+            }
+        });
 }
 
 fn generic_scan(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
@@ -791,6 +991,7 @@ fn fast_invscan<T>(mut arr: Array<T>, f: impl Fn(T, T) -> T) -> Array<T>
 where
     T: ArrayValue + Copy,
 {
+    arr.reset_meta_flags();
     match arr.shape.len() {
         0 => unreachable!("fast_invscan called on unit array, should have been guarded against"),
         1 => {
@@ -867,6 +1068,22 @@ pub fn fold(env: &mut Uiua) -> UiuaResult {
             }
         }
     }
+    // Batch map operations
+    match (f.as_primitive(&env.asm), arrays.as_mut_slice()) {
+        (Some(Primitive::Insert), [Ok(keys), Ok(values)]) => {
+            let mut map = env.pop("map")?;
+            map.insert_all(keys.zip(values), env)?;
+            env.push(map);
+            return Ok(());
+        }
+        (Some(Primitive::Remove), [Ok(keys)]) => {
+            let mut map = env.pop("map")?;
+            map.remove_all(keys, env)?;
+            env.push(map);
+            return Ok(());
+        }
+        _ => {}
+    }
     let mut row_count = arrays
         .iter()
         .filter_map(|arr| arr.as_ref().ok())
@@ -892,7 +1109,7 @@ pub fn adjacent(env: &mut Uiua) -> UiuaResult {
     let f = env.pop_function()?;
     let n = env.pop(1)?;
     let xs = env.pop(2)?;
-    if n.rank() != 0 {
+    if n.rank() != 0 || env.pad_modes().is_some() {
         return adjacent_fallback(f, n, xs, env);
     }
     let n = n.as_int(env, "Window size must be an integer or list of integers")?;
@@ -903,6 +1120,7 @@ pub fn adjacent(env: &mut Uiua) -> UiuaResult {
     let n = n_abs;
     match (f.as_flipped_primitive(&env.asm), xs) {
         (Some((prim, flipped)), Value::Num(nums)) => env.push(match prim {
+            Primitive::Add if n > 2 && exact_rolling_sum(&nums, n) => rolling_sum(nums, n, env),
             Primitive::Add => fast_adjacent(nums, n, env, add::num_num),
             Primitive::Sub if flipped => fast_adjacent(nums, n, env, flip(sub::num_num)),
             Primitive::Sub => fast_adjacent(nums, n, env, sub::num_num),
@@ -913,11 +1131,18 @@ pub fn adjacent(env: &mut Uiua) -> UiuaResult {
             Primitive::Mod => fast_adjacent(nums, n, env, modulus::num_num),
             Primitive::Atan if flipped => fast_adjacent(nums, n, env, flip(atan2::num_num)),
             Primitive::Atan => fast_adjacent(nums, n, env, atan2::num_num),
+            Primitive::Max if n > 2 && !nums.data.iter().any(|x| x.is_nan()) => {
+                rolling_extreme(nums, n, env, |new, old| new >= old)
+            }
             Primitive::Max => fast_adjacent(nums, n, env, max::num_num),
+            Primitive::Min if n > 2 && !nums.data.iter().any(|x| x.is_nan()) => {
+                rolling_extreme(nums, n, env, |new, old| new <= old)
+            }
             Primitive::Min => fast_adjacent(nums, n, env, min::num_num),
             _ => return generic_adjacent(f, Value::Num(nums), n, env),
         }?),
         (Some((prim, flipped)), Value::Byte(bytes)) => env.push::<Value>(match prim {
+            Primitive::Add if n > 2 => rolling_sum(bytes.convert(), n, env)?.into(),
             Primitive::Add => fast_adjacent(bytes.convert(), n, env, add::num_num)?.into(),
             Primitive::Sub if flipped => {
                 fast_adjacent(bytes.convert(), n, env, flip(sub::num_num))?.into()
@@ -936,7 +1161,13 @@ pub fn adjacent(env: &mut Uiua) -> UiuaResult {
                 fast_adjacent(bytes.convert(), n, env, flip(atan2::num_num))?.into()
             }
             Primitive::Atan => fast_adjacent(bytes.convert(), n, env, atan2::num_num)?.into(),
+            Primitive::Max if n > 2 => {
+                rolling_extreme(bytes, n, env, |new, old| new >= old)?.into()
+            }
             Primitive::Max => fast_adjacent(bytes, n, env, max::byte_byte)?.into(),
+            Primitive::Min if n > 2 => {
+                rolling_extreme(bytes, n, env, |new, old| new <= old)?.into()
+            }
             Primitive::Min => fast_adjacent(bytes, n, env, min::byte_byte)?.into(),
             _ => return generic_adjacent(f, Value::Byte(bytes), n, env),
         }),
@@ -946,7 +1177,10 @@ pub fn adjacent(env: &mut Uiua) -> UiuaResult {
 }
 
 fn adjacent_fallback(f: Function, n: Value, xs: Value, env: &mut Uiua) -> UiuaResult {
-    let windows = n.windows(&xs, env)?;
+    let windows = match env.pad_modes() {
+        Some(modes) => padding::padded_windows(&modes, &n, xs, env)?,
+        None => n.windows(&xs, env)?,
+    };
     let mut new_rows = Vec::with_capacity(windows.row_count());
     for window in windows.into_rows() {
         env.push(window);
@@ -967,6 +1201,7 @@ fn fast_adjacent<T>(
 where
     T: Copy,
 {
+    arr.reset_meta_flags();
     match arr.rank() {
         0 => Err(env.error("Cannot get adjacency of scalar")),
         1 => {
@@ -1011,6 +1246,95 @@ where
     }
 }
 
+/// Whether a running sum over windows of size `n` gives the same results
+/// as summing each window
+///
+/// This is only guaranteed when every element and every sum is an exactly
+/// representable integer.
+fn exact_rolling_sum(arr: &Array<f64>, n: usize) -> bool {
+    const MAX_EXACT: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+    let limit = MAX_EXACT / n as f64;
+    arr.data
+        .iter()
+        .all(|&x| x.fract() == 0.0 && x.abs() < limit)
+}
+
+/// Sum windows of rows in O(n) by adding the row that enters
+/// each window and subtracting the row that leaves it
+fn rolling_sum(arr: Array<f64>, n: usize, env: &Uiua) -> UiuaResult<Array<f64>> {
+    if arr.rank() == 0 || arr.row_count() < n {
+        return fast_adjacent(arr, n, env, add::num_num);
+    }
+    let row_len = arr.row_len();
+    let row_count = arr.row_count();
+    let new_row_count = row_count - (n - 1);
+    let data = arr.data.as_slice();
+    let mut sums = vec![0.0; row_len];
+    for row in data.chunks_exact(row_len.max(1)).take(n) {
+        for (sum, &x) in sums.iter_mut().zip(row) {
+            *sum += x;
+        }
+    }
+    let mut new_data = EcoVec::with_capacity(new_row_count * row_len);
+    new_data.extend_from_slice(&sums);
+    for i in n..row_count {
+        let entering = &data[i * row_len..][..row_len];
+        let leaving = &data[(i - n) * row_len..][..row_len];
+        for ((sum, &a), &b) in sums.iter_mut().zip(entering).zip(leaving) {
+            *sum += a - b;
+        }
+        new_data.extend_from_slice(&sums);
+    }
+    let mut shape = arr.shape.clone();
+    shape[0] = new_row_count;
+    Ok(Array::new(shape, new_data))
+}
+
+/// Find the extreme of windows of rows in O(n) using a monotonic deque
+/// for each column
+///
+/// `dominates(new, old)` should return whether `new` makes `old`
+/// irrelevant for all later windows.
+fn rolling_extreme<T>(
+    arr: Array<T>,
+    n: usize,
+    env: &Uiua,
+    dominates: impl Fn(T, T) -> bool,
+) -> UiuaResult<Array<T>>
+where
+    T: ArrayValue + Copy,
+{
+    if arr.rank() == 0 || arr.row_count() < n {
+        return fast_adjacent(arr, n, env, |a, b| if dominates(b, a) { b } else { a });
+    }
+    let row_len = arr.row_len();
+    let row_count = arr.row_count();
+    let new_row_count = row_count - (n - 1);
+    let data = arr.data.as_slice();
+    let mut new_data = eco_vec![T::default(); new_row_count * row_len];
+    let slice = new_data.make_mut();
+    let mut deque = VecDeque::with_capacity(n);
+    for col in 0..row_len {
+        deque.clear();
+        for i in 0..row_count {
+            let x = data[i * row_len + col];
+            while (deque.back()).is_some_and(|&j| dominates(x, data[j * row_len + col])) {
+                deque.pop_back();
+            }
+            deque.push_back(i);
+            if deque.front().is_some_and(|&j| j + n <= i) {
+                deque.pop_front();
+            }
+            if i + 1 >= n {
+                slice[(i + 1 - n) * row_len + col] = data[deque[0] * row_len + col];
+            }
+        }
+    }
+    let mut shape = arr.shape.clone();
+    shape[0] = new_row_count;
+    Ok(Array::new(shape, new_data))
+}
+
 fn generic_adjacent(f: Function, xs: Value, n: usize, env: &mut Uiua) -> UiuaResult {
     let sig = f.signature();
     if sig != (2, 1) {
@@ -0,0 +1,209 @@
+//! Statistical reductions
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ecow::EcoVec;
+use rayon::prelude::*;
+
+use crate::{Array, Shape, Uiua, UiuaResult, Value};
+
+use super::{decimal::Decimal, num_array};
+
+/// Above this many elements, columns are processed in parallel
+const PAR_THRESHOLD: usize = 10_000;
+
+/// Apply a function to each list along the axis at some depth
+///
+/// Each call gets a list along the axis and writes `out_shape.elements()` values.
+/// In the result, the axis is replaced by `out_shape`.
+/// If `skip_nan` is set, `NaN`s are removed from each list.
+fn along_axis(
+    arr: &Array<f64>,
+    depth: usize,
+    out_shape: &[usize],
+    skip_nan: bool,
+    f: impl Fn(&mut Vec<f64>, &mut [f64]) + Sync,
+) -> Array<f64> {
+    let depth = depth.min(arr.rank());
+    let outer: usize = arr.shape[..depth].iter().product();
+    let n = arr.shape.get(depth).copied().unwrap_or(1);
+    let inner: usize = arr.shape.iter().skip(depth + 1).product();
+    let k: usize = out_shape.iter().product();
+    // Results for each column, laid out as [outer, inner, k]
+    let mut cols = vec![0.0; outer * inner * k];
+    let data = arr.data.as_slice();
+    let col = |c: usize, out: &mut [f64]| {
+        let (o, i) = (c / inner, c % inner);
+        let start = o * n * inner + i;
+        let mut list: Vec<f64> = (0..n).map(|r| data[start + r * inner]).collect();
+        if skip_nan {
+            list.retain(|x| !x.is_nan());
+        }
+        f(&mut list, out);
+    };
+    if k > 0 {
+        if arr.element_count() > PAR_THRESHOLD {
+            (cols.par_chunks_mut(k).enumerate()).for_each(|(c, out)| col(c, out));
+        } else {
+            (cols.chunks_mut(k).enumerate()).for_each(|(c, out)| col(c, out));
+        }
+    }
+    // Move the results into the axis's position
+    let mut res = EcoVec::with_capacity(cols.len());
+    for o in 0..outer {
+        for q in 0..k {
+            for i in 0..inner {
+                res.push(cols[(o * inner + i) * k + q]);
+            }
+        }
+    }
+    let mut shape: Shape = arr.shape.iter().take(depth).copied().collect();
+    shape.extend_from_slice(out_shape);
+    shape.extend_from_slice(arr.shape.get(depth + 1..).unwrap_or_default());
+    Array::new(shape, res)
+}
+
+/// Welford's algorithm for the mean and sum of squared deviations
+fn welford(list: &[f64]) -> (f64, f64) {
+    if list.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    let (mut mean, mut m2) = (0.0, 0.0);
+    for (i, &x) in list.iter().enumerate() {
+        let delta = x - mean;
+        mean += delta / (i + 1) as f64;
+        m2 += delta * (x - mean);
+    }
+    (mean, m2)
+}
+
+/// The exact sum of a list of numbers as decimals
+///
+/// Lists with non-finite numbers are summed normally.
+/// Sets `overflow` if the sum does not fit.
+fn decimal_sum(list: &[f64], overflow: &AtomicBool) -> Option<Decimal> {
+    let decimals: Option<Vec<Decimal>> = list.iter().map(|&x| Decimal::from_f64(x)).collect();
+    let sum = Decimal::sum(&decimals?);
+    if sum.is_none() {
+        overflow.store(true, Ordering::Relaxed);
+    }
+    sum
+}
+
+/// Linearly interpolated quantiles of a list
+fn quantiles(list: &mut [f64], qs: &[f64], out: &mut [f64]) {
+    let n = list.len();
+    if n == 0 || list.iter().any(|x| x.is_nan()) {
+        out.fill(f64::NAN);
+        return;
+    }
+    let position = |q: f64| {
+        let h = (n - 1) as f64 * q;
+        let lo = h.floor() as usize;
+        (lo, h - lo as f64)
+    };
+    if let [q] = qs {
+        // Selection is faster than sorting for a single quantile
+        let (lo, t) = position(*q);
+        let (_, &mut a, rest) = list.select_nth_unstable_by(lo, f64::total_cmp);
+        out[0] = if t == 0.0 {
+            a
+        } else {
+            let b = rest.iter().copied().fold(f64::INFINITY, f64::min);
+            a + (b - a) * t
+        };
+    } else {
+        list.sort_unstable_by(f64::total_cmp);
+        for (q, out) in qs.iter().zip(out) {
+            let (lo, t) = position(*q);
+            *out = if t == 0.0 {
+                list[lo]
+            } else {
+                list[lo] + (list[lo + 1] - list[lo]) * t
+            };
+        }
+    }
+}
+
+impl Value {
+    /// Get the mean along the axis at some depth
+    pub(crate) fn mean(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Mean arguments", env)?;
+        Ok(along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            out[0] = welford(list).0
+        })
+        .into())
+    }
+    /// Get the exact decimal sum along the axis at some depth
+    pub(crate) fn decimal_sum(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Decimal sum arguments", env)?;
+        let overflow = AtomicBool::new(false);
+        let res = along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            out[0] = match decimal_sum(list, &overflow) {
+                Some(sum) => sum.to_f64(),
+                None => list.iter().sum(),
+            }
+        });
+        if overflow.into_inner() {
+            return Err(env.error("Numbers span too many decimal places to sum exactly"));
+        }
+        Ok(res.into())
+    }
+    /// Get the exact decimal mean along the axis at some depth
+    pub(crate) fn decimal_mean(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Decimal mean arguments", env)?;
+        let overflow = AtomicBool::new(false);
+        let res = along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            out[0] = if list.is_empty() {
+                f64::NAN
+            } else {
+                match decimal_sum(list, &overflow) {
+                    Some(sum) => sum.div_to_f64(list.len()),
+                    None => list.iter().sum::<f64>() / list.len() as f64,
+                }
+            }
+        });
+        if overflow.into_inner() {
+            return Err(env.error("Numbers span too many decimal places to average exactly"));
+        }
+        Ok(res.into())
+    }
+    /// Get the population variance along the axis at some depth
+    pub(crate) fn variance(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Variance arguments", env)?;
+        Ok(along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            out[0] = welford(list).1 / list.len() as f64
+        })
+        .into())
+    }
+    /// Get the population standard deviation along the axis at some depth
+    pub(crate) fn stddev(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Standard deviation arguments", env)?;
+        Ok(along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            out[0] = (welford(list).1 / list.len() as f64).sqrt()
+        })
+        .into())
+    }
+    /// Get the median along the axis at some depth
+    pub(crate) fn median(&self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let arr = num_array(self, "Median arguments", env)?;
+        Ok(along_axis(&arr, depth, &[], env.skip_nan(), |list, out| {
+            quantiles(list, &[0.5], out)
+        })
+        .into())
+    }
+    /// Get quantiles along the axis at some depth
+    pub(crate) fn quantile(&self, from: &Self, depth: usize, env: &Uiua) -> UiuaResult<Self> {
+        let qs = num_array(self, "Quantiles", env)?;
+        if let Some(q) = qs.data.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+            return Err(env.error(format!("Quantiles must be between 0 and 1, but one is {q}")));
+        }
+        let arr = num_array(from, "Quantile arguments", env)?;
+        Ok(
+            along_axis(&arr, depth, &qs.shape, env.skip_nan(), |list, out| {
+                quantiles(list, &qs.data, out)
+            })
+            .into(),
+        )
+    }
+}
@@ -0,0 +1,314 @@
+//! Algorithms for strings and other lists
+
+use std::iter::repeat;
+
+use ecow::EcoVec;
+use rayon::prelude::*;
+
+use crate::{
+    algorithm::validate_size, Array, ArrayValue, Boxed, Complex, Shape, Timestamp, Uiua,
+    UiuaResult, Value,
+};
+
+impl Value {
+    /// Split a list at each occurrence of a delimiter
+    pub(crate) fn split(&self, list: Self, env: &Uiua) -> UiuaResult<Self> {
+        list.keep_label(|list| {
+            Ok(match (self, list) {
+                (Value::Num(delim), Value::Num(list)) => split(delim, &list, env)?,
+                (Value::Byte(delim), Value::Byte(list)) => split(delim, &list, env)?,
+                (Value::Num(delim), Value::Byte(list)) => split(delim, &list.convert(), env)?,
+                (Value::Byte(delim), Value::Num(list)) => split(&delim.convert_ref(), &list, env)?,
+                (Value::Complex(delim), Value::Complex(list)) => split(delim, &list, env)?,
+                (Value::Char(delim), Value::Char(list)) => split(delim, &list, env)?,
+                (Value::Box(delim), Value::Box(list)) => split(delim, &list, env)?,
+                (delim, list) => {
+                    return Err(env.error(format!(
+                        "Cannot split {} by {}",
+                        list.type_name_plural(),
+                        delim.type_name_plural()
+                    )))
+                }
+            }
+            .into())
+        })
+    }
+    /// Join the rows of an array with a separator between them
+    pub(crate) fn join_with(&self, parts: Self, env: &Uiua) -> UiuaResult<Self> {
+        parts.keep_label(|parts| {
+            if parts.rank() == 0 {
+                return Err(env.error("Cannot join a scalar"));
+            }
+            let parts: Vec<Value> = match parts {
+                Value::Box(arr) if arr.rank() == 1 => {
+                    arr.data.into_iter().map(|Boxed(val)| val).collect()
+                }
+                parts => parts.into_rows().collect(),
+            };
+            // Strings are joined directly
+            if let Value::Char(sep) = self {
+                if sep.rank() <= 1
+                    && parts
+                        .iter()
+                        .all(|p| matches!(p, Value::Char(p) if p.rank() <= 1))
+                {
+                    let len = parts.iter().map(Value::element_count).sum::<usize>()
+                        + sep.element_count() * parts.len().saturating_sub(1);
+                    let mut joined = EcoVec::with_capacity(len);
+                    for (i, part) in parts.iter().enumerate() {
+                        if i > 0 {
+                            joined.extend_from_slice(&sep.data);
+                        }
+                        if let Value::Char(part) = part {
+                            joined.extend_from_slice(&part.data);
+                        }
+                    }
+                    return Ok(Array::<char>::from(joined).into());
+                }
+            }
+            let mut parts = parts.into_iter();
+            let Some(mut joined) = parts.next() else {
+                let mut empty = self.clone();
+                empty.deshape();
+                return Ok(empty.first_dim_zero());
+            };
+            if joined.rank() == 0 {
+                joined.deshape();
+            }
+            for part in parts {
+                joined = joined.join(self.clone(), env)?.join(part, env)?;
+            }
+            Ok(joined)
+        })
+    }
+    /// Pad the last axis of an array to a width
+    ///
+    /// A negative width pads at the end instead of the start.
+    /// The rows of boxed arrays are padded individually.
+    pub(crate) fn pad(&self, arr: Self, env: &Uiua) -> UiuaResult<Self> {
+        let width = self.as_int(env, "Pad width must be an integer")?;
+        arr.keep_label(|arr| {
+            Ok(match arr {
+                Value::Num(arr) => {
+                    pad(arr, width, env.num_scalar_fill().unwrap_or(0.0), env)?.into()
+                }
+                Value::Byte(arr) => {
+                    pad(arr, width, env.byte_scalar_fill().unwrap_or(0), env)?.into()
+                }
+                Value::Complex(arr) => {
+                    let fill = env.complex_scalar_fill().unwrap_or(Complex::ZERO);
+                    pad(arr, width, fill, env)?.into()
+                }
+                Value::Char(arr) => {
+                    pad(arr, width, env.char_scalar_fill().unwrap_or(' '), env)?.into()
+                }
+                Value::Time(arr) => {
+                    let fill = env.time_scalar_fill().unwrap_or(Timestamp::EPOCH);
+                    pad(arr, width, fill, env)?.into()
+                }
+                Value::Box(mut arr) => {
+                    for Boxed(val) in arr.data.as_mut_slice() {
+                        *val = self.pad(val.clone(), env)?;
+                    }
+                    arr.into()
+                }
+            })
+        })
+    }
+    /// Convert characters to uppercase
+    pub(crate) fn uppercase(self, env: &Uiua) -> UiuaResult<Self> {
+        self.map_case("uppercase", char::to_uppercase, env)
+    }
+    /// Convert characters to lowercase
+    pub(crate) fn lowercase(self, env: &Uiua) -> UiuaResult<Self> {
+        self.map_case("lowercase", char::to_lowercase, env)
+    }
+    fn map_case<I>(self, name: &str, f: fn(char) -> I, env: &Uiua) -> UiuaResult<Self>
+    where
+        I: Iterator<Item = char>,
+    {
+        Ok(match self {
+            Value::Char(mut arr) => {
+                for c in arr.data.as_mut_slice() {
+                    // Characters whose case maps to multiple characters are kept
+                    let mut mapped = f(*c);
+                    if let (Some(m), None) = (mapped.next(), mapped.next()) {
+                        *c = m;
+                    }
+                }
+                arr.into()
+            }
+            Value::Box(mut arr) => {
+                for Boxed(val) in arr.data.as_mut_slice() {
+                    *val = val.clone().map_case(name, f, env)?;
+                }
+                arr.into()
+            }
+            val => return Err(env.error(format!("Cannot {name} {}", val.type_name_plural()))),
+        })
+    }
+}
+
+/// Get a string or list of strings for edit distance
+fn edit_strings(val: &Value, env: &Uiua) -> UiuaResult<(Vec<Vec<char>>, bool)> {
+    let not_strings = || {
+        env.error(format!(
+            "Edit distance requires strings or lists of strings, \
+            but the array is {} of shape {}",
+            val.type_name_plural(),
+            val.shape()
+        ))
+    };
+    Ok(match val {
+        Value::Char(arr) if arr.rank() <= 1 => (vec![arr.data.to_vec()], false),
+        Value::Char(arr) if arr.rank() == 2 => {
+            (arr.row_slices().map(<[char]>::to_vec).collect(), true)
+        }
+        Value::Box(arr) if arr.rank() == 1 => {
+            let mut strings = Vec::with_capacity(arr.row_count());
+            for Boxed(val) in &arr.data {
+                match val {
+                    Value::Char(s) if s.rank() <= 1 => strings.push(s.data.to_vec()),
+                    _ => return Err(not_strings()),
+                }
+            }
+            (strings, true)
+        }
+        _ => return Err(not_strings()),
+    })
+}
+
+impl Value {
+    /// Get the Levenshtein distance between strings
+    ///
+    /// Lists of strings give the distance between every pair.
+    pub(crate) fn levenshtein(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let (a, a_list) = edit_strings(self, env)?;
+        let (b, b_list) = edit_strings(other, env)?;
+        let data: Vec<f64> = (a.par_iter())
+            .flat_map_iter(|a| b.iter().map(move |b| levenshtein(a, b) as f64))
+            .collect();
+        let mut shape = Shape::scalar();
+        if a_list {
+            shape.push(a.len());
+        }
+        if b_list {
+            shape.push(b.len());
+        }
+        Ok(Array::new(shape, EcoVec::from(data)).into())
+    }
+}
+
+/// The Levenshtein distance between two lists
+///
+/// Only a diagonal band of the usual dynamic programming table is computed.
+/// The band is doubled until the distance fits inside it.
+fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    if b.is_empty() {
+        return a.len();
+    }
+    let mut band = (a.len() - b.len()).max(1);
+    loop {
+        if let Some(dist) = banded_levenshtein(a, b, band) {
+            return dist;
+        }
+        band *= 2;
+    }
+}
+
+/// The Levenshtein distance if it is at most `band`
+fn banded_levenshtein<T: PartialEq>(a: &[T], b: &[T], band: usize) -> Option<usize> {
+    const FAR: usize = usize::MAX / 2;
+    let n = b.len();
+    // Cells outside the band are treated as unreachable
+    let mut prev: Vec<usize> = (0..=n).map(|j| if j <= band { j } else { FAR }).collect();
+    let mut curr = vec![FAR; n + 1];
+    for i in 1..=a.len() {
+        let lo = i.saturating_sub(band).max(1);
+        let hi = (i + band).min(n);
+        curr.fill(FAR);
+        if i <= band {
+            curr[0] = i;
+        }
+        for j in lo..=hi {
+            let sub = prev[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+            curr[j] = sub.min(prev[j] + 1).min(curr[j - 1] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    let dist = prev[n];
+    (dist <= band).then_some(dist)
+}
+
+fn split<T: ArrayValue>(delim: &Array<T>, list: &Array<T>, env: &Uiua) -> UiuaResult<Array<Boxed>>
+where
+    Value: From<Array<T>>,
+{
+    if list.rank() != 1 {
+        return Err(env.error(format!(
+            "Only lists can be split, but the array has shape {}",
+            list.shape()
+        )));
+    }
+    if delim.rank() > 1 {
+        return Err(env.error(format!(
+            "Delimiter must be a scalar or list, but it has shape {}",
+            delim.shape()
+        )));
+    }
+    let delim = delim.data.as_slice();
+    if delim.is_empty() {
+        return Err(env.error("Cannot split by an empty delimiter"));
+    }
+    let data = list.data.as_slice();
+    let mut segments = EcoVec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + delim.len() <= data.len() {
+        if (data[i..][..delim.len()].iter().zip(delim)).all(|(a, b)| a.array_eq(b)) {
+            let segment: EcoVec<T> = data[start..i].iter().cloned().collect();
+            segments.push(Boxed(Array::from(segment).into()));
+            i += delim.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    let segment: EcoVec<T> = data[start..].iter().cloned().collect();
+    segments.push(Boxed(Array::from(segment).into()));
+    Ok(Array::from(segments))
+}
+
+fn pad<T: ArrayValue>(
+    mut arr: Array<T>,
+    width: isize,
+    fill: T,
+    env: &Uiua,
+) -> UiuaResult<Array<T>> {
+    if arr.rank() == 0 {
+        arr.shape.push(1);
+    }
+    let len = *arr.shape.last().unwrap();
+    let target = width.unsigned_abs();
+    if len >= target {
+        return Ok(arr);
+    }
+    let extra = target - len;
+    let row_count: usize = arr.shape[..arr.rank() - 1].iter().product();
+    let new_len = validate_size::<T>([row_count, target], env)?;
+    let mut data = EcoVec::with_capacity(new_len);
+    for i in 0..row_count {
+        let row = &arr.data[i * len..][..len];
+        if width < 0 {
+            data.extend_from_slice(row);
+            data.extend(repeat(fill.clone()).take(extra));
+        } else {
+            data.extend(repeat(fill.clone()).take(extra));
+            data.extend_from_slice(row);
+        }
+    }
+    *arr.shape.last_mut().unwrap() = target;
+    arr.data = data.into();
+    Ok(arr)
+}
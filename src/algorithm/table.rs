@@ -1,11 +1,11 @@
 //! Algorithms for tabling modifiers
 
 use ecow::eco_vec;
+use rayon::prelude::*;
 
 use crate::{
     algorithm::{pervade::*, zip::rows1},
     function::Function,
-    random,
     value::Value,
     Array, ArrayValue, Complex, ImplPrimitive, Instr, Primitive, Shape, Uiua, UiuaResult,
 };
@@ -31,7 +31,12 @@ pub fn table(env: &mut Uiua) -> UiuaResult {
                     if let Some((Primitive::Add, _)) = f.as_flipped_primitive(&env.asm) {
                         match (&xs, &ys) {
                             (Value::Num(a), Value::Num(b)) => {
-                                return a.matrix_mul(b, env).map(|val| env.push(val))
+                                #[cfg(feature = "gpu")]
+                                if let Some(prod) = crate::gpu::matrix_mul(a, b, env) {
+                                    env.push(prod);
+                                    return Ok(());
+                                }
+                                return a.matrix_mul(b, env).map(|val| env.push(val));
                             }
                             (Value::Num(a), Value::Byte(b)) => {
                                 return a.matrix_mul(&b.convert_ref(), env).map(|val| env.push(val))
@@ -41,10 +46,25 @@ pub fn table(env: &mut Uiua) -> UiuaResult {
                             }
                             (Value::Byte(a), Value::Byte(b)) => {
                                 return a
-                                    .convert_ref()
+                                    .convert_ref::<f64>()
                                     .matrix_mul(&b.convert_ref(), env)
                                     .map(|val| env.push(val))
                             }
+                            (Value::Complex(a), Value::Complex(b)) => {
+                                return a.matrix_mul(b, env).map(|val| env.push(val))
+                            }
+                            (Value::Complex(a), Value::Num(b)) => {
+                                return a.matrix_mul(&b.convert_ref(), env).map(|val| env.push(val))
+                            }
+                            (Value::Num(a), Value::Complex(b)) => {
+                                return a.convert_ref().matrix_mul(b, env).map(|val| env.push(val))
+                            }
+                            (Value::Complex(a), Value::Byte(b)) => {
+                                return a.matrix_mul(&b.convert_ref(), env).map(|val| env.push(val))
+                            }
+                            (Value::Byte(a), Value::Complex(b)) => {
+                                return a.convert_ref().matrix_mul(b, env).map(|val| env.push(val))
+                            }
                             _ => {}
                         }
                     }
@@ -266,7 +286,7 @@ pub fn table_list(f: Function, xs: Value, ys: Value, env: &mut Uiua) -> UiuaResu
                 let shape = [xs.row_count(), ys.row_count()];
                 let mut data = eco_vec![0.0; xs.row_count() * ys.row_count()];
                 for n in data.make_mut() {
-                    *n = random();
+                    *n = env.random();
                 }
                 env.push(Array::new(shape, data));
             }
@@ -340,27 +360,44 @@ macro_rules! table_math {
 table_math!(table_nums, f64, num_num);
 table_math!(table_coms, crate::Complex, com_x);
 
+/// Above this many elements, list tables are computed in parallel
+const PAR_TABLE_THRESHOLD: usize = 100_000;
+
 fn fast_table_list<A: ArrayValue, B: ArrayValue, C: ArrayValue + Default>(
     a: Array<A>,
     b: Array<B>,
-    f: impl Fn(A, B) -> C,
+    f: impl Fn(A, B) -> C + Sync,
     env: &Uiua,
 ) -> UiuaResult<Array<C>> {
     let elem_count = validate_size::<C>([a.data.len(), b.data.len()], env)?;
     let mut new_data = eco_vec![C::default(); elem_count];
     let data_slice = new_data.make_mut();
-    let mut i = 0;
-    for x in a.data {
-        for y in b.data.iter().cloned() {
-            data_slice[i] = f(x.clone(), y);
-            i += 1;
-        }
+    let row_len = b.data.len();
+    if elem_count > PAR_TABLE_THRESHOLD {
+        // Each block is a run of whole rows of the result
+        let block_rows = a.data.len().div_ceil(rayon::current_num_threads()).max(1);
+        (data_slice.par_chunks_mut(block_rows * row_len))
+            .zip(a.data.par_chunks(block_rows))
+            .for_each(|(block, xs)| table_block(block, xs, &b.data, &f));
+    } else {
+        table_block(data_slice, &a.data, &b.data, &f);
     }
     let mut new_shape = a.shape;
     new_shape.extend_from_slice(&b.shape);
     Ok(Array::new(new_shape, new_data))
 }
 
+/// Fill a block of a list table whose rows correspond to `xs`
+fn table_block<A: Clone, B: Clone, C>(block: &mut [C], xs: &[A], ys: &[B], f: &impl Fn(A, B) -> C) {
+    let mut i = 0;
+    for x in xs {
+        for y in ys.iter().cloned() {
+            block[i] = f(x.clone(), y);
+            i += 1;
+        }
+    }
+}
+
 fn fast_table_list_join_or_couple<T: ArrayValue + Default>(
     a: Array<T>,
     b: Array<T>,
@@ -0,0 +1,198 @@
+//! Units of measure, which are stored in an array's metadata and carried through pervasive math
+
+use std::fmt;
+
+use ecow::{EcoString, EcoVec};
+use serde::*;
+
+use crate::{Uiua, UiuaResult, Value};
+
+/// A unit of measure
+///
+/// A unit is a product of base units raised to nonzero integer powers, sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Unit(EcoVec<(EcoString, i32)>);
+
+impl Unit {
+    /// Parse a unit like `m/s` or `kg*m/s^2`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut unit = Unit::default();
+        for (i, part) in s.split('/').enumerate() {
+            let (part, sign) = if i == 0 {
+                (part, 1)
+            } else {
+                let part = (part
+                    .strip_prefix('(')
+                    .and_then(|part| part.strip_suffix(')')))
+                .unwrap_or(part);
+                (part, -1)
+            };
+            if part == "1" || i == 0 && part.is_empty() {
+                continue;
+            }
+            for factor in part.split(['*', '·']) {
+                let (name, power) = match factor.split_once('^') {
+                    Some((name, power)) => {
+                        let power: i32 = (power.parse())
+                            .map_err(|_| format!("Invalid power {power:?} in unit {s:?}"))?;
+                        (name, power)
+                    }
+                    None => (factor, 1),
+                };
+                if name.is_empty()
+                    || (name.chars()).any(|c| c.is_ascii_digit() || "()^".contains(c))
+                {
+                    return Err(format!("Invalid unit {s:?}"));
+                }
+                unit.add_power(name, sign * power);
+            }
+        }
+        Ok(unit)
+    }
+    /// Whether the unit has no base units
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.is_empty()
+    }
+    fn add_power(&mut self, name: &str, power: i32) {
+        match self.0.iter().position(|(n, _)| n.as_str() >= name) {
+            Some(i) if self.0[i].0 == name => {
+                let powers = self.0.make_mut();
+                powers[i].1 += power;
+                if powers[i].1 == 0 {
+                    self.0.remove(i);
+                }
+            }
+            Some(i) if power != 0 => self.0.insert(i, (name.into(), power)),
+            None if power != 0 => self.0.push((name.into(), power)),
+            _ => {}
+        }
+    }
+    /// Multiply two units
+    pub fn product(&self, other: &Self) -> Self {
+        let mut unit = self.clone();
+        for (name, power) in &other.0 {
+            unit.add_power(name, *power);
+        }
+        unit
+    }
+    /// Divide one unit by another
+    pub fn quotient(&self, other: &Self) -> Self {
+        let mut unit = self.clone();
+        for (name, power) in &other.0 {
+            unit.add_power(name, -*power);
+        }
+        unit
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn factors<'a>(
+            f: &mut fmt::Formatter<'_>,
+            factors: impl Iterator<Item = (&'a EcoString, i32)>,
+        ) -> fmt::Result {
+            for (i, (name, power)) in factors.enumerate() {
+                if i > 0 {
+                    write!(f, "*")?;
+                }
+                write!(f, "{name}")?;
+                if power != 1 {
+                    write!(f, "^{power}")?;
+                }
+            }
+            Ok(())
+        }
+        let num = || {
+            (self.0.iter())
+                .filter(|(_, p)| *p > 0)
+                .map(|(n, p)| (n, *p))
+        };
+        let den = || {
+            (self.0.iter())
+                .filter(|(_, p)| *p < 0)
+                .map(|(n, p)| (n, -*p))
+        };
+        if num().next().is_some() {
+            factors(f, num())?;
+        } else {
+            write!(f, "1")?;
+        }
+        match den().count() {
+            0 => Ok(()),
+            1 => {
+                write!(f, "/")?;
+                factors(f, den())
+            }
+            _ => {
+                write!(f, "/(")?;
+                factors(f, den())?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// A rule for the unit of the result of a dyadic pervasive operation
+pub(crate) type UnitRule = fn(Option<Unit>, Option<Unit>, &Uiua) -> UiuaResult<Option<Unit>>;
+
+/// Units of pervasive operations whose arguments must have the same unit
+///
+/// A unitless argument takes the unit of the other.
+pub(crate) fn same(a: Option<Unit>, b: Option<Unit>, env: &Uiua) -> UiuaResult<Option<Unit>> {
+    match (a, b) {
+        (Some(a), Some(b)) if a != b => Err(env.error(format!("Units {a} and {b} do not match"))),
+        (a, b) => Ok(a.or(b)),
+    }
+}
+
+/// Units of comparisons, which must match but are not kept
+pub(crate) fn compare(a: Option<Unit>, b: Option<Unit>, env: &Uiua) -> UiuaResult<Option<Unit>> {
+    same(a, b, env).map(|_| None)
+}
+
+/// Units of multiplication
+pub(crate) fn product(a: Option<Unit>, b: Option<Unit>, _: &Uiua) -> UiuaResult<Option<Unit>> {
+    let unit = match (a, b) {
+        (Some(a), Some(b)) => a.product(&b),
+        (a, b) => a.or(b).unwrap_or_default(),
+    };
+    Ok(Some(unit).filter(|unit| !unit.is_dimensionless()))
+}
+
+/// Units of division
+///
+/// The second argument is divided by the first.
+pub(crate) fn quotient(a: Option<Unit>, b: Option<Unit>, _: &Uiua) -> UiuaResult<Option<Unit>> {
+    let a = a.unwrap_or_default();
+    let unit = b.unwrap_or_default().quotient(&a);
+    Ok(Some(unit).filter(|unit| !unit.is_dimensionless()))
+}
+
+impl Value {
+    /// Attach a unit of measure to a numeric value
+    ///
+    /// An empty unit removes the value's unit.
+    pub fn with_unit(&self, mut target: Value, env: &Uiua) -> UiuaResult<Value> {
+        let s = self.as_string(env, "Unit must be a string")?;
+        let unit = Unit::parse(&s).map_err(|e| env.error(e))?;
+        if let Value::Char(_) | Value::Box(_) | Value::Time(_) = target {
+            return Err(env.error(format!(
+                "Only numeric arrays can have units, but this is a {} array",
+                target.type_name()
+            )));
+        }
+        if unit.is_dimensionless() {
+            target.take_unit();
+        } else {
+            target.meta_mut().unit = Some(unit);
+        }
+        Ok(target)
+    }
+    /// Take the unit of measure from a value as a string
+    pub fn unwith_unit(mut self) -> (Value, Value) {
+        let unit = self.take_unit();
+        let unit = unit.map(|unit| unit.to_string()).unwrap_or_default();
+        (unit.into(), self)
+    }
+}
@@ -5,12 +5,16 @@ use std::{boxed, iter::repeat, mem::swap, slice};
 use ecow::{eco_vec, EcoVec};
 
 use crate::{
-    algorithm::pervade::bin_pervade_generic, cowslice::CowSlice, function::Function, random,
-    value::Value, Array, ArrayValue, Boxed, Complex, ImplPrimitive, Instr, PersistentMeta,
-    Primitive, Shape, Uiua, UiuaResult,
+    algorithm::pervade::bin_pervade_generic, cowslice::CowSlice, function::Function, value::Value,
+    Array, ArrayValue, Boxed, Complex, ImplPrimitive, Instr, PersistentMeta, Primitive, Shape,
+    Timestamp, Uiua, UiuaResult,
 };
 
-use super::{fill_value_shapes, fixed_rows, multi_output, FillContext, FixedRowsData, MultiOutput};
+use super::{
+    fill_value_shapes, fixed_rows, multi_output,
+    reduce::{has_fast_scan, scan_depth},
+    FillContext, FixedRowsData, MultiOutput,
+};
 
 type ValueUnFn = Box<dyn Fn(Value, usize, &mut Uiua) -> UiuaResult<Value>>;
 type ValueBinFn = Box<dyn Fn(Value, Value, usize, usize, &mut Uiua) -> UiuaResult<Value>>;
@@ -46,6 +50,12 @@ fn prim_mon_fast_fn(prim: Primitive, span: usize) -> Option<ValueUnFn> {
             Ok(v)
         }),
         Classify => spanned_mon_fn(span, |v, d, _| Ok(v.classify_depth(d))),
+        Rise => spanned_mon_fn(span, |v, d, env| Ok(v.rise_depth(d, env)?.into())),
+        Fall => spanned_mon_fn(span, |v, d, env| Ok(v.fall_depth(d, env)?.into())),
+        Mean => spanned_mon_fn(span, |v, d, env| v.mean(d, env)),
+        Variance => spanned_mon_fn(span, |v, d, env| v.variance(d, env)),
+        Stddev => spanned_mon_fn(span, |v, d, env| v.stddev(d, env)),
+        Median => spanned_mon_fn(span, |v, d, env| v.median(d, env)),
         Fix => spanned_mon_fn(span, |mut v, d, _| {
             v.fix_depth(d);
             Ok(v)
@@ -61,12 +71,12 @@ fn impl_prim_mon_fast_fn(prim: ImplPrimitive, span: usize) -> Option<ValueUnFn>
             Value::transpose_depth(&mut v, d, n);
             Ok(v)
         }),
-        ReplaceRand => spanned_mon_fn(span, |v, d, _| {
+        ReplaceRand => Box::new(|v, d, env| {
             let shape = &v.shape()[..d.min(v.rank())];
             let elem_count: usize = shape.iter().product();
             let mut data = eco_vec![0.0; elem_count];
             for n in data.make_mut() {
-                *n = random();
+                *n = env.random();
             }
             Ok(Array::new(shape, data).into())
         }),
@@ -89,6 +99,18 @@ fn f_mon_fast_fn(f: &Function, env: &Uiua) -> Option<(ValueUnFn, usize)> {
             let (f, d) = f_mon_fast_fn(f, env)?;
             (f, d + 1)
         }
+        [Instr::PushFunc(f), Instr::Prim(Scan, span)]
+            if (f.as_flipped_primitive(&env.asm)).is_some_and(|(prim, _)| has_fast_scan(prim)) =>
+        {
+            let f = f.clone();
+            let span = *span;
+            (
+                boxed::Box::new(move |v, d, env| {
+                    env.with_span(span, |env| scan_depth(&f, v, d, env))
+                }),
+                0,
+            )
+        }
         [Instr::Prim(Pop, _), Instr::Push(repl)] => {
             let replacement = repl.clone();
             (
@@ -111,6 +133,7 @@ impl Value {
             |arr| arr.repeat_shape(Shape::from(&self.shape()[..depth])),
             |arr| arr.repeat_shape(Shape::from(&self.shape()[..depth])),
             |arr| arr.repeat_shape(Shape::from(&self.shape()[..depth])),
+            |arr| arr.repeat_shape(Shape::from(&self.shape()[..depth])),
         );
         replacement
     }
@@ -1129,6 +1152,7 @@ impl Value {
             Value::Complex(_) => ctx.scalar_fill::<Complex>().is_ok(),
             Value::Char(_) => ctx.scalar_fill::<char>().is_ok(),
             Value::Box(_) => ctx.scalar_fill::<Boxed>().is_ok(),
+            Value::Time(_) => ctx.scalar_fill::<Timestamp>().is_ok(),
         }
     }
     pub(crate) fn fill_length_to<C>(&mut self, len: usize, ctx: &C) -> Result<(), &'static str>
@@ -1141,6 +1165,7 @@ impl Value {
             Value::Complex(arr) => arr.fill_length_to(len, ctx),
             Value::Char(arr) => arr.fill_length_to(len, ctx),
             Value::Box(arr) => arr.fill_length_to(len, ctx),
+            Value::Time(arr) => arr.fill_length_to(len, ctx),
         }
     }
 }
@@ -1158,6 +1183,7 @@ impl<T: ArrayValue> Array<T> {
         self.data.reserve(more_elems);
         self.data.extend(repeat(fill).take(more_elems));
         self.shape[0] = len;
+        self.reset_meta_flags();
         Ok(())
     }
 }
@@ -11,16 +11,19 @@ use ecow::{EcoString, EcoVec};
 use serde::{de::DeserializeOwned, *};
 
 use crate::{
-    algorithm::map::{MapKeys, EMPTY_NAN, TOMBSTONE_NAN},
+    algorithm::{
+        map::{MapKeys, EMPTY_NAN, TOMBSTONE_NAN},
+        units::Unit,
+    },
     cowslice::{cowslice, CowSlice},
     grid_fmt::GridFmt,
-    Boxed, Complex, HandleKind, Shape, Uiua, Value,
+    Boxed, Complex, HandleKind, Shape, Timestamp, Uiua, Value,
 };
 
 /// Uiua's array type
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(
-    from = "ArrayRep<T>",
+    try_from = "ArrayRep<T>",
     into = "ArrayRep<T>",
     bound(
         serialize = "T: ArrayValueSer + Serialize",
@@ -46,12 +49,24 @@ pub struct ArrayMeta {
     /// The keys of a map array
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub map_keys: Option<MapKeys>,
+    /// The names of the array's axes
+    ///
+    /// Unnamed axes have empty names.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub axes: Option<EcoVec<EcoString>>,
+    /// The unit of measure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
     /// The pointer value for FFI
     #[serde(skip)]
     pub pointer: Option<MetaPtr>,
     /// The kind of system handle
     #[serde(skip)]
     pub handle_kind: Option<HandleKind>,
+    /// The array's data on the GPU
+    #[cfg(feature = "gpu")]
+    #[serde(skip)]
+    pub gpu_buffer: Option<crate::GpuBuffer>,
 }
 
 /// Array pointer metadata
@@ -103,6 +118,8 @@ bitflags! {
         const BOOLEAN = 1;
         /// The array was *created from* a boolean
         const BOOLEAN_LITERAL = 2;
+        /// All the array's elements are integers
+        const INTEGER = 4;
     }
 }
 
@@ -111,6 +128,10 @@ impl ArrayFlags {
     pub fn is_boolean(self) -> bool {
         self.contains(Self::BOOLEAN)
     }
+    /// Check if the array is known to contain only integers
+    pub fn is_integer(self) -> bool {
+        self.contains(Self::INTEGER)
+    }
     /// Reset all flags
     pub fn reset(&mut self) {
         *self = Self::NONE;
@@ -122,8 +143,12 @@ pub static DEFAULT_META: ArrayMeta = ArrayMeta {
     label: None,
     flags: ArrayFlags::NONE,
     map_keys: None,
+    axes: None,
+    unit: None,
     pointer: None,
     handle_kind: None,
+    #[cfg(feature = "gpu")]
+    gpu_buffer: None,
 };
 
 /// Array metadata that can be persisted across operations
@@ -330,6 +355,31 @@ impl<T> Array<T> {
     pub fn map_keys_mut(&mut self) -> Option<&mut MapKeys> {
         self.get_meta_mut().and_then(|meta| meta.map_keys.as_mut())
     }
+    /// Get the names of the array's axes
+    ///
+    /// Names are only returned if there is one for every axis.
+    pub fn axes(&self) -> Option<&[EcoString]> {
+        (self.meta().axes.as_deref()).filter(|axes| axes.len() == self.rank())
+    }
+    /// Take the names of the array's axes from the metadata
+    pub fn take_axes(&mut self) -> Option<EcoVec<EcoString>> {
+        let rank = self.rank();
+        (self.get_meta_mut().and_then(|meta| meta.axes.take())).filter(|axes| axes.len() == rank)
+    }
+    /// Take the unit of measure from the metadata
+    pub fn take_unit(&mut self) -> Option<Unit> {
+        self.meta().unit.as_ref()?;
+        self.meta_mut().unit.take()
+    }
+    /// Modify the names of the array's axes if it has them
+    pub(crate) fn map_axes(&mut self, f: impl FnOnce(&mut EcoVec<EcoString>)) {
+        let rank = self.rank();
+        if let Some(axes) = self.get_meta_mut().and_then(|meta| meta.axes.as_mut()) {
+            if axes.len() == rank {
+                f(axes);
+            }
+        }
+    }
     /// Reset all metadata
     pub fn reset_meta(&mut self) {
         self.meta = None;
@@ -340,6 +390,16 @@ impl<T> Array<T> {
             self.meta_mut().flags.reset();
         }
     }
+    /// Get the flat data of the array
+    ///
+    /// The data is in row-major order.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+    /// Borrow the shape and flat data of the array
+    pub fn as_shaped_slice(&self) -> (&Shape, &[T]) {
+        (&self.shape, &self.data)
+    }
     /// Get an iterator over the row slices of the array
     pub fn row_slices(
         &self,
@@ -355,6 +415,18 @@ impl<T> Array<T> {
         let row_len = self.row_len();
         &self.data[row * row_len..(row + 1) * row_len]
     }
+    /// Get a cursor over the rows of the array
+    ///
+    /// See [`RowCursor`] for details.
+    pub fn row_cursor(&self) -> RowCursor<'_, T> {
+        RowCursor {
+            data: &self.data,
+            row_len: self.row_len(),
+            row_count: self.row_count(),
+            pos: 0,
+            scratch: Vec::new(),
+        }
+    }
     /// Combine the metadata of two arrays
     pub fn combine_meta(&mut self, other: &ArrayMeta) {
         if let Some(meta) = self.get_meta_mut() {
@@ -367,6 +439,107 @@ impl<T> Array<T> {
     }
 }
 
+/// A cursor over the rows of an [`Array`]
+///
+/// Rows are borrowed directly from the array's data, so no [`Array`]s are created
+/// and no data is copied. This makes the cursor suitable for extracting data in bulk.
+///
+/// Rows that need to be modified can be copied into a scratch buffer with
+/// [`RowCursor::next_row_scratch`]. The buffer is reused for every row.
+///
+/// ```
+/// # use uiua::*;
+/// let arr = Array::<f64>::new([3, 2], [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let mut cursor = arr.row_cursor();
+/// assert_eq!(cursor.nth_row_slice(2), Some(&[5.0, 6.0][..]));
+/// assert_eq!(cursor.next_row_slice(), Some(&[1.0, 2.0][..]));
+///
+/// let row = cursor.next_row_scratch().unwrap();
+/// row[0] *= 10.0;
+/// assert_eq!(row, [30.0, 4.0]);
+///
+/// assert_eq!(cursor.remaining(), 1);
+/// assert_eq!(cursor.collect::<Vec<_>>(), [&[5.0, 6.0]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowCursor<'a, T> {
+    data: &'a [T],
+    row_len: usize,
+    row_count: usize,
+    pos: usize,
+    scratch: Vec<T>,
+}
+
+impl<'a, T> RowCursor<'a, T> {
+    /// Get the number of elements in each row
+    pub fn row_len(&self) -> usize {
+        self.row_len
+    }
+    /// Get the total number of rows
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+    /// Get the index of the next row
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+    /// Get the number of rows that have not been visited
+    pub fn remaining(&self) -> usize {
+        self.row_count - self.pos
+    }
+    /// Move the cursor to a row
+    ///
+    /// The position is clamped to the number of rows.
+    pub fn seek(&mut self, row: usize) {
+        self.pos = row.min(self.row_count);
+    }
+    /// Get a slice of the row at an index, regardless of the cursor's position
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn nth_row_slice(&self, row: usize) -> Option<&'a [T]> {
+        if row >= self.row_count {
+            return None;
+        }
+        let start = row * self.row_len;
+        Some(&self.data[start..start + self.row_len])
+    }
+    /// Get a slice of the next row and advance the cursor
+    pub fn next_row_slice(&mut self) -> Option<&'a [T]> {
+        let row = self.nth_row_slice(self.pos)?;
+        self.pos += 1;
+        Some(row)
+    }
+}
+
+impl<'a, T: Clone> RowCursor<'a, T> {
+    /// Copy the next row into the cursor's scratch buffer and advance the cursor
+    ///
+    /// The returned slice may be modified freely.
+    /// It is overwritten by the next call.
+    pub fn next_row_scratch(&mut self) -> Option<&mut [T]> {
+        let row = self.next_row_slice()?;
+        self.scratch.clear();
+        self.scratch.extend_from_slice(row);
+        Some(&mut self.scratch)
+    }
+}
+
+impl<'a, T> Iterator for RowCursor<'a, T> {
+    type Item = &'a [T];
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_row_slice()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.seek(self.pos.saturating_add(n));
+        self.next_row_slice()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RowCursor<'a, T> {}
+
 impl<T: ArrayValue> Array<T> {
     /// Create a scalar array
     pub fn scalar(data: T) -> Self {
@@ -396,6 +569,30 @@ impl<T: ArrayValue> Array<T> {
             None
         }
     }
+    /// Create an array from a shape and a buffer of data without copying
+    ///
+    /// The data is returned if its length does not match the shape.
+    pub fn from_raw_parts(shape: impl Into<Shape>, data: EcoVec<T>) -> Result<Self, EcoVec<T>> {
+        let shape = shape.into();
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(data);
+        }
+        Ok(Self {
+            shape,
+            data: data.into(),
+            meta: None,
+        })
+    }
+    /// Take the shape and data buffer of the array without copying
+    ///
+    /// This fails if the data is shared with another array or is a slice of a larger buffer.
+    /// In that case, the array is returned unchanged.
+    pub fn try_into_raw_parts(self) -> Result<(Shape, EcoVec<T>), Self> {
+        match self.data.try_into_ecovec() {
+            Ok(data) => Ok((self.shape, data)),
+            Err(data) => Err(Self { data, ..self }),
+        }
+    }
     /// Get an iterator over the row arrays of the array
     pub fn rows(&self) -> impl ExactSizeIterator<Item = Self> + DoubleEndedIterator + '_ {
         (0..self.row_count()).map(|row| self.row(row))
@@ -557,6 +754,17 @@ impl<T: Clone> Array<T> {
     }
 }
 
+impl Array<f64> {
+    /// Mark the array as containing only integers
+    ///
+    /// This should only be used when the elements are known to be integers
+    /// without checking them.
+    pub(crate) fn integers(mut self) -> Self {
+        self.meta_mut().flags |= ArrayFlags::INTEGER;
+        self
+    }
+}
+
 impl Array<u8> {
     pub(crate) fn json_bool(b: bool) -> Self {
         let mut arr = Self::from(b);
@@ -699,6 +907,7 @@ impl From<Vec<usize>> for Array<f64> {
             data.len(),
             data.into_iter().map(|u| u as f64).collect::<CowSlice<_>>(),
         )
+        .integers()
     }
 }
 
@@ -892,6 +1101,27 @@ impl ArrayValue for Complex {
     }
 }
 
+impl ArrayValue for Timestamp {
+    const NAME: &'static str = "datetime";
+    const SYMBOL: char = 'τ';
+    const TYPE_ID: u8 = 4;
+    fn get_scalar_fill(env: &Uiua) -> Result<Self, &'static str> {
+        env.time_scalar_fill()
+    }
+    fn get_array_fill(env: &Uiua) -> Result<Array<Self>, &'static str> {
+        env.time_array_fill()
+    }
+    fn array_hash<H: Hasher>(&self, hasher: &mut H) {
+        self.0.hash(hasher)
+    }
+    fn proxy() -> Self {
+        Timestamp::EPOCH
+    }
+    fn empty_list_inner() -> &'static str {
+        "τ"
+    }
+}
+
 /// Trait for [`ArrayValue`]s that are real numbers
 pub trait RealArrayValue: ArrayValue + Copy {
     /// Whether the value is an integer
@@ -970,6 +1200,12 @@ impl ArrayCmp for Boxed {
     }
 }
 
+impl ArrayCmp for Timestamp {
+    fn array_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
 impl ArrayCmp<f64> for u8 {
     fn array_cmp(&self, other: &f64) -> Ordering {
         (*self as f64).array_cmp(other)
@@ -1011,7 +1247,7 @@ impl<'a> fmt::Display for FormatShape<'a> {
     serialize = "T: ArrayValueSer + Serialize",
     deserialize = "T: ArrayValueSer + Deserialize<'de>"
 ))]
-enum ArrayRep<T: ArrayValueSer> {
+pub(crate) enum ArrayRep<T: ArrayValueSer> {
     Scalar(T),
     List(T::Collection),
     Metaless(Shape, T::Collection),
@@ -1022,9 +1258,10 @@ enum ArrayRep<T: ArrayValueSer> {
     ),
 }
 
-impl<T: ArrayValueSer> From<ArrayRep<T>> for Array<T> {
-    fn from(rep: ArrayRep<T>) -> Self {
-        match rep {
+impl<T: ArrayValueSer> TryFrom<ArrayRep<T>> for Array<T> {
+    type Error = String;
+    fn try_from(rep: ArrayRep<T>) -> Result<Self, Self::Error> {
+        Ok(match rep {
             ArrayRep::Scalar(data) => Self::new([], [data]),
             ArrayRep::List(data) => {
                 let data = T::make_data(data);
@@ -1032,17 +1269,43 @@ impl<T: ArrayValueSer> From<ArrayRep<T>> for Array<T> {
             }
             ArrayRep::Metaless(shape, data) => {
                 let data = T::make_data(data);
+                validate_rep_shape(&shape, data.len())?;
                 Self::new(shape, data)
             }
-            ArrayRep::Full(shape, data, meta) => {
+            ArrayRep::Full(shape, data, mut meta) => {
                 let data = T::make_data(data);
+                validate_rep_shape(&shape, data.len())?;
+                let meta_mut = Arc::make_mut(&mut meta);
+                if let Some(map_keys) = &mut meta_mut.map_keys {
+                    let Some(&row_count) = shape.first() else {
+                        return Err("A scalar cannot be a map".into());
+                    };
+                    map_keys.validate(row_count)?;
+                }
+                if (meta_mut.axes.as_ref()).is_some_and(|axes| axes.len() != shape.len()) {
+                    meta_mut.axes = None;
+                }
+                T::repair_flags(&data, &mut meta_mut.flags);
                 Self {
                     shape,
                     data,
                     meta: Some(meta),
                 }
             }
-        }
+        })
+    }
+}
+
+/// Check that a deserialized shape matches the length of its data
+fn validate_rep_shape(shape: &Shape, len: usize) -> Result<(), String> {
+    let elements = (shape.iter()).try_fold(1usize, |acc, &dim| acc.checked_mul(dim));
+    match elements {
+        Some(elements) if elements == len => Ok(()),
+        Some(elements) => Err(format!(
+            "Array of shape {shape} should have {elements} element{}, but it has {len}",
+            if elements == 1 { "" } else { "s" }
+        )),
+        None => Err(format!("Array shape {shape} is too large")),
     }
 }
 
@@ -1052,17 +1315,24 @@ impl<T: ArrayValueSer> From<Array<T>> for ArrayRep<T> {
             return ArrayRep::Full(arr.shape, T::make_collection(arr.data), meta);
         }
         match arr.rank() {
-            0 => ArrayRep::Scalar(arr.data[0].clone()),
+            0 if arr.data[0].serializes_as_scalar() => ArrayRep::Scalar(arr.data[0].clone()),
+            0 => ArrayRep::Metaless(arr.shape, T::make_collection(arr.data)),
             1 => ArrayRep::List(T::make_collection(arr.data)),
             _ => ArrayRep::Metaless(arr.shape, T::make_collection(arr.data)),
         }
     }
 }
 
-trait ArrayValueSer: Clone {
+pub(crate) trait ArrayValueSer: Clone {
     type Collection: Serialize + DeserializeOwned;
     fn make_collection(data: CowSlice<Self>) -> Self::Collection;
     fn make_data(collection: Self::Collection) -> CowSlice<Self>;
+    /// Whether a scalar of this value can be serialized as itself rather than as a collection
+    fn serializes_as_scalar(&self) -> bool {
+        true
+    }
+    /// Remove deserialized flags that do not hold for the data
+    fn repair_flags(_data: &[Self], _flags: &mut ArrayFlags) {}
 }
 
 macro_rules! array_value_ser {
@@ -1079,11 +1349,26 @@ macro_rules! array_value_ser {
     };
 }
 
-array_value_ser!(u8);
 array_value_ser!(isize);
 array_value_ser!(usize);
 array_value_ser!(Boxed);
 array_value_ser!(Complex);
+array_value_ser!(Timestamp);
+
+impl ArrayValueSer for u8 {
+    type Collection = CowSlice<u8>;
+    fn make_collection(data: CowSlice<Self>) -> Self::Collection {
+        data
+    }
+    fn make_data(collection: Self::Collection) -> CowSlice<Self> {
+        collection
+    }
+    fn repair_flags(data: &[Self], flags: &mut ArrayFlags) {
+        if flags.is_boolean() && data.iter().any(|&b| b > 1) {
+            flags.remove(ArrayFlags::BOOLEAN);
+        }
+    }
+}
 
 impl ArrayValueSer for f64 {
     type Collection = Vec<F64Rep>;
@@ -1093,6 +1378,17 @@ impl ArrayValueSer for f64 {
     fn make_data(collection: Self::Collection) -> CowSlice<Self> {
         collection.into_iter().map(f64::from).collect()
     }
+    fn serializes_as_scalar(&self) -> bool {
+        self.is_finite()
+    }
+    fn repair_flags(data: &[Self], flags: &mut ArrayFlags) {
+        if flags.is_boolean() && !data.iter().all(|&n| n == 0.0 || n == 1.0) {
+            flags.remove(ArrayFlags::BOOLEAN);
+        }
+        if flags.is_integer() && !data.iter().all(|n| n.fract() == 0.0) {
+            flags.remove(ArrayFlags::INTEGER);
+        }
+    }
 }
 
 impl ArrayValueSer for char {
@@ -1106,7 +1402,7 @@ impl ArrayValueSer for char {
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
-enum F64Rep {
+pub(crate) enum F64Rep {
     #[serde(rename = "NaN")]
     NaN,
     #[serde(rename = "empty")]
@@ -23,7 +23,7 @@ pub struct Assembly {
     pub(crate) dynamic_functions: EcoVec<DynFn>,
 }
 
-type DynFn = Arc<dyn Fn(&mut Uiua) -> UiuaResult + Send + Sync + 'static>;
+pub(crate) type DynFn = Arc<dyn Fn(&mut Uiua) -> UiuaResult + Send + Sync + 'static>;
 
 impl Default for Assembly {
     fn default() -> Self {
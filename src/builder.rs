@@ -0,0 +1,301 @@
+//! Building arrays row by row
+
+use std::{mem::take, sync::Arc};
+
+use ecow::EcoVec;
+
+use crate::{
+    algorithm::ErrorContext, Array, ArrayMeta, ArrayValue, Boxed, Complex, FormatShape, Shape,
+    Timestamp, Value,
+};
+
+/// A builder that creates an [`Array`] from rows
+///
+/// Rows are added with [`ArrayBuilder::push_row`] in amortized constant time
+/// and removed with [`ArrayBuilder::pop_row`].
+/// All rows must have the same shape. Fill values are never used.
+///
+/// [`ArrayBuilder::finish`] turns the builder into an array without copying its data.
+///
+/// ```
+/// # use uiua::*;
+/// let mut builder = ArrayBuilder::with_capacity(3);
+/// for i in 0..3 {
+///     let row = Array::<f64>::from_iter([i as f64, i as f64 * 2.0]);
+///     builder.push_row(row, &()).unwrap();
+/// }
+/// assert_eq!(builder.pop_row(), Some(Array::from_iter([2.0, 4.0])));
+/// let arr = builder.finish();
+/// assert_eq!(arr.shape(), &[2, 2]);
+/// assert_eq!(arr.data(), [0.0, 0.0, 1.0, 2.0]);
+/// ```
+#[derive(Clone)]
+pub struct ArrayBuilder<T> {
+    row_shape: Option<Shape>,
+    row_count: usize,
+    reserve_rows: usize,
+    data: EcoVec<T>,
+    meta: Option<Arc<ArrayMeta>>,
+}
+
+impl<T: ArrayValue> Default for ArrayBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ArrayValue> ArrayBuilder<T> {
+    /// Create a new builder
+    ///
+    /// The row shape is taken from the first row that is pushed.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+    /// Create a new builder with space for some number of rows
+    ///
+    /// The space is allocated when the first row is pushed.
+    pub fn with_capacity(rows: usize) -> Self {
+        ArrayBuilder {
+            row_shape: None,
+            row_count: 0,
+            reserve_rows: rows,
+            data: EcoVec::new(),
+            meta: None,
+        }
+    }
+    /// Create a new builder whose rows must have the given shape
+    ///
+    /// If no rows are pushed, the finished array still has the row shape.
+    pub fn with_row_shape(row_shape: impl Into<Shape>) -> Self {
+        ArrayBuilder {
+            row_shape: Some(row_shape.into()),
+            ..Self::new()
+        }
+    }
+    /// Get the number of rows that have been pushed
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+    /// Check if no rows have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.row_count == 0
+    }
+    /// Get the shape that rows must have, if it is known
+    pub fn row_shape(&self) -> Option<&Shape> {
+        self.row_shape.as_ref()
+    }
+    /// Check if a row can be pushed
+    pub(crate) fn fits(&self, row: &Array<T>) -> bool {
+        (self.row_shape.as_ref()).map_or(true, |shape| *shape == row.shape)
+    }
+    /// Push a row
+    ///
+    /// Fails if the row's shape does not match the shape of the other rows.
+    pub fn push_row<C: ErrorContext>(&mut self, row: Array<T>, ctx: &C) -> Result<(), C::Error> {
+        if !self.fits(&row) {
+            return Err(ctx.error(format!(
+                "Cannot add shape {} row to array with shape {} rows",
+                row.shape,
+                FormatShape(self.row_shape.as_ref().unwrap())
+            )));
+        }
+        if self.row_count == 0 {
+            self.data.reserve(self.reserve_rows * row.element_count());
+            self.row_shape = Some(row.shape.clone());
+            self.meta = row.meta.clone();
+        } else if let Some(meta) = self.meta.as_mut().map(Arc::make_mut) {
+            // This matches the metadata of arrays created by joining rows
+            meta.flags &= row.meta().flags;
+            meta.label = None;
+            meta.map_keys = None;
+            if meta.handle_kind != row.meta().handle_kind {
+                meta.handle_kind = None;
+            }
+        }
+        self.data.extend_from_slice(&row.data);
+        self.row_count += 1;
+        Ok(())
+    }
+    /// Remove the last row
+    pub fn pop_row(&mut self) -> Option<Array<T>> {
+        if self.row_count == 0 {
+            return None;
+        }
+        let row_shape = self.row_shape.clone().unwrap();
+        let start = self.data.len() - row_shape.elements();
+        let row = EcoVec::from(&self.data[start..]);
+        self.data.truncate(start);
+        self.row_count -= 1;
+        Some(Array::new(row_shape, row))
+    }
+    /// Finish building the array
+    pub fn finish(self) -> Array<T> {
+        let mut shape = self.row_shape.unwrap_or_else(Shape::scalar);
+        shape.insert(0, self.row_count);
+        let mut arr = Array::new(shape, self.data);
+        if self.row_count > 0 {
+            arr.meta = self.meta;
+        }
+        arr
+    }
+    fn convert<U>(self) -> ArrayBuilder<U>
+    where
+        T: Into<U>,
+        U: ArrayValue,
+    {
+        ArrayBuilder {
+            row_shape: self.row_shape,
+            row_count: self.row_count,
+            reserve_rows: self.reserve_rows,
+            data: self.data.into_iter().map(Into::into).collect(),
+            meta: self.meta,
+        }
+    }
+}
+
+/// A builder that creates a [`Value`] from rows
+///
+/// This works like [`ArrayBuilder`], but rows may have different types.
+/// Byte rows can be added to number arrays, and real rows can be added to complex arrays.
+/// The array is converted the first time a row of a wider type is pushed.
+///
+/// ```
+/// # use uiua::*;
+/// let mut builder = ValueBuilder::new();
+/// builder.push_row(Value::from(1u8), &()).unwrap();
+/// builder.push_row(Value::from(2.5), &()).unwrap();
+/// assert_eq!(builder.finish(), Value::from(Array::<f64>::from_iter([1.0, 2.5])));
+/// ```
+#[derive(Clone, Default)]
+pub struct ValueBuilder(ValueBuilderRows);
+
+#[derive(Clone)]
+enum ValueBuilderRows {
+    Empty(usize),
+    Num(ArrayBuilder<f64>),
+    Byte(ArrayBuilder<u8>),
+    Complex(ArrayBuilder<Complex>),
+    Char(ArrayBuilder<char>),
+    Box(ArrayBuilder<Boxed>),
+    Time(ArrayBuilder<Timestamp>),
+}
+
+impl Default for ValueBuilderRows {
+    fn default() -> Self {
+        ValueBuilderRows::Empty(0)
+    }
+}
+
+impl ValueBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new builder with space for some number of rows
+    pub fn with_capacity(rows: usize) -> Self {
+        ValueBuilder(ValueBuilderRows::Empty(rows))
+    }
+    /// Get the number of rows that have been pushed
+    pub fn row_count(&self) -> usize {
+        use ValueBuilderRows::*;
+        match &self.0 {
+            Empty(_) => 0,
+            Num(b) => b.row_count(),
+            Byte(b) => b.row_count(),
+            Complex(b) => b.row_count(),
+            Char(b) => b.row_count(),
+            Box(b) => b.row_count(),
+            Time(b) => b.row_count(),
+        }
+    }
+    /// Check if no rows have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.row_count() == 0
+    }
+    /// Push a row
+    ///
+    /// Fails if the row's shape does not match the shape of the other rows
+    /// or if its type is not compatible with theirs.
+    pub fn push_row<C: ErrorContext>(&mut self, row: Value, ctx: &C) -> Result<(), C::Error> {
+        use ValueBuilderRows::*;
+        match (&mut self.0, row) {
+            (Empty(rows), row) => {
+                let rows = *rows;
+                self.0 = match row {
+                    Value::Num(_) => Num(ArrayBuilder::with_capacity(rows)),
+                    Value::Byte(_) => Byte(ArrayBuilder::with_capacity(rows)),
+                    Value::Complex(_) => Complex(ArrayBuilder::with_capacity(rows)),
+                    Value::Char(_) => Char(ArrayBuilder::with_capacity(rows)),
+                    Value::Box(_) => Box(ArrayBuilder::with_capacity(rows)),
+                    Value::Time(_) => Time(ArrayBuilder::with_capacity(rows)),
+                };
+                self.push_row(row, ctx)?;
+            }
+            (Num(b), Value::Num(row)) => b.push_row(row, ctx)?,
+            (Num(b), Value::Byte(row)) => b.push_row(row.convert(), ctx)?,
+            (Byte(b), Value::Byte(row)) => b.push_row(row, ctx)?,
+            (Complex(b), Value::Complex(row)) => b.push_row(row, ctx)?,
+            (Complex(b), Value::Num(row)) => b.push_row(row.convert(), ctx)?,
+            (Complex(b), Value::Byte(row)) => b.push_row(row.convert(), ctx)?,
+            (Char(b), Value::Char(row)) => b.push_row(row, ctx)?,
+            (Box(b), Value::Box(row)) => b.push_row(row, ctx)?,
+            (Time(b), Value::Time(row)) => b.push_row(row, ctx)?,
+            (Byte(b), row @ Value::Num(_)) => {
+                self.0 = Num(take(b).convert());
+                self.push_row(row, ctx)?;
+            }
+            (Byte(b), row @ Value::Complex(_)) => {
+                self.0 = Complex(take(b).convert());
+                self.push_row(row, ctx)?;
+            }
+            (Num(b), row @ Value::Complex(_)) => {
+                self.0 = Complex(take(b).convert());
+                self.push_row(row, ctx)?;
+            }
+            (_, row) => {
+                return Err(ctx.error(format!(
+                    "Cannot add {} row to array of {}",
+                    row.type_name(),
+                    self.type_name_plural()
+                )))
+            }
+        }
+        Ok(())
+    }
+    /// Remove the last row
+    pub fn pop_row(&mut self) -> Option<Value> {
+        use ValueBuilderRows::*;
+        match &mut self.0 {
+            Empty(_) => None,
+            Num(b) => b.pop_row().map(Into::into),
+            Byte(b) => b.pop_row().map(Into::into),
+            Complex(b) => b.pop_row().map(Into::into),
+            Char(b) => b.pop_row().map(Into::into),
+            Box(b) => b.pop_row().map(Into::into),
+            Time(b) => b.pop_row().map(Into::into),
+        }
+    }
+    /// Finish building the value
+    pub fn finish(self) -> Value {
+        use ValueBuilderRows::*;
+        match self.0 {
+            Empty(_) => Value::default(),
+            Num(b) => b.finish().into(),
+            Byte(b) => b.finish().into(),
+            Complex(b) => b.finish().into(),
+            Char(b) => b.finish().into(),
+            Box(b) => b.finish().into(),
+            Time(b) => b.finish().into(),
+        }
+    }
+    fn type_name_plural(&self) -> &'static str {
+        use ValueBuilderRows::*;
+        match &self.0 {
+            Empty(_) | Num(_) | Byte(_) => "numbers",
+            Complex(_) => "complexes",
+            Char(_) => "characters",
+            Box(_) => "boxes",
+            Time(_) => "datetimes",
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Bundling compiled programs into redistributable artifacts
+
+use std::io;
+
+use crate::{cache::CachedAssembly, Assembly, Uiua, UiuaResult, VERSION};
+
+const BUNDLE_HEADER: &str = "uiua bundle";
+const BUNDLE_SIGNATURE: &[u8] = b"Uiua bundle";
+
+/// A compiled program that can be run without its source
+///
+/// A bundle contains an [`Assembly`] along with the source files it was compiled from,
+/// so errors can still show the code they came from.
+/// Bundles can only be run by the same version of the interpreter that created them.
+///
+/// A bundle can be saved with [`Bundle::to_bytes`] and loaded with [`Bundle::from_bytes`].
+/// It can also be appended to another file, such as an interpreter executable,
+/// with [`Bundle::append_to`] and found again with [`Bundle::find_in`].
+///
+/// ```
+/// # use uiua::*;
+/// let asm = Compiler::new().load_str("F ← ×2\nF +1").unwrap().finish();
+/// let bytes = Bundle::new(asm).unwrap().to_bytes();
+///
+/// let bundle = Bundle::from_bytes(&bytes).unwrap();
+/// let mut env = Uiua::with_safe_sys();
+/// env.push(4);
+/// bundle.run(&mut env).unwrap();
+/// assert_eq!(env.pop_int().unwrap(), 10);
+///
+/// // Bundles can be appended to other files
+/// let exe = bundle.append_to(b"interpreter".to_vec());
+/// assert!(Bundle::find_in(&exe).unwrap().is_some());
+/// assert!(Bundle::find_in(b"interpreter").unwrap().is_none());
+/// ```
+#[derive(Clone)]
+pub struct Bundle {
+    asm: Assembly,
+}
+
+impl Bundle {
+    /// Create a bundle from an assembly
+    ///
+    /// Assemblies with functions bound from Rust cannot be bundled.
+    pub fn new(asm: Assembly) -> io::Result<Self> {
+        if !asm.dynamic_functions.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Assemblies with Rust functions cannot be bundled",
+            ));
+        }
+        Ok(Bundle { asm })
+    }
+    /// Get the bundled assembly
+    pub fn assembly(&self) -> &Assembly {
+        &self.asm
+    }
+    /// Take the bundled assembly
+    pub fn into_assembly(self) -> Assembly {
+        self.asm
+    }
+    /// Run the bundled program
+    pub fn run(&self, env: &mut Uiua) -> UiuaResult {
+        env.run_asm(&self.asm)
+    }
+    /// Serialize the bundle
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = bundle_version().into_bytes();
+        bytes.push(b'\n');
+        serde_json::to_writer(&mut bytes, &CachedAssembly::from(&self.asm))
+            .expect("Serializing an assembly should not fail");
+        bytes
+    }
+    /// Deserialize a bundle
+    ///
+    /// Fails if the bytes are not a bundle or if the bundle was created by a different
+    /// version of the interpreter.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let newline = (bytes.iter().position(|&b| b == b'\n'))
+            .ok_or_else(|| invalid("Not a Uiua bundle".into()))?;
+        let (version, asm) = bytes.split_at(newline);
+        let version = String::from_utf8_lossy(version);
+        let Some(version) = version.strip_prefix(BUNDLE_HEADER) else {
+            return Err(invalid("Not a Uiua bundle".into()));
+        };
+        let version = version.trim();
+        if version != VERSION {
+            return Err(invalid(format!(
+                "Bundle was created by Uiua {version}, \
+                but this is Uiua {VERSION}"
+            )));
+        }
+        let asm: CachedAssembly =
+            serde_json::from_slice(&asm[1..]).map_err(|e| invalid(e.to_string()))?;
+        Ok(Bundle { asm: asm.into() })
+    }
+    /// Append the bundle to the end of some bytes
+    ///
+    /// The bundle can be found again with [`Bundle::find_in`].
+    pub fn append_to(&self, mut bytes: Vec<u8>) -> Vec<u8> {
+        let bundle = self.to_bytes();
+        bytes.extend_from_slice(&bundle);
+        bytes.extend((bundle.len() as u64).to_le_bytes());
+        bytes.extend(BUNDLE_SIGNATURE);
+        bytes
+    }
+    /// Find a bundle appended to the end of some bytes with [`Bundle::append_to`]
+    ///
+    /// Returns `Ok(None)` if no bundle was appended.
+    pub fn find_in(bytes: &[u8]) -> io::Result<Option<Self>> {
+        let Some(bytes) = bytes.strip_suffix(BUNDLE_SIGNATURE) else {
+            return Ok(None);
+        };
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Truncated Uiua bundle");
+        let len_start = bytes.len().checked_sub(8).ok_or_else(invalid)?;
+        let (bytes, len_bytes) = bytes.split_at(len_start);
+        let len = u64::from_le_bytes(len_bytes.try_into().unwrap());
+        let start = (usize::try_from(len).ok())
+            .and_then(|len| bytes.len().checked_sub(len))
+            .ok_or_else(invalid)?;
+        Self::from_bytes(&bytes[start..]).map(Some)
+    }
+}
+
+fn bundle_version() -> String {
+    format!("{BUNDLE_HEADER} {VERSION}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn bundle() {
+        let asm = Compiler::new()
+            .load_str("M ← map [3 4] [1 2]\nF ← get ⊙M\n[1 2 3] {=1 1 \"x\"} F")
+            .unwrap()
+            .finish();
+        let run = |asm: &Assembly| {
+            let mut env = Uiua::with_safe_sys();
+            env.push(4);
+            env.run_asm(asm).unwrap();
+            env.take_stack()
+        };
+        let expected = run(&asm);
+        let bundle = Bundle::new(asm).unwrap();
+        let exe = bundle.append_to(b"not a bundle\n".to_vec());
+        let found = Bundle::find_in(&exe).unwrap().unwrap();
+        assert_eq!(run(found.assembly()), expected);
+
+        // Errors still point at the bundled source
+        let bundle = Compiler::new().load_str("⊂ [1 2] ⊟").unwrap().finish();
+        let bundle = Bundle::from_bytes(&Bundle::new(bundle).unwrap().to_bytes()).unwrap();
+        let mut env = Uiua::with_safe_sys();
+        env.push([1, 2, 3]);
+        env.push([1, 2]);
+        let err = bundle.run(&mut env).unwrap_err();
+        assert!(err.report().to_string().contains("⊂ [1 2] ⊟"));
+
+        assert!(Bundle::from_bytes(b"uiua bundle 0.0.0\n{}").is_err());
+        assert!(Bundle::find_in(b"Uiua bundle").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Caching compiled assemblies on disk
+
+use std::path::PathBuf;
+#[cfg(feature = "sha2")]
+use std::{env, fs, io, path::Path};
+
+use dashmap::DashMap;
+use ecow::{EcoString, EcoVec};
+use serde::*;
+#[cfg(feature = "sha2")]
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Array, ArrayMeta, Assembly, BindingInfo, BindingKind, Boxed, CodeSpan, Complex, DocComment,
+    FuncSlice, Inputs, Instr, Shape, Span, Timestamp, Value,
+};
+#[cfg(feature = "sha2")]
+use crate::{RunMode, VERSION};
+
+/// A cache of compiled assemblies on disk
+///
+/// Assemblies are cached per file and [`RunMode`].
+/// A cached assembly is only used if it was compiled by the same version of the interpreter
+/// and none of the files it was compiled from have changed since.
+///
+/// Code that is evaluated at compile time is not re-evaluated when a cached assembly is used.
+/// If it reads files or other external state, the cache may be out of date.
+///
+/// ```
+/// # use uiua::*;
+/// let dir = std::env::temp_dir().join(format!("uiua_cache_doc_{}", std::process::id()));
+/// let path = dir.join("main.ua");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(&path, "+ 1 2").unwrap();
+///
+/// let cache = AssemblyCache::new(dir.join("cache"));
+/// assert!(cache.load(&path, RunMode::Normal).is_none());
+/// let asm = Compiler::new().load_file(&path).unwrap().finish();
+/// cache.store(&path, RunMode::Normal, &asm).unwrap();
+/// assert!(cache.load(&path, RunMode::Normal).is_some());
+///
+/// // Changing the file invalidates the cache
+/// std::fs::write(&path, "+ 1 3").unwrap();
+/// assert!(cache.load(&path, RunMode::Normal).is_none());
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[cfg(feature = "sha2")]
+#[derive(Debug, Clone)]
+pub struct AssemblyCache {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "sha2")]
+impl AssemblyCache {
+    /// Create a cache that stores assemblies in a directory
+    ///
+    /// The directory is created when the first assembly is stored.
+    /// On Unix, only the current user may access it.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        AssemblyCache { dir: dir.into() }
+    }
+    /// Create a cache in the current user's cache directory
+    ///
+    /// This is `$XDG_CACHE_HOME/uiua` or `~/.cache/uiua` on Unix
+    /// and `%LOCALAPPDATA%\uiua` on Windows.
+    /// Returns `None` if the directory cannot be determined.
+    pub fn in_user_dir() -> Option<Self> {
+        let base = if cfg!(windows) {
+            env::var_os("LOCALAPPDATA").map(PathBuf::from)
+        } else {
+            (env::var_os("XDG_CACHE_HOME").map(PathBuf::from))
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        }?;
+        Some(Self::new(base.join("uiua")))
+    }
+    /// Get the directory the cache is stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Load the cached assembly for a file
+    ///
+    /// Returns `None` if there is no cached assembly or if it is out of date.
+    pub fn load(&self, path: &Path, mode: RunMode) -> Option<Assembly> {
+        let cached = fs::read_to_string(self.cache_path(path, mode)).ok()?;
+        let (version, rest) = cached.split_once('\n')?;
+        if version != cache_version() {
+            return None;
+        }
+        // Validate the sources before parsing the assembly
+        let (sources, asm) = rest.split_once('\n')?;
+        let sources: Vec<(PathBuf, String)> = serde_json::from_str(sources).ok()?;
+        for (path, hash) in sources {
+            let input = fs::read_to_string(&path).ok()?;
+            if hash_input(&input) != hash {
+                return None;
+            }
+        }
+        let asm: CachedAssembly = serde_json::from_str(asm).ok()?;
+        Some(asm.into())
+    }
+    /// Store the assembly for a file in the cache
+    ///
+    /// Assemblies with functions bound from Rust cannot be cached.
+    pub fn store(&self, path: &Path, mode: RunMode, asm: &Assembly) -> io::Result<()> {
+        if !asm.dynamic_functions.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Assemblies with Rust functions cannot be cached",
+            ));
+        }
+        let sources: Vec<(PathBuf, String)> = (asm.inputs.files.iter())
+            .map(|entry| (entry.key().clone(), hash_input(entry.value())))
+            .collect();
+        let mut cached = cache_version();
+        cached.push('\n');
+        cached.push_str(&serde_json::to_string(&sources)?);
+        cached.push('\n');
+        cached.push_str(&serde_json::to_string(&CachedAssembly::from(asm))?);
+        create_private_dir(&self.dir)?;
+        fs::write(self.cache_path(path, mode), cached)
+    }
+    /// Remove all cached assemblies
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+    fn cache_path(&self, path: &Path, mode: RunMode) -> PathBuf {
+        let path = path.canonicalize().unwrap_or_else(|_| path.into());
+        let key = format!("{path:?}\n{:?}\n{mode:?}", env::current_dir().ok());
+        self.dir.join(format!("{}.json", hash_input(&key)))
+    }
+}
+
+#[cfg(feature = "sha2")]
+fn cache_version() -> String {
+    format!("uiua {VERSION}")
+}
+
+/// Hash some input as hexadecimal SHA-256
+#[cfg(feature = "sha2")]
+pub(crate) fn hash_input(input: &str) -> String {
+    (Sha256::digest(input).iter())
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Create a directory that only the current user can access
+#[cfg(feature = "sha2")]
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+        // The directory may have already existed with other permissions
+        let meta = fs::metadata(dir)?;
+        if meta.permissions().mode() & 0o077 != 0 {
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    fs::create_dir_all(dir)
+}
+
+/// The serialized form of an [`Assembly`]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedAssembly {
+    instrs: Vec<CachedInstr>,
+    top_slices: Vec<FuncSlice>,
+    bindings: Vec<CachedBinding>,
+    spans: EcoVec<Span>,
+    files: Vec<(PathBuf, EcoString)>,
+    strings: EcoVec<EcoString>,
+    macros: Vec<(CodeSpan, EcoString)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedBinding {
+    kind: CachedBindingKind,
+    public: bool,
+    span: CodeSpan,
+    comment: Option<DocComment>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedInstr {
+    Push(Box<CachedValue>),
+    Instr(Instr),
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedBindingKind {
+    Const(Option<Box<CachedValue>>),
+    Kind(BindingKind),
+}
+
+/// A value that keeps its type when serialized
+///
+/// [`Value`]'s own serialization is shared by all array types,
+/// so, for example, a number array of small integers would come back as a byte array.
+#[derive(Serialize, Deserialize)]
+struct CachedValue {
+    data: CachedData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    map_keys: Option<Box<CachedValue>>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedData {
+    Byte(Array<u8>),
+    Num(Array<f64>),
+    Complex(Array<Complex>),
+    /// A character scalar and a string of one character serialize the same way,
+    /// so the shape is kept separately
+    Char(Shape, String, Option<ArrayMeta>),
+    Box(Shape, Vec<CachedValue>, Option<ArrayMeta>),
+    Time(Array<Timestamp>),
+}
+
+impl From<&Value> for CachedValue {
+    fn from(value: &Value) -> Self {
+        let data = match value {
+            Value::Byte(arr) => CachedData::Byte(arr.clone()),
+            Value::Num(arr) => CachedData::Num(arr.clone()),
+            Value::Complex(arr) => CachedData::Complex(arr.clone()),
+            Value::Char(arr) => CachedData::Char(
+                arr.shape.clone(),
+                arr.data.iter().collect(),
+                arr.meta.as_deref().cloned(),
+            ),
+            Value::Box(arr) => CachedData::Box(
+                arr.shape.clone(),
+                arr.data.iter().map(|b| (&b.0).into()).collect(),
+                arr.meta.as_deref().cloned(),
+            ),
+            Value::Time(arr) => CachedData::Time(arr.clone()),
+        };
+        let map_keys = (value.map_keys()).map(|keys| Box::new((&keys.keys).into()));
+        CachedValue { data, map_keys }
+    }
+}
+
+impl From<CachedValue> for Value {
+    fn from(cached: CachedValue) -> Self {
+        let mut value: Value = match cached.data {
+            CachedData::Byte(arr) => arr.into(),
+            CachedData::Num(arr) => arr.into(),
+            CachedData::Complex(arr) => arr.into(),
+            CachedData::Char(shape, data, meta) => {
+                let mut arr = Array::new(shape, data.chars().collect::<EcoVec<_>>());
+                arr.meta = meta.map(Into::into);
+                arr.into()
+            }
+            CachedData::Box(shape, data, meta) => {
+                let data: EcoVec<Boxed> = data.into_iter().map(|v| Boxed(v.into())).collect();
+                let mut arr = Array::new(shape, data);
+                arr.meta = meta.map(Into::into);
+                arr.into()
+            }
+            CachedData::Time(arr) => arr.into(),
+        };
+        if let (Some(keys), Some(map_keys)) = (cached.map_keys, value.map_keys_mut()) {
+            map_keys.keys = (*keys).into();
+        }
+        value
+    }
+}
+
+impl From<&Assembly> for CachedAssembly {
+    fn from(asm: &Assembly) -> Self {
+        CachedAssembly {
+            instrs: (asm.instrs.iter())
+                .map(|instr| match instr {
+                    Instr::Push(val) => CachedInstr::Push(Box::new(val.into())),
+                    instr => CachedInstr::Instr(instr.clone()),
+                })
+                .collect(),
+            top_slices: asm.top_slices.clone(),
+            bindings: (asm.bindings.iter())
+                .map(|binding| CachedBinding {
+                    kind: match &binding.kind {
+                        BindingKind::Const(val) => {
+                            CachedBindingKind::Const(val.as_ref().map(|val| Box::new(val.into())))
+                        }
+                        kind => CachedBindingKind::Kind(kind.clone()),
+                    },
+                    public: binding.public,
+                    span: binding.span.clone(),
+                    comment: binding.comment.clone(),
+                })
+                .collect(),
+            spans: asm.spans.clone(),
+            files: (asm.inputs.files.iter())
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+            strings: asm.inputs.strings.clone(),
+            macros: (asm.inputs.macros.iter())
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<CachedAssembly> for Assembly {
+    fn from(cached: CachedAssembly) -> Self {
+        Assembly {
+            instrs: (cached.instrs.into_iter())
+                .map(|instr| match instr {
+                    CachedInstr::Push(val) => Instr::Push((*val).into()),
+                    CachedInstr::Instr(instr) => instr,
+                })
+                .collect(),
+            top_slices: cached.top_slices,
+            bindings: (cached.bindings.into_iter())
+                .map(|binding| BindingInfo {
+                    kind: match binding.kind {
+                        CachedBindingKind::Const(val) => {
+                            BindingKind::Const(val.map(|val| (*val).into()))
+                        }
+                        CachedBindingKind::Kind(kind) => kind,
+                    },
+                    public: binding.public,
+                    span: binding.span,
+                    comment: binding.comment,
+                })
+                .collect(),
+            spans: cached.spans,
+            inputs: Inputs {
+                files: cached.files.into_iter().collect::<DashMap<_, _>>(),
+                strings: cached.strings,
+                macros: cached.macros.into_iter().collect::<DashMap<_, _>>(),
+            },
+            dynamic_functions: EcoVec::new(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sha2"))]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn assembly_cache() {
+        let dir = std::env::temp_dir().join(format!("uiua_cache_{}", std::process::id()));
+        let path = dir.join("main.ua");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &path,
+            "M ← map [3 4] [1 2]\nF ← get ⊙M\n[1 2 3] {=1 1 \"x\"} F",
+        )
+        .unwrap();
+        let cache = AssemblyCache::new(dir.join("cache"));
+        let asm = Compiler::new().load_file(&path).unwrap().finish();
+        cache.store(&path, RunMode::Normal, &asm).unwrap();
+        assert!(cache.load(&path, RunMode::Test).is_none());
+
+        // Cached constants keep their types
+        let run = |asm: Assembly| {
+            let mut env = Uiua::with_safe_sys();
+            env.push(4);
+            env.run_asm(asm).unwrap();
+            env.take_stack()
+        };
+        let expected = run(asm);
+        let cached = run(cache.load(&path, RunMode::Normal).unwrap());
+        assert_eq!(cached, expected);
+        let types = |vals: &[Value]| -> Vec<_> { vals.iter().map(Value::type_name).collect() };
+        assert_eq!(types(&cached), types(&expected));
+        assert_eq!(cached[0], Value::from(2));
+
+        cache.clear().unwrap();
+        assert!(cache.load(&path, RunMode::Normal).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+//! A C ABI for embedding the interpreter and exchanging arrays
+//!
+//! The declarations for C are in `include/uiua.h`.
+//! To get a linkable library, build the crate with `cargo rustc --lib --crate-type cdylib`
+//! or `--crate-type staticlib`.
+//!
+//! # Ownership
+//! - Values and environments returned as `*mut` pointers are owned by the caller.
+//!   They must be freed exactly once with [`uiua_value_free`] or [`uiua_free`].
+//! - [`uiua_push`] takes ownership of the value it is given. The value must not be used or freed afterward.
+//! - Pointers inside a [`UiuaArray`] returned by [`uiua_value_array`] and values returned by
+//!   [`uiua_value_unbox`] are borrowed from the value they came from.
+//!   They are valid until that value is freed or given away.
+//! - Strings returned as `*mut c_char` are owned by the caller and must be freed with [`uiua_string_free`].
+//! - Data passed in is always copied, so the caller keeps ownership of it.
+
+use std::{
+    any::Any,
+    ffi::{c_char, c_void, CStr, CString},
+    mem::size_of,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use ecow::EcoVec;
+
+use crate::{Array, Complex, Shape, Uiua, Value};
+
+/// The element type of a [`UiuaArray`] with `u8` elements
+pub const UIUA_BYTE: u32 = 0;
+/// The element type of a [`UiuaArray`] with `f64` elements
+pub const UIUA_NUM: u32 = 1;
+/// The element type of a [`UiuaArray`] with character elements, as `u32` Unicode scalar values
+pub const UIUA_CHAR: u32 = 2;
+/// The element type of a [`UiuaArray`] with complex elements, as pairs of `f64`s
+pub const UIUA_COMPLEX: u32 = 3;
+/// The element type of a [`UiuaArray`] with box elements
+///
+/// Box arrays have no flat data. Their elements can be read with [`uiua_value_unbox`].
+pub const UIUA_BOX: u32 = 4;
+/// The element type of a [`UiuaArray`] with datetime elements
+///
+/// Datetime arrays have no flat data.
+pub const UIUA_TIME: u32 = 5;
+
+/// A description of an array's type, shape, and row-major data
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UiuaArray {
+    /// The element type, one of the `UIUA_*` constants
+    pub ty: u32,
+    /// The number of dimensions
+    pub rank: usize,
+    /// A pointer to `rank` dimensions
+    pub shape: *const usize,
+    /// A pointer to the elements, or null if the array has no flat data
+    pub data: *const c_void,
+}
+
+/// Create a value by copying an array
+///
+/// Returns null if the type is not one of [`UIUA_BYTE`], [`UIUA_NUM`], [`UIUA_CHAR`],
+/// or [`UIUA_COMPLEX`], if a pointer is null when it should not be,
+/// if the shape has too many elements to address,
+/// or if a character is not a valid Unicode scalar value.
+///
+/// # Safety
+/// `shape` must point to `rank` dimensions, and `data` must point to
+/// as many elements of the given type as the shape has.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_from_array(array: UiuaArray) -> *mut Value {
+    if array.rank > 0 && array.shape.is_null() {
+        return ptr::null_mut();
+    }
+    let shape = if array.rank == 0 {
+        Shape::scalar()
+    } else {
+        Shape::from(slice::from_raw_parts(array.shape, array.rank))
+    };
+    let Ok(len) = shape.checked_elements() else {
+        return ptr::null_mut();
+    };
+    if len > 0 && array.data.is_null() {
+        return ptr::null_mut();
+    }
+    /// Get the data as a slice, or `None` if it is too large to address
+    unsafe fn data<'a, T>(ptr: *const c_void, len: usize) -> Option<&'a [T]> {
+        if len == 0 {
+            Some(&[])
+        } else if len.checked_mul(size_of::<T>())? > isize::MAX as usize {
+            None
+        } else {
+            Some(slice::from_raw_parts(ptr as *const T, len))
+        }
+    }
+    let value: Value = match array.ty {
+        UIUA_BYTE => match data::<u8>(array.data, len) {
+            Some(data) => Array::<u8>::new(shape, data).into(),
+            None => return ptr::null_mut(),
+        },
+        UIUA_NUM => match data::<f64>(array.data, len) {
+            Some(data) => Array::<f64>::new(shape, data).into(),
+            None => return ptr::null_mut(),
+        },
+        UIUA_CHAR => {
+            let chars: Option<EcoVec<char>> = data::<u32>(array.data, len)
+                .and_then(|data| (data.iter()).map(|&c| char::from_u32(c)).collect());
+            let Some(chars) = chars else {
+                return ptr::null_mut();
+            };
+            Array::new(shape, chars).into()
+        }
+        UIUA_COMPLEX => {
+            let Some(data) = data::<[f64; 2]>(array.data, len) else {
+                return ptr::null_mut();
+            };
+            let data: EcoVec<Complex> = (data.iter())
+                .map(|&[re, im]| Complex::new(re, im))
+                .collect();
+            Array::new(shape, data).into()
+        }
+        _ => return ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(value))
+}
+
+/// Get a borrowed view of a value's type, shape, and data
+///
+/// Complex data is laid out as pairs of `f64`s.
+///
+/// # Safety
+/// `value` must be a valid value pointer.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_array(value: *const Value) -> UiuaArray {
+    let value = &*value;
+    let (ty, data) = match value {
+        Value::Byte(arr) => (UIUA_BYTE, arr.data.as_ptr() as *const c_void),
+        Value::Num(arr) => (UIUA_NUM, arr.data.as_ptr() as *const c_void),
+        Value::Char(arr) => (UIUA_CHAR, arr.data.as_ptr() as *const c_void),
+        Value::Complex(arr) => (UIUA_COMPLEX, arr.data.as_ptr() as *const c_void),
+        Value::Box(_) => (UIUA_BOX, ptr::null()),
+        Value::Time(_) => (UIUA_TIME, ptr::null()),
+    };
+    UiuaArray {
+        ty,
+        rank: value.rank(),
+        shape: value.shape().as_ptr(),
+        data,
+    }
+}
+
+/// Get a borrowed pointer to the value in an element of a box array
+///
+/// `index` is an index into the flattened elements.
+/// Returns null if the value is not a box array or the index is out of bounds.
+///
+/// # Safety
+/// `value` must be a valid value pointer.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_unbox(value: *const Value, index: usize) -> *const Value {
+    match &*value {
+        Value::Box(arr) => arr.data.get(index).map_or(ptr::null(), |b| &b.0),
+        _ => ptr::null(),
+    }
+}
+
+/// Free a value
+///
+/// # Safety
+/// `value` must be null or an owned value pointer that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_free(value: *mut Value) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// Create a new interpreter environment
+///
+/// The environment has the standard IO backend if it is enabled, and no IO otherwise.
+#[no_mangle]
+pub extern "C" fn uiua_new() -> *mut Uiua {
+    #[cfg(feature = "native_sys")]
+    let env = Uiua::with_native_sys();
+    #[cfg(not(feature = "native_sys"))]
+    let env = Uiua::with_safe_sys();
+    Box::into_raw(Box::new(env))
+}
+
+/// Free an interpreter environment
+///
+/// # Safety
+/// `env` must be null or an owned environment pointer that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_free(env: *mut Uiua) {
+    if !env.is_null() {
+        drop(Box::from_raw(env));
+    }
+}
+
+/// Push a value onto an environment's stack, taking ownership of it
+///
+/// # Safety
+/// `env` must be a valid environment pointer, and `value` must be an owned value pointer.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_push(env: *mut Uiua, value: *mut Value) {
+    (*env).push(*Box::from_raw(value));
+}
+
+/// Pop a value from an environment's stack
+///
+/// On success, the value is written to `value` and null is returned.
+/// Otherwise, `value` is set to null and an error message is returned.
+/// Popping from an empty stack is an error.
+///
+/// # Safety
+/// `env` must be a valid environment pointer, and `value` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_pop(env: *mut Uiua, value: *mut *mut Value) -> *mut c_char {
+    *value = ptr::null_mut();
+    let env = &mut *env;
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        if env.stack().is_empty() {
+            return Err("The stack is empty".into());
+        }
+        env.pop("value").map_err(|e| e.to_string())
+    }));
+    match res {
+        Ok(Ok(popped)) => {
+            *value = Box::into_raw(Box::new(popped));
+            ptr::null_mut()
+        }
+        Ok(Err(e)) => error_string(e),
+        Err(payload) => error_string(panic_message(payload)),
+    }
+}
+
+/// Run code in an environment
+///
+/// Values already on the stack can be used by the code, and its results are left on the stack.
+/// Returns null on success or an error message on failure.
+///
+/// # Safety
+/// `env` must be a valid environment pointer, and `code` must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_run(env: *mut Uiua, code: *const c_char) -> *mut c_char {
+    let code = CStr::from_ptr(code).to_string_lossy();
+    let env = &mut *env;
+    match catch_unwind(AssertUnwindSafe(|| env.run_str(&code))) {
+        Ok(Ok(_)) => ptr::null_mut(),
+        Ok(Err(e)) => error_string(e.to_string()),
+        Err(payload) => error_string(panic_message(payload)),
+    }
+}
+
+/// Make an owned C string from an error message
+fn error_string(message: String) -> *mut c_char {
+    CString::new(message.replace('\0', "")).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Describe a panic that was caught so it does not unwind into C
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = (payload.downcast_ref::<&str>().copied())
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error");
+    format!("The interpreter panicked: {message}")
+}
+
+/// Free a string returned by the interpreter
+///
+/// # Safety
+/// `s` must be null or an owned string pointer that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_abi() {
+        use std::{ptr, slice};
+        unsafe {
+            let env = uiua_new();
+            let shape = [2usize, 3];
+            let data = [1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0];
+            let value = uiua_value_from_array(UiuaArray {
+                ty: UIUA_NUM,
+                rank: 2,
+                shape: shape.as_ptr(),
+                data: data.as_ptr() as *const c_void,
+            });
+            assert!(!value.is_null());
+            uiua_push(env, value);
+            let code = CString::new("/+").unwrap();
+            assert!(uiua_run(env, code.as_ptr()).is_null());
+            let mut result = ptr::null_mut();
+            assert!(uiua_pop(env, &mut result).is_null());
+            let array = uiua_value_array(result);
+            assert_eq!((array.ty, array.rank), (UIUA_NUM, 1));
+            assert_eq!(*array.shape, 3);
+            let data = slice::from_raw_parts(array.data as *const f64, 3);
+            assert_eq!(data, [5.0, 7.0, 9.0]);
+            uiua_value_free(result);
+            let error = uiua_pop(env, &mut result);
+            assert!(!error.is_null() && result.is_null());
+            uiua_string_free(error);
+
+            // Boxes
+            let code = CString::new("{\"ab\" 1}").unwrap();
+            assert!(uiua_run(env, code.as_ptr()).is_null());
+            let mut boxes = ptr::null_mut();
+            assert!(uiua_pop(env, &mut boxes).is_null());
+            assert_eq!(uiua_value_array(boxes).ty, UIUA_BOX);
+            let array = uiua_value_array(uiua_value_unbox(boxes, 0));
+            assert_eq!((array.ty, *array.shape), (UIUA_CHAR, 2));
+            let data = slice::from_raw_parts(array.data as *const u32, 2);
+            assert_eq!(data, ['a' as u32, 'b' as u32]);
+            assert!(uiua_value_unbox(boxes, 2).is_null());
+            uiua_value_free(boxes);
+
+            // Invalid arrays
+            let invalid = |ty, data: *const c_void| {
+                uiua_value_from_array(UiuaArray {
+                    ty,
+                    rank: 1,
+                    shape: [1usize].as_ptr(),
+                    data,
+                })
+            };
+            assert!(invalid(UIUA_BOX, ptr::null()).is_null());
+            assert!(invalid(UIUA_NUM, ptr::null()).is_null());
+            let bad_char = 0xD800u32;
+            assert!(invalid(UIUA_CHAR, &bad_char as *const u32 as *const c_void).is_null());
+            let huge = uiua_value_from_array(UiuaArray {
+                ty: UIUA_BYTE,
+                rank: 2,
+                shape: [usize::MAX, 2].as_ptr(),
+                data: data.as_ptr() as *const c_void,
+            });
+            assert!(huge.is_null());
+
+            // Errors
+            let code = CString::new("+[1 2] [1 2 3]").unwrap();
+            let error = uiua_run(env, code.as_ptr());
+            assert!(!error.is_null());
+            assert!(!CStr::from_ptr(error).to_bytes().is_empty());
+            uiua_string_free(error);
+            uiua_free(env);
+        }
+    }
+}
@@ -160,6 +160,7 @@ impl BasicValue {
                 Value::Complex(c) => c.data.iter().map(|_| BasicValue::Other).collect(),
                 Value::Char(c) => c.data.iter().map(|_| BasicValue::Other).collect(),
                 Value::Box(b) => b.data.iter().map(|_| BasicValue::Other).collect(),
+                Value::Time(b) => b.data.iter().map(|_| BasicValue::Other).collect(),
             })
         } else {
             BasicValue::Other
@@ -361,6 +362,10 @@ impl<'a> VirtualEnv<'a> {
                     let sig = self.pop_func()?.signature();
                     self.handle_args_outputs(sig.args, 1)?;
                 }
+                TimeIt => {
+                    let sig = self.pop_func()?.signature();
+                    self.handle_args_outputs(sig.args, sig.outputs + 1)?;
+                }
                 Repeat => {
                     let f = self.pop_func()?;
                     let sig = f.signature();
@@ -467,7 +472,19 @@ impl<'a> VirtualEnv<'a> {
                     let f = self.pop_func()?;
                     self.handle_sig(f.signature())?;
                 }
-                Content | Memo | Comptime => {
+                PadMode => {
+                    let modes_sig = self.pop_func()?.signature();
+                    self.handle_sig(modes_sig)?;
+                    self.handle_args_outputs(modes_sig.outputs, 0)?;
+                    let f = self.pop_func()?;
+                    self.handle_sig(f.signature())?;
+                }
+                FillBy => {
+                    let _fill = self.pop_func()?;
+                    let f = self.pop_func()?;
+                    self.handle_sig(f.signature())?;
+                }
+                Content | Memo | Comptime | SkipNan | DeepBox | Cubic => {
                     let f = self.pop_func()?;
                     self.handle_sig(f.signature())?;
                 }
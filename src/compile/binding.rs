@@ -339,7 +339,8 @@ impl Compiler {
                             );
                         }
                     }
-                    if let Some(Instr::Push(val)) = self.asm.instrs.last() {
+                    let above = self.asm.instrs.get(self.fold_floor..).unwrap_or_default();
+                    if let Some(Instr::Push(val)) = above.last() {
                         let val = val.clone();
                         self.asm.instrs.pop();
                         self.compile_bind_const(
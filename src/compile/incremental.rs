@@ -0,0 +1,809 @@
+//! Incremental compilation of edited code
+
+use std::{collections::VecDeque, ops::Range};
+
+use super::*;
+use crate::{
+    assembly::{BindingInfo, Inputs},
+    lex::{lex, Loc, Token},
+};
+
+/// A compiler that reuses work between edits of the same input
+///
+/// Every top-level item is compiled into the same long-lived compiler,
+/// and only what each item added to it is remembered:
+/// the instructions, spans, and bindings it created, the names it binds and uses,
+/// and the module files it loaded.
+///
+/// When the input is compiled again, items are matched by a hash of their text.
+/// An unchanged item is reused if the names it uses still refer to the same bindings
+/// and the files it imports have not changed. Otherwise, it is recompiled,
+/// which in turn causes the items that use the names it binds to be recompiled.
+///
+/// Items that define or invoke macros or that run code at compile time may depend on
+/// anything before them, so they are only reused if nothing before them has changed.
+///
+/// ```
+/// # use uiua::*;
+/// let mut inc = IncrementalCompiler::new(Compiler::new(), ());
+/// inc.load_str("X ← 1\nY ← 2\n+X Y").unwrap();
+/// assert_eq!(inc.reused_items(), 0);
+/// // `Y` does not depend on `X`, so only `X` and the last line are recompiled
+/// let asm = inc.load_str("X ← 5\nY ← 2\n+X Y").unwrap().finish();
+/// assert_eq!(inc.reused_items(), 1);
+/// let mut env = Uiua::with_safe_sys();
+/// env.run_asm(asm).unwrap();
+/// assert_eq!(env.pop_int().unwrap(), 7);
+/// ```
+#[derive(Clone)]
+pub struct IncrementalCompiler {
+    base: Compiler,
+    src: InputSrc,
+    /// The compiler that every item is compiled into
+    arena: Compiler,
+    /// What each item of the last compilation added to the arena
+    items: Vec<ItemRecord>,
+    /// The id of the next compiled item
+    next_id: u64,
+    /// The number of instructions, spans, and bindings in the arena that belong to discarded items
+    garbage: usize,
+    current: Compiler,
+    reused: usize,
+}
+
+/// The amount of garbage tolerated in the arena beyond the size of the live items
+const GARBAGE_SLACK: usize = 1000;
+
+/// What compiling a top-level item added to the arena
+#[derive(Clone)]
+struct ItemRecord {
+    id: u64,
+    /// A hash of the item's text and where it started, if it has any text
+    key: Option<(u64, Loc)>,
+    instrs: Range<usize>,
+    spans: Range<usize>,
+    bindings: Range<usize>,
+    top_slices: Vec<FuncSlice>,
+    /// The names the item bound
+    binds: Vec<(Ident, LocalName)>,
+    /// The names the item uses, with the ids of the items that bound them
+    uses: Vec<(Ident, Option<u64>)>,
+    /// Already-loaded modules the item imported, with the ids of the items that loaded them
+    modules: Vec<(PathBuf, Option<u64>)>,
+    /// The module files the item loaded, with hashes of their code
+    files: Vec<(PathBuf, u64)>,
+    /// The modules the item loaded, set aside until it is reused
+    loaded: Vec<(PathBuf, Import)>,
+    /// Whether the item may depend on anything before it
+    global: bool,
+    errored: bool,
+    before: ItemState,
+    after: ItemState,
+    /// The error that made the stack height unknown, if it was in this item
+    height_error: Option<Sp<SigCheckError>>,
+    code_meta: CodeMeta,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The compiler state that a top-level item depends on and changes
+#[derive(Clone, PartialEq)]
+struct ItemState {
+    stack_height: Option<usize>,
+    prev_comment: Option<EcoString>,
+    experimental: bool,
+    experimental_error: bool,
+    fill_sig_error: bool,
+    deprecated_prim_errors: HashSet<Primitive>,
+}
+
+/// What is known about a top-level item before compiling it
+struct ItemInfo {
+    key: Option<(u64, Loc)>,
+    binds: Vec<Ident>,
+    uses: Vec<Ident>,
+    global: bool,
+}
+
+/// The state of a pass over the items
+#[derive(Default)]
+struct Progress {
+    /// The ids of the items that bound each name
+    binders: HashMap<Ident, u64>,
+    /// The ids of the items that loaded each module
+    loaders: HashMap<PathBuf, u64>,
+    /// Whether any item so far was not reused
+    changed: bool,
+    prev_comment: Option<EcoString>,
+    item_errored: bool,
+}
+
+/// How far a reused item has moved since it was compiled
+#[derive(Clone, Copy)]
+struct Shift {
+    line: i64,
+    col: i64,
+    byte_pos: i64,
+    char_pos: i64,
+    /// The line the item started on, which is the only one whose columns change
+    first_line: u16,
+}
+
+impl IncrementalCompiler {
+    /// Create a new incremental compiler
+    ///
+    /// Every call to [`IncrementalCompiler::load_str`] compiles the input
+    /// as the given source on top of the given compiler's state.
+    pub fn new(mut compiler: Compiler, src: impl IntoInputSrc) -> Self {
+        let src = compiler.asm.inputs.add_src(src, "");
+        if let InputSrc::File(path) = &src {
+            compiler.scope.file_path = Some(if path.is_absolute() {
+                current_dir()
+                    .ok()
+                    .and_then(|dir| pathdiff::diff_paths(path, dir))
+                    .unwrap_or_else(|| path.to_path_buf())
+            } else {
+                path.to_path_buf()
+            });
+        }
+        IncrementalCompiler {
+            arena: compiler.clone(),
+            current: compiler.clone(),
+            base: compiler,
+            src,
+            items: Vec::new(),
+            next_id: 0,
+            garbage: 0,
+            reused: 0,
+        }
+    }
+    /// Compile the full text of the input, reusing unaffected items from the last compilation
+    pub fn load_str(&mut self, input: &str) -> UiuaResult<&mut Compiler> {
+        let mut inputs = self.base.asm.inputs.clone();
+        let (items, errors, diagnostics) = parse(input, self.src.clone(), &mut inputs);
+        if !errors.is_empty() {
+            self.current = self.base.clone();
+            self.current.asm.inputs = inputs;
+            self.current.diagnostics.extend(diagnostics);
+            self.reused = 0;
+            return Err(UiuaError::Parse(
+                errors,
+                self.current.asm.inputs.clone().into(),
+            ));
+        }
+
+        // Compile
+        let infos = item_infos(&items, input, &self.src);
+        let mut arena = take(&mut self.arena);
+        let res = arena.catching_crash(input, |arena| {
+            self.compile_items(arena, input, items, infos)
+        });
+        self.arena = arena;
+        if let Err(e) = res {
+            self.arena = self.base.clone();
+            self.items.clear();
+            self.garbage = 0;
+            self.reused = 0;
+            self.arena.asm.inputs.add_src(self.src.clone(), input);
+            self.arena.errors.push(e);
+        }
+        let mut compiler = self.arena.clone();
+
+        // Report
+        if compiler.print_diagnostics {
+            for diagnostic in diagnostics.into_iter().chain(compiler.take_diagnostics()) {
+                eprintln!("{}", diagnostic.report());
+            }
+        } else {
+            compiler.diagnostics.extend(diagnostics);
+        }
+        self.current = compiler;
+        match self.current.errors.len() {
+            0 => Ok(&mut self.current),
+            1 => Err(self.current.errors.pop().unwrap()),
+            _ => Err(UiuaError::Multi(take(&mut self.current.errors))),
+        }
+    }
+    fn compile_items(
+        &mut self,
+        arena: &mut Compiler,
+        input: &str,
+        items: Vec<Item>,
+        infos: Vec<ItemInfo>,
+    ) {
+        // Start over if too much of the arena belongs to discarded items
+        let live: usize = self.items.iter().map(ItemRecord::size).sum();
+        if self.garbage > live + GARBAGE_SLACK {
+            *arena = self.base.clone();
+            self.items.clear();
+            self.garbage = 0;
+        }
+        arena.asm.inputs.add_src(self.src.clone(), input);
+
+        // Set aside loaded modules so that only the items before an item can see them
+        for record in &mut self.items {
+            for (path, _) in &record.files {
+                if let Some(import) = arena.imports.remove(path) {
+                    record.loaded.push((path.clone(), import));
+                }
+            }
+        }
+
+        // Match items by their text
+        let mut unmatched: HashMap<u64, VecDeque<ItemRecord>> = HashMap::new();
+        for record in take(&mut self.items) {
+            if let Some((key, _)) = record.key {
+                unmatched.entry(key).or_default().push_back(record);
+            } else {
+                self.discard(arena, record);
+            }
+        }
+        let matches: Vec<Option<ItemRecord>> = (infos.iter())
+            .map(|info| {
+                let (key, _) = info.key?;
+                unmatched.get_mut(&key)?.pop_front()
+            })
+            .collect();
+        for record in unmatched.into_values().flatten() {
+            self.discard(arena, record);
+        }
+
+        // Reset the state that items build up
+        arena.scope = self.base.scope.clone();
+        arena.set_scope_comment(&items);
+        arena.asm.top_slices = self.base.asm.top_slices.clone();
+        arena.errors.clear();
+        arena.diagnostics.clear();
+        arena.code_meta = CodeMeta::default();
+        arena.deprecated_prim_errors = self.base.deprecated_prim_errors.clone();
+        if let InputSrc::File(path) = &self.src {
+            arena.current_imports.push(path.to_path_buf());
+        }
+
+        // Reuse or compile each item
+        let mut progress = Progress::default();
+        let mut file_hashes: HashMap<PathBuf, Option<u64>> = HashMap::new();
+        self.reused = 0;
+        for ((item, info), matched) in items.into_iter().zip(infos).zip(matches) {
+            let before = ItemState::new(arena, &progress.prev_comment);
+            let mut reused = None;
+            if let Some(mut record) = matched {
+                let (_, from) = record.key.unwrap();
+                let (_, to) = info.key.unwrap();
+                let shift = Shift::new(from, to);
+                let reusable = !record.errored
+                    && record.before == before
+                    && (!record.global || !progress.changed && shift.is_zero())
+                    && (record.uses.iter())
+                        .all(|(name, id)| progress.binders.get(name) == id.as_ref())
+                    && (record.modules.iter())
+                        .all(|(path, id)| progress.loaders.get(path) == id.as_ref())
+                    && record.files.iter().all(|(path, hash)| {
+                        let current = (file_hashes.entry(path.clone()))
+                            .or_insert_with(|| file_hash(arena, path));
+                        *current == Some(*hash)
+                    });
+                if reusable {
+                    if !shift.is_zero() {
+                        shift.record(arena, &mut record, &self.src);
+                        record.key = info.key;
+                    }
+                    record.restore(arena, &mut progress.prev_comment);
+                    self.reused += 1;
+                    reused = Some(record);
+                } else {
+                    self.discard(arena, record);
+                }
+            }
+            let record = match reused {
+                Some(record) => record,
+                None => {
+                    progress.changed = true;
+                    self.compile_item(arena, item, info, before, &mut progress)
+                }
+            };
+            for (name, _) in &record.binds {
+                progress.binders.insert(base_name(name), record.id);
+            }
+            for (path, _) in &record.files {
+                progress.loaders.insert(path.clone(), record.id);
+            }
+            self.items.push(record);
+        }
+        if let InputSrc::File(_) = &self.src {
+            arena.current_imports.pop();
+        }
+
+        // Collect the metadata of every item
+        arena.diagnostics = self.base.diagnostics.clone();
+        arena.code_meta = self.base.code_meta.clone();
+        for record in &self.items {
+            merge_code_meta(&mut arena.code_meta, &record.code_meta);
+            for diagnostic in &record.diagnostics {
+                let mut diagnostic = diagnostic.clone();
+                diagnostic.inputs = arena.asm.inputs.clone();
+                arena.diagnostics.insert(diagnostic);
+            }
+        }
+    }
+    fn compile_item(
+        &mut self,
+        arena: &mut Compiler,
+        item: Item,
+        info: ItemInfo,
+        before: ItemState,
+        progress: &mut Progress,
+    ) -> ItemRecord {
+        let instrs_start = arena.asm.instrs.len();
+        let spans_start = arena.asm.spans.len();
+        let bindings_start = arena.next_global;
+        let slices_start = arena.asm.top_slices.len();
+        let errors_start = arena.errors.len();
+        let bound_before: Vec<Option<LocalName>> = (info.binds.iter())
+            .map(|name| arena.scope.names.get(name).copied())
+            .collect();
+        let height_known = arena.scope.stack_height.is_ok();
+
+        arena.fold_floor = instrs_start;
+        arena.import_log = Some(Vec::new());
+        let res = arena.item(item, false, &mut progress.prev_comment);
+        arena.fold_floor = 0;
+        let import_log = arena.import_log.take().unwrap_or_default();
+
+        let item_code_meta = take(&mut arena.code_meta);
+        let item_diagnostics = take(&mut arena.diagnostics).into_iter().collect();
+        let mut errored = arena.errors.len() > errors_start;
+        if let Err(e) = res {
+            if !progress.item_errored {
+                arena.errors.push(e);
+            }
+            progress.item_errored = true;
+            errored = true;
+        }
+
+        // Determine dependencies
+        let binds = (info.binds.into_iter().zip(bound_before))
+            .filter_map(|(name, before)| {
+                let local = arena.scope.names.get(&name).copied()?;
+                (Some(local) != before).then_some((name, local))
+            })
+            .collect();
+        let uses = (info.uses.into_iter())
+            .map(|name| {
+                let id = progress.binders.get(&name).copied();
+                (name, id)
+            })
+            .collect();
+        let mut files: Vec<(PathBuf, u64)> = Vec::new();
+        let mut modules = Vec::new();
+        for (path, hash) in import_log {
+            if let Some(hash) = hash {
+                files.push((path, hash));
+            } else if !files.iter().any(|(p, _)| *p == path) {
+                let id = progress.loaders.get(&path).copied();
+                modules.push((path, id));
+            }
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        ItemRecord {
+            id,
+            key: info.key,
+            instrs: instrs_start..arena.asm.instrs.len().max(instrs_start),
+            spans: spans_start..arena.asm.spans.len(),
+            bindings: bindings_start..arena.next_global,
+            top_slices: arena
+                .asm
+                .top_slices
+                .get(slices_start..)
+                .unwrap_or_default()
+                .to_vec(),
+            binds,
+            uses,
+            modules,
+            files,
+            loaded: Vec::new(),
+            global: info.global,
+            errored,
+            after: ItemState::new(arena, &progress.prev_comment),
+            before,
+            height_error: (arena.scope.stack_height.clone().err()).filter(|_| height_known),
+            code_meta: item_code_meta,
+            diagnostics: item_diagnostics,
+        }
+    }
+    /// Throw away an item's additions to the arena
+    ///
+    /// Its instructions are left in place, but they are no longer referenced.
+    fn discard(&mut self, arena: &mut Compiler, record: ItemRecord) {
+        self.garbage += record.size();
+        let spans = arena.asm.spans.make_mut();
+        for span in spans.get_mut(record.spans).into_iter().flatten() {
+            *span = Span::Builtin;
+        }
+        let bindings = arena.asm.bindings.make_mut();
+        for binding in bindings.get_mut(record.bindings).into_iter().flatten() {
+            *binding = BindingInfo {
+                kind: BindingKind::Const(None),
+                public: false,
+                span: CodeSpan::dummy(),
+                comment: None,
+            };
+        }
+    }
+    /// Get the compiler as of the last compilation
+    pub fn compiler(&self) -> &Compiler {
+        &self.current
+    }
+    /// Get a mutable reference to the compiler as of the last compilation
+    pub fn compiler_mut(&mut self) -> &mut Compiler {
+        &mut self.current
+    }
+    /// Get the number of top-level items that were reused by the last compilation
+    pub fn reused_items(&self) -> usize {
+        self.reused
+    }
+    /// Get the number of top-level items in the last compilation
+    pub fn total_items(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl ItemRecord {
+    fn size(&self) -> usize {
+        self.instrs.len() + self.spans.len() + self.bindings.len()
+    }
+    /// Apply the item's effects on the compiler state again
+    fn restore(&mut self, comp: &mut Compiler, prev_comment: &mut Option<EcoString>) {
+        for (name, local) in &self.binds {
+            comp.scope.names.insert(name.clone(), *local);
+        }
+        comp.imports.extend(self.loaded.drain(..));
+        comp.asm.top_slices.extend(self.top_slices.iter().copied());
+        let after = &self.after;
+        if let Some(height) = after.stack_height {
+            comp.scope.stack_height = Ok(height);
+        } else if let Some(error) = &self.height_error {
+            comp.scope.stack_height = Err(error.clone());
+        }
+        *prev_comment = after.prev_comment.clone();
+        comp.scope.experimental = after.experimental;
+        comp.scope.experimental_error = after.experimental_error;
+        comp.scope.fill_sig_error = after.fill_sig_error;
+        comp.deprecated_prim_errors = after.deprecated_prim_errors.clone();
+    }
+}
+
+impl ItemState {
+    fn new(comp: &Compiler, prev_comment: &Option<EcoString>) -> Self {
+        ItemState {
+            stack_height: comp.scope.stack_height.as_ref().ok().copied(),
+            prev_comment: prev_comment.clone(),
+            experimental: comp.scope.experimental,
+            experimental_error: comp.scope.experimental_error,
+            fill_sig_error: comp.scope.fill_sig_error,
+            deprecated_prim_errors: comp.deprecated_prim_errors.clone(),
+        }
+    }
+}
+
+impl Shift {
+    fn new(from: Loc, to: Loc) -> Self {
+        Shift {
+            line: to.line as i64 - from.line as i64,
+            col: to.col as i64 - from.col as i64,
+            byte_pos: to.byte_pos as i64 - from.byte_pos as i64,
+            char_pos: to.char_pos as i64 - from.char_pos as i64,
+            first_line: from.line,
+        }
+    }
+    fn is_zero(&self) -> bool {
+        self.line == 0 && self.col == 0 && self.byte_pos == 0 && self.char_pos == 0
+    }
+    fn loc(&self, loc: &mut Loc) {
+        if loc.line == self.first_line {
+            loc.col = (loc.col as i64 + self.col) as u16;
+        }
+        loc.line = (loc.line as i64 + self.line) as u16;
+        loc.byte_pos = (loc.byte_pos as i64 + self.byte_pos) as u32;
+        loc.char_pos = (loc.char_pos as i64 + self.char_pos) as u32;
+    }
+    fn span(&self, span: &mut CodeSpan, src: &InputSrc) {
+        if span.src == *src {
+            self.loc(&mut span.start);
+            self.loc(&mut span.end);
+        }
+    }
+    fn function(&self, f: &mut Function, src: &InputSrc) {
+        if let FunctionId::Anonymous(span) | FunctionId::Macro(span) = &mut f.id {
+            self.span(span, src);
+        }
+    }
+    /// Move everything an item added to the arena
+    fn record(&self, arena: &mut Compiler, record: &mut ItemRecord, src: &InputSrc) {
+        let spans = arena.asm.spans.make_mut();
+        for span in spans.get_mut(record.spans.clone()).into_iter().flatten() {
+            if let Span::Code(span) = span {
+                self.span(span, src);
+            }
+        }
+        let bindings = arena.asm.bindings.make_mut();
+        for binding in bindings
+            .get_mut(record.bindings.clone())
+            .into_iter()
+            .flatten()
+        {
+            self.span(&mut binding.span, src);
+            if let BindingKind::Func(f) = &mut binding.kind {
+                self.function(f, src);
+            }
+        }
+        let instrs = arena.asm.instrs.make_mut();
+        for instr in instrs.get_mut(record.instrs.clone()).into_iter().flatten() {
+            if let Instr::PushFunc(f) = instr {
+                self.function(f, src);
+            }
+        }
+        self.code_meta(&mut record.code_meta, src);
+        for diagnostic in &mut record.diagnostics {
+            self.span(&mut diagnostic.span, src);
+        }
+        if let Some(error) = &mut record.height_error {
+            self.span(&mut error.span, src);
+        }
+    }
+    fn code_meta(&self, meta: &mut CodeMeta, src: &InputSrc) {
+        let span = |mut span: CodeSpan| {
+            self.span(&mut span, src);
+            span
+        };
+        let name = |mut name: Sp<Ident>| {
+            self.span(&mut name.span, src);
+            name
+        };
+        meta.global_references = (take(&mut meta.global_references).into_iter())
+            .map(|(n, index)| (name(n), index))
+            .collect();
+        meta.constant_references = (take(&mut meta.constant_references).into_iter())
+            .map(name)
+            .collect();
+        meta.function_sigs = (take(&mut meta.function_sigs).into_iter())
+            .map(|(s, sig)| (span(s), sig))
+            .collect();
+        meta.macro_expansions = (take(&mut meta.macro_expansions).into_iter())
+            .map(|(s, expansion)| (span(s), expansion))
+            .collect();
+        meta.incomplete_refs = (take(&mut meta.incomplete_refs).into_iter())
+            .map(|(s, index)| (span(s), index))
+            .collect();
+        meta.top_level_values = (take(&mut meta.top_level_values).into_iter())
+            .map(|(s, values)| (span(s), values))
+            .collect();
+        meta.strands = (take(&mut meta.strands).into_iter())
+            .map(|(s, parts)| (span(s), parts.into_iter().map(span).collect()))
+            .collect();
+        meta.arrays = (take(&mut meta.arrays).into_iter())
+            .map(|(s, parts)| (span(s), parts.into_iter().map(span).collect()))
+            .collect();
+    }
+}
+
+fn merge_code_meta(into: &mut CodeMeta, from: &CodeMeta) {
+    (into.global_references).extend(from.global_references.clone());
+    (into.constant_references).extend(from.constant_references.clone());
+    (into.function_sigs).extend(from.function_sigs.clone());
+    (into.macro_expansions).extend(from.macro_expansions.clone());
+    (into.incomplete_refs).extend(from.incomplete_refs.clone());
+    (into.top_level_values).extend(from.top_level_values.clone());
+    (into.strands).extend(from.strands.clone());
+    (into.arrays).extend(from.arrays.clone());
+}
+
+/// Hash the code of a module
+pub(super) fn module_hash(code: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash the current code of a module file
+fn file_hash(comp: &Compiler, path: &Path) -> Option<u64> {
+    let embedded = (path.to_str()).and_then(|path| path.strip_prefix(EMBEDDED_PREFIX));
+    Some(if let Some(name) = embedded {
+        module_hash(comp.embedded_module(name)?.as_bytes())
+    } else {
+        module_hash(&comp.read_module_file(path).ok()?)
+    })
+}
+
+/// Find what can be known about items from their text
+fn item_infos(items: &[Item], input: &str, src: &InputSrc) -> Vec<ItemInfo> {
+    let (tokens, ..) = lex(input, src.clone(), &mut Inputs::default());
+    (items.iter())
+        .map(|item| {
+            let binds = item_binds(item);
+            let Some(span) = item_span(item) else {
+                return ItemInfo {
+                    key: None,
+                    binds,
+                    uses: Vec::new(),
+                    global: false,
+                };
+            };
+            let mut hasher = DefaultHasher::new();
+            input[span.byte_range()].hash(&mut hasher);
+            let key = Some((hasher.finish(), span.start));
+            // Find the names the item uses
+            let mut uses = Vec::new();
+            let mut global = false;
+            let first = tokens.partition_point(|t| t.span.start.byte_pos < span.start.byte_pos);
+            for token in tokens[first..]
+                .iter()
+                .take_while(|t| t.span.end.byte_pos <= span.end.byte_pos)
+            {
+                match &token.value {
+                    Token::Ident => {
+                        let name = &input[token.span.byte_range()];
+                        global |= name.contains(['!', '‼']);
+                        uses.push(base_name(name));
+                    }
+                    Token::Glyph(Primitive::Comptime | Primitive::Quote) => global = true,
+                    _ => {}
+                }
+            }
+            uses.retain(|name| !name.is_empty());
+            uses.sort();
+            uses.dedup();
+            ItemInfo {
+                key,
+                binds,
+                uses,
+                global,
+            }
+        })
+        .collect()
+}
+
+/// Get the names an item may bind
+fn item_binds(item: &Item) -> Vec<Ident> {
+    match item {
+        Item::Binding(binding) => vec![binding.name.value.clone()],
+        Item::Import(import) => (import.name.iter())
+            .chain(import.items())
+            .map(|name| name.value.clone())
+            .collect(),
+        Item::Words(_) | Item::TestScope(_) => Vec::new(),
+    }
+}
+
+/// Get the alphabetic part of a name
+///
+/// Subscripts and macro exclamation marks can be written in multiple ways,
+/// so dependencies are tracked by this part alone.
+fn base_name(name: &str) -> Ident {
+    name.chars().take_while(|c| c.is_alphabetic()).collect()
+}
+
+/// Get the span of an item
+fn item_span(item: &Item) -> Option<CodeSpan> {
+    Some(match item {
+        Item::Words(lines) => {
+            let first = lines.iter().find_map(|line| line.first())?;
+            let last = lines.iter().rev().find_map(|line| line.last())?;
+            (first.span.clone()).merge(last.span.clone())
+        }
+        Item::Binding(binding) => binding.span(),
+        Item::Import(import) => import.span(),
+        Item::TestScope(items) => items.span.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::Any,
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::*;
+
+    fn run(comp: &mut Compiler) -> Vec<Value> {
+        let mut env = Uiua::with_safe_sys();
+        env.run_asm(comp.assembly().clone()).unwrap();
+        env.take_stack()
+    }
+
+    #[test]
+    fn incremental_compile() {
+        let mut inc = IncrementalCompiler::new(Compiler::new(), ());
+        let code = "F ← +1\nG ← ×2\nG F 3";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(8)]);
+        assert_eq!(inc.total_items(), 3);
+
+        // Editing the last item reuses the bindings
+        let code = "F ← +1\nG ← ×2\nF G 3";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(7)]);
+        assert_eq!(inc.reused_items(), 2);
+
+        // Editing a binding recompiles only it and its dependents
+        let code = "F ← +10\nG ← ×2\nF G 3";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(16)]);
+        assert_eq!(inc.reused_items(), 1);
+
+        // Errors are reported, and the unaffected items are still reused
+        let code = "F ← +10\nG ← ×2\nF H 3";
+        assert!(inc.load_str(code).is_err());
+        assert_eq!(inc.reused_items(), 2);
+        assert!(inc.load_str(code).is_err());
+        assert_eq!(inc.reused_items(), 2);
+        let code = "F ← +10\nG ← ×2\nG F 3";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(26)]);
+        assert_eq!(inc.reused_items(), 2);
+
+        // Removing a binding recompiles the items that used it
+        let code = "G ← ×2\nG F 3";
+        assert!(inc.load_str(code).is_err());
+        assert_eq!(inc.reused_items(), 1);
+        let code = "F ← +10\nG ← ×2\nG F 3";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(26)]);
+        assert_eq!(inc.reused_items(), 1);
+    }
+
+    #[test]
+    fn incremental_compile_moved_items() {
+        let mut inc = IncrementalCompiler::new(Compiler::new(), ());
+        let code = "F ← +1\nG ← ⍤\"oops\" 0\nH ← (\n  G\n)\nF 1\nH";
+        inc.load_str(code).unwrap();
+
+        // Items that move keep working and have their spans moved
+        let code = "X ← 5\n\n\nF ← +1\n  G ← ⍤\"oops\" 0\nH ← (\n  G\n)\nF X\nH";
+        let comp = inc.load_str(code).unwrap();
+        let asm = comp.assembly().clone();
+        assert_eq!(inc.reused_items(), 3);
+        let mut env = Uiua::with_safe_sys();
+        let err = env.run_asm(asm).unwrap_err().to_string();
+        assert!(err.contains("5:7: oops"), "{err}");
+
+        // The result matches compiling from scratch
+        let scratch = Compiler::new().load_str(code).unwrap().finish();
+        let mut env = Uiua::with_safe_sys();
+        let scratch_err = env.run_asm(scratch).unwrap_err().to_string();
+        assert_eq!(err, scratch_err);
+    }
+
+    #[test]
+    fn incremental_compile_imports() {
+        type Files = Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>;
+        struct FileSys(Files);
+        impl SysBackend for FileSys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+                (self.0.lock().unwrap().get(path).cloned()).ok_or_else(|| "Not found".into())
+            }
+        }
+        let files = Files::default();
+        let write = |code: &str| {
+            let path = PathBuf::from("lib.ua");
+            files.lock().unwrap().insert(path, code.into());
+        };
+        write("A ← 1");
+        let comp = Compiler::with_backend(FileSys(files.clone()));
+        let mut inc = IncrementalCompiler::new(comp, ());
+        let code = "~ \"lib.ua\" ~ A\nB ← 10\n+A B";
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(11)]);
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(11)]);
+        assert_eq!(inc.reused_items(), 3);
+
+        // Changing an imported file recompiles the import and its dependents
+        write("A ← 2");
+        assert_eq!(run(inc.load_str(code).unwrap()), [Value::from(12)]);
+        assert_eq!(inc.reused_items(), 1);
+    }
+}
@@ -1,6 +1,10 @@
 mod binding;
+mod incremental;
 mod modifier;
 
+use incremental::module_hash;
+pub use incremental::IncrementalCompiler;
+
 use std::{
     cell::RefCell,
     collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
@@ -21,8 +25,10 @@ use instant::Duration;
 
 use crate::{
     algorithm::invert::{invert_instrs, under_instrs},
+    assembly::DynFn,
     ast::*,
     check::{instrs_all_signatures, instrs_signature, SigCheckError, SigCheckErrorKind},
+    embedded::{EMBEDDED_PREFIX, STD_MODULES},
     format::format_word,
     function::*,
     ident_modifier_args,
@@ -32,7 +38,8 @@ use crate::{
     parse::{count_placeholders, parse, split_words, unsplit_words},
     Array, Assembly, BindingKind, Boxed, Diagnostic, DiagnosticKind, DocComment, Ident,
     ImplPrimitive, InputSrc, IntoInputSrc, IntoSysBackend, Primitive, RunMode, SemanticComment,
-    SysBackend, Uiua, UiuaError, UiuaResult, Value, CONSTANTS, EXAMPLE_UA, VERSION,
+    SysBackend, SysCapability, SysPolicy, Uiua, UiuaError, UiuaResult, Value, CONSTANTS,
+    EXAMPLE_UA, VERSION,
 };
 
 /// The Uiua compiler
@@ -56,6 +63,8 @@ pub struct Compiler {
     current_imports: Vec<PathBuf>,
     /// The bindings of imported files
     imports: HashMap<PathBuf, Import>,
+    /// Files loaded from remote modules, mapped to their module's source and root directory
+    remote_files: HashMap<PathBuf, (String, PathBuf)>,
     /// Unexpanded stack macros
     stack_macros: HashMap<usize, StackMacro>,
     /// Unexpanded array macros
@@ -78,6 +87,16 @@ pub struct Compiler {
     pre_eval_mode: PreEvalMode,
     /// The interpreter used for comptime code
     macro_env: Uiua,
+    /// Modules registered by the host that can be imported by name
+    embedded_modules: HashMap<EcoString, EcoString>,
+    /// Functions registered by the host that can be called by name
+    host_functions: HashMap<Ident, HostFunction>,
+    /// Whether modules may be imported over plain HTTP
+    allow_http_imports: bool,
+    /// Instructions before this index may not be folded into later code
+    fold_floor: usize,
+    /// The modules imported so far, with a hash of their code if they were loaded rather than reused
+    import_log: Option<Vec<(PathBuf, Option<u64>)>>,
 }
 
 impl Default for Compiler {
@@ -93,6 +112,7 @@ impl Default for Compiler {
             mode: RunMode::All,
             current_imports: Vec::new(),
             imports: HashMap::new(),
+            remote_files: HashMap::new(),
             stack_macros: HashMap::new(),
             array_macros: HashMap::new(),
             macro_depth: 0,
@@ -104,10 +124,22 @@ impl Default for Compiler {
             comptime: true,
             pre_eval_mode: PreEvalMode::default(),
             macro_env: Uiua::default(),
+            embedded_modules: HashMap::new(),
+            host_functions: HashMap::new(),
+            allow_http_imports: false,
+            fold_floor: 0,
+            import_log: None,
         }
     }
 }
 
+/// A function registered by the host
+#[derive(Clone)]
+struct HostFunction {
+    signature: Signature,
+    f: DynFn,
+}
+
 /// An imported module
 #[derive(Clone)]
 pub struct Import {
@@ -197,7 +229,7 @@ impl Default for Scope {
 }
 
 /// The index of a named local in the bindings, and whether it is public
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) struct LocalName {
     pub index: usize,
     pub public: bool,
@@ -251,6 +283,15 @@ impl Compiler {
             ..Self::default()
         }
     }
+    /// Set which capabilities `comptime` code and imports may use
+    pub fn with_policy(mut self, policy: SysPolicy) -> Self {
+        self.macro_env.rt.policy = policy;
+        self
+    }
+    /// Get the policy for `comptime` code and imports
+    pub fn policy(&self) -> SysPolicy {
+        self.macro_env.rt.policy
+    }
     /// Set the compiler's assembly
     pub fn with_assembly(self, asm: Assembly) -> Self {
         Self { asm, ..self }
@@ -294,11 +335,49 @@ impl Compiler {
         self.print_diagnostics = print_diagnostics;
         self
     }
+    /// Set whether modules may be imported from `http://` URLs
+    ///
+    /// Modules downloaded over plain HTTP can be tampered with in transit,
+    /// so only `https://` imports are allowed by default.
+    pub fn allow_http_imports(&mut self, allow: bool) -> &mut Self {
+        self.allow_http_imports = allow;
+        self
+    }
     /// Set the run mode
     pub fn mode(&mut self, mode: RunMode) -> &mut Self {
         self.mode = mode;
         self
     }
+    /// Register a module that can be imported by name
+    ///
+    /// The module is imported with `~ "std:name"`.
+    /// Modules registered this way take precedence over the built-in ones.
+    pub fn embed_module(
+        &mut self,
+        name: impl Into<EcoString>,
+        input: impl Into<EcoString>,
+    ) -> &mut Self {
+        self.embedded_modules.insert(name.into(), input.into());
+        self
+    }
+    /// Get the source of a module that can be imported by name
+    pub fn embedded_module(&self, name: &str) -> Option<EcoString> {
+        (self.embedded_modules.get(name).cloned()).or_else(|| {
+            STD_MODULES
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, input)| (*input).into())
+        })
+    }
+    /// Get the names of all modules that can be imported by name
+    pub fn embedded_module_names(&self) -> Vec<EcoString> {
+        let mut names: Vec<EcoString> = (self.embedded_modules.keys().cloned())
+            .chain(STD_MODULES.iter().map(|(name, _)| (*name).into()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
     /// Get the backend
     pub fn backend(&self) -> Arc<dyn SysBackend> {
         self.macro_env.rt.backend.clone()
@@ -432,7 +511,21 @@ code:
         }
     }
     pub(crate) fn items(&mut self, items: Vec<Item>, in_test: bool) -> UiuaResult {
-        // Set scope comment
+        self.set_scope_comment(&items);
+        let mut prev_comment = None;
+        let mut item_errored = false;
+        for item in items {
+            if let Err(e) = self.item(item, in_test, &mut prev_comment) {
+                if !item_errored {
+                    self.errors.push(e);
+                }
+                item_errored = true;
+            }
+        }
+        Ok(())
+    }
+    /// Set the scope comment from the first item
+    fn set_scope_comment(&mut self, items: &[Item]) {
         if let Some(Item::Words(lines)) = items.first() {
             let mut started = false;
             let mut comment = String::new();
@@ -463,18 +556,6 @@ code:
                 self.scope.comment = Some(comment.trim().into());
             }
         }
-
-        let mut prev_comment = None;
-        let mut item_errored = false;
-        for item in items {
-            if let Err(e) = self.item(item, in_test, &mut prev_comment) {
-                if !item_errored {
-                    self.errors.push(e);
-                }
-                item_errored = true;
-            }
-        }
-        Ok(())
     }
     fn item(
         &mut self,
@@ -561,7 +642,7 @@ code:
                     // This can be done when there are at least as many push instructions
                     // preceding the current line as there are arguments to the line
                     if !instrs.is_empty()
-                        && instr_count_before >= sig.args
+                        && instr_count_before >= self.fold_floor + sig.args
                         && (self.asm.instrs.iter().take(instr_count_before).rev())
                             .take(sig.args)
                             .all(|instr| matches!(instr, Instr::Push(_)))
@@ -707,10 +788,51 @@ code:
         self.asm
             .add_global_at(local, BindingKind::Const(value), span, comment);
     }
+    /// Check that the policy allows the capabilities an import needs
+    fn check_import_policy(
+        &self,
+        capabilities: &[SysCapability],
+        path_str: &str,
+        span: &CodeSpan,
+    ) -> UiuaResult {
+        let policy = self.macro_env.rt.policy;
+        if let Some(denied) = capabilities.iter().find(|&&cap| !policy.allows(cap)) {
+            return Err(self.fatal_error(
+                span.clone(),
+                format!(
+                    "Permission denied: importing {path_str:?} needs {} access",
+                    denied.description()
+                ),
+            ));
+        }
+        Ok(())
+    }
+    /// Check that a remote import is secure unless plain HTTP is allowed
+    fn check_http_import(&self, url: &str, span: &CodeSpan) -> UiuaResult {
+        if url.starts_with("http://") && !self.allow_http_imports {
+            return Err(self.fatal_error(
+                span.clone(),
+                format!("Importing {url:?} over plain HTTP is not allowed. Use https:// instead."),
+            ));
+        }
+        Ok(())
+    }
     /// Import a module
     pub(crate) fn import_module(&mut self, path_str: &str, span: &CodeSpan) -> UiuaResult<PathBuf> {
+        use SysCapability::*;
         // Resolve path
-        let path = if let Some(mut url) = path_str.strip_prefix("git:") {
+        let mut remote = None;
+        let mut embedded = None;
+        let path = if let Some(name) = path_str.strip_prefix(EMBEDDED_PREFIX) {
+            // Embedded import
+            let name = name.trim();
+            let input = self.embedded_module(name).ok_or_else(|| {
+                self.fatal_error(span.clone(), format!("No embedded module named `{name}`"))
+            })?;
+            embedded = Some(input);
+            PathBuf::from(format!("{EMBEDDED_PREFIX}{name}"))
+        } else if let Some(mut url) = path_str.strip_prefix("git:") {
+            self.check_import_policy(&[FsRead, FsWrite, Network], path_str, span)?;
             let mut branch = None;
             if let Some((a, b)) = url.split_once("branch:") {
                 url = a;
@@ -730,28 +852,61 @@ code:
             if !(url.starts_with("https://") || url.starts_with("http://")) {
                 url = format!("https://{url}");
             }
-            self.backend()
+            self.check_http_import(&url, span)?;
+            let path = self
+                .backend()
                 .load_git_module(&url, branch)
-                .map_err(|e| self.fatal_error(span.clone(), e))?
+                .map_err(|e| self.fatal_error(span.clone(), e))?;
+            let source = match branch {
+                Some(branch) => format!("git:{url} branch:{branch}"),
+                None => format!("git:{url}"),
+            };
+            self.add_remote_module(&path, &source);
+            remote = Some(source);
+            path
+        } else if path_str.starts_with("https://") || path_str.starts_with("http://") {
+            // URL import
+            self.check_import_policy(&[FsRead, FsWrite, Network], path_str, span)?;
+            self.check_http_import(path_str, span)?;
+            let path = self
+                .backend()
+                .load_url_module(path_str)
+                .map_err(|e| self.fatal_error(span.clone(), e))?;
+            self.add_remote_module(&path, path_str);
+            remote = Some(path_str.to_string());
+            path
         } else {
             // Normal import
-            self.resolve_import_path(Path::new(path_str))
+            self.check_import_policy(&[FsRead], path_str, span)?;
+            let path = self.resolve_import_path(Path::new(path_str));
+            // Files imported from inside a remote module are locked as part of it
+            let origin = (self.current_imports.last()).and_then(|p| self.remote_files.get(p));
+            if let Some((source, root)) = origin.cloned() {
+                let relative = pathdiff::diff_paths(&path, &root).unwrap_or_else(|| path.clone());
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                remote = Some(format!("{source} file:{relative}"));
+                self.remote_files.insert(path.clone(), (source, root));
+            }
+            path
         };
         if !self.imports.contains_key(&path) {
-            let bytes = self
-                .backend()
-                .file_read_all(&path)
-                .or_else(|e| {
-                    if path.ends_with(Path::new("example.ua")) {
-                        Ok(EXAMPLE_UA.as_bytes().to_vec())
-                    } else {
-                        Err(e)
-                    }
-                })
-                .map_err(|e| self.fatal_error(span.clone(), e))?;
-            let input: EcoString = String::from_utf8(bytes)
-                .map_err(|e| self.fatal_error(span.clone(), format!("Failed to read file: {e}")))?
-                .into();
+            let input: EcoString = if let Some(input) = embedded {
+                input
+            } else {
+                let bytes = (self.read_module_file(&path))
+                    .map_err(|e| self.fatal_error(span.clone(), e))?;
+                if let Some(source) = &remote {
+                    self.verify_remote_module(source, &bytes, span)?;
+                }
+                String::from_utf8(bytes)
+                    .map_err(|e| {
+                        self.fatal_error(span.clone(), format!("Failed to read file: {e}"))
+                    })?
+                    .into()
+            };
+            if let Some(log) = &mut self.import_log {
+                log.push((path.clone(), Some(module_hash(input.as_bytes()))));
+            }
             if self.current_imports.iter().any(|p| p == &path) {
                 return Err(self.fatal_error(
                     span.clone(),
@@ -762,6 +917,8 @@ code:
                 env.load_str_src(&input, &path).map(drop)
             })?;
             self.imports.insert(path.clone(), import);
+        } else if let Some(log) = &mut self.import_log {
+            log.push((path.clone(), None));
         }
         let import = self.imports.get(&path).unwrap();
         if import.experimental {
@@ -774,6 +931,53 @@ code:
         }
         Ok(path)
     }
+    /// Read the code of a module file
+    fn read_module_file(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.backend().file_read_all(path).or_else(|e| {
+            if path.ends_with(Path::new("example.ua")) {
+                Ok(EXAMPLE_UA.as_bytes().to_vec())
+            } else {
+                Err(e)
+            }
+        })
+    }
+    /// Remember that a file is the entry of a remote module
+    ///
+    /// Files imported relative to it are locked as part of the module.
+    fn add_remote_module(&mut self, path: &Path, source: &str) {
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        (self.remote_files).insert(path.to_path_buf(), (source.into(), root));
+    }
+    /// Check a remote module against the lockfile, locking it if it is new
+    #[cfg(feature = "sha2")]
+    fn verify_remote_module(&self, source: &str, contents: &[u8], span: &CodeSpan) -> UiuaResult {
+        use crate::Lockfile;
+        let backend = self.backend();
+        let lock_path = Path::new(Lockfile::FILE_NAME);
+        let mut lockfile = if backend.file_exists(Lockfile::FILE_NAME) {
+            let bytes = (backend.file_read_all(lock_path))
+                .map_err(|e| self.fatal_error(span.clone(), e))?;
+            Lockfile::from_toml(&String::from_utf8_lossy(&bytes))
+                .map_err(|e| self.fatal_error(span.clone(), e))?
+        } else {
+            Lockfile::default()
+        };
+        let added =
+            (lockfile.verify(source, contents)).map_err(|e| self.fatal_error(span.clone(), e))?;
+        if added {
+            (backend.file_write_all(lock_path, lockfile.to_toml().as_bytes()))
+                .map_err(|e| self.fatal_error(span.clone(), e))?;
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "sha2"))]
+    fn verify_remote_module(&self, _source: &str, _contents: &[u8], span: &CodeSpan) -> UiuaResult {
+        Err(self.fatal_error(
+            span.clone(),
+            "Remote modules cannot be verified because \
+            SHA-256 hashing is not enabled in this environment",
+        ))
+    }
     /// Resolve a declared import path relative to the path of the file that is being executed
     pub(crate) fn resolve_import_path(&self, path: &Path) -> PathBuf {
         let mut target = if let Some(parent) = self.current_imports.last().and_then(|p| p.parent())
@@ -815,7 +1019,9 @@ code:
             sig = Some(f.signature());
             let slice = f.slice;
             instrs = f.instrs(self).into();
-            if slice.start + slice.len >= self.asm.instrs.len() - 1 {
+            if slice.start >= self.fold_floor
+                && slice.start + slice.len >= self.asm.instrs.len() - 1
+            {
                 self.asm.instrs.truncate(slice.start);
                 if matches!(self.asm.instrs.last(), Some(Instr::Comment(com)) if com.starts_with('('))
                 {
@@ -1529,6 +1735,24 @@ code:
             // Name exists in scope
             (self.code_meta.global_references).insert(span.clone().sp(ident), local.index);
             self.global_index(local.index, span, call);
+        } else if let Some(hf) = self.host_functions.get(&ident).cloned() {
+            // Name is a function registered by the host
+            let index = (self.asm.dynamic_functions.iter())
+                .position(|f| Arc::ptr_eq(f, &hf.f))
+                .unwrap_or_else(|| {
+                    self.asm.dynamic_functions.push(hf.f);
+                    self.asm.dynamic_functions.len() - 1
+                });
+            let instr = Instr::Dynamic(DynamicFunction {
+                index,
+                signature: hf.signature,
+            });
+            let f = self.make_function(FunctionId::Named(ident), hf.signature, vec![instr]);
+            self.push_instr(Instr::PushFunc(f));
+            if call {
+                let span = self.add_span(span);
+                self.push_instr(Instr::Call(span));
+            }
         } else if let Some(constant) = CONSTANTS.iter().find(|c| c.name == ident) {
             // Name is a built-in constant
             let instr = Instr::push(constant.value.resolve(self.scope.file_path.as_deref()));
@@ -2076,6 +2300,50 @@ code:
             vec![Instr::Dynamic(DynamicFunction { index, signature })],
         )
     }
+    /// Register a Rust function that can be called by name like a primitive
+    ///
+    /// Unlike functions bound with [`Compiler::bind_function`], registered functions
+    /// can be called from every file, including imported modules.
+    /// Bindings with the same name take precedence over them.
+    ///
+    /// The function is passed its arguments in stack order, so the first argument is the top of the stack.
+    /// It must return as many values as its signature has outputs, and the first output ends up on top of the stack.
+    /// An error returned by the function is reported at the code that called it.
+    pub fn register_function(
+        &mut self,
+        name: impl Into<EcoString>,
+        signature: impl Into<Signature>,
+        f: impl Fn(Vec<Value>) -> Result<Vec<Value>, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        let name = name.into();
+        let signature = signature.into();
+        let fname = name.clone();
+        let f: DynFn = Arc::new(move |env: &mut Uiua| {
+            let args = (0..signature.args)
+                .map(|i| env.pop(i + 1))
+                .collect::<UiuaResult<Vec<_>>>()?;
+            let outputs = f(args).map_err(|e| env.error(e))?;
+            if outputs.len() != signature.outputs {
+                return Err(env.error(format!(
+                    "{fname} returned {} value{}, but its signature has {} output{}",
+                    outputs.len(),
+                    if outputs.len() == 1 { "" } else { "s" },
+                    signature.outputs,
+                    if signature.outputs == 1 { "" } else { "s" },
+                )));
+            }
+            for val in outputs.into_iter().rev() {
+                env.push(val);
+            }
+            Ok(())
+        });
+        (self.host_functions).insert(name, HostFunction { signature, f });
+        self
+    }
+    /// Get the names and signatures of all registered Rust functions
+    pub fn registered_functions(&self) -> impl Iterator<Item = (&Ident, Signature)> {
+        (self.host_functions.iter()).map(|(name, hf)| (name, hf.signature))
+    }
     /// Bind a function in the current scope
     ///
     /// # Errors
@@ -2092,6 +2360,22 @@ code:
         self.scope.names.insert(name, local);
         Ok(())
     }
+    /// Bind a value in the current scope
+    ///
+    /// # Errors
+    /// Returns an error in the binding name is not valid
+    pub fn bind_value(&mut self, name: impl Into<EcoString>, value: Value) -> UiuaResult {
+        let index = self.next_global;
+        let name = name.into();
+        let local = LocalName {
+            index,
+            public: true,
+        };
+        self.compile_bind_const(&name, local, Some(value), 0, None);
+        self.next_global += 1;
+        self.scope.names.insert(name, local);
+        Ok(())
+    }
     /// Create and bind a function in the current scope
     ///
     /// # Errors
@@ -2349,3 +2633,69 @@ fn recurse_words(words: &mut Vec<Sp<Word>>, f: &mut dyn FnMut(&mut Sp<Word>)) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn embedded_modules() {
+        let mut comp = Compiler::new();
+        comp.embed_module("greet", "Greet ← $\"Hello, _!\"");
+        assert!(comp.embedded_module_names().contains(&"stats".into()));
+        let asm = comp
+            .load_str(
+                "~ \"std:stats\" ~ Mean Median StdDev\n\
+                ~ \"std:string\" ~ Join Split Trim\n\
+                ~ \"std:greet\" ~ Greet\n\
+                F ← Greet Join \"+\" Split @, Trim\n\
+                G ← ⊃(Median|StdDev|Mean)\n\
+                ⊙F [G]",
+            )
+            .unwrap()
+            .finish();
+        let mut env = Uiua::with_safe_sys();
+        env.push(" a,b ");
+        env.push([2, 4, 4, 4, 5, 5, 7, 9]);
+        env.run_asm(asm).unwrap();
+        let stack = env.take_stack();
+        assert_eq!(stack, ["Hello, a+b!".into(), Value::from([4.5, 2.0, 5.0])]);
+
+        let err = Compiler::new().load_str("~ \"std:nope\"").err().unwrap();
+        assert!(err.to_string().contains("No embedded module"), "{err}");
+    }
+
+    #[test]
+    fn host_functions() {
+        let mut comp = Compiler::new();
+        comp.register_function("DbGet", (1, 1), |args| match &args[0] {
+            key if *key == "a".into() => Ok(vec![1.into()]),
+            key if *key == "b".into() => Ok(vec![2.into()]),
+            key => Err(format!("No key {}", key.show())),
+        })
+        .register_function("Flip", (2, 2), |args| {
+            Ok(vec![args[1].clone(), args[0].clone()])
+        })
+        .embed_module("db", "Sum ← +⊃(DbGet \"a\"|DbGet \"b\")");
+        let asm = comp
+            .load_str("~ \"std:db\" ~ Sum\nDbGet\n[Flip 5 Sum]")
+            .unwrap()
+            .finish();
+        let mut env = Uiua::with_safe_sys();
+        env.push("b");
+        env.run_asm(asm.clone()).unwrap();
+        assert_eq!(env.take_stack(), [Value::from(2), Value::from([3, 5])]);
+
+        // Errors are reported where the function is called
+        let mut env = Uiua::with_safe_sys();
+        env.push("c");
+        let err = env.run_asm(asm).unwrap_err();
+        assert!(err.to_string().contains("No key \"c\""), "{err}");
+        assert!(err.to_string().contains("2:1"), "{err}");
+        assert!(
+            err.report().to_string().contains("DbGet"),
+            "{}",
+            err.report()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Code coverage collection
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{Assembly, CodeSpan, InputSrc, Span};
+
+/// How many times a span of code was evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanCoverage {
+    /// The span of code
+    pub span: CodeSpan,
+    /// The number of times it was evaluated
+    pub count: usize,
+}
+
+/// Which code was evaluated during runs
+///
+/// Get it with [`Uiua::coverage`](crate::Uiua::coverage).
+///
+/// ```
+/// # use uiua::*;
+/// let mut env = Uiua::with_safe_sys().with_coverage();
+/// env.push(3);
+/// env.run_str("F ← ⟨¯|×2⟩ >2 .\nF").unwrap();
+/// let report = env.coverage().unwrap();
+/// let uncovered: Vec<_> = report.uncovered().collect();
+/// assert_eq!(uncovered.len(), 1);
+/// assert_eq!(uncovered[0].as_str(env.inputs(), |s| s.to_string()), "¯");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Every span of code that can be evaluated, sorted by source and position
+    pub spans: Vec<SpanCoverage>,
+}
+
+impl CoverageReport {
+    /// Iterate over the spans that were never evaluated
+    pub fn uncovered(&self) -> impl Iterator<Item = &CodeSpan> {
+        (self.spans.iter())
+            .filter(|cov| cov.count == 0)
+            .map(|cov| &cov.span)
+    }
+    /// Get the number of spans that were evaluated at least once
+    pub fn covered_count(&self) -> usize {
+        self.spans.iter().filter(|cov| cov.count > 0).count()
+    }
+    /// Get the fraction of spans that were evaluated at least once
+    ///
+    /// This is `1.0` if there are no spans.
+    pub fn fraction(&self) -> f64 {
+        if self.spans.is_empty() {
+            1.0
+        } else {
+            self.covered_count() as f64 / self.spans.len() as f64
+        }
+    }
+    /// Get only the coverage of a single input
+    pub fn for_src(&self, src: &InputSrc) -> Self {
+        CoverageReport {
+            spans: (self.spans.iter())
+                .filter(|cov| cov.span.src == *src)
+                .cloned()
+                .collect(),
+        }
+    }
+    /// Get the evaluation count of each line of an input
+    ///
+    /// A line's count is the largest count of any span that starts on it.
+    /// Lines are 1-indexed.
+    pub fn lines(&self, src: &InputSrc) -> BTreeMap<u16, usize> {
+        let mut lines = BTreeMap::new();
+        for cov in self.spans.iter().filter(|cov| cov.span.src == *src) {
+            let count = lines.entry(cov.span.start.line).or_insert(0);
+            *count = (*count).max(cov.count);
+        }
+        lines
+    }
+    /// Combine the coverage of another report into this one
+    ///
+    /// This is useful when the same code is run in multiple interpreters.
+    pub fn merge(&mut self, other: CoverageReport) {
+        let mut spans: BTreeMap<CodeSpan, usize> = (self.spans.drain(..))
+            .map(|cov| (cov.span, cov.count))
+            .collect();
+        for cov in other.spans {
+            *spans.entry(cov.span).or_default() += cov.count;
+        }
+        self.spans = (spans.into_iter())
+            .map(|(span, count)| SpanCoverage { span, count })
+            .collect();
+    }
+}
+
+/// Counts evaluations of spans
+#[derive(Clone, Default)]
+pub(crate) struct CoverageCounter {
+    counts: Arc<Mutex<HashMap<CodeSpan, usize>>>,
+}
+
+impl CoverageCounter {
+    pub fn record(&self, span: &Span) {
+        if let Span::Code(span) = span {
+            *self.counts.lock().entry(span.clone()).or_default() += 1;
+        }
+    }
+    pub fn report(&self, asm: &Assembly) -> CoverageReport {
+        let mut spans: BTreeMap<CodeSpan, usize> = (asm.instrs.iter())
+            .filter_map(|instr| instr.span())
+            .filter_map(|span| asm.spans.get(span)?.clone().code())
+            .map(|span| (span, 0))
+            .collect();
+        for (span, count) in self.counts.lock().iter() {
+            *spans.entry(span.clone()).or_default() += count;
+        }
+        CoverageReport {
+            spans: (spans.into_iter())
+                .map(|(span, count)| SpanCoverage { span, count })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn coverage() {
+        let mut comp = Compiler::new();
+        comp.pre_eval_mode(PreEvalMode::Lazy);
+        let asm = comp
+            .load_str("F ← ⟨+1|¯⟩\nG ← ×2\nF 0 F 0 5")
+            .unwrap()
+            .finish();
+        let mut env = Uiua::with_safe_sys().with_coverage();
+        env.run_asm(asm.clone()).unwrap();
+        let src = InputSrc::Str(0);
+        let report = env.coverage().unwrap();
+        let lines = report.lines(&src);
+        assert_eq!(lines[&1], 2);
+        assert_eq!(lines[&2], 0);
+        assert_eq!(report.uncovered().count(), 2);
+
+        // Counts are kept between runs
+        env.run_asm(asm.clone()).unwrap();
+        let mut report = env.coverage().unwrap();
+        assert_eq!(report.lines(&src)[&1], 4);
+
+        // Reports from other interpreters can be merged
+        let mut other = Uiua::with_safe_sys().with_coverage();
+        other.run_asm(asm).unwrap();
+        report.merge(other.coverage().unwrap());
+        assert_eq!(report.lines(&src)[&1], 6);
+        assert!(report.fraction() < 1.0);
+    }
+}
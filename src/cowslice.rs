@@ -54,6 +54,16 @@ impl<T> CowSlice<T> {
     pub fn is_unique(&mut self) -> bool {
         self.data.is_unique()
     }
+    /// Take the backing buffer without copying
+    ///
+    /// This only succeeds if the slice covers its whole buffer and no other slice shares it.
+    pub fn try_into_ecovec(mut self) -> Result<EcoVec<T>, Self> {
+        if self.start == 0 && self.end == self.data.len() && self.data.is_unique() {
+            Ok(self.data)
+        } else {
+            Err(self)
+        }
+    }
     pub fn is_copy_of(&self, other: &Self) -> bool {
         ptr::eq(self.data.as_ptr(), other.data.as_ptr())
             && self.start == other.start
@@ -284,6 +294,18 @@ fn cow_slice_deref_mut() {
     assert_eq!(sub, [7, 5]);
 }
 
+#[test]
+fn cow_slice_try_into_ecovec() {
+    let slice = CowSlice::from([1, 2, 3, 4]);
+    let ptr = slice.as_ptr();
+    let sub = slice.slice(1..3);
+    let slice = slice.try_into_ecovec().unwrap_err();
+    let sub = sub.try_into_ecovec().unwrap_err();
+    drop(sub);
+    let vec = slice.try_into_ecovec().unwrap();
+    assert_eq!(vec.as_ptr(), ptr);
+}
+
 impl<T: Clone> From<CowSlice<T>> for Vec<T> {
     fn from(mut slice: CowSlice<T>) -> Self {
         if slice.data.is_unique() && slice.start == 0 && slice.end == slice.data.len() {
@@ -0,0 +1,163 @@
+//! Inspecting the interpreter's state for debuggers
+
+use std::sync::Arc;
+
+use crate::{FunctionId, Shape, Span, Uiua};
+
+/// A summary of a value on the stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueSnapshot {
+    /// The name of the value's type
+    pub type_name: &'static str,
+    /// The value's shape
+    pub shape: Shape,
+    /// The pretty-printed value, with rows truncated at the preview width
+    pub preview: String,
+}
+
+/// A function call that is currently executing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallFrame {
+    /// The function that was called
+    pub id: FunctionId,
+    /// The span of the call
+    pub call_span: Span,
+    /// The span of the code currently executing in the function
+    pub span: Span,
+    /// The index of the instruction currently executing in the function
+    ///
+    /// Look it up in a [`SourceMap`](crate::SourceMap) to find the code that produced it.
+    pub instr: usize,
+}
+
+/// A snapshot of the interpreter's stacks
+///
+/// Get one with [`Uiua::stack_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackSnapshot {
+    /// The values on the stack, from bottom to top
+    pub values: Vec<ValueSnapshot>,
+    /// The function calls currently executing, from outermost to innermost
+    pub call_stack: Vec<CallFrame>,
+    /// The span of the code currently executing
+    pub span: Span,
+}
+
+pub(crate) type StepHook = Arc<dyn Fn(&Uiua) + Send + Sync>;
+
+impl Uiua {
+    /// Take a snapshot of the value stack and call stack
+    ///
+    /// Value previews have their rows truncated at `preview_width`.
+    pub fn stack_snapshot(&self, preview_width: usize) -> StackSnapshot {
+        let values = (self.rt.stack.iter())
+            .map(|val| ValueSnapshot {
+                type_name: val.type_name(),
+                shape: val.shape().clone(),
+                preview: val.show_with_width(preview_width),
+            })
+            .collect();
+        let call_stack = (self.rt.call_stack.iter())
+            .map(|frame| CallFrame {
+                id: frame.id.clone(),
+                call_span: self.get_span(frame.call_span),
+                span: self.get_span(frame.spans.last().map_or(frame.call_span, |(i, _)| *i)),
+                instr: frame.slice.start + frame.pc,
+            })
+            .collect();
+        StackSnapshot {
+            values,
+            call_stack,
+            span: self.span(),
+        }
+    }
+    /// Call a function before each primitive is executed
+    ///
+    /// The hook can inspect the interpreter with [`Uiua::stack_snapshot`].
+    /// Execution does not continue until the hook returns, so a debugger can
+    /// implement stepping by blocking in the hook.
+    pub fn with_step_hook(mut self, hook: impl Fn(&Uiua) + Send + Sync + 'static) -> Self {
+        self.rt.step_hook = Some(Arc::new(hook));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn step_hook() {
+        use std::sync::{Arc, Mutex};
+
+        let snapshots = Arc::new(Mutex::new(Vec::new()));
+        let hook_snapshots = snapshots.clone();
+        let mut env = Uiua::with_safe_sys()
+            .with_step_hook(move |env| hook_snapshots.lock().unwrap().push(env.stack_snapshot(20)));
+        env.push([1, 2, 3]);
+        env.run_str("+1 ×2").unwrap();
+        let snapshots = snapshots.lock().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let [mul, add] = &snapshots[..] else {
+            unreachable!()
+        };
+        let Span::Code(span) = &mul.span else {
+            panic!("{:?}", mul.span)
+        };
+        assert_eq!(span.as_str(env.inputs(), |s| s.to_string()), "×");
+        assert_eq!(mul.values.len(), 2);
+        assert_eq!(mul.values[0].shape, [3]);
+        assert_eq!(mul.values[0].type_name, "number");
+        assert_eq!(mul.values[0].preview, "[1 2 3]");
+        assert_eq!(add.values[0].preview, "[2 4 6]");
+        let frame = add.call_stack.last().unwrap();
+        assert_eq!(frame.id, FunctionId::Main);
+        assert_eq!(frame.span, add.span);
+    }
+
+    #[test]
+    fn breakpoint() {
+        use std::{any::Any, sync::Mutex};
+
+        #[derive(Default)]
+        struct BreakSys {
+            stop: bool,
+            snapshots: Mutex<Vec<StackSnapshot>>,
+        }
+        impl SysBackend for BreakSys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            fn breakpoint(&self, env: &Uiua) -> Result<bool, String> {
+                self.snapshots.lock().unwrap().push(env.stack_snapshot(20));
+                Ok(!self.stop)
+            }
+        }
+
+        let mut env = Uiua::with_backend(BreakSys::default());
+        env.run_str("F ← ⊙(&b +1)\nF 1 2").unwrap();
+        assert_eq!(env.take_stack(), [Value::from(3), 1.into()]);
+        let sys = env.take_backend::<BreakSys>().unwrap();
+        let snapshots = sys.snapshots.into_inner().unwrap();
+        let [snapshot] = &snapshots[..] else {
+            panic!("{} snapshots", snapshots.len())
+        };
+        let previews: Vec<_> = (snapshot.values.iter())
+            .map(|val| val.preview.as_str())
+            .collect();
+        assert_eq!(previews, ["3"]);
+
+        let mut env = Uiua::with_backend(BreakSys {
+            stop: true,
+            ..BreakSys::default()
+        });
+        let err = env.run_str("+1 &b 1").err().unwrap();
+        assert!(err.to_string().contains("interrupted"), "{err}");
+
+        let err = Uiua::with_safe_sys().run_str("&b").err().unwrap();
+        assert!(err.to_string().contains("not supported"), "{err}");
+    }
+}
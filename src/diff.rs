@@ -0,0 +1,220 @@
+//! Structural differences between values
+
+use std::fmt;
+
+use colored::*;
+
+use crate::{grid_fmt::GridFmt, Array, ArrayCmp, ArrayValue, Boxed, Shape, Value};
+
+/// The maximum number of differences shown when formatting a [`ValueDiff`]
+const MAX_SHOWN: usize = 10;
+
+/// The structural differences between two values
+///
+/// Created with [`Value::diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueDiff {
+    /// The differences, in row-major order
+    pub differences: Vec<Difference>,
+}
+
+/// A single difference between two values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The path to the part of the values that differs
+    ///
+    /// Each item is the index of an element, descending into boxes.
+    /// Scalars do not add an index, so the path is empty if the values differ as a whole.
+    pub path: Vec<Vec<usize>>,
+    /// What differs
+    pub kind: DifferenceKind,
+}
+
+/// The kind of a [`Difference`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferenceKind {
+    /// The values have different types
+    Type(&'static str, &'static str),
+    /// The values have different shapes
+    Shape(Shape, Shape),
+    /// The values have different map keys, or only one is a map
+    MapKeys,
+    /// The elements at the path differ
+    Element(String, String),
+}
+
+impl Value {
+    /// Get the structural differences between this value and another
+    ///
+    /// The values [`match`](crate::Primitive::Match) if and only if the diff is empty.
+    pub fn diff(&self, other: &Self) -> ValueDiff {
+        let mut differences = Vec::new();
+        diff_values(self, other, &mut Vec::new(), &mut differences);
+        ValueDiff { differences }
+    }
+}
+
+fn diff_values(a: &Value, b: &Value, path: &mut Vec<Vec<usize>>, out: &mut Vec<Difference>) {
+    match (a, b) {
+        (Value::Num(a), Value::Num(b)) => diff_arrays(a, b, path, out),
+        (Value::Byte(a), Value::Byte(b)) => diff_arrays(a, b, path, out),
+        (Value::Num(a), Value::Byte(b)) => diff_arrays(a, b, path, out),
+        (Value::Byte(a), Value::Num(b)) => diff_arrays(a, b, path, out),
+        (Value::Complex(a), Value::Complex(b)) => diff_arrays(a, b, path, out),
+        (Value::Char(a), Value::Char(b)) => diff_arrays(a, b, path, out),
+        (Value::Time(a), Value::Time(b)) => diff_arrays(a, b, path, out),
+        (Value::Box(a), Value::Box(b)) => {
+            if !diff_shapes(a, b, path, out) {
+                return;
+            }
+            let mut index = Vec::new();
+            for (i, (Boxed(x), Boxed(y))) in a.data.iter().zip(&b.data).enumerate() {
+                if a.rank() == 0 {
+                    diff_values(x, y, path, out);
+                    continue;
+                }
+                a.shape().flat_to_dims(i, &mut index);
+                path.push(index.clone());
+                diff_values(x, y, path, out);
+                path.pop();
+            }
+        }
+        (a, b) => out.push(Difference {
+            path: path.clone(),
+            kind: DifferenceKind::Type(a.type_name(), b.type_name()),
+        }),
+    }
+}
+
+/// Record differences in shape and map keys, returning whether the elements can be compared
+fn diff_shapes<T: ArrayValue, U: ArrayValue>(
+    a: &Array<T>,
+    b: &Array<U>,
+    path: &[Vec<usize>],
+    out: &mut Vec<Difference>,
+) -> bool {
+    if a.shape() != b.shape() {
+        out.push(Difference {
+            path: path.to_vec(),
+            kind: DifferenceKind::Shape(a.shape().clone(), b.shape().clone()),
+        });
+        return false;
+    }
+    if a.map_keys() != b.map_keys() {
+        out.push(Difference {
+            path: path.to_vec(),
+            kind: DifferenceKind::MapKeys,
+        });
+    }
+    true
+}
+
+fn diff_arrays<T, U>(a: &Array<T>, b: &Array<U>, path: &[Vec<usize>], out: &mut Vec<Difference>)
+where
+    T: ArrayValue + ArrayCmp<U>,
+    U: ArrayValue,
+{
+    if !diff_shapes(a, b, path, out) {
+        return;
+    }
+    for (i, (x, y)) in a.data.iter().zip(&b.data).enumerate() {
+        if x.array_eq(y) {
+            continue;
+        }
+        let mut path = path.to_vec();
+        if a.rank() > 0 {
+            let mut index = Vec::new();
+            a.shape().flat_to_dims(i, &mut index);
+            path.push(index);
+        }
+        out.push(Difference {
+            path,
+            kind: DifferenceKind::Element(
+                Array::from(x.clone()).grid_string(false),
+                Array::from(y.clone()).grid_string(false),
+            ),
+        });
+    }
+}
+
+impl ValueDiff {
+    /// Check if there are no differences
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+    /// Format the differences as the message of a failed assertion
+    ///
+    /// The first value is the expected one, and the second is the actual one.
+    pub(crate) fn assertion_message(&self) -> String {
+        format!("Values do not match (expected ≠ actual)\n{self}")
+    }
+    /// Format the differences with the first value's parts in red and the second's in green
+    pub fn colored(&self) -> String {
+        self.format(|s| s.red().to_string(), |s| s.green().to_string())
+    }
+    fn format(&self, left: impl Fn(&str) -> String, right: impl Fn(&str) -> String) -> String {
+        let mut lines = Vec::new();
+        for diff in self.differences.iter().take(MAX_SHOWN) {
+            let mut line = if diff.path.is_empty() {
+                "at top level: ".to_string()
+            } else {
+                let path: Vec<String> = (diff.path.iter())
+                    .map(|index| {
+                        let dims: Vec<String> = index.iter().map(usize::to_string).collect();
+                        format!("[{}]", dims.join(" "))
+                    })
+                    .collect();
+                format!("at {}: ", path.join(" › "))
+            };
+            match &diff.kind {
+                DifferenceKind::Type(a, b) => {
+                    line += &format!("type {} ≠ {}", left(a), right(b));
+                }
+                DifferenceKind::Shape(a, b) => {
+                    line += &format!("shape {} ≠ {}", left(&a.to_string()), right(&b.to_string()));
+                }
+                DifferenceKind::MapKeys => line += "map keys differ",
+                DifferenceKind::Element(a, b) => line += &format!("{} ≠ {}", left(a), right(b)),
+            }
+            lines.push(line);
+        }
+        if self.differences.len() > MAX_SHOWN {
+            lines.push(format!(
+                "… and {} more differences",
+                self.differences.len() - MAX_SHOWN
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for ValueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format(str::to_string, str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn value_diff() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(r#"{[1 2 3] "abc" 5} {[1 5 3] "ab" @x}"#)
+            .unwrap();
+        let a = env.pop(()).unwrap();
+        let b = env.pop(()).unwrap();
+        assert!(a.diff(&a).is_empty());
+        let diff = a.diff(&b);
+        assert_eq!(
+            diff.to_string(),
+            "at [0] › [1]: 2 ≠ 5\n\
+            at [1]: shape [3] ≠ [2]\n\
+            at [2]: type number ≠ character"
+        );
+        let diff = Value::from(1).diff(&Value::from(2.0));
+        assert_eq!(diff.to_string(), "at top level: 1 ≠ 2");
+        assert!(Value::from(1).diff(&Value::from(1.0)).is_empty());
+    }
+}
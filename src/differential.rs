@@ -0,0 +1,409 @@
+//! Differential testing of optimized algorithms
+//!
+//! Random programs are run through the interpreter and their results are
+//! compared to naive reference implementations of the same primitives.
+//! The interpreter crashing is always a failure, even when the reference
+//! implementation expects an error.
+//!
+//! This module is only available in tests or with the `differential` feature.
+
+use std::fmt;
+
+use crate::{algorithm::random::Rng, Array, Uiua, Value};
+
+/// A program whose result did not match the reference implementation
+#[derive(Debug, Clone)]
+pub struct Failure {
+    /// The program's code
+    pub code: String,
+    /// What the reference implementation expected
+    pub expected: String,
+    /// What the interpreter produced
+    pub got: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Differential test failed for `{}`\n\
+            expected: {}\n\
+            got: {}",
+            self.code, self.expected, self.got
+        )
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Run some random programs and compare their results to the reference implementations
+///
+/// The same seed always generates the same programs.
+pub fn check_random_programs(seed: u64, count: usize) -> Result<(), Failure> {
+    let mut rng = Rng::from_seed(seed);
+    for _ in 0..count {
+        let case = match rng.below(4) {
+            0 => keep_case(&mut rng),
+            1 => rotate_case(&mut rng),
+            2 => windows_case(&mut rng),
+            _ => find_case(&mut rng),
+        };
+        case.check()?;
+    }
+    Ok(())
+}
+
+/// A naive array
+#[derive(Debug, Clone)]
+struct Arr {
+    shape: Vec<usize>,
+    data: Vec<f64>,
+}
+
+impl Arr {
+    fn new(shape: Vec<usize>, data: Vec<f64>) -> Self {
+        debug_assert_eq!(shape.iter().product::<usize>(), data.len());
+        Arr { shape, data }
+    }
+    fn rows(&self) -> usize {
+        self.shape.first().copied().unwrap_or(1)
+    }
+    fn row_len(&self) -> usize {
+        self.shape.iter().skip(1).product()
+    }
+    fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.row_len()..][..self.row_len()]
+    }
+    /// Get an element from a multidimensional index
+    fn get(&self, index: &[usize]) -> f64 {
+        let mut flat = 0;
+        for (&i, &d) in index.iter().zip(&self.shape) {
+            flat = flat * d + i;
+        }
+        self.data[flat]
+    }
+    /// Build an array by computing each element from its index
+    fn from_fn(shape: Vec<usize>, f: impl FnMut(Vec<usize>) -> f64) -> Self {
+        let data = indices(&shape).map(f).collect();
+        Arr::new(shape, data)
+    }
+    fn code(&self) -> String {
+        if self.shape.is_empty() {
+            return num_code(self.data[0]);
+        }
+        let shape = list_code(self.shape.iter().map(|&d| d as f64));
+        format!("↯{shape}{}", list_code(self.data.iter().copied()))
+    }
+    fn into_value(self) -> Value {
+        Array::new(self.shape.as_slice(), self.data.as_slice()).into()
+    }
+}
+
+/// Iterate over all indices into an array with the given shape
+fn indices(shape: &[usize]) -> impl Iterator<Item = Vec<usize>> + '_ {
+    let len: usize = shape.iter().product();
+    let mut index = vec![0; shape.len()];
+    (0..len).map(move |_| {
+        let current = index.clone();
+        for (i, &d) in index.iter_mut().zip(shape).rev() {
+            *i += 1;
+            if *i < d {
+                break;
+            }
+            *i = 0;
+        }
+        current
+    })
+}
+
+fn num_code(n: f64) -> String {
+    if n < 0.0 {
+        format!("¯{}", -n)
+    } else {
+        n.to_string()
+    }
+}
+
+fn list_code(items: impl IntoIterator<Item = f64>) -> String {
+    let items: Vec<_> = items.into_iter().map(num_code).collect();
+    format!("[{}]", items.join(" "))
+}
+
+struct Case {
+    code: String,
+    /// `None` if the program should fail
+    expected: Option<Arr>,
+}
+
+impl Case {
+    fn check(self) -> Result<(), Failure> {
+        let mut env = Uiua::with_safe_sys();
+        let res = env.run_str(&self.code).map(|_| env.take_stack());
+        let fail = |expected: String, got: String| Failure {
+            code: self.code.clone(),
+            expected,
+            got,
+        };
+        match (self.expected, res) {
+            (_, Err(e)) if e.to_string().contains("The interpreter has crashed") => {
+                Err(fail("no crash".into(), e.to_string()))
+            }
+            (None, Err(_)) => Ok(()),
+            (None, Ok(stack)) => Err(fail("an error".into(), format!("{stack:?}"))),
+            (Some(expected), Err(e)) => Err(fail(format!("{expected:?}"), e.to_string())),
+            (Some(expected), Ok(stack)) => {
+                let expected = expected.into_value();
+                if stack.len() == 1 && stack[0] == expected {
+                    Ok(())
+                } else {
+                    Err(fail(expected.show(), format!("{stack:?}")))
+                }
+            }
+        }
+    }
+}
+
+fn random_shape(rng: &mut Rng, min_rank: usize, max_rank: usize) -> Vec<usize> {
+    let rank = min_rank + rng.below((max_rank - min_rank + 1) as u64) as usize;
+    (0..rank).map(|_| rng.below(5) as usize).collect()
+}
+
+fn random_arr(rng: &mut Rng, shape: Vec<usize>, max: u64) -> Arr {
+    let len = shape.iter().product();
+    let data = (0..len).map(|_| rng.below(max) as f64).collect();
+    Arr::new(shape, data)
+}
+
+fn random_int(rng: &mut Rng, radius: u64) -> isize {
+    rng.below(2 * radius + 1) as isize - radius as isize
+}
+
+fn keep_case(rng: &mut Rng) -> Case {
+    let shape = random_shape(rng, 1, 3);
+    let arr = random_arr(rng, shape, 10);
+    let rows = arr.rows();
+    // A scalar count repeats every row
+    if rng.below(4) == 0 {
+        let n = rng.below(4) as usize;
+        let data = (0..rows)
+            .flat_map(|i| arr.row(i).repeat(n))
+            .collect::<Vec<_>>();
+        let mut shape = arr.shape.clone();
+        shape[0] *= n;
+        return Case {
+            code: format!("▽{n} {}", arr.code()),
+            expected: Some(Arr::new(shape, data)),
+        };
+    }
+    let fill: Option<Vec<usize>> = match rng.below(3) {
+        0 => Some(vec![rng.below(3) as usize]),
+        1 => Some(
+            (0..1 + rng.below(3))
+                .map(|_| rng.below(3) as usize)
+                .collect(),
+        ),
+        _ => None,
+    };
+    let len = if fill.is_some() || rng.below(5) == 0 {
+        rng.below(rows as u64 + 1) as usize
+    } else {
+        rows
+    };
+    let counts: Vec<usize> = (0..len).map(|_| rng.below(3) as usize).collect();
+    let mut code = format!(
+        "▽{} {}",
+        list_code(counts.iter().map(|&c| c as f64)),
+        arr.code()
+    );
+    if let Some(fill) = &fill {
+        let fill_code = if let [f] = fill.as_slice() {
+            f.to_string()
+        } else {
+            list_code(fill.iter().map(|&f| f as f64))
+        };
+        code = format!("⬚{fill_code}{code}");
+    }
+    let full_counts: Option<Vec<usize>> = match &fill {
+        _ if len == rows => Some(counts),
+        Some(fill) => Some(
+            (counts.iter().copied())
+                .chain(fill.iter().copied().cycle())
+                .take(rows)
+                .collect(),
+        ),
+        None => None,
+    };
+    let expected = full_counts.map(|counts| {
+        let data: Vec<f64> = (counts.iter().enumerate())
+            .flat_map(|(i, &n)| arr.row(i).repeat(n))
+            .collect();
+        let mut shape = arr.shape.clone();
+        shape[0] = counts.iter().sum();
+        Arr::new(shape, data)
+    });
+    Case { code, expected }
+}
+
+fn rotate_case(rng: &mut Rng) -> Case {
+    let shape = random_shape(rng, 1, 3);
+    let arr = random_arr(rng, shape, 10);
+    let scalar = rng.below(2) == 0;
+    let k = if scalar {
+        1
+    } else {
+        1 + rng.below(arr.shape.len() as u64) as usize
+    };
+    let amounts: Vec<isize> = (0..k).map(|_| random_int(rng, 5)).collect();
+    let fill = (rng.below(2) == 0).then(|| rng.below(10) as f64);
+    let amounts_code = if scalar {
+        num_code(amounts[0] as f64)
+    } else {
+        list_code(amounts.iter().map(|&a| a as f64))
+    };
+    let mut code = format!("↻{amounts_code} {}", arr.code());
+    if let Some(fill) = fill {
+        code = format!("⬚{fill}{code}");
+    }
+    let expected = Arr::from_fn(arr.shape.clone(), |mut source| {
+        for ((i, &d), &a) in source.iter_mut().zip(&arr.shape).zip(&amounts) {
+            let shifted = *i as isize + a;
+            if let Some(fill) = fill {
+                if shifted < 0 || shifted >= d as isize {
+                    return fill;
+                }
+                *i = shifted as usize;
+            } else {
+                *i = shifted.rem_euclid(d as isize) as usize;
+            }
+        }
+        arr.get(&source)
+    });
+    Case {
+        code,
+        expected: Some(expected),
+    }
+}
+
+fn windows_case(rng: &mut Rng) -> Case {
+    let shape = random_shape(rng, 1, 3);
+    let arr = random_arr(rng, shape, 10);
+    let scalar = rng.below(2) == 0;
+    let k = if scalar {
+        1
+    } else {
+        1 + rng.below(arr.shape.len() as u64) as usize
+    };
+    // Negative sizes count from the length of the axis
+    let sizes: Vec<isize> = (arr.shape.iter().take(k))
+        .map(|&d| match rng.below(3) {
+            0 if d > 0 => -(1 + rng.below(d as u64) as isize),
+            _ => 1 + rng.below(d as u64 + 1) as isize,
+        })
+        .collect();
+    let sizes_code = if scalar {
+        num_code(sizes[0] as f64)
+    } else {
+        list_code(sizes.iter().map(|&s| s as f64))
+    };
+    let mut code = format!("◫{sizes_code} {}", arr.code());
+    // Fills do not affect windows
+    if rng.below(3) == 0 {
+        code = format!("⬚{}{code}", rng.below(10));
+    }
+    let sizes: Vec<usize> = (sizes.iter().zip(&arr.shape))
+        .map(|(&s, &d)| {
+            if s < 0 {
+                (d as isize + 1 + s) as usize
+            } else {
+                s as usize
+            }
+        })
+        .collect();
+    let mut shape: Vec<usize> = (sizes.iter().zip(&arr.shape))
+        .map(|(&s, &d)| (d + 1).saturating_sub(s))
+        .collect();
+    shape.extend(&sizes);
+    shape.extend(&arr.shape[k..]);
+    let expected = Arr::from_fn(shape, |index| {
+        let (windows, rest) = index.split_at(k);
+        let (offsets, rest) = rest.split_at(k);
+        let source: Vec<usize> = (windows.iter().zip(offsets))
+            .map(|(w, o)| w + o)
+            .chain(rest.iter().copied())
+            .collect();
+        arr.get(&source)
+    });
+    Case {
+        code,
+        expected: Some(expected),
+    }
+}
+
+fn find_case(rng: &mut Rng) -> Case {
+    let shape = random_shape(rng, 1, 2);
+    let arr = random_arr(rng, shape, 3);
+    let pattern = if arr.shape.len() == 1 && rng.below(4) == 0 {
+        random_arr(rng, Vec::new(), 3)
+    } else {
+        let shape = (arr.shape.iter())
+            .map(|&d| rng.below(d as u64 + 2) as usize)
+            .collect();
+        random_arr(rng, shape, 3)
+    };
+    let fill = (rng.below(3) == 0).then(|| rng.below(3) as f64);
+    let mut code = format!("⌕{} {}", pattern.code(), arr.code());
+    if let Some(fill) = fill {
+        code = format!("⬚{fill}{code}");
+    }
+    // A fill only matters if the pattern is bigger than the array,
+    // which a scalar pattern never is
+    let arr = match fill {
+        Some(fill) if pattern.shape.iter().zip(&arr.shape).any(|(p, d)| p > d) => {
+            let shape = (pattern.shape.iter().zip(&arr.shape))
+                .map(|(&p, &d)| p.max(d))
+                .collect();
+            Arr::from_fn(shape, |index| {
+                if index.iter().zip(&arr.shape).all(|(i, d)| i < d) {
+                    arr.get(&index)
+                } else {
+                    fill
+                }
+            })
+        }
+        _ => arr,
+    };
+    // Otherwise a scalar pattern is like a list of one element
+    let pattern = if pattern.shape.is_empty() {
+        Arr::new(vec![1], pattern.data)
+    } else {
+        pattern
+    };
+    let empty = pattern.data.is_empty();
+    let expected = Arr::from_fn(arr.shape.clone(), |index| {
+        let fits =
+            (index.iter().zip(&pattern.shape).zip(&arr.shape)).all(|((i, p), d)| i + p <= *d);
+        let matches = !empty
+            && fits
+            && indices(&pattern.shape).all(|offset| {
+                let source: Vec<usize> = index.iter().zip(&offset).map(|(i, o)| i + o).collect();
+                arr.get(&source) == pattern.get(&offset)
+            });
+        matches as u8 as f64
+    });
+    Case {
+        code,
+        expected: Some(expected),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differential() {
+        if let Err(failure) = check_random_programs(0, 2000) {
+            panic!("{failure}");
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Checking examples written in comments
+
+use std::fmt;
+
+use crate::{
+    Compiler, IntoInputSrc, PreEvalMode, RunMode, Uiua, UiuaError, UiuaResult, Value, ValueDiff,
+};
+
+/// An example written in a comment
+///
+/// Examples are written like the ones in primitive documentation.
+/// `ex:` starts an example, `ex!` starts one that should throw an error,
+/// and lines starting with `:` continue it.
+/// A line starting with `=>` gives code that evaluates to the expected stack.
+/// It may also be continued with `:` lines.
+///
+/// ```uiua
+/// # Add two numbers
+/// # ex: F 1 2
+/// # => 3
+/// F ← +
+/// ```
+///
+/// Examples without expected output only need to run without error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocExample {
+    /// The 1-based line that the example starts on
+    pub line: usize,
+    /// The code of the example
+    pub code: String,
+    /// The code for the expected stack
+    pub expected: Option<String>,
+    /// Whether the example should throw an error
+    pub should_error: bool,
+}
+
+/// A doc example that did not behave as expected
+#[derive(Debug, Clone)]
+pub struct DocExampleFailure {
+    /// The example that failed
+    pub example: DocExample,
+    /// How it failed
+    pub kind: DocExampleFailureKind,
+}
+
+/// The kind of a [`DocExampleFailure`]
+#[derive(Debug, Clone)]
+pub enum DocExampleFailureKind {
+    /// The example threw an error
+    Error(UiuaError),
+    /// The example should have thrown an error but did not
+    NoError,
+    /// The expected output threw an error
+    ExpectedError(UiuaError),
+    /// The example left a different number of values on the stack than expected
+    StackSize {
+        /// The expected number of values
+        expected: usize,
+        /// The actual number of values
+        actual: usize,
+    },
+    /// A value on the stack differs from the expected one
+    Mismatch {
+        /// The index of the value, counting from the top of the stack
+        depth: usize,
+        /// The differences between the expected and actual values
+        diff: ValueDiff,
+    },
+}
+
+/// Find the examples in the comments of some code
+pub fn doc_examples(input: &str) -> Vec<DocExample> {
+    let mut examples: Vec<DocExample> = Vec::new();
+    let mut in_expected = false;
+    let mut in_example = false;
+    for (i, line) in input.lines().enumerate() {
+        let Some(comment) = line.trim_start().strip_prefix('#') else {
+            in_example = false;
+            continue;
+        };
+        if comment.starts_with('#') {
+            in_example = false;
+            continue;
+        }
+        let comment = comment.trim_start();
+        if let Some(code) = (comment.strip_prefix("ex:").map(|code| (code, false)))
+            .or_else(|| comment.strip_prefix("ex!").map(|code| (code, true)))
+        {
+            let (code, should_error) = code;
+            examples.push(DocExample {
+                line: i + 1,
+                code: code.trim().into(),
+                expected: None,
+                should_error,
+            });
+            in_example = true;
+            in_expected = false;
+        } else if let Some(ex) = examples.last_mut().filter(|_| in_example) {
+            if let Some(code) = comment.strip_prefix(':') {
+                let target = if in_expected {
+                    ex.expected.as_mut().unwrap()
+                } else {
+                    &mut ex.code
+                };
+                target.push('\n');
+                target.push_str(code.trim());
+            } else if let Some(code) = comment.strip_prefix("=>") {
+                ex.expected = Some(code.trim().into());
+                in_expected = true;
+            } else {
+                in_example = false;
+            }
+        }
+    }
+    examples
+}
+
+/// Check the examples in the comments of some code
+///
+/// The code is compiled and run first, so examples can use its bindings.
+/// Each example is then run on its own, along with its expected output.
+/// Values are compared with [`Value::diff`].
+///
+/// Returns an error if the code itself fails to compile or run.
+///
+/// If the interpreter collects [coverage](Uiua::with_coverage), code is not evaluated
+/// at compile time, so everything the examples run counts toward it.
+pub fn check_doc_examples(
+    env: &mut Uiua,
+    input: &str,
+    src: impl IntoInputSrc,
+) -> UiuaResult<Vec<DocExampleFailure>> {
+    let mut comp = Compiler::with_backend(env.rt.backend.clone()).with_policy(env.rt.policy);
+    if env.rt.coverage.is_some() {
+        comp.pre_eval_mode(PreEvalMode::Lazy);
+    }
+    comp.mode(RunMode::Normal).load_str_src(input, src)?;
+    env.run_asm(comp.finish())?;
+    env.take_stack();
+    let mut asm = env.take_asm();
+    asm.remove_top_level();
+
+    let mut run = |code: &str| -> UiuaResult<Vec<Value>> {
+        let mut comp = comp.clone().with_assembly(asm.clone());
+        comp.load_str(code)?;
+        let res = env.run_asm(comp.finish());
+        let stack = env.take_stack();
+        res.map(|_| stack)
+    };
+
+    let mut failures = Vec::new();
+    for example in doc_examples(input) {
+        let actual = run(&example.code);
+        let kind = match (actual, &example.expected) {
+            (Ok(_), _) if example.should_error => Some(DocExampleFailureKind::NoError),
+            (Err(_), _) if example.should_error => None,
+            (Err(e), _) => Some(DocExampleFailureKind::Error(e)),
+            (Ok(_), None) => None,
+            (Ok(actual), Some(expected)) => match run(expected) {
+                Err(e) => Some(DocExampleFailureKind::ExpectedError(e)),
+                Ok(expected) if expected.len() != actual.len() => {
+                    Some(DocExampleFailureKind::StackSize {
+                        expected: expected.len(),
+                        actual: actual.len(),
+                    })
+                }
+                Ok(expected) => (expected.iter().rev().zip(actual.iter().rev()))
+                    .enumerate()
+                    .map(|(depth, (e, a))| (depth, e.diff(a)))
+                    .find(|(_, diff)| !diff.is_empty())
+                    .map(|(depth, diff)| DocExampleFailureKind::Mismatch { depth, diff }),
+            },
+        };
+        if let Some(kind) = kind {
+            failures.push(DocExampleFailure { example, kind });
+        }
+    }
+    Ok(failures)
+}
+
+impl fmt::Display for DocExampleFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Example on line {} ", self.example.line)?;
+        match &self.kind {
+            DocExampleFailureKind::Error(e) => write!(f, "threw an error: {e}"),
+            DocExampleFailureKind::NoError => write!(f, "should have thrown an error"),
+            DocExampleFailureKind::ExpectedError(e) => {
+                write!(f, "has expected output that threw an error: {e}")
+            }
+            DocExampleFailureKind::StackSize { expected, actual } => write!(
+                f,
+                "should leave {expected} value{} on the stack, but it left {actual}",
+                if *expected == 1 { "" } else { "s" }
+            ),
+            DocExampleFailureKind::Mismatch { depth, diff } => {
+                write!(f, "has a different value ")?;
+                if *depth == 0 {
+                    write!(f, "on top of the stack")?;
+                } else {
+                    write!(f, "{depth} below the top of the stack")?;
+                }
+                write!(f, " (expected ≠ actual)\n{diff}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SysCapability, SysPolicy};
+
+    #[test]
+    fn doc_examples_respect_policy() {
+        let policy = SysPolicy::allow_all()
+            .deny(SysCapability::FsRead)
+            .deny(SysCapability::Network);
+        let mut env = Uiua::with_native_sys().with_policy(policy);
+        let code = "\
+# ex: ~ \"https://example.com/lib.ua\"
+# ex: ~ \"git:uiua-lang/example\"
+# ex: ~ \"tests/units.ua\"
+# ex: comptime(&fras \"Cargo.toml\")
+F ← +";
+        let failures = check_doc_examples(&mut env, code, ()).unwrap();
+        assert_eq!(failures.len(), 4);
+        for failure in failures {
+            let DocExampleFailureKind::Error(e) = failure.kind else {
+                panic!(
+                    "Example on line {} did not fail to run",
+                    failure.example.line
+                );
+            };
+            assert!(e.to_string().contains("Permission denied"), "{e}");
+        }
+
+        let err = check_doc_examples(&mut env, "~ \"tests/units.ua\"", ())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Permission denied"), "{err}");
+    }
+
+    #[test]
+    fn doc_examples() {
+        let code = "\
+# Add two numbers
+# ex: F 1 2
+# => 3
+# ex: [4 5 5]
+#   : F [1 2 3]
+# => [5 7 8]
+# ex! F [1 2] [1 2 3]
+# ex! F 1 2
+# ex: F 1 2
+# => 3 4
+# ex: F 1 [2 3]
+# => [3 5]
+## 3
+F ← +";
+        let examples = super::doc_examples(code);
+        assert_eq!(examples.len(), 6);
+        assert_eq!(examples[1].code, "[4 5 5]\nF [1 2 3]");
+        assert_eq!(examples[1].expected.as_deref(), Some("[5 7 8]"));
+        assert!(examples[2].should_error);
+
+        let mut env = Uiua::with_safe_sys();
+        let failures = check_doc_examples(&mut env, code, ()).unwrap();
+        let lines: Vec<usize> = failures.iter().map(|f| f.example.line).collect();
+        assert_eq!(lines, [8, 9, 11]);
+        assert!(matches!(failures[0].kind, DocExampleFailureKind::NoError));
+        assert!(matches!(
+            failures[1].kind,
+            DocExampleFailureKind::StackSize {
+                expected: 2,
+                actual: 1
+            }
+        ));
+        assert_eq!(
+            failures[2].to_string(),
+            "Example on line 11 has a different value on top of the stack (expected ≠ actual)\n\
+            at [1]: 5 ≠ 4"
+        );
+    }
+}
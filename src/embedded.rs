@@ -0,0 +1,10 @@
+//! Uiua modules that are built into the interpreter
+
+/// The prefix of imports of embedded modules
+pub(crate) const EMBEDDED_PREFIX: &str = "std:";
+
+/// The built-in modules and their sources
+pub(crate) static STD_MODULES: &[(&str, &str)] = &[
+    ("stats", include_str!("embedded/stats.ua")),
+    ("string", include_str!("embedded/string.ua")),
+];
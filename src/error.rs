@@ -33,6 +33,8 @@ pub enum UiuaError {
     Throw(Box<Value>, Span, Box<Inputs>),
     /// Maximum execution time exceeded
     Timeout(Span, Box<Inputs>),
+    /// Execution was cancelled with a [`CancelToken`](crate::CancelToken)
+    Interrupted(Span, Box<Inputs>),
     /// A wrapper marking this error as being fill-related
     Fill(Box<Self>),
     /// A pattern match failed
@@ -89,6 +91,7 @@ impl fmt::Display for UiuaError {
             }
             UiuaError::Throw(value, span, _) => write!(f, "{span}: {value}"),
             UiuaError::Timeout(..) => write!(f, "Maximum execution time exceeded"),
+            UiuaError::Interrupted(..) => write!(f, "Execution was interrupted"),
             UiuaError::Fill(error) => error.fmt(f),
             UiuaError::PatternMatch(span, _) => write!(f, "{span}: Pattern match failed"),
             UiuaError::Panic(message) => message.fmt(f),
@@ -171,6 +174,16 @@ impl UiuaError {
             _ => false,
         }
     }
+    /// Check if the error is from execution being cancelled
+    pub fn is_interrupt(&self) -> bool {
+        match self {
+            UiuaError::Traced { error, .. } | UiuaError::WithInfo { error, .. } => {
+                error.is_interrupt()
+            }
+            UiuaError::Interrupted(..) => true,
+            _ => false,
+        }
+    }
     /// Mark the error as fill-related
     pub(crate) fn fill(self) -> Self {
         UiuaError::Fill(Box::new(self))
@@ -272,6 +285,9 @@ impl UiuaError {
                 inputs,
                 [("Maximum execution time exceeded", span.clone())],
             ),
+            UiuaError::Interrupted(span, inputs) => {
+                Report::new_multi(kind, inputs, [("Execution was interrupted", span.clone())])
+            }
             UiuaError::Fill(error) => error.report(),
             UiuaError::PatternMatch(span, inputs) => {
                 Report::new_multi(kind, inputs, [("Pattern match failed", span.clone())])
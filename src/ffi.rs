@@ -36,6 +36,8 @@ pub enum FfiType {
     },
     Struct {
         fields: Vec<Self>,
+        /// The names of the fields, or empty if the fields are unnamed
+        names: Vec<String>,
     },
 }
 
@@ -81,8 +83,15 @@ impl FromStr for FfiType {
         if let Some(mut s) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
             s = s.trim();
             let mut fields = Vec::new();
+            let mut names = Vec::new();
             let mut curr = String::new();
             let mut depth = 0;
+            let mut push_field = |curr: &str| -> Result<(), String> {
+                let (field, name) = parse_struct_field(curr)?;
+                fields.push(field);
+                names.extend(name);
+                Ok(())
+            };
             for c in s.chars() {
                 match c {
                     '{' => {
@@ -94,16 +103,17 @@ impl FromStr for FfiType {
                         curr.push(c);
                     }
                     ';' if depth == 0 => {
-                        fields.push(curr.parse()?);
+                        push_field(&curr)?;
                         curr.clear();
                     }
                     _ => curr.push(c),
                 }
             }
-            if !curr.is_empty() {
-                fields.push(curr.parse()?);
+            if !curr.trim().is_empty() {
+                push_field(&curr)?;
             }
-            return Ok(FfiType::Struct { fields });
+            validate_struct(&fields, &names)?;
+            return Ok(FfiType::Struct { fields, names });
         }
         Ok(match s {
             "void" => FfiType::Void,
@@ -124,6 +134,44 @@ impl FromStr for FfiType {
     }
 }
 
+/// Parse a struct field, which may be followed by a name
+fn parse_struct_field(s: &str) -> Result<(FfiType, Option<String>), String> {
+    let s = s.trim();
+    let unnamed_err = match s.parse() {
+        Ok(ty) => return Ok((ty, None)),
+        Err(e) => e,
+    };
+    if let Some((ty, name)) = s.rsplit_once(char::is_whitespace) {
+        if name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+        {
+            if let Ok(ty) = ty.parse() {
+                return Ok((ty, Some(name.into())));
+            }
+        }
+    }
+    Err(unnamed_err)
+}
+
+/// Check that a struct layout is valid
+fn validate_struct(fields: &[FfiType], names: &[String]) -> Result<(), String> {
+    if fields.is_empty() {
+        return Err("Structs must have at least one field".into());
+    }
+    if fields.contains(&FfiType::Void) {
+        return Err("Cannot have void fields in a struct".into());
+    }
+    if !names.is_empty() && names.len() != fields.len() {
+        return Err("Either all or none of a struct's fields must be named".into());
+    }
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(format!("Struct field `{name}` is defined more than once"));
+        }
+    }
+    Ok(())
+}
+
 impl fmt::Display for FfiType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -154,13 +202,16 @@ impl fmt::Display for FfiType {
                 inner,
                 len_index,
             ),
-            FfiType::Struct { fields } => {
+            FfiType::Struct { fields, names } => {
                 write!(f, "{{")?;
                 for (i, field) in fields.iter().enumerate() {
                     if i > 0 {
                         write!(f, "; ")?;
                     }
                     write!(f, "{}", field)?;
+                    if let Some(name) = names.get(i) {
+                        write!(f, " {name}")?;
+                    }
                 }
                 write!(f, "}}")
             }
@@ -186,14 +237,16 @@ impl FfiType {
             FfiType::ULong => (size_of::<c_ulong>(), align_of::<c_ulong>()),
             FfiType::ULongLong => (size_of::<c_ulonglong>(), align_of::<c_ulonglong>()),
             FfiType::Ptr { .. } | FfiType::List { .. } => (size_of::<usize>(), align_of::<usize>()),
-            FfiType::Struct { fields } => struct_fields_size_align(fields),
+            FfiType::Struct { fields, .. } => struct_fields_size_align(fields),
         }
     }
     /// Check if a type is a scalar type
     pub fn is_scalar(&self) -> bool {
         match self {
             FfiType::Void | FfiType::Ptr { .. } | FfiType::List { .. } => false,
-            FfiType::Struct { fields } => fields.iter().all(|f| f.is_scalar() && *f == fields[0]),
+            FfiType::Struct { fields, .. } => {
+                fields.iter().all(|f| f.is_scalar() && *f == fields[0])
+            }
             _ => true,
         }
     }
@@ -223,15 +276,20 @@ mod enabled {
     use std::{
         any::{type_name, Any},
         mem::{forget, take, transmute},
-        slice,
+        ptr, slice,
+        sync::Arc,
     };
 
     use dashmap::DashMap;
     use ecow::EcoVec;
-    use libffi::middle::*;
+    use libffi::{
+        low::{ffi_arg, ffi_cif, ffi_sarg},
+        middle::*,
+    };
+    use parking_lot::Mutex;
 
     use super::*;
-    use crate::{Array, Boxed, MetaPtr, Value};
+    use crate::{Array, Boxed, FfiCallbackFn, MetaPtr, Value};
 
     macro_rules! dbgln {
         ($($arg:tt)*) => {
@@ -244,9 +302,50 @@ mod enabled {
     #[derive(Default)]
     pub struct FfiState {
         libraries: DashMap<String, libloading::Library>,
+        /// The first error from a callback since the last FFI call
+        callback_error: Arc<Mutex<Option<String>>>,
     }
 
     impl FfiState {
+        /// Create a C function pointer that calls a function
+        ///
+        /// The callback is never freed.
+        pub(crate) fn make_callback(
+            &self,
+            result_ty: FfiType,
+            arg_tys: &[FfiType],
+            f: FfiCallbackFn,
+        ) -> Result<Value, String> {
+            match &result_ty {
+                FfiType::Struct { .. } | FfiType::List { .. } => {
+                    return Err(format!(
+                        "{result_ty} is not a supported callback return type"
+                    ))
+                }
+                _ => {}
+            }
+            for arg_ty in arg_tys {
+                if let FfiType::Void | FfiType::List { .. } = arg_ty {
+                    return Err(format!(
+                        "{arg_ty} is not a supported callback argument type"
+                    ));
+                }
+            }
+            let cif = Cif::new(arg_tys.iter().map(ffity_to_cty), ffity_to_cty(&result_ty));
+            let data = Box::leak(Box::new(CallbackData {
+                result_ty,
+                arg_tys: arg_tys.to_vec(),
+                f,
+                error: self.callback_error.clone(),
+            }));
+            let closure = Closure::new_mut(cif, call_callback, data);
+            let code_ptr = *closure.code_ptr() as *const ();
+            // The closure must live as long as the function pointer might be called
+            forget(closure);
+            let mut val = Value::default();
+            val.meta_mut().pointer = Some(MetaPtr::new(code_ptr, true));
+            Ok(val)
+        }
         pub(crate) fn do_ffi(
             &self,
             file: &str,
@@ -391,11 +490,11 @@ mod enabled {
                         let s = CStr::from_ptr(ptr).to_str().map_err(|e| e.to_string())?;
                         results.push(Value::from(s))
                     },
-                    FfiType::Struct { fields } => unsafe {
+                    FfiType::Struct { fields, names } => unsafe {
                         let ptr = cif.call::<*const u8>(fptr, &bindings.args);
                         let (size, _) = struct_fields_size_align(fields);
                         let slice = slice::from_raw_parts(ptr, size);
-                        results.push(bindings.struct_repr_to_value(slice, fields)?);
+                        results.push(bindings.struct_repr_to_value(slice, fields, names)?);
                         // Clean up the pointer's memory
                         drop(Vec::from_raw_parts(ptr as *mut u8, size, size));
                     },
@@ -438,7 +537,7 @@ mod enabled {
                         ))
                     }
                 },
-                FfiType::Struct { fields } => {
+                FfiType::Struct { fields, names } => {
                     let (size, _) = return_ty.size_align();
                     let args = &bindings.args;
                     macro_rules! call_ret_struct {
@@ -446,6 +545,7 @@ mod enabled {
                             bindings.struct_repr_to_value(
                                 &unsafe { cif.call::<[u8; $n]>(fptr, args) },
                                 fields,
+                                names,
                             )
                         };
                     }
@@ -537,9 +637,9 @@ mod enabled {
                             FfiType::ULongLong => out_param_scalar!(c_ulonglong, i, f64),
                             FfiType::Float => out_param_scalar!(c_float, i, f64),
                             FfiType::Double => out_param_scalar!(c_double, i, f64),
-                            FfiType::Struct { fields } => {
+                            FfiType::Struct { fields, names } => {
                                 let repr = bindings.get_repr(i);
-                                results.push(bindings.struct_repr_to_value(repr, fields)?);
+                                results.push(bindings.struct_repr_to_value(repr, fields, names)?);
                             }
                             FfiType::Ptr { inner, .. } => match &**inner {
                                 FfiType::Char => unsafe {
@@ -598,7 +698,7 @@ mod enabled {
                         FfiType::ULongLong => out_param_list!(c_ulonglong, len_index, i, f64),
                         FfiType::Float => out_param_list!(c_float, len_index, i, f64),
                         FfiType::Double => out_param_list!(c_double, len_index, i, f64),
-                        FfiType::Struct { fields } => {
+                        FfiType::Struct { fields, names } => {
                             let len = *bindings.get::<c_int>(*len_index) as usize;
                             let repr = bindings.get_repr(i);
                             if len > 0 && repr.len() % len != 0 {
@@ -608,7 +708,7 @@ mod enabled {
                             }
                             let mut rows = Vec::new();
                             for chunk in repr.chunks_exact(repr.len() / len) {
-                                rows.push(bindings.struct_repr_to_value(chunk, fields)?);
+                                rows.push(bindings.struct_repr_to_value(chunk, fields, names)?);
                             }
                             let value = Value::from_row_values_infallible(rows);
                             results.push(value);
@@ -628,6 +728,10 @@ mod enabled {
             // Bindings must live until after the call
             drop(bindings);
 
+            if let Some(error) = self.callback_error.lock().take() {
+                return Err(format!("Error in FFI callback: {error}"));
+            }
+
             Ok(match results.len() {
                 0 => Value::default(),
                 1 => results.pop().unwrap(),
@@ -636,6 +740,127 @@ mod enabled {
         }
     }
 
+    struct CallbackData {
+        result_ty: FfiType,
+        arg_tys: Vec<FfiType>,
+        f: FfiCallbackFn,
+        error: Arc<Mutex<Option<String>>>,
+    }
+
+    unsafe extern "C" fn call_callback(
+        _cif: &ffi_cif,
+        result: &mut c_void,
+        args: *const *const c_void,
+        data: &mut CallbackData,
+    ) {
+        let mut call = || -> Result<(), String> {
+            let mut values = Vec::with_capacity(data.arg_tys.len());
+            for (i, arg_ty) in data.arg_tys.iter().enumerate() {
+                values.push(unsafe { callback_arg_to_value(arg_ty, *args.add(i)) }?);
+            }
+            let ret = (data.f)(values).map_err(|e| e.to_string())?;
+            unsafe { write_callback_result(&data.result_ty, ret, result) }
+        };
+        if let Err(e) = call() {
+            data.error.lock().get_or_insert(e);
+        }
+    }
+
+    /// Convert a callback argument to a [`Value`]
+    unsafe fn callback_arg_to_value(ty: &FfiType, arg: *const c_void) -> Result<Value, String> {
+        macro_rules! scalar {
+            ($ty:ty) => {
+                Value::from(ptr::read_unaligned(arg as *const $ty) as f64)
+            };
+        }
+        Ok(match ty {
+            FfiType::Char => Value::from(ptr::read_unaligned(arg as *const c_char) as u8 as char),
+            FfiType::Short => scalar!(c_short),
+            FfiType::Int => scalar!(c_int),
+            FfiType::Long => scalar!(c_long),
+            FfiType::LongLong => scalar!(c_longlong),
+            FfiType::UChar => scalar!(c_uchar),
+            FfiType::UShort => scalar!(c_ushort),
+            FfiType::UInt => scalar!(c_uint),
+            FfiType::ULong => scalar!(c_ulong),
+            FfiType::ULongLong => scalar!(c_ulonglong),
+            FfiType::Float => scalar!(c_float),
+            FfiType::Double => scalar!(c_double),
+            FfiType::Ptr { inner, .. } => {
+                let ptr = ptr::read_unaligned(arg as *const *const c_void);
+                match &**inner {
+                    FfiType::Char if ptr.is_null() => Value::null(),
+                    FfiType::Char => {
+                        let c_str = CStr::from_ptr(ptr as *const c_char);
+                        Value::from(c_str.to_str().map_err(|e| e.to_string())?)
+                    }
+                    _ => {
+                        let mut val = Value::default();
+                        val.meta_mut().pointer = Some(MetaPtr::new(ptr, true));
+                        val
+                    }
+                }
+            }
+            FfiType::Struct { fields, names } => {
+                let (size, _) = struct_fields_size_align(fields);
+                let repr = slice::from_raw_parts(arg as *const u8, size);
+                FfiBindings::default().struct_repr_to_value(repr, fields, names)?
+            }
+            FfiType::Void | FfiType::List { .. } => {
+                return Err(format!("{ty} is not a supported callback argument type"))
+            }
+        })
+    }
+
+    /// Write a callback's return value
+    ///
+    /// Integer return values are widened to the size of a register.
+    unsafe fn write_callback_result(
+        ty: &FfiType,
+        value: Option<Value>,
+        result: &mut c_void,
+    ) -> Result<(), String> {
+        let result = result as *mut c_void;
+        let value = match (ty, value) {
+            (FfiType::Void, _) => return Ok(()),
+            (_, Some(value)) => value,
+            (_, None) => return Err("Callback did not return a value".into()),
+        };
+        if let FfiType::Ptr { .. } = ty {
+            let ptr = (value.meta().pointer)
+                .ok_or("Callback must return a pointer value")?
+                .get::<c_void>();
+            (result as *mut *const c_void).write(ptr);
+            return Ok(());
+        }
+        let n = match &value {
+            Value::Num(arr) if arr.rank() == 0 => arr.data[0],
+            Value::Byte(arr) if arr.rank() == 0 => arr.data[0] as f64,
+            Value::Char(arr) if arr.rank() == 0 => arr.data[0] as u32 as f64,
+            value => {
+                return Err(format!(
+                    "Callback must return a scalar {ty}, but it returned {} {}",
+                    value.shape(),
+                    value.type_name_plural()
+                ))
+            }
+        };
+        match ty {
+            FfiType::Char | FfiType::Short | FfiType::Int | FfiType::Long | FfiType::LongLong => {
+                (result as *mut ffi_sarg).write(n as ffi_sarg)
+            }
+            FfiType::UChar
+            | FfiType::UShort
+            | FfiType::UInt
+            | FfiType::ULong
+            | FfiType::ULongLong => (result as *mut ffi_arg).write(n as ffi_arg),
+            FfiType::Float => (result as *mut c_float).write(n as c_float),
+            FfiType::Double => (result as *mut c_double).write(n),
+            _ => return Err(format!("{ty} is not a supported callback return type")),
+        }
+        Ok(())
+    }
+
     type ListStorage<T> = (*mut T, Box<[T]>);
 
     #[derive(Default)]
@@ -881,8 +1106,8 @@ mod enabled {
                     (FfiType::Char, Value::Char(arr)) => {
                         self.push_string(arr.data.iter().copied().collect()) as *mut ()
                     }
-                    (FfiType::Struct { fields }, val) => {
-                        let repr = self.value_to_struct_repr(val, fields)?;
+                    (FfiType::Struct { fields, names }, val) => {
+                        let repr = self.value_to_struct_repr(val, fields, names)?;
                         self.push_repr_ptr(repr)
                     }
                     (_, arg) if arg.meta().pointer.is_some() => {
@@ -957,10 +1182,10 @@ mod enabled {
                     (FfiType::ULongLong, Value::Byte(arr)) => list!(arr, c_ulonglong),
                     (FfiType::Float, Value::Byte(arr)) => list!(arr, c_float),
                     (FfiType::Double, Value::Byte(arr)) => list!(arr, c_double),
-                    (FfiType::Struct { fields }, val) => {
+                    (FfiType::Struct { fields, names }, val) => {
                         let mut all_reprs = Vec::new();
                         for row in val.rows() {
-                            let repr = self.value_to_struct_repr(&row, fields)?;
+                            let repr = self.value_to_struct_repr(&row, fields, names)?;
                             all_reprs.extend(repr);
                         }
                         self.push_repr_ptr(all_reprs)
@@ -974,8 +1199,8 @@ mod enabled {
                         ))
                     }
                 },
-                (FfiType::Struct { fields }, val) => {
-                    let repr = self.value_to_struct_repr(val, fields)?;
+                (FfiType::Struct { fields, names }, val) => {
+                    let repr = self.value_to_struct_repr(val, fields, names)?;
                     self.push_repr(repr)
                 }
                 (ty, arg) => {
@@ -994,23 +1219,30 @@ mod enabled {
         }
         /// Convert a [`Value`] to a C-ABI-compatiable struct byte representation
         ///
-        /// Takes into account the size and alignment of the fields
+        /// Takes into account the size and alignment of the fields.
+        /// If the struct's fields are named, the value may be a map from field names to values.
         fn value_to_struct_repr(
             &mut self,
             value: &Value,
             fields: &[FfiType],
+            names: &[String],
         ) -> Result<Vec<u8>, String> {
-            if value.row_count() != fields.len() {
-                return Err(format!(
-                    "Value has {} rows, but the struct has {} fields",
-                    value.row_count(),
-                    fields.len()
-                ));
-            }
+            let rows: Vec<Value> = if value.is_map() && !names.is_empty() {
+                struct_map_fields(value, names)?
+            } else {
+                if value.row_count() != fields.len() {
+                    return Err(format!(
+                        "Value has {} rows, but the struct has {} fields",
+                        value.row_count(),
+                        fields.len()
+                    ));
+                }
+                value.rows().collect()
+            };
             let (size, _) = struct_fields_size_align(fields);
             let mut repr = vec![0; size];
             let mut offset = 0;
-            for (i, (row, field)) in value.rows().map(Value::unboxed).zip(fields).enumerate() {
+            for (i, (row, field)) in rows.into_iter().map(Value::unboxed).zip(fields).enumerate() {
                 let (size, align) = field.size_align();
                 if offset % align != 0 {
                     offset += align - (offset % align);
@@ -1063,8 +1295,9 @@ mod enabled {
                         scalar!(arr, c_double)
                     }
                     // Structs
-                    (FfiType::Struct { fields }, value) => {
-                        repr[range].copy_from_slice(&self.value_to_struct_repr(&value, fields)?);
+                    (FfiType::Struct { fields, names }, value) => {
+                        repr[range]
+                            .copy_from_slice(&self.value_to_struct_repr(&value, fields, names)?);
                     }
                     // Pointers
                     (FfiType::Ptr { inner, .. }, value) => {
@@ -1095,8 +1328,15 @@ mod enabled {
             Ok(repr)
         }
         /// Convert a C-ABI-compatiable struct byte representation to a [`Value`]
+        ///
+        /// If the struct's fields are named, the value is a map from field names to values.
         #[allow(clippy::only_used_in_recursion)]
-        fn struct_repr_to_value(&self, repr: &[u8], fields: &[FfiType]) -> Result<Value, String> {
+        fn struct_repr_to_value(
+            &self,
+            repr: &[u8],
+            fields: &[FfiType],
+            names: &[String],
+        ) -> Result<Value, String> {
             let mut rows: Vec<Value> = Vec::new();
             let mut offset = 0;
             for (i, field) in fields.iter().enumerate() {
@@ -1125,8 +1365,12 @@ mod enabled {
                     FfiType::Float => scalar!(c_float),
                     FfiType::Double => scalar!(c_double),
                     // Structs
-                    FfiType::Struct { fields } => {
-                        rows.push(self.struct_repr_to_value(&repr[offset..offset + size], fields)?);
+                    FfiType::Struct { fields, names } => {
+                        rows.push(self.struct_repr_to_value(
+                            &repr[offset..offset + size],
+                            fields,
+                            names,
+                        )?);
                     }
                     // Pointers
                     FfiType::Ptr { inner, .. } => match &**inner {
@@ -1140,13 +1384,13 @@ mod enabled {
                             let s = c_str.to_str().map_err(|e| e.to_string())?;
                             rows.push(Value::from(s));
                         }
-                        FfiType::Struct { fields } => {
+                        FfiType::Struct { fields, names } => {
                             let mut bytes: [u8; size_of::<*const u8>()] = Default::default();
                             bytes.copy_from_slice(&repr[offset..offset + size_of::<*const u8>()]);
                             let ptr = unsafe { transmute::<_, *const u8>(bytes) };
                             let (size, _) = struct_fields_size_align(fields);
                             let inner_repr = unsafe { slice::from_raw_parts(ptr, size) };
-                            rows.push(self.struct_repr_to_value(inner_repr, fields)?);
+                            rows.push(self.struct_repr_to_value(inner_repr, fields, names)?);
                         }
                         inner => {
                             let mut bytes: [u8; size_of::<*const u8>()] = Default::default();
@@ -1155,7 +1399,7 @@ mod enabled {
                             let (size, _) = inner.size_align();
                             let inner_repr = unsafe { slice::from_raw_parts(ptr, size) };
                             let mut row = self
-                                .struct_repr_to_value(inner_repr, slice::from_ref(inner))?
+                                .struct_repr_to_value(inner_repr, slice::from_ref(inner), &[])?
                                 .into_rows()
                                 .next()
                                 .unwrap();
@@ -1172,6 +1416,12 @@ mod enabled {
                 }
                 offset += size;
             }
+            if !names.is_empty() {
+                let pairs = (names.iter())
+                    .map(|name| Boxed(name.as_str().into()).into())
+                    .zip(rows.into_iter().map(|row| Boxed(row).into()));
+                return Ok(Value::map_from_pairs(pairs, &()).unwrap_or_else(|e| match e {}));
+            }
             Ok(
                 if fields.iter().all(|f| f.is_scalar() && fields[0] == *f)
                     && rows.iter().all(|r| r.shape() == rows[0].shape())
@@ -1188,6 +1438,21 @@ mod enabled {
         }
     }
 
+    /// Get the fields of a struct from a map of field names to values
+    fn struct_map_fields(map: &Value, names: &[String]) -> Result<Vec<Value>, String> {
+        let mut rows: Vec<Option<Value>> = vec![None; names.len()];
+        for (key, value) in map.map_kv() {
+            let key = key.unboxed();
+            let Some(i) = (names.iter()).position(|name| key == Value::from(name.as_str())) else {
+                return Err(format!("Struct has no field {}", key.format()));
+            };
+            rows[i] = Some(value);
+        }
+        (rows.into_iter().zip(names))
+            .map(|(row, name)| row.ok_or_else(|| format!("Struct field `{name}` is missing")))
+            .collect()
+    }
+
     /// Convert a [`FfiType`] to a C-compatible [`Type`]
     pub(crate) fn ffity_to_cty(ty: &FfiType) -> Type {
         match ty {
@@ -1206,7 +1471,7 @@ mod enabled {
             FfiType::Double => Type::f64(),
             FfiType::Ptr { .. } => Type::pointer(),
             FfiType::List { .. } => Type::pointer(),
-            FfiType::Struct { fields } => {
+            FfiType::Struct { fields, .. } => {
                 let mut types = Vec::with_capacity(fields.len());
                 for field in fields {
                     types.push(ffity_to_cty(field));
@@ -1264,9 +1529,11 @@ mod enabled {
 fn parse_ffi_type() {
     let rect = FfiType::Struct {
         fields: vec![FfiType::Float; 4],
+        names: Vec::new(),
     };
     let texture = FfiType::Struct {
         fields: vec![FfiType::Int; 5],
+        names: Vec::new(),
     };
     let image = FfiType::Struct {
         fields: vec![
@@ -1279,6 +1546,7 @@ fn parse_ffi_type() {
             FfiType::Int,
             FfiType::Int,
         ],
+        names: Vec::new(),
     };
     let glyph_info = FfiType::Struct {
         fields: vec![
@@ -1288,6 +1556,7 @@ fn parse_ffi_type() {
             FfiType::Int,
             image,
         ],
+        names: Vec::new(),
     };
     let font = FfiType::Struct {
         fields: vec![
@@ -1304,6 +1573,7 @@ fn parse_ffi_type() {
                 inner: glyph_info.into(),
             },
         ],
+        names: Vec::new(),
     };
     let expected = "{\
         int; int; int; \
@@ -1313,4 +1583,15 @@ fn parse_ffi_type() {
     }";
     assert_eq!(font.to_string(), expected);
     assert_eq!(expected.parse(), Ok(font));
+
+    let vec2 = FfiType::Struct {
+        fields: vec![FfiType::Double; 2],
+        names: vec!["x".into(), "y".into()],
+    };
+    assert_eq!(vec2.to_string(), "{double x; double y}");
+    assert_eq!("{double x; double y}".parse(), Ok(vec2));
+    assert!("{}".parse::<FfiType>().is_err());
+    assert!("{int x; int}".parse::<FfiType>().is_err());
+    assert!("{int x; int x}".parse::<FfiType>().is_err());
+    assert!("{void; int}".parse::<FfiType>().is_err());
 }
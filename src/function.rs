@@ -247,6 +247,29 @@ impl Instr {
     pub(crate) fn is_code(&self) -> bool {
         !matches!(self, Self::NoInline)
     }
+    /// Get the index of the instruction's span, if it has one
+    pub(crate) fn span(&self) -> Option<usize> {
+        match self {
+            Self::BindGlobal { span, .. }
+            | Self::EndArray { span, .. }
+            | Self::Prim(_, span)
+            | Self::ImplPrim(_, span)
+            | Self::Call(span)
+            | Self::CallRecursive(span)
+            | Self::Recur(span)
+            | Self::Switch { span, .. }
+            | Self::Format { span, .. }
+            | Self::MatchFormatPattern { span, .. }
+            | Self::StackSwizzle(_, span)
+            | Self::Label { span, .. }
+            | Self::Unpack { span, .. }
+            | Self::TouchStack { span, .. }
+            | Self::PushTemp { span, .. }
+            | Self::PopTemp { span, .. }
+            | Self::CopyToTemp { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) struct FmtInstrs<'a>(pub &'a [Instr], pub &'a Assembly);
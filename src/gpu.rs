@@ -0,0 +1,586 @@
+//! Offloading large array operations to the GPU
+
+use std::{borrow::Cow, collections::HashMap, fmt, sync::Arc};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use wgpu::util::DeviceExt;
+
+use crate::{cowslice::CowSlice, Array, Primitive, Shape, Uiua};
+
+/// Options for running array operations on the GPU
+///
+/// Pass them to [`Uiua::with_gpu`].
+/// Operations that the GPU does not support, or that are run when no GPU is available,
+/// fall back to the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuOptions {
+    /// Operations whose largest input has fewer elements than this run on the CPU
+    pub threshold: usize,
+    /// Whether to compute with 32-bit floats if the GPU does not support 64-bit floats
+    ///
+    /// This is faster than the CPU, but the results are less precise.
+    pub allow_f32: bool,
+}
+
+impl Default for GpuOptions {
+    fn default() -> Self {
+        GpuOptions {
+            threshold: 1 << 16,
+            allow_f32: false,
+        }
+    }
+}
+
+impl GpuOptions {
+    /// Check if a GPU that these options can use is available
+    pub fn gpu_available(&self) -> bool {
+        GPU.as_ref()
+            .is_some_and(|gpu| gpu.precision(self).is_some())
+    }
+}
+
+/// An array's data in a GPU buffer
+///
+/// This is kept in an array's metadata so that the results of GPU operations
+/// do not need to be uploaded again when they are used by the next operation.
+/// The buffer keeps a reference to the array's data, so modifying the array in place
+/// copies the data and the buffer is no longer used.
+#[derive(Clone)]
+pub struct GpuBuffer {
+    data: CowSlice<f64>,
+    buffer: Arc<wgpu::Buffer>,
+    precision: Precision,
+}
+
+impl PartialEq for GpuBuffer {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for GpuBuffer {}
+
+impl fmt::Debug for GpuBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GpuBuffer({:?}, {})", self.precision, self.data.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Precision {
+    F32,
+    F64,
+}
+
+impl Precision {
+    fn ty(&self) -> &'static str {
+        match self {
+            Precision::F32 => "f32",
+            Precision::F64 => "f64",
+        }
+    }
+    fn size(&self) -> u64 {
+        match self {
+            Precision::F32 => 4,
+            Precision::F64 => 8,
+        }
+    }
+}
+
+/// A pervasive binary operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum GpuBinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+}
+
+impl GpuBinOp {
+    /// The operation on the first argument `x` and the second argument `y`
+    fn expr(&self) -> &'static str {
+        match self {
+            GpuBinOp::Add => "y + x",
+            GpuBinOp::Sub => "y - x",
+            GpuBinOp::Mul => "y * x",
+            GpuBinOp::Div => "y / x",
+            GpuBinOp::Min => "nan_min(x, y)",
+            GpuBinOp::Max => "nan_max(x, y)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kernel {
+    Bin(GpuBinOp),
+    Reduce(GpuBinOp),
+    MatMul,
+}
+
+/// The number of rows each invocation of a reduction folds
+const REDUCE_CHUNK: usize = 256;
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65535;
+
+const SHADER_PRELUDE: &str = "
+fn nan_min(x: T, y: T) -> T {
+    if x != x { return y; }
+    if y != y { return x; }
+    return select(y, x, x < y);
+}
+
+fn nan_max(x: T, y: T) -> T {
+    if x != x { return y; }
+    if y != y { return x; }
+    return select(y, x, x > y);
+}
+
+fn invocation(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.x + id.y * groups.x * 64u;
+}
+";
+
+impl Kernel {
+    /// The number of input buffers
+    fn inputs(&self) -> u32 {
+        match self {
+            Kernel::Bin(_) | Kernel::MatMul => 2,
+            Kernel::Reduce(_) => 1,
+        }
+    }
+    fn source(&self, precision: Precision) -> String {
+        let body = match self {
+            // params: len, whether a is scalar, whether b is scalar
+            Kernel::Bin(op) => format!(
+                "
+@group(0) @binding(0) var<storage, read> a: array<T>;
+@group(0) @binding(1) var<storage, read> b: array<T>;
+@group(0) @binding(2) var<storage, read_write> out: array<T>;
+@group(0) @binding(3) var<uniform> params: vec4<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {{
+    let i = invocation(id, groups);
+    if i >= params.x {{ return; }}
+    let x = a[select(i, 0u, params.y != 0u)];
+    let y = b[select(i, 0u, params.z != 0u)];
+    out[i] = {};
+}}",
+                op.expr()
+            ),
+            // params: row count, row length, rows per invocation
+            Kernel::Reduce(op) => format!(
+                "
+@group(0) @binding(0) var<storage, read> a: array<T>;
+@group(0) @binding(1) var<storage, read_write> out: array<T>;
+@group(0) @binding(2) var<uniform> params: vec4<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {{
+    let i = invocation(id, groups);
+    let row_len = params.y;
+    let out_rows = (params.x + params.z - 1u) / params.z;
+    if i >= out_rows * row_len {{ return; }}
+    let col = i % row_len;
+    let start = (i / row_len) * params.z;
+    let end = min(start + params.z, params.x);
+    var y = a[start * row_len + col];
+    for (var r = start + 1u; r < end; r = r + 1u) {{
+        let x = a[r * row_len + col];
+        y = {};
+    }}
+    out[i] = y;
+}}",
+                op.expr()
+            ),
+            // params: a row count, b row count, row length
+            Kernel::MatMul => "
+@group(0) @binding(0) var<storage, read> a: array<T>;
+@group(0) @binding(1) var<storage, read> b: array<T>;
+@group(0) @binding(2) var<storage, read_write> out: array<T>;
+@group(0) @binding(3) var<uniform> params: vec4<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = invocation(id, groups);
+    if i >= params.x * params.y { return; }
+    let k = params.z;
+    let a_start = (i / params.y) * k;
+    let b_start = (i % params.y) * k;
+    var sum = a[a_start] * b[b_start];
+    for (var j = 1u; j < k; j = j + 1u) {
+        sum = sum + a[a_start + j] * b[b_start + j];
+    }
+    out[i] = sum;
+}"
+            .into(),
+        };
+        format!("alias T = {};\n{SHADER_PRELUDE}{body}", precision.ty())
+    }
+}
+
+static GPU: Lazy<Option<Gpu>> = Lazy::new(Gpu::new);
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    f64: bool,
+    pipelines: Mutex<HashMap<(Kernel, Precision), Arc<wgpu::ComputePipeline>>>,
+}
+
+/// A buffer that is an input to a kernel
+enum Input {
+    Cached(Arc<wgpu::Buffer>),
+    New(wgpu::Buffer),
+}
+
+impl Input {
+    fn buffer(&self) -> &wgpu::Buffer {
+        match self {
+            Input::Cached(buffer) => buffer,
+            Input::New(buffer) => buffer,
+        }
+    }
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        let features = adapter.features() & wgpu::Features::SHADER_F64;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("uiua"),
+                required_features: features,
+                required_limits: adapter.limits(),
+            },
+            None,
+        ))
+        .ok()?;
+        Some(Gpu {
+            device,
+            queue,
+            f64: features.contains(wgpu::Features::SHADER_F64),
+            pipelines: Mutex::new(HashMap::new()),
+        })
+    }
+    fn precision(&self, options: &GpuOptions) -> Option<Precision> {
+        if self.f64 {
+            Some(Precision::F64)
+        } else if options.allow_f32 {
+            Some(Precision::F32)
+        } else {
+            None
+        }
+    }
+    /// Check that a buffer of some number of elements can be bound
+    fn fits(&self, len: usize, precision: Precision) -> bool {
+        let limits = self.device.limits();
+        let size = len as u64 * precision.size();
+        len > 0
+            && len <= u32::MAX as usize
+            && size <= limits.max_storage_buffer_binding_size as u64
+            && size <= limits.max_buffer_size
+    }
+    fn pipeline(&self, kernel: Kernel, precision: Precision) -> Arc<wgpu::ComputePipeline> {
+        let mut pipelines = self.pipelines.lock();
+        (pipelines.entry((kernel, precision)))
+            .or_insert_with(|| {
+                let source = kernel.source(precision);
+                let module = (self.device).create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(Cow::Owned(source)),
+                });
+                let pipeline =
+                    (self.device).create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                        label: None,
+                        layout: None,
+                        module: &module,
+                        entry_point: "main",
+                        compilation_options: Default::default(),
+                    });
+                Arc::new(pipeline)
+            })
+            .clone()
+    }
+    /// Get an array's data in a buffer, reusing the cached buffer if possible
+    fn input(&self, arr: &Array<f64>, precision: Precision) -> Input {
+        if let Some(cached) = &arr.meta().gpu_buffer {
+            if cached.precision == precision && cached.data.is_copy_of(&arr.data) {
+                return Input::Cached(cached.buffer.clone());
+            }
+        }
+        let contents = match precision {
+            Precision::F64 => Cow::Borrowed(bytemuck::cast_slice(arr.data.as_slice())),
+            Precision::F32 => {
+                let data: Vec<f32> = arr.data.iter().map(|&x| x as f32).collect();
+                Cow::Owned(bytemuck::cast_slice(&data).to_vec())
+            }
+        };
+        Input::New(
+            (self.device).create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: wgpu::BufferUsages::STORAGE,
+            }),
+        )
+    }
+    fn output(&self, len: usize, precision: Precision) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: len as u64 * precision.size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        })
+    }
+    /// Run a kernel with one invocation per output element
+    fn run(
+        &self,
+        kernel: Kernel,
+        precision: Precision,
+        inputs: &[&wgpu::Buffer],
+        out: &wgpu::Buffer,
+        invocations: usize,
+        params: [u32; 4],
+    ) {
+        debug_assert_eq!(inputs.len() as u32, kernel.inputs());
+        let pipeline = self.pipeline(kernel, precision);
+        let params = (self.device).create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let entries: Vec<wgpu::BindGroupEntry> = (inputs.iter().copied())
+            .chain([out, &params])
+            .enumerate()
+            .map(|(i, buffer)| wgpu::BindGroupEntry {
+                binding: i as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+        let groups = (invocations as u32).div_ceil(WORKGROUP_SIZE);
+        let (x, y) = if groups <= MAX_WORKGROUPS {
+            (groups, 1)
+        } else {
+            (MAX_WORKGROUPS, groups.div_ceil(MAX_WORKGROUPS))
+        };
+        let mut encoder = (self.device).create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+        }
+        self.queue.submit([encoder.finish()]);
+    }
+    /// Read a buffer back into an array
+    ///
+    /// The buffer is cached in the array's metadata.
+    fn read(&self, buffer: wgpu::Buffer, shape: Shape, precision: Precision) -> Array<f64> {
+        let size = buffer.size();
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = (self.device).create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(&buffer, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+        let data: CowSlice<f64> = {
+            let bytes = slice.get_mapped_range();
+            match precision {
+                Precision::F64 => bytemuck::cast_slice::<u8, f64>(&bytes)
+                    .iter()
+                    .copied()
+                    .collect(),
+                Precision::F32 => (bytemuck::cast_slice::<u8, f32>(&bytes).iter())
+                    .map(|&x| x as f64)
+                    .collect(),
+            }
+        };
+        staging.unmap();
+        let mut arr = Array::new(shape, data);
+        arr.meta_mut().gpu_buffer = Some(GpuBuffer {
+            data: arr.data.clone(),
+            buffer: Arc::new(buffer),
+            precision,
+        });
+        arr
+    }
+}
+
+/// Get the GPU and precision to use for an operation whose largest input has `len` elements
+fn gpu_for(len: usize, env: &Uiua) -> Option<(&'static Gpu, Precision)> {
+    let options = env.rt.gpu.as_ref()?;
+    if len < options.threshold {
+        return None;
+    }
+    let gpu = GPU.as_ref()?;
+    let precision = gpu.precision(options)?;
+    gpu.fits(len, precision).then_some((gpu, precision))
+}
+
+/// Try to run a pervasive binary operation on the GPU
+///
+/// The arrays must have the same shape, or one of them must be a scalar.
+pub(crate) fn bin_pervade(
+    op: GpuBinOp,
+    a: &Array<f64>,
+    b: &Array<f64>,
+    a_depth: usize,
+    b_depth: usize,
+    env: &Uiua,
+) -> Option<Array<f64>> {
+    if a_depth > 0 || b_depth > 0 || a.rank() > 0 && b.rank() > 0 && a.shape() != b.shape() {
+        return None;
+    }
+    let shape = if a.rank() >= b.rank() {
+        a.shape()
+    } else {
+        b.shape()
+    };
+    let len = shape.elements();
+    let (gpu, precision) = gpu_for(len, env)?;
+    let (a_buf, b_buf) = (gpu.input(a, precision), gpu.input(b, precision));
+    let out = gpu.output(len, precision);
+    let params = [
+        len as u32,
+        (a.rank() == 0) as u32,
+        (b.rank() == 0) as u32,
+        0,
+    ];
+    let inputs = [a_buf.buffer(), b_buf.buffer()];
+    gpu.run(Kernel::Bin(op), precision, &inputs, &out, len, params);
+    Some(gpu.read(out, shape.clone(), precision))
+}
+
+/// Try to reduce an array along its first axis on the GPU
+///
+/// Reductions on the GPU combine rows in a different order than the CPU,
+/// so sums and products may differ by rounding.
+pub(crate) fn reduce(prim: Primitive, arr: &Array<f64>, env: &Uiua) -> Option<Array<f64>> {
+    let op = match prim {
+        Primitive::Add => GpuBinOp::Add,
+        Primitive::Mul => GpuBinOp::Mul,
+        Primitive::Min => GpuBinOp::Min,
+        Primitive::Max => GpuBinOp::Max,
+        _ => return None,
+    };
+    if arr.rank() == 0 || arr.row_count() < 2 || arr.row_len() == 0 {
+        return None;
+    }
+    let (gpu, precision) = gpu_for(arr.element_count(), env)?;
+    let row_len = arr.row_len();
+    let mut rows = arr.row_count();
+    let mut input = gpu.input(arr, precision);
+    // Each pass folds chunks of rows until there is only one row left
+    loop {
+        let out_rows = rows.div_ceil(REDUCE_CHUNK);
+        let out = gpu.output(out_rows * row_len, precision);
+        let params = [rows as u32, row_len as u32, REDUCE_CHUNK as u32, 0];
+        let inputs = [input.buffer()];
+        gpu.run(
+            Kernel::Reduce(op),
+            precision,
+            &inputs,
+            &out,
+            out_rows * row_len,
+            params,
+        );
+        if out_rows == 1 {
+            let shape: Shape = arr.shape()[1..].iter().copied().collect();
+            return Some(gpu.read(out, shape, precision));
+        }
+        input = Input::New(out);
+        rows = out_rows;
+    }
+}
+
+/// Try to multiply two matrices on the GPU
+///
+/// Like the table of the sum of products, the result is the product of `a`
+/// and the transpose of `b`.
+pub(crate) fn matrix_mul(a: &Array<f64>, b: &Array<f64>, env: &Uiua) -> Option<Array<f64>> {
+    if a.rank() != 2 || b.rank() != 2 || a.row_len() != b.row_len() || a.row_len() == 0 {
+        return None;
+    }
+    let (m, n, k) = (a.row_count(), b.row_count(), a.row_len());
+    let len = m * n;
+    let (gpu, precision) = gpu_for(a.element_count().max(b.element_count()), env)?;
+    if !gpu.fits(len, precision) {
+        return None;
+    }
+    let (a_buf, b_buf) = (gpu.input(a, precision), gpu.input(b, precision));
+    let out = gpu.output(len, precision);
+    let params = [m as u32, n as u32, k as u32, 0];
+    let inputs = [a_buf.buffer(), b_buf.buffer()];
+    gpu.run(Kernel::MatMul, precision, &inputs, &out, len, params);
+    Some(gpu.read(out, Shape::from([m, n]), precision))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn gpu_matches_cpu() {
+        let code = "\
+A ← ÷7 ⇡600
+B ← ⍜(⊡10)(◌NaN) ×3 ⇌A
+M ← ↯20_30 A
+[+A B] [-A B] [×A B] [÷A B] [↥A B] [↧A B]
++1 ×2 A
++1 ⍜(⊡0)(+5) ×2 A
+/+ A /× ÷300 A /↥ B /↧ B /+ M /× ÷10 M
+⊞(/+×) M ↯15_30 B";
+        let run = |gpu: Option<GpuOptions>| {
+            let mut env = Uiua::with_safe_sys();
+            if let Some(options) = gpu {
+                env = env.with_gpu(options);
+            }
+            // Constants are not evaluated at compile time so that they run on the GPU
+            env.compile_run(|comp| comp.pre_eval_mode(PreEvalMode::Lazy).load_str(code))
+                .unwrap();
+            env.take_stack()
+        };
+        let cpu = run(None);
+        for (allow_f32, tolerance) in [(false, 1e-9), (true, 1e-3)] {
+            let options = GpuOptions {
+                threshold: 0,
+                allow_f32,
+            };
+            let gpu = run(Some(options));
+            assert_eq!(cpu.len(), gpu.len());
+            for (c, g) in cpu.iter().zip(&gpu) {
+                let (Value::Num(c), Value::Num(g)) = (c, g) else {
+                    assert_eq!(c, g);
+                    continue;
+                };
+                assert_eq!(c.shape(), g.shape());
+                for (&c, &g) in c.data.iter().zip(&g.data) {
+                    assert!(
+                        c == g
+                            || c.is_nan() && g.is_nan()
+                            || (c - g).abs() <= tolerance * c.abs().max(1.0),
+                        "CPU result {c} and GPU result {g} differ (allow_f32: {allow_f32})"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Showing image-like arrays with terminal graphics protocols
+
+use std::{fmt::Write, str::FromStr};
+
+use crate::{algorithm::encode::base64_encode, Value};
+
+/// The environment variable that selects a [`TerminalGraphics`] protocol
+pub const TERMINAL_GRAPHICS_VAR: &str = "UIUA_GRAPHICS";
+
+/// The maximum number of base64 characters in a single Kitty graphics escape sequence
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// A protocol for drawing images directly in a terminal
+///
+/// Image-like values are drawn as images instead of being formatted as text.
+/// See [`Value::is_image_like`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalGraphics {
+    /// The Kitty graphics protocol
+    Kitty,
+    /// Sixel graphics
+    Sixel,
+}
+
+impl FromStr for TerminalGraphics {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "kitty" => Ok(TerminalGraphics::Kitty),
+            "sixel" => Ok(TerminalGraphics::Sixel),
+            _ => Err(format!("unknown terminal graphics protocol `{s}`")),
+        }
+    }
+}
+
+impl TerminalGraphics {
+    /// Render an image-like value as escape sequences
+    ///
+    /// A label is put on its own line before the image.
+    /// Returns `None` if the value is not image-like.
+    pub fn render(&self, value: &Value) -> Option<String> {
+        let (width, height, pixels) = value.image_pixels()?;
+        let mut s = String::new();
+        if let Some(label) = &value.meta().label {
+            s.push_str(label);
+            s.push_str(":\n");
+        }
+        match self {
+            TerminalGraphics::Kitty => kitty(&mut s, width, height, &pixels),
+            TerminalGraphics::Sixel => sixel(&mut s, width, &pixels),
+        }
+        Some(s)
+    }
+}
+
+fn kitty(s: &mut String, width: usize, height: usize, pixels: &[[f64; 4]]) {
+    let bytes: Vec<u8> = (pixels.iter().flatten())
+        .map(|&c| (c * 255.0).round() as u8)
+        .collect();
+    let encoded = base64_encode(&bytes);
+    let chunks: Vec<&[char]> = encoded.chunks(KITTY_CHUNK_SIZE).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        if i == 0 {
+            write!(s, "\x1b_Gf=32,s={width},v={height},a=T,m={more};").unwrap();
+        } else {
+            write!(s, "\x1b_Gm={more};").unwrap();
+        }
+        s.extend(chunk.iter());
+        s.push_str("\x1b\\");
+    }
+}
+
+/// The number of levels of each channel in the sixel palette
+const SIXEL_LEVELS: usize = 6;
+
+fn sixel(s: &mut String, width: usize, pixels: &[[f64; 4]]) {
+    // Quantize to a color cube, blending transparent pixels with black
+    let level = |c: f64| (c.clamp(0.0, 1.0) * (SIXEL_LEVELS - 1) as f64).round() as usize;
+    let colors: Vec<usize> = (pixels.iter())
+        .map(|&[r, g, b, a]| {
+            let a = a.clamp(0.0, 1.0);
+            (level(r * a) * SIXEL_LEVELS + level(g * a)) * SIXEL_LEVELS + level(b * a)
+        })
+        .collect();
+    let height = colors.len() / width;
+    s.push_str("\x1bPq");
+    write!(s, "\"1;1;{width};{height}").unwrap();
+    let percent = |level: usize| level * 100 / (SIXEL_LEVELS - 1);
+    for i in 0..SIXEL_LEVELS.pow(3) {
+        let (r, g, b) = (
+            i / (SIXEL_LEVELS * SIXEL_LEVELS),
+            i / SIXEL_LEVELS % SIXEL_LEVELS,
+            i % SIXEL_LEVELS,
+        );
+        write!(s, "#{i};2;{};{};{}", percent(r), percent(g), percent(b)).unwrap();
+    }
+    // Each band is 6 pixels tall, and each color in it is drawn in its own pass
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut used = vec![false; SIXEL_LEVELS.pow(3)];
+        for row in rows.clone() {
+            for &color in &colors[row * width..][..width] {
+                used[color] = true;
+            }
+        }
+        let mut first = true;
+        for color in (0..used.len()).filter(|&c| used[c]) {
+            if !first {
+                s.push('$');
+            }
+            first = false;
+            write!(s, "#{color}").unwrap();
+            let mut run: Option<(char, usize)> = None;
+            for col in 0..width {
+                let bits = (rows.clone())
+                    .filter(|&row| colors[row * width + col] == color)
+                    .fold(0, |bits, row| bits | 1 << (row - band));
+                let c = char::from(63 + bits);
+                match &mut run {
+                    Some((prev, n)) if *prev == c => *n += 1,
+                    _ => {
+                        if let Some((prev, n)) = run {
+                            push_sixel_run(s, prev, n);
+                        }
+                        run = Some((c, 1));
+                    }
+                }
+            }
+            if let Some((c, n)) = run {
+                push_sixel_run(s, c, n);
+            }
+        }
+        s.push('-');
+    }
+    s.push_str("\x1b\\");
+}
+
+fn push_sixel_run(s: &mut String, c: char, n: usize) {
+    if n > 3 {
+        write!(s, "!{n}{c}").unwrap();
+    } else {
+        s.extend(std::iter::repeat(c).take(n));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn terminal_graphics() {
+        assert_eq!("Kitty".parse(), Ok(TerminalGraphics::Kitty));
+        assert_eq!("sixel".parse(), Ok(TerminalGraphics::Sixel));
+        assert!("png".parse::<TerminalGraphics>().is_err());
+
+        let mut env = Uiua::with_safe_sys();
+        env.run_str("÷255 ↯40_60_3 ⇡256\n↯29_40 0.5").unwrap();
+        let [rgb, small] = &env.take_stack()[..] else {
+            panic!("expected 2 values")
+        };
+        assert_eq!(TerminalGraphics::Kitty.render(small), None);
+        assert_eq!(env.display_value(rgb), rgb.show());
+
+        // 40×60 RGBA pixels are 12800 base64 characters, which take 4 chunks
+        let kitty = TerminalGraphics::Kitty.render(rgb).unwrap();
+        assert!(kitty.starts_with("\x1b_Gf=32,s=60,v=40,a=T,m=1;"));
+        assert_eq!(kitty.matches("\x1b_G").count(), 4);
+        assert!(kitty.contains("\x1b_Gm=0;"));
+        assert!(kitty.ends_with("\x1b\\"));
+
+        // 40 rows take 7 bands of 6
+        let sixel = TerminalGraphics::Sixel.render(rgb).unwrap();
+        assert!(sixel.starts_with("\x1bPq\"1;1;60;40"));
+        assert_eq!(sixel.matches('-').count(), 7);
+        assert!(sixel.ends_with("-\x1b\\"));
+
+        let env = env.with_terminal_graphics(TerminalGraphics::Sixel);
+        assert_eq!(env.display_value(rgb), sixel);
+        assert_eq!(env.display_value(small), small.show());
+    }
+}
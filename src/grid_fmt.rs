@@ -9,12 +9,14 @@ use std::{
     mem::take,
 };
 
+use ecow::EcoString;
+
 use crate::{
     algorithm::map::{EMPTY_NAN, TOMBSTONE_NAN},
     array::{Array, ArrayValue},
     boxed::Boxed,
     value::Value,
-    Complex, Primitive, WILDCARD_CHAR, WILDCARD_NAN,
+    Complex, Primitive, Timestamp, WILDCARD_CHAR, WILDCARD_NAN,
 };
 
 type Grid<T = char> = Vec<Vec<T>>;
@@ -24,6 +26,39 @@ type Metagrid = Grid<Grid>;
 pub struct GridFmtParams {
     pub boxed: bool,
     pub label: bool,
+    /// Render nested boxes as a tree, expanding up to this many levels
+    pub tree_depth: Option<usize>,
+    /// The width of the display that the grid is shown on
+    ///
+    /// Rows of arrays with rank > 1 are truncated at this width,
+    /// and fewer rows are shown.
+    /// If this is `None`, only very large arrays are truncated.
+    pub max_width: Option<usize>,
+    /// Replace the characters of numbers, characters, and labels with mask characters
+    ///
+    /// This is used to tag the parts of a grid without changing its layout.
+    pub mask: bool,
+}
+
+const NUMBER_MASK: char = '\u{E000}';
+const CHAR_MASK: char = '\u{E001}';
+const LABEL_MASK: char = '\u{E002}';
+
+/// Mask every character of a grid except box glyphs
+fn mask_grid(params: GridFmtParams, mask: char, mut grid: Grid) -> Grid {
+    if params.mask {
+        for c in grid.iter_mut().flatten() {
+            if *c != Primitive::Box.glyph().unwrap() {
+                *c = mask;
+            }
+        }
+    }
+    grid
+}
+
+/// Get the characters of a label, masked if necessary
+fn label_chars(params: GridFmtParams, label: &str) -> impl Iterator<Item = char> + '_ {
+    (label.chars()).map(move |c| if params.mask { LABEL_MASK } else { c })
 }
 
 pub trait GridFmt {
@@ -49,76 +84,80 @@ fn boxed_scalar(boxed: bool) -> impl Iterator<Item = char> {
 impl GridFmt for u8 {
     fn fmt_grid(&self, params: GridFmtParams) -> Grid {
         let boxed = boxed_scalar(params.boxed);
-        vec![boxed.chain(self.to_string().chars()).collect()]
+        let grid = vec![boxed.chain(self.to_string().chars()).collect()];
+        mask_grid(params, NUMBER_MASK, grid)
     }
 }
 
 impl GridFmt for f64 {
     fn fmt_grid(&self, params: GridFmtParams) -> Grid {
-        let f = *self;
-        let positive = f.abs();
-        let is_neg = f < 0.0;
-        let minus = if is_neg { "¯" } else { "" };
-        let s = if (positive - PI).abs() < f64::EPSILON {
-            format!("{minus}π")
-        } else if (positive - TAU).abs() < f64::EPSILON {
-            format!("{minus}τ")
-        } else if (positive - PI / 2.0).abs() < f64::EPSILON {
-            format!("{minus}η")
-        } else if positive == INFINITY {
-            format!("{minus}∞")
-        } else if f.to_bits() == EMPTY_NAN.to_bits() || f.to_bits() == TOMBSTONE_NAN.to_bits() {
-            return vec![vec!['⋅']];
-        } else if f.to_bits() == WILDCARD_NAN.to_bits() {
-            return vec![vec!['W']];
-        } else if positive.fract() == 0.0 || positive.is_nan() {
-            format!("{minus}{positive}")
-        } else {
-            let mut pos_formatted = positive.to_string();
-            if pos_formatted.len() >= 18 {
-                let mut consecutive_start = 0;
-                let mut consecutive_len = 0;
-                let mut hit_decimal = false;
-                for (i, c) in pos_formatted.chars().enumerate() {
-                    if c == '.' {
-                        hit_decimal = true;
-                    } else if !hit_decimal {
-                        continue;
-                    }
-                    let local_len = pos_formatted
-                        .chars()
-                        .skip(i + 1)
-                        .take_while(|&d| d == c)
-                        .count();
-                    if local_len > consecutive_len {
-                        consecutive_start = i;
-                        consecutive_len = local_len;
-                    }
+        mask_grid(params, NUMBER_MASK, fmt_num(*self, params))
+    }
+}
+
+fn fmt_num(f: f64, params: GridFmtParams) -> Grid {
+    let positive = f.abs();
+    let is_neg = f < 0.0;
+    let minus = if is_neg { "¯" } else { "" };
+    let s = if (positive - PI).abs() < f64::EPSILON {
+        format!("{minus}π")
+    } else if (positive - TAU).abs() < f64::EPSILON {
+        format!("{minus}τ")
+    } else if (positive - PI / 2.0).abs() < f64::EPSILON {
+        format!("{minus}η")
+    } else if positive == INFINITY {
+        format!("{minus}∞")
+    } else if f.to_bits() == EMPTY_NAN.to_bits() || f.to_bits() == TOMBSTONE_NAN.to_bits() {
+        return vec![vec!['⋅']];
+    } else if f.to_bits() == WILDCARD_NAN.to_bits() {
+        return vec![vec!['W']];
+    } else if positive.fract() == 0.0 || positive.is_nan() {
+        format!("{minus}{positive}")
+    } else {
+        let mut pos_formatted = positive.to_string();
+        if pos_formatted.len() >= 18 {
+            let mut consecutive_start = 0;
+            let mut consecutive_len = 0;
+            let mut hit_decimal = false;
+            for (i, c) in pos_formatted.chars().enumerate() {
+                if c == '.' {
+                    hit_decimal = true;
+                } else if !hit_decimal {
+                    continue;
                 }
-                if consecutive_len >= 5 {
-                    if consecutive_start + consecutive_len + 1 == pos_formatted.len() {
-                        pos_formatted.replace_range(consecutive_start + 3.., "…")
-                    } else {
-                        pos_formatted.replace_range(
-                            consecutive_start + 2..consecutive_start + consecutive_len,
-                            "…",
-                        )
-                    }
+                let local_len = pos_formatted
+                    .chars()
+                    .skip(i + 1)
+                    .take_while(|&d| d == c)
+                    .count();
+                if local_len > consecutive_len {
+                    consecutive_start = i;
+                    consecutive_len = local_len;
                 }
             }
-            if is_neg {
-                format!("{minus}{pos_formatted}")
-            } else {
-                pos_formatted
+            if consecutive_len >= 5 {
+                if consecutive_start + consecutive_len + 1 == pos_formatted.len() {
+                    pos_formatted.replace_range(consecutive_start + 3.., "…")
+                } else {
+                    pos_formatted.replace_range(
+                        consecutive_start + 2..consecutive_start + consecutive_len,
+                        "…",
+                    )
+                }
             }
-        };
-        vec![boxed_scalar(params.boxed).chain(s.chars()).collect()]
-    }
+        }
+        if is_neg {
+            format!("{minus}{pos_formatted}")
+        } else {
+            pos_formatted
+        }
+    };
+    vec![boxed_scalar(params.boxed).chain(s.chars()).collect()]
 }
 
 impl GridFmt for Complex {
     fn fmt_grid(&self, params: GridFmtParams) -> Grid {
-        if self.im.abs() == 0.0 {
+        let grid = if self.im.abs() == 0.0 {
             self.re.fmt_grid(params)
         } else if self.re.abs() == 0.0 {
             if self.im == 1.0 {
@@ -142,12 +181,31 @@ impl GridFmt for Complex {
             re[0].extend(im.chars());
             re[0].push('i');
             re
-        }
+        };
+        mask_grid(params, NUMBER_MASK, grid)
+    }
+}
+
+impl GridFmt for Timestamp {
+    fn fmt_grid(&self, params: GridFmtParams) -> Grid {
+        vec![boxed_scalar(params.boxed)
+            .chain(self.to_string().chars())
+            .collect()]
     }
 }
 
 impl GridFmt for Value {
     fn fmt_grid(&self, params: GridFmtParams) -> Grid {
+        if let Some(depth) = params.tree_depth {
+            let params = GridFmtParams {
+                tree_depth: None,
+                ..params
+            };
+            return match self {
+                Value::Box(arr) if depth > 0 => fmt_tree(arr, depth, params),
+                val => val.fmt_grid(params),
+            };
+        }
         'box_list: {
             let Value::Box(b) = self else {
                 break 'box_list;
@@ -169,7 +227,7 @@ impl GridFmt for Value {
             }
             let mut only_row = Vec::new();
             if let Some(label) = &b.meta().label {
-                only_row.extend(label.chars());
+                only_row.extend(label_chars(params, label));
                 only_row.push(':');
                 only_row.push(' ');
             }
@@ -188,6 +246,7 @@ impl GridFmt for Value {
             Value::Byte(b) => b.fmt_grid(params),
             Value::Complex(c) => c.fmt_grid(params),
             Value::Box(v) => v.fmt_grid(params),
+            Value::Time(v) => v.fmt_grid(params),
             Value::Char(c) => c.fmt_grid(params),
         }
     }
@@ -218,9 +277,10 @@ pub fn format_char_inner(c: char) -> String {
 
 impl GridFmt for char {
     fn fmt_grid(&self, params: GridFmtParams) -> Grid {
-        vec![once(if params.boxed { '⌞' } else { '@' })
+        let grid = vec![once(if params.boxed { '⌞' } else { '@' })
             .chain(format_char_inner(*self).chars())
-            .collect()]
+            .collect()];
+        mask_grid(params, CHAR_MASK, grid)
     }
 }
 
@@ -236,6 +296,7 @@ impl GridFmt for Boxed {
             Value::Complex(array) => array.fmt_grid(subparams),
             Value::Char(array) => array.fmt_grid(subparams),
             Value::Box(array) => array.fmt_grid(subparams),
+            Value::Time(array) => array.fmt_grid(subparams),
         };
         if params.boxed && grid.len() == 1 {
             grid = vec![boxed_scalar(true)
@@ -286,6 +347,7 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
                         Value::Complex(_) => shape_row::<Complex>(&keys_row_shape),
                         Value::Char(_) => shape_row::<char>(&keys_row_shape),
                         Value::Box(_) => shape_row::<Boxed>(&keys_row_shape),
+                        Value::Time(_) => shape_row::<Timestamp>(&keys_row_shape),
                     };
                     row.extend([' ', '→', ' ']);
                     let mut value_row_shape = self.shape.clone();
@@ -380,13 +442,13 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
         if params.label {
             if let Some(label) = &self.meta().label {
                 if grid.len() == 1 {
-                    grid[0] = (label.chars().chain([':', ' ']))
+                    grid[0] = (label_chars(params, label).chain([':', ' ']))
                         .chain(take(&mut grid[0]))
                         .collect();
                 } else {
                     grid[0].truncate(2);
                     grid[0].push(' ');
-                    grid[0].extend(label.chars());
+                    grid[0].extend(label_chars(params, label));
                     while grid[0].len() < grid[1].len() {
                         grid[0].push(' ');
                     }
@@ -394,6 +456,41 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
             }
         }
 
+        // Add axis names to the bottom corner
+        if let Some(axes) = self
+            .axes()
+            .filter(|axes| axes.iter().any(|name| !name.is_empty()))
+        {
+            if grid.len() > 1 {
+                let names: Vec<&str> = (axes.iter())
+                    .map(|name| if name.is_empty() { "_" } else { name })
+                    .collect();
+                let names: Vec<char> = names.join("×").chars().collect();
+                let last = grid.last_mut().unwrap();
+                let corner = last.pop().unwrap();
+                last.truncate(last.len().saturating_sub(names.len() + 1).max(1));
+                last.extend(names);
+                last.push(' ');
+                last.push(corner);
+            }
+        }
+
+        // Add unit
+        if let Some(unit) = &self.meta().unit {
+            let unit: Vec<char> = unit.to_string().chars().collect();
+            if grid.len() == 1 {
+                grid[0].push(' ');
+                grid[0].extend(unit);
+            } else {
+                let top = &mut grid[0];
+                let min_start = top.iter().rposition(|c| !c.is_whitespace()).unwrap_or(0) + 2;
+                let start = top.len().saturating_sub(unit.len()).max(min_start);
+                top.truncate(start);
+                top.resize(start, ' ');
+                top.extend(unit);
+            }
+        }
+
         // Add pointer
         if let Some(pointer) = self.meta().pointer.filter(|p| !p.raw) {
             if grid.len() == 1 {
@@ -403,7 +500,7 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
 
         // Handle really big grid
         if self.rank() > 1 {
-            let max_width = term_size::dimensions().map_or(1000, |(w, _)| w);
+            let max_width = params.max_width.unwrap_or(1000).max(4);
             for row in grid.iter_mut() {
                 if row.len() > max_width {
                     let diff = row.len() - max_width;
@@ -424,7 +521,12 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
 impl<T: ArrayValue> Array<T> {
     /// Get a string representation of the shape of the array
     pub fn shape_string(&self) -> String {
-        let base: String = shape_row::<T>(&self.shape).into_iter().collect();
+        let base: String = match self.axes() {
+            Some(axes) => named_shape_row::<T>(&self.shape, axes),
+            None => shape_row::<T>(&self.shape),
+        }
+        .into_iter()
+        .collect();
         if let Some(keys) = &self.meta().map_keys {
             let mut keys_shape = keys.keys.shape().clone();
             keys_shape[0] = self.row_count();
@@ -434,6 +536,7 @@ impl<T: ArrayValue> Array<T> {
                 Value::Complex(_) => shape_row::<Complex>(&keys_shape),
                 Value::Char(_) => shape_row::<char>(&keys_shape),
                 Value::Box(_) => shape_row::<Boxed>(&keys_shape),
+                Value::Time(_) => shape_row::<Timestamp>(&keys_shape),
             }
             .into_iter()
             .collect();
@@ -455,10 +558,148 @@ impl Value {
             Array::shape_string,
             Array::shape_string,
             Array::shape_string,
+            Array::shape_string,
         )
     }
 }
 
+impl Value {
+    /// Get the pretty-printed string representation of the value, truncating rows at a given width
+    ///
+    /// [`Value::show`] only truncates very large arrays.
+    pub fn show_with_width(&self, width: usize) -> String {
+        let mut s: String = (self.fmt_grid(GridFmtParams {
+            label: true,
+            max_width: Some(width),
+            ..Default::default()
+        }))
+        .into_iter()
+        .flat_map(|v| v.into_iter().chain(once('\n')))
+        .collect();
+        s.pop();
+        s
+    }
+    /// Get a pretty-printed string representation of the value with nested boxes drawn as a tree
+    ///
+    /// Boxes nested deeper than `max_depth` are formatted normally.
+    pub fn show_tree(&self, max_depth: usize) -> String {
+        let mut s: String = (self.fmt_grid(GridFmtParams {
+            label: true,
+            tree_depth: Some(max_depth),
+            ..Default::default()
+        }))
+        .into_iter()
+        .flat_map(|v| v.into_iter().chain(once('\n')))
+        .collect();
+        s.pop();
+        s
+    }
+}
+
+/// The kind of a [`ShownToken`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShownTokenKind {
+    /// A number, including complex numbers and special values like `∞`
+    Number,
+    /// A character or string, including its delimiters
+    Char,
+    /// Part of the frame drawn around an array
+    BoxCorner,
+    /// The label of a value
+    Label,
+    /// The arrow between a map key and its value
+    MapArrow,
+    /// Anything else, such as whitespace and brackets
+    Other,
+}
+
+/// A tagged piece of the pretty-printed representation of a value
+///
+/// Created with [`Value::show_tokens`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShownToken {
+    /// The kind of the token
+    pub kind: ShownTokenKind,
+    /// The text of the token
+    pub text: String,
+}
+
+impl Value {
+    /// Get the pretty-printed representation of the value as a sequence of tagged tokens
+    ///
+    /// Concatenating the tokens' text gives the output of [`Value::show`].
+    /// Adjacent tokens of the same kind are merged, and lines are separated by `\n` in [`ShownTokenKind::Other`] tokens.
+    pub fn show_tokens(&self) -> Vec<ShownToken> {
+        let params = GridFmtParams {
+            label: true,
+            ..Default::default()
+        };
+        let grid = self.fmt_grid(params);
+        // Masking does not change the layout, so the masks line up with the real characters
+        let masked = self.fmt_grid(GridFmtParams {
+            mask: true,
+            ..params
+        });
+        let mut tokens: Vec<ShownToken> = Vec::new();
+        let mut push = |kind: ShownTokenKind, text: &[char]| match tokens.last_mut() {
+            Some(last) if last.kind == kind => last.text.extend(text),
+            _ => tokens.push(ShownToken {
+                kind,
+                text: text.iter().collect(),
+            }),
+        };
+        for (i, (row, masked_row)) in grid.iter().zip(&masked).enumerate() {
+            if i > 0 {
+                push(ShownTokenKind::Other, &['\n']);
+            }
+            for (c, mask) in row.iter().zip(masked_row) {
+                let kind = match mask {
+                    &NUMBER_MASK => ShownTokenKind::Number,
+                    &CHAR_MASK | '"' | '⌜' | '⌟' => ShownTokenKind::Char,
+                    &LABEL_MASK => ShownTokenKind::Label,
+                    '╭' | '╓' | '╷' | '╟' | '─' | '╯' | '╜' => {
+                        ShownTokenKind::BoxCorner
+                    }
+                    '→' => ShownTokenKind::MapArrow,
+                    _ => ShownTokenKind::Other,
+                };
+                push(kind, &[*c]);
+            }
+        }
+        tokens
+    }
+}
+
+/// Format a box array as a tree of its items
+fn fmt_tree(arr: &Array<Boxed>, depth: usize, params: GridFmtParams) -> Grid {
+    let mut header = Vec::new();
+    if params.label {
+        if let Some(label) = &arr.meta().label {
+            header.extend(label_chars(params, label).chain([':', ' ']));
+        }
+    }
+    header.extend(arr.shape_string().chars());
+    let mut grid = vec![header];
+    let subparams = GridFmtParams {
+        boxed: false,
+        tree_depth: Some(depth - 1),
+        ..params
+    };
+    for (i, Boxed(val)) in arr.data.iter().enumerate() {
+        let last = i == arr.data.len() - 1;
+        let (first_prefix, rest_prefix) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        for (j, line) in val.fmt_grid(subparams).into_iter().enumerate() {
+            let prefix = if j == 0 { first_prefix } else { rest_prefix };
+            grid.push(prefix.chars().chain(line).collect());
+        }
+    }
+    grid
+}
+
 fn shape_row<T: ArrayValue>(shape: &[usize]) -> Vec<char> {
     let mut shape_row = Vec::new();
     for (i, dim) in shape.iter().enumerate() {
@@ -474,6 +715,26 @@ fn shape_row<T: ArrayValue>(shape: &[usize]) -> Vec<char> {
     shape_row
 }
 
+/// Like [`shape_row`], but each named axis is prefixed with its name
+fn named_shape_row<T: ArrayValue>(shape: &[usize], axes: &[EcoString]) -> Vec<char> {
+    let mut shape_row = Vec::new();
+    for (i, (dim, name)) in shape.iter().zip(axes).enumerate() {
+        if i > 0 {
+            shape_row.extend("×".chars());
+        }
+        if !name.is_empty() {
+            shape_row.extend(name.chars());
+            shape_row.push(':');
+        }
+        shape_row.extend(dim.to_string().chars());
+    }
+    if !shape.is_empty() {
+        shape_row.push(' ');
+    }
+    shape_row.push(T::SYMBOL);
+    shape_row
+}
+
 fn fmt_array<T: GridFmt + ArrayValue>(
     shape: &[usize],
     data: &[T],
@@ -502,7 +763,7 @@ fn fmt_array<T: GridFmt + ArrayValue>(
                 .chars()
                 .map(format_char_inner)
                 .collect();
-            row.push(vec![s.chars().collect()]);
+            row.push(mask_grid(params, CHAR_MASK, vec![s.chars().collect()]));
         } else {
             for (i, val) in data.iter().enumerate() {
                 let mut grid = val.fmt_grid(GridFmtParams {
@@ -526,11 +787,7 @@ fn fmt_array<T: GridFmt + ArrayValue>(
     let row_shape = &shape[1..];
     let cell_size = data.len() / cell_count;
     let row_height: usize = row_shape.iter().rev().skip(1).product();
-    let max_height = if term_size::dimensions().is_some() {
-        100
-    } else {
-        300
-    };
+    let max_height = if params.max_width.is_some() { 100 } else { 300 };
     for (i, cell) in data.chunks(cell_size).enumerate() {
         if i > 0 && rank > 2 {
             for _ in 0..rank - 2 {
@@ -615,3 +872,87 @@ fn pad_grid_min(width: usize, height: usize, grid: &mut Grid) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn show_tree() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(r#"{1 {2 {3 "hi"}} [4 5]}"#).unwrap();
+        let val = env.pop(()).unwrap();
+        let expected = r#"3 □
+├─ 1
+├─ 2 □
+│  ├─ 2
+│  └─ 2 □
+│     ├─ 3
+│     └─ "hi"
+└─ [4 5]"#;
+        assert_eq!(val.show_tree(usize::MAX), expected);
+        let expected = r#"3 □
+├─ 1
+├─ {2 {3 "hi"}}
+└─ [4 5]"#;
+        assert_eq!(val.show_tree(1), expected);
+    }
+
+    #[test]
+    fn show_tokens() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(
+            "# Experimental!\n{¯1.5 @\\n \"a\\\"b\" $lab 3 ℂ1 2}\nmap 1_2 \"ab\"\n\
+            {1 \"ab\" [1_2 3_4] $lb 3}\n$bar ↯2_2 1",
+        )
+        .unwrap();
+        for value in env.take_stack() {
+            let tokens = value.show_tokens();
+            let text: String = tokens.iter().map(|t| t.text.as_str()).collect();
+            assert_eq!(text, value.show());
+        }
+        env.run_str("# Experimental!\n{¯1.5 @\\n \"a\\\"b\" $lab 3 ℂ1 2}")
+            .unwrap();
+        let tokens = env.pop(()).unwrap().show_tokens();
+        let tagged: Vec<(ShownTokenKind, &str)> = (tokens.iter())
+            .filter(|t| t.kind != ShownTokenKind::Other)
+            .map(|t| (t.kind, t.text.as_str()))
+            .collect();
+        use ShownTokenKind::*;
+        assert_eq!(
+            tagged,
+            [
+                (Number, "¯1.5"),
+                (Char, "@\\n"),
+                (Char, "\"a\"b\""),
+                (Label, "lab"),
+                (Number, "3"),
+                (Number, "2+i"),
+            ]
+        );
+        env.run_str("# Experimental!\n$bar ↯2_2 1").unwrap();
+        let tokens = env.pop(()).unwrap().show_tokens();
+        assert!((tokens.iter()).any(|t| t.kind == Label && t.text == "bar"));
+        env.run_str("map 1_2 \"ab\"").unwrap();
+        let tokens = env.pop(()).unwrap().show_tokens();
+        let count = |kind| tokens.iter().filter(|t| t.kind == kind).count();
+        assert_eq!(count(MapArrow), 2);
+        assert_eq!(count(Char), 2);
+        assert!(count(BoxCorner) >= 2);
+    }
+
+    #[test]
+    fn show_with_width() {
+        let value = Value::from(Array::<f64>::new([2, 20], [1.0; 40].as_slice()));
+        let shown = value.show_with_width(12);
+        assert!(shown.lines().all(|line| line.chars().count() <= 12));
+        assert!(shown.contains('…'));
+        let shown = value.show_with_width(100);
+        assert!(!shown.contains('…'));
+        let value = Value::from(Array::<f64>::new([2, 300], [1.0; 600].as_slice()));
+        assert!(!value.show().contains('…'));
+        let env = Uiua::with_safe_sys().with_grid_width(12);
+        assert_eq!(env.grid_width(), Some(12));
+        assert_eq!(env.show_value(&value), value.show_with_width(12));
+    }
+}
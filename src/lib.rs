@@ -14,7 +14,7 @@ This disables some of the features that many users are used to having, so to re-
 uiua = { version = "*", default-features = false, features = ["batteries"] }
 ```
 
-The main entry point is the [`Uiua`] struct, which is the Uiua runtime. It must be created with a [`SysBackend`]. [`Uiua::with_native_sys`] is a convenient way to create a Uiua runtime that uses the same backend as the Uiua CLI, though keep in mind it gives full access to the filesystem and TCP sockets and so probably shouldn't be used in a sandboxed environment.
+The main entry point is the [`Uiua`] struct, which is the Uiua runtime. It must be created with a [`SysBackend`]. [`Uiua::with_native_sys`] is a convenient way to create a Uiua runtime that uses the same backend as the Uiua CLI, though keep in mind it gives full access to the filesystem and TCP sockets and so probably shouldn't be used in a sandboxed environment. A [`SysPolicy`] set with [`Uiua::with_policy`] can deny system functions access to the filesystem, network, subprocesses, or clipboard.
 
 [`Value`] is the generic value type. It wraps one of five [`Array`] types.
 
@@ -133,6 +133,10 @@ The `uiua` crate has the following noteable feature flags:
 - `invoke`: Enables the `&invk` system function
 - `trash`: Enables the `&ftr` system function
 - `raw_mode`: Enables the `&raw` system function
+- `differential`: Enables the `differential` module for testing optimized algorithms against reference implementations
+- `zstd`: Enables Zstandard in the `compress` and `decompress` functions
+- `sha2`: Enables the [`AssemblyCache`], the [`Lockfile`], and SHA-256 in the `hash` function. `native_sys` and `hash` enable it.
+- `ndarray`: Enables conversions between [`Array`]s and [`ndarray`](https://docs.rs/ndarray) arrays
 */
 
 #![allow(clippy::single_match, clippy::needless_range_loop)]
@@ -143,54 +147,108 @@ mod array;
 mod assembly;
 pub mod ast;
 mod boxed;
+mod builder;
+mod bundle;
+mod cache;
+pub mod capi;
 mod check;
 mod compile;
 mod complex;
+mod coverage;
 mod cowslice;
+mod debug;
+mod diff;
+#[cfg(any(test, feature = "differential"))]
+#[doc(hidden)]
+pub mod differential;
+mod doctest;
+mod embedded;
 mod error;
 mod ffi;
 pub mod format;
 mod function;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod graphics;
 mod grid_fmt;
 mod lex;
+#[cfg(feature = "sha2")]
+mod lockfile;
 pub mod lsp;
+#[cfg(feature = "ndarray")]
+mod ndarray_impl;
 mod optimize;
 mod parse;
+mod preview;
 mod primitive;
 #[doc(hidden)]
 pub mod profile;
+mod recompute;
+mod repl;
+mod replay;
 mod run;
 mod shape;
+mod source_map;
 #[cfg(feature = "stand")]
 #[doc(hidden)]
 pub mod stand;
 mod sys;
 #[cfg(feature = "native_sys")]
 mod sys_native;
+mod timestamp;
+mod trace;
+mod usage;
 mod value;
+#[cfg(feature = "watch")]
+mod watch;
+mod workspace;
 
 #[allow(unused_imports)]
 pub use self::{
+    algorithm::SizeError,
     array::*,
     assembly::*,
     boxed::*,
+    builder::{ArrayBuilder, ValueBuilder},
+    bundle::Bundle,
     compile::*,
+    coverage::{CoverageReport, SpanCoverage},
+    debug::{CallFrame, StackSnapshot, ValueSnapshot},
+    diff::*,
+    doctest::*,
     error::*,
     ffi::*,
     function::*,
+    graphics::*,
+    grid_fmt::{ShownToken, ShownTokenKind},
     lex::is_ident_char,
     lex::*,
     lsp::{spans, SpanKind},
     parse::{ident_modifier_args, parse, ParseError},
+    preview::MIN_IMAGE_PREVIEW_DIM,
     primitive::*,
+    recompute::RecomputeStats,
+    repl::*,
+    replay::*,
     run::*,
     shape::*,
+    source_map::{InstrSource, SourceBinding, SourceMap},
     sys::*,
+    timestamp::*,
+    trace::*,
+    usage::ResourceUsage,
     value::*,
+    workspace::*,
 };
 
+#[cfg(feature = "gpu")]
+pub use self::gpu::{GpuBuffer, GpuOptions};
 #[cfg(feature = "native_sys")]
 pub use self::sys_native::*;
+#[cfg(feature = "watch")]
+pub use self::watch::*;
+#[cfg(feature = "sha2")]
+pub use self::{cache::AssemblyCache, lockfile::Lockfile};
 
 pub use complex::*;
 use ecow::EcoString;
@@ -0,0 +1,193 @@
+//! Pinning remote modules with content hashes
+
+use std::collections::BTreeMap;
+
+use serde::*;
+use sha2::{Digest, Sha256};
+
+/// The content hashes of the remote modules a program imports
+///
+/// When a module is imported from a URL or git repository, its main file is hashed.
+/// Files it imports by relative path are hashed too, under the module's source
+/// followed by `file:` and their path within the module.
+/// The first time a module is imported, its hash is added to the lockfile.
+/// After that, the module must always match the hash, so a module that changes,
+/// either at its source or in the local module cache, fails to load.
+///
+/// To update a module, remove its entry from the lockfile.
+///
+/// The compiler keeps the lockfile in a file called [`Lockfile::FILE_NAME`]
+/// in the current directory.
+///
+/// ```
+/// # use uiua::*;
+/// let mut lock = Lockfile::default();
+/// assert_eq!(lock.verify("https://example.com/lib.ua", "F ← +1".as_bytes()), Ok(true));
+/// assert_eq!(lock.verify("https://example.com/lib.ua", "F ← +1".as_bytes()), Ok(false));
+/// assert!(lock.verify("https://example.com/lib.ua", "F ← +2".as_bytes()).is_err());
+///
+/// let lock = Lockfile::from_toml(&lock.to_toml()).unwrap();
+/// assert!(lock.get("https://example.com/lib.ua").unwrap().starts_with("sha256:"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Map module sources to content hashes
+    #[serde(default)]
+    modules: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    /// The name of the lockfile
+    pub const FILE_NAME: &'static str = "uiua.lock";
+    /// Parse a lockfile
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        toml::from_str(toml).map_err(|e| format!("Invalid {}: {e}", Self::FILE_NAME))
+    }
+    /// Serialize the lockfile
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::from(
+            "# This file is generated by Uiua.\n\
+            # It pins the contents of imported remote modules.\n\n",
+        );
+        toml.push_str(&toml::to_string(self).expect("Serializing a lockfile should not fail"));
+        toml
+    }
+    /// Get the hash of a module's contents
+    pub fn hash(contents: &[u8]) -> String {
+        let digest = Sha256::digest(contents);
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        format!("sha256:{hex}")
+    }
+    /// Get the locked hash of a module
+    pub fn get(&self, source: &str) -> Option<&str> {
+        self.modules.get(source).map(String::as_str)
+    }
+    /// Iterate over the locked modules and their hashes
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        (self.modules.iter()).map(|(source, hash)| (source.as_str(), hash.as_str()))
+    }
+    /// Remove a module so that it is locked again the next time it is imported
+    ///
+    /// Returns the removed hash, if any.
+    pub fn remove(&mut self, source: &str) -> Option<String> {
+        self.modules.remove(source)
+    }
+    /// Check a module's contents against its locked hash
+    ///
+    /// If the module is not locked yet, it is added and `Ok(true)` is returned.
+    pub fn verify(&mut self, source: &str, contents: &[u8]) -> Result<bool, String> {
+        let hash = Self::hash(contents);
+        match self.modules.get(source) {
+            Some(locked) if *locked == hash => Ok(false),
+            Some(locked) => Err(format!(
+                "Module {source} does not match {}\n  \
+                expected: {locked}\n  \
+                found:    {hash}\n\
+                If the change is expected, remove the module from {}",
+                Self::FILE_NAME,
+                Self::FILE_NAME
+            )),
+            None => {
+                self.modules.insert(source.into(), hash);
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn remote_module_lock() {
+        use std::{
+            any::Any,
+            collections::HashMap,
+            path::{Path, PathBuf},
+            sync::{Arc, Mutex},
+        };
+
+        type Files = Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>;
+        struct ModuleSys(Files);
+        impl SysBackend for ModuleSys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            fn file_exists(&self, path: &str) -> bool {
+                self.0.lock().unwrap().contains_key(Path::new(path))
+            }
+            fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+                (self.0.lock().unwrap().get(path).cloned()).ok_or_else(|| "Not found".into())
+            }
+            fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+                self.0.lock().unwrap().insert(path.into(), contents.into());
+                Ok(())
+            }
+            fn load_url_module(&self, url: &str) -> Result<PathBuf, String> {
+                let path = PathBuf::from(url.trim_start_matches("https://"));
+                (self.0.lock().unwrap().entry(path.clone())).or_insert("F ← +1".into());
+                Ok(path)
+            }
+        }
+
+        let files = Files::default();
+        let compile = |files: &Files| {
+            Compiler::with_backend(ModuleSys(files.clone()))
+                .load_str("~ \"https://example.com/lib.ua\" ~ F\nF 1")
+                .map(|comp| comp.finish())
+        };
+        let mut env = Uiua::with_safe_sys();
+        env.run_asm(compile(&files).unwrap()).unwrap();
+        assert_eq!(env.pop_int().unwrap(), 2);
+
+        // The module is locked
+        let lock = files.lock().unwrap()[Path::new(Lockfile::FILE_NAME)].clone();
+        let lock = Lockfile::from_toml(std::str::from_utf8(&lock).unwrap()).unwrap();
+        let hash = lock.get("https://example.com/lib.ua").unwrap();
+        assert_eq!(hash, Lockfile::hash("F ← +1".as_bytes()));
+        assert!(compile(&files).is_ok());
+
+        // Changed modules fail to load
+        (files.lock().unwrap()).insert("example.com/lib.ua".into(), "F ← +2".into());
+        let err = compile(&files).err().unwrap();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        // Files imported from inside a remote module are locked with it
+        files.lock().unwrap().extend([
+            (
+                "example.com/main.ua".into(),
+                "~ \"other.ua\" ~ G\nF ← G".into(),
+            ),
+            ("example.com/other.ua".into(), "G ← +3".into()),
+        ]);
+        let compile_main = |files: &Files| {
+            Compiler::with_backend(ModuleSys(files.clone()))
+                .load_str("~ \"https://example.com/main.ua\" ~ F\nF 1")
+                .map(|comp| comp.finish())
+        };
+        let mut env = Uiua::with_safe_sys();
+        env.run_asm(compile_main(&files).unwrap()).unwrap();
+        assert_eq!(env.pop_int().unwrap(), 4);
+        let lock = files.lock().unwrap()[Path::new(Lockfile::FILE_NAME)].clone();
+        let lock = Lockfile::from_toml(std::str::from_utf8(&lock).unwrap()).unwrap();
+        let hash = lock
+            .get("https://example.com/main.ua file:other.ua")
+            .unwrap();
+        assert_eq!(hash, Lockfile::hash("G ← +3".as_bytes()));
+        (files.lock().unwrap()).insert("example.com/other.ua".into(), "G ← +4".into());
+        let err = compile_main(&files).err().unwrap();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        // Plain HTTP imports must be allowed explicitly
+        let http = "~ \"http://example.com/lib.ua\" ~ F\nF 1";
+        let mut comp = Compiler::with_backend(ModuleSys(files.clone()));
+        let err = comp.load_str(http).err().unwrap();
+        assert!(err.to_string().contains("plain HTTP"), "{err}");
+        let mut comp = Compiler::with_backend(ModuleSys(files.clone()));
+        assert!(comp.allow_http_imports(true).load_str(http).is_ok());
+    }
+}
@@ -10,7 +10,7 @@ use std::{
 use crate::{
     algorithm::invert::{invert_instrs, under_instrs},
     ast::{Item, Modifier, PlaceholderOp, Ref, RefComponent, Word},
-    ident_modifier_args, instrs_are_pure,
+    ident_modifier_args, instrs_are_pure, is_ident_char,
     lex::{CodeSpan, Sp},
     parse::parse,
     ArraySwizzle, Assembly, BindingInfo, BindingKind, Compiler, DocComment, Ident, InputSrc,
@@ -103,6 +103,155 @@ pub fn spans_with_backend(input: &str, backend: impl SysBackend) -> (Vec<Sp<Span
     (spanner.items_spans(&items), spanner.asm.inputs)
 }
 
+/// The kind of a [`Completion`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// A primitive
+    Primitive(Primitive),
+    /// A bound constant or a shadowable constant
+    Constant,
+    /// A bound function
+    Function,
+    /// A bound modifier
+    Modifier,
+    /// A module
+    Module,
+}
+
+/// A candidate for completing the word at a cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The text to replace the word with
+    ///
+    /// For primitives with glyphs, this is the glyph.
+    pub text: String,
+    /// The name to display
+    pub label: String,
+    /// The kind of thing being completed
+    pub kind: CompletionKind,
+    /// The signature, if there is one
+    pub signature: Option<Signature>,
+    /// A short description
+    pub doc: Option<String>,
+    /// The byte offset of the start of the word being completed
+    ///
+    /// The text from here to the cursor should be replaced with [`Completion::text`].
+    pub start: usize,
+}
+
+/// Get completions for the word before a cursor in Uiua code
+///
+/// The cursor is a byte offset into the input.
+/// Candidates are primitives, bindings defined before the cursor, and shadowable constants.
+///
+/// ```
+/// # use uiua::lsp::*;
+/// let code = "Double ← ×2\nDou";
+/// assert_eq!(completions(code, code.len())[0].text, "Double");
+/// let code = "⊂ rev";
+/// assert_eq!(completions(code, code.len())[0].text, "⇌");
+/// ```
+pub fn completions(input: &str, cursor: usize) -> Vec<Completion> {
+    completions_with_compiler(input, cursor, &Compiler::with_backend(SafeSys::default()))
+}
+
+/// Get completions for the word before a cursor in Uiua code compiled after other code
+///
+/// Bindings from the compiler are candidates as well.
+/// This is useful for completing lines in a REPL.
+pub fn completions_with_compiler(
+    input: &str,
+    cursor: usize,
+    compiler: &Compiler,
+) -> Vec<Completion> {
+    let Some(before) = input.get(..cursor) else {
+        return Vec::new();
+    };
+    let start = (before.char_indices().rev())
+        .take_while(|&(_, c)| is_ident_char(c) || c == '&')
+        .last()
+        .map_or(cursor, |(i, _)| i);
+    let word = &input[start..cursor];
+    if word.is_empty() {
+        return Vec::new();
+    }
+    let lower = word.to_lowercase();
+    let mut completions = Vec::new();
+
+    // Bindings
+    let mut compiler = compiler.clone();
+    compiler.pre_eval_mode(PreEvalMode::Lsp);
+    let src = InputSrc::Str(compiler.asm.inputs.strings.len());
+    _ = compiler.load_str_src(input, src.clone());
+    let asm = &compiler.asm;
+    let mut seen = HashSet::new();
+    for binding in asm.bindings.iter().rev() {
+        let in_scope = match &binding.span.src {
+            InputSrc::Str(_) if binding.span.src == src => {
+                binding.span.end.byte_pos as usize <= start
+            }
+            InputSrc::Str(_) => true,
+            _ => false,
+        };
+        if !in_scope {
+            continue;
+        }
+        let Some(name) = binding.span.try_as_str(&asm.inputs, |s| s.to_string()) else {
+            continue;
+        };
+        if !name.to_lowercase().starts_with(&lower) || !seen.insert(name.clone()) {
+            continue;
+        }
+        let kind = match &binding.kind {
+            BindingKind::Const(_) => CompletionKind::Constant,
+            BindingKind::Func(_) => CompletionKind::Function,
+            BindingKind::Macro => CompletionKind::Modifier,
+            BindingKind::Module(_) => CompletionKind::Module,
+        };
+        completions.push(Completion {
+            text: name.clone(),
+            label: name,
+            kind,
+            signature: binding.kind.signature(),
+            doc: binding.comment.as_ref().map(|c| c.text.to_string()),
+            start,
+        });
+    }
+    completions.reverse();
+
+    // Primitives
+    for prim in Primitive::non_deprecated().filter(|p| p.name().starts_with(&lower)) {
+        let signature = match prim {
+            Primitive::Sys(op) => Some(Signature::new(op.args(), op.outputs())),
+            prim => prim.signature(),
+        };
+        completions.push(Completion {
+            text: (prim.glyph()).map_or_else(|| prim.name().to_string(), |c| c.to_string()),
+            label: prim.format().to_string(),
+            kind: CompletionKind::Primitive(prim),
+            signature,
+            doc: Some(prim.doc().short_text().to_string()),
+            start,
+        });
+    }
+
+    // Shadowable constants
+    for constant in &CONSTANTS {
+        if !constant.name.to_lowercase().starts_with(&lower) {
+            continue;
+        }
+        completions.push(Completion {
+            text: constant.name.into(),
+            label: constant.name.into(),
+            kind: CompletionKind::Constant,
+            signature: Some(Signature::new(0, 1)),
+            doc: Some(constant.doc.into()),
+            start,
+        });
+    }
+    completions
+}
+
 /// Metadata for code for use in IDE tools
 #[derive(Debug, Clone, Default)]
 pub struct CodeMeta {
@@ -1957,3 +2106,37 @@ mod server {
         value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn completions() {
+        use super::*;
+        let code = "AddOne ← +1\n# The answer\nAnswer ← 42\na";
+        let comps = completions(code, code.len());
+        let labels: Vec<_> = comps.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels[..2], ["AddOne", "Answer"]);
+        assert!(labels.contains(&"+ add"));
+        assert_eq!(comps[1].kind, CompletionKind::Constant);
+        assert_eq!(comps[1].doc.as_deref(), Some("The answer"));
+        assert_eq!(comps[1].start, code.len() - 1);
+        let add = (comps.iter())
+            .find(|c| c.kind == CompletionKind::Primitive(Primitive::Add))
+            .unwrap();
+        assert_eq!(add.text, "+");
+        assert_eq!(add.signature, Some(Signature::new(2, 1)));
+
+        // Bindings defined after the cursor are not in scope
+        let comps = completions("Ad\nAddOne ← +1", 2);
+        assert!(comps.iter().all(|c| c.label != "AddOne"));
+
+        let mut repl = Repl::new(Uiua::with_safe_sys(), Compiler::new()).unwrap();
+        repl.eval("Fizz ← 3");
+        let comps = repl.completions("+Fi", 3);
+        assert_eq!(comps[0].label, "Fizz");
+        assert_eq!(comps[0].start, 1);
+        assert!(repl.completions("+ ", 2).is_empty());
+    }
+}
@@ -6,7 +6,7 @@ use std::{
     io::{self, stderr, stdin, BufRead, Write},
     path::{Path, PathBuf},
     process::{exit, Child, Command, Stdio},
-    sync::mpsc::channel,
+    sync::{mpsc::channel, Arc},
     thread::sleep,
     time::Duration,
 };
@@ -19,10 +19,11 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use rustyline::{error::ReadlineError, DefaultEditor};
 use uiua::{
+    check_doc_examples, doc_examples,
     format::{format_file, format_str, FormatConfig, FormatConfigSource},
     lsp::BindingDocsKind,
-    Assembly, Compiler, NativeSys, PrimClass, RunMode, SpanKind, Uiua, UiuaError, UiuaResult,
-    Value,
+    Assembly, AssemblyCache, Compiler, CoverageReport, InputSrc, NativeSys, PreEvalMode, PrimClass,
+    Repl, RunMode, SpanKind, Uiua, UiuaError, UiuaResult, Value, Workspace,
 };
 
 fn main() {
@@ -64,7 +65,7 @@ fn run() -> UiuaResult {
     if let Some(asm) = &*uiua::stand::STAND_ASM {
         let mut rt = Uiua::with_native_sys().with_args(env::args().skip(1).collect());
         rt.run_asm(asm)?;
-        print_stack(&rt.take_stack(), true);
+        print_stack(&rt.take_stack(), true, &rt);
         return Ok(());
     }
     match App::try_parse() {
@@ -116,6 +117,11 @@ fn run() -> UiuaResult {
                 formatter_options,
                 time_instrs,
                 mode,
+                cache,
+                preview_images,
+                allow_http_imports,
+                #[cfg(feature = "gpu")]
+                gpu,
                 #[cfg(feature = "audio")]
                 audio_options,
                 args,
@@ -136,7 +142,12 @@ fn run() -> UiuaResult {
                 let mut rt = Uiua::with_native_sys()
                     .with_file_path(&path)
                     .with_args(args)
-                    .time_instrs(time_instrs);
+                    .time_instrs(time_instrs)
+                    .with_image_previews(preview_images);
+                #[cfg(feature = "gpu")]
+                if gpu {
+                    rt = rt.with_gpu(uiua::GpuOptions::default());
+                }
                 if path.extension().is_some_and(|ext| ext == "uasm") {
                     let uasm = match fs::read_to_string(&path) {
                         Ok(json) => json,
@@ -162,11 +173,29 @@ fn run() -> UiuaResult {
                         format_file(&path, &config)?;
                     }
                     let mode = mode.unwrap_or(RunMode::Normal);
-                    rt.compile_run(|comp| {
-                        comp.mode(mode).print_diagnostics(true).load_file(&path)
-                    })?;
+                    let cache = cache.then(AssemblyCache::in_user_dir).flatten();
+                    let cached = (cache.as_ref()).and_then(|cache| cache.load(&path, mode));
+                    let asm = if let Some(asm) = cached {
+                        asm
+                    } else {
+                        let mut comp = Compiler::with_backend(NativeSys);
+                        let res = (comp.mode(mode).allow_http_imports(allow_http_imports))
+                            .load_file(&path)
+                            .map(drop);
+                        let diagnostic_count = (comp.take_diagnostics().into_iter())
+                            .inspect(|diagnostic| eprintln!("{}", diagnostic.report()))
+                            .count();
+                        res?;
+                        let asm = comp.finish();
+                        // Only cache assemblies without diagnostics so they are shown every run
+                        if let Some(cache) = cache.filter(|_| diagnostic_count == 0) {
+                            _ = cache.store(&path, mode, &asm);
+                        }
+                        asm
+                    };
+                    rt.run_asm(asm)?;
                 }
-                print_stack(&rt.take_stack(), !no_color);
+                print_stack(&rt.take_stack(), !no_color, &rt);
             }
             App::Build { path, output } => {
                 let path = if let Some(path) = path {
@@ -205,11 +234,12 @@ fn run() -> UiuaResult {
                         .print_diagnostics(true)
                         .load_str(&code)
                 })?;
-                print_stack(&rt.take_stack(), !no_color);
+                print_stack(&rt.take_stack(), !no_color, &rt);
             }
             App::Test {
                 path,
                 formatter_options,
+                coverage,
                 args,
             } => {
                 let path = if let Some(path) = path {
@@ -228,12 +258,50 @@ fn run() -> UiuaResult {
                 format_file(&path, &config)?;
                 let mut rt = Uiua::with_native_sys()
                     .with_file_path(&path)
-                    .with_args(args);
+                    .with_args(args.clone());
+                if coverage {
+                    rt = rt.with_coverage();
+                }
                 rt.compile_run(|comp| {
+                    if coverage {
+                        comp.pre_eval_mode(PreEvalMode::Lazy);
+                    }
                     comp.mode(RunMode::Test)
                         .print_diagnostics(true)
-                        .load_file(path)
+                        .load_file(&path)
                 })?;
+                let mut report = rt.coverage();
+                let input = fs::read_to_string(&path)
+                    .map_err(|e| UiuaError::Load(path.clone(), e.into()))?;
+                if !doc_examples(&input).is_empty() {
+                    let mut rt = Uiua::with_native_sys()
+                        .with_file_path(&path)
+                        .with_args(args);
+                    if coverage {
+                        rt = rt.with_coverage();
+                    }
+                    let failures = check_doc_examples(&mut rt, &input, &path);
+                    if let (Some(report), Some(examples)) = (&mut report, rt.coverage()) {
+                        report.merge(examples);
+                    }
+                    if let Some(report) = &report {
+                        print_coverage(report, &path);
+                    }
+                    let failures = failures?;
+                    if !failures.is_empty() {
+                        for failure in &failures {
+                            eprintln!("{failure}");
+                        }
+                        eprintln!(
+                            "{} example{} failed",
+                            failures.len(),
+                            if failures.len() == 1 { "" } else { "s" }
+                        );
+                        exit(1);
+                    }
+                } else if let Some(report) = &report {
+                    print_coverage(report, &path);
+                }
                 println!("No failures!");
             }
             App::Watch {
@@ -260,6 +328,7 @@ fn run() -> UiuaResult {
             App::Lsp => uiua::lsp::run_language_server(),
             App::Repl {
                 file,
+                workspace,
                 formatter_options,
                 #[cfg(feature = "audio")]
                 audio_options,
@@ -279,7 +348,12 @@ fn run() -> UiuaResult {
                     compiler.load_file(file)?;
                     rt.run_compiler(&mut compiler)?;
                 }
-                repl(rt, compiler, true, config);
+                if let Some(path) = workspace.as_ref().filter(|path| path.exists()) {
+                    Workspace::load(path)
+                        .map_err(|e| UiuaError::Load(path.clone(), Arc::new(e)))?
+                        .bind(&mut compiler)?;
+                }
+                repl(Repl::new(rt, compiler)?, true, config, workspace);
             }
             App::Update { main, check } => update(main, check),
             #[cfg(feature = "stand")]
@@ -586,6 +660,15 @@ enum App {
         time_instrs: bool,
         #[clap(long, help = "Run the file in a specific mode")]
         mode: Option<RunMode>,
+        #[clap(long, help = "Cache the compiled file in the user's cache directory")]
+        cache: bool,
+        #[clap(long, help = "Show arrays that look like images as compact previews")]
+        preview_images: bool,
+        #[clap(long, help = "Allow importing modules over plain HTTP")]
+        allow_http_imports: bool,
+        #[cfg(feature = "gpu")]
+        #[clap(long, help = "Run large array operations on the GPU")]
+        gpu: bool,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
@@ -614,6 +697,8 @@ enum App {
         path: Option<PathBuf>,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
+        #[clap(long, help = "Report code that was not evaluated by the tests")]
+        coverage: bool,
         #[clap(trailing_var_arg = true, help = "Arguments to pass to the program")]
         args: Vec<String>,
     },
@@ -647,6 +732,11 @@ enum App {
     Repl {
         #[clap(help = "A Uiua file to run before the REPL starts")]
         file: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "A workspace file to restore bound values from and save them to on exit"
+        )]
+        workspace: Option<PathBuf>,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
         #[cfg(feature = "audio")]
@@ -815,10 +905,32 @@ fn format_multi_files(config: &FormatConfig) -> Result<(), UiuaError> {
     Ok(())
 }
 
-fn print_stack(stack: &[Value], color: bool) {
+fn print_coverage(report: &CoverageReport, path: &Path) {
+    let src = InputSrc::from(path);
+    let report = report.for_src(&src);
+    println!(
+        "{:.1}% of code was evaluated ({}/{} spans)",
+        report.fraction() * 100.0,
+        report.covered_count(),
+        report.spans.len()
+    );
+    let lines: Vec<_> = (report.lines(&src).into_iter())
+        .filter(|&(_, count)| count == 0)
+        .map(|(line, _)| line)
+        .collect();
+    if !lines.is_empty() {
+        println!("Lines that were never evaluated:");
+        for line in lines {
+            println!("  {}:{line}", path.display());
+        }
+    }
+}
+
+fn print_stack(stack: &[Value], color: bool, env: &Uiua) {
+    let show = |value: &Value| env.display_value(value);
     if stack.len() == 1 || !color {
         for value in stack {
-            println!("{}", value.show());
+            println!("{}", show(value));
         }
         return;
     }
@@ -837,11 +949,11 @@ fn print_stack(stack: &[Value], color: bool) {
             5 => (w, b, w),
             _ => unreachable!(),
         };
-        println!("{}", value.show().truecolor(r, g, b));
+        println!("{}", show(value).truecolor(r, g, b));
     }
 }
 
-fn repl(mut env: Uiua, mut compiler: Compiler, color: bool, config: FormatConfig) {
+fn repl(mut session: Repl, color: bool, config: FormatConfig, workspace: Option<PathBuf>) {
     let mut line_reader = DefaultEditor::new().expect("Failed to read from Stdin");
     let mut repl = || -> Result<bool, UiuaError> {
         let mut code = match line_reader.readline("» ") {
@@ -864,25 +976,13 @@ fn repl(mut env: Uiua, mut compiler: Compiler, color: bool, config: FormatConfig
             }
         }
 
-        print!("↪ ");
-        let backup = compiler.clone();
-        let res = compiler.load_str(&code).map(drop);
-        println!("{}", color_code(&code, &compiler));
-        let res = res.and_then(|()| env.run_asm(compiler.finish()));
-
-        print_stack(&env.take_stack(), color);
-        let mut asm = env.take_asm();
-        match res {
-            Ok(()) => {
-                asm.remove_top_level();
-                *compiler.assembly_mut() = asm;
-                Ok(true)
-            }
-            Err(e) => {
-                compiler = backup;
-                Err(e)
-            }
+        println!("↪ {}", color_code(&code, session.compiler()));
+        let output = session.eval(&code);
+        for diagnostic in output.diagnostics {
+            eprintln!("{}", diagnostic.report());
         }
+        print_stack(&output.values, color, session.env());
+        output.error.map_or(Ok(true), Err)
     };
 
     println!("Uiua {} (end with ctrl+C)\n", env!("CARGO_PKG_VERSION"));
@@ -895,6 +995,11 @@ fn repl(mut env: Uiua, mut compiler: Compiler, color: bool, config: FormatConfig
             }
         }
     }
+    if let Some(path) = workspace {
+        if let Err(e) = Workspace::new(session.compiler().assembly()).save(&path) {
+            eprintln!("Failed to save workspace to {}: {e}", path.display());
+        }
+    }
 }
 
 fn color_code(code: &str, compiler: &Compiler) -> String {
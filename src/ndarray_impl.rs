@@ -0,0 +1,111 @@
+//! Conversions between Uiua arrays and [`ndarray`] arrays
+
+use ecow::EcoVec;
+use ndarray::{ArrayD, ArrayViewD, IxDyn};
+
+use crate::{Array, ArrayValue, Shape, Value};
+
+impl<T: ArrayValue> From<ArrayD<T>> for Array<T> {
+    fn from(arr: ArrayD<T>) -> Self {
+        let shape = Shape::from(arr.shape());
+        let data: EcoVec<T> = if arr.is_standard_layout() {
+            // Standard layout data is contiguous, so it can be moved out directly
+            let len = arr.len();
+            let (mut vec, offset) = arr.into_raw_vec_and_offset();
+            let offset = offset.unwrap_or(0);
+            vec.truncate(offset + len);
+            vec.drain(offset..).collect()
+        } else {
+            // Iterating handles non-standard layouts
+            arr.iter().cloned().collect()
+        };
+        Array::new(shape, data)
+    }
+}
+
+impl<'a, T: ArrayValue> From<&'a Array<T>> for ArrayViewD<'a, T> {
+    fn from(arr: &'a Array<T>) -> Self {
+        ArrayViewD::from_shape(IxDyn(&arr.shape), arr.data.as_slice())
+            .expect("Array shape should match its data")
+    }
+}
+
+impl<T: ArrayValue> From<&Array<T>> for ArrayD<T> {
+    fn from(arr: &Array<T>) -> Self {
+        ArrayD::from_shape_vec(IxDyn(&arr.shape), arr.data.to_vec())
+            .expect("Array shape should match its data")
+    }
+}
+
+impl<T: ArrayValue> From<Array<T>> for ArrayD<T> {
+    fn from(arr: Array<T>) -> Self {
+        (&arr).into()
+    }
+}
+
+impl From<ArrayD<f64>> for Value {
+    fn from(arr: ArrayD<f64>) -> Self {
+        Array::from(arr).into()
+    }
+}
+
+impl TryFrom<&Value> for ArrayD<f64> {
+    type Error = String;
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Num(arr) => Ok(arr.into()),
+            Value::Byte(arr) => Ok(arr.convert_ref::<f64>().into()),
+            value => Err(format!(
+                "Cannot convert {} array to an ndarray of numbers",
+                value.type_name()
+            )),
+        }
+    }
+}
+
+impl TryFrom<Value> for ArrayD<f64> {
+    type Error = String;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        (&value).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ndarray_conversion() {
+        use ndarray::{ArrayD, ArrayViewD, IxDyn};
+        let mut env = Uiua::with_safe_sys();
+        env.run_str("↯2_3⇡6").unwrap();
+        let original = env.pop("array").unwrap();
+        let nd = ArrayD::<f64>::try_from(&original).unwrap();
+        assert_eq!(nd.shape(), &[2, 3]);
+        assert_eq!(nd[IxDyn(&[1, 0])], 3.0);
+
+        // Views borrow the array's data
+        let nums = Array::<f64>::new([2, 3], [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        let view = ArrayViewD::from(&nums);
+        assert_eq!(view.shape(), &[2, 3]);
+        assert_eq!(view.as_ptr(), nums.data.as_ptr());
+        assert_eq!(Array::from(view.to_owned()), nums);
+
+        // Sliced standard layout arrays keep only their own elements
+        let tail = nd.clone().slice_move(ndarray::s![1.., ..]).into_dyn();
+        assert!(tail.is_standard_layout());
+        assert_eq!(Array::from(tail).data.as_slice(), [3.0, 4.0, 5.0]);
+
+        // Non-standard layouts keep their logical order
+        let arr = Array::from(nd.reversed_axes());
+        assert_eq!(arr.shape, [3, 2]);
+        assert_eq!(arr.data.as_slice(), [0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+        env.push(Value::from(ArrayD::from(arr)));
+        env.run_str("⍉").unwrap();
+        assert_eq!(env.pop("array").unwrap(), original);
+
+        let bytes = ArrayD::<f64>::try_from(Value::from([1u8, 0])).unwrap();
+        assert_eq!(bytes.as_slice().unwrap(), [1.0, 0.0]);
+        assert!(ArrayD::<f64>::try_from(Value::from("hi")).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Compact previews of image-like arrays
+
+use crate::{grid_fmt::GridFmt, Value};
+
+/// The minimum height and width of an array for it to be previewed as an image
+///
+/// This matches the threshold for showing arrays as images on the website.
+pub const MIN_IMAGE_PREVIEW_DIM: usize = 30;
+
+/// The maximum width and height of an image preview's thumbnail, in braille dots
+const MAX_THUMBNAIL_DOTS: usize = 64;
+
+impl Value {
+    /// Check if the value looks like an image
+    ///
+    /// Image-like values are rank 2, or rank 3 with at most 4 channels.
+    /// They must be at least [`MIN_IMAGE_PREVIEW_DIM`] tall and wide,
+    /// and they must be numbers between 0 and 1 or bytes.
+    /// Boolean arrays, including byte arrays of only 0s and 1s, are masks rather than images.
+    pub fn is_image_like(&self) -> bool {
+        let (height, width) = match *self.shape().dims() {
+            [h, w] | [h, w, 1..=4] => (h, w),
+            _ => return false,
+        };
+        if height < MIN_IMAGE_PREVIEW_DIM
+            || width < MIN_IMAGE_PREVIEW_DIM
+            || self.is_map()
+            || self.meta().flags.is_boolean()
+        {
+            return false;
+        }
+        match self {
+            Value::Num(arr) => arr.data.iter().all(|n| (0.0..=1.0).contains(n)),
+            Value::Byte(arr) => arr.data.iter().any(|&b| b > 1),
+            _ => false,
+        }
+    }
+    /// Get a compact preview of an image-like value
+    ///
+    /// The preview has the image's dimensions, its minimum and maximum values,
+    /// and a downsampled braille thumbnail.
+    /// Returns `None` if the value is not [image-like](Value::is_image_like).
+    pub fn image_preview(&self) -> Option<String> {
+        let (width, height, pixels) = self.image_pixels()?;
+        let channels = self.shape().get(2).copied().unwrap_or(1);
+        let (min, max) = match self {
+            Value::Num(arr) => (arr.data.iter())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), &n| {
+                    (a.min(n), b.max(n))
+                }),
+            Value::Byte(arr) => (arr.data.iter())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), &n| {
+                    (a.min(n as f64), b.max(n as f64))
+                }),
+            _ => return None,
+        };
+
+        // Header
+        let mut s = String::new();
+        if let Some(label) = &self.meta().label {
+            s.push_str(label);
+            s.push_str(": ");
+        }
+        let kind = match channels {
+            1 => "grayscale",
+            2 => "grayscale+alpha",
+            3 => "rgb",
+            _ => "rgba",
+        };
+        let round = |n: f64| ((n * 100.0).round() / 100.0).grid_string(false);
+        s.push_str(&format!(
+            "{height}×{width} {kind} image, min {}, max {}",
+            round(min),
+            round(max)
+        ));
+
+        // Thumbnail
+        let luma = |row: usize, col: usize| {
+            let [r, g, b, _] = pixels[row * width + col];
+            0.299 * r + 0.587 * g + 0.114 * b
+        };
+        let step = (width.max(height) as f64 / MAX_THUMBNAIL_DOTS as f64).max(1.0);
+        let dots_w = (width as f64 / step).ceil() as usize;
+        let dots_h = (height as f64 / step).ceil() as usize;
+        let dot = |x: usize, y: usize| {
+            if x >= dots_w || y >= dots_h {
+                return false;
+            }
+            let rows = (y as f64 * step) as usize..(((y + 1) as f64 * step) as usize).min(height);
+            let cols = (x as f64 * step) as usize..(((x + 1) as f64 * step) as usize).min(width);
+            let count = (rows.len() * cols.len()).max(1);
+            let sum: f64 = (rows.flat_map(|r| cols.clone().map(move |c| (r, c))))
+                .map(|(r, c)| luma(r, c))
+                .sum();
+            sum / count as f64 >= 0.5
+        };
+        const DOT_BITS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+        for cell_y in 0..dots_h.div_ceil(4) {
+            s.push('\n');
+            for cell_x in 0..dots_w.div_ceil(2) {
+                let mut bits = 0;
+                for (dy, row_bits) in DOT_BITS.iter().enumerate() {
+                    for (dx, bit) in row_bits.iter().enumerate() {
+                        if dot(cell_x * 2 + dx, cell_y * 4 + dy) {
+                            bits |= bit;
+                        }
+                    }
+                }
+                s.push(char::from_u32(0x2800 + bits).unwrap());
+            }
+        }
+        Some(s)
+    }
+    /// Get the width, height, and RGBA pixels of an image-like value
+    ///
+    /// Channels are between 0 and 1.
+    pub(crate) fn image_pixels(&self) -> Option<(usize, usize, Vec<[f64; 4]>)> {
+        if !self.is_image_like() {
+            return None;
+        }
+        let shape = self.shape();
+        let (height, width) = (shape[0], shape[1]);
+        let channels = shape.get(2).copied().unwrap_or(1);
+        let data: Vec<f64> = match self {
+            Value::Num(arr) => arr.data.to_vec(),
+            Value::Byte(arr) => arr.data.iter().map(|&b| b as f64 / 255.0).collect(),
+            _ => return None,
+        };
+        let pixels = (data.chunks_exact(channels))
+            .map(|px| match *px {
+                [l] => [l, l, l, 1.0],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 1.0],
+                [r, g, b, a, ..] => [r, g, b, a],
+                [] => [0.0; 4],
+            })
+            .collect();
+        Some((width, height, pixels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn image_preview() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str("÷255 ↯40_60_3 ⇡256\n↯40_40 200\n↯29_40 0.5\n↯40_40 2.5\n↯40_40_5 0")
+            .unwrap();
+        let [rgb, bytes, small, large, channels] = &env.take_stack()[..] else {
+            panic!("expected 5 values")
+        };
+        let mask = &Value::from(Array::<u8>::new([40, 40], [0, 1].repeat(800).as_slice()));
+        assert!(rgb.is_image_like());
+        assert!(bytes.is_image_like());
+        assert!(!mask.is_image_like());
+        assert!(!small.is_image_like());
+        assert!(!large.is_image_like());
+        assert!(!channels.is_image_like());
+
+        // Previews are only displayed when enabled
+        assert_eq!(env.display_value(rgb), rgb.show());
+        assert!(rgb.show().starts_with('╭'));
+        let env = env.with_image_previews(true);
+        let preview = env.display_value(rgb);
+        let mut lines = preview.lines();
+        assert_eq!(lines.next(), Some("40×60 rgb image, min 0, max 1"));
+        // 60 pixels fit in 30 braille cells, and 40 pixels fit in 10 rows
+        let thumbnail: Vec<&str> = lines.collect();
+        assert_eq!(thumbnail.len(), 10);
+        assert!(thumbnail.iter().all(|line| line.chars().count() == 30));
+        assert_eq!(env.display_value(small), small.show());
+        assert_eq!(env.display_value(mask), mask.show());
+    }
+}
@@ -481,6 +481,17 @@ primitive!(
     /// ex: ⁅1.5
     /// ex: ⁅[0.1 π 2 9.9 7.5]
     (1, Round, MonadicPervasive, ("round", '⁅')),
+    /// Flip the bits of integers
+    ///
+    /// Integers are treated as signed, so the result does not depend on how the array is stored.
+    /// ex: # Experimental!
+    ///   : bitnot [0 1 5 ¯1]
+    /// ex: # Experimental!
+    ///   : bitnot utf "ab"
+    /// Numbers that are not integers give `NaN`.
+    /// ex: # Experimental!
+    ///   : bitnot 1.5
+    (1, BitNot, MonadicPervasive, "bitnot"),
     /// Compare for equality
     ///
     /// ex: =1 2
@@ -668,6 +679,63 @@ primitive!(
     /// ex: [°ℂ] i
     /// ex: [°ℂ] ×. ℂ3 4
     (2, Complex, DyadicPervasive, ("complex", 'ℂ')),
+    /// Bitwise AND two arrays of integers
+    ///
+    /// If both arrays are bytes, the result is bytes.
+    /// ex: # Experimental!
+    ///   : bitand 12 10
+    /// ex: # Experimental!
+    ///   : bitand 15 utf "abc"
+    /// Numbers that are not integers give `NaN`.
+    /// ex: # Experimental!
+    ///   : bitand 1 [1 2.5 3]
+    (2, BitAnd, DyadicPervasive, "bitand"),
+    /// Bitwise OR two arrays of integers
+    ///
+    /// If both arrays are bytes, the result is bytes.
+    /// ex: # Experimental!
+    ///   : bitor 12 10
+    /// ex: # Experimental!
+    ///   : bitor 1 [0 2 4 6]
+    (2, BitOr, DyadicPervasive, "bitor"),
+    /// Bitwise XOR two arrays of integers
+    ///
+    /// If both arrays are bytes, the result is bytes.
+    /// ex: # Experimental!
+    ///   : bitxor 12 10
+    /// ex: # Experimental!
+    ///   : °utf bitxor 32 utf "Hello"
+    (2, BitXor, DyadicPervasive, "bitxor"),
+    /// Shift the bits of integers left
+    ///
+    /// The first argument is the number of bits to shift by.
+    /// ex: # Experimental!
+    ///   : shiftleft 3 [1 2 5]
+    /// The result is always numbers, so shifting bytes never overflows.
+    /// ex: # Experimental!
+    ///   : shiftleft 8 utf "ab"
+    /// A negative shift shifts right.
+    /// ex: # Experimental!
+    ///   : shiftleft ¯1 [4 5 ¯5]
+    ///
+    /// See also: [shiftright]
+    (2, ShiftLeft, DyadicPervasive, "shiftleft"),
+    /// Shift the bits of integers right
+    ///
+    /// The first argument is the number of bits to shift by.
+    /// Shifts are arithmetic, so negative numbers round down.
+    /// ex: # Experimental!
+    ///   : shiftright 1 [4 5 ¯5]
+    /// If both arrays are bytes, the result is bytes.
+    /// This is useful for parsing binary formats.
+    /// ex: # Experimental!
+    ///   : bitand 15 shiftright [0 4] [171]
+    /// A negative shift shifts left.
+    /// ex: # Experimental!
+    ///   : shiftright ¯2 3
+    ///
+    /// See also: [shiftleft]
+    (2, ShiftRight, DyadicPervasive, "shiftright"),
     /// Get the number of rows in an array
     ///
     /// ex: ⧻5
@@ -800,6 +868,9 @@ primitive!(
     /// ex: ⊏⍏⌵.6_2_7_0_¯1_5
     ///
     /// [first][rise] and [first][reverse][rise] are optimized in the interpreter to be O(n).
+    ///
+    /// [rows][rise] grades along a deeper axis. This is optimized to not actually call [rise] on each row.
+    /// ex: ≡⍏ [3_1_2 2_3_1]
     (1, Rise, MonadicArray, ("rise", '⍏')),
     /// Get the indices into an array if it were sorted descending
     ///
@@ -813,6 +884,9 @@ primitive!(
     /// ex: ⊏⍖⌵.6_2_7_0_¯1_5
     ///
     /// [first][fall] and [first][reverse][fall] are optimized in the interpreter to be O(n).
+    ///
+    /// [rows][fall] grades along a deeper axis. This is optimized to not actually call [fall] on each row.
+    /// ex: ≡⍖ [3_1_2 2_3_1]
     (1, Fall, MonadicArray, ("fall", '⍖')),
     /// Get indices where array values are not equal to zero
     ///
@@ -1150,6 +1224,8 @@ primitive!(
     /// ex: ⬚0↻ 2 [1 2 3 4 5]
     ///   :   ↻ 2 [1 2 3 4 5]
     /// ex: ⬚0↻ 1_2 .↯4_5⇡20
+    /// [under][fill][rotate] restores the elements that were shifted out.
+    /// ex: ⍜(⬚0↻1)(×10) [1 2 3 4]
     ///
     /// [rotate] works through boxes.
     /// ex: ↻1 □[1 2 3 4]
@@ -1172,6 +1248,11 @@ primitive!(
     /// Usually, [windows] "materialzes" the windows. This means that the windows are copied into a new array. While this is very general, it can be slow and wasteful if you end up immediately reducing the windows.
     /// For this reason, the pattern `rows``reduce``F``windows` is optimized for scalar window sizes to [reduce] windows as they are generated.
     /// ex: ≡/+◫ 5 [1 8 2 9 3 0 2 4 4 5 1] # Fast!
+    ///
+    /// [un][windows] reconstructs an array from its windows. Overlapping windows must agree.
+    /// ex: °(◫3) ◫3 [1 2 3 4 5]
+    /// [under][windows] lets you modify the windows, as long as they still agree where they overlap.
+    /// ex: ⍜(◫2)≡⇌ [1 2 1 2]
     (2, Windows, DyadicArray, ("windows", '◫')),
     /// Discard or copy some rows of an array
     ///
@@ -1212,6 +1293,10 @@ primitive!(
     /// ex: ⌕ [1_2 2_0] . ↯4_4⇡3
     ///
     /// If you want to mark the entire occurence, use [mask] instead.
+    ///
+    /// [under][find] passes the non-overlapping occurences to the function as the rows of an array. The modified occurences are put back in place.
+    /// ex: ⍜⌕≡⌵ "ab" "abracadabra"
+    /// ex: ⍜⌕(×0) 1_2 [1 2 3 1 2 1]
     (2, Find, DyadicArray, ("find", '⌕')),
     /// Mask the occurences of one array in another
     ///
@@ -1388,6 +1473,11 @@ primitive!(
     /// [scan]ning with [add] and then using [group] can split by a delimiter while keeping the delimiter.
     /// ex: ⊕□\+=@    . "Everyday man's on the block"
     ///   : ⊕□\+↻¯1=@ . "Everyday man's on the block"
+    ///
+    /// [rows][scan] scans along a deeper axis.
+    /// ex: ≡\+ [1_2_3 4_5_6]
+    /// When the function is an arithmetic, comparison, [minimum], or [maximum] primitive, this is optimized to not actually call [scan] on each row.
+    /// Long [scan]s with [add], [minimum], or [maximum] are done in parallel when the result is exact.
     (1[1], Scan, AggregatingModifier, ("scan", '\\')),
     /// Apply a function to each element of an array or arrays
     ///
@@ -1851,6 +1941,107 @@ primitive!(
     ///   : c ← (°□⊡2°◌)
     ///   : ⬚{⊙⊙∘}(×b+c×a a) 2 3 4
     ([2], Fill, OtherModifier, ("fill", '⬚')),
+    /// Set a function that computes each fill value
+    ///
+    /// [fillby] is like [fill], but its first function is called for each element that needs to be filled.
+    /// It is supported by [take], [rotate], [reshape], and [find].
+    /// ex: # Experimental!
+    ///   : fillby(10) ↙5 [1 2 3]
+    /// If the function takes an argument, it is passed the index of the filled element.
+    /// ex: # Experimental!
+    ///   : fillby⊢↙6 [1 2 3]
+    /// ex: # Experimental!
+    ///   : fillby/+↙3_4 [1_2 3_4]
+    /// If the function takes a second argument, it is passed the value of the nearest element that was not filled.
+    /// This can be used to repeat the edges of an array.
+    /// ex: # Experimental!
+    ///   : fillby⋅∘↙5 [1 2 3]
+    /// ex: # Experimental!
+    ///   : fillby⋅∘↙¯3_4 [1_2 3_4]
+    /// ex: # Experimental!
+    ///   : fillby⋅∘↻2 [1 2 3 4 5]
+    /// ex: # Experimental!
+    ///   : fillby⋅∘↯3_3 [1 2 3 4]
+    ([2], FillBy, OtherModifier, "fillby"),
+    /// Set how to pad each axis when indexing past its bounds
+    ///
+    /// The first function should return a padding mode or a list of modes, one for each axis.
+    /// The last mode applies to all remaining axes.
+    /// The modes are:
+    /// - `"fill"` - Use the [fill] value
+    /// - `"clamp"` - Repeat the nearest edge element
+    /// - `"wrap"` - Wrap around to the other side
+    /// - `"reflect"` - Mirror the elements, including the edge element
+    /// [padmode] is supported by [take], [windows], [select], and [pick].
+    /// ex: # Experimental!
+    ///   : padmode"clamp"↙6 [1 2 3]
+    ///   : padmode"wrap"↙6 [1 2 3]
+    ///   : padmode"reflect"↙¯6 [1 2 3]
+    /// The `"fill"` mode uses the fill value that was set outside of [padmode].
+    /// ex: # Experimental!
+    ///   : ⬚0padmode{"fill" "wrap"}↙3_5 [1_2 3_4]
+    /// With [padmode], each windowed axis is padded so that there is a window centered on each element.
+    /// This is useful for convolution kernels.
+    /// ex: # Experimental!
+    ///   : padmode"clamp"(≡/+◫3) [1 2 3 4 5]
+    /// ex: # Experimental!
+    ///   : padmode{"clamp" "wrap"}◫2_3 ↯3_4⇡12
+    /// With [padmode], [select] and [pick] resolve every out-of-bounds index with the mode of its axis.
+    /// Negative indices do not count from the end. They are out of bounds like any other.
+    /// ex: # Experimental!
+    ///   : padmode"clamp"⊏[¯2 0 4] [1 2 3]
+    ///   : padmode"wrap"⊏[¯1 3 5] [1 2 3]
+    /// ex: # Experimental!
+    ///   : padmode{"clamp" "wrap"}⊡[5_¯1 ¯1_4] ↯3_4⇡12
+    /// ex: # Experimental!
+    ///   : ⬚0padmode"fill"⊏[¯1 0 3] [1 2 3]
+    ([2], PadMode, OtherModifier, "padmode"),
+    /// Call a function with reductions and statistics skipping `NaN`s
+    ///
+    /// Normally, `NaN`s propagate through [reduce] and statistical functions.
+    /// ex: /+ [1 NaN 2 3]
+    /// With [skipnan], they are ignored.
+    /// ex: # Experimental!
+    ///   : skipnan/+ [1 NaN 2 3]
+    /// ex: # Experimental!
+    ///   : skipnan(≡/↥) [1_NaN_3 NaN_5_2]
+    /// If all the values being reduced are `NaN`, the result is the function's identity, as if the list were empty.
+    /// ex: # Experimental!
+    ///   : skipnan/+ [NaN NaN]
+    /// [mean], [variance], [stddev], [median], and [quantile] also skip `NaN`s.
+    /// ex: # Experimental!
+    ///   : skipnan mean [1 NaN 2 3]
+    ///   : skipnan median [1 NaN 2 3]
+    /// The `NaN`s are skipped as the values are reduced, so no masked copy of the array is made.
+    ([1], SkipNan, OtherModifier, "skipnan"),
+    /// Call a function with pervasive functions descending into boxes on both sides
+    ///
+    /// Most pervasive functions already descend into boxes.
+    /// ex: + {1 [2 3]} {10 [20 30]}
+    /// However, comparing two boxes normally compares their contents as whole values.
+    /// ex: = {1 [2 3]} {1 [2 4]}
+    /// With [deepbox], the comparison is applied to the boxes' contents instead, recursively zipping the boxes' structure.
+    /// ex: # Experimental!
+    ///   : deepbox= {1 [2 3]} {1 [2 4]}
+    /// ex: # Experimental!
+    ///   : deepbox< {1 {2 [3 4]}} {0 {5 [3 5]}}
+    /// A scalar box is paired with every box on the other side, rather than being unboxed.
+    /// ex: + □{1 2} {10 20}
+    /// ex: # Experimental!
+    ///   : deepbox+ □{1 2} {10 20}
+    ([1], DeepBox, OtherModifier, "deepbox"),
+    /// Call a function with [interp] and [resample] using cubic interpolation
+    ///
+    /// Normally, [interp] and [resample] draw straight lines between samples.
+    /// ex: # Experimental!
+    ///   : interp [0_1_2_3 0_1_0_1] [0.5 1.5]
+    /// With [cubic], they draw a smooth curve instead.
+    /// ex: # Experimental!
+    ///   : cubic interp [0_1_2_3 0_1_0_1] [0.5 1.5]
+    /// ex: # Experimental!
+    ///   : cubic resample [0.5 1.5] [0 1 0 1]
+    /// The curve passes through every sample. Its slope at each sample is the average of the slopes of the lines to the neighboring samples.
+    ([1], Cubic, OtherModifier, "cubic"),
     /// Call a function and catch errors
     ///
     /// If the first function errors, the second function is called with the original arguments and the error value.
@@ -1896,9 +2087,25 @@ primitive!(
     ///
     /// Errors thrown by [assert] can be caught with [try].
     (2(0), Assert, Misc, ("assert", '⍤'), Impure),
+    /// Throw an error showing where two arrays differ if they do not [match]
+    ///
+    /// Expects the expected value and then the actual value.
+    /// ex: # Experimental!
+    ///   : expect [1 2 3] +1 [0 1 2]
+    /// If the values do not match, the error lists the first few differing coordinates with both elements, rather than the whole arrays.
+    /// ex! # Experimental!
+    ///   : expect [1 2 3] +1 [0 1 1]
+    /// Differences in type and shape are reported too, descending into boxes.
+    /// ex! # Experimental!
+    ///   : expect {1 "abc"} {1 "ab"}
+    ///
+    /// Errors thrown by [expect] can be caught with [try].
+    ///
+    /// See also: [assert], [match]
+    (2(0), Expect, Misc, "expect", Impure),
     /// Generate a random number in the range `[0, 1)`
     ///
-    /// If you need a seeded random number, use [gen].
+    /// If you need a seeded random number, use [gen] or [seed].
     ///
     /// ex: ⚂
     /// ex: [⚂⚂⚂]
@@ -2017,6 +2224,118 @@ primitive!(
     /// ex: deal⚂ [1 2 3 4 5]
     /// ex: deal⚂ [1_2 3_4 5_6 7_8]
     (2, Deal, Misc, "deal", Impure),
+    /// Seed the interpreter's random number generator
+    ///
+    /// After seeding, [random], [uniform], [normal], [permutation], [choice], and [sample] produce the same values every time.
+    /// ex: # Experimental!
+    ///   : [⚂⚂⚂] seed 42
+    ///   : [⚂⚂⚂] seed 42
+    /// ex: # Experimental!
+    ///   : uniform 4 seed 0
+    ///   : uniform 4 seed 0
+    /// The generator and the way each function uses it are stable across versions of Uiua.
+    ///
+    /// Without [seed], the generator is seeded randomly when the program starts.
+    /// Spawned threads get their own generators, seeded from the generator of the thread that spawned them.
+    (1(0), Seed, Misc, "seed", Impure),
+    /// Generate an array of random numbers between 0 and 1
+    ///
+    /// The argument is the shape of the array.
+    /// ex: # Experimental!
+    ///   : uniform 5
+    /// ex: # Experimental!
+    ///   : uniform 2_3
+    /// This is much faster than calling [random] in a loop.
+    /// Use [multiply] and [floor] to generate random integers in a range.
+    /// ex: # Experimental!
+    ///   : ⌊×10 uniform 10
+    ///
+    /// See also: [normal], [seed]
+    (1, Uniform, Misc, "uniform", Impure),
+    /// Generate an array of random numbers from the standard normal distribution
+    ///
+    /// The argument is the shape of the array.
+    /// The numbers have a mean of 0 and a standard deviation of 1.
+    /// ex: # Experimental!
+    ///   : normal 5
+    /// Use [multiply] and [add] to change the standard deviation and mean.
+    /// ex: # Experimental!
+    ///   : +100×15 normal 2_3
+    ///
+    /// See also: [uniform], [seed]
+    (1, Normal, Misc, "normal", Impure),
+    /// Generate a random permutation of [range] of a number
+    ///
+    /// ex: # Experimental!
+    ///   : permutation 10
+    /// [select] with a permutation to shuffle the rows of an array.
+    /// ex: # Experimental!
+    ///   : ⊏permutation⧻. "Uiua!"
+    ///
+    /// See also: [sample], [shuffle], [seed]
+    (1, Permutation, Misc, "permutation", Impure),
+    /// Randomly reorder the rows of an array
+    ///
+    /// ex: # Experimental!
+    ///   : shuffle "Uiua!"
+    /// ex: # Experimental!
+    ///   : shuffle ↯4_2⇡8
+    /// [shuffle] gives the same result as [select]ing with a [permutation] of the same length.
+    /// ex: # Experimental!
+    ///   : ⊏permutation⧻. ⇡10 seed 1
+    ///   : shuffle ⇡10 seed 1
+    /// [under][shuffle] puts the rows back in their original order.
+    /// ex: # Experimental!
+    ///   : ⍜shuffle(\+) [1 2 3 4 5]
+    ///
+    /// See also: [permute], [permutation]
+    (1, Shuffle, Misc, "shuffle", Impure),
+    /// Reorder the rows of an array with a permutation
+    ///
+    /// The first argument is a permutation of [range] of the number of rows. Row `i` of the result is the row of the array at the `i`th index of the permutation.
+    /// ex: # Experimental!
+    ///   : permute [2 0 1] "abc"
+    /// Unlike [select], the indices must be a permutation. Every row must be used exactly once.
+    /// ex! # Experimental!
+    ///   : permute [0 0 1] "abc"
+    /// [un][permute] applies the inverse permutation.
+    /// ex: # Experimental!
+    ///   : °(permute [2 0 1]) "cab"
+    /// ex: # Experimental!
+    ///   : ⍜(permute [2 0 1])(+⇡3) [10 20 30]
+    ///
+    /// See also: [shuffle], [select]
+    (2, Permute, Misc, "permute"),
+    /// Randomly choose rows of an array with replacement
+    ///
+    /// The first argument is the number of rows to choose. The second is the weights of the rows. The third is the array.
+    /// A scalar weight chooses every row with equal probability.
+    /// ex: # Experimental!
+    ///   : choice 10 1 "abc"
+    /// A list of weights gives each row a probability proportional to its weight.
+    /// ex: # Experimental!
+    ///   : choice 10 [8 1 1] "abc"
+    /// Weighted choices use the alias method, so each row is chosen in constant time no matter how many rows there are.
+    /// ex: # Experimental!
+    ///   : ⊕⧻. choice 1000 [1 2 3 4] ⇡4
+    ///
+    /// See also: [sample], [seed]
+    (3, Choice, Misc, "choice", Impure),
+    /// Randomly choose distinct rows of an array
+    ///
+    /// The first argument is the number of rows to choose. The second is the weights of the rows. The third is the array.
+    /// A scalar weight chooses every row with equal probability.
+    /// ex: # Experimental!
+    ///   : sample 3 1 "abcdefg"
+    /// A list of weights makes rows with larger weights more likely to be chosen first. Rows with a weight of `0` are never chosen.
+    /// ex: # Experimental!
+    ///   : sample 3 [1 1 0 1 100] "abcde"
+    /// It is an error to ask for more rows than there are.
+    /// ex! # Experimental!
+    ///   : sample 5 1 "abc"
+    ///
+    /// See also: [choice], [permutation], [seed]
+    (3, Sample, Misc, "sample", Impure),
     /// Match a regex pattern
     ///
     /// Returns a rank-2 array of [box]ed strings, with one string per matching group and one row per match
@@ -2045,6 +2364,97 @@ primitive!(
     /// ex: -@\0 "👩🏽‍👩🏻‍👦🏻‍👧🏽"
     /// ex: utf "👩🏽‍👩🏻‍👦🏻‍👧🏽"
     (1, Utf, Encoding, "utf"),
+    /// Encode bytes or a string as base64
+    ///
+    /// Strings are encoded as UTF-8 first.
+    /// ex: # Experimental!
+    ///   : basesixtyfour "Hello, World!"
+    /// ex: # Experimental!
+    ///   : basesixtyfour [0 255 127]
+    /// You can use [un] to decode base64 back into bytes.
+    /// ex: # Experimental!
+    ///   : °basesixtyfour "SGVsbG8sIFdvcmxkIQ=="
+    /// ex: # Experimental!
+    ///   : °utf °basesixtyfour "SGVsbG8sIFdvcmxkIQ=="
+    /// Boxed arrays are encoded individually.
+    /// ex: # Experimental!
+    ///   : basesixtyfour {"a" "bc" "def"}
+    (1, Base64, Encoding, "basesixtyfour"),
+    /// Encode bytes or a string as hexadecimal
+    ///
+    /// Strings are encoded as UTF-8 first.
+    /// ex: # Experimental!
+    ///   : hex [0 15 16 255]
+    /// ex: # Experimental!
+    ///   : hex "hi!"
+    /// You can use [un] to decode hexadecimal back into bytes. Both upper and lower case digits are allowed.
+    /// ex: # Experimental!
+    ///   : °hex "00ff10Ab"
+    (1, Hex, Encoding, "hex"),
+    /// Percent-encode a string for use in a URL
+    ///
+    /// Every byte except letters, digits, and `-_.~` is encoded.
+    /// ex: # Experimental!
+    ///   : url "a b&c=d/é"
+    /// You can use [un] to decode a percent-encoded string.
+    /// ex: # Experimental!
+    ///   : °url "a%20b%26c%3Dd%2F%C3%A9"
+    (1, Url, Encoding, "url"),
+    /// Compress bytes or a string
+    ///
+    /// The first argument is the format, which is one of `"gzip"`, `"zlib"`, `"deflate"`, or `"zstd"`.
+    /// `"zstd"` is only available if the interpreter was built with the `zstd` feature.
+    /// Strings are encoded as UTF-8 first.
+    /// ex: # Experimental!
+    ///   : compress "gzip" ▽10 "abc"
+    /// ex: # Experimental!
+    ///   : ⧻compress "zlib" ▽1000 "abc"
+    /// Boxed arrays are compressed individually.
+    /// ex: # Experimental!
+    ///   : compress "deflate" {"abc" "abcabcabc"}
+    ///
+    /// See also: [decompress]
+    (2, Compress, Encoding, "compress"),
+    /// Decompress bytes
+    ///
+    /// The first argument is the format, which is one of `"gzip"`, `"zlib"`, `"deflate"`, or `"zstd"`.
+    /// ex: # Experimental!
+    ///   : °utf decompress "gzip" compress "gzip" "Hello, World!"
+    /// This is useful for reading compressed files.
+    /// ex! # Experimental!
+    ///   : °utf decompress "gzip" &frab "example.txt.gz"
+    ///
+    /// See also: [compress]
+    (2, Decompress, Encoding, "decompress"),
+    /// Hash bytes or a string
+    ///
+    /// The first argument is the hash function, which is one of `"sha256"`, `"md5"`, or `"crc32"`.
+    /// The digest is returned as bytes. Strings are encoded as UTF-8 first.
+    /// ex: # Experimental!
+    ///   : hash "sha256" "abc"
+    /// Use [hex] to get the digest as a hexadecimal string.
+    /// ex: # Experimental!
+    ///   : hex hash "md5" "abc"
+    /// ex: # Experimental!
+    ///   : hex hash "crc32" "123456789"
+    /// Boxed arrays are hashed individually.
+    /// ex: # Experimental!
+    ///   : ≡hex hash "sha256" {"a" "bc"}
+    ///
+    /// See also: [rowhash]
+    (2, HashBytes, Encoding, "hash"),
+    /// Get a fast hash of each row of an array
+    ///
+    /// The hash is not cryptographic, but rows that [match] always have the same hash.
+    /// Hashes are consistent within a single version of the interpreter, but may change between versions.
+    /// ex: # Experimental!
+    ///   : rowhash [1_2 3_4 1_2]
+    /// Hashes can be used as keys for grouping large rows.
+    /// ex: # Experimental!
+    ///   : ⊛ rowhash ["ab" "cd" "ab"]
+    ///
+    /// See also: [hash]
+    (1, RowHash, Misc, "rowhash"),
     /// Generate a unique tag
     ///
     /// Tags are just numbers and are unique across multiple threads, but not across multiple runs.
@@ -2057,6 +2467,7 @@ primitive!(
     /// `1` indicates a complex array.
     /// `2` indicates a character array.
     /// `3` indicates a box array.
+    /// `4` indicates a datetime array.
     /// ex: type 5
     /// ex: type i
     /// ex: type "hello"
@@ -2070,6 +2481,703 @@ primitive!(
     /// [under][now] can be used to time a function.
     /// ex: ⍜now(5&sl1)
     (0, Now, Misc, "now", Impure),
+    /// Get the time in nanoseconds from a monotonic clock
+    ///
+    /// Unlike [now], the clock never jumps when the system time is changed, so it is better for measuring how long code takes.
+    /// The time is measured from an arbitrary point, so only differences between times are meaningful.
+    /// ex: # Experimental!
+    ///   : clock
+    /// [under][clock] can be used to time a function in nanoseconds.
+    /// ex: # Experimental!
+    ///   : ⍜clock(/+⇡1e5)
+    ///
+    /// See also: [timeit]
+    (0, Clock, Misc, "clock", Impure),
+    /// Call a function and get how long it took
+    ///
+    /// The time in seconds is pushed on top of the function's outputs.
+    /// It is measured with the same monotonic clock as [clock].
+    /// ex: # Experimental!
+    ///   : timeit(/+⇡) 1e5
+    /// To get the average of several runs, use [repeat].
+    /// ex: # Experimental!
+    ///   : ÷10 /+ [⍥(◌timeit(/+⇡) 1e5)10]
+    ([1], TimeIt, OtherModifier, "timeit", Impure),
+    /// Offset dates by a number of business days
+    ///
+    /// Dates are Unix timestamps in seconds, the same as those returned by [now].
+    /// The first argument is the number of business days to move. The second is the dates. The third is a list of holiday dates.
+    /// Saturdays, Sundays, and holidays are never business days.
+    /// ex: # Experimental!
+    ///   : workday 3 1717977600 [] # Monday → Thursday
+    /// ex: # Experimental!
+    ///   : workday 3 1717977600 [1718064000] # Skip Tuesday
+    /// The date itself is not counted, and a count of `0` leaves the date unchanged.
+    /// Negative counts move backwards.
+    /// ex: # Experimental!
+    ///   : workday [¯1 0 1] 1717977600 []
+    /// The time of day is preserved.
+    /// ex: # Experimental!
+    ///   : workday 1 +3600 1718409600 []
+    /// [workday] is pervasive in its first two arguments.
+    /// ex: # Experimental!
+    ///   : workday 5_10 [1717977600 1718582400] []
+    ///
+    /// See also: [workdays]
+    (3, Workday, Misc, "workday"),
+    /// Count the business days between dates
+    ///
+    /// Dates are Unix timestamps in seconds, the same as those returned by [now].
+    /// The first argument is the start dates. The second is the end dates. The third is a list of holiday dates.
+    /// Saturdays, Sundays, and holidays are never business days.
+    /// The start date is counted, but the end date is not.
+    /// ex: # Experimental!
+    ///   : workdays 1717977600 1718582400 [] # One week
+    /// ex: # Experimental!
+    ///   : workdays 1717977600 1718582400 [1718064000]
+    /// If the end date is before the start date, the count is negative.
+    /// ex: # Experimental!
+    ///   : workdays 1718582400 1717977600 []
+    /// [workdays] is the inverse of [workday] when starting from a business day.
+    /// ex: # Experimental!
+    ///   : workdays 1717977600 workday 12 1717977600 [] []
+    /// [workdays] is pervasive in its first two arguments.
+    /// ex: # Experimental!
+    ///   : workdays 1717977600 +×86400⇡10 1717977600 []
+    ///
+    /// See also: [workday]
+    (3, Workdays, Misc, "workdays"),
+    /// Convert to a datetime array
+    ///
+    /// Strings are parsed as ISO 8601 dates and times. Times without an offset are in UTC.
+    /// ex: # Experimental!
+    ///   : timestamp "2024-03-01T12:30:00Z"
+    /// ex: # Experimental!
+    ///   : timestamp {"2024-03-01" "2024-03-01 12:30+02:00"}
+    /// Numbers are Unix timestamps in seconds, the same as those returned by [now].
+    /// ex: # Experimental!
+    ///   : timestamp 1709296200
+    /// Datetimes can be offset by numbers of seconds with [add] and [subtract]. [subtract]ing two datetimes gives the number of seconds between them.
+    /// ex: # Experimental!
+    ///   : + 86400 timestamp "2024-02-28"
+    /// ex: # Experimental!
+    ///   : - timestamp "2024-01-01" timestamp "2024-03-01"
+    /// Datetimes can be compared and sorted.
+    /// ex: # Experimental!
+    ///   : ⊏⍏. timestamp {"2024-03-01" "1999-12-31" "2024-01-01"}
+    /// [un][timestamp] converts datetimes back to Unix timestamps. [un][parse] formats them as strings.
+    /// ex: # Experimental!
+    ///   : °timestamp timestamp "2024-03-01"
+    /// ex: # Experimental!
+    ///   : °⋕ timestamp 1709296200
+    (1, Timestamp, Misc, "timestamp"),
+    /// Linearly interpolate between sample points
+    ///
+    /// The first argument is a rank 2 array whose first row is the sample x values and whose second row is the sample y values. The x values must be sorted ascending.
+    /// The second argument is the x values to query. The result has the same shape as the queries.
+    /// ex: # Experimental!
+    ///   : interp [0_1_2 0_10_40] [0.5 1 1.5]
+    /// ex: # Experimental!
+    ///   : interp [0_1_2 0_10_40] [0_0.5 1.5_2]
+    /// Queries outside the range of the samples take the nearest sample's y value.
+    /// ex: # Experimental!
+    ///   : interp [0_1_2 0_10_40] [¯1 3]
+    /// If a [fill] value is set, out-of-range queries take it instead.
+    /// ex: # Experimental!
+    ///   : ⬚NaN interp [0_1_2 0_10_40] [¯1 1.5 3]
+    /// A common pattern is to build the samples from separate lists with [couple].
+    /// ex: # Experimental!
+    ///   : interp ⊟ [1 2 4 8] ⁿ2[1 2 4 8] ⇡9
+    ///
+    /// [cubic] interpolates with a smooth curve instead.
+    ///
+    /// See also: [resample]
+    (2, Interp, Misc, "interp"),
+    /// Sample the rows of an array at fractional indices
+    ///
+    /// Each index selects a row like [select], but fractional indices linearly interpolate between adjacent rows.
+    /// ex: # Experimental!
+    ///   : resample [0 0.5 1.25 2] [10 20 40]
+    /// ex: # Experimental!
+    ///   : resample 0.5 [1_2 3_4]
+    /// Indices that are out of bounds are an error unless a [fill] value is set.
+    /// ex! # Experimental!
+    ///   : resample 3 [10 20 40]
+    /// ex: # Experimental!
+    ///   : ⬚0resample [¯1 0.5 2.5] [10 20 40]
+    /// A signal can be resized to a new length by resampling at evenly spaced indices.
+    /// ex: # Experimental!
+    ///   : Resize ← resample ×÷⊃(-1|⇡)⊙(-1⧻.)
+    ///   : Resize 7 [1 5 3 9]
+    /// [resample] an image along both axes to scale it.
+    /// ex: # Experimental!
+    ///   : ⍜⍉(resample ÷2⇡5) resample ÷2⇡5 [0_1_0 1_0_1 0_1_0]
+    ///
+    /// [cubic] interpolates with a smooth curve instead.
+    ///
+    /// See also: [interp]
+    (2, Resample, Misc, "resample"),
+    /// Split integer amounts into parts proportional to some weights
+    ///
+    /// The first argument is a list of non-negative weights. The second is the integer amounts to split.
+    /// The parts always sum exactly to the amount.
+    /// ex: # Experimental!
+    ///   : allocate [1 1 1] 100
+    /// ex: # Experimental!
+    ///   : allocate [70 20 10] 1001
+    /// This makes [allocate] useful for splitting amounts of money. Work in the smallest unit of the currency, such as cents, so that every amount is an integer.
+    /// ex: # Experimental!
+    ///   : ÷100 allocate [1 2] ×100 10
+    /// Parts that are owed a larger fraction of a unit get the leftover units first. Ties go to earlier parts.
+    /// ex: # Experimental!
+    ///   : allocate [1 1 1] [1 2 ¯1]
+    /// ex: # Experimental!
+    ///   : allocate [0.5 0.25 0.25] 7
+    /// Weights are split exactly as the decimals they are written as, so `0.1` is exactly one tenth.
+    /// Amounts must be exactly representable, so they may be at most `2^53` in magnitude.
+    ///
+    /// See also: [decimalsum], [decimalmean]
+    (2, Allocate, Misc, "allocate"),
+    /// Sum the rows of an array exactly as decimals
+    ///
+    /// Numbers are summed as the decimals they are written as, and the exact sum is rounded once.
+    /// This avoids the errors that build up when adding amounts of money.
+    /// ex: # Experimental!
+    ///   : /+ [0.1 0.2]
+    ///   : decimalsum [0.1 0.2]
+    /// ex: # Experimental!
+    ///   : decimalsum ↯10 0.1
+    /// ex: # Experimental!
+    ///   : decimalsum [1_2 3_4 5_9.99]
+    /// Numbers that span too many decimal places to sum exactly are an error.
+    /// ex! # Experimental!
+    ///   : decimalsum [1e30 1e-30]
+    ///
+    /// See also: [decimalmean], [allocate]
+    (1, DecimalSum, Misc, "decimalsum"),
+    /// Get the mean of the rows of an array exactly as decimals
+    ///
+    /// The exact [decimalsum] is divided by the number of rows and rounded once.
+    /// ex: # Experimental!
+    ///   : decimalmean [0.1 0.2 0.3]
+    /// ex: # Experimental!
+    ///   : decimalmean [1_2 3_4 5_9.99]
+    /// The mean of an empty list is `NaN`.
+    /// ex: # Experimental!
+    ///   : decimalmean []
+    ///
+    /// See also: [decimalsum], [mean]
+    (1, DecimalMean, Misc, "decimalmean"),
+    /// Get the mean of the rows of an array
+    ///
+    /// ex: # Experimental!
+    ///   : mean [1 2 3 4]
+    /// ex: # Experimental!
+    ///   : mean [1_2 3_4 5_9]
+    /// Use [rows] to get the mean along a deeper axis. This is optimized to not actually call [mean] on each row.
+    /// ex: # Experimental!
+    ///   : ≡mean [1_2 3_4 5_9]
+    /// The mean of an empty list is `NaN`.
+    /// ex: # Experimental!
+    ///   : mean []
+    ///
+    /// [mean] uses Welford's algorithm, so it is more accurate than dividing the sum by the length.
+    ///
+    /// See also: [variance], [median]
+    (1, Mean, Misc, "mean"),
+    /// Get the variance of the rows of an array
+    ///
+    /// This is the population variance, the mean of the squared deviations from the [mean].
+    /// ex: # Experimental!
+    ///   : variance [2 4 4 4 5 5 7 9]
+    /// ex: # Experimental!
+    ///   : variance [1_2 3_4 5_9]
+    /// ex: # Experimental!
+    ///   : ≡variance [1_2 3_4 5_9]
+    /// To get the sample variance, multiply by `n/(n-1)`.
+    /// ex: # Experimental!
+    ///   : ×÷-1⟜∘⧻⟜variance [2 4 4 4 5 5 7 9]
+    ///
+    /// See also: [stddev], [mean]
+    (1, Variance, Misc, "variance"),
+    /// Get the standard deviation of the rows of an array
+    ///
+    /// This is the population standard deviation, the [sqrt] of the [variance].
+    /// ex: # Experimental!
+    ///   : stddev [2 4 4 4 5 5 7 9]
+    /// ex: # Experimental!
+    ///   : ≡stddev [1_2 3_4 5_9]
+    ///
+    /// See also: [variance], [mean]
+    (1, Stddev, Misc, "stddev"),
+    /// Get the median of the rows of an array
+    ///
+    /// ex: # Experimental!
+    ///   : median [5 1 4 2 3]
+    /// If there is an even number of rows, the median is the mean of the middle two.
+    /// ex: # Experimental!
+    ///   : median [5 1 4 2]
+    /// ex: # Experimental!
+    ///   : median [1_2 3_4 5_9]
+    /// ex: # Experimental!
+    ///   : ≡median [1_2 3_4 5_9]
+    /// [median] selects the middle without sorting, so it is faster than [select][rise] and [first][drop].
+    ///
+    /// See also: [quantile], [mean]
+    (1, Median, Misc, "median"),
+    /// Get quantiles of the rows of an array
+    ///
+    /// The first argument is the quantile or quantiles to get, each between 0 and 1.
+    /// Quantiles between rows are linearly interpolated.
+    /// ex: # Experimental!
+    ///   : quantile 0.25 [1 2 3 4 5]
+    /// ex: # Experimental!
+    ///   : quantile [0 0.25 0.5 0.75 1] [1 2 3 4]
+    /// The quantile axis replaces the first axis of the array.
+    /// ex: # Experimental!
+    ///   : quantile [0.1 0.9] [1_2 3_4 5_9]
+    ///
+    /// See also: [median]
+    (2, Quantile, Misc, "quantile"),
+    /// Check if two numeric arrays are approximately equal
+    ///
+    /// The first argument is the tolerance. Elements are close if their absolute difference is at most the tolerance.
+    /// ex: # Experimental!
+    ///   : approx 0.01 [1 2 3] [1.001 2 2.999]
+    /// ex: # Experimental!
+    ///   : approx 0.01 [1 2 3] [1.1 2 3]
+    /// The tolerance may also be a list of an absolute and a relative tolerance. The relative tolerance is multiplied by the larger magnitude of the two elements.
+    /// ex: # Experimental!
+    ///   : approx [0 0.01] [100 1000] [100.5 1005]
+    /// Arrays with different shapes are never approximately equal.
+    /// ex: # Experimental!
+    ///   : approx 1 [1 2 3] [1 2]
+    ///
+    /// See also: [deviation], [match]
+    (3, ApproxEq, Misc, "approx"),
+    /// Get the largest absolute difference between two numeric arrays and where it is
+    ///
+    /// The difference is on top of the stack. Below it is the coordinate of the first element where it occurs.
+    /// ex: # Experimental!
+    ///   : deviation [1 2 3] [1 2.5 3]
+    /// ex: # Experimental!
+    ///   : deviation [1_2 3_4] [1_2 3_3.5]
+    /// The arrays must have the same shape.
+    /// ex! # Experimental!
+    ///   : deviation [1 2 3] [1 2]
+    ///
+    /// See also: [approx]
+    (2(2), Deviation, Misc, "deviation"),
+    /// Get the great-circle distances between two arrays of points on the Earth
+    ///
+    /// Points are `[latitude longitude]` pairs in degrees. Distances are in kilometers.
+    /// ex: # Experimental!
+    ///   : haversine [51.5 ¯0.13] [48.86 2.35] # London to Paris
+    /// The result is a table of the distance between every point in the first array and every point in the second.
+    /// ex: # Experimental!
+    ///   : haversine [0_0 0_90] [0_0 0_180 90_0]
+    /// Points must have a last axis of length 2.
+    /// ex! # Experimental!
+    ///   : haversine [1 2 3] [4 5 6]
+    ///
+    /// See also: [inpolygon]
+    (2, Haversine, Misc, "haversine"),
+    /// Check whether points are inside a polygon
+    ///
+    /// The first argument is a list of the `[x y]` vertices of the polygon. The second is the points to check.
+    /// ex: # Experimental!
+    ///   : inpolygon [0_0 4_0 4_4 0_4] [2_2 5_1 1_3]
+    /// The polygon does not need to be convex. Self-intersecting polygons use the even-odd rule.
+    /// ex: # Experimental!
+    ///   : inpolygon [0_0 4_0 4_4 2_1 0_4] [2_3 2_0.5]
+    /// The result has the shape of the array of points.
+    /// ex: # Experimental!
+    ///   : inpolygon [0_0 4_0 4_4 0_4] ⊞⊟. [1 3 5]
+    ///
+    /// See also: [haversine]
+    (2, InPolygon, Misc, "inpolygon"),
+    /// Get the QR decomposition of a matrix
+    ///
+    /// The orthonormal matrix Q is on top of the stack. Below it is the upper triangular matrix R. Their matrix product is the original matrix.
+    /// ex: # Experimental!
+    ///   : qr [3_1 4_2]
+    /// ex: # Experimental!
+    ///   : ⊞(/+×)⊙⍉ qr [3_1 4_2]
+    /// The diagonal of R is never negative.
+    /// Taller matrices give a Q with the same shape as the matrix and a square R.
+    /// ex: # Experimental!
+    ///   : qr [1_0 1_1 0_1]
+    ///
+    /// See also: [cholesky], [eigenvalues]
+    (1(2), Qr, Misc, "qr"),
+    /// Get the Cholesky decomposition of a symmetric positive-definite matrix
+    ///
+    /// The result is a lower triangular matrix. Its matrix product with its transpose is the original matrix.
+    /// ex: # Experimental!
+    ///   : cholesky [4_2 2_5]
+    /// ex: # Experimental!
+    ///   : ⊞(/+×). cholesky [4_2 2_5]
+    /// The matrix must be positive-definite.
+    /// ex! # Experimental!
+    ///   : cholesky [1_2 2_1]
+    ///
+    /// See also: [qr], [eigenvalues]
+    (1, Cholesky, Misc, "cholesky"),
+    /// Get the eigenvalues of a symmetric matrix
+    ///
+    /// The eigenvalues are sorted in ascending order.
+    /// ex: # Experimental!
+    ///   : eigenvalues [2_1 1_2]
+    /// ex: # Experimental!
+    ///   : eigenvalues [4_1_0 1_3_1 0_1_2]
+    /// The matrix must be symmetric.
+    /// ex! # Experimental!
+    ///   : eigenvalues [1_2 3_4]
+    ///
+    /// See also: [qr], [cholesky]
+    (1, Eigenvalues, Misc, "eigenvalues"),
+    /// Take the lower triangle of a matrix
+    ///
+    /// The first argument is the offset of the highest diagonal to keep. `0` is the leading diagonal.
+    /// Elements above that diagonal become `0`.
+    /// ex: # Experimental!
+    ///   : tril 0 +1↯3_3⇡9
+    /// ex: # Experimental!
+    ///   : tril ¯1 +1↯3_4⇡12
+    /// ex: # Experimental!
+    ///   : tril 1 +1↯3_4⇡12
+    /// If a [fill] value is set, elements become that instead.
+    /// ex: # Experimental!
+    ///   : ⬚∞tril 0 ↯3_3 0
+    ///
+    /// See also: [triu], [band]
+    (2, Tril, Misc, "tril"),
+    /// Take the upper triangle of a matrix
+    ///
+    /// The first argument is the offset of the lowest diagonal to keep. `0` is the leading diagonal.
+    /// Elements below that diagonal become `0`.
+    /// ex: # Experimental!
+    ///   : triu 0 +1↯3_3⇡9
+    /// ex: # Experimental!
+    ///   : triu 1 +1↯3_4⇡12
+    /// If a [fill] value is set, elements become that instead.
+    /// ex: # Experimental!
+    ///   : ⬚¯1triu 0 ↯3_3 1
+    ///
+    /// See also: [tril], [band]
+    (2, Triu, Misc, "triu"),
+    /// Take a band of diagonals of a matrix
+    ///
+    /// The first argument is the number of diagonals to keep below and above the leading diagonal.
+    /// Elements outside the band become `0`.
+    /// ex: # Experimental!
+    ///   : band 1 +1↯4_4⇡16
+    /// Different numbers of diagonals can be kept below and above.
+    /// ex: # Experimental!
+    ///   : band 0_2 +1↯4_5⇡20
+    /// If a [fill] value is set, elements become that instead.
+    /// ex: # Experimental!
+    ///   : ⬚0band 2_0 ↯4_4 1
+    ///
+    /// See also: [tril], [triu]
+    (2, Band, Misc, "band"),
+    /// Evaluate an Einstein summation
+    ///
+    /// The first argument is a string of subscripts, and the second is a list of boxed numeric arrays.
+    /// The subscripts have a term for each array, separated by commas. Each term has a letter for each axis of its array.
+    /// After an `->` is a term for the axes of the result.
+    /// Axes with the same letter are multiplied together, and axes whose letters are not in the result are summed.
+    /// This is matrix multiplication.
+    /// ex: # Experimental!
+    ///   : einsum "ij,jk->ik" {[1_2 3_4] [5_6 7_8]}
+    /// Shared letters that are in the result are kept, as in this batched matrix multiplication.
+    /// ex: # Experimental!
+    ///   : einsum "bij,bjk->bik" {↯2_2_2⇡8 ↯2_2_2⇡8}
+    /// Letters that are not shared make outer products, transposes, and sums along axes.
+    /// ex: # Experimental!
+    ///   : einsum "i,j->ij" {[1 2 3] [4 5]}
+    /// ex: # Experimental!
+    ///   : einsum "ij->ji" {↯2_3⇡6}
+    /// ex: # Experimental!
+    ///   : einsum "ij->j" {↯2_3⇡6}
+    /// A letter repeated within a term takes the diagonal of those axes.
+    /// ex: # Experimental!
+    ///   : einsum "ii->i" {↯3_3⇡9}
+    /// If there is no `->`, the result's axes are the letters that appear only once, in alphabetical order.
+    /// ex: # Experimental!
+    ///   : einsum "i,i" {[1 2 3] [4 5 6]}
+    /// ex: # Experimental!
+    ///   : einsum "ii" {↯3_3⇡9}
+    /// When there are more than two arrays, they are multiplied in pairs, in an order that keeps intermediate results small.
+    /// ex: # Experimental!
+    ///   : einsum "ij,jk,kl->il" {↯2_3 1 ↯3_4 1 ↯4_2 1}
+    (2, Einsum, Misc, "einsum"),
+    /// Sort the rows of an array by the rows of some keys
+    ///
+    /// The first argument is the keys. It must have as many rows as the array.
+    /// ex: # Experimental!
+    ///   : sortby [3 1 2] "abc"
+    /// Rows with equal keys keep their order.
+    /// ex: # Experimental!
+    ///   : sortby [1 0 1 0] [1_2 3_4 5_6 7_8]
+    /// [sortby] is equivalent to [select][rise], but the array's rows are only moved once.
+    /// ex: # Experimental!
+    ///   : sortby ⌵. [2 ¯3 1 ¯1]
+    /// ex: ⊏⍏⌵. [2 ¯3 1 ¯1]
+    (2, SortBy, Misc, "sortby"),
+    /// Group the rows of an array by the rows of some keys into a map
+    ///
+    /// The first argument is the keys. It must have as many rows as the array.
+    /// The result is a map from each unique key to a box of the rows with that key.
+    /// ex: # Experimental!
+    ///   : groupby [1 2 1 3 2] "abcde"
+    /// Keys are ordered by their first appearance.
+    /// ex: # Experimental!
+    ///   : groupby ◿3. [5 3 7 1 2 9]
+    /// Keys can be any array, such as strings.
+    /// ex: # Experimental!
+    ///   : groupby {"x" "y" "x"} [1_2 3_4 5_6]
+    /// [groupby] is equivalent to [map][deduplicate] of the keys and [group][box][classify], but the keys are only hashed once.
+    /// ex: # Experimental!
+    ///   : groupby [0 1 0 1] [1 2 3 4]
+    /// ex: map ⊃◴(⊕□⊛) [0 1 0 1] [1 2 3 4]
+    (2, GroupBy, Misc, "groupby"),
+    /// Unbox the contents of an array of boxes into a single array
+    ///
+    /// If the contents have different shapes, they are padded with the fill value.
+    /// ex: # Experimental!
+    ///   : ⬚0rectangularize {[1 2 3] [4] [5 6]}
+    /// ex: # Experimental!
+    ///   : ⬚@ rectangularize {"hello" "hi" "hey"}
+    /// Without a fill, the contents must all have the same shape.
+    /// ex: # Experimental!
+    ///   : rectangularize {1_2 3_4}
+    /// ex! # Experimental!
+    ///   : rectangularize {1_2 [3]}
+    /// [rectangularize] is equivalent to [fill][rows][un][box], but contents of the same type are copied into the result without combining them row by row.
+    /// ex: # Experimental!
+    ///   : ⬚0rectangularize {[1_2 3_4] [¤5]}
+    /// ex: ⬚0≡°□ {[1_2 3_4] [¤5]}
+    (1, Rectangularize, MonadicArray, "rectangularize"),
+    /// Transpose a list of boxed arrays of differing lengths
+    ///
+    /// The nth box of the result contains the nth rows of every array that has at least n rows.
+    /// ex: # Experimental!
+    ///   : raggedtranspose {[1 2 3] [4] [5 6]}
+    /// ex: # Experimental!
+    ///   : raggedtranspose {"abc" "de" "f"}
+    /// Transposing twice only gives back the original arrays if their lengths never increase.
+    /// ex: # Experimental!
+    ///   : raggedtranspose raggedtranspose {[1 2 3] [4 5] [6]}
+    /// Other operations on ragged arrays can be done with [content]. [rows][under][un][box] maps the boxes, and [reduce][content] reduces them.
+    /// ex: ≡⍜°□⇌ {[1 2 3] [4] [5 6]}
+    /// ex: /◇⊂ {[1 2 3] [4] [5 6]}
+    (1, RaggedTranspose, MonadicArray, "raggedtranspose"),
+    /// Name the axes of an array
+    ///
+    /// The first argument is a list of names, one for each axis. Empty names leave axes unnamed.
+    /// ex: # Experimental!
+    ///   : axes {"row" "col"} ↯2_3⇡6
+    /// Axis names are shown in the array's shape.
+    /// ex: # Experimental!
+    ///   : △ axes {"row" "col"} ↯2_3⇡6
+    /// Pervasive functions and [reshape]s that keep the array's rank keep its axis names. [transpose] moves them with the axes.
+    /// ex: # Experimental!
+    ///   : ⍉ axes {"row" "col"} ↯2_3⇡6
+    /// Named axes can be picked from with [pickaxis].
+    /// [un][axes] gets the axis names of an array.
+    /// ex: # Experimental!
+    ///   : °axes axes {"" "col"} ↯2_3⇡6
+    (2, Axes, Misc, "axes"),
+    /// Pick from an array along a named axis
+    ///
+    /// The first argument is the axis name. The second argument is the index.
+    /// A single index removes the axis.
+    /// ex: # Experimental!
+    ///   : pickaxis "col" 1 axes {"row" "col"} ↯2_3⇡6
+    /// A list of indices keeps it.
+    /// ex: # Experimental!
+    ///   : pickaxis "row" [1 0 1] axes {"row" "col"} ↯2_3⇡6
+    /// Negative indices count from the end.
+    /// ex: # Experimental!
+    ///   : pickaxis "col" ¯1 axes {"row" "col"} ↯2_3⇡6
+    (3, PickAxis, Misc, "pickaxis"),
+    /// Reverse an array along some axes
+    ///
+    /// The first argument is a list of axis names or axis indices.
+    /// ex: # Experimental!
+    ///   : reverseaxes {"col"} axes {"row" "col"} ↯2_3⇡6
+    /// ex: # Experimental!
+    ///   : reverseaxes [0 2] ↯2_2_3⇡12
+    /// Negative indices count from the last axis.
+    /// ex: # Experimental!
+    ///   : reverseaxes ¯1 ↯2_3⇡6
+    /// Reversing several axes at once only goes over the array's data once.
+    (2, ReverseAxes, Misc, "reverseaxes"),
+    /// Move the axes of an array to the given positions
+    ///
+    /// The first argument has a position for each axis of the array.
+    /// ex: # Experimental!
+    ///   : transposeaxes [1 0] ↯2_3⇡6
+    /// ex: # Experimental!
+    ///   : △ transposeaxes [2 0 1] ↯2_3_4⇡24
+    /// If several axes are moved to the same position, the result contains their diagonal.
+    /// ex: # Experimental!
+    ///   : transposeaxes [0 0] ↯3_3⇡9
+    /// ex: # Experimental!
+    ///   : transposeaxes [0 1 0] ↯2_3_2⇡12
+    /// Named axes move with the axes. See [axes].
+    /// ex: # Experimental!
+    ///   : △ transposeaxes [1 2 0] axes {"a" "b" "c"} ↯2_3_4⇡24
+    (2, TransposeAxes, Misc, "transposeaxes"),
+    /// Take a diagonal of an array
+    ///
+    /// The first argument is the offset of the diagonal. `0` gives the leading diagonal.
+    /// ex: # Experimental!
+    ///   : diagonal 0 ↯3_3⇡9
+    /// Positive offsets give diagonals above the leading diagonal, and negative offsets give diagonals below it.
+    /// ex: # Experimental!
+    ///   : diagonal 1 ↯3_4⇡12
+    /// ex: # Experimental!
+    ///   : diagonal ¯1 ↯3_4⇡12
+    /// The diagonal is taken along the first two axes. The other axes are kept.
+    /// ex: # Experimental!
+    ///   : diagonal 0 ↯2_2_3⇡12
+    /// [under][diagonal] modifies the diagonal in place.
+    /// ex: # Experimental!
+    ///   : ⍜(diagonal 0|×10) ↯3_3⇡9
+    /// ex: # Experimental!
+    ///   : ⍜(diagonal 1|⇌) ↯4_4⇡16
+    ///
+    /// See also: [diag]
+    (2, Diagonal, Misc, "diagonal"),
+    /// Make a diagonal matrix
+    ///
+    /// The rows of the array are placed along the leading diagonal.
+    /// ex: # Experimental!
+    ///   : diag [1 2 3]
+    /// ex: # Experimental!
+    ///   : diag "abc"
+    /// ex: # Experimental!
+    ///   : diag [1_2 3_4]
+    /// Elements off the diagonal are the [fill] value if one is set.
+    /// ex: # Experimental!
+    ///   : ⬚5diag [1 2 3]
+    /// [un][diag] takes the leading diagonal.
+    /// ex: # Experimental!
+    ///   : °diag ↯3_3⇡9
+    ///
+    /// See also: [diagonal]
+    (1, Diag, Misc, "diag"),
+    /// Attach a unit of measure to a numeric array
+    ///
+    /// Units are written like `m`, `m/s`, or `kg*m/s^2`.
+    /// ex: # Experimental!
+    ///   : unit "m/s" 3
+    /// Units are carried through pervasive math. [add], [subtract], [modulus], [minimum], and [maximum] keep them.
+    /// ex: # Experimental!
+    ///   : + unit "m" 1 unit "m" 2
+    /// [multiply] and [divide] combine them.
+    /// ex: # Experimental!
+    ///   : ÷ unit "s" 2 unit "m" 10
+    /// ex: # Experimental!
+    ///   : × . unit "m" 3
+    /// Adding or comparing arrays with different units is an error.
+    /// ex! # Experimental!
+    ///   : + unit "s" 1 unit "m" 2
+    /// Arrays without units can be combined with arrays that have them.
+    /// ex: # Experimental!
+    ///   : × 2 unit "m" [1 2 3]
+    /// Other operations drop the unit.
+    /// An empty unit removes an array's unit. [un][unit] gets the unit of an array as a string.
+    /// ex: # Experimental!
+    ///   : °unit unit "kg*m/s^2" 5
+    (2, Unit, Misc, "unit"),
+    /// Split a list at each occurrence of a delimiter
+    ///
+    /// The result is a list of boxed segments.
+    /// ex: # Experimental!
+    ///   : split @, "a,b,,c"
+    /// The delimiter can be a list.
+    /// ex: # Experimental!
+    ///   : split ", " "apples, bananas, cherries"
+    /// ex: # Experimental!
+    ///   : split 0 [1 2 0 3 0 4 5]
+    /// Unlike [partition], empty segments are kept, so [split] can be undone with [joinwith].
+    /// ex: # Experimental!
+    ///   : joinwith @, split @, "a,b,,c"
+    ///
+    /// See also: [joinwith]
+    (2, Split, Misc, "split"),
+    /// Join the rows of an array with a separator between them
+    ///
+    /// The rows of a list of boxes are unboxed first.
+    /// ex: # Experimental!
+    ///   : joinwith ", " {"apples" "bananas" "cherries"}
+    /// ex: # Experimental!
+    ///   : joinwith @- ["ab" "cd" "ef"]
+    /// ex: # Experimental!
+    ///   : joinwith 0 {1_2 3 4_5_6}
+    ///
+    /// See also: [split]
+    (2, JoinWith, Misc, "joinwith"),
+    /// Pad the last axis of an array to a width
+    ///
+    /// The width is the first argument. A positive width pads at the start, and a negative width pads at the end.
+    /// ex: # Experimental!
+    ///   : pad 6 "abc"
+    /// ex: # Experimental!
+    ///   : pad ¯6 "abc"
+    /// Arrays that are already long enough are unchanged.
+    /// ex: # Experimental!
+    ///   : pad 2 "abc"
+    /// Each boxed array is padded individually.
+    /// ex: # Experimental!
+    ///   : pad 4 {"a" "bcd" "efghi"}
+    /// Characters are padded with spaces and numbers with `0`. Use [fill] to pad with something else.
+    /// ex: # Experimental!
+    ///   : ⬚@0pad 5 "42"
+    /// ex: # Experimental!
+    ///   : pad ¯4 [1_2 3_4]
+    (2, Pad, Misc, "pad"),
+    /// Convert characters to uppercase
+    ///
+    /// ex: # Experimental!
+    ///   : upper "Hello, World!"
+    /// Boxed strings are converted individually.
+    /// ex: # Experimental!
+    ///   : upper {"abc" "Déjà vu"}
+    /// Characters whose uppercase form is multiple characters are unchanged.
+    /// ex: # Experimental!
+    ///   : upper "straße"
+    ///
+    /// See also: [lower]
+    (1, Upper, Misc, "upper"),
+    /// Convert characters to lowercase
+    ///
+    /// ex: # Experimental!
+    ///   : lower "Hello, World!"
+    /// Boxed strings are converted individually.
+    /// ex: # Experimental!
+    ///   : lower {"ABC" "DÉJÀ VU"}
+    ///
+    /// See also: [upper]
+    (1, Lower, Misc, "lower"),
+    /// Get the Levenshtein distance between two strings
+    ///
+    /// This is the number of single-character insertions, deletions, and substitutions needed to turn one string into the other.
+    /// ex: # Experimental!
+    ///   : levenshtein "kitten" "sitting"
+    /// If either argument is a list of strings, the distance to each string is computed.
+    /// ex: # Experimental!
+    ///   : levenshtein "apple" {"apply" "ape" "maple"}
+    /// Two lists of strings give a table of distances.
+    /// ex: # Experimental!
+    ///   : levenshtein {"cat" "dog"} {"cart" "dot" "cow"}
+    /// This can be used to find the closest match for a misspelled word.
+    /// ex: # Experimental!
+    ///   : ⊏⊢⍏⊸levenshtein "bananna" {"apple" "banana" "cherry"}
+    (2, Levenshtein, Misc, "levenshtein"),
     /// The number of radians in a quarter circle
     ///
     /// Equivalent to `divide``2``pi` or `divide``4``tau`
@@ -2226,6 +3334,38 @@ primitive!(
     ///
     /// See also: [insert], [has], [get]
     (2, Remove, Map, "remove"),
+    /// Get the value of a field of a record
+    ///
+    /// A record is a list of boxes, each labeled with a field name.
+    /// ex: # Experimental!
+    ///   : {$name "Dave" $age 31}
+    ///   : field "name" .
+    /// If the field is not found, an error is thrown.
+    /// ex! # Experimental!
+    ///   : field "height" {$name "Dave" $age 31}
+    /// You can provide a default value with [fill].
+    /// ex: # Experimental!
+    ///   : ⬚0field "height" {$name "Dave" $age 31}
+    /// You can use [under][field] to modify the value of a field.
+    /// ex: # Experimental!
+    ///   : ⍜(field "age"|+1) {$name "Dave" $age 31}
+    ///
+    /// See also: [setfield]
+    (2, Field, Map, "field"),
+    /// Set the value of a field of a record
+    ///
+    /// A record is a list of boxes, each labeled with a field name.
+    /// If the field already exists, its value is replaced. Otherwise, it is added to the end.
+    /// ex: # Experimental!
+    ///   : setfield "age" 32 {$name "Dave" $age 31}
+    /// ex: # Experimental!
+    ///   : setfield "height" 1.8 {$name "Dave" $age 31}
+    /// Records can be built up from an empty list.
+    /// ex: # Experimental!
+    ///   : setfield "b" [4 5 6] setfield "a" 1 []
+    ///
+    /// See also: [field]
+    (3, SetField, Map, "setfield"),
     /// Debug print all stack values without popping them
     ///
     /// This is equivalent to [dump][identity], but is easier to type.
@@ -2446,12 +3586,20 @@ impl_primitive!(
     (1, UnWhere),
     (1(2), UnCouple),
     (1, UnUtf),
+    (1, UnBase64),
+    (1, UnHex),
+    (1, UnUrl),
     (1(2), UnAtan),
     (1(2), UnComplex),
     (1, UnParse),
     (1, UnFix),
     (1[1], UnScan),
     (1(2), UnMap),
+    (1(2), UnAxes),
+    (1, UnDiag),
+    (1(2), UnUnit),
+    (1, UnTimestamp),
+    (2, UnPermute),
     (1, UnTrace, Impure),
     (2(2), UnBothTrace, Impure),
     (0(0), UnStack, Impure),
@@ -2475,7 +3623,13 @@ impl_primitive!(
     (2, UndoLast),
     (3, UndoKeep),
     (3, UndoRerank),
-    (2, UndoReshape),
+    (3, UndoReshape),
+    (3, UndoRotate),
+    (2, UnWindows),
+    (3, UndoWindows),
+    (2, FindMatches),
+    (1(2), DeduplicateInverse),
+    (3, UndoFind),
     (3(2), UndoJoin),
     (1[1], UndoPartition1),
     (3, UndpPartition2),
@@ -2483,6 +3637,7 @@ impl_primitive!(
     (3, UndoGroup2),
     (4, UndoInsert),
     (3, UndoRemove),
+    (3, UndoDiagonal),
     // Optimizations
     (1, Last),
     (1, FirstMinIndex),
@@ -28,7 +28,7 @@ use rand::prelude::*;
 use serde::*;
 
 use crate::{
-    algorithm::{self, invert, loops, reduce, table, zip},
+    algorithm::{self, invert, lazy_fill, loops, padding, reduce, table, zip},
     array::Array,
     boxed::Boxed,
     check::instrs_signature,
@@ -163,9 +163,17 @@ impl fmt::Display for ImplPrimitive {
             UnWhere => write!(f, "{Un}{Where}"),
             UnCouple => write!(f, "{Un}{Couple}"),
             UnMap => write!(f, "{Un}{Map}"),
+            UnAxes => write!(f, "{Un}{Axes}"),
+            UnDiag => write!(f, "{Un}{Diag}"),
+            UnUnit => write!(f, "{Un}{Unit}"),
+            UnTimestamp => write!(f, "{Un}{Timestamp}"),
+            UnPermute => write!(f, "{Un}{Permute}"),
             UnAtan => write!(f, "{Un}{Atan}"),
             UnComplex => write!(f, "{Un}{Complex}"),
             UnUtf => write!(f, "{Un}{Utf}"),
+            UnBase64 => write!(f, "{Un}{Base64}"),
+            UnHex => write!(f, "{Un}{Hex}"),
+            UnUrl => write!(f, "{Un}{Url}"),
             UnParse => write!(f, "{Un}{Parse}"),
             UnFix => write!(f, "{Un}{Fix}"),
             UnJoin | UnJoinPattern => write!(f, "{Un}{Join}"),
@@ -184,6 +192,7 @@ impl fmt::Display for ImplPrimitive {
             UndoPick => write!(f, "{Under}{Pick}"),
             UndoInsert => write!(f, "{Under}{Insert}"),
             UndoRemove => write!(f, "{Under}{Remove}"),
+            UndoDiagonal => write!(f, "{Under}{Diagonal}"),
             UndoPartition1 | UndpPartition2 => write!(f, "{Under}{Partition}"),
             UndoGroup1 | UndoGroup2 => write!(f, "{Under}{Group}"),
             Asin => write!(f, "{Un}{Sin}"),
@@ -194,6 +203,11 @@ impl fmt::Display for ImplPrimitive {
             UndoKeep => write!(f, "{Under}{Keep}"),
             UndoRerank => write!(f, "{Under}{Rerank}"),
             UndoReshape => write!(f, "{Un}{Reshape}"),
+            UndoRotate => write!(f, "{Under}{Rotate}"),
+            UnWindows => write!(f, "{Un}{Windows}"),
+            UndoWindows => write!(f, "{Under}{Windows}"),
+            FindMatches => write!(f, "{Find}"),
+            UndoFind => write!(f, "{Under}{Find}"),
             UndoJoin => write!(f, "{Under}{Join}"),
             FirstMinIndex => write!(f, "{First}{Rise}"),
             FirstMaxIndex => write!(f, "{First}{Fall}"),
@@ -211,6 +225,7 @@ impl fmt::Display for ImplPrimitive {
             BothTrace => write!(f, "{Both}{Trace}"),
             UnBothTrace => write!(f, "{Un}{Both}{Trace}"),
             CountUnique => write!(f, "{Len}{Deduplicate}"),
+            DeduplicateInverse => write!(f, "{Deduplicate}"),
             MatchPattern => write!(f, "pattern match"),
             EndRandArray => write!(f, "[{Repeat}{Rand}"),
             &ReduceDepth(n) => {
@@ -369,7 +384,34 @@ impl Primitive {
         use SysOp::*;
         matches!(
             self,
-            Coordinate | Sys(Ffi | MemCopy | MemFree | TlsListen) | (Stringify | Quote | Sig)
+            Coordinate
+                | Sys(Ffi | FfiCallback | MemCopy | MemFree | TlsListen)
+                | (Stringify | Quote | Sig)
+                | (Workday | Workdays | Timestamp)
+                | (Clock | TimeIt)
+                | (Interp | Resample)
+                | (Allocate | DecimalSum | DecimalMean)
+                | (Seed | Uniform | Normal | Permutation | Choice | Sample)
+                | (Shuffle | Permute)
+                | (Mean | Variance | Stddev | Median | Quantile)
+                | (ApproxEq | Deviation)
+                | Expect
+                | (Haversine | InPolygon)
+                | (Qr | Cholesky | Eigenvalues | Tril | Triu | Band | Einsum)
+                | (SortBy | GroupBy)
+                | (Rectangularize | RaggedTranspose)
+                | (Axes | PickAxis | ReverseAxes | TransposeAxes | Diagonal | Diag)
+                | Unit
+                | FillBy
+                | PadMode
+                | SkipNan
+                | DeepBox
+                | Cubic
+                | (Split | JoinWith | Pad | Upper | Lower | Levenshtein)
+                | (Base64 | Hex | Url | Compress | Decompress)
+                | (HashBytes | RowHash)
+                | (BitNot | BitAnd | BitOr | BitXor | ShiftLeft | ShiftRight)
+                | (Field | SetField)
         )
     }
     /// Check if this primitive is deprecated
@@ -524,6 +566,7 @@ impl Primitive {
             Primitive::Floor => env.monadic_env(Value::floor)?,
             Primitive::Ceil => env.monadic_env(Value::ceil)?,
             Primitive::Round => env.monadic_env(Value::round)?,
+            Primitive::BitNot => env.monadic_env(Value::bit_not)?,
             Primitive::Eq => env.dyadic_oo_00_env(Value::is_eq)?,
             Primitive::Ne => env.dyadic_oo_00_env(Value::is_ne)?,
             Primitive::Lt => env.dyadic_oo_00_env(Value::is_lt)?,
@@ -541,16 +584,39 @@ impl Primitive {
             Primitive::Max => env.dyadic_oo_00_env(Value::max)?,
             Primitive::Atan => env.dyadic_oo_00_env(Value::atan2)?,
             Primitive::Complex => env.dyadic_oo_00_env(Value::complex)?,
+            Primitive::BitAnd => env.dyadic_oo_00_env(Value::bit_and)?,
+            Primitive::BitOr => env.dyadic_oo_00_env(Value::bit_or)?,
+            Primitive::BitXor => env.dyadic_oo_00_env(Value::bit_xor)?,
+            Primitive::ShiftLeft => env.dyadic_oo_00_env(Value::shift_left)?,
+            Primitive::ShiftRight => env.dyadic_oo_00_env(Value::shift_right)?,
             Primitive::Match => env.dyadic_rr(|a, b| a == b)?,
             Primitive::Join => env.dyadic_oo_env(Value::join)?,
             Primitive::Transpose => env.monadic_mut(Value::transpose)?,
             Primitive::Keep => env.dyadic_ro_env(Value::keep)?,
+            Primitive::Take if env.pad_modes().is_some() => {
+                padding::take(env.pad_modes().unwrap(), env)?
+            }
+            Primitive::Windows if env.pad_modes().is_some() => {
+                padding::windows(env.pad_modes().unwrap(), env)?
+            }
+            Primitive::Take if env.fill_by().is_some() => {
+                lazy_fill::take(env.fill_by().unwrap(), env)?
+            }
+            Primitive::Rotate if env.fill_by().is_some() => {
+                lazy_fill::rotate(env.fill_by().unwrap(), env)?
+            }
+            Primitive::Reshape if env.fill_by().is_some() => {
+                lazy_fill::reshape(env.fill_by().unwrap(), env)?
+            }
+            Primitive::Find if env.fill_by().is_some() => {
+                lazy_fill::find(env.fill_by().unwrap(), env)?
+            }
             Primitive::Take => env.dyadic_oo_env(Value::take)?,
             Primitive::Drop => env.dyadic_oo_env(Value::drop)?,
             Primitive::Rotate => env.dyadic_ro_env(Value::rotate)?,
             Primitive::Couple => env.dyadic_oo_env(Value::couple)?,
-            Primitive::Rise => env.monadic_ref(Value::rise)?,
-            Primitive::Fall => env.monadic_ref(Value::fall)?,
+            Primitive::Rise => env.monadic_ref_env(Value::rise)?,
+            Primitive::Fall => env.monadic_ref_env(Value::fall)?,
             Primitive::Pick => env.dyadic_oo_env(Value::pick)?,
             Primitive::Select => env.dyadic_rr_env(Value::select)?,
             Primitive::Windows => env.dyadic_rr_env(Value::windows)?,
@@ -571,6 +637,13 @@ impl Primitive {
             Primitive::Repr => env.monadic_ref(Value::representation)?,
             Primitive::Parse => env.monadic_ref_env(Value::parse_num)?,
             Primitive::Utf => env.monadic_ref_env(Value::utf8)?,
+            Primitive::Base64 => env.monadic_ref_env(Value::base64)?,
+            Primitive::Hex => env.monadic_ref_env(Value::hex)?,
+            Primitive::Url => env.monadic_ref_env(Value::url)?,
+            Primitive::Compress => env.dyadic_rr_env(Value::compress_bytes)?,
+            Primitive::Decompress => env.dyadic_rr_env(Value::decompress_bytes)?,
+            Primitive::HashBytes => env.dyadic_rr_env(Value::hash_bytes)?,
+            Primitive::RowHash => env.monadic_ref(Value::row_hashes)?,
             Primitive::Range => env.monadic_ref_env(Value::range)?,
             Primitive::Reverse => env.monadic_mut(Value::reverse)?,
             Primitive::Deshape => env.monadic_mut(Value::deshape)?,
@@ -625,6 +698,20 @@ impl Primitive {
             Primitive::Pop => {
                 env.pop(1)?;
             }
+            Primitive::FillBy => lazy_fill::fill_by(env)?,
+            Primitive::PadMode => padding::pad_mode(env)?,
+            Primitive::SkipNan => {
+                let f = env.pop_function()?;
+                env.with_skip_nan(|env| env.call(f))?;
+            }
+            Primitive::DeepBox => {
+                let f = env.pop_function()?;
+                env.with_deep_box(|env| env.call(f))?;
+            }
+            Primitive::Cubic => {
+                let f = env.pop_function()?;
+                env.with_cubic(|env| env.call(f))?;
+            }
             Primitive::Fill => {
                 let fill = env.pop_function()?;
                 let f = env.pop_function()?;
@@ -664,7 +751,22 @@ impl Primitive {
                     ));
                 }
             }
-            Primitive::Rand => env.push(random()),
+            Primitive::Expect => {
+                let expected = env.pop(1)?;
+                let actual = env.pop(2)?;
+                let diff = expected.diff(&actual);
+                if !diff.is_empty() {
+                    return Err(UiuaError::Throw(
+                        Value::from(diff.assertion_message()).into(),
+                        env.span().clone(),
+                        env.inputs().clone().into(),
+                    ));
+                }
+            }
+            Primitive::Rand => {
+                let n = env.random();
+                env.push(n);
+            }
             Primitive::Gen => {
                 let seed = env.pop(1)?;
                 let mut rng =
@@ -674,6 +776,25 @@ impl Primitive {
                 env.push(val);
                 env.push(next_seed);
             }
+            Primitive::Seed => {
+                let seed = env.pop("seed")?.as_num(env, "Seed must be a number")?;
+                env.seed_rng(seed);
+            }
+            Primitive::Uniform => {
+                let shape = env.pop("shape")?;
+                let arr = env.uniform(&shape)?;
+                env.push(arr);
+            }
+            Primitive::Normal => {
+                let shape = env.pop("shape")?;
+                let arr = env.normal(&shape)?;
+                env.push(arr);
+            }
+            Primitive::Permutation => {
+                let n = env.pop("length")?;
+                let perm = env.permutation(&n)?;
+                env.push(perm);
+            }
             Primitive::Deal => {
                 let seed = env.pop(1)?.as_num(env, "Deal expects a number")?.to_bits();
                 let arr = env.pop(2)?;
@@ -681,6 +802,26 @@ impl Primitive {
                 rows.shuffle(&mut SmallRng::seed_from_u64(seed));
                 env.push(Value::from_row_values_infallible(rows));
             }
+            Primitive::Shuffle => {
+                let val = env.pop(1)?;
+                let shuffled = env.shuffle(val);
+                env.push(shuffled);
+            }
+            Primitive::Permute => env.dyadic_ro_env(Value::permute)?,
+            Primitive::Choice => {
+                let k = env.pop("count")?;
+                let weights = env.pop("weights")?;
+                let from = env.pop(3)?;
+                let chosen = env.choice(&k, &weights, &from)?;
+                env.push(chosen);
+            }
+            Primitive::Sample => {
+                let k = env.pop("count")?;
+                let weights = env.pop("weights")?;
+                let from = env.pop(3)?;
+                let sampled = env.sample(&k, &weights, &from)?;
+                env.push(sampled);
+            }
             Primitive::Tag => {
                 static NEXT_TAG: AtomicUsize = AtomicUsize::new(0);
                 let tag = NEXT_TAG.fetch_add(1, atomic::Ordering::Relaxed);
@@ -746,7 +887,98 @@ impl Primitive {
                 let id = env.pop(1)?;
                 env.try_recv(id)?;
             }
-            Primitive::Now => env.push(instant::now() / 1000.0),
+            Primitive::Now => env.push(env.rt.backend.now()),
+            Primitive::Clock => env.push(clock_nanos()),
+            Primitive::TimeIt => {
+                let f = env.pop_function()?;
+                let start = instant::Instant::now();
+                env.call(f)?;
+                env.push(start.elapsed().as_secs_f64());
+            }
+            Primitive::Workday => {
+                let counts = env.pop("business day count")?;
+                let dates = env.pop("dates")?;
+                let holidays = env.pop("holidays")?;
+                env.push(counts.workday(&dates, &holidays, env)?);
+            }
+            Primitive::Workdays => {
+                let starts = env.pop("start dates")?;
+                let ends = env.pop("end dates")?;
+                let holidays = env.pop("holidays")?;
+                env.push(starts.workdays(&ends, &holidays, env)?);
+            }
+            Primitive::Interp => {
+                let samples = env.pop("samples")?;
+                let queries = env.pop("queries")?;
+                env.push(samples.interp(&queries, env)?);
+            }
+            Primitive::Resample => {
+                let indices = env.pop("indices")?;
+                let from = env.pop("array")?;
+                env.push(indices.resample(&from, env)?);
+            }
+            Primitive::Allocate => {
+                let weights = env.pop("weights")?;
+                let totals = env.pop("amounts")?;
+                env.push(weights.allocate(&totals, env)?);
+            }
+            Primitive::DecimalSum => env.monadic_ref_env(|val, env| val.decimal_sum(0, env))?,
+            Primitive::DecimalMean => env.monadic_ref_env(|val, env| val.decimal_mean(0, env))?,
+            Primitive::Mean => env.monadic_ref_env(|val, env| val.mean(0, env))?,
+            Primitive::Variance => env.monadic_ref_env(|val, env| val.variance(0, env))?,
+            Primitive::Stddev => env.monadic_ref_env(|val, env| val.stddev(0, env))?,
+            Primitive::Median => env.monadic_ref_env(|val, env| val.median(0, env))?,
+            Primitive::Quantile => env.dyadic_rr_env(|qs, val, env| qs.quantile(val, 0, env))?,
+            Primitive::ApproxEq => {
+                let tolerance = env.pop(1)?;
+                let a = env.pop(2)?;
+                let b = env.pop(3)?;
+                env.push(tolerance.approx(&a, &b, env)?);
+            }
+            Primitive::Deviation => {
+                let a = env.pop(1)?;
+                let b = env.pop(2)?;
+                let (dev, coord) = a.deviation(&b, env)?;
+                env.push(coord);
+                env.push(dev);
+            }
+            Primitive::Haversine => env.dyadic_rr_env(Value::haversine)?,
+            Primitive::InPolygon => env.dyadic_rr_env(Value::in_polygon)?,
+            Primitive::Qr => {
+                let val = env.pop(1)?;
+                let (q, r) = val.qr(env)?;
+                env.push(r);
+                env.push(q);
+            }
+            Primitive::Cholesky => env.monadic_ref_env(Value::cholesky)?,
+            Primitive::Eigenvalues => env.monadic_ref_env(Value::eigenvalues)?,
+            Primitive::Tril => env.dyadic_ro_env(Value::tril)?,
+            Primitive::Triu => env.dyadic_ro_env(Value::triu)?,
+            Primitive::Band => env.dyadic_ro_env(Value::band)?,
+            Primitive::Einsum => env.dyadic_rr_env(Value::einsum)?,
+            Primitive::SortBy => env.dyadic_rr_env(Value::sort_by_key)?,
+            Primitive::GroupBy => env.dyadic_rr_env(Value::group_by_key)?,
+            Primitive::Rectangularize => env.monadic_env(Value::rectangularize)?,
+            Primitive::RaggedTranspose => env.monadic_env(Value::ragged_transpose)?,
+            Primitive::Axes => env.dyadic_ro_env(Value::name_axes)?,
+            Primitive::PickAxis => {
+                let name = env.pop(1)?;
+                let index = env.pop(2)?;
+                let from = env.pop(3)?;
+                env.push(name.pick_axis(&index, &from, env)?);
+            }
+            Primitive::ReverseAxes => env.dyadic_ro_env(Value::reverse_axes)?,
+            Primitive::TransposeAxes => env.dyadic_ro_env(Value::transpose_axes)?,
+            Primitive::Diagonal => env.dyadic_rr_env(Value::diagonal)?,
+            Primitive::Diag => env.monadic_ref_env(Value::diag)?,
+            Primitive::Unit => env.dyadic_ro_env(Value::with_unit)?,
+            Primitive::Timestamp => env.monadic_ref_env(Value::to_timestamp)?,
+            Primitive::Split => env.dyadic_ro_env(Value::split)?,
+            Primitive::JoinWith => env.dyadic_ro_env(Value::join_with)?,
+            Primitive::Pad => env.dyadic_ro_env(Value::pad)?,
+            Primitive::Upper => env.monadic_env(Value::uppercase)?,
+            Primitive::Lower => env.monadic_env(Value::lowercase)?,
+            Primitive::Levenshtein => env.dyadic_rr_env(Value::levenshtein)?,
             Primitive::SetInverse => {
                 let f = env.pop_function()?;
                 let _inv = env.pop_function()?;
@@ -782,6 +1014,19 @@ impl Primitive {
                 map.remove(key, env)?;
                 env.push(map);
             }
+            Primitive::Field => {
+                let name = env.pop("name")?;
+                let record = env.pop("record")?;
+                let val = record.get_field(&name, env)?;
+                env.push(val);
+            }
+            Primitive::SetField => {
+                let name = env.pop("name")?;
+                let val = env.pop("value")?;
+                let mut record = env.pop("record")?;
+                record.set_field(&name, val, env)?;
+                env.push(record);
+            }
             Primitive::Map => {
                 let keys = env.pop("keys")?;
                 let mut vals = env.pop("values")?;
@@ -854,6 +1099,20 @@ impl ImplPrimitive {
                 env.push(b);
                 env.push(a);
             }
+            ImplPrimitive::UnAxes => {
+                let val = env.pop(1)?;
+                let (names, val) = val.unname_axes();
+                env.push(val);
+                env.push(names);
+            }
+            ImplPrimitive::UnUnit => {
+                let val = env.pop(1)?;
+                let (unit, val) = val.unwith_unit();
+                env.push(val);
+                env.push(unit);
+            }
+            ImplPrimitive::UnPermute => env.dyadic_ro_env(Value::unpermute)?,
+            ImplPrimitive::UnTimestamp => env.monadic_ref_env(Value::untimestamp)?,
             ImplPrimitive::UnMap => {
                 let map = env.pop(1)?;
                 let (keys, vals) = map.unmap(env)?;
@@ -866,6 +1125,13 @@ impl ImplPrimitive {
                 let from = env.pop(3)?;
                 env.push(from.undo_pick(index, into, env)?);
             }
+            ImplPrimitive::UndoDiagonal => {
+                let k = env.pop(1)?;
+                let into = env.pop(2)?;
+                let from = env.pop(3)?;
+                env.push(from.undo_diagonal(&k, into, env)?);
+            }
+            ImplPrimitive::UnDiag => env.monadic_ref_env(|val, env| val.diagonal_at(0, env))?,
             ImplPrimitive::UndoSelect => {
                 let index = env.pop(1)?;
                 let into = env.pop(2)?;
@@ -880,11 +1146,46 @@ impl ImplPrimitive {
                 env.push(array);
             }
             ImplPrimitive::UndoReshape => {
-                let orig_shape = env.pop(1)?;
-                let mut array = env.pop(2)?;
-                array.undo_reshape(&orig_shape, env)?;
+                let shape = env.pop(1)?;
+                let orig_shape = env.pop(2)?;
+                let mut array = env.pop(3)?;
+                array.undo_reshape(&shape, &orig_shape, env)?;
                 env.push(array);
             }
+            ImplPrimitive::UndoRotate => {
+                let by = env.pop(1)?;
+                let rotated = env.pop(2)?;
+                let original = env.pop(3)?;
+                env.push(by.undo_rotate(rotated, original, env)?);
+            }
+            ImplPrimitive::UnWindows => {
+                let size = env.pop(1)?;
+                let windows = env.pop(2)?;
+                env.push(size.unwindows(windows, env)?);
+            }
+            ImplPrimitive::UndoWindows => {
+                let size = env.pop(1)?;
+                let windows = env.pop(2)?;
+                let into = env.pop(3)?;
+                env.push(size.undo_windows(windows, into, env)?);
+            }
+            ImplPrimitive::DeduplicateInverse => {
+                let mut val = env.pop(1)?;
+                let inverse = val.deduplicate_inverse(env)?;
+                env.push(val);
+                env.push(inverse);
+            }
+            ImplPrimitive::FindMatches => {
+                let needle = env.pop(1)?;
+                let haystack = env.pop(2)?;
+                env.push(needle.find_matches(&haystack, env)?);
+            }
+            ImplPrimitive::UndoFind => {
+                let needle = env.pop(1)?;
+                let matches = env.pop(2)?;
+                let haystack = env.pop(3)?;
+                env.push(needle.undo_find(matches, haystack, env)?);
+            }
             ImplPrimitive::UndoFirst => {
                 let into = env.pop(1)?;
                 let from = env.pop(2)?;
@@ -897,6 +1198,9 @@ impl ImplPrimitive {
             }
             ImplPrimitive::UnWhere => env.monadic_ref_env(Value::unwhere)?,
             ImplPrimitive::UnUtf => env.monadic_ref_env(Value::unutf8)?,
+            ImplPrimitive::UnBase64 => env.monadic_ref_env(Value::unbase64)?,
+            ImplPrimitive::UnHex => env.monadic_ref_env(Value::unhex)?,
+            ImplPrimitive::UnUrl => env.monadic_ref_env(Value::unurl)?,
             ImplPrimitive::UnBits => env.monadic_ref_env(Value::unbits)?,
             ImplPrimitive::UndoPartition1 => loops::undo_partition_part1(env)?,
             ImplPrimitive::UndpPartition2 => loops::undo_partition_part2(env)?,
@@ -994,18 +1298,20 @@ impl ImplPrimitive {
             ImplPrimitive::LastMinIndex => env.monadic_ref_env(Value::last_min_index)?,
             ImplPrimitive::LastMaxIndex => env.monadic_ref_env(Value::last_max_index)?,
             ImplPrimitive::FirstWhere => env.monadic_ref_env(Value::first_where)?,
-            ImplPrimitive::SortUp => env.monadic_mut(Value::sort_up)?,
-            ImplPrimitive::SortDown => env.monadic_mut(Value::sort_down)?,
+            ImplPrimitive::SortUp => env.monadic_mut_env(Value::sort_up)?,
+            ImplPrimitive::SortDown => env.monadic_mut_env(Value::sort_down)?,
             ImplPrimitive::ReduceContent => reduce::reduce_content(env)?,
             ImplPrimitive::ReduceTable => table::reduce_table(env)?,
             ImplPrimitive::ReplaceRand => {
                 env.pop(1)?;
-                env.push(random());
+                let n = env.random();
+                env.push(n);
             }
             ImplPrimitive::ReplaceRand2 => {
                 env.pop(1)?;
                 env.pop(2)?;
-                env.push(random());
+                let n = env.random();
+                env.push(n);
             }
             ImplPrimitive::Adjacent => reduce::adjacent(env)?,
             ImplPrimitive::CountUnique => env.monadic_ref(Value::count_unique)?,
@@ -1014,7 +1320,7 @@ impl ImplPrimitive {
                 let n = env
                     .pop(1)?
                     .as_nat(env, "Repetition count must be a natural number")?;
-                let arr: Array<f64> = (0..n).map(|_| random()).collect();
+                let arr: Array<f64> = (0..n).map(|_| env.random()).collect();
                 env.end_array(false, Some(arr.into()))?;
             }
             &ImplPrimitive::ReduceDepth(depth) => reduce::reduce(depth, env)?,
@@ -1061,7 +1367,9 @@ fn regex(env: &mut Uiua) -> UiuaResult {
     })
 }
 
-/// Generate a random number, equivalent to [`Primitive::Rand`]
+/// Generate a random number between 0 and 1
+///
+/// Unlike [`Primitive::Rand`], this does not use the interpreter's generator, so it is not affected by [`Primitive::Seed`]
 pub fn random() -> f64 {
     thread_local! {
         static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
@@ -1069,6 +1377,15 @@ pub fn random() -> f64 {
     RNG.with(|rng| rng.borrow_mut().gen::<f64>())
 }
 
+/// Get the nanoseconds since the monotonic clock was first read
+fn clock_nanos() -> f64 {
+    static START: OnceLock<instant::Instant> = OnceLock::new();
+    START
+        .get_or_init(instant::Instant::now)
+        .elapsed()
+        .as_nanos() as f64
+}
+
 fn trace(env: &mut Uiua, inverse: bool) -> UiuaResult {
     let val = env.pop(1)?;
     let span: String = if inverse {
@@ -1077,8 +1394,10 @@ fn trace(env: &mut Uiua, inverse: bool) -> UiuaResult {
         env.span().to_string()
     };
     let max_line_len = span.chars().count() + 2;
-    let item_lines =
-        format_trace_item_lines(val.show().lines().map(Into::into).collect(), max_line_len);
+    let item_lines = format_trace_item_lines(
+        env.show_value(&val).lines().map(Into::into).collect(),
+        max_line_len,
+    );
     env.push(val);
     env.rt.backend.print_str_trace(&format!("┌╴{span}\n"));
     for line in item_lines {
@@ -1107,10 +1426,12 @@ fn both_trace(env: &mut Uiua, inverse: bool) -> UiuaResult {
         format!("{}{} {}", Primitive::Both, Primitive::Trace, env.span())
     };
     let max_line_len = span.chars().count() + 2;
-    let mut item_lines =
-        format_trace_item_lines(b.show().lines().map(Into::into).collect(), max_line_len);
+    let mut item_lines = format_trace_item_lines(
+        env.show_value(&b).lines().map(Into::into).collect(),
+        max_line_len,
+    );
     item_lines.extend(format_trace_item_lines(
-        a.show().lines().map(Into::into).collect(),
+        env.show_value(&a).lines().map(Into::into).collect(),
         max_line_len,
     ));
     env.push(b);
@@ -1543,8 +1864,9 @@ mod tests {
             for line in &prim.doc().lines {
                 if let PrimDocLine::Example(ex) = line {
                     if [
-                        "&sl", "&tcpc", "&tlsc", "&ast", "&clset", "&fo", "&fc", "&fde", "&ftr",
-                        "&fld", "&fif", "&fras",
+                        "&sl", "&tcpc", "&tlsc", "&http", "&udp", "&ast", "&clset", "&fo", "&fc",
+                        "&fde", "&ftr", "&fld", "&fif", "&fras", "&fwalk", "&fmeta", "&fwatch",
+                        "&ch", "&log",
                     ]
                     .iter()
                     .any(|prim| ex.input.contains(prim))
@@ -0,0 +1,213 @@
+//! Caching the results of pure function calls across runs
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    function::{instrs_are_pure, Function, Instr, Purity},
+    Assembly, BindingKind, Value,
+};
+
+/// Statistics about a recompute cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecomputeStats {
+    /// The number of calls whose results were found in the cache
+    pub hits: usize,
+    /// The number of cacheable calls whose results were not in the cache
+    pub misses: usize,
+    /// The number of cached results
+    pub entries: usize,
+    /// The total number of array elements in the cached results
+    pub elements: usize,
+}
+
+/// A key for a cached call
+///
+/// This is a hash of the function's code, including the code and values it refers to,
+/// and a hash of each of its arguments.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct RecomputeKey(u64, Vec<u64>);
+
+struct Entry {
+    outputs: Vec<Value>,
+    elements: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    max_elements: usize,
+    entries: HashMap<RecomputeKey, Entry>,
+    /// Code hashes of functions in the current assembly, or `None` if they are impure
+    code_hashes: HashMap<Function, Option<u64>>,
+    tick: u64,
+    stats: RecomputeStats,
+}
+
+/// A size-bounded, least-recently-used cache of the outputs of pure named functions
+///
+/// Cached results are kept between runs, so re-running a program only recomputes
+/// calls whose code or arguments changed.
+#[derive(Clone, Default)]
+pub(crate) struct RecomputeCache {
+    inner: Arc<Mutex<CacheInner>>,
+}
+
+impl RecomputeCache {
+    pub fn new(max_elements: usize) -> Self {
+        RecomputeCache {
+            inner: Arc::new(Mutex::new(CacheInner {
+                max_elements,
+                ..CacheInner::default()
+            })),
+        }
+    }
+    /// Forget code hashes from the previous assembly
+    pub fn new_assembly(&self) {
+        self.inner.lock().code_hashes.clear();
+    }
+    /// Get the key for calling a function with some arguments
+    ///
+    /// Returns `None` if the function's results cannot be cached.
+    pub fn key(&self, f: &Function, args: &[Value], asm: &Assembly) -> Option<RecomputeKey> {
+        if f.recursive {
+            return None;
+        }
+        let mut inner = self.inner.lock();
+        let code_hash = inner.code_hashes.entry(f.clone()).or_insert_with(|| {
+            let instrs = f.instrs(asm);
+            instrs_are_pure(instrs, asm, Purity::Pure).then(|| {
+                let mut hasher = DefaultHasher::new();
+                f.signature().hash(&mut hasher);
+                hash_code(instrs, asm, &mut hasher, &mut HashSet::new());
+                hasher.finish()
+            })
+        });
+        let code_hash = (*code_hash)?;
+        let arg_hashes = args
+            .iter()
+            .map(|arg| {
+                let mut hasher = DefaultHasher::new();
+                arg.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        Some(RecomputeKey(code_hash, arg_hashes))
+    }
+    /// Get the cached outputs for a key
+    pub fn get(&self, key: &RecomputeKey) -> Option<Vec<Value>> {
+        let mut inner = self.inner.lock();
+        inner.tick += 1;
+        let tick = inner.tick;
+        if let Some(entry) = inner.entries.get_mut(key) {
+            entry.last_used = tick;
+            let outputs = entry.outputs.clone();
+            inner.stats.hits += 1;
+            Some(outputs)
+        } else {
+            inner.stats.misses += 1;
+            None
+        }
+    }
+    /// Cache the outputs for a key, evicting the least recently used results if necessary
+    pub fn insert(&self, key: RecomputeKey, outputs: Vec<Value>) {
+        let elements: usize = outputs.iter().map(Value::element_count).sum();
+        let mut inner = self.inner.lock();
+        if elements > inner.max_elements {
+            return;
+        }
+        while inner.stats.elements + elements > inner.max_elements {
+            let Some(oldest) = (inner.entries.iter())
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            let removed = inner.entries.remove(&oldest).unwrap();
+            inner.stats.elements -= removed.elements;
+        }
+        inner.tick += 1;
+        let entry = Entry {
+            outputs,
+            elements,
+            last_used: inner.tick,
+        };
+        if let Some(replaced) = inner.entries.insert(key, entry) {
+            inner.stats.elements -= replaced.elements;
+        }
+        inner.stats.elements += elements;
+        inner.stats.entries = inner.entries.len();
+    }
+    pub fn stats(&self) -> RecomputeStats {
+        self.inner.lock().stats
+    }
+}
+
+/// Hash some instructions along with the code and values of the bindings they refer to
+///
+/// Instruction hashes ignore spans, so moving code around does not change its hash.
+fn hash_code(
+    instrs: &[Instr],
+    asm: &Assembly,
+    hasher: &mut impl Hasher,
+    seen: &mut HashSet<usize>,
+) {
+    for instr in instrs {
+        instr.hash(hasher);
+        match instr {
+            Instr::PushFunc(f) => hash_code(f.instrs(asm), asm, hasher, seen),
+            &Instr::CallGlobal { index, .. } if seen.insert(index) => {
+                match asm.bindings.get(index).map(|binding| &binding.kind) {
+                    Some(BindingKind::Const(Some(val))) => val.hash(hasher),
+                    Some(BindingKind::Func(f)) => hash_code(f.instrs(asm), asm, hasher, seen),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn recompute_cache() {
+        fn run(env: &mut Uiua, code: &str) {
+            env.compile_run(|comp| comp.pre_eval_mode(PreEvalMode::Lazy).load_str(code))
+                .unwrap();
+        }
+        let mut env = Uiua::with_safe_sys().with_recompute_cache(1000);
+        // Functions short enough to be inlined are never cached
+        let code = "G ← ×2\nF ← /+G-1+1-1+1-1+1⇡\nR ← +⚂\nF 10 F 10 F 20 R 1";
+        run(&mut env, code);
+        let stats = env.recompute_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert_eq!(
+            env.take_stack()[1..],
+            [Value::from(380), 90.into(), 90.into()]
+        );
+
+        // Re-running only recomputes calls whose code changed
+        run(&mut env, code);
+        let stats = env.recompute_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (4, 2));
+        env.take_stack();
+        run(&mut env, &code.replace("×2", "×3"));
+        let stats = env.recompute_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (5, 4));
+        assert_eq!(env.take_stack()[1..3], [Value::from(570), 135.into()]);
+
+        // Results that are too large are not cached
+        let mut env = Uiua::with_safe_sys().with_recompute_cache(5);
+        run(&mut env, "F ← -1+1-1+1-1+1⇡\nF 10 F 10 F 3 F 3");
+        let stats = env.recompute_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+    }
+}
@@ -0,0 +1,149 @@
+//! A read-eval-print loop session
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{
+    lsp::{completions_with_compiler, Completion},
+    Compiler, Diagnostic, Uiua, UiuaError, UiuaResult, Value,
+};
+
+/// The result of evaluating a line in a [`Repl`]
+#[derive(Debug, Clone)]
+pub struct ReplOutput {
+    /// The values the line left on the stack, from bottom to top
+    pub values: Vec<Value>,
+    /// The values as they are shown by the interpreter
+    pub shown: Vec<String>,
+    /// Diagnostics emitted while compiling the line
+    pub diagnostics: Vec<Diagnostic>,
+    /// The error, if the line failed
+    pub error: Option<UiuaError>,
+}
+
+/// A read-eval-print loop session
+///
+/// Each line is compiled and run on its own, but bindings are kept between lines.
+/// Values left on the stack are returned and added to the session's history.
+///
+/// The history can be accessed from Uiua code with the `Ans` function.
+/// `Ans 1` is the most recent result, `Ans 2` is the one before it, and so on.
+///
+/// ```
+/// # use uiua::*;
+/// let mut repl = Repl::new(Uiua::with_safe_sys(), Compiler::new()).unwrap();
+/// repl.eval("X ← 5");
+/// assert_eq!(repl.eval("×2 X").shown, ["10"]);
+/// assert_eq!(repl.eval("+1 Ans 1").shown, ["11"]);
+/// assert!(repl.eval("Y").error.is_some());
+/// ```
+pub struct Repl {
+    env: Uiua,
+    compiler: Compiler,
+    history: Arc<Mutex<Vec<Value>>>,
+}
+
+impl Repl {
+    /// Start a session with an interpreter and a compiler
+    ///
+    /// The compiler may already have bindings, such as from a loaded file.
+    /// Diagnostics are collected instead of printed.
+    pub fn new(env: Uiua, mut compiler: Compiler) -> UiuaResult<Self> {
+        let history = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let ans_history = history.clone();
+        compiler.create_bind_function("Ans", (1, 1), move |env| {
+            let n = env.pop_nat()?;
+            let history = ans_history.lock();
+            let value = (n.checked_sub(1))
+                .and_then(|i| history.iter().rev().nth(i))
+                .ok_or_else(|| {
+                    env.error(format!(
+                        "There is no result {n} back. There are only {} results",
+                        history.len()
+                    ))
+                })?;
+            env.push(value.clone());
+            Ok(())
+        })?;
+        compiler.print_diagnostics(false);
+        Ok(Repl {
+            env,
+            compiler,
+            history,
+        })
+    }
+    /// Compile and run a line of code
+    ///
+    /// If the line fails, its bindings are discarded.
+    pub fn eval(&mut self, code: &str) -> ReplOutput {
+        let backup = self.compiler.clone();
+        let res = (self.compiler.load_str(code).map(drop))
+            .and_then(|()| self.env.run_asm(self.compiler.finish()));
+        let diagnostics = self.compiler.take_diagnostics().into_iter().collect();
+        let values = self.env.take_stack();
+        let mut asm = self.env.take_asm();
+        let error = match res {
+            Ok(()) => {
+                asm.remove_top_level();
+                *self.compiler.assembly_mut() = asm;
+                self.history.lock().extend(values.iter().cloned());
+                None
+            }
+            Err(e) => {
+                self.compiler = backup;
+                Some(e)
+            }
+        };
+        let shown = values.iter().map(|val| self.env.show_value(val)).collect();
+        ReplOutput {
+            values,
+            shown,
+            diagnostics,
+            error,
+        }
+    }
+    /// Get completions for the word before a cursor in a line
+    ///
+    /// Bindings from previous lines are candidates as well.
+    /// See [`lsp::completions`](crate::lsp::completions).
+    pub fn completions(&self, line: &str, cursor: usize) -> Vec<Completion> {
+        completions_with_compiler(line, cursor, &self.compiler)
+    }
+    /// Get the results of all successful lines, from oldest to newest
+    pub fn history(&self) -> Vec<Value> {
+        self.history.lock().clone()
+    }
+    /// Get a reference to the interpreter
+    pub fn env(&self) -> &Uiua {
+        &self.env
+    }
+    /// Get a mutable reference to the interpreter
+    pub fn env_mut(&mut self) -> &mut Uiua {
+        &mut self.env
+    }
+    /// Get a reference to the compiler
+    pub fn compiler(&self) -> &Compiler {
+        &self.compiler
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn repl_session() {
+        let mut repl = Repl::new(Uiua::with_safe_sys(), Compiler::new()).unwrap();
+        let output = repl.eval("F ← +1\nF 1 F 2");
+        assert!(output.error.is_none());
+        assert_eq!(output.values, [Value::from(3), 2.into()]);
+
+        // A failing line's bindings are discarded
+        assert!(repl.eval("G ← 5\nH").error.is_some());
+        assert!(repl.eval("G").error.is_some());
+
+        assert_eq!(repl.eval("F Ans 2").shown, ["4"]);
+        assert_eq!(repl.history(), [Value::from(3), 2.into(), 4.into()]);
+    }
+}
@@ -0,0 +1,653 @@
+//! Recording and replaying the results of system functions
+
+use std::{
+    any::Any,
+    collections::VecDeque,
+    mem::take,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use parking_lot::Mutex;
+use serde::*;
+
+use crate::{
+    AudioStreamFn, FfiType, FileKind, FileMetadata, Handle, HttpRequest, HttpResponse,
+    IntoSysBackend, LogRecord, SysBackend, Timestamp, Uiua, Value,
+};
+
+/// A log of the results of calls to a [`SysBackend`]
+///
+/// It is made by a [`RecordingSys`] and played back by a [`ReplaySys`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SysLog {
+    /// The calls, in the order they were made
+    pub entries: Vec<SysLogEntry>,
+}
+
+/// A single call in a [`SysLog`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SysLogEntry {
+    /// The name of the [`SysBackend`] method that was called
+    pub call: String,
+    /// The result of the call
+    pub result: SysLogValue,
+}
+
+/// A recorded result in a [`SysLog`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum SysLogValue {
+    Unit,
+    None,
+    Bool(bool),
+    Int(i64),
+    Num(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    List(Vec<SysLogValue>),
+    Err(String),
+}
+
+impl SysLog {
+    /// Serialize the log to JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    /// Deserialize a log from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
+
+/// A value that can be stored in a [`SysLog`]
+trait Recordable: Sized {
+    fn to_log(&self) -> SysLogValue;
+    fn from_log(value: SysLogValue) -> Option<Self>;
+}
+
+/// A value that can stand in for a result that could not be replayed
+trait Replayable: Recordable {
+    fn replay_failed(message: String) -> Self;
+}
+
+impl Recordable for () {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Unit
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        matches!(value, SysLogValue::Unit).then_some(())
+    }
+}
+
+impl Recordable for bool {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Bool(*self)
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Replayable for bool {
+    fn replay_failed(_: String) -> Self {
+        false
+    }
+}
+
+impl Recordable for f64 {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Num(*self)
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::Num(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+impl Replayable for f64 {
+    fn replay_failed(_: String) -> Self {
+        0.0
+    }
+}
+
+macro_rules! recordable_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Recordable for $ty {
+                fn to_log(&self) -> SysLogValue {
+                    SysLogValue::Int(*self as i64)
+                }
+                fn from_log(value: SysLogValue) -> Option<Self> {
+                    match value {
+                        SysLogValue::Int(i) => Some(i as $ty),
+                        _ => None,
+                    }
+                }
+            }
+
+            impl Replayable for $ty {
+                fn replay_failed(_: String) -> Self {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+recordable_int!(i32, i64, u16, u32, u64, usize);
+
+impl Recordable for String {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Str(self.clone())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl Recordable for Vec<u8> {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Bytes(self.clone())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl Recordable for Vec<String> {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::List(self.iter().map(Recordable::to_log).collect())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::List(items) => items.into_iter().map(String::from_log).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl Recordable for Vec<(String, String)> {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::List(self.iter().map(Recordable::to_log).collect())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::List(items) => items.into_iter().map(Recordable::from_log).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl Replayable for Vec<(String, String)> {
+    fn replay_failed(_: String) -> Self {
+        Vec::new()
+    }
+}
+
+macro_rules! recordable_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Recordable),*> Recordable for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn to_log(&self) -> SysLogValue {
+                let ($($name,)*) = self;
+                SysLogValue::List(vec![$($name.to_log()),*])
+            }
+            #[allow(non_snake_case)]
+            fn from_log(value: SysLogValue) -> Option<Self> {
+                let SysLogValue::List(items) = value else {
+                    return None;
+                };
+                let mut items = items.into_iter();
+                $(let $name = $name::from_log(items.next()?)?;)*
+                items.next().is_none().then_some(($($name,)*))
+            }
+        }
+    };
+}
+
+recordable_tuple!(A, B);
+recordable_tuple!(A, B, C);
+
+impl Recordable for Handle {
+    fn to_log(&self) -> SysLogValue {
+        self.0.to_log()
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        u64::from_log(value).map(Handle)
+    }
+}
+
+impl Recordable for SocketAddr {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Str(self.to_string())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        String::from_log(value)?.parse().ok()
+    }
+}
+
+impl Recordable for PathBuf {
+    fn to_log(&self) -> SysLogValue {
+        SysLogValue::Str(self.to_string_lossy().into_owned())
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        String::from_log(value).map(Into::into)
+    }
+}
+
+impl Recordable for FileMetadata {
+    fn to_log(&self) -> SysLogValue {
+        (self.size, self.modified.0, self.kind.name().to_string()).to_log()
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        let (size, modified, kind) = <(u64, i64, String)>::from_log(value)?;
+        let kind = match kind.as_str() {
+            "file" => FileKind::File,
+            "directory" => FileKind::Directory,
+            "symlink" => FileKind::Symlink,
+            _ => return None,
+        };
+        Some(FileMetadata {
+            size,
+            modified: Timestamp(modified),
+            kind,
+        })
+    }
+}
+
+impl Recordable for HttpResponse {
+    fn to_log(&self) -> SysLogValue {
+        (self.status, self.headers.clone(), self.body.clone()).to_log()
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        let (status, headers, body) = Recordable::from_log(value)?;
+        Some(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+impl<T: Recordable> Recordable for Option<T> {
+    fn to_log(&self) -> SysLogValue {
+        match self {
+            Some(value) => value.to_log(),
+            None => SysLogValue::None,
+        }
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::None => Some(None),
+            value => T::from_log(value).map(Some),
+        }
+    }
+}
+
+impl<T: Recordable> Replayable for Option<T> {
+    fn replay_failed(_: String) -> Self {
+        None
+    }
+}
+
+impl<T: Recordable> Recordable for Result<T, String> {
+    fn to_log(&self) -> SysLogValue {
+        match self {
+            Ok(value) => value.to_log(),
+            Err(e) => SysLogValue::Err(e.clone()),
+        }
+    }
+    fn from_log(value: SysLogValue) -> Option<Self> {
+        match value {
+            SysLogValue::Err(e) => Some(Err(e)),
+            value => T::from_log(value).map(Ok),
+        }
+    }
+}
+
+impl<T: Recordable> Replayable for Result<T, String> {
+    fn replay_failed(message: String) -> Self {
+        Err(message)
+    }
+}
+
+/// A system backend that records the results of calls to another backend
+///
+/// Time, randomness, input, files, networking, and commands are all recorded.
+/// Output is passed through and not recorded.
+/// The [`SysLog`] it makes can be played back with a [`ReplaySys`].
+///
+/// Calls are recorded in the order they are made,
+/// so programs that do IO on several threads at once may not replay correctly.
+pub struct RecordingSys {
+    inner: Arc<dyn SysBackend>,
+    log: Mutex<SysLog>,
+}
+
+impl RecordingSys {
+    /// Record the results of calls to a backend
+    pub fn new(inner: impl IntoSysBackend) -> Self {
+        RecordingSys {
+            inner: inner.into_sys_backend(),
+            log: Mutex::new(SysLog::default()),
+        }
+    }
+    /// Get the log of the calls made so far
+    pub fn log(&self) -> SysLog {
+        self.log.lock().clone()
+    }
+    fn record<T: Recordable>(&self, call: &str, result: T) -> T {
+        self.log.lock().entries.push(SysLogEntry {
+            call: call.into(),
+            result: result.to_log(),
+        });
+        result
+    }
+}
+
+/// A system backend that plays back a [`SysLog`]
+///
+/// Each call is answered with the next entry in the log.
+/// If the program makes a call that is not next in the log,
+/// the call fails and the first such failure is kept in [`ReplaySys::error`].
+///
+/// Output is captured like in [`SafeSys`](crate::SafeSys).
+/// Channels and FFI are not supported.
+pub struct ReplaySys {
+    log: Mutex<VecDeque<SysLogEntry>>,
+    error: Mutex<Option<String>>,
+    stdout: Mutex<Vec<u8>>,
+    stderr: Mutex<Vec<u8>>,
+}
+
+impl ReplaySys {
+    /// Play back a log
+    pub fn new(log: SysLog) -> Self {
+        ReplaySys {
+            log: Mutex::new(log.entries.into()),
+            error: Mutex::new(None),
+            stdout: Mutex::new(Vec::new()),
+            stderr: Mutex::new(Vec::new()),
+        }
+    }
+    /// Get the first call that could not be replayed, if any
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().clone()
+    }
+    /// Get the number of entries in the log that have not been replayed
+    pub fn remaining(&self) -> usize {
+        self.log.lock().len()
+    }
+    /// Take the captured stdout
+    pub fn take_stdout(&self) -> Vec<u8> {
+        take(&mut *self.stdout.lock())
+    }
+    /// Take the captured stderr
+    pub fn take_stderr(&self) -> Vec<u8> {
+        take(&mut *self.stderr.lock())
+    }
+    fn replay<T: Replayable>(&self, call: &str) -> T {
+        let entry = self.log.lock().pop_front();
+        let message = match entry {
+            Some(entry) if entry.call == call => match T::from_log(entry.result) {
+                Some(result) => return result,
+                None => format!("The recorded result of `{call}` has the wrong type"),
+            },
+            Some(entry) => format!(
+                "Expected a call to `{}` to replay, but the program called `{call}`",
+                entry.call
+            ),
+            None => format!("There are no more calls to replay, but the program called `{call}`"),
+        };
+        self.error.lock().get_or_insert_with(|| message.clone());
+        T::replay_failed(message)
+    }
+}
+
+macro_rules! recorded_calls {
+    (
+        impl RecordingSys { $($record_item:tt)* }
+        impl ReplaySys { $($replay_item:tt)* }
+        $(
+            $(#[$attr:meta])*
+            fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;
+        )*
+    ) => {
+        impl SysBackend for RecordingSys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            $($record_item)*
+            $(
+                $(#[$attr])*
+                fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    self.record(stringify!($name), self.inner.$name($($arg),*))
+                }
+            )*
+        }
+
+        #[allow(unused_variables)]
+        impl SysBackend for ReplaySys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            $($replay_item)*
+            $(
+                $(#[$attr])*
+                fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    self.replay(stringify!($name))
+                }
+            )*
+        }
+    };
+}
+
+recorded_calls! {
+    impl RecordingSys {
+        fn save_error_color(&self, message: String, colored: String) {
+            self.inner.save_error_color(message, colored)
+        }
+        fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+            self.inner.print_str_stdout(s)
+        }
+        fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+            self.inner.print_str_stderr(s)
+        }
+        fn print_str_trace(&self, s: &str) {
+            self.inner.print_str_trace(s)
+        }
+        fn random_seed(&self) -> Option<u64> {
+            let seed = self.inner.random_seed().unwrap_or_else(rand::random);
+            self.record("random_seed", Some(seed))
+        }
+        fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+            self.record("stream_audio", self.inner.stream_audio(f))
+        }
+        fn exit(&self, status: i32) -> Result<(), String> {
+            self.record::<Result<(), String>>("exit", Ok(()))?;
+            self.inner.exit(status)
+        }
+        fn create_channel(&self, capacity: Option<usize>) -> Result<Handle, String> {
+            self.inner.create_channel(capacity)
+        }
+        fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+            self.inner.channel_send(handle, value)
+        }
+        fn channel_recv(&self, handle: Handle) -> Result<Value, String> {
+            self.inner.channel_recv(handle)
+        }
+        fn channel_try_recv(&self, handle: Handle) -> Result<Option<Value>, String> {
+            self.inner.channel_try_recv(handle)
+        }
+        fn ffi(
+            &self,
+            file: &str,
+            result_ty: FfiType,
+            name: &str,
+            arg_tys: &[FfiType],
+            args: &[Value],
+        ) -> Result<Value, String> {
+            self.inner.ffi(file, result_ty, name, arg_tys, args)
+        }
+        fn mem_copy(&self, ty: FfiType, ptr: *const (), len: usize) -> Result<Value, String> {
+            self.inner.mem_copy(ty, ptr, len)
+        }
+        fn mem_free(&self, ptr: *const ()) -> Result<(), String> {
+            self.inner.mem_free(ptr)
+        }
+        fn breakpoint(&self, env: &Uiua) -> Result<bool, String> {
+            self.inner.breakpoint(env)
+        }
+        fn log(&self, record: LogRecord) -> Result<(), String> {
+            self.inner.log(record)
+        }
+    }
+    impl ReplaySys {
+        fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+            self.stdout.lock().extend_from_slice(s.as_bytes());
+            Ok(())
+        }
+        fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+            self.stderr.lock().extend_from_slice(s.as_bytes());
+            Ok(())
+        }
+        fn random_seed(&self) -> Option<u64> {
+            self.replay("random_seed")
+        }
+        fn stream_audio(&self, _: AudioStreamFn) -> Result<(), String> {
+            self.replay("stream_audio")
+        }
+        fn exit(&self, status: i32) -> Result<(), String> {
+            self.replay::<Result<(), String>>("exit")?;
+            Err(format!("The program exited with status {status}"))
+        }
+        fn breakpoint(&self, _: &Uiua) -> Result<bool, String> {
+            Ok(true)
+        }
+    }
+    fn now(&self) -> f64;
+    fn scan_line_stdin(&self) -> Result<Option<String>, String>;
+    fn scan_stdin(&self, count: usize) -> Result<Vec<u8>, String>;
+    fn scan_stdin_to_end(&self) -> Result<Vec<u8>, String>;
+    fn scan_until_stdin(&self, delim: &[u8]) -> Result<Vec<u8>, String>;
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String>;
+    fn var(&self, name: &str) -> Option<String>;
+    fn vars(&self) -> Vec<(String, String)>;
+    fn term_size(&self) -> Result<(usize, usize), String>;
+    fn file_exists(&self, path: &str) -> bool;
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String>;
+    fn is_file(&self, path: &str) -> Result<bool, String>;
+    fn walk_dir(&self, path: &str) -> Result<Vec<String>, String>;
+    fn file_metadata(&self, path: &str) -> Result<FileMetadata, String>;
+    fn watch_files(&self, paths: &[String]) -> Result<Vec<String>, String>;
+    fn delete(&self, path: &str) -> Result<(), String>;
+    fn trash(&self, path: &str) -> Result<(), String>;
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String>;
+    fn read_all(&self, handle: Handle) -> Result<Vec<u8>, String>;
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String>;
+    fn read_line(&self, handle: Handle) -> Result<Option<String>, String>;
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String>;
+    fn create_file(&self, path: &Path) -> Result<Handle, String>;
+    fn open_file(&self, path: &Path, write: bool) -> Result<Handle, String>;
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String>;
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String>;
+    fn clipboard(&self) -> Result<String, String>;
+    fn set_clipboard(&self, contents: &str) -> Result<(), String>;
+    fn sleep(&self, seconds: f64) -> Result<(), String>;
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String>;
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String>;
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String>;
+    fn audio_sample_rate(&self) -> u32;
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String>;
+    fn tls_listen(&self, addr: &str, cert: &[u8], key: &[u8]) -> Result<Handle, String>;
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String>;
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String>;
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String>;
+    fn tcp_addr(&self, handle: Handle) -> Result<SocketAddr, String>;
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String>;
+    fn tcp_set_read_timeout(&self, handle: Handle, timeout: Option<Duration>) -> Result<(), String>;
+    fn tcp_set_write_timeout(&self, handle: Handle, timeout: Option<Duration>) -> Result<(), String>;
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String>;
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String>;
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, SocketAddr), String>;
+    fn close(&self, handle: Handle) -> Result<(), String>;
+    fn invoke(&self, path: &str) -> Result<(), String>;
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String>;
+    fn run_command_capture(&self, command: &str, args: &[&str]) -> Result<(i32, String, String), String>;
+    fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String>;
+    fn run_command_stream_stderr(&self, command: &str, args: &[&str]) -> Result<(Handle, Handle), String>;
+    fn close_command_stdin(&self, handle: Handle) -> Result<(), String>;
+    fn wait_command(&self, handle: Handle) -> Result<i32, String>;
+    fn change_directory(&self, path: &str) -> Result<(), String>;
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String>;
+    fn http_request(&self, request: HttpRequest) -> Result<HttpResponse, String>;
+    fn load_git_module(&self, url: &str, branch: Option<&str>) -> Result<PathBuf, String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn record_replay() {
+        let path = std::env::temp_dir().join(format!("uiua_replay_{}", std::process::id()));
+        std::fs::write(&path, "recorded").unwrap();
+        let code = format!(
+            "now ⚂ [⍥⚂3] &fras {:?}\n&p \"done\"",
+            path.to_string_lossy()
+        );
+        let mut env = Uiua::with_backend(RecordingSys::new(SafeSys::new()));
+        assert!(env.run_str(&code).is_err());
+        let mut env = Uiua::with_backend(RecordingSys::new(NativeSys));
+        env.run_str(&code).unwrap();
+        let recorded = env.take_stack();
+        let log = env.downcast_backend::<RecordingSys>().unwrap().log();
+        std::fs::remove_file(&path).unwrap();
+
+        let log = SysLog::from_json(&log.to_json()).unwrap();
+        let mut env = Uiua::with_backend(ReplaySys::new(log));
+        env.run_str(&code).unwrap();
+        assert_eq!(env.take_stack(), recorded);
+        let sys = env.downcast_backend::<ReplaySys>().unwrap();
+        assert_eq!(sys.error(), None);
+        assert_eq!(sys.remaining(), 0);
+        assert_eq!(sys.take_stdout(), b"done\n");
+
+        let mut env = Uiua::with_backend(ReplaySys::new(SysLog::default()));
+        assert!(env.run_str(&code).is_err());
+        let sys = env.downcast_backend::<ReplaySys>().unwrap();
+        assert!(sys.error().unwrap().contains("random_seed"));
+
+        // Numbers are logged exactly
+        let log = SysLog {
+            entries: vec![SysLogEntry {
+                call: "now".into(),
+                result: SysLogValue::Num(1728226646.5716615),
+            }],
+        };
+        assert_eq!(SysLog::from_json(&log.to_json()).unwrap(), log);
+    }
+}
@@ -6,12 +6,12 @@ use std::{
     collections::HashMap,
     fmt,
     hash::Hash,
-    mem::{size_of, take},
+    mem::{replace, size_of, take},
     panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     str::FromStr,
     sync::{
-        atomic::{self, AtomicBool},
+        atomic::{self, AtomicBool, AtomicUsize},
         Arc,
     },
 };
@@ -22,15 +22,21 @@ use instant::Duration;
 use thread_local::ThreadLocal;
 
 use crate::{
-    algorithm::{self, invert, validate_size_impl},
+    algorithm::{self, invert, padding::PadMode, random::Rng, validate_size_impl},
     array::Array,
     boxed::Boxed,
     check::instrs_temp_signatures,
+    coverage::{CoverageCounter, CoverageReport},
+    debug::StepHook,
     function::*,
     lex::Span,
+    recompute::{RecomputeCache, RecomputeStats},
+    trace::{PrimStats, PrimTrace, TracedPrim, Tracer},
+    usage::{ResourceUsage, UsageCounter},
     value::Value,
     Assembly, BindingKind, Compiler, Complex, Ident, Inputs, IntoSysBackend, LocalName, Primitive,
-    SafeSys, SysBackend, SysOp, TraceFrame, UiuaError, UiuaResult, VERSION,
+    SafeSys, SysBackend, SysOp, SysPolicy, TerminalGraphics, Timestamp, TraceFrame, UiuaError,
+    UiuaResult, VERSION,
 };
 
 /// The Uiua interpreter
@@ -53,15 +59,43 @@ pub(crate) struct Runtime {
     /// The stack height at the start of each array currently being built
     pub(crate) array_stack: Vec<usize>,
     /// The call stack
-    call_stack: Vec<StackFrame>,
+    pub(crate) call_stack: Vec<StackFrame>,
     /// The stack for tracking recursion points
     recur_stack: Vec<usize>,
     /// The fill stack
     fill_stack: Vec<Fill>,
+    /// Whether reductions and statistics skip `NaN`s
+    skip_nan: bool,
+    /// Whether pervasive functions descend into boxes on both sides
+    deep_box: bool,
+    /// Whether interpolation is cubic rather than linear
+    cubic: bool,
     /// A limit on the execution duration in milliseconds
     pub(crate) execution_limit: Option<f64>,
     /// The time at which execution started
     pub(crate) execution_start: f64,
+    /// A limit on the number of elements in a single array
+    pub(crate) array_size_limit: Option<usize>,
+    /// A limit on the total number of array elements allocated during execution
+    pub(crate) allocation_limit: Option<usize>,
+    /// The width at which shown arrays are truncated
+    pub(crate) grid_width: Option<usize>,
+    /// The protocol used to draw image-like arrays shown by the interpreter
+    pub(crate) graphics: Option<TerminalGraphics>,
+    /// Whether image-like arrays shown by the interpreter are previewed as text
+    pub(crate) image_previews: bool,
+    /// The number of array elements allocated during execution
+    allocated_elements: Arc<AtomicUsize>,
+    /// The token for cancelling execution
+    pub(crate) cancel: CancelToken,
+    /// The primitive tracer
+    tracer: Option<Tracer>,
+    /// The resource usage counter
+    usage: Option<UsageCounter>,
+    /// The code coverage counter
+    pub(crate) coverage: Option<CoverageCounter>,
+    /// The function called before each primitive is executed
+    pub(crate) step_hook: Option<StepHook>,
     /// Whether to print the time taken to execute each instruction
     time_instrs: bool,
     /// The time at which the last instruction was executed
@@ -78,6 +112,15 @@ pub(crate) struct Runtime {
     pub(crate) output_comments: HashMap<usize, Vec<Vec<Value>>>,
     /// Memoized values
     pub(crate) memo: Arc<ThreadLocal<RefCell<MemoMap>>>,
+    /// Cached results of pure function calls, kept between runs
+    recompute: Option<RecomputeCache>,
+    /// The random number generator
+    pub(crate) rng: Rng,
+    /// Which capabilities system functions may use
+    pub(crate) policy: SysPolicy,
+    /// Options for running array operations on the GPU
+    #[cfg(feature = "gpu")]
+    pub(crate) gpu: Option<crate::GpuOptions>,
 }
 
 type MemoMap = HashMap<FunctionId, HashMap<Vec<Value>, Vec<Value>>>;
@@ -101,11 +144,11 @@ pub(crate) struct StackFrame {
     pub(crate) id: FunctionId,
     pub(crate) sig: Signature,
     /// The span at which the function was called
-    call_span: usize,
+    pub(crate) call_span: usize,
     /// The program counter for the function
     pub(crate) pc: usize,
     /// Additional spans for error reporting
-    spans: Vec<(usize, Option<Primitive>)>,
+    pub(crate) spans: Vec<(usize, Option<Primitive>)>,
 }
 
 #[derive(Debug, Clone)]
@@ -172,9 +215,35 @@ impl FromStr for RunMode {
     }
 }
 
+/// A handle for cancelling an evaluation, possibly from another thread
+///
+/// Get one with [`Uiua::cancel_token`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Cancel the current or next evaluation
+    ///
+    /// The evaluation fails with [`UiuaError::Interrupted`].
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+    /// Check if cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+    fn reset(&self) {
+        self.0.store(false, atomic::Ordering::Relaxed);
+    }
+}
+
 #[derive(Clone)]
 struct Fill {
-    value: Value,
+    value: Option<Value>,
+    /// A function that computes each fill value
+    by: Option<Function>,
+    /// How to pad along each axis
+    pad: Option<Arc<[PadMode]>>,
     removed: Arc<AtomicBool>,
 }
 
@@ -204,6 +273,9 @@ impl Default for Runtime {
             }],
             recur_stack: Vec::new(),
             fill_stack: Vec::new(),
+            skip_nan: false,
+            deep_box: false,
+            cubic: false,
             backend: Arc::new(SafeSys::default()),
             time_instrs: false,
             last_time: 0.0,
@@ -211,9 +283,25 @@ impl Default for Runtime {
             cli_file_path: PathBuf::new(),
             execution_limit: None,
             execution_start: 0.0,
+            array_size_limit: None,
+            allocation_limit: None,
+            grid_width: None,
+            graphics: None,
+            image_previews: false,
+            allocated_elements: Arc::new(AtomicUsize::new(0)),
+            cancel: CancelToken::default(),
+            tracer: None,
+            usage: None,
+            coverage: None,
+            step_hook: None,
             thread: ThisThread::default(),
             output_comments: HashMap::new(),
             memo: Arc::new(ThreadLocal::new()),
+            recompute: None,
+            rng: Rng::default(),
+            policy: SysPolicy::default(),
+            #[cfg(feature = "gpu")]
+            gpu: None,
         }
     }
 }
@@ -221,8 +309,13 @@ impl Default for Runtime {
 impl Uiua {
     /// Create a new Uiua runtime with the standard IO backend
     #[cfg(feature = "native_sys")]
+    ///
+    /// If stdout is a terminal, shown arrays are truncated at its width.
     pub fn with_native_sys() -> Self {
-        Self::with_backend(crate::NativeSys)
+        let mut env = Self::with_backend(crate::NativeSys);
+        env.rt.grid_width = crate::sys_native::stdout_width();
+        env.rt.graphics = crate::sys_native::stdout_graphics();
+        env
     }
     /// Create a new Uiua runtime with no IO capabilities
     pub fn with_safe_sys() -> Self {
@@ -230,9 +323,15 @@ impl Uiua {
     }
     /// Create a new Uiua runtime with a custom IO backend
     pub fn with_backend(backend: impl IntoSysBackend) -> Self {
+        let backend = backend.into_sys_backend();
+        let rng = match backend.random_seed() {
+            Some(seed) => Rng::from_seed(seed),
+            None => Rng::default(),
+        };
         Uiua {
             rt: Runtime {
-                backend: backend.into_sys_backend(),
+                backend,
+                rng,
                 ..Runtime::default()
             },
             asm: Assembly::default(),
@@ -272,6 +371,181 @@ impl Uiua {
         self.rt.execution_limit = Some(limit.as_millis() as f64);
         self
     }
+    /// Set which capabilities system functions may use
+    pub fn with_policy(mut self, policy: SysPolicy) -> Self {
+        self.rt.policy = policy;
+        self
+    }
+    /// Get the policy for system functions
+    pub fn policy(&self) -> SysPolicy {
+        self.rt.policy
+    }
+    /// Run large pervasive operations, reductions, and matrix multiplications on the GPU
+    ///
+    /// Operations fall back to the CPU if no GPU is available.
+    #[cfg(feature = "gpu")]
+    pub fn with_gpu(mut self, options: crate::GpuOptions) -> Self {
+        self.rt.gpu = Some(options);
+        self
+    }
+    /// Limit the number of elements in a single array
+    ///
+    /// Creating a larger array is an error rather than an allocation.
+    pub fn with_array_size_limit(mut self, limit: usize) -> Self {
+        self.rt.array_size_limit = Some(limit);
+        self
+    }
+    /// Limit the total number of array elements allocated during an execution
+    ///
+    /// Only allocations made by algorithms that can create large arrays
+    /// (such as `reshape`, `keep`, `windows`, and `range`) are counted.
+    pub fn with_allocation_limit(mut self, limit: usize) -> Self {
+        self.rt.allocation_limit = Some(limit);
+        self
+    }
+    /// Set the width at which arrays shown by the interpreter are truncated
+    ///
+    /// By default, only very large arrays are truncated.
+    pub fn with_grid_width(mut self, width: usize) -> Self {
+        self.rt.grid_width = Some(width);
+        self
+    }
+    /// Get the width at which arrays shown by the interpreter are truncated
+    pub fn grid_width(&self) -> Option<usize> {
+        self.rt.grid_width
+    }
+    /// Get the string representation of a value as it is shown by the interpreter
+    pub(crate) fn show_value(&self, value: &Value) -> String {
+        match self.rt.grid_width {
+            Some(width) => value.show_with_width(width),
+            None => value.show(),
+        }
+    }
+    /// Set the protocol used to draw image-like arrays when they are shown
+    ///
+    /// With the native system backend, this is set from the
+    /// [`TERMINAL_GRAPHICS_VAR`] environment variable if stdout is a terminal.
+    pub fn with_terminal_graphics(mut self, graphics: TerminalGraphics) -> Self {
+        self.rt.graphics = Some(graphics);
+        self
+    }
+    /// Get the protocol used to draw image-like arrays when they are shown
+    pub fn terminal_graphics(&self) -> Option<TerminalGraphics> {
+        self.rt.graphics
+    }
+    /// Set whether image-like arrays are shown as a compact [preview](Value::image_preview)
+    ///
+    /// This only affects values that are [displayed](Uiua::display_value).
+    /// It is disabled by default.
+    pub fn with_image_previews(mut self, enabled: bool) -> Self {
+        self.rt.image_previews = enabled;
+        self
+    }
+    /// Check whether image-like arrays are shown as a compact preview
+    pub fn image_previews(&self) -> bool {
+        self.rt.image_previews
+    }
+    /// Get the string to print to display a value
+    ///
+    /// This is like the value's shown string, but image-like arrays are drawn
+    /// with the [terminal graphics](Uiua::with_terminal_graphics) protocol if there is one.
+    /// Otherwise, they are shown as a compact preview if [enabled](Uiua::with_image_previews).
+    pub fn display_value(&self, value: &Value) -> String {
+        (self.rt.graphics)
+            .and_then(|graphics| graphics.render(value))
+            .or_else(|| {
+                (self.rt.image_previews)
+                    .then(|| value.image_preview())
+                    .flatten()
+            })
+            .unwrap_or_else(|| self.show_value(value))
+    }
+    /// Record the time taken by each primitive
+    ///
+    /// Get the totals with [`Uiua::take_prim_stats`].
+    pub fn with_prim_tracing(mut self) -> Self {
+        self.rt.tracer.get_or_insert_with(Tracer::default);
+        self
+    }
+    /// Record the time taken by each primitive and pass each record to a sink
+    ///
+    /// Totals are still available with [`Uiua::take_prim_stats`].
+    pub fn with_trace_sink(mut self, sink: impl Fn(&PrimTrace) + Send + Sync + 'static) -> Self {
+        self.rt.tracer = Some(Tracer::with_sink(sink));
+        self
+    }
+    /// Take the per-primitive totals recorded since tracing was enabled or since this was last called
+    ///
+    /// The primitives are sorted by descending total time.
+    /// This is empty if tracing is not enabled.
+    pub fn take_prim_stats(&mut self) -> Vec<(TracedPrim, PrimStats)> {
+        (self.rt.tracer.as_ref()).map_or_else(Vec::new, Tracer::take_totals)
+    }
+    /// Count the resources used by each run
+    ///
+    /// Get the counts with [`Uiua::usage`].
+    pub fn with_usage_accounting(mut self) -> Self {
+        self.rt.usage.get_or_insert_with(UsageCounter::default);
+        self
+    }
+    /// Get the resources used by the last run
+    ///
+    /// This is `None` if usage accounting is not enabled.
+    pub fn usage(&self) -> Option<ResourceUsage> {
+        let allocated = self.rt.allocated_elements.load(atomic::Ordering::Relaxed);
+        self.rt.usage.as_ref().map(|usage| usage.get(allocated))
+    }
+    /// Record which code is evaluated
+    ///
+    /// Counts are kept between runs. Get them with [`Uiua::coverage`].
+    ///
+    /// Code that the compiler evaluates ahead of time is not run, so it does not count as covered.
+    /// Compile with [`PreEvalMode::Lazy`](crate::PreEvalMode::Lazy) to avoid this.
+    pub fn with_coverage(mut self) -> Self {
+        self.rt
+            .coverage
+            .get_or_insert_with(CoverageCounter::default);
+        self
+    }
+    /// Get the coverage of the code in the current assembly
+    ///
+    /// This is `None` if coverage is not enabled.
+    pub fn coverage(&self) -> Option<CoverageReport> {
+        (self.rt.coverage.as_ref()).map(|coverage| coverage.report(&self.asm))
+    }
+    /// Cache the results of calls to pure named functions
+    ///
+    /// Results are keyed by the function's code and the hashes of its arguments,
+    /// and they are kept between runs. This is useful for hosts that re-run a
+    /// whole document when only part of it changes.
+    /// Functions that are short enough to be inlined are not cached.
+    /// The least recently used results are evicted once the cached outputs
+    /// hold more than `max_elements` array elements in total.
+    pub fn with_recompute_cache(mut self, max_elements: usize) -> Self {
+        self.rt.recompute = Some(RecomputeCache::new(max_elements));
+        self
+    }
+    /// Get statistics about the recompute cache
+    ///
+    /// This is `None` if the cache is not enabled.
+    pub fn recompute_stats(&self) -> Option<RecomputeStats> {
+        self.rt.recompute.as_ref().map(RecomputeCache::stats)
+    }
+    /// Get a token that can be used to cancel execution
+    ///
+    /// The interpreter checks the token between instructions and inside some
+    /// long-running algorithms. The token is reset when an evaluation finishes.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.rt.cancel.clone()
+    }
+    /// Seed the random number generator
+    ///
+    /// This is the same as calling `seed` with the number.
+    /// By default, it is seeded from the operating system.
+    pub fn with_seed(mut self, seed: f64) -> Self {
+        self.seed_rng(seed);
+        self
+    }
     /// Set the command line arguments
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.rt.cli_arguments = args;
@@ -299,7 +573,7 @@ impl Uiua {
         &mut self,
         compile: impl FnOnce(&mut Compiler) -> UiuaResult<&mut Compiler>,
     ) -> UiuaResult<Compiler> {
-        let mut comp = Compiler::with_backend(self.rt.backend.clone());
+        let mut comp = Compiler::with_backend(self.rt.backend.clone()).with_policy(self.rt.policy);
         let asm = compile(&mut comp)?.finish();
         self.run_asm(&asm)?;
         Ok(comp)
@@ -335,14 +609,36 @@ impl Uiua {
     pub fn run_asm(&mut self, asm: impl Into<Assembly>) -> UiuaResult {
         fn run_asm(env: &mut Uiua, asm: Assembly) -> UiuaResult {
             env.asm = asm;
+            if let Some(cache) = &env.rt.recompute {
+                cache.new_assembly();
+            }
             env.rt.execution_start = instant::now();
+            (env.rt.allocated_elements).store(0, atomic::Ordering::Relaxed);
+            if let Some(usage) = &env.rt.usage {
+                usage.reset();
+            }
             let res = env.run_top_slices();
+            env.rt.cancel.reset();
             if res.is_err() {
                 env.rt = Runtime {
                     backend: env.rt.backend.clone(),
+                    cancel: env.rt.cancel.clone(),
+                    tracer: env.rt.tracer.clone(),
+                    usage: env.rt.usage.clone(),
+                    coverage: env.rt.coverage.clone(),
+                    step_hook: env.rt.step_hook.clone(),
                     execution_limit: env.rt.execution_limit,
+                    array_size_limit: env.rt.array_size_limit,
+                    allocation_limit: env.rt.allocation_limit,
+                    grid_width: env.rt.grid_width,
+                    graphics: env.rt.graphics,
+                    image_previews: env.rt.image_previews,
                     time_instrs: env.rt.time_instrs,
                     output_comments: env.rt.output_comments.clone(),
+                    recompute: env.rt.recompute.clone(),
+                    policy: env.rt.policy,
+                    #[cfg(feature = "gpu")]
+                    gpu: env.rt.gpu,
                     ..Runtime::default()
                 };
             }
@@ -454,6 +750,11 @@ code:
                 formatted_instr = format!("{instr:?}");
                 self.rt.last_time = instant::now();
             }
+            if let Some(coverage) = &self.rt.coverage {
+                if let Some(span) = instr.span() {
+                    coverage.record(&self.asm.spans[span]);
+                }
+            }
             let res = match instr {
                 Instr::Comment(_) => Ok(()),
                 // Pause execution timer during &sc
@@ -465,10 +766,15 @@ code:
                         res
                     })
                 }
-                &Instr::Prim(prim, span) => {
-                    self.with_prim_span(span, Some(prim), |env| prim.run(env))
-                }
-                &Instr::ImplPrim(prim, span) => self.with_span(span, |env| prim.run(env)),
+                &Instr::Prim(prim, span) => self.with_prim_span(span, Some(prim), |env| {
+                    let args = prim.args().unwrap_or(0);
+                    env.traced(TracedPrim::Prim(prim), args, span, |env| prim.run(env))
+                }),
+                &Instr::ImplPrim(prim, span) => self.with_span(span, |env| {
+                    env.traced(TracedPrim::Impl(prim), prim.args(), span, |env| {
+                        prim.run(env)
+                    })
+                }),
                 Instr::Push(val) => {
                     self.rt.stack.push(Value::clone(val));
                     Ok(())
@@ -530,7 +836,7 @@ code:
                 }
                 &Instr::Call(span) => self
                     .pop_function()
-                    .and_then(|f| self.call_with_span(f, span)),
+                    .and_then(|f| self.call_with_cache(f, span)),
                 &Instr::CallRecursive(span) => self.with_span(span, |env| {
                     let f = env.pop_function()?;
                     env.call_recursive(f)
@@ -685,6 +991,7 @@ code:
             }
             self.rt.call_stack.last_mut().unwrap().pc += 1;
             self.respect_execution_limit()?;
+            self.respect_cancellation()?;
         }
         self.rt.call_stack.pop();
         Ok(())
@@ -701,6 +1008,86 @@ code:
         }
         Ok(())
     }
+    /// Run a primitive, recording a trace if tracing is enabled
+    /// and counting resources if usage accounting is enabled
+    ///
+    /// The step hook is called first if there is one.
+    fn traced(
+        &mut self,
+        prim: TracedPrim,
+        args: usize,
+        span: usize,
+        f: impl FnOnce(&mut Self) -> UiuaResult,
+    ) -> UiuaResult {
+        if let Some(hook) = self.rt.step_hook.clone() {
+            hook(self);
+        }
+        if self.rt.tracer.is_none() && self.rt.usage.is_none() {
+            return f(self);
+        }
+        let arg_values = &self.rt.stack[self.rt.stack.len().saturating_sub(args)..];
+        if let Some(usage) = &self.rt.usage {
+            usage.record_call(prim, arg_values);
+        }
+        let arg_shapes = if self.rt.tracer.is_some() {
+            (arg_values.iter().rev())
+                .map(|val| val.shape().clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let start = instant::now();
+        let res = f(self);
+        if let Some(usage) = &self.rt.usage {
+            usage.record_stack(&self.rt.stack);
+        }
+        if let Some(tracer) = &self.rt.tracer {
+            tracer.record(PrimTrace {
+                prim,
+                span: self.get_span(span),
+                arg_shapes,
+                time: instant::now() - start,
+            });
+        }
+        res
+    }
+    /// Interrupt execution if it has been cancelled with a [`CancelToken`]
+    pub fn respect_cancellation(&self) -> UiuaResult {
+        if self.rt.cancel.is_cancelled() {
+            return Err(UiuaError::Interrupted(
+                self.span(),
+                self.inputs().clone().into(),
+            ));
+        }
+        Ok(())
+    }
+    /// Error if allocating an array of the given size would exceed a size limit
+    ///
+    /// This also checks the execution time limit, since large allocations
+    /// usually come before long-running algorithms.
+    pub fn respect_size_limits(&self, elements: usize) -> UiuaResult {
+        if let Some(limit) = self.rt.array_size_limit {
+            if elements > limit {
+                return Err(self.error(format!(
+                    "Array of {elements} elements would exceed \
+                    the limit of {limit} elements"
+                )));
+            }
+        }
+        if self.rt.allocation_limit.is_some() || self.rt.usage.is_some() {
+            let allocated = (self.rt.allocated_elements)
+                .fetch_add(elements, atomic::Ordering::Relaxed)
+                .saturating_add(elements);
+            if let Some(limit) = self.rt.allocation_limit.filter(|&limit| allocated > limit) {
+                return Err(self.error(format!(
+                    "Execution would allocate {allocated} array elements, \
+                    exceeding the limit of {limit}"
+                )));
+            }
+        }
+        self.respect_execution_limit()?;
+        self.respect_cancellation()
+    }
     pub(crate) fn with_span<T>(
         &mut self,
         span: usize,
@@ -730,6 +1117,36 @@ code:
         let call_span = self.span_index();
         self.call_with_span(f, call_span)
     }
+    /// Call a function, using the recompute cache for named functions if it is enabled
+    fn call_with_cache(&mut self, f: Function, call_span: usize) -> UiuaResult {
+        let cache = match (&self.rt.recompute, &f.id) {
+            (Some(cache), FunctionId::Named(_)) => cache.clone(),
+            _ => return self.call_with_span(f, call_span),
+        };
+        // Results may depend on the fill context
+        let args = f.signature().args;
+        if !self.rt.fill_stack.is_empty()
+            || self.rt.skip_nan
+            || self.rt.deep_box
+            || self.rt.cubic
+            || self.rt.stack.len() < args
+        {
+            return self.call_with_span(f, call_span);
+        }
+        let outputs = f.signature().outputs;
+        let arg_values = &self.rt.stack[self.rt.stack.len() - args..];
+        let Some(key) = cache.key(&f, arg_values, &self.asm) else {
+            return self.call_with_span(f, call_span);
+        };
+        if let Some(cached) = cache.get(&key) {
+            self.truncate_stack(self.rt.stack.len() - args);
+            self.rt.stack.extend(cached);
+            return Ok(());
+        }
+        self.call_with_span(f, call_span)?;
+        cache.insert(key, self.rt.stack[self.rt.stack.len() - outputs..].to_vec());
+        Ok(())
+    }
     #[inline]
     fn call_slice(&mut self, slice: FuncSlice) -> UiuaResult {
         let call_span = self.span_index();
@@ -1200,6 +1617,19 @@ code:
             _ => Err(self.fill_error(false)),
         }
     }
+    pub(crate) fn time_scalar_fill(&self) -> Result<Timestamp, &'static str> {
+        match self.value_fill() {
+            Some(Value::Time(t)) if t.rank() == 0 => Ok(t.data[0]),
+            Some(Value::Time(_)) => Err(self.fill_error(true)),
+            _ => Err(self.fill_error(false)),
+        }
+    }
+    pub(crate) fn time_array_fill(&self) -> Result<Array<Timestamp>, &'static str> {
+        match self.value_fill() {
+            Some(Value::Time(t)) => Ok(t.clone()),
+            _ => Err(self.fill_error(false)),
+        }
+    }
     pub(crate) fn box_scalar_fill(&self) -> Result<Boxed, &'static str> {
         match self.value_fill() {
             Some(Value::Box(b)) if b.rank() == 0 => Ok(b.data[0].clone()),
@@ -1237,10 +1667,22 @@ code:
     pub(crate) fn value_fill(&self) -> Option<&Value> {
         (self.rt.fill_stack.iter().rev())
             .find(|fill| !fill.removed())
-            .map(|fill| &fill.value)
+            .and_then(|fill| fill.value.as_ref())
+    }
+    /// Get the fill function, if one is set
+    pub(crate) fn fill_by(&self) -> Option<Function> {
+        (self.rt.fill_stack.iter().rev())
+            .find(|fill| !fill.removed())
+            .and_then(|fill| fill.by.clone())
+    }
+    /// Get the padding modes, if they are set
+    pub(crate) fn pad_modes(&self) -> Option<Arc<[PadMode]>> {
+        (self.rt.fill_stack.iter().rev())
+            .find(|fill| !fill.removed())
+            .and_then(|fill| fill.pad.clone())
     }
     pub(crate) fn last_fill(&self) -> Option<&Value> {
-        self.rt.fill_stack.last().map(|fill| &fill.value)
+        (self.rt.fill_stack.last()).and_then(|fill| fill.value.as_ref())
     }
     fn fill_error(&self, scalar: bool) -> &'static str {
         if scalar {
@@ -1250,6 +1692,7 @@ code:
                 Some(Value::Char(_)) => ". A character fill is set, but is is not a scalar.",
                 Some(Value::Complex(_)) => ". A complex fill is set, but is is not a scalar.",
                 Some(Value::Box(_)) => ". A box fill is set, but is is not a scalar.",
+                Some(Value::Time(_)) => ". A datetime fill is set, but is is not a scalar.",
                 None => "",
             }
         } else {
@@ -1263,6 +1706,7 @@ code:
                     ". A complex fill is set, but the array is not complex numbers."
                 }
                 Some(Value::Box(_)) => ". A box fill is set, but the array is not boxed values.",
+                Some(Value::Time(_)) => ". A datetime fill is set, but the array is not datetimes.",
                 None => "",
             }
         }
@@ -1279,13 +1723,91 @@ code:
         in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
     ) -> UiuaResult<T> {
         self.rt.fill_stack.push(Fill {
-            value,
+            value: Some(value),
+            by: None,
+            pad: None,
             removed: Arc::new(false.into()),
         });
         let res = in_ctx(self);
         self.rt.fill_stack.pop();
         res
     }
+    /// Do something with a fill function set
+    pub(crate) fn with_fill_by<T>(
+        &mut self,
+        by: Function,
+        in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
+    ) -> UiuaResult<T> {
+        self.rt.fill_stack.push(Fill {
+            value: None,
+            by: Some(by),
+            pad: None,
+            removed: Arc::new(false.into()),
+        });
+        let res = in_ctx(self);
+        self.rt.fill_stack.pop();
+        res
+    }
+    /// Do something with padding modes set
+    ///
+    /// The current fill value, if any, is kept for axes that pad with it.
+    pub(crate) fn with_pad_modes<T>(
+        &mut self,
+        modes: Arc<[PadMode]>,
+        in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
+    ) -> UiuaResult<T> {
+        self.rt.fill_stack.push(Fill {
+            value: self.value_fill().cloned(),
+            by: None,
+            pad: Some(modes),
+            removed: Arc::new(false.into()),
+        });
+        let res = in_ctx(self);
+        self.rt.fill_stack.pop();
+        res
+    }
+    /// Whether reductions and statistics should skip `NaN`s
+    pub(crate) fn skip_nan(&self) -> bool {
+        self.rt.skip_nan
+    }
+    /// Do something with reductions and statistics skipping `NaN`s
+    pub(crate) fn with_skip_nan<T>(
+        &mut self,
+        in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
+    ) -> UiuaResult<T> {
+        let prev = replace(&mut self.rt.skip_nan, true);
+        let res = in_ctx(self);
+        self.rt.skip_nan = prev;
+        res
+    }
+    /// Whether pervasive functions should descend into boxes on both sides
+    pub(crate) fn deep_box(&self) -> bool {
+        self.rt.deep_box
+    }
+    /// Do something with pervasive functions descending into boxes on both sides
+    pub(crate) fn with_deep_box<T>(
+        &mut self,
+        in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
+    ) -> UiuaResult<T> {
+        let prev = replace(&mut self.rt.deep_box, true);
+        let res = in_ctx(self);
+        self.rt.deep_box = prev;
+        res
+    }
+    /// Whether interpolation should be cubic rather than linear
+    pub(crate) fn cubic(&self) -> bool {
+        self.rt.cubic
+    }
+    /// Do something with cubic interpolation
+    pub(crate) fn with_cubic<T>(
+        &mut self,
+        in_ctx: impl FnOnce(&mut Self) -> UiuaResult<T>,
+    ) -> UiuaResult<T> {
+        let prev = replace(&mut self.rt.cubic, true);
+        let res = in_ctx(self);
+        self.rt.cubic = prev;
+        res
+    }
     /// Do something with the top fill context unset
     pub(crate) fn without_fill<T>(&mut self, in_ctx: impl FnOnce(&mut Self) -> T) -> T {
         let Some(pos) = (self.rt.fill_stack.iter()).rposition(|fill| !fill.removed()) else {
@@ -1312,7 +1834,7 @@ code:
             }
         }
         for fill in fills.iter().rev().cloned() {
-            self.push(fill.value);
+            self.push(fill.value.unwrap_or_default());
         }
         let res1 = but(self);
         let res2 = in_ctx(self);
@@ -1373,6 +1895,9 @@ code:
                 temp_stacks: [Vec::new(), Vec::new()],
                 array_stack: Vec::new(),
                 fill_stack: Vec::new(),
+                skip_nan: false,
+                deep_box: self.rt.deep_box,
+                cubic: self.rt.cubic,
                 recur_stack: self.rt.recur_stack.clone(),
                 call_stack: Vec::new(),
                 time_instrs: self.rt.time_instrs,
@@ -1382,8 +1907,24 @@ code:
                 backend: self.rt.backend.clone(),
                 execution_limit: self.rt.execution_limit,
                 execution_start: self.rt.execution_start,
+                array_size_limit: self.rt.array_size_limit,
+                allocation_limit: self.rt.allocation_limit,
+                grid_width: self.rt.grid_width,
+                graphics: self.rt.graphics,
+                image_previews: self.rt.image_previews,
+                allocated_elements: self.rt.allocated_elements.clone(),
+                cancel: self.rt.cancel.clone(),
+                tracer: self.rt.tracer.clone(),
+                usage: self.rt.usage.clone(),
+                coverage: self.rt.coverage.clone(),
+                step_hook: self.rt.step_hook.clone(),
                 output_comments: HashMap::new(),
                 memo: self.rt.memo.clone(),
+                recompute: self.rt.recompute.clone(),
+                rng: self.rt.rng.fork(),
+                policy: self.rt.policy,
+                #[cfg(feature = "gpu")]
+                gpu: self.rt.gpu,
                 thread,
             },
         };
@@ -1624,3 +2165,60 @@ where
         self().arg_name()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn size_limits() {
+        // Sizes are pushed so that the compiler does not evaluate them ahead of time
+        let mut env = Uiua::with_safe_sys().with_array_size_limit(100);
+        env.push(100);
+        env.run_str("↯:1").unwrap();
+        env.push(101);
+        assert!(env.run_str("↯:1").is_err());
+        env.push(102);
+        assert!(env.run_str("◫3 ⇡").is_err());
+        env.push(1e12);
+        env.run_str("⍣(↯:1)(0)").unwrap();
+        assert_eq!(env.pop_int().unwrap(), 0);
+
+        let mut env = Uiua::with_safe_sys().with_allocation_limit(100);
+        env.push(50);
+        env.run_str("⍥(◌▽:1.)2").unwrap();
+        env.push(50);
+        assert!(env.run_str("⍥(◌▽:1.)3").is_err());
+    }
+
+    #[test]
+    fn cancellation() {
+        use std::{thread, time::Duration};
+
+        let mut env = Uiua::with_safe_sys();
+        for code in ["⍢(+1)1", "⍣(⍢(+1)1)0"] {
+            let token = env.cancel_token();
+            let canceller = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                token.cancel();
+            });
+            env.push(0);
+            let Err(err) = env.run_str(code) else {
+                panic!("{code} was not interrupted");
+            };
+            assert!(err.is_interrupt(), "{err}");
+            canceller.join().unwrap();
+        }
+        env.run_str("+1 2").unwrap();
+        assert_eq!(env.pop_int().unwrap(), 3);
+    }
+
+    #[test]
+    fn with_seed() {
+        let mut seeded = Uiua::with_safe_sys().with_seed(42.0);
+        seeded.run_str("[⚂⚂⚂]").unwrap();
+        let mut env = Uiua::with_safe_sys();
+        env.run_str("# Experimental!\n[⚂⚂⚂] seed 42").unwrap();
+        assert_eq!(seeded.take_stack(), env.take_stack());
+    }
+}
@@ -7,6 +7,8 @@ use std::{
 use serde::*;
 use tinyvec::{tiny_vec, TinyVec};
 
+use crate::SizeError;
+
 /// Uiua's array shape type
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -63,6 +65,18 @@ impl Shape {
     pub fn elements(&self) -> usize {
         self.iter().product()
     }
+    /// Get the number of elements, checking for overflow
+    ///
+    /// Shapes built from untrusted dimensions should be checked with this
+    /// before their elements are allocated.
+    pub fn checked_elements(&self) -> Result<usize, SizeError> {
+        if self.contains(&0) {
+            return Ok(0);
+        }
+        (self.iter()).try_fold(1usize, |acc, &dim| {
+            acc.checked_mul(dim).ok_or(SizeError::Overflow)
+        })
+    }
     /// Make the shape its row shape
     pub fn make_row(&mut self) {
         if self.len() > 0 {
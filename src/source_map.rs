@@ -0,0 +1,172 @@
+//! Mapping compiled instructions back to source code
+
+use crate::{Assembly, BindingKind, CodeSpan, FuncSlice, Ident, InputSrc, Instr};
+
+/// Where an instruction came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstrSource {
+    /// The index of the instruction in the assembly
+    pub index: usize,
+    /// The span of the code that compiled to the instruction
+    ///
+    /// This is `None` for instructions that have no span, such as pushing a value,
+    /// or that were generated by the interpreter.
+    pub span: Option<CodeSpan>,
+    /// The binding whose function contains the instruction
+    ///
+    /// This is `None` for top-level code.
+    /// Functions that are inlined belong to the function they are inlined into.
+    pub binding: Option<SourceBinding>,
+}
+
+/// A binding that contains an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceBinding {
+    /// The index of the binding in [`Assembly::bindings`]
+    pub index: usize,
+    /// The name of the binding
+    pub name: Ident,
+    /// The span of the binding's name
+    pub span: CodeSpan,
+}
+
+/// A map from every instruction in an assembly to the code that produced it
+///
+/// Get one with [`Assembly::source_map`].
+///
+/// Instructions are identified by their index in the assembly.
+/// During execution, the index of the instruction each function is executing is available
+/// in [`CallFrame::instr`](crate::CallFrame::instr).
+///
+/// ```
+/// # use uiua::*;
+/// let asm = Compiler::new().load_str("F ← +1\nF 5").unwrap().finish();
+/// let map = asm.source_map();
+/// let add = map.line(&InputSrc::Str(0), 1).next().unwrap();
+/// assert_eq!(add.binding.as_ref().unwrap().name, "F");
+/// assert_eq!(add.span.as_ref().unwrap().start.col, 5);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    instrs: Vec<InstrSource>,
+}
+
+impl SourceMap {
+    /// Get the source of the instruction at an index
+    pub fn get(&self, index: usize) -> Option<&InstrSource> {
+        self.instrs.get(index)
+    }
+    /// Iterate over the sources of all instructions in order
+    pub fn iter(&self) -> impl Iterator<Item = &InstrSource> {
+        self.instrs.iter()
+    }
+    /// Get the number of instructions
+    pub fn len(&self) -> usize {
+        self.instrs.len()
+    }
+    /// Check if there are no instructions
+    pub fn is_empty(&self) -> bool {
+        self.instrs.is_empty()
+    }
+    /// Iterate over the instructions compiled from a line of an input
+    ///
+    /// Lines are 1-indexed.
+    pub fn line<'a>(
+        &'a self,
+        src: &'a InputSrc,
+        line: u16,
+    ) -> impl Iterator<Item = &'a InstrSource> + 'a {
+        self.instrs.iter().filter(move |instr| {
+            instr.span.as_ref().is_some_and(|span| {
+                span.src == *src && span.start.line <= line && line <= span.end.line
+            })
+        })
+    }
+}
+
+impl Assembly {
+    /// Map every instruction in the assembly to the code that produced it
+    pub fn source_map(&self) -> SourceMap {
+        // Find the binding that owns each instruction
+        let mut owners = vec![None; self.instrs.len()];
+        for (index, binding) in self.bindings.iter().enumerate() {
+            if let BindingKind::Func(f) = &binding.kind {
+                self.mark_owner(f.slice, index, &mut owners);
+            }
+        }
+        let instrs = (self.instrs.iter().zip(owners))
+            .enumerate()
+            .map(|(index, (instr, owner))| InstrSource {
+                index,
+                span: (instr.span())
+                    .and_then(|span| self.spans.get(span))
+                    .and_then(|span| span.clone().code()),
+                binding: owner.map(|index| {
+                    let span = self.bindings[index].span.clone();
+                    SourceBinding {
+                        index,
+                        name: (span.try_as_str(&self.inputs, |s| s.into())).unwrap_or_default(),
+                        span,
+                    }
+                }),
+            })
+            .collect();
+        SourceMap { instrs }
+    }
+    fn mark_owner(&self, slice: FuncSlice, binding: usize, owners: &mut [Option<usize>]) {
+        for i in slice.start..slice.end().min(owners.len()) {
+            if owners[i].is_some() {
+                continue;
+            }
+            owners[i] = Some(binding);
+            if let Instr::PushFunc(f) = &self.instrs[i] {
+                self.mark_owner(f.slice, binding, owners);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn source_map() {
+        use std::sync::{Arc, Mutex};
+
+        let asm = Compiler::new()
+            .load_str("F ← ×2\nG ← +1 F\nG")
+            .unwrap()
+            .finish();
+        let map = asm.source_map();
+        assert_eq!(map.len(), asm.instrs.len());
+        let owners = |line| -> Vec<_> {
+            (map.line(&InputSrc::Str(0), line))
+                .map(|src| src.binding.as_ref().map(|b| b.name.as_str()))
+                .collect()
+        };
+        // Small functions are inlined where they are called
+        assert_eq!(owners(1), [Some("F"), Some("G"), None]);
+        assert_eq!(owners(2), [Some("G"), None]);
+
+        // Attribute executed instructions to code
+        let instrs = Arc::new(Mutex::new(Vec::new()));
+        let hook_instrs = instrs.clone();
+        let mut env = Uiua::with_safe_sys().with_step_hook(move |env| {
+            let frame = env.stack_snapshot(0).call_stack.pop().unwrap();
+            hook_instrs.lock().unwrap().push(frame.instr);
+        });
+        env.push([1, 2, 3]);
+        env.run_asm(asm).unwrap();
+        let executed: Vec<_> = (instrs.lock().unwrap().iter())
+            .map(|&i| {
+                let span = map.get(i).unwrap().span.as_ref().unwrap();
+                (
+                    span.start.line,
+                    span.as_str(env.inputs(), |s| s.to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(executed, [(1, "×".into()), (2, "+".into())]);
+    }
+}
@@ -8,6 +8,7 @@ use std::{
     time::Duration,
 };
 
+use ecow::EcoVec;
 use enum_iterator::{all, Sequence};
 #[cfg(feature = "audio_encode")]
 use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
@@ -19,7 +20,7 @@ use serde::*;
 
 use crate::{
     algorithm::validate_size, cowslice::cowslice, primitive::PrimDoc, Array, Boxed, FfiType,
-    Purity, Signature, Uiua, UiuaResult, Value,
+    Purity, Signature, Span, Timestamp, Uiua, UiuaError, UiuaResult, Value,
 };
 
 /// The text of Uiua's example module
@@ -151,6 +152,7 @@ pub enum SysOpClass {
     Images,
     Gifs,
     Tcp,
+    Channel,
     Ffi,
     Misc,
 }
@@ -162,6 +164,87 @@ impl SysOpClass {
     }
 }
 
+/// A kind of access to the outside world that a [`SysPolicy`] can allow or deny
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum SysCapability {
+    /// Reading files and directories
+    FsRead,
+    /// Creating, writing, and deleting files
+    FsWrite,
+    /// Making and accepting network connections
+    Network,
+    /// Running commands and foreign code
+    Subprocess,
+    /// Reading and writing the clipboard
+    Clipboard,
+}
+
+impl SysCapability {
+    /// All capabilities
+    pub fn all() -> impl Iterator<Item = Self> {
+        all()
+    }
+    /// Get a description of the capability for error messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            SysCapability::FsRead => "filesystem read",
+            SysCapability::FsWrite => "filesystem write",
+            SysCapability::Network => "network",
+            SysCapability::Subprocess => "subprocess",
+            SysCapability::Clipboard => "clipboard",
+        }
+    }
+    fn bit(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// Which [`SysCapability`]s system functions are allowed to use
+///
+/// The policy is checked before a system function calls the backend.
+/// A system function that needs a denied capability is an error.
+/// The same goes for `comptime` code and for imports, which need filesystem access,
+/// and network access if they are remote.
+/// By default, everything is allowed.
+///
+/// ```
+/// # use uiua::*;
+/// let policy = SysPolicy::deny_all().allow(SysCapability::FsRead);
+/// let mut env = Uiua::with_native_sys().with_policy(policy);
+/// assert!(env.run_str("&fe \"nonexistent.ua\"").is_ok());
+/// let err = env.run_str("&fde \"nonexistent.ua\"").err().unwrap();
+/// assert!(err.to_string().contains("Permission denied"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SysPolicy {
+    denied: u8,
+}
+
+impl SysPolicy {
+    /// A policy that allows every capability
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+    /// A policy that denies every capability
+    pub fn deny_all() -> Self {
+        SysCapability::all().fold(Self::default(), Self::deny)
+    }
+    /// Allow a capability
+    pub fn allow(mut self, capability: SysCapability) -> Self {
+        self.denied &= !capability.bit();
+        self
+    }
+    /// Deny a capability
+    pub fn deny(mut self, capability: SysCapability) -> Self {
+        self.denied |= capability.bit();
+        self
+    }
+    /// Check if a capability is allowed
+    pub fn allows(&self, capability: SysCapability) -> bool {
+        self.denied & capability.bit() == 0
+    }
+}
+
 sys_op! {
     /// Print a nicely formatted representation of a value to stdout
     (1(0), Show, StdIO, "&s", "show", Mutating),
@@ -169,6 +252,15 @@ sys_op! {
     (1(0), Prin, StdIO, "&pf", "print and flush", Mutating),
     /// Print a value to stdout followed by a newline
     (1(0), Print, StdIO, "&p", "print with newline", Mutating),
+    /// Log a message and a value at a level
+    ///
+    /// Expects a level, a message string, and any value.
+    /// The level must be one of `"debug"`, `"info"`, `"warn"`, or `"error"`.
+    /// ex: &log "info" "Loaded rows" [1 2 3]
+    ///
+    /// Logs are sent to the environment as structured records so that they can be filtered.
+    /// In the native interpreter, they are printed to stderr.
+    (3(0), Log, StdIO, "&log", "log", Mutating),
     /// Read a line from stdin
     ///
     /// The normal output is a string.
@@ -199,6 +291,11 @@ sys_op! {
     /// Expects a string and returns a string.
     /// If the environment variable does not exist, an error is thrown.
     (1, Var, Env, "&var", "environment variable"),
+    /// Get all environment variables
+    ///
+    /// The result is a [map] array of variable names to values, sorted by name.
+    /// ex: has "PATH" &vars
+    (0, Vars, Env, "&vars", "environment variables"),
     /// Run a command and wait for it to finish
     ///
     /// Standard IO will be inherited. Returns the exit code of the command.
@@ -221,6 +318,25 @@ sys_op! {
     /// Using [&cl] on the handle will kill the child process.
     /// [under][&runs] calls [&cl] automatically.
     (1, RunStream, Command, "&runs", "run command stream", Mutating),
+    /// Run a command with streaming IO, including stderr
+    ///
+    /// Expects either a string, a rank `2` character array, or a rank `1` array of [box] strings.
+    /// This is like [&runs], but stderr is also captured.
+    /// Returns a stream handle for the command's stderr and a stream handle for the command.
+    /// Reading from the stderr handle with [&rs], [&rb], or [&ru] will read from the command's stderr.
+    /// Use [&cl] on both handles when you are done with them.
+    (1(2), RunStreamStderr, Command, "&runse", "run command stream with stderr", Mutating),
+    /// Close the stdin of a command run with [&runs] or [&runse]
+    ///
+    /// Many commands wait for their input to end before they finish.
+    /// The command's stdout can still be read.
+    (1(0), RunCloseStdin, Command, "&runcin", "run command close stdin", Mutating),
+    /// Wait for a command run with [&runs] or [&runse] to finish
+    ///
+    /// The command's stdin is closed first. Returns the exit code of the command.
+    /// Anything the command wrote that has not been read can still be read from its handles.
+    /// A command that writes a lot of output may not finish until its output is read.
+    (1, RunWait, Command, "&runw", "run command wait", Mutating),
     /// Change the current directory
     (1(0), ChangeDirectory, Filesystem, "&cd", "change directory", Mutating),
     /// Get the contents of the clipboard
@@ -237,6 +353,14 @@ sys_op! {
     ///
     /// See also: [&clget]
     (1(0), ClipboardSet, Misc, "&clset", "set clipboard contents", Mutating),
+    /// Pause execution and hand control to the host
+    ///
+    /// In the native interpreter, the stack is printed and execution resumes when you press enter.
+    /// Entering `q` stops the program instead.
+    /// Breakpoints let you see the state of the program in the middle of a function.
+    ///
+    /// Not all environments support breakpoints.
+    (0(0), Breakpoint, Misc, "&b", "breakpoint", Mutating),
     /// Sleep for n seconds
     ///
     /// On the web, this example will hang for 1 second.
@@ -272,6 +396,57 @@ sys_op! {
     /// The stream handle `0` is stdin.
     /// ex: &ru "Uiua" &fo "example.txt"
     (2, ReadUntil, Stream, "&ru", "read until", Mutating),
+    /// Fold a function over the lines of a stream
+    ///
+    /// Expects a stream handle and an initial accumulator.
+    /// The function is called with the accumulator and each line, and it should return the new accumulator.
+    /// Lines are read one at a time, so the whole stream never has to be in memory at once.
+    /// Lines do not include their trailing newline.
+    /// The stream handle `0` is stdin.
+    ///
+    /// Count the lines of a file:
+    /// ex: &rlf(+1◌) &fo "example.txt" 0
+    /// Keep only the lines that contain an `@H`:
+    /// ex: &rlf(⊂:▽⊃(∊@H)□) &fo "example.txt" []
+    /// Find the length of the longest line:
+    /// ex: &rlf(↥⧻) &fo "example.txt" 0
+    ///
+    /// See also: [&ru]
+    (2[1], ReadLinesFold, Stream, "&rlf", "read lines fold", Mutating),
+    /// Fold a function over batches of CSV records from a stream
+    ///
+    /// Expects a batch size, a stream handle, and an initial accumulator.
+    /// The first line of the stream is the header.
+    /// Each batch is a [map] from the header's column names to lists of boxed strings,
+    /// with at most the batch size records.
+    /// The function is called with the accumulator and each batch, and it should return the new accumulator.
+    /// Only one batch is in memory at a time.
+    /// The stream handle `0` is stdin.
+    ///
+    /// Sum a column in batches of 1000 records:
+    /// ex: &fwa "people.csv" "name,age\nAlice,31\nBob,28\nCarol,45\n"
+    ///   : &rcsv(+/+⋕get"age") 1000 &fo "people.csv" 0
+    ///
+    /// See also: [&rjsl], [csv]
+    (3[1], ReadCsvFold, Stream, "&rcsv", "read csv batches fold", Mutating),
+    /// Fold a function over batches of JSON lines from a stream
+    ///
+    /// Expects a batch size, a stream handle, and an initial accumulator.
+    /// Each line must be a JSON object. Blank lines are skipped.
+    /// Each batch is a [map] from keys to columns with at most the batch size rows.
+    /// Columns are decoded like JSON arrays with [un][json], and keys missing from a line are `NaN`.
+    /// The function is called with the accumulator and each batch, and it should return the new accumulator.
+    /// Only one batch is in memory at a time.
+    /// The stream handle `0` is stdin.
+    ///
+    /// Find the largest value in batches of 2 lines:
+    /// ex: &fwa "data.jsonl" $ {"x": 3, "y": 1}
+    ///   :                   $ {"x": 5}
+    ///   :                   $ {"x": 4, "y": 8}
+    ///   : &rjsl(↥/↥get"x") 2 &fo "data.jsonl" 0
+    ///
+    /// See also: [&rcsv], [json]
+    (3[1], ReadJsonLinesFold, Stream, "&rjsl", "read json lines batches fold", Mutating),
     /// Write an array to a stream
     ///
     /// If the stream is a file, the file may not be written to until it is closed with [&cl].
@@ -328,6 +503,35 @@ sys_op! {
     ///
     /// ex: &fif "example.txt"
     (1, FIsFile, Filesystem, "&fif", "file - is file"),
+    /// Recursively list the contents of a directory, filtered by a glob pattern
+    ///
+    /// Expects a glob pattern and a path.
+    /// The result is a sorted list of boxed strings of the files and directories under the path.
+    /// ex: &fwalk "**" "."
+    /// In the pattern, `*` matches any part of a name, `?` matches any single character, and `**` matches any number of directories.
+    /// Patterns are matched against paths relative to the directory, with `/` as the separator.
+    /// ex: &fwalk "src/**/*.rs" "."
+    /// A pattern without a `/` is matched against only the file name, so it finds matches at any depth.
+    /// ex: &fwalk "*.ua" "."
+    (2, FWalk, Filesystem, "&fwalk", "file - walk directory"),
+    /// Get the metadata of a file or directory
+    ///
+    /// Expects a path or a list of paths.
+    /// The result is a [map] array with the keys `"size"`, `"modified"`, and `"type"`.
+    /// `"size"` is the size in bytes, `"modified"` is the last modification time as a datetime, and `"type"` is one of `"file"`, `"directory"`, or `"symlink"`.
+    /// ex: &fmeta "example.txt"
+    /// If a list of paths is given, each value is a list with an element for each path.
+    /// ex: &fmeta &fwalk "*.txt" "."
+    (1, FMeta, Filesystem, "&fmeta", "file - metadata"),
+    /// Wait for files to change
+    ///
+    /// Expects a path or a list of paths.
+    /// Blocks until a file at one of the paths changes, then returns a sorted list of boxed strings of the paths that changed.
+    /// Directories are watched recursively.
+    /// ex: &fwatch "data"
+    /// Changes that happen between calls with the same paths are not missed, so [&fwatch] can be used in a loop to rerun a pipeline whenever its inputs change.
+    /// ex: ⍢(&p &fwatch {"data" "config.json"})1
+    (1, FWatch, Filesystem, "&fwatch", "file - watch", Mutating),
     /// Read all the contents of a file into a string
     ///
     /// Expects a path and returns a rank-`1` character array.
@@ -531,14 +735,39 @@ sys_op! {
     ///
     /// See also: [&tcpc]
     (1, TlsConnect, Tcp, "&tlsc", "tls - connect", Mutating),
-    /// Set a TCP socket to non-blocking mode
+    /// Set a TCP or UDP socket to non-blocking mode
     (1, TcpSetNonBlocking, Tcp, "&tcpsnb", "tcp - set non-blocking", Mutating),
-    /// Set the read timeout of a TCP socket in seconds
+    /// Set the read timeout of a TCP or UDP socket in seconds
     (2(0), TcpSetReadTimeout, Tcp, "&tcpsrt", "tcp - set read timeout", Mutating),
-    /// Set the write timeout of a TCP socket in seconds
+    /// Set the write timeout of a TCP or UDP socket in seconds
     (2(0), TcpSetWriteTimeout, Tcp, "&tcpswt", "tcp - set write timeout", Mutating),
-    /// Get the connection address of a TCP socket
+    /// Get the connection address of a TCP socket or the local address of a UDP socket
     (1, TcpAddr, Tcp, "&tcpaddr", "tcp - address", Mutating),
+    /// Create a UDP socket and bind it to an address
+    ///
+    /// Returns a socket handle.
+    /// Send datagrams with [&udps] and receive them with [&udpr].
+    /// Binding to port `0` picks an unused port. Use [&tcpaddr] to get the bound address.
+    /// [under][&udpb] calls [&cl] automatically.
+    /// ex: ⍜(&udpb "127.0.0.1:0")&tcpaddr
+    (1, UdpBind, Tcp, "&udpb", "udp - bind", Mutating),
+    /// Send a datagram from a UDP socket
+    ///
+    /// Expects the data, an address, and a socket handle.
+    /// The data may be a string or a byte array.
+    /// ex: ⍜(&udpb "127.0.0.1:0")(&udps "Hello!" "127.0.0.1:9999")
+    (3(0), UdpSend, Tcp, "&udps", "udp - send", Mutating),
+    /// Receive a datagram with a UDP socket
+    ///
+    /// Expects a maximum number of bytes and a socket handle.
+    /// Returns the received bytes and the address they were sent from.
+    /// Bytes beyond the maximum are discarded.
+    /// This waits for a datagram unless a timeout is set with [&tcpsrt].
+    /// Here, a socket sends a datagram to itself.
+    /// ex: ⍜(&udpb "127.0.0.1:0")(
+    ///   :   &udps "Hello!" ⊸&tcpaddr .
+    ///   :   &udpr 1024)
+    (2(2), UdpReceive, Tcp, "&udpr", "udp - receive", Mutating),
     /// Make an HTTP(S) request
     ///
     /// Takes in an 1.x HTTP request and returns an HTTP response.
@@ -560,6 +789,46 @@ sys_op! {
     /// - The HTTP version
     /// - The `Host` header (if not defined)
     (2, HttpsWrite, Tcp, "&httpsw", "https - Make an HTTP(S) request", Mutating),
+    /// Make an HTTP or HTTPS request
+    ///
+    /// Expects a method, a URL, a [map] of headers, and a body.
+    /// Returns the response's status code, a [map] of its headers, and its body as bytes.
+    /// The headers may be an empty list if there are none.
+    /// The body may be a string or a byte array.
+    /// ex: &http "GET" "https://example.com" [] ""
+    /// ex: °utf₈ ⊙◌ &http "POST" "https://httpbin.org/post" map {"Content-Type"} {"application/json"} json map {"a"} [1]
+    ///
+    /// The `Host`, `Connection`, and `Content-Length` headers are set automatically.
+    /// Responses with chunked transfer encoding are decoded.
+    (4(3), HttpRequest, Tcp, "&http", "http - request", Mutating),
+    /// Create a channel for passing values between threads
+    ///
+    /// Expects a capacity and returns a channel handle.
+    /// If the capacity is [infinity], sending never blocks.
+    /// Otherwise, [&chs] blocks while the channel holds that many values, so a fast producer cannot get far ahead of a slow consumer.
+    /// Unlike [send] and [recv], which only connect a thread to its parent, a channel handle can be passed to any number of threads spawned with [spawn] or [pool].
+    /// ex: &chr &chs 5 . &chan ∞
+    /// ex: Ch ← &chan 2
+    ///   : ◌spawn(∵(&chs:Ch) ⇡10) # Producer
+    ///   : ⇌[⍥(&chr Ch)10]
+    /// Channels are closed with [&cl]. A thread waiting on a closed channel will stop with an error.
+    (1, ChannelCreate, Channel, "&chan", "channel - create", Mutating),
+    /// Send a value over a channel
+    ///
+    /// Expects a value and a channel handle from [&chan].
+    /// Values are not copied, so sending large arrays is cheap.
+    (2(0), ChannelSend, Channel, "&chs", "channel - send", Mutating),
+    /// Receive a value from a channel
+    ///
+    /// Expects a channel handle from [&chan].
+    /// Blocks until a value is available.
+    (1, ChannelRecv, Channel, "&chr", "channel - receive", Mutating),
+    /// Try to receive a value from a channel
+    ///
+    /// Expects a channel handle from [&chan].
+    /// If no value is available, an error is thrown instead of blocking.
+    /// ex: ⍣&chtr(⋅"empty") &chan ∞
+    (1, ChannelTryRecv, Channel, "&chtr", "channel - try receive", Mutating),
     /// Call a foreign function interface
     ///
     /// *Warning ⚠️: Using FFI is deeply unsafe. Calling a function incorrectly is undefined behavior.*
@@ -585,6 +854,8 @@ sys_op! {
     /// - `unsigned long long`
     /// Suffixing any of these with `*` makes them a pointer type.
     /// Struct types are defined as a list of types between `{}`s separated by `;`s, i.e. `{int; float}`. A trailing `;` is optional.
+    /// Struct fields may optionally be named, i.e. `{int id; float score}`. Either all or none of a struct's fields must be named.
+    /// Struct layouts are checked when the signature is parsed, so a struct with an empty, `void`, or duplicate field is an error.
     ///
     /// Arguments are passed as a list of boxed values.
     /// If we have a C function `int add(int a, int b)` in a shared library `example.dll`, we can call it like this:
@@ -624,11 +895,42 @@ sys_op! {
     ///   : Add ← Lib {VecII "vec2_add" VecII VecII}
     ///   : Add {[1 2] [3 4]} # [4 6]
     ///
+    /// Structs with named fields can also be passed as maps from field names to values, in any order.
+    /// Structs with named fields returned by a foreign function are returned as maps.
+    /// ex! # Experimental!
+    ///   : Lib ← &ffi ⊂□"example.dll"
+    ///   : Vec ← "{float x; float y}"
+    ///   : Add ← Lib {Vec "vec2_add" Vec Vec}
+    ///   : Add {map {"y" "x"} {2 1} [3 4]} # map {"x" "y"} {4 6}
+    ///
+    /// Uiua functions can be passed to foreign functions as callbacks with [&ffcb].
+    ///
     /// If a foreign function returns or has an out-parameter that is a pointer type, a special array is returned representing the pointer. This array is not useful as a normal array, but it can be passed back as an [&ffi] argument, read from with [&memcpy], or freed with [&memfree].
     ///
     /// Coverage of types that are supported for binding is currently best-effort.
     /// If you encounter a type that you need support for, please [open an issue](https://github.com/uiua-lang/uiua/issues/new).
     (2, Ffi, Ffi, "&ffi", "foreign function interface", Mutating),
+    /// Create a C function pointer that calls a Uiua function
+    ///
+    /// *Warning ⚠️: Using FFI is deeply unsafe. Calling a callback incorrectly is undefined behavior.*
+    ///
+    /// Expects a function and a list of boxed strings specifying the callback's signature.
+    /// The list must be of the form `{"return_type" "arg1_type" "arg2_type" …}`.
+    /// Types are specified in the same way as in [&ffi].
+    /// Arguments may be scalars, pointers, or structs. The return type may be `void`, a scalar, or a pointer.
+    /// The function's signature must match the number of arguments and return values.
+    ///
+    /// The result is a pointer value that can be passed to [&ffi] for a function pointer parameter.
+    /// The first argument of the callback is on top of the stack when the function is called.
+    /// If we have a C function `int apply(int (*f)(int, int), int a, int b)` in a shared library `example.dll`, we can call it like this:
+    /// ex! # Experimental!
+    ///   : Lib ← &ffi ⊂□"example.dll"
+    ///   : Apply ← Lib {"int" "apply" "const void*" "int" "int"}
+    ///   : Apply {&ffcb(-) {"int" "int" "int"} 5 3} # -2
+    ///
+    /// Callbacks are never freed, so they should be created once and reused.
+    /// If a callback fails, the error is reported when the [&ffi] call that called it returns.
+    (1[1], FfiCallback, Ffi, "&ffcb", "foreign function interface - callback", Mutating),
     /// Copy data from a pointer into an array
     ///
     /// *Warning ⚠️: [&memcpy] can lead to undefined behavior if used incorrectly.*
@@ -715,9 +1017,127 @@ impl Value {
     }
 }
 
+/// An HTTP request made with `&http`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The method, like `GET` or `POST`
+    pub method: String,
+    /// The URL, including the `http://` or `https://` scheme
+    pub url: String,
+    /// The header names and values
+    pub headers: Vec<(String, String)>,
+    /// The body
+    pub body: Vec<u8>,
+}
+
+/// An HTTP response returned by `&http`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The status code
+    pub status: u16,
+    /// The header names and values
+    pub headers: Vec<(String, String)>,
+    /// The body
+    pub body: Vec<u8>,
+}
+
+/// The level of a [`LogRecord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum LogLevel {
+    /// Detailed information for debugging
+    Debug,
+    /// Normal progress information
+    Info,
+    /// Something unexpected that the program can continue after
+    Warn,
+    /// Something that went wrong
+    Error,
+}
+
+impl LogLevel {
+    /// Get the name of the level as it is written in Uiua code
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+    /// Get a level from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        all::<Self>().find(|level| level.name() == name)
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A record logged with `&log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// The level
+    pub level: LogLevel,
+    /// The message
+    pub message: String,
+    /// The value attached to the message
+    pub value: Value,
+    /// The span of the `&log` call
+    pub span: Span,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = self.value.show();
+        let sep = if shown.contains('\n') { '\n' } else { ' ' };
+        write!(f, "[{}] {}:{sep}{shown}", self.level, self.message)
+    }
+}
+
+/// The kind of a [`FileMetadata`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    /// A regular file
+    File,
+    /// A directory
+    Directory,
+    /// A symbolic link
+    Symlink,
+}
+
+impl FileKind {
+    /// Get the name of the kind, as returned by `&fmeta`
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::File => "file",
+            FileKind::Directory => "directory",
+            FileKind::Symlink => "symlink",
+        }
+    }
+}
+
+/// The metadata of a file or directory returned by `&fmeta`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// The size in bytes
+    pub size: u64,
+    /// The last modification time
+    pub modified: Timestamp,
+    /// The kind of file
+    pub kind: FileKind,
+}
+
 /// The function type passed to `&ast`
 pub type AudioStreamFn = Box<dyn FnMut(&[f64]) -> UiuaResult<Vec<[f64; 2]>> + Send>;
 
+/// The function type passed to `&ffcb`
+///
+/// It takes the callback's arguments and returns its return value, if any.
+pub type FfiCallbackFn = Box<dyn FnMut(Vec<Value>) -> UiuaResult<Option<Value>> + Send>;
+
 /// The kind of a handle
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
@@ -728,6 +1148,9 @@ pub enum HandleKind {
     TcpSocket(SocketAddr),
     TlsSocket(SocketAddr),
     ChildProcess(String),
+    ChildStderr(String),
+    UdpSocket(SocketAddr),
+    Channel,
 }
 
 impl fmt::Display for HandleKind {
@@ -739,6 +1162,9 @@ impl fmt::Display for HandleKind {
             Self::TcpSocket(addr) => write!(f, "tcp socket {}", addr),
             Self::TlsSocket(addr) => write!(f, "tls socket {}", addr),
             Self::ChildProcess(com) => write!(f, "child {com}"),
+            Self::ChildStderr(com) => write!(f, "child stderr {com}"),
+            Self::UdpSocket(addr) => write!(f, "udp socket {}", addr),
+            Self::Channel => write!(f, "channel"),
         }
     }
 }
@@ -762,6 +1188,12 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     }
     /// Print a string that was create by `trace`
     fn print_str_trace(&self, s: &str) {}
+    /// Handle a record logged with `&log`
+    ///
+    /// By default, the record is printed to stderr.
+    fn log(&self, record: LogRecord) -> Result<(), String> {
+        self.print_str_stderr(&format!("{record}\n"))
+    }
     /// Read a line from stdin
     ///
     /// Should return `Ok(None)` if EOF is reached.
@@ -772,6 +1204,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn scan_stdin(&self, count: usize) -> Result<Vec<u8>, String> {
         Err("Reading from stdin is not supported in this environment".into())
     }
+    /// Read from stdin until EOF is reached
+    fn scan_stdin_to_end(&self) -> Result<Vec<u8>, String> {
+        Err("Reading from stdin is not supported in this environment".into())
+    }
     /// Read from stdin until a delimiter is reached
     fn scan_until_stdin(&self, delim: &[u8]) -> Result<Vec<u8>, String> {
         let mut buffer = Vec::new();
@@ -795,6 +1231,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn var(&self, name: &str) -> Option<String> {
         None
     }
+    /// Get all environment variables
+    fn vars(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
     /// Get the size of the terminal
     fn term_size(&self) -> Result<(usize, usize), String> {
         Err("Getting the terminal size is not supported in this environment".into())
@@ -815,6 +1255,48 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn is_file(&self, path: &str) -> Result<bool, String> {
         Err("Checking if a path is a file is not supported in this environment".into())
     }
+    /// Recursively list the contents of a directory
+    ///
+    /// Directories are listed before their contents.
+    fn walk_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        let mut paths = Vec::new();
+        for entry in self.list_dir(path)? {
+            let name = Path::new(&entry).file_name().unwrap_or_default();
+            let entry = Path::new(path).join(name).to_string_lossy().into_owned();
+            let is_dir = !self.is_file(&entry)?;
+            paths.push(entry.clone());
+            if is_dir {
+                paths.extend(self.walk_dir(&entry)?);
+            }
+        }
+        Ok(paths)
+    }
+    /// Get the metadata of a file or directory
+    fn file_metadata(&self, path: &str) -> Result<FileMetadata, String> {
+        Err("Getting file metadata is not supported in this environment".into())
+    }
+    /// Create a channel
+    ///
+    /// If `capacity` is `None`, the channel is unbounded.
+    fn create_channel(&self, capacity: Option<usize>) -> Result<Handle, String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    /// Send a value over a channel, blocking if it is full
+    fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    /// Receive a value from a channel, blocking until one is available
+    fn channel_recv(&self, handle: Handle) -> Result<Value, String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    /// Receive a value from a channel if one is available
+    fn channel_try_recv(&self, handle: Handle) -> Result<Option<Value>, String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    /// Block until files at some paths change, and get the paths that changed
+    fn watch_files(&self, paths: &[String]) -> Result<Vec<String>, String> {
+        Err("Watching files is not supported in this environment".into())
+    }
     /// Delete a file or directory
     fn delete(&self, path: &str) -> Result<(), String> {
         Err("Deleting files is not supported in this environment".into())
@@ -846,6 +1328,25 @@ pub trait SysBackend: Any + Send + Sync + 'static {
         }
         Ok(buffer)
     }
+    /// Read a line from a stream
+    ///
+    /// The line should not include its trailing newline.
+    /// Should return `Ok(None)` if EOF is reached.
+    fn read_line(&self, handle: Handle) -> Result<Option<String>, String> {
+        let mut bytes = self.read_until(handle, b"\n")?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        if bytes.ends_with(b"\n") {
+            bytes.pop();
+            if bytes.ends_with(b"\r") {
+                bytes.pop();
+            }
+        }
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
     /// Write bytes to a stream
     fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
         Err("Writing to streams is not supported in this environment".into())
@@ -880,6 +1381,24 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn set_clipboard(&self, contents: &str) -> Result<(), String> {
         Err("Setting the clipboard is not supported in this environment".into())
     }
+    /// Get the current time in seconds since the Unix epoch
+    fn now(&self) -> f64 {
+        instant::now() / 1000.0
+    }
+    /// Get a seed for the random number generator
+    ///
+    /// If `None`, the generator is seeded from the operating system.
+    fn random_seed(&self) -> Option<u64> {
+        None
+    }
+    /// Pause execution at a breakpoint
+    ///
+    /// The interpreter can be inspected with [`Uiua::stack_snapshot`].
+    /// Execution resumes when this returns.
+    /// Should return `Ok(false)` if execution should stop instead.
+    fn breakpoint(&self, env: &Uiua) -> Result<bool, String> {
+        Err("Breakpoints are not supported in this environment".into())
+    }
     /// Sleep the current thread for `seconds` seconds
     fn sleep(&self, seconds: f64) -> Result<(), String> {
         Err("Sleeping is not supported in this environment".into())
@@ -949,6 +1468,20 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<(), String> {
         Err("TCP sockets are not supported in this environment".into())
     }
+    /// Create a UDP socket and bind it to an address
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    /// Send a datagram from a UDP socket to an address
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    /// Receive a datagram of at most `max` bytes with a UDP socket
+    ///
+    /// Returns the bytes and the address they were sent from.
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, SocketAddr), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
     /// Close a stream
     fn close(&self, handle: Handle) -> Result<(), String> {
         Ok(())
@@ -973,6 +1506,22 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String> {
         Err("Running streamed commands is not supported in this environment".into())
     }
+    /// Run a command and return an IO stream handle and a handle for its stderr
+    fn run_command_stream_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, Handle), String> {
+        Err("Running streamed commands is not supported in this environment".into())
+    }
+    /// Close the stdin of a streamed command
+    fn close_command_stdin(&self, handle: Handle) -> Result<(), String> {
+        Err("Running streamed commands is not supported in this environment".into())
+    }
+    /// Wait for a streamed command to finish and return its exit code
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        Err("Running streamed commands is not supported in this environment".into())
+    }
     /// Change the current directory
     fn change_directory(&self, path: &str) -> Result<(), String> {
         Err("Changing directories is not supported in this environment".into())
@@ -981,6 +1530,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
         Err("Making HTTPS requests is not supported in this environment".into())
     }
+    /// Make an HTTP request
+    fn http_request(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        Err("Making HTTP requests is not supported in this environment".into())
+    }
     /// Call a foreign function interface
     fn ffi(
         &self,
@@ -992,6 +1545,17 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<Value, String> {
         Err("FFI is not supported in this environment".into())
     }
+    /// Create a C function pointer that calls a function
+    ///
+    /// The returned value should be a pointer value.
+    fn ffi_callback(
+        &self,
+        result_ty: FfiType,
+        arg_tys: &[FfiType],
+        f: FfiCallbackFn,
+    ) -> Result<Value, String> {
+        Err("FFI callbacks are not supported in this environment".into())
+    }
     /// Copy the data from a pointer into an array
     fn mem_copy(&self, ty: FfiType, ptr: *const (), len: usize) -> Result<Value, String> {
         Err("Pointer copying is not supported in this environment".into())
@@ -1006,6 +1570,12 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn load_git_module(&self, url: &str, branch: Option<&str>) -> Result<PathBuf, String> {
         Err("Loading git modules is not supported in this environment".into())
     }
+    /// Load a file from a URL as a module
+    ///
+    /// The returned path should be loadable via [`SysBackend::file_read_all`]
+    fn load_url_module(&self, url: &str) -> Result<PathBuf, String> {
+        Err("Loading modules from URLs is not supported in this environment".into())
+    }
 }
 
 impl fmt::Debug for dyn SysBackend {
@@ -1073,11 +1643,57 @@ impl IntoSysBackend for Arc<dyn SysBackend> {
     }
 }
 
+/// Read a line from a stream, or `None` at the end of the stream
+fn read_line(env: &Uiua, handle: Handle) -> UiuaResult<Option<String>> {
+    match handle {
+        Handle::STDOUT => Err(env.error("Cannot read from stdout")),
+        Handle::STDERR => Err(env.error("Cannot read from stderr")),
+        Handle::STDIN => env.rt.backend.scan_line_stdin().map_err(|e| env.error(e)),
+        _ => env.rt.backend.read_line(handle).map_err(|e| env.error(e)),
+    }
+}
+
 impl SysOp {
+    /// Get the capabilities the system function needs
+    ///
+    /// System functions that only use standard IO, the environment,
+    /// or values already in the program need none.
+    pub fn capabilities(&self) -> &'static [SysCapability] {
+        use SysCapability::*;
+        match self {
+            SysOp::FOpen => &[FsRead, FsWrite],
+            SysOp::FExists
+            | SysOp::FListDir
+            | SysOp::FIsFile
+            | SysOp::FWalk
+            | SysOp::FMeta
+            | SysOp::FWatch
+            | SysOp::FReadAllStr
+            | SysOp::FReadAllBytes
+            | SysOp::ChangeDirectory => &[FsRead],
+            SysOp::FCreate | SysOp::FDelete | SysOp::FTrash | SysOp::FWriteAll => &[FsWrite],
+            SysOp::ClipboardGet | SysOp::ClipboardSet => &[Clipboard],
+            SysOp::Ffi | SysOp::FfiCallback | SysOp::MemCopy | SysOp::MemFree => &[Subprocess],
+            op => match op.class() {
+                SysOpClass::Command => &[Subprocess],
+                SysOpClass::Tcp => &[Network],
+                _ => &[],
+            },
+        }
+    }
     pub(crate) fn run(&self, env: &mut Uiua) -> UiuaResult {
+        if let Some(denied) = (self.capabilities().iter()).find(|&&cap| !env.rt.policy.allows(cap))
+        {
+            return Err(env.error(format!(
+                "Permission denied: {} needs {} access",
+                self.name(),
+                denied.description()
+            )));
+        }
         match self {
             SysOp::Show => {
-                let s = env.pop(1)?.show();
+                let val = env.pop(1)?;
+                let s = env.display_value(&val);
                 (env.rt.backend)
                     .print_str_stdout(&s)
                     .map_err(|e| env.error(e))?;
@@ -1100,6 +1716,26 @@ impl SysOp {
                     .print_str_stdout("\n")
                     .map_err(|e| env.error(e))?;
             }
+            SysOp::Log => {
+                let level = env.pop(1)?.as_string(env, "Log level must be a string")?;
+                let level = LogLevel::from_name(&level).ok_or_else(|| {
+                    env.error(format!(
+                        "Unknown log level {level:?}. Must be one of {}",
+                        (all::<LogLevel>().map(|level| format!("{:?}", level.name())))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+                let message = env.pop(2)?.as_string(env, "Log message must be a string")?;
+                let value = env.pop(3)?;
+                let record = LogRecord {
+                    level,
+                    message,
+                    value,
+                    span: env.span(),
+                };
+                env.rt.backend.log(record).map_err(|e| env.error(e))?;
+            }
             SysOp::ScanLine => {
                 if let Some(line) = env.rt.backend.scan_line_stdin().map_err(|e| env.error(e))? {
                     env.push(line);
@@ -1111,6 +1747,17 @@ impl SysOp {
                 let (width, height) = env.rt.backend.term_size().map_err(|e| env.error(e))?;
                 env.push(cowslice![height as f64, width as f64])
             }
+            SysOp::Breakpoint => {
+                if !(env.rt.backend.clone())
+                    .breakpoint(env)
+                    .map_err(|e| env.error(e))?
+                {
+                    return Err(UiuaError::Interrupted(
+                        env.span(),
+                        env.inputs().clone().into(),
+                    ));
+                }
+            }
             SysOp::Exit => {
                 let status = env.pop(1)?.as_int(env, "Status must be an integer")? as i32;
                 (env.rt.backend).exit(status).map_err(|e| env.error(e))?;
@@ -1137,6 +1784,16 @@ impl SysOp {
                     })?;
                 env.push(var);
             }
+            SysOp::Vars => {
+                let mut vars = env.rt.backend.vars();
+                vars.sort_unstable();
+                let (keys, values): (EcoVec<Boxed>, EcoVec<Boxed>) = (vars.into_iter())
+                    .map(|(k, v)| (Boxed(k.into()), Boxed(v.into())))
+                    .unzip();
+                let mut vars = Value::from(Array::from(values));
+                vars.map(keys.into(), env)?;
+                env.push(vars);
+            }
             SysOp::FOpen => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 let handle = (env.rt.backend)
@@ -1197,7 +1854,9 @@ impl SysOp {
                                 }
                             }
                         } else {
-                            return Err(env.error("Cannot read an infinite amount from stdin"));
+                            let buf = env.rt.backend.scan_stdin_to_end();
+                            String::from_utf8(buf.map_err(|e| env.error(e))?)
+                                .map_err(|e| env.error(e))?
                         }
                     }
                     _ => {
@@ -1252,7 +1911,9 @@ impl SysOp {
                         if let Some(count) = count {
                             env.rt.backend.scan_stdin(count).map_err(|e| env.error(e))?
                         } else {
-                            return Err(env.error("Cannot read an infinite amount from stdin"));
+                            (env.rt.backend)
+                                .scan_stdin_to_end()
+                                .map_err(|e| env.error(e))?
                         }
                     }
                     _ => {
@@ -1342,6 +2003,7 @@ impl SysOp {
                     Value::Complex(_) => return Err(env.error("Cannot write complex array")),
                     Value::Char(arr) => arr.data.iter().collect::<String>().into(),
                     Value::Box(_) => return Err(env.error("Cannot write box array")),
+                    Value::Time(_) => return Err(env.error("Cannot write datetime array")),
                 };
                 match handle {
                     Handle::STDOUT => env
@@ -1400,6 +2062,7 @@ impl SysOp {
                     }
                     Value::Char(arr) => arr.data.iter().collect::<String>().into(),
                     Value::Box(_) => return Err(env.error("Cannot write box array to file")),
+                    Value::Time(_) => return Err(env.error("Cannot write datetime array to file")),
                 };
                 (env.rt.backend)
                     .file_write_all(path.as_ref(), &bytes)
@@ -1429,6 +2092,77 @@ impl SysOp {
                 let is_file = env.rt.backend.is_file(&path).map_err(|e| env.error(e))?;
                 env.push(is_file);
             }
+            SysOp::FWalk => {
+                let pattern = env.pop(1)?.as_string(env, "Pattern must be a string")?;
+                let root = env.pop(2)?.as_string(env, "Path must be a string")?;
+                let mut paths = env.rt.backend.walk_dir(&root).map_err(|e| env.error(e))?;
+                paths.retain(|path| {
+                    let relative = Path::new(path).strip_prefix(&root).unwrap_or(path.as_ref());
+                    let relative: Vec<_> = (relative.components())
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect();
+                    if pattern.contains('/') {
+                        let relative: Vec<&str> = relative.iter().map(|s| s.as_ref()).collect();
+                        let pattern: Vec<&str> = pattern.split('/').collect();
+                        glob_match_path(&pattern, &relative)
+                    } else {
+                        (relative.last()).is_some_and(|name| glob_match_name(&pattern, name))
+                    }
+                });
+                paths.sort();
+                env.push(Array::<Boxed>::from_iter(paths));
+            }
+            SysOp::FMeta => {
+                let paths = env.pop(1)?;
+                let metadata = |path: &str| env.rt.backend.file_metadata(path);
+                let single = match &paths {
+                    Value::Char(arr) => arr.rank() <= 1,
+                    Value::Box(arr) => arr.rank() == 0,
+                    _ => false,
+                };
+                let [size, modified, kind]: [Value; 3] = if single {
+                    let path = paths.as_string(env, "Path must be a string or list of strings")?;
+                    let meta = metadata(&path).map_err(|e| env.error(e))?;
+                    [
+                        (meta.size as f64).into(),
+                        meta.modified.into(),
+                        meta.kind.name().into(),
+                    ]
+                } else {
+                    let paths =
+                        paths.as_string_list(env, "Path must be a string or list of strings")?;
+                    let mut sizes = EcoVec::with_capacity(paths.len());
+                    let mut times = EcoVec::with_capacity(paths.len());
+                    let mut kinds = EcoVec::with_capacity(paths.len());
+                    for path in &paths {
+                        let meta = metadata(path).map_err(|e| env.error(e))?;
+                        sizes.push(meta.size as f64);
+                        times.push(meta.modified);
+                        kinds.push(Boxed(meta.kind.name().into()));
+                    }
+                    [
+                        Array::from(sizes).into(),
+                        Array::from(times).into(),
+                        Array::from(kinds).into(),
+                    ]
+                };
+                let keys =
+                    Array::<Boxed>::from_iter(["size", "modified", "type"].map(String::from));
+                let mut meta = Value::from(Array::from_iter([size, modified, kind].map(Boxed)));
+                meta.map(keys.into(), env)?;
+                env.push(meta);
+            }
+            SysOp::FWatch => {
+                let paths = env.pop(1)?;
+                let paths = match &paths {
+                    Value::Char(arr) if arr.rank() <= 1 => vec![paths.as_string(env, "")?],
+                    _ => paths.as_string_list(env, "Paths must be a string or list of strings")?,
+                };
+                let changed = (env.rt.backend)
+                    .watch_files(&paths)
+                    .map_err(|e| env.error(e))?;
+                env.push(Array::<Boxed>::from_iter(changed));
+            }
             SysOp::Invoke => {
                 let path = env.pop(1)?.as_string(env, "Invoke path must be a string")?;
                 env.rt.backend.invoke(&path).map_err(|e| env.error(e))?;
@@ -1606,6 +2340,91 @@ impl SysOp {
                 let sample_rate = env.rt.backend.audio_sample_rate();
                 env.push(f64::from(sample_rate));
             }
+            SysOp::ReadLinesFold => {
+                let f = env.pop_function()?;
+                if f.signature() != (2, 1) {
+                    return Err(env.error(format!(
+                        "&rlf's function's signature must be {}, but it is {}",
+                        Signature::new(2, 1),
+                        f.signature()
+                    )));
+                }
+                let handle = env.pop(1)?.as_handle(env, "")?;
+                let mut acc = env.pop(2)?;
+                while let Some(line) = read_line(env, handle)? {
+                    env.push(acc);
+                    env.push(line);
+                    env.call(f.clone())?;
+                    acc = env.pop("folded value")?;
+                }
+                env.push(acc);
+            }
+            SysOp::ReadCsvFold | SysOp::ReadJsonLinesFold => {
+                let f = env.pop_function()?;
+                if f.signature() != (2, 1) {
+                    return Err(env.error(format!(
+                        "{}'s function's signature must be {}, but it is {}",
+                        self.name(),
+                        Signature::new(2, 1),
+                        f.signature()
+                    )));
+                }
+                let size = env
+                    .pop(1)?
+                    .as_nat(env, "Batch size must be a natural number")?;
+                if size == 0 {
+                    return Err(env.error("Batch size must be positive"));
+                }
+                let handle = env.pop(2)?.as_handle(env, "")?;
+                let mut acc = env.pop(3)?;
+                let csv = *self == SysOp::ReadCsvFold;
+                let header = if csv {
+                    read_line(env, handle)?
+                } else {
+                    Some(String::new())
+                };
+                let Some(header) = header else {
+                    env.push(acc);
+                    return Ok(());
+                };
+                loop {
+                    let mut lines = Vec::with_capacity(size);
+                    while lines.len() < size {
+                        let Some(mut line) = read_line(env, handle)? else {
+                            break;
+                        };
+                        if csv {
+                            // Quoted fields may contain newlines
+                            while line.matches('"').count() % 2 == 1 {
+                                let Some(next) = read_line(env, handle)? else {
+                                    break;
+                                };
+                                line.push('\n');
+                                line.push_str(&next);
+                            }
+                        } else if line.trim().is_empty() {
+                            continue;
+                        }
+                        lines.push(line);
+                    }
+                    if lines.is_empty() {
+                        break;
+                    }
+                    let batch = if csv {
+                        Value::from_csv_columns(&header, &lines, env)?
+                    } else {
+                        Value::from_json_lines(&lines, env)?
+                    };
+                    env.push(acc);
+                    env.push(batch);
+                    env.call(f.clone())?;
+                    acc = env.pop("folded value")?;
+                    if lines.len() < size {
+                        break;
+                    }
+                }
+                env.push(acc);
+            }
             SysOp::AudioStream => {
                 let f = env.pop_function()?;
                 if f.signature() != (1, 1) {
@@ -1723,6 +2542,40 @@ impl SysOp {
                 let addr = env.rt.backend.tcp_addr(handle).map_err(|e| env.error(e))?;
                 env.push(addr.to_string());
             }
+            SysOp::UdpBind => {
+                let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
+                let handle = env.rt.backend.udp_bind(&addr).map_err(|e| env.error(e))?;
+                let sock_addr = env.rt.backend.tcp_addr(handle).map_err(|e| env.error(e))?;
+                let handle = handle.value(HandleKind::UdpSocket(sock_addr));
+                env.push(handle);
+            }
+            SysOp::UdpSend => {
+                let data = env.pop(1)?;
+                let addr = env.pop(2)?.as_string(env, "Address must be a string")?;
+                let handle = env.pop(3)?.as_handle(env, "")?;
+                let bytes: Vec<u8> = match data {
+                    Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
+                    Value::Byte(arr) => arr.data.into(),
+                    Value::Complex(_) => return Err(env.error("Cannot send complex array")),
+                    Value::Char(arr) => arr.data.iter().collect::<String>().into(),
+                    Value::Box(_) => return Err(env.error("Cannot send box array")),
+                    Value::Time(_) => return Err(env.error("Cannot send datetime array")),
+                };
+                (env.rt.backend)
+                    .udp_send(handle, &bytes, &addr)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::UdpReceive => {
+                let max = env
+                    .pop(1)?
+                    .as_nat(env, "Maximum size must be a natural number")?;
+                let handle = env.pop(2)?.as_handle(env, "")?;
+                let (bytes, addr) = (env.rt.backend)
+                    .udp_receive(handle, max)
+                    .map_err(|e| env.error(e))?;
+                env.push(addr.to_string());
+                env.push(Array::<u8>::from(bytes.as_slice()));
+            }
             SysOp::TcpSetNonBlocking => {
                 let handle = env.pop(1)?.as_handle(env, "")?;
                 (env.rt.backend)
@@ -1763,6 +2616,78 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.push(res);
             }
+            SysOp::ChannelCreate => {
+                let capacity = (env.pop(1)?)
+                    .as_nat_or_inf(env, "Capacity must be a natural number or infinity")?;
+                let handle = (env.rt.backend)
+                    .create_channel(capacity)
+                    .map_err(|e| env.error(e))?;
+                env.push(handle.value(HandleKind::Channel));
+            }
+            SysOp::ChannelSend => {
+                let value = env.pop(1)?;
+                let handle = env.pop(2)?.as_handle(env, "")?;
+                (env.rt.backend)
+                    .channel_send(handle, value)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::ChannelRecv => {
+                let handle = env.pop(1)?.as_handle(env, "")?;
+                let value = (env.rt.backend)
+                    .channel_recv(handle)
+                    .map_err(|e| env.error(e))?;
+                env.push(value);
+            }
+            SysOp::ChannelTryRecv => {
+                let handle = env.pop(1)?.as_handle(env, "")?;
+                let value = (env.rt.backend)
+                    .channel_try_recv(handle)
+                    .map_err(|e| env.error(e))?
+                    .ok_or_else(|| env.error("No value available"))?;
+                env.push(value);
+            }
+            SysOp::HttpRequest => {
+                let method = env.pop(1)?.as_string(env, "HTTP method must be a string")?;
+                let url = env.pop(2)?.as_string(env, "URL must be a string")?;
+                let headers = env.pop(3)?;
+                let headers = if headers.is_map() {
+                    (headers.map_kv().into_iter())
+                        .map(|(k, v)| {
+                            let k = k.as_string(env, "Header names must be strings")?;
+                            let v = v.as_string(env, "Header values must be strings")?;
+                            Ok((k, v))
+                        })
+                        .collect::<UiuaResult<_>>()?
+                } else if headers.row_count() == 0 {
+                    Vec::new()
+                } else {
+                    return Err(env.error("Headers must be a map array"));
+                };
+                let body = match env.pop(4)? {
+                    Value::Char(arr) if arr.rank() <= 1 => {
+                        arr.data.iter().collect::<String>().into_bytes()
+                    }
+                    body => body.as_bytes(env, "Body must be a string or byte array")?,
+                };
+                let request = HttpRequest {
+                    method,
+                    url,
+                    headers,
+                    body,
+                };
+                let response = (env.rt.backend)
+                    .http_request(request)
+                    .map_err(|e| env.error(e))?;
+                let (keys, values): (EcoVec<Boxed>, EcoVec<Boxed>) = (response.headers)
+                    .into_iter()
+                    .map(|(k, v)| (Boxed(k.into()), Boxed(v.into())))
+                    .unzip();
+                let mut headers = Value::from(Array::from(values));
+                headers.map(keys.into(), env)?;
+                env.push(Array::<u8>::from(response.body.as_slice()));
+                env.push(headers);
+                env.push(response.status as f64);
+            }
             SysOp::Close => {
                 let handle = env.pop(1)?.as_handle(env, "")?;
                 env.rt.backend.close(handle).map_err(|e| env.error(e))?;
@@ -1794,6 +2719,28 @@ impl SysOp {
                     .value(HandleKind::ChildProcess(command));
                 env.push(handle);
             }
+            SysOp::RunStreamStderr => {
+                let (command, args) = value_to_command(&env.pop(1)?, env)?;
+                let args: Vec<_> = args.iter().map(|s| s.as_str()).collect();
+                let (handle, stderr) = (env.rt.backend)
+                    .run_command_stream_stderr(&command, &args)
+                    .map_err(|e| env.error(e))?;
+                env.push(stderr.value(HandleKind::ChildStderr(command.clone())));
+                env.push(handle.value(HandleKind::ChildProcess(command)));
+            }
+            SysOp::RunCloseStdin => {
+                let handle = env.pop(1)?.as_handle(env, "")?;
+                (env.rt.backend)
+                    .close_command_stdin(handle)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::RunWait => {
+                let handle = env.pop(1)?.as_handle(env, "")?;
+                let code = (env.rt.backend)
+                    .wait_command(handle)
+                    .map_err(|e| env.error(e))?;
+                env.push(code);
+            }
             SysOp::ChangeDirectory => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 (env.rt.backend)
@@ -1842,6 +2789,57 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.push(result);
             }
+            SysOp::FfiCallback => {
+                let f = env.pop_function()?;
+                let sig_def = env.pop(1)?;
+                let sig_def = match sig_def {
+                    Value::Box(arr) if arr.rank() == 1 && arr.row_count() > 0 => arr,
+                    val => {
+                        return Err(env.error(format!(
+                            "FFI callback signature must be a non-empty list of boxed strings, \
+                            but it is {} {}",
+                            val.shape(),
+                            val.type_name_plural()
+                        )))
+                    }
+                };
+                let mut tys = (sig_def.data.into_iter())
+                    .map(|frag| {
+                        (frag.0)
+                            .as_string(env, "FFI callback types must be strings")
+                            .and_then(|ty| ty.parse::<FfiType>().map_err(|e| env.error(e)))
+                    })
+                    .collect::<UiuaResult<Vec<_>>>()?;
+                let result_ty = tys.remove(0);
+                let arg_tys = tys;
+                let sig = Signature::new(arg_tys.len(), (result_ty != FfiType::Void) as usize);
+                if f.signature() != sig {
+                    return Err(env.error(format!(
+                        "&ffcb's function's signature must be {sig} to match the \
+                        callback's types, but it is {}",
+                        f.signature()
+                    )));
+                }
+                let mut callback_env = env.clone();
+                let ptr = (env.rt.backend)
+                    .ffi_callback(
+                        result_ty,
+                        &arg_tys,
+                        Box::new(move |args| {
+                            for arg in args.into_iter().rev() {
+                                callback_env.push(arg);
+                            }
+                            callback_env.call(f.clone())?;
+                            Ok(if sig.outputs == 0 {
+                                None
+                            } else {
+                                Some(callback_env.pop("callback result")?)
+                            })
+                        }),
+                    )
+                    .map_err(|e| env.error(e))?;
+                env.push(ptr);
+            }
             SysOp::MemCopy => {
                 let ty = env
                     .pop(1)?
@@ -1875,6 +2873,34 @@ impl SysOp {
     }
 }
 
+/// Match a glob pattern against a single path component
+fn glob_match_name(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Match a glob pattern against the components of a path
+///
+/// A `**` component matches any number of components.
+fn glob_match_path(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| glob_match_path(rest, &path[i..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(name, path)| {
+            glob_match_name(first, name) && glob_match_path(rest, path)
+        }),
+    }
+}
+
 fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String>)> {
     let mut strings = Vec::new();
     match value {
@@ -1922,6 +2948,12 @@ fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String
                 value.type_name_plural()
             )))
         }
+        Value::Time(_) => {
+            return Err(env.error(format!(
+                "Command must be a string or box array, but it is {}",
+                value.type_name_plural()
+            )))
+        }
         Value::Byte(_) => {
             return Err(env.error(format!(
                 "Command must be a string or box array, but it is {}",
@@ -2387,3 +3419,82 @@ pub fn gif_bytes_to_value(bytes: &[u8]) -> Result<(f64, Value), gif::DecodingErr
     num.compress();
     Ok((frame_rate, num))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn log_records() {
+        use std::{any::Any, sync::Mutex};
+
+        #[derive(Default)]
+        struct LogSys(Mutex<Vec<LogRecord>>);
+        impl SysBackend for LogSys {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+            fn log(&self, record: LogRecord) -> Result<(), String> {
+                self.0.lock().unwrap().push(record);
+                Ok(())
+            }
+        }
+
+        let mut env = Uiua::with_backend(LogSys::default());
+        env.run_str("&log \"warn\" \"Rows\" [1 2 3]\n&log \"debug\" \"Done\" □\"ok\"")
+            .unwrap();
+        let sys = env.take_backend::<LogSys>().unwrap();
+        let records = sys.0.into_inner().unwrap();
+        let [rows, done] = &records[..] else {
+            panic!("{} records", records.len())
+        };
+        assert_eq!(rows.level, LogLevel::Warn);
+        assert_eq!(rows.message, "Rows");
+        assert_eq!(rows.value, Value::from([1, 2, 3]));
+        assert_eq!(rows.to_string(), "[warn] Rows: [1 2 3]");
+        assert!(done.level < LogLevel::Info);
+
+        let err = env.run_str("&log \"loud\" \"Rows\" 1").err().unwrap();
+        assert!(err.to_string().contains("Unknown log level"), "{err}");
+    }
+
+    #[test]
+    fn sys_policy() {
+        let policy = SysPolicy::allow_all()
+            .deny(SysCapability::FsWrite)
+            .deny(SysCapability::Network);
+        let mut env = Uiua::with_native_sys().with_policy(policy);
+        env.run_str("&fe \"Cargo.toml\"").unwrap();
+        for code in [
+            "&fwa \"policy.txt\" \"denied\"",
+            "&fo \"Cargo.toml\"",
+            "&tcpc \"localhost:1\"",
+            "wait spawn(&fc \"policy.txt\") 0",
+        ] {
+            let err = env.run_str(code).err().unwrap().to_string();
+            assert!(err.contains("Permission denied"), "{code}: {err}");
+        }
+        assert!(!std::path::Path::new("policy.txt").exists());
+
+        let mut env = Uiua::with_native_sys().with_policy(SysPolicy::deny_all());
+        let err = env
+            .run_str("&fras \"Cargo.toml\"")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("&fras needs filesystem read access"), "{err}");
+        env.run_str("&p \"still allowed\"").unwrap();
+        for code in [
+            "comptime(&fras \"Cargo.toml\")",
+            "~ \"tests/units.ua\"",
+            "~ \"https://example.com/lib.ua\"",
+            "~ \"git:uiua-lang/example\"",
+        ] {
+            let err = env.run_str(code).err().unwrap().to_string();
+            assert!(err.contains("Permission denied"), "{code}: {err}");
+        }
+    }
+}
@@ -2,20 +2,39 @@ use std::{
     any::Any,
     env,
     fs::{self, File, OpenOptions},
-    io::{stderr, stdin, stdout, BufReader, Read, Write},
+    io::{stderr, stdin, stdout, BufReader, IsTerminal, Read, Write},
     net::*,
     path::{Path, PathBuf},
-    process::{Child, Command, Stdio},
+    process::{Child, ChildStderr, Command, Stdio},
     slice,
     sync::atomic::{self, AtomicBool, AtomicU64},
     thread::sleep,
-    time::Duration,
+    time::{Duration, UNIX_EPOCH},
 };
 
-use crate::{Handle, SysBackend};
+use crate::{
+    FileKind, FileMetadata, Handle, SysBackend, TerminalGraphics, Timestamp, Uiua, Value,
+    TERMINAL_GRAPHICS_VAR,
+};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
 
+/// Get the width of the terminal if stdout is one
+pub(crate) fn stdout_width() -> Option<usize> {
+    (stdout().is_terminal())
+        .then(term_size::dimensions)
+        .flatten()
+        .map(|(w, _)| w)
+}
+
+/// Get the terminal graphics protocol set in the environment if stdout is a terminal
+pub(crate) fn stdout_graphics() -> Option<TerminalGraphics> {
+    (stdout().is_terminal())
+        .then(|| env::var(TERMINAL_GRAPHICS_VAR).ok()?.parse().ok())
+        .flatten()
+}
+
 /// The defualt native system backend
 #[derive(Default)]
 pub struct NativeSys;
@@ -25,12 +44,17 @@ struct GlobalNativeSys {
     next_handle: AtomicU64,
     files: DashMap<Handle, BufReader<File>>,
     child_procs: DashMap<Handle, Child>,
+    child_stderrs: DashMap<Handle, ChildStderr>,
     tcp_listeners: DashMap<Handle, TcpListener>,
     tls_listeners: DashMap<Handle, TlsListener>,
     tcp_sockets: DashMap<Handle, TcpStream>,
     tls_sockets: DashMap<Handle, TlsSocket>,
+    udp_sockets: DashMap<Handle, UdpSocket>,
+    channels: DashMap<Handle, (Sender<Value>, Receiver<Value>)>,
     hostnames: DashMap<Handle, String>,
     git_paths: DashMap<String, Result<PathBuf, String>>,
+    #[cfg(feature = "watch")]
+    watchers: DashMap<Vec<String>, std::sync::Arc<crate::FileWatcher>>,
     #[cfg(feature = "audio")]
     audio_stream_time: parking_lot::Mutex<Option<f64>>,
     #[cfg(feature = "audio")]
@@ -45,6 +69,7 @@ struct GlobalNativeSys {
 enum SysStream<'a> {
     File(dashmap::mapref::one::RefMut<'a, Handle, BufReader<File>>),
     Child(dashmap::mapref::one::RefMut<'a, Handle, Child>),
+    ChildStderr(dashmap::mapref::one::RefMut<'a, Handle, ChildStderr>),
     TcpSocket(dashmap::mapref::one::Ref<'a, Handle, TcpStream>),
     TlsSocket(dashmap::mapref::one::Ref<'a, Handle, TlsSocket>),
 }
@@ -113,12 +138,17 @@ impl Default for GlobalNativeSys {
             next_handle: Handle::FIRST_UNRESERVED.0.into(),
             files: DashMap::new(),
             child_procs: DashMap::new(),
+            child_stderrs: DashMap::new(),
             tcp_listeners: DashMap::new(),
             tls_listeners: DashMap::new(),
             tcp_sockets: DashMap::new(),
             tls_sockets: DashMap::new(),
+            udp_sockets: DashMap::new(),
+            channels: DashMap::new(),
             hostnames: DashMap::new(),
             git_paths: DashMap::new(),
+            #[cfg(feature = "watch")]
+            watchers: DashMap::new(),
             #[cfg(feature = "audio")]
             audio_stream_time: parking_lot::Mutex::new(None),
             #[cfg(feature = "audio")]
@@ -138,9 +168,12 @@ impl GlobalNativeSys {
             let handle = Handle(self.next_handle.fetch_add(1, atomic::Ordering::Relaxed));
             if !self.files.contains_key(&handle)
                 && !self.child_procs.contains_key(&handle)
+                && !self.child_stderrs.contains_key(&handle)
                 && !self.tcp_listeners.contains_key(&handle)
                 && !self.tcp_sockets.contains_key(&handle)
                 && !self.tls_sockets.contains_key(&handle)
+                && !self.udp_sockets.contains_key(&handle)
+                && !self.channels.contains_key(&handle)
             {
                 return handle;
             }
@@ -152,6 +185,8 @@ impl GlobalNativeSys {
             SysStream::File(file)
         } else if let Some(child) = self.child_procs.get_mut(&handle) {
             SysStream::Child(child)
+        } else if let Some(stderr) = self.child_stderrs.get_mut(&handle) {
+            SysStream::ChildStderr(stderr)
         } else if let Some(socket) = self.tcp_sockets.get(&handle) {
             SysStream::TcpSocket(socket)
         } else if let Some(tls_socket) = self.tls_sockets.get(&handle) {
@@ -265,6 +300,16 @@ impl SysBackend for NativeSys {
         stdin().read_exact(&mut buffer).map_err(|e| e.to_string())?;
         Ok(buffer)
     }
+    fn scan_stdin_to_end(&self) -> Result<Vec<u8>, String> {
+        if !output_enabled() {
+            return Ok(Vec::new());
+        }
+        let mut buffer = Vec::new();
+        stdin()
+            .read_to_end(&mut buffer)
+            .map_err(|e| e.to_string())?;
+        Ok(buffer)
+    }
     fn save_error_color(&self, message: String, colored: String) {
         NATIVE_SYS.colored_errors.insert(message, colored);
     }
@@ -290,6 +335,11 @@ impl SysBackend for NativeSys {
     fn var(&self, name: &str) -> Option<String> {
         env::var(name).ok()
     }
+    fn vars(&self) -> Vec<(String, String)> {
+        env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+            .collect()
+    }
     fn file_exists(&self, path: &str) -> bool {
         fs::metadata(path).is_ok()
     }
@@ -306,6 +356,42 @@ impl SysBackend for NativeSys {
         }
         Ok(paths)
     }
+    fn walk_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        let mut paths = Vec::new();
+        let mut dirs = vec![PathBuf::from(path)];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+                let entry = entry.map_err(|e| e.to_string())?;
+                // Symlinks are not followed, so cycles are impossible
+                if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+                    dirs.push(entry.path());
+                }
+                paths.push(entry.path().to_string_lossy().into());
+            }
+        }
+        Ok(paths)
+    }
+    fn file_metadata(&self, path: &str) -> Result<FileMetadata, String> {
+        let meta = fs::symlink_metadata(path).map_err(|e| format!("{e} {path}"))?;
+        let kind = if meta.is_symlink() {
+            FileKind::Symlink
+        } else if meta.is_dir() {
+            FileKind::Directory
+        } else {
+            FileKind::File
+        };
+        let modified = (meta.modified().ok())
+            .and_then(|time| match time.duration_since(UNIX_EPOCH) {
+                Ok(since) => Timestamp::from_secs(since.as_secs_f64()),
+                Err(e) => Timestamp::from_secs(-e.duration().as_secs_f64()),
+            })
+            .unwrap_or(Timestamp::EPOCH);
+        Ok(FileMetadata {
+            size: meta.len(),
+            modified,
+            kind,
+        })
+    }
     fn open_file(&self, path: &Path, write: bool) -> Result<Handle, String> {
         let handle = NATIVE_SYS.new_handle();
         let file = OpenOptions::new()
@@ -328,6 +414,55 @@ impl SysBackend for NativeSys {
         NATIVE_SYS.files.insert(handle, BufReader::new(file));
         Ok(handle)
     }
+    fn create_channel(&self, capacity: Option<usize>) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        let channel = match capacity {
+            Some(capacity) => crossbeam_channel::bounded(capacity),
+            None => crossbeam_channel::unbounded(),
+        };
+        NATIVE_SYS.channels.insert(handle, channel);
+        Ok(handle)
+    }
+    fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+        // The map entry is not held while blocking so other threads can use the channel
+        let send = (NATIVE_SYS.channels.get(&handle))
+            .map(|channel| channel.0.clone())
+            .ok_or("Invalid channel handle")?;
+        send.send(value).map_err(|_| "Channel closed".into())
+    }
+    fn channel_recv(&self, handle: Handle) -> Result<Value, String> {
+        let recv = (NATIVE_SYS.channels.get(&handle))
+            .map(|channel| channel.1.clone())
+            .ok_or("Invalid channel handle")?;
+        recv.recv().map_err(|_| "Channel closed".into())
+    }
+    fn channel_try_recv(&self, handle: Handle) -> Result<Option<Value>, String> {
+        let channel = (NATIVE_SYS.channels.get(&handle)).ok_or("Invalid channel handle")?;
+        match channel.1.try_recv() {
+            Ok(value) => Ok(Some(value)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err("Channel closed".into()),
+        }
+    }
+    #[cfg(feature = "watch")]
+    fn watch_files(&self, paths: &[String]) -> Result<Vec<String>, String> {
+        // Watchers are kept so that changes between calls are not missed
+        let mut key = paths.to_vec();
+        key.sort();
+        key.dedup();
+        let watcher = match NATIVE_SYS.watchers.get(&key) {
+            Some(watcher) => watcher.clone(),
+            None => {
+                let watcher = std::sync::Arc::new(crate::FileWatcher::new(&key)?);
+                NATIVE_SYS.watchers.insert(key, watcher.clone());
+                watcher
+            }
+        };
+        let changed = watcher.wait(None)?;
+        Ok((changed.into_iter())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
     fn delete(&self, path: &str) -> Result<(), String> {
         let path = Path::new(path);
         if !path.exists() {
@@ -359,6 +494,12 @@ impl SysBackend for NativeSys {
                 buf.truncate(n);
                 buf
             }
+            SysStream::ChildStderr(mut stderr) => {
+                let mut buf = vec![0; len];
+                let n = stderr.read(&mut buf).map_err(|e| e.to_string())?;
+                buf.truncate(n);
+                buf
+            }
             SysStream::TcpSocket(socket) => {
                 let mut buf = vec![0; len];
                 let n = (&mut &*socket).read(&mut buf).map_err(|e| e.to_string())?;
@@ -387,6 +528,11 @@ impl SysBackend for NativeSys {
                     .map_err(|e| e.to_string())?;
                 buf
             }
+            SysStream::ChildStderr(mut stderr) => {
+                let mut buf = Vec::new();
+                stderr.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+                buf
+            }
             SysStream::TcpSocket(socket) => {
                 let mut buf = Vec::new();
                 ((&mut &*socket).read_to_end(&mut buf)).map_err(|e| e.to_string())?;
@@ -413,9 +559,11 @@ impl SysBackend for NativeSys {
         }
         match NATIVE_SYS.get_stream(handle)? {
             SysStream::File(mut file) => file.get_mut().write_all(conts).map_err(|e| e.to_string()),
-            SysStream::Child(mut child) => (child.stdin.as_mut().unwrap())
+            SysStream::Child(mut child) => (child.stdin.as_mut())
+                .ok_or("The child process's stdin is closed")?
                 .write_all(conts)
                 .map_err(|e| e.to_string()),
+            SysStream::ChildStderr(_) => Err("Cannot write to a child process's stderr".into()),
             SysStream::TcpSocket(socket) => {
                 (&mut &*socket).write_all(conts).map_err(|e| e.to_string())
             }
@@ -440,6 +588,24 @@ impl SysBackend for NativeSys {
             Err(e) => Err(format!("Failed to get clipboard provider: {e}")),
         }
     }
+    fn breakpoint(&self, env: &Uiua) -> Result<bool, String> {
+        if !output_enabled() {
+            return Ok(true);
+        }
+        let snapshot = env.stack_snapshot(stdout_width().unwrap_or(80));
+        let mut message = format!("Breakpoint at {}\n", snapshot.span);
+        if snapshot.values.is_empty() {
+            message.push_str("(empty stack)\n");
+        }
+        for value in snapshot.values.iter().rev() {
+            message.push_str(&value.preview);
+            message.push('\n');
+        }
+        message.push_str("Press enter to continue, or enter q to stop: ");
+        self.print_str_stderr(&message)?;
+        let line = self.scan_line_stdin()?;
+        Ok(line.as_deref().map(str::trim) != Some("q"))
+    }
     fn sleep(&self, seconds: f64) -> Result<(), String> {
         sleep(Duration::from_secs_f64(seconds));
         Ok(())
@@ -667,12 +833,16 @@ impl SysBackend for NativeSys {
     fn tcp_addr(&self, handle: Handle) -> Result<SocketAddr, String> {
         (NATIVE_SYS.get_tcp_stream(handle, |s| s.peer_addr()))
             .or_else(|| NATIVE_SYS.get_tcp_listener(handle, |l| l.local_addr()))
+            .or_else(|| NATIVE_SYS.udp_sockets.get(&handle).map(|s| s.local_addr()))
             .ok_or_else(|| "Invalid tcp socket handle".to_string())
             .and_then(|r| r.map_err(|e| e.to_string()))
     }
     fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
         NATIVE_SYS
             .get_tcp_stream(handle, |s| s.set_nonblocking(non_blocking))
+            .or_else(|| {
+                (NATIVE_SYS.udp_sockets.get(&handle)).map(|s| s.set_nonblocking(non_blocking))
+            })
             .ok_or_else(|| "Invalid tcp socket handle".to_string())?
             .map_err(|e| e.to_string())
     }
@@ -683,6 +853,7 @@ impl SysBackend for NativeSys {
     ) -> Result<(), String> {
         NATIVE_SYS
             .get_tcp_stream(handle, |s| s.set_read_timeout(timeout))
+            .or_else(|| (NATIVE_SYS.udp_sockets.get(&handle)).map(|s| s.set_read_timeout(timeout)))
             .ok_or_else(|| "Invalid tcp socket handle".to_string())?
             .map_err(|e| e.to_string())
     }
@@ -693,13 +864,36 @@ impl SysBackend for NativeSys {
     ) -> Result<(), String> {
         NATIVE_SYS
             .get_tcp_stream(handle, |s| s.set_write_timeout(timeout))
+            .or_else(|| (NATIVE_SYS.udp_sockets.get(&handle)).map(|s| s.set_write_timeout(timeout)))
             .ok_or_else(|| "Invalid tcp socket handle".to_string())?
             .map_err(|e| e.to_string())
     }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        let socket = UdpSocket::bind(addr).map_err(|e| e.to_string())?;
+        NATIVE_SYS.udp_sockets.insert(handle, socket);
+        Ok(handle)
+    }
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        let socket = (NATIVE_SYS.udp_sockets.get(&handle))
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        socket.send_to(data, addr).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, SocketAddr), String> {
+        let socket = (NATIVE_SYS.udp_sockets.get(&handle))
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        let mut buf = vec![0; max];
+        let (n, addr) = socket.recv_from(&mut buf).map_err(|e| e.to_string())?;
+        buf.truncate(n);
+        Ok((buf, addr))
+    }
     fn close(&self, handle: Handle) -> Result<(), String> {
         if let Some((_, mut child)) = NATIVE_SYS.child_procs.remove(&handle) {
             child.kill().map_err(|e| e.to_string())?;
             Ok(())
+        } else if NATIVE_SYS.child_stderrs.remove(&handle).is_some() {
+            Ok(())
         } else if let Some((_, mut file)) = NATIVE_SYS.files.remove(&handle) {
             file.get_mut().flush().map_err(|e| e.to_string())
         } else if let Some((_, socket)) = NATIVE_SYS.tcp_sockets.remove(&handle) {
@@ -710,6 +904,8 @@ impl SysBackend for NativeSys {
             (&mut &socket).flush().map_err(|e| e.to_string())
         } else if NATIVE_SYS.tcp_listeners.remove(&handle).is_some()
             || NATIVE_SYS.tls_listeners.remove(&handle).is_some()
+            || NATIVE_SYS.udp_sockets.remove(&handle).is_some()
+            || NATIVE_SYS.channels.remove(&handle).is_some()
         {
             NATIVE_SYS.hostnames.remove(&handle);
             Ok(())
@@ -757,6 +953,38 @@ impl SysBackend for NativeSys {
         NATIVE_SYS.child_procs.insert(handle, child);
         Ok(handle)
     }
+    fn run_command_stream_stderr(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, Handle), String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let stderr = child.stderr.take().unwrap();
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.child_procs.insert(handle, child);
+        let stderr_handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.child_stderrs.insert(stderr_handle, stderr);
+        Ok((handle, stderr_handle))
+    }
+    fn close_command_stdin(&self, handle: Handle) -> Result<(), String> {
+        let mut child = (NATIVE_SYS.child_procs.get_mut(&handle))
+            .ok_or_else(|| "Invalid child process handle".to_string())?;
+        drop(child.stdin.take());
+        Ok(())
+    }
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        let mut child = (NATIVE_SYS.child_procs.get_mut(&handle))
+            .ok_or_else(|| "Invalid child process handle".to_string())?;
+        drop(child.stdin.take());
+        let status = child.wait().map_err(|e| e.to_string())?;
+        Ok(status.code().unwrap_or(0))
+    }
     fn change_directory(&self, path: &str) -> Result<(), String> {
         env::set_current_dir(path).map_err(|e| e.to_string())
     }
@@ -808,6 +1036,83 @@ impl SysBackend for NativeSys {
 
         Ok(s)
     }
+    #[cfg(feature = "http")]
+    fn http_request(&self, request: crate::HttpRequest) -> Result<crate::HttpResponse, String> {
+        use std::io;
+
+        let (tls, rest) = if let Some(rest) = request.url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = request.url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!(
+                "URL must start with http:// or https://, but it is {}",
+                request.url
+            ));
+        };
+        let rest = rest.split('#').next().unwrap_or(rest);
+        let (authority, path) = rest
+            .find(['/', '?'])
+            .map_or((rest, ""), |i| rest.split_at(i));
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{path}")
+        };
+        let (host, addr) = match authority.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() => (host, authority.to_string()),
+            _ => (
+                authority,
+                format!("{authority}:{}", if tls { 443 } else { 80 }),
+            ),
+        };
+
+        let mut head = format!(
+            "{} {path} HTTP/1.1\r\nHost: {authority}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            request.method,
+            request.body.len()
+        );
+        for (name, value) in &request.headers {
+            if ["host", "connection", "content-length"]
+                .iter()
+                .any(|auto| name.eq_ignore_ascii_case(auto))
+            {
+                continue;
+            }
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str("\r\n");
+        let mut message = head.into_bytes();
+        message.extend_from_slice(&request.body);
+
+        let mut stream = TcpStream::connect(&addr).map_err(|e| e.to_string())?;
+        let mut buffer = Vec::new();
+        if tls {
+            static CLIENT_CONFIG: Lazy<std::sync::Arc<rustls::ClientConfig>> = Lazy::new(|| {
+                let mut store = rustls::RootCertStore::empty();
+                store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                rustls::ClientConfig::builder()
+                    .with_root_certificates(store)
+                    .with_no_client_auth()
+                    .into()
+            });
+            let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+                .map_err(|e| e.to_string())?;
+            let mut conn = rustls::ClientConnection::new(CLIENT_CONFIG.clone(), server_name)
+                .map_err(|e| e.to_string())?;
+            let mut tls = rustls::Stream::new(&mut conn, &mut stream);
+            tls.write_all(&message).map_err(|e| e.to_string())?;
+            match tls.read_to_end(&mut buffer) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        } else {
+            stream.write_all(&message).map_err(|e| e.to_string())?;
+            stream.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+        }
+        parse_http_response(&buffer)
+    }
     #[cfg(feature = "ffi")]
     fn ffi(
         &self,
@@ -822,6 +1127,15 @@ impl SysBackend for NativeSys {
             .do_ffi(file, return_ty, name, arg_tys, arg_values)
     }
     #[cfg(feature = "ffi")]
+    fn ffi_callback(
+        &self,
+        result_ty: crate::FfiType,
+        arg_tys: &[crate::FfiType],
+        f: crate::FfiCallbackFn,
+    ) -> Result<crate::Value, String> {
+        NATIVE_SYS.ffi.make_callback(result_ty, arg_tys, f)
+    }
+    #[cfg(feature = "ffi")]
     fn mem_copy(
         &self,
         ty: crate::FfiType,
@@ -903,6 +1217,48 @@ impl SysBackend for NativeSys {
         NATIVE_SYS.git_paths.insert(url.to_string(), res.clone());
         res
     }
+    fn load_url_module(&self, url: &str) -> Result<PathBuf, String> {
+        let rest = (url.strip_prefix("https://"))
+            .or_else(|| url.strip_prefix("http://"))
+            .ok_or("Invalid module url")?;
+        // Key the cache by the whole url so that distinct urls never share a file
+        let key = crate::cache::hash_input(url);
+        let name = rest.split(['?', '#']).next().unwrap_or(rest);
+        let name: String = (name.rsplit('/').find(|s| !s.is_empty()))
+            .filter(|s| *s != "." && *s != "..")
+            .unwrap_or("module.ua")
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || "._-".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = Path::new("uiua-modules").join("url").join(key).join(name);
+        // Early return if the module is already cached
+        if path.exists() {
+            return Ok(path);
+        }
+        let response = self.http_request(crate::HttpRequest {
+            method: "GET".into(),
+            url: url.into(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        })?;
+        if !(200..300).contains(&response.status) {
+            return Err(format!(
+                "Failed to download module {url}: status {}",
+                response.status
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, response.body).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
 }
 
 /// Takes an HTTP request, validates it, and fixes it (if possible) by adding
@@ -992,3 +1348,212 @@ fn check_http(mut request: String, hostname: &str) -> Result<String, String> {
 
     Ok(request)
 }
+
+/// Parse an HTTP response, merging repeated headers and decoding the body
+#[cfg(feature = "http")]
+fn parse_http_response(bytes: &[u8]) -> Result<crate::HttpResponse, String> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut res = httparse::Response::new(&mut headers);
+    let httparse::Status::Complete(len) = res
+        .parse(bytes)
+        .map_err(|e| format!("Failed to parse HTTP response: {e}"))?
+    else {
+        return Err("Incomplete HTTP response".into());
+    };
+    let mut merged: Vec<(String, String)> = Vec::new();
+    for header in res.headers.iter() {
+        let value = String::from_utf8_lossy(header.value).into_owned();
+        match merged
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(header.name))
+        {
+            Some((_, v)) => {
+                v.push_str(", ");
+                v.push_str(&value);
+            }
+            None => merged.push((header.name.into(), value)),
+        }
+    }
+    let header = |name: &str| {
+        (merged.iter())
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+    let mut body = bytes[len..].to_vec();
+    if header("transfer-encoding").is_some_and(|te| te.to_ascii_lowercase().contains("chunked")) {
+        body = decode_chunked(&body)?;
+    } else if let Some(n) = header("content-length").and_then(|n| n.trim().parse().ok()) {
+        body.truncate(n);
+    }
+    Ok(crate::HttpResponse {
+        status: res.code.unwrap_or(0),
+        headers: merged,
+        body,
+    })
+}
+
+/// Decode an HTTP body with chunked transfer encoding
+#[cfg(feature = "http")]
+fn decode_chunked(mut bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = (bytes.windows(2))
+            .position(|w| w == b"\r\n")
+            .ok_or("Invalid chunked HTTP body")?;
+        let size = std::str::from_utf8(&bytes[..line_end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next()?.trim(), 16).ok())
+            .ok_or("Invalid chunk size in HTTP body")?;
+        bytes = &bytes[line_end + 2..];
+        if size == 0 {
+            break;
+        }
+        let chunk = bytes.get(..size).ok_or("Truncated chunk in HTTP body")?;
+        body.extend_from_slice(chunk);
+        bytes = bytes.get(size + 2..).unwrap_or(&[]);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn http_request() {
+        use std::{
+            io::{Read, Write},
+            net::TcpListener,
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\nhi") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(
+                    b"HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\
+                    Set-Cookie: a\r\nSet-Cookie: b\r\n\r\n\
+                    3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n",
+                )
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut env = Uiua::with_native_sys();
+        env.run_str(&format!(
+            "&http \"POST\" \"http://127.0.0.1:{port}/path?q=1\" map {{\"X-Test\"}} {{\"yes\"}} \"hi\""
+        ))
+        .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /path?q=1 HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nX-Test: yes\r\n"));
+        assert!(request.contains("\r\nContent-Length: 2\r\n"));
+
+        let [body, headers, status] = &env.take_stack()[..] else {
+            panic!("expected 3 values")
+        };
+        assert_eq!(status, &Value::from(201.0));
+        assert_eq!(body, &Value::from(Array::<u8>::from(b"abcde".as_slice())));
+        let cookie = (headers.map_kv().into_iter())
+            .find(|(k, _)| k.as_string(&env, "").unwrap() == "Set-Cookie")
+            .map(|(_, v)| v.as_string(&env, "").unwrap());
+        assert_eq!(cookie.as_deref(), Some("a, b"));
+    }
+
+    #[test]
+    fn udp_sockets() {
+        let mut env = Uiua::with_native_sys();
+        env.run_str(
+            "A ← &udpb \"127.0.0.1:0\"\n\
+            B ← &udpb \"127.0.0.1:0\"\n\
+            &udps \"hello\" &tcpaddr B A\n\
+            &tcpsrt 5 B\n\
+            &udpr 100 B\n\
+            &tcpaddr A\n\
+            &tcpsrt 0.01 B\n\
+            ⍣(◌&udpr 100 B)(\"timed out\")\n\
+            &cl A &cl B",
+        )
+        .unwrap();
+        let [from, bytes, a_addr, timed_out] = &env.take_stack()[..] else {
+            panic!("expected 4 values")
+        };
+        assert_eq!(bytes, &Value::from(Array::<u8>::from(b"hello".as_slice())));
+        assert_eq!(from, a_addr);
+        assert_eq!(timed_out, &Value::from("timed out"));
+    }
+
+    #[test]
+    fn walk_dir() {
+        let dir = std::env::temp_dir().join(format!("uiua_walk_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a/b")).unwrap();
+        std::fs::write(dir.join("x.ua"), "1").unwrap();
+        std::fs::write(dir.join("a/b/y.ua"), "22").unwrap();
+        std::fs::write(dir.join("a/z.txt"), "333").unwrap();
+        let mut env = Uiua::with_native_sys();
+        let res = env.run_str(&format!(
+            "D ← {:?}\n\
+            ⧻&fwalk \"**\" D\n\
+            ⧻&fwalk \"a/**/*.ua\" D\n\
+            °□get \"size\" &fmeta &fwalk \"*.ua\" D",
+            dir.to_string_lossy()
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+        res.unwrap();
+        let sizes = Value::from(Array::<f64>::from_iter([2.0, 1.0]));
+        assert_eq!(env.take_stack(), [5.into(), 1.into(), sizes]);
+    }
+
+    #[test]
+    fn channels() {
+        let mut env = Uiua::with_native_sys();
+        env.run_str(
+            "Ch ← &chan 1\n\
+            ◌≡spawn(&chs:Ch) ⇡10\n\
+            /+[⍥(&chr Ch)10]\n\
+            ⍣&chtr(⋅0) Ch\n\
+            &cl Ch",
+        )
+        .unwrap();
+        assert_eq!(env.take_stack(), [45.into(), 0.into()]);
+    }
+
+    #[test]
+    fn environment_variables() {
+        std::env::set_var("UIUA_TEST_VARS", "hello");
+        let mut env = Uiua::with_native_sys();
+        env.run_str("get \"UIUA_TEST_VARS\" &vars").unwrap();
+        assert_eq!(env.pop_string().unwrap(), "hello");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn command_streams() {
+        let mut env = Uiua::with_native_sys();
+        env.run_str(
+            "Hs ← [&runse {\"sh\" \"-c\" \"cat; echo oops >&2; exit 3\"}]\n\
+            Proc ← ⊡0 Hs\n\
+            Err ← ⊡1 Hs\n\
+            &w \"hello\" Proc\n\
+            &runcin Proc\n\
+            &rs ∞ Proc\n\
+            &runw Proc\n\
+            &rs ∞ Err\n\
+            &cl Proc &cl Err",
+        )
+        .unwrap();
+        let [stdout, code, stderr] = &env.take_stack()[..] else {
+            panic!("expected 3 values")
+        };
+        assert_eq!(stdout, &Value::from("hello"));
+        assert_eq!(code, &Value::from(3));
+        assert_eq!(stderr, &Value::from("oops\n"));
+    }
+}
@@ -0,0 +1,178 @@
+//! The [`Timestamp`] type
+
+use std::fmt;
+
+use serde::*;
+
+/// Uiua's datetime type
+///
+/// A timestamp is a number of nanoseconds since the Unix epoch in UTC.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Timestamp(pub i64);
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const SECS_PER_DAY: i64 = 86400;
+
+impl Timestamp {
+    /// The Unix epoch
+    pub const EPOCH: Self = Self(0);
+    /// Create a timestamp from a number of seconds since the Unix epoch
+    ///
+    /// Returns `None` if the number is not finite or is out of range.
+    pub fn from_secs(secs: f64) -> Option<Self> {
+        let nanos = (secs * NANOS_PER_SEC as f64).round();
+        (nanos.is_finite() && nanos.abs() < i64::MAX as f64).then_some(Self(nanos as i64))
+    }
+    /// Get the number of seconds since the Unix epoch
+    pub fn secs(self) -> f64 {
+        let secs = self.0.div_euclid(NANOS_PER_SEC);
+        let nanos = self.0.rem_euclid(NANOS_PER_SEC);
+        secs as f64 + nanos as f64 / NANOS_PER_SEC as f64
+    }
+    /// Offset the timestamp by a number of seconds
+    pub fn add_secs(self, secs: f64) -> Self {
+        let nanos = (secs * NANOS_PER_SEC as f64).round() as i64;
+        Self(self.0.saturating_add(nanos))
+    }
+    /// Get the number of seconds from another timestamp to this one
+    pub fn secs_since(self, other: Self) -> f64 {
+        let diff = self.0 as i128 - other.0 as i128;
+        diff as f64 / NANOS_PER_SEC as f64
+    }
+    /// Get the date and time components of the timestamp
+    ///
+    /// Returns `[year, month, day, hour, minute, second, nanosecond]`
+    pub fn components(self) -> [i64; 7] {
+        let secs = self.0.div_euclid(NANOS_PER_SEC);
+        let nanos = self.0.rem_euclid(NANOS_PER_SEC);
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let time = secs.rem_euclid(SECS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        [
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            nanos,
+        ]
+    }
+    /// Create a timestamp from date and time components
+    ///
+    /// Components are `[year, month, day, hour, minute, second, nanosecond]`.
+    /// Out-of-range components carry into the next larger ones.
+    pub fn from_components([year, month, day, hour, minute, second, nanos]: [i64; 7]) -> Self {
+        let year = year + (month - 1).div_euclid(12);
+        let month = (month - 1).rem_euclid(12) + 1;
+        let days = days_from_civil(year, month, 1) + day - 1;
+        let secs = days * SECS_PER_DAY + hour * 3600 + minute * 60 + second;
+        Self(secs.saturating_mul(NANOS_PER_SEC).saturating_add(nanos))
+    }
+    /// Parse an ISO 8601 timestamp like `2024-03-01`, `2024-03-01T12:30:00Z`, or `2024-03-01 12:30+02:00`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (date, time) = match s.find(['T', 't', ' ']) {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        let (neg, date) = match date.strip_prefix('-') {
+            Some(date) => (true, date),
+            None => (false, date.strip_prefix('+').unwrap_or(date)),
+        };
+        let mut parts = date.split('-');
+        let year: i64 = parts.next()?.parse().ok()?;
+        let year = if neg { -year } else { year };
+        let month: i64 = parts.next()?.parse().ok()?;
+        let day: i64 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let mut components = [year, month, day, 0, 0, 0, 0];
+        let mut offset = 0;
+        if let Some(time) = time {
+            let (time, off) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+                (time, 0)
+            } else if let Some(i) = time.rfind(['+', '-']) {
+                let sign = if time[i..].starts_with('-') { -1 } else { 1 };
+                let (h, m) = time[i + 1..]
+                    .split_once(':')
+                    .unwrap_or((&time[i + 1..], "0"));
+                let off = h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60;
+                (&time[..i], sign * off)
+            } else {
+                (time, 0)
+            };
+            offset = off;
+            let mut parts = time.split(':');
+            components[3] = parts.next()?.parse().ok()?;
+            components[4] = parts.next()?.parse().ok()?;
+            if let Some(sec) = parts.next() {
+                let (sec, frac) = sec.split_once('.').unwrap_or((sec, ""));
+                components[5] = sec.parse().ok()?;
+                if !frac.is_empty() {
+                    if frac.len() > 9 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+                        return None;
+                    }
+                    components[6] = format!("{frac:0<9}").parse().ok()?;
+                }
+            }
+            if parts.next().is_some()
+                || components[3] > 23
+                || components[4] > 59
+                || components[5] > 60
+            {
+                return None;
+            }
+        }
+        let ts = Self::from_components(components);
+        Some(Self(ts.0.saturating_sub(offset * NANOS_PER_SEC)))
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [year, month, day, hour, minute, second, nanos] = self.components();
+        if (0..10000).contains(&year) {
+            write!(f, "{year:04}")?;
+        } else {
+            write!(f, "{year:+05}")?;
+        }
+        write!(f, "-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}")?;
+        if nanos != 0 {
+            let frac = format!("{nanos:09}");
+            write!(f, ".{}", frac.trim_end_matches('0'))?;
+        }
+        write!(f, "Z")
+    }
+}
+
+/// Get the year, month, and day of a number of days since the Unix epoch
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html>
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Get the number of days since the Unix epoch of a year, month, and day
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
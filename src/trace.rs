@@ -0,0 +1,114 @@
+//! Per-primitive execution tracing
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::{ImplPrimitive, Primitive, Shape, Span};
+
+/// A primitive that was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TracedPrim {
+    /// A normal primitive
+    Prim(Primitive),
+    /// A primitive that exists as an implementation detail
+    Impl(ImplPrimitive),
+}
+
+impl fmt::Display for TracedPrim {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TracedPrim::Prim(prim) => prim.fmt(f),
+            TracedPrim::Impl(prim) => prim.fmt(f),
+        }
+    }
+}
+
+/// A record of a single primitive execution
+#[derive(Debug, Clone)]
+pub struct PrimTrace {
+    /// The primitive
+    pub prim: TracedPrim,
+    /// The span of the primitive in the source
+    pub span: Span,
+    /// The shapes of the primitive's arguments
+    pub arg_shapes: Vec<Shape>,
+    /// The time taken in milliseconds
+    ///
+    /// This includes the time taken by any functions the primitive calls.
+    pub time: f64,
+}
+
+/// Aggregated statistics for a primitive
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PrimStats {
+    /// The number of times the primitive was executed
+    pub count: usize,
+    /// The total time taken in milliseconds
+    pub total_time: f64,
+    /// The longest time taken by a single execution in milliseconds
+    pub max_time: f64,
+}
+
+type TraceSink = Arc<dyn Fn(&PrimTrace) + Send + Sync>;
+
+/// Collects primitive traces during execution
+#[derive(Clone, Default)]
+pub(crate) struct Tracer {
+    sink: Option<TraceSink>,
+    totals: Arc<Mutex<HashMap<TracedPrim, PrimStats>>>,
+}
+
+impl Tracer {
+    pub fn with_sink(sink: impl Fn(&PrimTrace) + Send + Sync + 'static) -> Self {
+        Tracer {
+            sink: Some(Arc::new(sink)),
+            ..Tracer::default()
+        }
+    }
+    pub fn record(&self, trace: PrimTrace) {
+        let mut totals = self.totals.lock();
+        let stats = totals.entry(trace.prim).or_default();
+        stats.count += 1;
+        stats.total_time += trace.time;
+        stats.max_time = stats.max_time.max(trace.time);
+        drop(totals);
+        if let Some(sink) = &self.sink {
+            sink(&trace);
+        }
+    }
+    /// Take the totals, sorted by descending total time
+    pub fn take_totals(&self) -> Vec<(TracedPrim, PrimStats)> {
+        let mut totals: Vec<_> = self.totals.lock().drain().collect();
+        totals.sort_by(|(_, a), (_, b)| b.total_time.total_cmp(&a.total_time));
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn prim_tracing() {
+        use std::sync::{Arc, Mutex};
+
+        let traces = Arc::new(Mutex::new(Vec::new()));
+        let sink_traces = traces.clone();
+        let mut env = Uiua::with_safe_sys()
+            .with_trace_sink(move |trace| sink_traces.lock().unwrap().push(trace.clone()));
+        env.push(3);
+        env.run_str("/+ ⇡ ⍥(+1) 2").unwrap();
+        let traces = traces.lock().unwrap();
+        let range = (traces.iter())
+            .find(|trace| trace.prim == TracedPrim::Prim(Primitive::Range))
+            .unwrap();
+        assert_eq!(range.arg_shapes, [Shape::scalar()]);
+        let stats = env.take_prim_stats();
+        let (_, add) = (stats.iter())
+            .find(|(prim, _)| *prim == TracedPrim::Prim(Primitive::Add))
+            .unwrap();
+        assert_eq!(add.count, 2);
+        assert!(env.take_prim_stats().is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+//! Resource usage accounting
+
+use std::{collections::HashMap, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::{TracedPrim, Value};
+
+/// Resources used by a run
+///
+/// Get it with [`Uiua::usage`](crate::Uiua::usage).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The total number of array elements allocated
+    ///
+    /// Only allocations made by algorithms that can create large arrays
+    /// (such as `reshape`, `keep`, `windows`, and `range`) are counted.
+    pub allocated_elements: usize,
+    /// The largest number of bytes held by arrays on the stack at once
+    ///
+    /// This does not count the contents of boxes.
+    pub peak_array_bytes: usize,
+    /// The total number of elements in the arguments of executed primitives
+    pub elements_processed: usize,
+    /// The number of times each primitive was executed
+    pub prim_calls: HashMap<TracedPrim, usize>,
+}
+
+impl ResourceUsage {
+    /// Get the total number of primitive executions
+    pub fn total_prim_calls(&self) -> usize {
+        self.prim_calls.values().sum()
+    }
+}
+
+/// Counts resources used during execution
+#[derive(Clone, Default)]
+pub(crate) struct UsageCounter {
+    usage: Arc<Mutex<ResourceUsage>>,
+}
+
+impl UsageCounter {
+    pub fn reset(&self) {
+        *self.usage.lock() = ResourceUsage::default();
+    }
+    pub fn record_call(&self, prim: TracedPrim, args: &[Value]) {
+        let mut usage = self.usage.lock();
+        usage.elements_processed += args.iter().map(Value::element_count).sum::<usize>();
+        *usage.prim_calls.entry(prim).or_default() += 1;
+    }
+    pub fn record_stack(&self, stack: &[Value]) {
+        let bytes = (stack.iter())
+            .map(|val| val.element_count() * val.elem_size())
+            .sum();
+        let mut usage = self.usage.lock();
+        usage.peak_array_bytes = usage.peak_array_bytes.max(bytes);
+    }
+    pub fn get(&self, allocated_elements: usize) -> ResourceUsage {
+        ResourceUsage {
+            allocated_elements,
+            ..self.usage.lock().clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn usage_accounting() {
+        let mut env = Uiua::with_safe_sys().with_usage_accounting();
+        env.push(5);
+        env.push(1000);
+        env.run_str("⍥(+1) 2 /+ ⇡").unwrap();
+        let usage = env.usage().unwrap();
+        assert_eq!(usage.allocated_elements, 1000);
+        assert_eq!(usage.peak_array_bytes, 1001 * size_of::<f64>());
+        assert!(usage.elements_processed >= 1000);
+        assert_eq!(usage.prim_calls[&TracedPrim::Prim(Primitive::Add)], 2);
+        assert_eq!(usage.prim_calls[&TracedPrim::Prim(Primitive::Range)], 1);
+
+        // Counts are reset for each run
+        env.run_str("+").unwrap();
+        let usage = env.usage().unwrap();
+        assert_eq!(usage.total_prim_calls(), 1);
+        assert_eq!(usage.allocated_elements, 0);
+        assert!(Uiua::with_safe_sys().usage().is_none());
+    }
+}
@@ -11,17 +11,22 @@ use ecow::{EcoString, EcoVec};
 use serde::*;
 
 use crate::{
-    algorithm::{map::MapKeys, pervade::*, ErrorContext, FillContext},
+    algorithm::{map::MapKeys, pervade::*, units, units::Unit, ErrorContext, FillContext},
     array::*,
     cowslice::CowSlice,
     grid_fmt::GridFmt,
-    Boxed, Complex, Shape, Uiua, UiuaResult,
+    Boxed, Complex, Shape, Timestamp, Uiua, UiuaResult,
 };
 
+/// The number of rows, number of columns, and row-major data of a numeric matrix
+///
+/// Returned by [`Value::as_matrix`].
+pub type NumMatrix<'a> = (usize, usize, Cow<'a, [f64]>);
+
 /// A generic array value
 ///
 /// This enum is used to represent all possible array types.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize)]
 #[serde(untagged)]
 #[repr(C)]
 pub enum Value {
@@ -35,6 +40,44 @@ pub enum Value {
     Char(Array<char>),
     /// Common box array
     Box(Array<Boxed>),
+    /// Datetime array
+    Time(Array<Timestamp>),
+}
+
+/// The deserialized form of a [`Value`]
+///
+/// Arrays are validated only after a variant is chosen,
+/// so that validation errors are not hidden by the untagged enum.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueRep {
+    Byte(ArrayRep<u8>),
+    Num(ArrayRep<f64>),
+    Complex(ArrayRep<Complex>),
+    Char(ArrayRep<char>),
+    Box(ArrayRep<Boxed>),
+    Time(ArrayRep<Timestamp>),
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn validate<T, E>(rep: ArrayRep<T>) -> Result<Value, E>
+        where
+            T: ArrayValueSer,
+            Array<T>: TryFrom<ArrayRep<T>, Error = String> + Into<Value>,
+            E: de::Error,
+        {
+            Array::try_from(rep).map(Into::into).map_err(E::custom)
+        }
+        match ValueRep::deserialize(deserializer)? {
+            ValueRep::Byte(rep) => validate(rep),
+            ValueRep::Num(rep) => validate(rep),
+            ValueRep::Complex(rep) => validate(rep),
+            ValueRep::Char(rep) => validate(rep),
+            ValueRep::Box(rep) => validate(rep),
+            ValueRep::Time(rep) => validate(rep),
+        }
+    }
 }
 
 impl Default for Value {
@@ -63,6 +106,7 @@ impl Value {
             Self::Complex(_) => Complex::TYPE_ID,
             Self::Char(_) => char::TYPE_ID,
             Self::Box(_) => Boxed::TYPE_ID,
+            Self::Time(_) => Timestamp::TYPE_ID,
         }
     }
     /// Get a reference to a possible number array
@@ -105,6 +149,7 @@ impl Value {
             Self::Complex(array) => Box::new(array.rows().map(Value::from)),
             Self::Char(array) => Box::new(array.rows().map(Value::from)),
             Self::Box(array) => Box::new(array.rows().map(Value::from)),
+            Self::Time(array) => Box::new(array.rows().map(Value::from)),
         }
     }
     /// Get an iterator over the rows of the value that have the given shape
@@ -118,6 +163,7 @@ impl Value {
             Self::Complex(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             Self::Char(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             Self::Box(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
+            Self::Time(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
         }
     }
     /// Get an iterator over the rows of the value that have the given shape
@@ -133,6 +179,7 @@ impl Value {
             }
             Self::Char(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
             Self::Box(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
+            Self::Time(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
         }
     }
     /// Consume the value and get an iterator over its rows
@@ -143,6 +190,7 @@ impl Value {
             Self::Complex(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Char(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Box(array) => Box::new(array.into_rows().map(Value::from)),
+            Self::Time(array) => Box::new(array.into_rows().map(Value::from)),
         }
     }
     /// Get an iterator over the elements of the value
@@ -153,6 +201,7 @@ impl Value {
             Self::Complex(array) => Box::new(array.data.iter().copied().map(Value::from)),
             Self::Char(array) => Box::new(array.data.iter().copied().map(Value::from)),
             Self::Box(array) => Box::new(array.data.iter().cloned().map(Value::from)),
+            Self::Time(array) => Box::new(array.data.iter().cloned().map(Value::from)),
         }
     }
     /// Cosume the value and get an iterator over its elements
@@ -163,6 +212,7 @@ impl Value {
             Self::Complex(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Char(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Box(array) => Box::new(array.data.into_iter().map(Value::from)),
+            Self::Time(array) => Box::new(array.data.into_iter().map(Value::from)),
         }
     }
     /// Get the value's type name
@@ -173,6 +223,7 @@ impl Value {
             Self::Complex(_) => "complex",
             Self::Char(_) => "character",
             Self::Box(_) => "box",
+            Self::Time(_) => "datetime",
         }
     }
     /// Get a plural form of the value's type name
@@ -183,6 +234,7 @@ impl Value {
             Self::Complex(_) => "complexes",
             Self::Char(_) => "characters",
             Self::Box(_) => "boxes",
+            Self::Time(_) => "datetimes",
         }
     }
     /// Get the number of rows
@@ -215,6 +267,10 @@ impl Value {
                 .box_scalar_fill()
                 .unwrap_or_else(|_| Boxed::proxy())
                 .into(),
+            Self::Time(_) => env
+                .time_scalar_fill()
+                .unwrap_or_else(|_| Timestamp::proxy())
+                .into(),
         }
     }
     pub(crate) fn proxy_row(&self, env: &Uiua) -> Self {
@@ -262,6 +318,16 @@ impl Value {
                     .collect::<CowSlice<_>>(),
             )
             .into(),
+            Self::Time(_) => Array::new(
+                shape,
+                repeat(
+                    env.time_scalar_fill()
+                        .unwrap_or_else(|_| Timestamp::proxy()),
+                )
+                .take(elem_count)
+                .collect::<CowSlice<_>>(),
+            )
+            .into(),
         }
     }
     pub(crate) fn first_dim_zero(&self) -> Self {
@@ -271,6 +337,7 @@ impl Value {
             Self::Complex(array) => array.first_dim_zero().into(),
             Self::Char(array) => array.first_dim_zero().into(),
             Self::Box(array) => array.first_dim_zero().into(),
+            Self::Time(array) => array.first_dim_zero().into(),
         }
     }
     /// Get the rank
@@ -284,6 +351,7 @@ impl Value {
             Self::Complex(array) => array.pop_row().map(Value::from),
             Self::Char(array) => array.pop_row().map(Value::from),
             Self::Box(array) => array.pop_row().map(Value::from),
+            Self::Time(array) => array.pop_row().map(Value::from),
         }
     }
     pub(crate) fn elem_size(&self) -> usize {
@@ -293,6 +361,7 @@ impl Value {
             Self::Complex(_) => size_of::<Complex>(),
             Self::Char(_) => size_of::<char>(),
             Self::Box(_) => size_of::<Boxed>(),
+            Self::Time(_) => size_of::<Timestamp>(),
         }
     }
 }
@@ -342,10 +411,23 @@ impl Value {
     pub fn take_label(&mut self) -> Option<EcoString> {
         unsafe { self.repr_mut() }.arr.take_label()
     }
+    /// Set the value's label
+    pub fn with_label(mut self, label: impl Into<EcoString>) -> Self {
+        self.meta_mut().label = Some(label.into());
+        self
+    }
     /// Take the map keys from the value
     pub fn take_map_keys(&mut self) -> Option<MapKeys> {
         unsafe { self.repr_mut() }.arr.take_map_keys()
     }
+    /// Take the axis names from the value
+    pub fn take_axes(&mut self) -> Option<EcoVec<EcoString>> {
+        unsafe { self.repr_mut() }.arr.take_axes()
+    }
+    /// Take the unit of measure from the value
+    pub fn take_unit(&mut self) -> Option<Unit> {
+        unsafe { self.repr_mut() }.arr.take_unit()
+    }
     /// Take the persistent metadata from the value
     pub fn take_per_meta(&mut self) -> PersistentMeta {
         unsafe { self.repr_mut() }.arr.take_per_meta()
@@ -354,6 +436,10 @@ impl Value {
     pub fn set_per_meta(&mut self, per_meta: PersistentMeta) {
         unsafe { self.repr_mut() }.arr.set_per_meta(per_meta)
     }
+    /// Get the names of the value's axes
+    pub fn axes(&self) -> Option<&[EcoString]> {
+        unsafe { self.repr() }.arr.axes()
+    }
     /// Get the value's map keys
     pub fn map_keys(&self) -> Option<&MapKeys> {
         unsafe { self.repr() }.arr.map_keys()
@@ -396,6 +482,7 @@ impl Value {
             Array::validate_shape,
             Array::validate_shape,
             Array::validate_shape,
+            Array::validate_shape,
         )
     }
     /// Get the row at the given index
@@ -407,6 +494,7 @@ impl Value {
             Value::Complex(arr) => arr.row(i).into(),
             Value::Char(arr) => arr.row(i).into(),
             Value::Box(arr) => arr.row(i).into(),
+            Value::Time(arr) => arr.row(i).into(),
         }
     }
     #[track_caller]
@@ -417,6 +505,7 @@ impl Value {
             Value::Complex(arr) => arr.depth_row(depth, i).into(),
             Value::Char(arr) => arr.depth_row(depth, i).into(),
             Value::Box(arr) => arr.depth_row(depth, i).into(),
+            Value::Time(arr) => arr.depth_row(depth, i).into(),
         }
     }
     pub(crate) fn generic_into<T>(
@@ -426,6 +515,7 @@ impl Value {
         _co: impl FnOnce(Array<Complex>) -> T,
         ch: impl FnOnce(Array<char>) -> T,
         f: impl FnOnce(Array<Boxed>) -> T,
+        tm: impl FnOnce(Array<Timestamp>) -> T,
     ) -> T {
         match self {
             Self::Num(array) => n(array),
@@ -433,6 +523,7 @@ impl Value {
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
+            Self::Time(array) => tm(array),
         }
     }
     pub(crate) fn generic_ref<'a, T: 'a>(
@@ -442,6 +533,7 @@ impl Value {
         _co: impl FnOnce(&'a Array<Complex>) -> T,
         ch: impl FnOnce(&'a Array<char>) -> T,
         f: impl FnOnce(&'a Array<Boxed>) -> T,
+        tm: impl FnOnce(&'a Array<Timestamp>) -> T,
     ) -> T {
         match self {
             Self::Num(array) => n(array),
@@ -449,8 +541,10 @@ impl Value {
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
+            Self::Time(array) => tm(array),
         }
     }
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn generic_ref_env<'a, T: 'a>(
         &'a self,
         n: impl FnOnce(&'a Array<f64>, &Uiua) -> UiuaResult<T>,
@@ -458,6 +552,7 @@ impl Value {
         co: impl FnOnce(&'a Array<Complex>, &Uiua) -> UiuaResult<T>,
         ch: impl FnOnce(&'a Array<char>, &Uiua) -> UiuaResult<T>,
         f: impl FnOnce(&'a Array<Boxed>, &Uiua) -> UiuaResult<T>,
+        tm: impl FnOnce(&'a Array<Timestamp>, &Uiua) -> UiuaResult<T>,
         env: &Uiua,
     ) -> UiuaResult<T> {
         self.generic_ref(
@@ -466,6 +561,7 @@ impl Value {
             |a| co(a, env),
             |a| ch(a, env),
             |a| f(a, env),
+            |a| tm(a, env),
         )
     }
     pub(crate) fn generic_mut_shallow<T>(
@@ -475,6 +571,7 @@ impl Value {
         _co: impl FnOnce(&mut Array<Complex>) -> T,
         ch: impl FnOnce(&mut Array<char>) -> T,
         f: impl FnOnce(&mut Array<Boxed>) -> T,
+        tm: impl FnOnce(&mut Array<Timestamp>) -> T,
     ) -> T {
         match self {
             Self::Num(array) => n(array),
@@ -482,6 +579,7 @@ impl Value {
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
+            Self::Time(array) => tm(array),
         }
     }
     pub(crate) fn generic_mut_deep<T>(
@@ -491,6 +589,7 @@ impl Value {
         _co: impl FnOnce(&mut Array<Complex>) -> T,
         ch: impl FnOnce(&mut Array<char>) -> T,
         f: impl FnOnce(&mut Array<Boxed>) -> T,
+        tm: impl FnOnce(&mut Array<Timestamp>) -> T,
     ) -> T {
         match self {
             Self::Num(array) => n(array),
@@ -499,11 +598,12 @@ impl Value {
             Self::Char(array) => ch(array),
             Self::Box(array) => {
                 if let Some(Boxed(value)) = array.as_scalar_mut() {
-                    value.generic_mut_deep(n, _b, _co, ch, f)
+                    value.generic_mut_deep(n, _b, _co, ch, f, tm)
                 } else {
                     f(array)
                 }
             }
+            Self::Time(array) => tm(array),
         }
     }
     #[allow(clippy::too_many_arguments)]
@@ -515,6 +615,7 @@ impl Value {
         _co: impl FnOnce(Array<Complex>, Array<Complex>) -> Result<T, E>,
         ch: impl FnOnce(Array<char>, Array<char>) -> Result<T, E>,
         f: impl FnOnce(Array<Boxed>, Array<Boxed>) -> Result<T, E>,
+        tm: impl FnOnce(Array<Timestamp>, Array<Timestamp>) -> Result<T, E>,
         err: impl FnOnce(Self, Self) -> E,
     ) -> Result<T, E> {
         match (self, other) {
@@ -528,6 +629,7 @@ impl Value {
             (Self::Complex(a), Self::Byte(b)) => _co(a, b.convert()),
             (Self::Byte(a), Self::Complex(b)) => _co(a.convert(), b),
             (Self::Char(a), Self::Char(b)) => ch(a, b),
+            (Self::Time(a), Self::Time(b)) => tm(a, b),
             (Self::Box(a), Self::Box(b)) => f(a, b),
             (Self::Box(a), b) => f(a, b.coerce_to_boxes()),
             (a, Self::Box(b)) => f(a.coerce_to_boxes(), b),
@@ -543,6 +645,7 @@ impl Value {
         _co: impl FnOnce(&Array<Complex>, &Array<Complex>) -> Result<T, E>,
         ch: impl FnOnce(&Array<char>, &Array<char>) -> Result<T, E>,
         f: impl FnOnce(&Array<Boxed>, &Array<Boxed>) -> Result<T, E>,
+        tm: impl FnOnce(&Array<Timestamp>, &Array<Timestamp>) -> Result<T, E>,
         err: impl FnOnce(&Self, &Self) -> E,
     ) -> Result<T, E> {
         match (self, other) {
@@ -556,6 +659,7 @@ impl Value {
             (Self::Complex(a), Self::Byte(b)) => _co(a, &b.convert_ref()),
             (Self::Byte(a), Self::Complex(b)) => _co(&a.convert_ref(), b),
             (Self::Char(a), Self::Char(b)) => ch(a, b),
+            (Self::Time(a), Self::Time(b)) => tm(a, b),
             (Self::Box(a), Self::Box(b)) => f(a, b),
             (Self::Box(a), b) => f(a, &b.coerce_as_boxes()),
             (a, Self::Box(b)) => f(&a.coerce_as_boxes(), b),
@@ -571,6 +675,7 @@ impl Value {
         _co: impl FnOnce(&mut Array<Complex>, Array<Complex>) -> Result<T, E>,
         ch: impl FnOnce(&mut Array<char>, Array<char>) -> Result<T, E>,
         f: impl FnOnce(&mut Array<Boxed>, Array<Boxed>) -> Result<T, E>,
+        tm: impl FnOnce(&mut Array<Timestamp>, Array<Timestamp>) -> Result<T, E>,
         err: impl FnOnce(&Self, &Self) -> E,
     ) -> Result<T, E> {
         match (&mut *self, other) {
@@ -599,6 +704,7 @@ impl Value {
                 res
             }
             (Self::Char(a), Self::Char(b)) => ch(a, b),
+            (Self::Time(a), Self::Time(b)) => tm(a, b),
             (Self::Box(a), b) => f(a, b.coerce_to_boxes()),
             (a, Self::Box(b)) => {
                 let mut a_box = take(a).coerce_to_boxes();
@@ -617,6 +723,7 @@ impl Value {
             Self::Complex(arr) => arr.data.reserve_min(min),
             Self::Char(arr) => arr.data.reserve_min(min),
             Self::Box(arr) => arr.data.reserve_min(min),
+            Self::Time(arr) => arr.data.reserve_min(min),
         }
     }
     /// Get the pretty-printed string representation of the value that appears in output
@@ -633,6 +740,21 @@ impl Value {
             value => value.grid_string(false),
         }
     }
+    /// Whether the value is known to contain only integers without checking its elements
+    pub(crate) fn known_integers(&self) -> bool {
+        match self {
+            Value::Num(arr) => {
+                let known = arr.meta().flags.is_integer();
+                debug_assert!(
+                    !known || arr.data.iter().all(|n| n.fract() == 0.0),
+                    "Array flagged as integers has non-integer elements"
+                );
+                known
+            }
+            Value::Byte(_) => true,
+            _ => false,
+        }
+    }
     /// Attempt to convert the array to a list of integers
     ///
     /// The `requirement` parameter is used in error messages.
@@ -641,25 +763,30 @@ impl Value {
         ctx: &C,
         requirement: &'static str,
     ) -> Result<Vec<isize>, C::Error> {
-        self.as_number_list(ctx, requirement, |f| f.fract() == 0.0, |f| f as isize)
+        let test: fn(f64) -> bool = if self.known_integers() {
+            |_| true
+        } else {
+            |f| f.fract() == 0.0
+        };
+        self.as_number_list(ctx, requirement, test, |f| f as isize)
     }
     pub(crate) fn as_ints_or_infs(
         &self,
         env: &Uiua,
         requirement: &'static str,
     ) -> UiuaResult<Vec<Result<isize, bool>>> {
-        self.as_number_list(
-            env,
-            requirement,
-            |n| n.fract() == 0.0 || n.is_infinite(),
-            |n| {
-                if n.is_infinite() {
-                    Err(n.is_sign_negative())
-                } else {
-                    Ok(n as isize)
-                }
-            },
-        )
+        let test: fn(f64) -> bool = if self.known_integers() {
+            |_| true
+        } else {
+            |n| n.fract() == 0.0 || n.is_infinite()
+        };
+        self.as_number_list(env, requirement, test, |n| {
+            if n.is_infinite() {
+                Err(n.is_sign_negative())
+            } else {
+                Ok(n as isize)
+            }
+        })
     }
     /// Attempt to convert the array to a single boolean
     ///
@@ -842,19 +969,23 @@ impl Value {
         ctx: &C,
         requirement: &'static str,
     ) -> Result<Vec<usize>, C::Error> {
-        self.as_number_list(
-            ctx,
-            requirement,
-            |f| f.fract() == 0.0 && f >= 0.0,
-            |f| f as usize,
-        )
+        let test: fn(f64) -> bool = if self.known_integers() {
+            |f| f >= 0.0
+        } else {
+            |f| f.fract() == 0.0 && f >= 0.0
+        };
+        self.as_number_list(ctx, requirement, test, |f| f as usize)
     }
     /// Attempt to convert the array to a list of bytes
     ///
     /// The `requirement` parameter is used in error messages.
-    pub fn as_bytes(&self, env: &Uiua, requirement: &'static str) -> UiuaResult<Vec<u8>> {
+    pub fn as_bytes<C: ErrorContext>(
+        &self,
+        ctx: &C,
+        requirement: &'static str,
+    ) -> Result<Vec<u8>, C::Error> {
         self.as_number_list(
-            env,
+            ctx,
             requirement,
             |f| f.fract() == 0.0 && (0.0..256.0).contains(&f),
             |f| f as u8,
@@ -912,7 +1043,13 @@ impl Value {
                 let mut result = Vec::with_capacity(nums.row_count());
                 for &num in nums.data() {
                     if !test(num) {
-                        return Err(ctx.error(requirement));
+                        let data = nums.data().iter().copied();
+                        return Err(ctx.error(invalid_elements(
+                            requirement,
+                            &nums.shape,
+                            data,
+                            test,
+                        )));
                     }
                     result.push(convert(num));
                 }
@@ -928,7 +1065,13 @@ impl Value {
                 for &byte in bytes.data() {
                     let num = byte as f64;
                     if !test(num) {
-                        return Err(ctx.error(requirement));
+                        let data = bytes.data().iter().map(|&b| b as f64);
+                        return Err(ctx.error(invalid_elements(
+                            requirement,
+                            &bytes.shape,
+                            data,
+                            test,
+                        )));
                     }
                     result.push(convert(num));
                 }
@@ -947,26 +1090,104 @@ impl Value {
         env: &Uiua,
         requirement: &'static str,
     ) -> UiuaResult<Array<isize>> {
-        self.as_number_array(
-            env,
-            requirement,
-            |_| true,
-            |n| n.fract() == 0.0,
-            |n| n as isize,
-        )
+        let test: fn(f64) -> bool = if self.known_integers() {
+            |_| true
+        } else {
+            |n| n.fract() == 0.0
+        };
+        self.as_number_array(env, requirement, |_| true, test, |n| n as isize)
     }
     pub(crate) fn as_natural_array(
         &self,
         env: &Uiua,
         requirement: &'static str,
     ) -> UiuaResult<Array<usize>> {
-        self.as_number_array(
-            env,
-            requirement,
-            |_| true,
-            |n| n.fract() == 0.0 && n >= 0.0,
-            |n| n as usize,
-        )
+        let test: fn(f64) -> bool = if self.known_integers() {
+            |n| n >= 0.0
+        } else {
+            |n| n.fract() == 0.0 && n >= 0.0
+        };
+        self.as_number_array(env, requirement, |_| true, test, |n| n as usize)
+    }
+    /// Attempt to convert the array to an array of indices into an array of the given shape
+    ///
+    /// The last axis of this array must be as long as the shape.
+    /// Each row along it is an index, where negative numbers count from the end.
+    /// Every index is checked, and the error lists the invalid ones.
+    pub fn try_as_index_array<C: ErrorContext>(
+        &self,
+        shape: &[usize],
+        ctx: &C,
+    ) -> Result<Array<usize>, C::Error> {
+        let requirement = "Indices must be integers";
+        let nums: Cow<[f64]> = match self {
+            Value::Num(arr) => Cow::Borrowed(&arr.data),
+            Value::Byte(arr) => Cow::Owned(arr.data.iter().map(|&b| b as f64).collect()),
+            value => {
+                return Err(ctx.error(format!(
+                    "{requirement}, but they are {}",
+                    value.type_name_plural()
+                )))
+            }
+        };
+        if self.shape().last().copied().unwrap_or(1) != shape.len() {
+            return Err(ctx.error(format!(
+                "Cannot index an array of shape {} with indices of shape {}",
+                FormatShape(shape),
+                self.shape()
+            )));
+        }
+        let integer = |n: f64| n.fract() == 0.0;
+        if !nums.iter().all(|&n| integer(n)) {
+            let message =
+                invalid_elements(requirement, self.shape(), nums.iter().copied(), integer);
+            return Err(ctx.error(message));
+        }
+        let mut count = 0;
+        let mut listed = Vec::new();
+        let mut data = EcoVec::with_capacity(nums.len());
+        let row_len = shape.len().max(1);
+        for (i, index) in nums.chunks(row_len).enumerate() {
+            let mut valid = true;
+            for (&n, &dim) in index.iter().zip(shape) {
+                let n = if n < 0.0 { n + dim as f64 } else { n };
+                valid &= n >= 0.0 && n < dim as f64;
+                data.push(n.max(0.0) as usize);
+            }
+            if !valid {
+                count += 1;
+                if listed.len() < MAX_REPORTED_INVALID {
+                    let index = (index.iter().map(|n| n.grid_string(false)))
+                        .collect::<Vec<_>>()
+                        .join("_");
+                    let at = self.shape()[..self.rank().saturating_sub(1)].to_vec();
+                    listed.push(if at.is_empty() {
+                        index
+                    } else {
+                        format!("{index} at index {}", format_index(&at, i))
+                    });
+                }
+            }
+        }
+        if count > 0 {
+            let mut message = format!(
+                "Indices must be in bounds of shape {}, but ",
+                FormatShape(shape)
+            );
+            if count == 1 {
+                message.push_str(&format!("{} is out of bounds", listed[0]));
+            } else {
+                message.push_str(&format!(
+                    "{count} indices are out of bounds: {}",
+                    listed.join(", ")
+                ));
+            }
+            if count > listed.len() {
+                message.push_str(&format!(", and {} more", count - listed.len()));
+            }
+            return Err(ctx.error(message));
+        }
+        Ok(Array::new(self.shape().clone(), data))
     }
     pub(crate) fn as_number_array<T: Clone>(
         &self,
@@ -986,7 +1207,13 @@ impl Value {
                 let mut result = EcoVec::with_capacity(nums.element_count());
                 for &num in nums.data() {
                     if !test_num(num) {
-                        return Err(env.error(requirement));
+                        let data = nums.data().iter().copied();
+                        return Err(env.error(invalid_elements(
+                            requirement,
+                            &nums.shape,
+                            data,
+                            test_num,
+                        )));
                     }
                     result.push(convert_num(num));
                 }
@@ -1002,7 +1229,13 @@ impl Value {
                 for &byte in bytes.data() {
                     let num = byte as f64;
                     if !test_num(num) {
-                        return Err(env.error(requirement));
+                        let data = bytes.data().iter().map(|&b| b as f64);
+                        return Err(env.error(invalid_elements(
+                            requirement,
+                            &bytes.shape,
+                            data,
+                            test_num,
+                        )));
                     }
                     result.push(convert_num(num));
                 }
@@ -1019,7 +1252,11 @@ impl Value {
     /// Attempt to convert the array to a string
     ///
     /// The `requirement` parameter is used in error messages.
-    pub fn as_string(&self, env: &Uiua, mut requirement: &'static str) -> UiuaResult<String> {
+    pub fn as_string<C: ErrorContext>(
+        &self,
+        ctx: &C,
+        mut requirement: &'static str,
+    ) -> Result<String, C::Error> {
         if requirement.is_empty() {
             requirement = "Expected value to be a string";
         }
@@ -1027,19 +1264,19 @@ impl Value {
             Value::Char(chars) => {
                 if chars.rank() > 1 {
                     return Err(
-                        env.error(format!("{requirement}, but its rank is {}", chars.rank()))
+                        ctx.error(format!("{requirement}, but its rank is {}", chars.rank()))
                     );
                 }
                 return Ok(chars.data().iter().collect());
             }
             Value::Box(boxes) => {
                 if let Some(bx) = boxes.as_scalar() {
-                    return bx.as_value().as_string(env, requirement);
+                    return bx.as_value().as_string(ctx, requirement);
                 }
             }
             _ => {}
         }
-        Err(env.error(format!(
+        Err(ctx.error(format!(
             "{requirement}, but its type is {}",
             self.type_name()
         )))
@@ -1078,6 +1315,126 @@ impl Value {
             }
         })
     }
+    /// Attempt to get the array's data as a list of numbers
+    ///
+    /// Number arrays are borrowed. Byte arrays are converted.
+    ///
+    /// The `requirement` parameter is used in error messages.
+    pub fn as_f64_slice<C: ErrorContext>(
+        &self,
+        ctx: &C,
+        mut requirement: &'static str,
+    ) -> Result<Cow<'_, [f64]>, C::Error> {
+        if requirement.is_empty() {
+            requirement = "Expected value to be a list of numbers";
+        }
+        if self.rank() > 1 {
+            return Err(ctx.error(format!("{requirement}, but its rank is {}", self.rank())));
+        }
+        Ok(match self {
+            Value::Num(arr) => Cow::Borrowed(arr.data()),
+            Value::Byte(arr) => Cow::Owned(arr.data().iter().map(|&b| b as f64).collect()),
+            value => {
+                return Err(ctx.error(format!(
+                    "{requirement}, but its type is {}",
+                    value.type_name()
+                )))
+            }
+        })
+    }
+    /// Attempt to convert the array to a list of strings
+    ///
+    /// This accepts a rank-2 character array, where each row is a string,
+    /// or a list of boxed strings.
+    ///
+    /// The `requirement` parameter is used in error messages.
+    pub fn as_string_list<C: ErrorContext>(
+        &self,
+        ctx: &C,
+        mut requirement: &'static str,
+    ) -> Result<Vec<String>, C::Error> {
+        if requirement.is_empty() {
+            requirement = "Expected value to be a list of strings";
+        }
+        match self {
+            Value::Char(arr) if arr.rank() == 2 => {
+                Ok(arr.row_slices().map(|row| row.iter().collect()).collect())
+            }
+            Value::Box(arr) if arr.rank() == 1 => (arr.data.iter())
+                .map(|Boxed(val)| val.as_string(ctx, requirement))
+                .collect(),
+            val if val.rank() == 1 && val.row_count() == 0 => Ok(Vec::new()),
+            val => Err(ctx.error(format!(
+                "{requirement}, but it is {} array of shape {}",
+                val.type_name(),
+                val.shape()
+            ))),
+        }
+    }
+    /// Attempt to convert a map array to a collection of key-value pairs
+    ///
+    /// `key` and `value` convert each key and value. The result can be
+    /// collected into anything that implements [`FromIterator`], such as a
+    /// [`Vec`] or a [`HashMap`](std::collections::HashMap).
+    ///
+    /// The `requirement` parameter is used in error messages.
+    pub fn as_map<C, K, V, M>(
+        &self,
+        ctx: &C,
+        mut requirement: &'static str,
+        mut key: impl FnMut(&Value, &C) -> Result<K, C::Error>,
+        mut value: impl FnMut(&Value, &C) -> Result<V, C::Error>,
+    ) -> Result<M, C::Error>
+    where
+        C: ErrorContext,
+        M: FromIterator<(K, V)>,
+    {
+        if requirement.is_empty() {
+            requirement = "Expected value to be a map";
+        }
+        if !self.is_map() {
+            return Err(ctx.error(format!(
+                "{requirement}, but it is {} array of shape {}",
+                self.type_name(),
+                self.shape()
+            )));
+        }
+        (self.map_kv().into_iter())
+            .map(|(k, v)| Ok((key(&k, ctx)?, value(&v.unboxed(), ctx)?)))
+            .collect()
+    }
+    /// Attempt to get the array's data as a matrix of numbers
+    ///
+    /// Returns the number of rows, the number of columns, and the row-major data.
+    ///
+    /// The `requirement` parameter is used in error messages.
+    pub fn as_matrix<C: ErrorContext>(
+        &self,
+        ctx: &C,
+        mut requirement: &'static str,
+    ) -> Result<NumMatrix<'_>, C::Error> {
+        if requirement.is_empty() {
+            requirement = "Expected value to be a matrix of numbers";
+        }
+        if self.rank() != 2 {
+            return Err(ctx.error(format!("{requirement}, but its rank is {}", self.rank())));
+        }
+        let (rows, cols) = (self.shape()[0], self.shape()[1]);
+        Ok(match self {
+            Value::Num(arr) => (rows, cols, Cow::Borrowed(arr.data())),
+            Value::Byte(arr) => (
+                rows,
+                cols,
+                Cow::Owned(arr.data().iter().map(|&b| b as f64).collect()),
+            ),
+            value => {
+                return Err(ctx.error(format!(
+                    "{requirement}, but its type is {}",
+                    value.type_name()
+                )))
+            }
+        })
+    }
     /// Remove all top-level layers of boxing
     pub fn unpack(&mut self) {
         if let Value::Box(arr) = self {
@@ -1213,6 +1570,7 @@ impl Value {
             Value::Complex(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             Value::Char(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             Value::Box(arr) => arr,
+            Value::Time(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
         }
     }
     /// Convert to a box array by boxing every element
@@ -1223,6 +1581,7 @@ impl Value {
             Value::Complex(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             Value::Char(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             Value::Box(arr) => Cow::Borrowed(arr),
+            Value::Time(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
         }
     }
     /// Propogate a value's label accross an operation
@@ -1271,6 +1630,26 @@ impl Value {
         }
         Ok(result)
     }
+    /// Propogate values' axis names accross an operation
+    ///
+    /// The names are only kept if the result has the same rank as the value they came from.
+    pub fn keep_axes(
+        mut self,
+        mut other: Self,
+        f: impl FnOnce(Self, Self) -> UiuaResult<Self>,
+    ) -> UiuaResult<Self> {
+        let axes = self.take_axes();
+        let other_axes = other.take_axes();
+        let mut result = f(self, other)?;
+        let rank = result.rank();
+        if let Some(axes) = (axes.into_iter())
+            .chain(other_axes)
+            .find(|axes| axes.len() == rank)
+        {
+            result.meta_mut().axes = Some(axes);
+        }
+        Ok(result)
+    }
     /// Propogate a value's uncorruptable metadata accross an operation
     pub fn keep_meta(self, f: impl FnOnce(Self) -> UiuaResult<Self>) -> UiuaResult<Self> {
         self.keep_label(|val| val.keep_map_key(f))
@@ -1281,7 +1660,7 @@ impl Value {
         other: Self,
         f: impl FnOnce(Self, Self) -> UiuaResult<Self>,
     ) -> UiuaResult<Self> {
-        self.keep_labels(other, |a, b| a.keep_map_keys(b, f))
+        self.keep_labels(other, |a, b| a.keep_map_keys(b, |a, b| a.keep_axes(b, f)))
     }
 }
 
@@ -1335,10 +1714,13 @@ value_from!(u8, Byte);
 value_from!(char, Char);
 value_from!(Boxed, Box);
 value_from!(Complex, Complex);
+value_from!(Timestamp, Time);
 
 impl FromIterator<usize> for Value {
     fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
-        iter.into_iter().map(|i| i as f64).collect()
+        Array::from_iter(iter.into_iter().map(|i| i as f64))
+            .integers()
+            .into()
     }
 }
 
@@ -1386,19 +1768,40 @@ impl From<i32> for Value {
     }
 }
 
+/// Reset the flags of an in-place result unless its arm is guarded by them
+macro_rules! reset_unguarded_flags {
+    ($val:ident) => {
+        $val.reset_meta_flags();
+    };
+    ($val:ident, $($guard:ident),+) => {};
+}
+
+/// Restore a unit taken from an operand if the operation keeps it
+macro_rules! restore_unit {
+    ($val:ident, $unit:ident) => {};
+    ($val:ident, $unit:ident, keep) => {
+        if let Some(unit) = $unit {
+            $val.meta_mut().unit = Some(unit);
+        }
+    };
+}
+
 macro_rules! value_un_impl {
-    ($name:ident, $(
+    ($name:ident, $(units($keep_unit:ident),)? $(
         $([$(|$meta:ident| $pred:expr,)* $in_place:ident, $f:ident])?
         $(($make_new:ident, $f2:ident))?
     ),* $(,)?) => {
         impl Value {
-            #[allow(clippy::redundant_closure_call)]
-            pub(crate) fn $name(self, env: &Uiua) -> UiuaResult<Self> {
-                self.keep_meta(|val| Ok(match val {
+            #[allow(clippy::redundant_closure_call, unused_mut, unused_variables)]
+            pub(crate) fn $name(mut self, env: &Uiua) -> UiuaResult<Self> {
+                let unit = self.take_unit();
+                #[allow(unused_mut)]
+                let mut val = self.keep_meta(|val| Ok(match val {
                     $($(Self::$in_place(mut array) $(if (|$meta: &ArrayMeta| $pred)(array.meta()))* => {
                         for val in &mut array.data {
                             *val = $name::$f(*val);
                         }
+                        reset_unguarded_flags!(array $(, $meta)*);
                         array.into()
                     },)*)*
                     $($(Self::$make_new(array) => {
@@ -1418,7 +1821,9 @@ macro_rules! value_un_impl {
                     }
                     #[allow(unreachable_patterns)]
                     val => return Err($name::error(val.type_name(), env))
-                }))
+                }))?;
+                restore_unit!(val, unit $(, $keep_unit)?);
+                Ok(val)
             }
         }
     }
@@ -1426,6 +1831,7 @@ macro_rules! value_un_impl {
 
 value_un_impl!(
     scalar_neg,
+    units(keep),
     [Num, num],
     (Byte, byte),
     [Complex, com],
@@ -1440,6 +1846,7 @@ value_un_impl!(
 );
 value_un_impl!(
     scalar_abs,
+    units(keep),
     [Num, num],
     (Byte, byte),
     (Complex, com),
@@ -1456,17 +1863,25 @@ value_un_impl!(
 value_un_impl!(sin, [Num, num], (Byte, byte), [Complex, com]);
 value_un_impl!(cos, [Num, num], (Byte, byte), [Complex, com]);
 value_un_impl!(asin, [Num, num], (Byte, byte), [Complex, com]);
-value_un_impl!(floor, [Num, num], [Byte, byte], [Complex, com]);
-value_un_impl!(ceil, [Num, num], [Byte, byte], [Complex, com]);
-value_un_impl!(round, [Num, num], [Byte, byte], [Complex, com]);
+value_un_impl!(floor, units(keep), [Num, num], [Byte, byte], [Complex, com]);
+value_un_impl!(ceil, units(keep), [Num, num], [Byte, byte], [Complex, com]);
+value_un_impl!(round, units(keep), [Num, num], [Byte, byte], [Complex, com]);
 value_un_impl!(
     complex_re,
+    units(keep),
     [Num, generic],
     [Byte, generic],
     (Complex, com),
     [Char, generic]
 );
-value_un_impl!(complex_im, [Num, num], [Byte, byte], (Complex, com));
+value_un_impl!(
+    complex_im,
+    units(keep),
+    [Num, num],
+    [Byte, byte],
+    (Complex, com)
+);
+value_un_impl!(bit_not, [Num, num], (Byte, byte));
 
 impl Value {
     /// Get the `absolute value` of a value
@@ -1544,15 +1959,154 @@ macro_rules! val_retry {
     };
 }
 
+/// Try to apply a checked byte kernel without promoting bytes to numbers
+///
+/// Scalar numbers that fit in a byte are treated as bytes.
+/// The most invalid elements to list in a conversion error
+const MAX_REPORTED_INVALID: usize = 5;
+
+/// Format an index into an array of some shape for an error message
+fn format_index(shape: &[usize], flat: usize) -> String {
+    let mut index = Vec::new();
+    Shape::from(shape).flat_to_dims(flat, &mut index);
+    (index.iter().map(|i| i.to_string()))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Describe which elements of an array fail a test
+fn invalid_elements(
+    requirement: &str,
+    shape: &[usize],
+    nums: impl Iterator<Item = f64>,
+    test: impl Fn(f64) -> bool,
+) -> String {
+    let mut count = 0;
+    let mut listed = Vec::new();
+    for (i, num) in nums.enumerate() {
+        if test(num) {
+            continue;
+        }
+        count += 1;
+        if listed.len() < MAX_REPORTED_INVALID {
+            listed.push(if shape.is_empty() {
+                num.grid_string(false)
+            } else {
+                format!(
+                    "{} at index {}",
+                    num.grid_string(false),
+                    format_index(shape, i)
+                )
+            });
+        }
+    }
+    let mut message = if count == 1 {
+        format!("{requirement}, but {} is invalid", listed[0])
+    } else {
+        format!(
+            "{requirement}, but {count} elements are invalid: {}",
+            listed.join(", ")
+        )
+    };
+    if count > listed.len() {
+        message.push_str(&format!(", and {} more", count - listed.len()));
+    }
+    message
+}
+
+fn bin_pervade_bytes_value(
+    a: &Value,
+    b: &Value,
+    a_depth: usize,
+    b_depth: usize,
+    f: fn(u8, u8) -> Option<u8>,
+) -> Option<Value> {
+    fn scalar_byte(arr: &Array<f64>) -> Option<Array<u8>> {
+        let n = *arr.as_scalar()?;
+        (n.fract() == 0.0 && (0.0..=255.0).contains(&n)).then(|| Array::scalar(n as u8))
+    }
+    let res = match (a, b) {
+        _ if a_depth > 0 || b_depth > 0 => None,
+        // Booleans have their own kernels that keep their flags
+        (Value::Byte(x), Value::Byte(y))
+            if !(x.meta().flags.is_boolean() && y.meta().flags.is_boolean()) =>
+        {
+            bin_pervade_bytes(x, y, f)
+        }
+        (Value::Byte(x), Value::Num(y)) => scalar_byte(y).and_then(|y| bin_pervade_bytes(x, &y, f)),
+        (Value::Num(x), Value::Byte(y)) => scalar_byte(x).and_then(|x| bin_pervade_bytes(&x, y, f)),
+        _ => None,
+    };
+    res.map(Into::into)
+}
+
+type ValueBinFn = fn(Value, Value, usize, usize, &Uiua) -> UiuaResult<Value>;
+
+/// Apply a pervasive function to the contents of two box arrays
+///
+/// Unless pervasive functions are descending into boxes on both sides,
+/// a scalar box is unboxed and its rows are paired with the other array's boxes.
+fn bin_pervade_boxes(
+    a: Array<Boxed>,
+    b: Array<Boxed>,
+    a_depth: usize,
+    b_depth: usize,
+    env: &Uiua,
+    f: ValueBinFn,
+) -> UiuaResult<Value> {
+    let (a, b) = if env.deep_box() {
+        (a, b)
+    } else {
+        match (a.into_unboxed(), b.into_unboxed()) {
+            (Ok(a), Ok(b)) => return Ok(Boxed(f(a, b, a_depth, b_depth, env)?).into()),
+            (Ok(a), Err(b)) => (a.coerce_as_boxes().into_owned(), b),
+            (Err(a), Ok(b)) => (a, b.coerce_as_boxes().into_owned()),
+            (Err(a), Err(b)) => (a, b),
+        }
+    };
+    let mut val: Value = bin_pervade(
+        a,
+        b,
+        a_depth,
+        b_depth,
+        env,
+        FalliblePerasiveFn::new(|a: Boxed, b: Boxed, env: &Uiua| {
+            Ok(Boxed(f(a.0, b.0, a_depth, b_depth, env)?))
+        }),
+    )?
+    .into();
+    val.reset_meta_flags();
+    Ok(val)
+}
+
 macro_rules! value_bin_impl {
-    ($name:ident, $(
+    ($name:ident, $(bytes($bytes:ident),)? $(units($units:ident),)? $(gpu($gpu:ident),)? $(
         $(($na:ident, $nb:ident, $f1:ident $(, $retry:ident)? ))*
         $([$(|$meta:ident| $pred:expr,)* $ip:ident, $f2:ident $(, $retry2:ident)? $(, $reset_meta:literal)?])*
     ),* ) => {
         impl Value {
             #[allow(unreachable_patterns, unused_mut, clippy::wrong_self_convention)]
-            pub(crate) fn $name(self, other: Self, a_depth: usize, b_depth: usize, env: &Uiua) -> UiuaResult<Self> {
-                self.keep_metas(other, |a, b| { Ok(match (a, b) {
+            pub(crate) fn $name(mut self, mut other: Self, a_depth: usize, b_depth: usize, env: &Uiua) -> UiuaResult<Self> {
+                let rule: Option<units::UnitRule> = None $(.or(Some(units::$units)))?;
+                let unit = match (rule, self.take_unit(), other.take_unit()) {
+                    (Some(rule), a, b) if a.is_some() || b.is_some() => rule(a, b, env)?,
+                    _ => None,
+                };
+                let mut val = self.keep_metas(other, |a, b| {
+                $(if let Some(val) = bin_pervade_bytes_value(&a, &b, a_depth, b_depth, $name::$bytes) {
+                    return Ok(val);
+                })?
+                $(#[cfg(feature = "gpu")]
+                if let (Value::Num(a), Value::Num(b)) = (&a, &b) {
+                    let op = crate::gpu::GpuBinOp::$gpu;
+                    if let Some(arr) = crate::gpu::bin_pervade(op, a, b, a_depth, b_depth, env) {
+                        return Ok(arr.into());
+                    }
+                })?
+                Ok(match (a, b) {
+                    (Value::Box(a), Value::Box(b)) if env.deep_box() => {
+                        bin_pervade_boxes(a, b, a_depth, b_depth, env, Value::$name)?
+                    }
                     $($((Value::$ip(mut a), Value::$ip(b)) $(if {
                         let f = |$meta: &ArrayMeta| $pred;
                         f(a.meta()) && f(b.meta())
@@ -1577,6 +2131,7 @@ macro_rules! value_bin_impl {
                             bin_pervade_mut(&mut a, b, a_depth, b_depth, env, $name::$f2)?;
                             a.into()
                         };
+                        reset_unguarded_flags!(val $(, $meta)*);
                         $(if $reset_meta {
                             val.reset_meta_flags();
                         })*
@@ -1600,19 +2155,7 @@ macro_rules! value_bin_impl {
                         val.reset_meta_flags();
                         val
                     },)*)*
-                    (Value::Box(a), Value::Box(b)) => {
-                        let (a, b) = match (a.into_unboxed(), b.into_unboxed()) {
-                            (Ok(a), Ok(b)) => return Ok(Boxed(Value::$name(a, b, a_depth, b_depth, env)?).into()),
-                            (Ok(a), Err(b)) => (a.coerce_as_boxes().into_owned(), b),
-                            (Err(a), Ok(b)) => (a, b.coerce_as_boxes().into_owned()),
-                            (Err(a), Err(b)) => (a, b),
-                        };
-                        let mut val: Value = bin_pervade(a, b, a_depth, b_depth, env, FalliblePerasiveFn::new(|a: Boxed, b: Boxed, env: &Uiua| {
-                            Ok(Boxed(Value::$name(a.0, b.0, a_depth, b_depth, env)?))
-                        }))?.into();
-                        val.reset_meta_flags();
-                        val
-                    }
+                    (Value::Box(a), Value::Box(b)) => bin_pervade_boxes(a, b, a_depth, b_depth, env, Value::$name)?,
                     (Value::Box(a), b) => {
                         let b = b.coerce_as_boxes().into_owned();
                         let mut val: Value = bin_pervade(a, b, a_depth, b_depth, env, FalliblePerasiveFn::new(|a: Boxed, b: Boxed, env: &Uiua| {
@@ -1630,7 +2173,11 @@ macro_rules! value_bin_impl {
                         val
                     },
                     (a, b) => return Err($name::error(a.type_name(), b.type_name(), env)),
-                })})
+                })})?;
+                if let Some(unit) = unit {
+                    val.meta_mut().unit = Some(unit);
+                }
+                Ok(val)
             }
         }
     };
@@ -1656,10 +2203,17 @@ macro_rules! value_bin_math_impl {
 
 value_bin_math_impl!(
     add,
+    bytes(byte_checked),
+    units(same),
+    gpu(Add),
     (Num, Char, num_char),
     (Char, Num, char_num),
     (Byte, Char, byte_char),
     (Char, Byte, char_byte),
+    (Num, Time, num_time),
+    (Time, Num, time_num),
+    (Byte, Time, byte_time),
+    (Time, Byte, time_byte),
     [
         |meta| meta.flags.is_boolean(),
         Byte,
@@ -1670,34 +2224,76 @@ value_bin_math_impl!(
 );
 value_bin_math_impl!(
     sub,
+    bytes(byte_checked),
+    units(same),
+    gpu(Sub),
     (Num, Char, num_char),
     (Char, Char, char_char),
     (Byte, Char, byte_char),
+    (Num, Time, num_time),
+    (Byte, Time, byte_time),
+    (Time, Time, time_time),
 );
 value_bin_math_impl!(
     mul,
+    bytes(byte_checked),
+    units(product),
+    gpu(Mul),
     (Num, Char, num_char),
     (Char, Num, char_num),
     (Byte, Char, byte_char),
     (Char, Byte, char_byte),
     [|meta| meta.flags.is_boolean(), Byte, bool_bool, num_num],
 );
-value_bin_math_impl!(div, (Num, Char, num_char), (Byte, Char, byte_char),);
-value_bin_math_impl!(modulus, (Complex, Complex, com_com));
+value_bin_math_impl!(
+    div,
+    units(quotient),
+    gpu(Div),
+    (Num, Char, num_char),
+    (Byte, Char, byte_char),
+);
+value_bin_math_impl!(
+    modulus,
+    bytes(byte_checked),
+    units(same),
+    (Complex, Complex, com_com)
+);
 value_bin_math_impl!(pow);
 value_bin_math_impl!(log);
 value_bin_math_impl!(atan2);
 value_bin_math_impl!(
     min,
+    bytes(byte_checked),
+    units(same),
+    gpu(Min),
     [Char, char_char],
     [|meta| meta.flags.is_boolean(), Byte, bool_bool, num_num],
 );
 value_bin_math_impl!(
     max,
+    bytes(byte_checked),
+    units(same),
+    gpu(Max),
     [Char, char_char],
     [|meta| meta.flags.is_boolean(), Byte, bool_bool, num_num],
 );
 
+macro_rules! value_bitwise_impl {
+    ($($name:ident),*) => {
+        $(
+            value_bin_impl!(
+                $name,
+                [Num, num_num],
+                (Byte, Byte, byte_byte),
+                (Byte, Num, byte_num),
+                (Num, Byte, num_byte),
+            );
+        )*
+    };
+}
+
+value_bitwise_impl!(bit_and, bit_or, bit_xor, shift_left, shift_right);
+
 value_bin_impl!(
     complex,
     (Num, Num, num_num),
@@ -1716,12 +2312,14 @@ macro_rules! eq_impls {
         $(
             value_bin_impl!(
                 $name,
+                units(compare),
                 // Value comparable
                 [Num, same_type],
                 [Complex, same_type],
                 (Box, Box, generic),
                 (Byte, Byte, same_type, num_num),
                 (Char, Char, generic),
+                (Time, Time, generic),
                 (Num, Byte, num_byte, num_num),
                 (Byte, Num, byte_num, num_num),
                 (Complex, Num, com_x),
@@ -1733,6 +2331,14 @@ macro_rules! eq_impls {
                 (Byte, Char, always_less),
                 (Char, Num, always_greater),
                 (Char, Byte, always_greater),
+                (Num, Time, always_less),
+                (Byte, Time, always_less),
+                (Complex, Time, always_less),
+                (Char, Time, always_less),
+                (Time, Num, always_greater),
+                (Time, Byte, always_greater),
+                (Time, Complex, always_greater),
+                (Time, Char, always_greater),
             );
         )*
     };
@@ -1743,12 +2349,14 @@ macro_rules! cmp_impls {
         $(
             value_bin_impl!(
                 $name,
+                units(compare),
                 // Value comparable
                 [Num, same_type],
                 [Complex, com_x],
                 (Box, Box, generic),
                 (Byte, Byte, same_type, num_num),
                 (Char, Char, generic),
+                (Time, Time, generic),
                 (Num, Byte, num_byte, num_num),
                 (Byte, Num, byte_num, num_num),
                 (Complex, Num, com_x),
@@ -1760,6 +2368,14 @@ macro_rules! cmp_impls {
                 (Byte, Char, always_less),
                 (Char, Num, always_greater),
                 (Char, Byte, always_greater),
+                (Num, Time, always_less),
+                (Byte, Time, always_less),
+                (Complex, Time, always_less),
+                (Char, Time, always_less),
+                (Time, Num, always_greater),
+                (Time, Byte, always_greater),
+                (Time, Complex, always_greater),
+                (Time, Char, always_greater),
             );
         )*
     };
@@ -1786,6 +2402,7 @@ impl PartialEq for Value {
             (Value::Char(a), Value::Char(b)) => a == b,
             (Value::Complex(a), Value::Complex(b)) => a == b,
             (Value::Box(a), Value::Box(b)) => a == b,
+            (Value::Time(a), Value::Time(b)) => a == b,
             (Value::Num(a), Value::Byte(b)) => a == b,
             (Value::Byte(a), Value::Num(b)) => a == b,
             _ => false,
@@ -1813,6 +2430,7 @@ impl Ord for Value {
             (Value::Complex(a), Value::Complex(b)) => a.cmp(b),
             (Value::Char(a), Value::Char(b)) => a.cmp(b),
             (Value::Box(a), Value::Box(b)) => a.cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.cmp(b),
             (Value::Num(a), Value::Byte(b)) => a.partial_cmp(b).unwrap(),
             (Value::Byte(a), Value::Num(b)) => a.partial_cmp(b).unwrap(),
             (Value::Num(_), _) => Ordering::Less,
@@ -1823,6 +2441,8 @@ impl Ord for Value {
             (_, Value::Complex(_)) => Ordering::Greater,
             (Value::Char(_), _) => Ordering::Less,
             (_, Value::Char(_)) => Ordering::Greater,
+            (Value::Box(_), _) => Ordering::Less,
+            (_, Value::Box(_)) => Ordering::Greater,
         }
     }
 }
@@ -1835,6 +2455,7 @@ impl Hash for Value {
             Value::Complex(arr) => arr.hash(state),
             Value::Char(arr) => arr.hash(state),
             Value::Box(arr) => arr.hash(state),
+            Value::Time(arr) => arr.hash(state),
         }
     }
 }
@@ -1847,6 +2468,7 @@ impl fmt::Debug for Value {
             Self::Complex(array) => array.fmt(f),
             Self::Char(array) => array.fmt(f),
             Self::Box(array) => array.fmt(f),
+            Self::Time(array) => array.fmt(f),
         }
     }
 }
@@ -1891,3 +2513,78 @@ impl ValueBuilder {
         self.value.unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn typed_extraction() {
+        use std::collections::HashMap;
+
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(r#"[1 2 3] ↯2_2 [1 2 3 4] {"a" "bc"} map {"x" "y"} [1 2] 5"#)
+            .unwrap();
+        let list = env.pop(()).unwrap();
+        let matrix = env.pop(()).unwrap();
+        let strings = env.pop(()).unwrap();
+        let map = env.pop(()).unwrap();
+        let scalar = env.pop(()).unwrap();
+        assert_eq!(&*list.as_f64_slice(&env, "").unwrap(), [1.0, 2.0, 3.0]);
+        assert_eq!(list.as_bytes(&env, "").unwrap(), [1, 2, 3]);
+        let (rows, cols, data) = matrix.as_matrix(&env, "").unwrap();
+        assert_eq!((rows, cols, &*data), (2, 2, &[1.0, 2.0, 3.0, 4.0][..]));
+        assert_eq!(strings.as_string_list(&env, "").unwrap(), ["a", "bc"]);
+        let pairs: HashMap<String, f64> = (map.as_map(
+            &env,
+            "",
+            |k, env| k.as_string(env, ""),
+            |v, env| v.as_num(env, ""),
+        ))
+        .unwrap();
+        assert_eq!(pairs, [("x".into(), 1.0), ("y".into(), 2.0)].into());
+        assert_eq!(&*scalar.as_f64_slice(&env, "").unwrap(), [5.0]);
+        assert!(matrix.as_f64_slice(&env, "").is_err());
+        assert!(list.as_matrix(&env, "").is_err());
+        assert!(list
+            .as_map::<_, f64, f64, Vec<_>>(
+                &env,
+                "",
+                |k, env| k.as_num(env, ""),
+                |v, env| v.as_num(env, "")
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn checked_conversions() {
+        let env = Uiua::with_safe_sys();
+        let err = Value::from_iter([1.0, -2.0, 3.5, 4.0, -5.0])
+            .as_nats(&env, "Expected naturals")
+            .unwrap_err();
+        assert!(err.message().ends_with(
+            "Expected naturals, but 3 elements are invalid: \
+            ¯2 at index 1, 3.5 at index 2, ¯5 at index 4"
+        ));
+        let err = Value::from_iter((0..20).map(|i| i as f64 + 0.5))
+            .as_ints(&env, "Expected integers")
+            .unwrap_err();
+        assert!(err.message().ends_with(", and 15 more"));
+
+        let indices = Array::new([3, 2], [0.0, 1.0, -1.0, -3.0, 1.0, 2.0].as_slice());
+        let indices = Value::from(indices).try_as_index_array(&[2, 3], &env);
+        assert_eq!(indices.unwrap().data.as_slice(), [0, 1, 1, 0, 1, 2]);
+        let indices = Array::new([3, 2], [0.0, 3.0, -3.0, 0.0, 1.0, 2.0].as_slice());
+        let err = (Value::from(indices).try_as_index_array(&[2, 3], &env))
+            .err()
+            .unwrap();
+        assert!(err.message().ends_with(
+            "Indices must be in bounds of shape [2 × 3], \
+            but 2 indices are out of bounds: 0_3 at index 0, ¯3_0 at index 1"
+        ));
+        let err = (Value::from(5).try_as_index_array(&[2, 3], &env))
+            .err()
+            .unwrap();
+        assert!(err.message().contains("Cannot index"));
+    }
+}
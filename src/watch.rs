@@ -0,0 +1,128 @@
+//! Waiting for changes to files
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+
+/// How long to keep collecting changes after the first one
+///
+/// Editors often save a file with several writes in a row.
+const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+/// Watches a set of paths for changes
+///
+/// Directories are watched recursively.
+/// Changes that happen between calls to [`FileWatcher::wait`] are not lost.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    recv: Mutex<Receiver<notify::Result<Event>>>,
+}
+
+impl FileWatcher {
+    /// Start watching some paths
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, String> {
+        let (send, recv) = channel();
+        let mut watcher = notify::recommended_watcher(send).map_err(|e| e.to_string())?;
+        for path in paths {
+            let path = path.as_ref();
+            (watcher.watch(path, RecursiveMode::Recursive))
+                .map_err(|e| format!("Failed to watch {}: {e}", path.display()))?;
+        }
+        Ok(FileWatcher {
+            _watcher: watcher,
+            recv: Mutex::new(recv),
+        })
+    }
+    /// Block until some files change, and get the paths that changed
+    ///
+    /// The paths are sorted and deduplicated.
+    /// Paths in the current directory are made relative to it.
+    /// If a timeout is given and nothing changes before it runs out,
+    /// the result is empty.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<Vec<PathBuf>, String> {
+        let recv = self.recv.lock();
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut changed = Vec::new();
+        // Wait for the first change
+        while changed.is_empty() {
+            let event = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    match recv.recv_timeout(remaining) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => return Ok(Vec::new()),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match recv.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+            };
+            add_changes(&mut changed, event)?;
+        }
+        // Collect the changes that come right after
+        while let Ok(event) = recv.recv_timeout(SETTLE_TIME) {
+            add_changes(&mut changed, event)?;
+        }
+        let curr = env::current_dir().ok();
+        let mut changed: Vec<PathBuf> = (changed.into_iter())
+            .map(|path| match &curr {
+                Some(curr) => path.strip_prefix(curr).map(Into::into).unwrap_or(path),
+                None => path,
+            })
+            .collect();
+        changed.sort();
+        changed.dedup();
+        Ok(changed)
+    }
+}
+
+fn add_changes(changed: &mut Vec<PathBuf>, event: notify::Result<Event>) -> Result<(), String> {
+    let event = event.map_err(|e| e.to_string())?;
+    if let EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) = event.kind {
+        changed.extend(event.paths);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn watch_files() {
+        use std::time::Duration;
+        let dir = std::env::temp_dir().join(format!("uiua_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let watcher = FileWatcher::new([&dir]).unwrap();
+        assert!(watcher.wait(Some(Duration::ZERO)).unwrap().is_empty());
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        let changed = watcher.wait(Some(Duration::from_secs(5))).unwrap();
+        assert!(changed.contains(&dir.join("a.txt")), "{changed:?}");
+
+        let path = dir.join("b.txt");
+        let writer = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(300));
+                std::fs::write(path, "b").unwrap();
+            }
+        });
+        let mut env = Uiua::with_native_sys();
+        env.push(dir.to_string_lossy().into_owned());
+        let res = env.run_str("&fwatch");
+        writer.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        res.unwrap();
+        let changed = env.pop("changed").unwrap();
+        let changed = changed.as_string_list(&env, "").unwrap();
+        assert!(changed.contains(&path.to_string_lossy().into_owned()));
+    }
+}
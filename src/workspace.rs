@@ -0,0 +1,127 @@
+//! Saving and restoring bound values between sessions
+
+use std::{fs, io, path::Path};
+
+use serde::*;
+
+use crate::{Assembly, BindingKind, Compiler, Ident, UiuaResult, Value};
+
+/// The version of the workspace format
+///
+/// This should be incremented whenever the serialized form of [`Workspace`] or [`Value`] changes
+/// in a way that older workspaces cannot be read.
+pub const WORKSPACE_VERSION: u32 = 1;
+
+/// A snapshot of the values bound in an assembly
+///
+/// Values keep their metadata, so map arrays and labels survive a round trip.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Workspace {
+    version: u32,
+    uiua_version: String,
+    bindings: Vec<(Ident, Value)>,
+}
+
+impl Workspace {
+    /// Snapshot the bound values of an assembly
+    ///
+    /// Bindings are only given values once the assembly has been run successfully.
+    /// If a name is bound more than once, only its last value is kept.
+    pub fn new(asm: &Assembly) -> Self {
+        let mut bindings: Vec<(Ident, Value)> = Vec::new();
+        for binding in &asm.bindings {
+            let BindingKind::Const(Some(val)) = &binding.kind else {
+                continue;
+            };
+            let name: Ident = binding.span.as_str(&asm.inputs, |s| s.into());
+            bindings.retain(|(n, _)| *n != name);
+            bindings.push((name, val.clone()));
+        }
+        Workspace {
+            version: WORKSPACE_VERSION,
+            uiua_version: crate::VERSION.into(),
+            bindings,
+        }
+    }
+    /// Get the saved bindings in the order they were bound
+    pub fn bindings(&self) -> &[(Ident, Value)] {
+        &self.bindings
+    }
+    /// Bind all the saved values in a compiler's current scope
+    pub fn bind(&self, compiler: &mut Compiler) -> UiuaResult {
+        for (name, value) in &self.bindings {
+            compiler.bind_value(name.clone(), value.clone())?;
+        }
+        Ok(())
+    }
+    /// Serialize the workspace to JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+    /// Deserialize a workspace from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+        let header: Header = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if header.version != WORKSPACE_VERSION {
+            return Err(format!(
+                "Workspace has version {}, but only version {WORKSPACE_VERSION} is supported",
+                header.version
+            ));
+        }
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+    /// Save the workspace to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+    /// Load a workspace from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn workspace_round_trip() {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(
+            "# Experimental!\n\
+            X ← map {\"a\" \"bc\"} [1 2]\n\
+            Y ← $y [NaN ∞]\n\
+            Z ← 1\n\
+            W ← +1 Z\n\
+            F ← +1",
+        )
+        .unwrap();
+        let workspace = Workspace::new(&env.take_asm());
+        let names: Vec<_> = workspace
+            .bindings()
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(names, ["X", "Y", "Z", "W"]);
+
+        let workspace = Workspace::from_json(&workspace.to_json()).unwrap();
+        let mut comp = Compiler::new();
+        workspace.bind(&mut comp).unwrap();
+        comp.load_str("get \"bc\" X Y W").unwrap();
+        let mut env = Uiua::with_safe_sys();
+        env.run_compiler(&mut comp).unwrap();
+        let stack = env.take_stack();
+        assert_eq!(stack[0].representation(), "2");
+        assert_eq!(stack[1].representation(), "$y [NaN ∞]");
+        assert_eq!(stack[2].representation(), "2");
+
+        let newer = r#"{"version":999,"uiua_version":"","bindings":[]}"#;
+        assert!(Workspace::from_json(newer)
+            .unwrap_err()
+            .contains("version 999"));
+    }
+}
@@ -200,6 +200,16 @@ pub unsafe extern "C" fn array_ptr(arr: *const c_int, len: c_int) -> *const c_in
     copied.leak().as_ptr()
 }
 
+#[no_mangle]
+pub extern "C" fn apply(f: extern "C" fn(c_int, c_int) -> c_int, a: c_int, b: c_int) -> c_int {
+    f(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn vec2_apply(v: Vec2, f: extern "C" fn(Vec2) -> f64) -> f64 {
+    f(v)
+}
+
 #[no_mangle]
 pub unsafe fn dummy_md5(m: *const c_uchar, len: c_int, out: *mut c_uchar) -> *const c_uchar {
     if len == 0 {